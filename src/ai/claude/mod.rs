@@ -5,4 +5,7 @@
 mod provider;
 mod types;
 
+#[cfg(test)]
+mod tests;
+
 pub use provider::ClaudeProvider;
@@ -1,11 +1,9 @@
 //! Non-streaming completion implementation
 
-use crate::ai::claude::types::{ClaudeMessage, ClaudeRequest, ClaudeResponse, ContentBlock};
-use crate::ai::provider::{
-    AIError, AIRequest, AIResponse, MessageRole, StopReason, ToolCall, Usage,
-};
+use crate::ai::claude::types::{ClaudeResponse, ContentBlock};
+use crate::ai::provider::{AIError, AIRequest, AIResponse, StopReason, ToolCall};
 
-use super::{convert_message, convert_tool, ClaudeProvider};
+use super::{build_request, convert_usage, ClaudeProvider};
 
 impl ClaudeProvider {
     /// Complete a request (non-streaming)
@@ -16,29 +14,7 @@ impl ClaudeProvider {
             .as_ref()
             .ok_or(AIError::Auth("No API key configured".to_string()))?;
 
-        // Build Claude request
-        let mut messages: Vec<ClaudeMessage> = Vec::new();
-
-        for msg in &request.messages {
-            if msg.role != MessageRole::System {
-                messages.push(convert_message(msg));
-            }
-        }
-
-        let claude_request = ClaudeRequest {
-            model: request.model.clone(),
-            messages,
-            system: request.system.clone(),
-            max_tokens: request.max_tokens.unwrap_or(4096),
-            temperature: request.temperature,
-            top_p: request.top_p,
-            stop_sequences: request.stop.clone(),
-            stream: false,
-            tools: request
-                .tools
-                .as_ref()
-                .map(|tools| tools.iter().map(convert_tool).collect()),
-        };
+        let claude_request = build_request(&request, false, self.prompt_caching);
 
         let response = self
             .client
@@ -51,17 +27,11 @@ impl ClaudeProvider {
             .await
             .map_err(|e| AIError::Network(e.to_string()))?;
 
-        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
-            return Err(AIError::Auth("Invalid API key".to_string()));
-        }
-
-        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
-            return Err(AIError::RateLimit);
-        }
-
         if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let headers = response.headers().clone();
             let error_text = response.text().await.unwrap_or_default();
-            return Err(AIError::Provider(error_text));
+            return Err(AIError::from_status(status, &headers, error_text));
         }
 
         let claude_response: ClaudeResponse = response
@@ -102,12 +72,8 @@ impl ClaudeProvider {
             content,
             stop_reason,
             tool_calls,
-            usage: Some(Usage {
-                input_tokens: claude_response.usage.input_tokens,
-                output_tokens: claude_response.usage.output_tokens,
-                total_tokens: claude_response.usage.input_tokens
-                    + claude_response.usage.output_tokens,
-            }),
+            usage: Some(convert_usage(&claude_response.usage)),
+            metadata: Default::default(),
         })
     }
 }
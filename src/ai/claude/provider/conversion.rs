@@ -1,7 +1,9 @@
 //! Message and tool conversion utilities
 
-use crate::ai::claude::types::{ClaudeMessage, ClaudeTool};
-use crate::ai::provider::{Message, MessageRole, ToolDefinition};
+use crate::ai::claude::types::{
+    CacheControl, ClaudeMessage, ClaudeRequest, ClaudeTool, ClaudeUsage, SystemBlock,
+};
+use crate::ai::provider::{AIRequest, Message, MessageRole, ToolDefinition, Usage};
 
 /// Convert internal message to Claude format
 pub(crate) fn convert_message(message: &Message) -> ClaudeMessage {
@@ -21,5 +23,71 @@ pub(crate) fn convert_tool(tool: &ToolDefinition) -> ClaudeTool {
         name: tool.name.clone(),
         description: tool.description.clone(),
         input_schema: tool.parameters.clone(),
+        cache_control: None,
+    }
+}
+
+/// Convert Claude usage, including prompt cache counters
+pub(crate) fn convert_usage(usage: &ClaudeUsage) -> Usage {
+    let cache_creation = usage.cache_creation_input_tokens.unwrap_or(0);
+    let cache_read = usage.cache_read_input_tokens.unwrap_or(0);
+    Usage {
+        input_tokens: usage.input_tokens,
+        output_tokens: usage.output_tokens,
+        // `input_tokens` excludes cached tokens, so add them back for the total
+        total_tokens: usage.input_tokens + cache_creation + cache_read + usage.output_tokens,
+        cache_creation_input_tokens: cache_creation,
+        cache_read_input_tokens: cache_read,
+    }
+}
+
+/// Build a Claude request
+///
+/// With prompt caching, cache breakpoints are placed after the tool
+/// definitions, the system prompt and the stable context, which form the
+/// prompt prefix in that order. Claude allows at most four breakpoints.
+pub(crate) fn build_request(
+    request: &AIRequest,
+    stream: bool,
+    prompt_caching: bool,
+) -> ClaudeRequest {
+    let cache_control = prompt_caching.then(CacheControl::ephemeral);
+
+    let messages = request
+        .messages
+        .iter()
+        .filter(|msg| msg.role != MessageRole::System)
+        .map(convert_message)
+        .collect();
+
+    let system: Vec<SystemBlock> = [&request.system, &request.context]
+        .into_iter()
+        .flatten()
+        .filter(|text| !text.is_empty())
+        .map(|text| SystemBlock {
+            block_type: "text",
+            text: text.clone(),
+            cache_control,
+        })
+        .collect();
+
+    let tools = request.tools.as_ref().map(|tools| {
+        let mut tools: Vec<ClaudeTool> = tools.iter().map(convert_tool).collect();
+        if let Some(last) = tools.last_mut() {
+            last.cache_control = cache_control;
+        }
+        tools
+    });
+
+    ClaudeRequest {
+        model: request.model.clone(),
+        messages,
+        system: (!system.is_empty()).then_some(system),
+        max_tokens: request.max_tokens.unwrap_or(4096),
+        temperature: request.temperature,
+        top_p: request.top_p,
+        stop_sequences: request.stop.clone(),
+        stream,
+        tools,
     }
 }
@@ -8,7 +8,7 @@ mod models;
 mod stream;
 mod trait_impl;
 
-pub(crate) use conversion::{convert_message, convert_tool};
+pub(crate) use conversion::{build_request, convert_usage};
 
 /// Claude API provider
 pub struct ClaudeProvider {
@@ -16,6 +16,8 @@ pub struct ClaudeProvider {
     pub(super) client: reqwest::Client,
     /// Configuration
     pub(super) config: ProviderConfig,
+    /// Whether to add prompt cache breakpoints
+    pub(super) prompt_caching: bool,
 }
 
 impl ClaudeProvider {
@@ -28,7 +30,11 @@ impl ClaudeProvider {
             .build()
             .expect("Failed to create HTTP client");
 
-        Self { client, config }
+        Self {
+            client,
+            config,
+            prompt_caching: true,
+        }
     }
 
     /// Create from API key
@@ -39,6 +45,12 @@ impl ClaudeProvider {
         })
     }
 
+    /// Enable or disable prompt caching (enabled by default)
+    pub fn with_prompt_caching(mut self, enabled: bool) -> Self {
+        self.prompt_caching = enabled;
+        self
+    }
+
     /// Get API base URL
     pub(super) fn base_url(&self) -> &str {
         self.config
@@ -50,8 +62,9 @@ impl ClaudeProvider {
 
 #[cfg(test)]
 mod tests {
+    use super::conversion::convert_message;
     use super::*;
-    use crate::ai::provider::Message;
+    use crate::ai::provider::{AIProvider, Message};
 
     #[test]
     fn test_provider_creation() {
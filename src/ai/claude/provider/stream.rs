@@ -2,10 +2,10 @@
 
 use futures::StreamExt;
 
-use crate::ai::claude::types::{ClaudeMessage, ClaudeRequest, StreamEvent};
-use crate::ai::provider::{AIError, AIRequest, AIStream, MessageRole, StopReason, StreamChunk};
+use crate::ai::claude::types::StreamEvent;
+use crate::ai::provider::{AIError, AIRequest, AIStream, StopReason, StreamChunk};
 
-use super::{convert_message, convert_tool, ClaudeProvider};
+use super::{build_request, convert_usage, ClaudeProvider};
 
 impl ClaudeProvider {
     /// Stream a request
@@ -17,29 +17,7 @@ impl ClaudeProvider {
             .ok_or(AIError::Auth("No API key configured".to_string()))?
             .clone();
 
-        // Build Claude request
-        let mut messages: Vec<ClaudeMessage> = Vec::new();
-
-        for msg in &request.messages {
-            if msg.role != MessageRole::System {
-                messages.push(convert_message(msg));
-            }
-        }
-
-        let claude_request = ClaudeRequest {
-            model: request.model.clone(),
-            messages,
-            system: request.system.clone(),
-            max_tokens: request.max_tokens.unwrap_or(4096),
-            temperature: request.temperature,
-            top_p: request.top_p,
-            stop_sequences: request.stop.clone(),
-            stream: true,
-            tools: request
-                .tools
-                .as_ref()
-                .map(|tools| tools.iter().map(convert_tool).collect()),
-        };
+        let claude_request = build_request(&request, true, self.prompt_caching);
 
         let base_url = self.base_url().to_string();
 
@@ -55,8 +33,10 @@ impl ClaudeProvider {
             .map_err(|e| AIError::Network(e.to_string()))?;
 
         if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let headers = response.headers().clone();
             let error_text = response.text().await.unwrap_or_default();
-            return Err(AIError::Provider(error_text));
+            return Err(AIError::from_status(status, &headers, error_text));
         }
 
         let stream = response.bytes_stream().map(move |result| {
@@ -77,6 +57,12 @@ impl ClaudeProvider {
                                             return Ok(StreamChunk::Text(text));
                                         }
                                     }
+                                    StreamEvent::MessageStart { message } => {
+                                        // Carries input and prompt cache usage
+                                        if let Some(usage) = message.usage {
+                                            return Ok(StreamChunk::Usage(convert_usage(&usage)));
+                                        }
+                                    }
                                     StreamEvent::MessageStop => {
                                         return Ok(StreamChunk::Stop(StopReason::EndTurn));
                                     }
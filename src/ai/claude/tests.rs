@@ -0,0 +1,123 @@
+//! Tests for the Claude provider against a local mock endpoint
+
+use super::ClaudeProvider;
+use crate::ai::mock_http::{http_response, MockServer};
+use crate::ai::provider::{
+    AIError, AIProvider, AIRequest, Message, ProviderConfig, ToolDefinition,
+};
+use std::time::Duration;
+
+fn message_response(cache_creation: usize, cache_read: usize) -> String {
+    let body = serde_json::json!({
+        "id": "msg_01",
+        "type": "message",
+        "role": "assistant",
+        "model": "claude-3-haiku-20240307",
+        "content": [{"type": "text", "text": "Hello!"}],
+        "stop_reason": "end_turn",
+        "usage": {
+            "input_tokens": 12,
+            "output_tokens": 5,
+            "cache_creation_input_tokens": cache_creation,
+            "cache_read_input_tokens": cache_read
+        }
+    });
+    http_response(
+        "200 OK",
+        &[("Content-Type", "application/json")],
+        &body.to_string(),
+    )
+}
+
+fn provider(url: &str) -> ClaudeProvider {
+    ClaudeProvider::new(ProviderConfig {
+        api_key: Some("test-key".to_string()),
+        base_url: Some(url.to_string()),
+        ..Default::default()
+    })
+}
+
+fn cached_request() -> AIRequest {
+    let tool = |name: &str| ToolDefinition {
+        name: name.to_string(),
+        description: format!("{} tool", name),
+        parameters: serde_json::json!({"type": "object"}),
+    };
+    AIRequest {
+        model: "claude-3-haiku-20240307".to_string(),
+        messages: vec![Message::user("What does main do?")],
+        system: Some("You are a coding assistant.".to_string()),
+        context: Some("=== CONTEXT ===\nfn main() {}\n=== END CONTEXT ===".to_string()),
+        tools: Some(vec![tool("read_file"), tool("search")]),
+        stream: false,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_prompt_cache_breakpoints() {
+    let server = MockServer::start(vec![message_response(1800, 0), message_response(0, 1800)]);
+    let provider = provider(&server.url);
+
+    let first = provider.complete(cached_request()).await.unwrap();
+    let second = provider.complete(cached_request()).await.unwrap();
+
+    let body = server.body(0);
+    let ephemeral = serde_json::json!({"type": "ephemeral"});
+    let system = body["system"].as_array().unwrap();
+    assert_eq!(system.len(), 2);
+    assert_eq!(system[0]["text"], "You are a coding assistant.");
+    assert_eq!(system[0]["cache_control"], ephemeral);
+    assert!(system[1]["text"].as_str().unwrap().contains("fn main"));
+    assert_eq!(system[1]["cache_control"], ephemeral);
+
+    let tools = body["tools"].as_array().unwrap();
+    assert!(tools[0].get("cache_control").is_none());
+    assert_eq!(tools[1]["cache_control"], ephemeral);
+
+    // Messages are not cached, and the prefix is identical across turns
+    assert!(body["messages"][0].get("cache_control").is_none());
+    assert_eq!(server.body(1), body);
+    assert!(server.head(0).contains("x-api-key: test-key"));
+
+    let usage = first.usage.unwrap();
+    assert_eq!(usage.cache_creation_input_tokens, 1800);
+    assert_eq!(usage.cache_read_input_tokens, 0);
+    assert_eq!(usage.total_tokens, 12 + 1800 + 5);
+
+    let usage = second.usage.unwrap();
+    assert_eq!(usage.cache_read_input_tokens, 1800);
+    assert_eq!(usage.input_tokens, 12);
+}
+
+#[tokio::test]
+async fn test_prompt_caching_disabled() {
+    let server = MockServer::start(vec![message_response(0, 0)]);
+    let provider = provider(&server.url).with_prompt_caching(false);
+
+    provider.complete(cached_request()).await.unwrap();
+
+    let body = server.body(0).to_string();
+    assert!(!body.contains("cache_control"));
+    assert!(body.contains("You are a coding assistant."));
+}
+
+#[tokio::test]
+async fn test_rate_limit_retry_after() {
+    let server = MockServer::start(vec![http_response(
+        "429 Too Many Requests",
+        &[("Retry-After", "3")],
+        r#"{"type":"error","error":{"type":"rate_limit_error"}}"#,
+    )]);
+
+    let err = provider(&server.url)
+        .complete(cached_request())
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        AIError::RateLimit {
+            retry_after: Some(d)
+        } if d == Duration::from_secs(3)
+    ));
+}
@@ -15,7 +15,7 @@ pub(crate) struct ClaudeRequest {
     pub(crate) model: String,
     pub(crate) messages: Vec<ClaudeMessage>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(crate) system: Option<String>,
+    pub(crate) system: Option<Vec<SystemBlock>>,
     pub(crate) max_tokens: usize,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) temperature: Option<f64>,
@@ -28,12 +28,40 @@ pub(crate) struct ClaudeRequest {
     pub(crate) tools: Option<Vec<ClaudeTool>>,
 }
 
+/// System prompt text block
+#[derive(Debug, Serialize)]
+pub(crate) struct SystemBlock {
+    #[serde(rename = "type")]
+    pub(crate) block_type: &'static str,
+    pub(crate) text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) cache_control: Option<CacheControl>,
+}
+
+/// Prompt cache breakpoint
+#[derive(Debug, Clone, Copy, Serialize)]
+pub(crate) struct CacheControl {
+    #[serde(rename = "type")]
+    pub(crate) cache_type: &'static str,
+}
+
+impl CacheControl {
+    /// Default (5 minute) cache lifetime
+    pub(crate) fn ephemeral() -> Self {
+        Self {
+            cache_type: "ephemeral",
+        }
+    }
+}
+
 /// Claude tool format
 #[derive(Debug, Serialize)]
 pub(crate) struct ClaudeTool {
     pub(crate) name: String,
     pub(crate) description: String,
     pub(crate) input_schema: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) cache_control: Option<CacheControl>,
 }
 
 /// Claude response
@@ -65,6 +93,10 @@ pub(crate) enum ContentBlock {
 pub(crate) struct ClaudeUsage {
     pub(crate) input_tokens: usize,
     pub(crate) output_tokens: usize,
+    #[serde(default)]
+    pub(crate) cache_creation_input_tokens: Option<usize>,
+    #[serde(default)]
+    pub(crate) cache_read_input_tokens: Option<usize>,
 }
 
 /// Stream event
@@ -103,6 +135,8 @@ pub(crate) struct Delta {
 pub(crate) struct MessageMeta {
     pub(crate) id: String,
     pub(crate) model: String,
+    #[serde(default)]
+    pub(crate) usage: Option<ClaudeUsage>,
 }
 
 /// Message delta content
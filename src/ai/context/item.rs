@@ -90,6 +90,23 @@ impl ContextItem {
         }
     }
 
+    /// Create a search result context item for a ranked code snippet
+    pub fn search_result(
+        path: impl Into<PathBuf>,
+        content: impl Into<String>,
+        start_line: usize,
+        end_line: usize,
+        query: impl Into<String>,
+        score: f32,
+    ) -> Self {
+        let mut item = Self::snippet(path, content, start_line, end_line);
+        item.item_type = ContextItemType::SearchResults;
+        item.metadata.insert("query".to_string(), query.into());
+        item.metadata
+            .insert("score".to_string(), format!("{:.4}", score));
+        item
+    }
+
     /// Create a web content context item
     pub fn web(url: impl Into<String>, content: impl Into<String>) -> Self {
         let url = url.into();
@@ -232,6 +249,24 @@ impl ContextItem {
                     output.push_str(&format!("```\n{}\n```", self.content));
                 }
             }
+            ContextItemType::SearchResults if self.start_line.is_some() => {
+                let query = self.metadata.get("query").map(|s| s.as_str()).unwrap_or("");
+                output.push_str(&format!(
+                    "Search result for \"{}\" in {} (lines {}-{}):\n",
+                    query,
+                    self.path
+                        .as_ref()
+                        .map(|p| p.display().to_string())
+                        .unwrap_or_else(|| self.name.clone()),
+                    self.start_line.unwrap_or(0),
+                    self.end_line.unwrap_or(0)
+                ));
+                if let Some(lang) = &self.language {
+                    output.push_str(&format!("```{}\n{}\n```", lang, self.content));
+                } else {
+                    output.push_str(&format!("```\n{}\n```", self.content));
+                }
+            }
             ContextItemType::Diff => {
                 output.push_str(&format!("Diff: {}\n", self.name));
                 output.push_str(&format!("```diff\n{}\n```", self.content));
@@ -1,5 +1,6 @@
 //! Context manager for tracking attached context items
 
+use super::selection::{select_context, ContextSelection, SelectionConfig};
 use super::types::{ContextError, ContextItem};
 use regex::Regex;
 use std::path::PathBuf;
@@ -31,19 +32,13 @@ impl ContextManager {
     }
 
     /// Add a context item
-    pub fn add(&mut self, item: ContextItem) -> Result<(), ContextError> {
-        // Check if adding this would exceed the limit
-        if self.current_tokens + item.token_count > self.max_tokens {
-            return Err(ContextError::TokenLimitExceeded {
-                current: self.current_tokens,
-                item: item.token_count,
-                max: self.max_tokens,
-            });
-        }
-
+    ///
+    /// Items are accepted whatever their size; the token limit is applied
+    /// when they are selected for a prompt, which trims or drops what does
+    /// not fit.
+    pub fn add(&mut self, item: ContextItem) {
         self.current_tokens += item.token_count;
         self.items.push(item);
-        Ok(())
     }
 
     /// Remove a context item by ID
@@ -91,19 +86,32 @@ impl ContextManager {
 
     /// Format all context for AI prompt
     pub fn format_for_prompt(&self) -> String {
-        if self.items.is_empty() {
-            return String::new();
-        }
+        format_items(self.items.iter())
+    }
 
-        let mut output = String::from("=== CONTEXT ===\n\n");
+    /// Format pinned items only
+    ///
+    /// Pinned items rarely change between turns, so they are sent as
+    /// `AIRequest::context` where providers can cache them.
+    pub fn format_pinned_for_prompt(&self) -> String {
+        format_items(self.pinned_items())
+    }
 
-        for item in &self.items {
-            output.push_str(&item.format_for_prompt());
-            output.push_str("\n\n");
-        }
+    /// Format non-pinned items only
+    pub fn format_unpinned_for_prompt(&self) -> String {
+        format_items(self.items.iter().filter(|i| !i.pinned))
+    }
 
-        output.push_str("=== END CONTEXT ===\n\n");
-        output
+    /// Select and trim items for a prompt within the manager's token limit
+    ///
+    /// Pinned items are kept whole and count against the limit.
+    pub fn select_for_prompt(&self, prompt: &str) -> ContextSelection {
+        self.select_with_config(prompt, &SelectionConfig::with_budget(self.max_tokens))
+    }
+
+    /// Select and trim items for a prompt with explicit selection settings
+    pub fn select_with_config(&self, prompt: &str, config: &SelectionConfig) -> ContextSelection {
+        select_context(&self.items, prompt, config)
     }
 
     /// Recalculate token count
@@ -161,7 +169,7 @@ impl ContextManager {
                 .map_err(|e| ContextError::FileReadError(e.to_string()))?;
 
             // Add as context item
-            self.add(ContextItem::file(&path, content))?;
+            self.add(ContextItem::file(&path, content));
             added.push(path);
         }
 
@@ -187,9 +195,25 @@ impl ContextManager {
     }
 }
 
+pub(super) fn format_items<'a>(items: impl Iterator<Item = &'a ContextItem>) -> String {
+    let mut items = items.peekable();
+    if items.peek().is_none() {
+        return String::new();
+    }
+
+    let mut output = String::from("=== CONTEXT ===\n\n");
+
+    for item in items {
+        output.push_str(&item.format_for_prompt());
+        output.push_str("\n\n");
+    }
+
+    output.push_str("=== END CONTEXT ===\n\n");
+    output
+}
+
 #[cfg(test)]
 mod tests {
-    use super::super::types::ContextItemType;
     use super::*;
 
     #[test]
@@ -199,17 +223,27 @@ mod tests {
         let item1 = ContextItem::file("/a.rs", "let x = 1;"); // ~3 tokens
         let item2 = ContextItem::file("/b.rs", "let y = 2;"); // ~3 tokens
 
-        assert!(manager.add(item1).is_ok());
-        assert!(manager.add(item2).is_ok());
+        manager.add(item1);
+        manager.add(item2);
         assert_eq!(manager.items().len(), 2);
     }
 
     #[test]
-    fn test_token_limit() {
-        let mut manager = ContextManager::new(10); // Very small limit
+    fn test_token_limit_applies_to_selection() {
+        let mut manager = ContextManager::new(100);
+
+        // ~250 tokens, over the limit on its own
+        let big = ContextItem::file("/big.txt", "lorem ipsum dolor\n".repeat(50));
+        let small = ContextItem::file("/small.txt", "ipsum");
+        manager.add(big);
+        manager.add(small);
+        assert_eq!(manager.items().len(), 2);
+        assert!(manager.token_count() > 100);
 
-        let item = ContextItem::file("/big.rs", "a".repeat(100)); // ~25 tokens
-        assert!(manager.add(item).is_err());
+        let selection = manager.select_for_prompt("what is ipsum?");
+        assert!(selection.total_tokens <= 100);
+        assert!(selection.is_reduced());
+        assert!(selection.format_for_prompt().contains("small.txt"));
     }
 
     #[test]
@@ -219,12 +253,31 @@ mod tests {
         let item1 = ContextItem::file("/a.rs", "code").pin();
         let item2 = ContextItem::file("/b.rs", "more code");
 
-        manager.add(item1).unwrap();
-        manager.add(item2).unwrap();
+        manager.add(item1);
+        manager.add(item2);
 
         assert_eq!(manager.pinned_items().count(), 1);
 
         manager.clear_unpinned();
         assert_eq!(manager.items().len(), 1);
     }
+
+    #[test]
+    fn test_format_pinned_split() {
+        let mut manager = ContextManager::new(1000);
+        manager.add(ContextItem::file("/pinned.rs", "fn stable() {}").pin());
+        manager.add(ContextItem::file("/scratch.rs", "fn changing() {}"));
+
+        let pinned = manager.format_pinned_for_prompt();
+        let unpinned = manager.format_unpinned_for_prompt();
+        assert!(pinned.contains("stable") && !pinned.contains("changing"));
+        assert!(unpinned.contains("changing") && !unpinned.contains("stable"));
+
+        let selection = manager.select_for_prompt("what changed?");
+        let unpinned = selection.format_unpinned_for_prompt();
+        assert!(unpinned.contains("changing") && !unpinned.contains("stable"));
+
+        manager.clear_unpinned();
+        assert!(manager.format_unpinned_for_prompt().is_empty());
+    }
 }
@@ -4,12 +4,18 @@
 
 mod item;
 mod manager;
+mod selection;
 mod types;
 mod utils;
 
 // Re-export public types
 pub use manager::ContextManager;
+pub use selection::{
+    select_context, ContextSelection, SelectionConfig, SelectionEntry, SelectionOutcome,
+    TrimStrategy,
+};
 pub use types::{ContextError, ContextItem, ContextItemType};
+pub(crate) use utils::{estimate_tokens, split_identifiers};
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,567 @@
+//! Relevance-based context selection
+//!
+//! Ranks unpinned context items against the current prompt and fits them into
+//! a token budget. Items that don't fit whole are shrunk to the regions that
+//! mention the prompt's identifiers, or to a symbol outline, before being
+//! dropped. Pinned items are always kept intact.
+
+use super::manager::format_items;
+use super::types::{ContextItem, ContextItemType};
+use super::utils::{estimate_tokens, split_identifiers};
+use crate::ai::mention::{parse_mentions, MentionKind};
+use crate::syntax::{extract_symbols, Symbol};
+use std::collections::HashSet;
+
+/// Selection tuning
+#[derive(Debug, Clone)]
+pub struct SelectionConfig {
+    /// Token budget for the whole context block
+    pub budget_tokens: usize,
+    /// Items above this size are shrunk even when they would fit
+    pub max_item_tokens: usize,
+    /// Smallest remaining budget worth spending on a shrunk item
+    pub min_item_tokens: usize,
+    /// Lines of surrounding context kept around matches in non-code items
+    pub context_lines: usize,
+    /// Score bonus for items referenced by an @mention
+    pub mention_weight: f32,
+    /// Score weight for recency (newest item gets the full weight)
+    pub recency_weight: f32,
+    /// Score weight for identifier overlap with the prompt
+    pub overlap_weight: f32,
+}
+
+impl Default for SelectionConfig {
+    fn default() -> Self {
+        Self {
+            budget_tokens: 100_000,
+            max_item_tokens: 16_000,
+            min_item_tokens: 64,
+            context_lines: 3,
+            mention_weight: 10.0,
+            recency_weight: 2.0,
+            overlap_weight: 5.0,
+        }
+    }
+}
+
+impl SelectionConfig {
+    /// Create a config with the given budget
+    pub fn with_budget(budget_tokens: usize) -> Self {
+        Self {
+            budget_tokens,
+            ..Default::default()
+        }
+    }
+}
+
+/// How an item was shrunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrimStrategy {
+    /// Only the regions relevant to the prompt were kept
+    RelevantRegions,
+    /// Replaced by an outline of its symbols
+    Outline,
+    /// Cut off after the first lines
+    Head,
+}
+
+impl TrimStrategy {
+    /// Short label for display
+    pub fn label(&self) -> &'static str {
+        match self {
+            TrimStrategy::RelevantRegions => "relevant regions",
+            TrimStrategy::Outline => "outline",
+            TrimStrategy::Head => "truncated",
+        }
+    }
+}
+
+/// What happened to an item during selection
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectionOutcome {
+    /// Included unchanged
+    Kept,
+    /// Included in reduced form
+    Trimmed(TrimStrategy),
+    /// Left out because the budget was exhausted
+    Dropped,
+}
+
+/// Per-item selection report entry
+#[derive(Debug, Clone)]
+pub struct SelectionEntry {
+    /// Item ID
+    pub id: String,
+    /// Item display name
+    pub name: String,
+    /// Relevance score (pinned items report `f32::INFINITY`)
+    pub score: f32,
+    /// Token count before selection
+    pub original_tokens: usize,
+    /// Token count after selection (0 when dropped)
+    pub tokens: usize,
+    /// Outcome
+    pub outcome: SelectionOutcome,
+}
+
+/// Result of a selection pass
+#[derive(Debug, Clone)]
+pub struct ContextSelection {
+    /// Selected items, in their original order
+    pub items: Vec<ContextItem>,
+    /// Report for every input item, highest score first
+    pub entries: Vec<SelectionEntry>,
+    /// Tokens used by the selected items
+    pub total_tokens: usize,
+    /// Budget the selection was made for
+    pub budget_tokens: usize,
+}
+
+impl ContextSelection {
+    /// Entries for items that were left out
+    pub fn dropped(&self) -> impl Iterator<Item = &SelectionEntry> {
+        self.entries
+            .iter()
+            .filter(|e| e.outcome == SelectionOutcome::Dropped)
+    }
+
+    /// Entries for items that were shrunk
+    pub fn trimmed(&self) -> impl Iterator<Item = &SelectionEntry> {
+        self.entries
+            .iter()
+            .filter(|e| matches!(e.outcome, SelectionOutcome::Trimmed(_)))
+    }
+
+    /// Whether any item was dropped or shrunk
+    pub fn is_reduced(&self) -> bool {
+        self.dropped().next().is_some() || self.trimmed().next().is_some()
+    }
+
+    /// Format the selected items for the AI prompt
+    pub fn format_for_prompt(&self) -> String {
+        format_items(self.items.iter())
+    }
+
+    /// Format the selected items that are not pinned
+    ///
+    /// For callers that send pinned items separately as a stable prefix.
+    pub fn format_unpinned_for_prompt(&self) -> String {
+        format_items(self.items.iter().filter(|i| !i.pinned))
+    }
+}
+
+/// Select items for a prompt
+pub fn select_context(
+    items: &[ContextItem],
+    prompt: &str,
+    config: &SelectionConfig,
+) -> ContextSelection {
+    let terms = prompt_terms(prompt);
+    let mentioned = mentioned_targets(prompt);
+
+    // Newest items get the full recency weight
+    let mut by_age: Vec<usize> = (0..items.len()).collect();
+    by_age.sort_by_key(|&i| items[i].added_at);
+    let mut recency = vec![0.0f32; items.len()];
+    for (rank, &i) in by_age.iter().enumerate() {
+        recency[i] = if items.len() > 1 {
+            rank as f32 / (items.len() - 1) as f32
+        } else {
+            1.0
+        };
+    }
+
+    let mut scored: Vec<(usize, f32)> = items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let score = if item.pinned {
+                f32::INFINITY
+            } else {
+                let mention = if is_mentioned(item, &mentioned) {
+                    config.mention_weight
+                } else {
+                    0.0
+                };
+                mention
+                    + recency[i] * config.recency_weight
+                    + overlap_score(item, &terms) * config.overlap_weight
+            };
+            (i, score)
+        })
+        .collect();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let mut remaining = config.budget_tokens;
+    let mut selected: Vec<Option<ContextItem>> = vec![None; items.len()];
+    let mut entries = Vec::with_capacity(items.len());
+
+    for (index, score) in scored {
+        let item = &items[index];
+        let original_tokens = item.token_count;
+
+        let outcome = if item.pinned {
+            // Pinned items are never shrunk; they may exceed the budget
+            remaining = remaining.saturating_sub(original_tokens);
+            selected[index] = Some(item.clone());
+            SelectionOutcome::Kept
+        } else if original_tokens <= remaining.min(config.max_item_tokens) {
+            remaining -= original_tokens;
+            selected[index] = Some(item.clone());
+            SelectionOutcome::Kept
+        } else {
+            let limit = remaining.min(config.max_item_tokens);
+            match (limit >= config.min_item_tokens)
+                .then(|| shrink_item(item, &terms, limit, config.context_lines))
+                .flatten()
+            {
+                Some((shrunk, strategy)) => {
+                    remaining -= shrunk.token_count;
+                    selected[index] = Some(shrunk);
+                    SelectionOutcome::Trimmed(strategy)
+                }
+                None => SelectionOutcome::Dropped,
+            }
+        };
+
+        entries.push(SelectionEntry {
+            id: item.id.clone(),
+            name: item.name.clone(),
+            score,
+            original_tokens,
+            tokens: selected[index].as_ref().map(|i| i.token_count).unwrap_or(0),
+            outcome,
+        });
+    }
+
+    let items: Vec<ContextItem> = selected.into_iter().flatten().collect();
+    let total_tokens = items.iter().map(|i| i.token_count).sum();
+
+    ContextSelection {
+        items,
+        entries,
+        total_tokens,
+        budget_tokens: config.budget_tokens,
+    }
+}
+
+/// Identifier-like words from the prompt (lowercased, 3+ chars)
+fn prompt_terms(prompt: &str) -> HashSet<String> {
+    split_identifiers(prompt)
+        .filter(|w| w.len() >= 3 && !STOP_WORDS.contains(&w.as_str()))
+        .collect()
+}
+
+/// Common English words that carry no signal about code
+const STOP_WORDS: &[&str] = &[
+    "the", "and", "for", "this", "that", "with", "from", "what", "why", "how", "does", "can",
+    "should", "would", "could", "please", "file", "code", "make", "into", "have", "are", "was",
+    "not", "use", "using", "fix", "add", "when", "where", "there", "then", "than", "about",
+];
+
+/// Paths, URLs and names referenced by @mentions in the prompt
+fn mentioned_targets(prompt: &str) -> Vec<String> {
+    parse_mentions(prompt)
+        .into_iter()
+        .map(|m| match m.kind {
+            MentionKind::File(path) | MentionKind::FileRange { path, .. } => {
+                path.to_string_lossy().trim_start_matches("./").to_string()
+            }
+            MentionKind::Url(url) => url,
+            MentionKind::Snippet(name) | MentionKind::Symbol(name) => name,
+        })
+        .collect()
+}
+
+fn is_mentioned(item: &ContextItem, mentioned: &[String]) -> bool {
+    mentioned.iter().any(|target| {
+        item.path
+            .as_ref()
+            .is_some_and(|p| p.to_string_lossy().ends_with(target.as_str()))
+            || item.metadata.get("url") == Some(target)
+            || item.name == *target
+    })
+}
+
+/// Fraction of prompt terms found in the item's name and identifiers
+fn overlap_score(item: &ContextItem, terms: &HashSet<String>) -> f32 {
+    if terms.is_empty() {
+        return 0.0;
+    }
+    let mut words: HashSet<String> = split_identifiers(&item.content).collect();
+    words.extend(split_identifiers(&item.name));
+
+    let hits = terms.iter().filter(|t| words.contains(*t)).count();
+    hits as f32 / terms.len() as f32
+}
+
+/// Shrink an item to fit `max_tokens`
+fn shrink_item(
+    item: &ContextItem,
+    terms: &HashSet<String>,
+    max_tokens: usize,
+    context_lines: usize,
+) -> Option<(ContextItem, TrimStrategy)> {
+    let shrinkable = matches!(
+        item.item_type,
+        ContextItemType::File
+            | ContextItemType::Snippet
+            | ContextItemType::Web
+            | ContextItemType::SearchResults
+            | ContextItemType::McpResource
+    );
+    if !shrinkable {
+        return None;
+    }
+
+    let lines: Vec<&str> = item.content.lines().collect();
+    let symbols = item
+        .language
+        .as_deref()
+        .map(|lang| extract_symbols(&item.content, lang))
+        .unwrap_or_default();
+
+    let mut candidates = Vec::new();
+    if symbols.is_empty() {
+        let ranges = matching_line_ranges(&lines, terms, context_lines);
+        if !ranges.is_empty() {
+            candidates.push((
+                render_regions(&lines, &ranges),
+                TrimStrategy::RelevantRegions,
+            ));
+        }
+    } else {
+        let ranges = relevant_symbol_ranges(&symbols, &item.content, terms);
+        if !ranges.is_empty() {
+            candidates.push((
+                render_regions(&lines, &ranges),
+                TrimStrategy::RelevantRegions,
+            ));
+        }
+        candidates.push((render_outline(&symbols), TrimStrategy::Outline));
+    }
+    candidates.push((render_head(&lines, max_tokens), TrimStrategy::Head));
+
+    let (content, strategy) = candidates
+        .into_iter()
+        .find(|(content, _)| estimate_tokens(content) <= max_tokens)?;
+    if content.trim().is_empty() {
+        return None;
+    }
+
+    let mut shrunk = item.clone();
+    shrunk.token_count = estimate_tokens(&content);
+    shrunk.content = content;
+    shrunk
+        .metadata
+        .insert("selection".to_string(), strategy.label().to_string());
+    shrunk
+        .metadata
+        .insert("original_tokens".to_string(), item.token_count.to_string());
+    Some((shrunk, strategy))
+}
+
+/// Line ranges (0-based, inclusive) of the innermost symbols relevant to the prompt
+fn relevant_symbol_ranges(
+    symbols: &[Symbol],
+    content: &str,
+    terms: &HashSet<String>,
+) -> Vec<(usize, usize)> {
+    let relevant: Vec<&Symbol> = symbols
+        .iter()
+        .filter(|s| {
+            let body = &content[s.start_byte..s.end_byte];
+            split_identifiers(&s.name).any(|w| terms.contains(&w))
+                || split_identifiers(body).any(|w| terms.contains(&w))
+        })
+        .collect();
+
+    // Prefer a matching method over its whole enclosing impl/class
+    let innermost = relevant.iter().filter(|outer| {
+        !relevant.iter().any(|inner| {
+            inner.depth > outer.depth
+                && inner.start_byte >= outer.start_byte
+                && inner.end_byte <= outer.end_byte
+        })
+    });
+
+    merge_ranges(
+        innermost
+            .map(|s| (s.start_line - 1, s.end_line - 1))
+            .collect(),
+    )
+}
+
+/// Line ranges (0-based, inclusive) around lines containing prompt terms
+fn matching_line_ranges(
+    lines: &[&str],
+    terms: &HashSet<String>,
+    context_lines: usize,
+) -> Vec<(usize, usize)> {
+    let ranges = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| split_identifiers(line).any(|w| terms.contains(&w)))
+        .map(|(i, _)| {
+            (
+                i.saturating_sub(context_lines),
+                (i + context_lines).min(lines.len().saturating_sub(1)),
+            )
+        })
+        .collect();
+    merge_ranges(ranges)
+}
+
+fn merge_ranges(mut ranges: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+    ranges.sort();
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+fn render_regions(lines: &[&str], ranges: &[(usize, usize)]) -> String {
+    let mut output = String::new();
+    for &(start, end) in ranges {
+        output.push_str(&format!("⋮ lines {}-{}\n", start + 1, end + 1));
+        for line in &lines[start..=end.min(lines.len() - 1)] {
+            output.push_str(line);
+            output.push('\n');
+        }
+    }
+    output.push('⋮');
+    output
+}
+
+fn render_outline(symbols: &[Symbol]) -> String {
+    let mut output = String::from("Outline:\n");
+    for symbol in symbols {
+        output.push_str(&format!(
+            "{}{}  // lines {}-{}\n",
+            "    ".repeat(symbol.depth),
+            symbol.signature,
+            symbol.start_line,
+            symbol.end_line
+        ));
+    }
+    output
+}
+
+fn render_head(lines: &[&str], max_tokens: usize) -> String {
+    // Leave room for the trailing marker
+    let max_chars = max_tokens.saturating_sub(8) * 4;
+    let mut output = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        if output.len() + line.len() + 1 > max_chars {
+            output.push_str(&format!("⋮ {} more lines", lines.len() - i));
+            break;
+        }
+        output.push_str(line);
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rust_file(path: &str, functions: &[&str], filler_lines: usize) -> ContextItem {
+        let mut content = String::new();
+        for name in functions {
+            content.push_str(&format!("fn {}() {{\n", name));
+            for i in 0..filler_lines {
+                content.push_str(&format!("    let value_{} = {};\n", i, i));
+            }
+            content.push_str("}\n\n");
+        }
+        ContextItem::file(path, content)
+    }
+
+    #[test]
+    fn test_mentioned_item_ranks_first() {
+        let a = ContextItem::file("src/a.rs", "fn alpha() {}");
+        let b = ContextItem::file("src/b.rs", "fn beta() {}");
+        let selection = select_context(
+            &[a, b.clone()],
+            "what does @src/b.rs do?",
+            &SelectionConfig::default(),
+        );
+        assert_eq!(selection.entries[0].id, b.id);
+        assert_eq!(selection.items.len(), 2);
+    }
+
+    #[test]
+    fn test_overlap_prefers_matching_item() {
+        let parser = ContextItem::file("/parser.rs", "fn parse_tokens() {}");
+        let render = ContextItem::file("/render.rs", "fn draw_frame() {}");
+        let small = ContextItem::file("/x.rs", "a".repeat(40));
+        let config = SelectionConfig {
+            budget_tokens: parser.token_count + small.token_count,
+            ..Default::default()
+        };
+        let selection = select_context(
+            &[parser.clone(), render.clone(), small],
+            "why does parse_tokens fail",
+            &config,
+        );
+        assert!(selection.items.iter().any(|i| i.id == parser.id));
+        assert!(selection.dropped().any(|e| e.id == render.id));
+    }
+
+    #[test]
+    fn test_pinned_items_kept_intact() {
+        let pinned = rust_file("/big.rs", &["one", "two"], 200).pin();
+        let selection = select_context(
+            std::slice::from_ref(&pinned),
+            "anything",
+            &SelectionConfig::with_budget(10),
+        );
+        assert_eq!(selection.items.len(), 1);
+        assert_eq!(selection.items[0].content, pinned.content);
+        assert_eq!(selection.entries[0].outcome, SelectionOutcome::Kept);
+    }
+
+    #[test]
+    fn test_large_file_shrunk_to_relevant_symbol() {
+        let file = rust_file("/lib.rs", &["load_config", "render_view", "save_state"], 60);
+        let config = SelectionConfig::with_budget(file.token_count / 2);
+        let selection = select_context(
+            std::slice::from_ref(&file),
+            "the load_config function panics",
+            &config,
+        );
+
+        let item = &selection.items[0];
+        assert_eq!(
+            selection.entries[0].outcome,
+            SelectionOutcome::Trimmed(TrimStrategy::RelevantRegions)
+        );
+        assert!(item.content.contains("fn load_config()"));
+        assert!(!item.content.contains("fn render_view()"));
+        assert!(item.token_count <= config.budget_tokens);
+        assert_eq!(
+            item.metadata.get("original_tokens"),
+            Some(&file.token_count.to_string())
+        );
+    }
+
+    #[test]
+    fn test_large_file_falls_back_to_outline() {
+        let file = rust_file("/lib.rs", &["load_config", "render_view", "save_state"], 60);
+        let config = SelectionConfig::with_budget(100);
+        let selection = select_context(&[file], "unrelated question", &config);
+
+        let item = &selection.items[0];
+        assert_eq!(
+            selection.entries[0].outcome,
+            SelectionOutcome::Trimmed(TrimStrategy::Outline)
+        );
+        assert!(item.content.contains("fn render_view()  // lines"));
+    }
+}
@@ -61,12 +61,6 @@ pub struct ContextItem {
 /// Context errors
 #[derive(Debug, thiserror::Error)]
 pub enum ContextError {
-    #[error("Token limit exceeded: current {current} + item {item} > max {max}")]
-    TokenLimitExceeded {
-        current: usize,
-        item: usize,
-        max: usize,
-    },
     #[error("Item not found: {0}")]
     ItemNotFound(String),
     #[error("Failed to read file: {0}")]
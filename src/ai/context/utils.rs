@@ -6,6 +6,31 @@ pub(crate) fn estimate_tokens(text: &str) -> usize {
     text.len() / 4
 }
 
+/// Split text into lowercased identifiers, also splitting snake_case and camelCase
+pub(crate) fn split_identifiers(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|w| !w.is_empty())
+        .flat_map(|word| {
+            let mut parts = vec![word.to_lowercase()];
+            let mut current = String::new();
+            let mut prev_lower = false;
+            for c in word.chars() {
+                if (c == '_' || (c.is_uppercase() && prev_lower)) && !current.is_empty() {
+                    parts.push(std::mem::take(&mut current).to_lowercase());
+                }
+                if c != '_' {
+                    current.push(c);
+                }
+                prev_lower = c.is_lowercase();
+            }
+            if !current.is_empty() && parts.len() > 1 {
+                parts.push(current.to_lowercase());
+            }
+            parts.dedup();
+            parts
+        })
+}
+
 /// Detect language from MIME type
 pub(crate) fn mime_to_language(mime: &str) -> Option<String> {
     match mime {
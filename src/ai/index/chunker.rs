@@ -0,0 +1,151 @@
+//! Splitting source files into chunks along symbol boundaries
+
+use super::types::CodeChunk;
+use crate::syntax::{extract_symbols, Symbol};
+use std::path::Path;
+
+/// Line prefixes of doc comments and attributes that belong to the next symbol
+const LEADING_PREFIXES: &[&str] = &["///", "//!", "//", "#[", "#", "@", "/*", "*"];
+
+/// Split a source file into chunks
+///
+/// Top-level symbols become one chunk each. Symbols longer than `max_lines`
+/// are split into their nested symbols (methods) or, failing that, into
+/// fixed windows. Code between symbols (imports, constants) is kept as its
+/// own chunks. Files without a supported grammar are windowed.
+pub fn chunk_source(path: &Path, content: &str, max_lines: usize) -> Vec<CodeChunk> {
+    let max_lines = max_lines.max(1);
+    let lines: Vec<&str> = content.lines().collect();
+    if lines.is_empty() {
+        return Vec::new();
+    }
+
+    let language = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let symbols = extract_symbols(content, &language);
+
+    let mut ranges: Vec<(usize, usize, Option<String>)> = Vec::new();
+    let top_level: Vec<&Symbol> = symbols.iter().filter(|s| s.depth == 0).collect();
+
+    for symbol in &top_level {
+        let start = symbol.start_line - 1;
+        let end = (symbol.end_line - 1).min(lines.len() - 1);
+        if end - start < max_lines {
+            ranges.push((start, end, Some(symbol.name.clone())));
+            continue;
+        }
+
+        let children: Vec<&Symbol> = symbols
+            .iter()
+            .filter(|s| {
+                s.depth == 1 && s.start_byte >= symbol.start_byte && s.end_byte <= symbol.end_byte
+            })
+            .collect();
+        if children.is_empty() {
+            push_windows(&mut ranges, start, end, max_lines, Some(&symbol.name));
+            continue;
+        }
+
+        // Container header (signature, fields) up to the first nested symbol
+        let first_child = children[0].start_line - 1;
+        if first_child > start {
+            push_windows(
+                &mut ranges,
+                start,
+                first_child - 1,
+                max_lines,
+                Some(&symbol.name),
+            );
+        }
+        for child in children {
+            let name = format!("{}::{}", symbol.name, child.name);
+            let child_end = (child.end_line - 1).min(end);
+            push_windows(
+                &mut ranges,
+                child.start_line - 1,
+                child_end,
+                max_lines,
+                Some(&name),
+            );
+        }
+    }
+
+    ranges.sort_by_key(|r| r.0);
+    extend_leading_comments(&mut ranges, &lines);
+    fill_gaps(&mut ranges, &lines, max_lines);
+
+    ranges
+        .into_iter()
+        .map(|(start, end, symbol)| CodeChunk {
+            path: path.to_path_buf(),
+            start_line: start + 1,
+            end_line: end + 1,
+            symbol,
+            content: lines[start..=end].join("\n"),
+        })
+        .collect()
+}
+
+/// Push `start..=end` split into windows of at most `max_lines`
+fn push_windows(
+    ranges: &mut Vec<(usize, usize, Option<String>)>,
+    start: usize,
+    end: usize,
+    max_lines: usize,
+    symbol: Option<&str>,
+) {
+    let mut window_start = start;
+    while window_start <= end {
+        let window_end = (window_start + max_lines - 1).min(end);
+        ranges.push((window_start, window_end, symbol.map(|s| s.to_string())));
+        window_start = window_end + 1;
+    }
+}
+
+/// Pull doc comments and attributes directly above a symbol into its chunk
+fn extend_leading_comments(ranges: &mut [(usize, usize, Option<String>)], lines: &[&str]) {
+    let mut previous_end: Option<usize> = None;
+    for range in ranges.iter_mut() {
+        if range.2.is_some() {
+            let floor = previous_end.map(|e| e + 1).unwrap_or(0);
+            while range.0 > floor {
+                let line = lines[range.0 - 1].trim_start();
+                if line.is_empty() || !LEADING_PREFIXES.iter().any(|p| line.starts_with(p)) {
+                    break;
+                }
+                range.0 -= 1;
+            }
+        }
+        previous_end = Some(range.1);
+    }
+}
+
+/// Add chunks for non-blank lines not covered by any symbol
+fn fill_gaps(ranges: &mut Vec<(usize, usize, Option<String>)>, lines: &[&str], max_lines: usize) {
+    let mut gaps = Vec::new();
+    let mut cursor = 0;
+    for &(start, end, _) in ranges.iter() {
+        if start > cursor {
+            gaps.push((cursor, start - 1));
+        }
+        cursor = cursor.max(end + 1);
+    }
+    if cursor < lines.len() {
+        gaps.push((cursor, lines.len() - 1));
+    }
+
+    for (start, end) in gaps {
+        // Trim blank lines at both ends
+        let Some(first) = (start..=end).find(|&i| !lines[i].trim().is_empty()) else {
+            continue;
+        };
+        let last = (first..=end)
+            .rev()
+            .find(|&i| !lines[i].trim().is_empty())
+            .unwrap_or(first);
+        push_windows(ranges, first, last, max_lines, None);
+    }
+    ranges.sort_by_key(|r| r.0);
+}
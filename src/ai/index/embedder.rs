@@ -0,0 +1,102 @@
+//! Embeddings from a local Ollama server
+
+use super::types::IndexError;
+use serde::{Deserialize, Serialize};
+
+/// Ollama embed request
+#[derive(Debug, Serialize)]
+struct EmbedRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+/// Ollama embed response
+#[derive(Debug, Deserialize)]
+struct EmbedResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+/// Client for Ollama's `/api/embed` endpoint
+pub struct OllamaEmbedder {
+    client: reqwest::Client,
+    base_url: String,
+    model: String,
+}
+
+impl OllamaEmbedder {
+    /// Create an embedder for a model served at `base_url`
+    pub fn new(base_url: impl Into<String>, model: impl Into<String>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(120))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            client,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            model: model.into(),
+        }
+    }
+
+    /// Model name
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Embed a batch of texts
+    pub async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, IndexError> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let response = self
+            .client
+            .post(format!("{}/api/embed", self.base_url))
+            .json(&EmbedRequest {
+                model: &self.model,
+                input: texts,
+            })
+            .send()
+            .await
+            .map_err(|e| IndexError::Embedding(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(IndexError::Embedding(format!(
+                "Ollama returned {}",
+                response.status()
+            )));
+        }
+
+        let body: EmbedResponse = response
+            .json()
+            .await
+            .map_err(|e| IndexError::Embedding(format!("Invalid response: {}", e)))?;
+        if body.embeddings.len() != texts.len() {
+            return Err(IndexError::Embedding(format!(
+                "Expected {} embeddings, got {}",
+                texts.len(),
+                body.embeddings.len()
+            )));
+        }
+        Ok(body.embeddings)
+    }
+}
+
+/// Cosine similarity of two vectors (0 for mismatched or zero vectors)
+pub(crate) fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let mut dot = 0.0;
+    let mut norm_a = 0.0;
+    let mut norm_b = 0.0;
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
@@ -0,0 +1,385 @@
+//! Project indexer and query API
+
+use super::chunker::chunk_source;
+use super::embedder::{cosine_similarity, OllamaEmbedder};
+use super::store::{FileState, IndexStore};
+use super::types::{EmbeddingBackend, IndexConfig, IndexError, IndexStats, SearchHit};
+use crate::ai::context::ContextItem;
+use parking_lot::Mutex;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Constant used by reciprocal rank fusion
+const RRF_K: f32 = 60.0;
+
+/// Semantic code index for one project
+pub struct CodeIndex {
+    /// Project root
+    root: PathBuf,
+    /// Configuration
+    config: IndexConfig,
+    /// Storage
+    store: Mutex<IndexStore>,
+    /// Embedding client (None in BM25 mode)
+    embedder: Option<OllamaEmbedder>,
+    /// Cleared after the embedding model fails, so we stop retrying this session
+    embedder_available: AtomicBool,
+}
+
+impl CodeIndex {
+    /// Open the index for a project in the default data directory
+    pub fn open(root: impl Into<PathBuf>, config: IndexConfig) -> Result<Self, IndexError> {
+        let root = root.into();
+        let path = Self::default_db_path(&root)?;
+        Self::with_store(root, config, IndexStore::open(&path)?)
+    }
+
+    /// Create an index over an existing store
+    pub fn with_store(
+        root: impl Into<PathBuf>,
+        config: IndexConfig,
+        store: IndexStore,
+    ) -> Result<Self, IndexError> {
+        let embedder = match &config.embedding {
+            EmbeddingBackend::Bm25 => None,
+            EmbeddingBackend::Ollama { base_url, model } => {
+                Some(OllamaEmbedder::new(base_url.clone(), model.clone()))
+            }
+        };
+
+        // Vectors from a different model are not comparable; BM25 mode is
+        // stored as an empty model name
+        let model = embedder.as_ref().map(|e| e.model()).unwrap_or_default();
+        if store.meta("embedding_model")?.as_deref() != Some(model) {
+            store.clear_embeddings()?;
+            store.set_meta("embedding_model", model)?;
+        }
+
+        Ok(Self {
+            root: root.into(),
+            config,
+            store: Mutex::new(store),
+            embedder,
+            embedder_available: AtomicBool::new(true),
+        })
+    }
+
+    /// Index database location (`<data>/claude-visual/index/<hash of root>.db`)
+    pub fn default_db_path(root: &Path) -> Result<PathBuf, IndexError> {
+        let data_dir = dirs::data_dir()
+            .ok_or_else(|| IndexError::Location("no data directory".to_string()))?;
+        let hash = Sha256::digest(root.to_string_lossy().as_bytes());
+        Ok(data_dir
+            .join("claude-visual")
+            .join("index")
+            .join(format!("{}.db", &hex::encode(hash)[..16])))
+    }
+
+    /// Project root
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Whether queries can use embeddings
+    pub fn uses_embeddings(&self) -> bool {
+        self.embedder.is_some() && self.embedder_available.load(Ordering::Relaxed)
+    }
+
+    /// Number of indexed files and chunks
+    pub fn counts(&self) -> Result<(usize, usize), IndexError> {
+        self.store.lock().counts()
+    }
+
+    /// Bring the index up to date with the project
+    ///
+    /// Only files whose mtime or size changed are re-chunked; files that
+    /// disappeared are removed. In git repositories the file list comes from
+    /// the index and working-tree status, so ignored files are skipped.
+    pub async fn update(&self) -> Result<IndexStats, IndexError> {
+        let files = self.list_files();
+        let mut stats = IndexStats {
+            files_scanned: files.len(),
+            ..Default::default()
+        };
+
+        let known: HashMap<PathBuf, FileState> =
+            self.store.lock().file_states()?.into_iter().collect();
+        let current: HashSet<&PathBuf> = files.iter().collect();
+
+        for path in known.keys().filter(|p| !current.contains(p)) {
+            self.store.lock().remove_file(path)?;
+            stats.files_removed += 1;
+        }
+
+        for path in &files {
+            let Some(state) = self.file_state(path) else {
+                continue;
+            };
+            if known.get(path) == Some(&state) {
+                continue;
+            }
+            stats.chunks_written += self.index_file(path, state)?;
+            stats.files_updated += 1;
+        }
+
+        stats.chunks_embedded = self.embed_pending().await;
+        Ok(stats)
+    }
+
+    /// Re-index specific files (e.g. from a file watcher or `git status`)
+    ///
+    /// Paths may be absolute or relative to the project root. Missing files
+    /// are removed from the index.
+    pub async fn update_paths(&self, paths: &[PathBuf]) -> Result<IndexStats, IndexError> {
+        let mut stats = IndexStats::default();
+        for path in paths {
+            let relative = path.strip_prefix(&self.root).unwrap_or(path).to_path_buf();
+            stats.files_scanned += 1;
+            match self.file_state(&relative) {
+                Some(state) if self.is_indexable(&relative) => {
+                    stats.chunks_written += self.index_file(&relative, state)?;
+                    stats.files_updated += 1;
+                }
+                // Missing, or still on disk but no longer indexable
+                _ => {
+                    self.store.lock().remove_file(&relative)?;
+                    stats.files_removed += 1;
+                }
+            }
+        }
+        stats.chunks_embedded = self.embed_pending().await;
+        Ok(stats)
+    }
+
+    /// Search the index
+    ///
+    /// Uses reciprocal rank fusion of vector and BM25 rankings when
+    /// embeddings are available, BM25 alone otherwise.
+    pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>, IndexError> {
+        let candidates = limit.max(1) * 4;
+        let keyword = self.store.lock().search_bm25(query, candidates)?;
+        let vector = self.vector_search(query, candidates).await;
+
+        let ranked: Vec<(i64, f32)> = match vector {
+            Some(vector) if !vector.is_empty() => {
+                let mut fused: HashMap<i64, f32> = HashMap::new();
+                for list in [&keyword, &vector] {
+                    for (rank, (id, _)) in list.iter().enumerate() {
+                        *fused.entry(*id).or_default() += 1.0 / (RRF_K + rank as f32 + 1.0);
+                    }
+                }
+                let mut fused: Vec<_> = fused.into_iter().collect();
+                fused.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+                fused
+            }
+            _ => keyword,
+        };
+
+        let store = self.store.lock();
+        let mut hits = Vec::new();
+        for (id, score) in ranked.into_iter().take(limit) {
+            if let Some(chunk) = store.chunk(id)? {
+                hits.push(SearchHit { chunk, score });
+            }
+        }
+        Ok(hits)
+    }
+
+    /// Search and return ranked snippets as context items
+    pub async fn search_context_items(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<ContextItem>, IndexError> {
+        let hits = self.search(query, limit).await?;
+        Ok(hits
+            .into_iter()
+            .map(|hit| {
+                let mut item = ContextItem::search_result(
+                    self.root.join(&hit.chunk.path),
+                    hit.chunk.content,
+                    hit.chunk.start_line,
+                    hit.chunk.end_line,
+                    query,
+                    hit.score,
+                );
+                if let Some(symbol) = hit.chunk.symbol {
+                    item.metadata.insert("symbol".to_string(), symbol);
+                }
+                item
+            })
+            .collect())
+    }
+
+    /// Embed the query and rank chunks by cosine similarity
+    async fn vector_search(&self, query: &str, limit: usize) -> Option<Vec<(i64, f32)>> {
+        if !self.uses_embeddings() {
+            return None;
+        }
+        let embedder = self.embedder.as_ref()?;
+        let query_vector = match embedder.embed(&[query.to_string()]).await {
+            Ok(mut vectors) => vectors.pop()?,
+            Err(e) => {
+                self.disable_embeddings(&e);
+                return None;
+            }
+        };
+
+        let embeddings = self.store.lock().embeddings().ok()?;
+        let mut scored: Vec<(i64, f32)> = embeddings
+            .into_iter()
+            .map(|(id, vector)| (id, cosine_similarity(&query_vector, &vector)))
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.truncate(limit);
+        Some(scored)
+    }
+
+    /// Embed chunks that don't have a vector yet; returns how many were embedded
+    async fn embed_pending(&self) -> usize {
+        let Some(embedder) = self.embedder.as_ref() else {
+            return 0;
+        };
+
+        let mut embedded = 0;
+        while self.uses_embeddings() {
+            let batch = match self
+                .store
+                .lock()
+                .chunks_without_embedding(self.config.embedding_batch_size)
+            {
+                Ok(batch) if !batch.is_empty() => batch,
+                _ => break,
+            };
+
+            let texts: Vec<String> = batch.iter().map(|(_, content)| content.clone()).collect();
+            match embedder.embed(&texts).await {
+                Ok(vectors) => {
+                    let pairs: Vec<(i64, Vec<f32>)> =
+                        batch.iter().map(|(id, _)| *id).zip(vectors).collect();
+                    if let Err(e) = self.store.lock().set_embeddings(&pairs) {
+                        tracing::warn!("Failed to store embeddings: {}", e);
+                        break;
+                    }
+                    embedded += pairs.len();
+                }
+                Err(e) => self.disable_embeddings(&e),
+            }
+        }
+        embedded
+    }
+
+    fn disable_embeddings(&self, error: &IndexError) {
+        if self.embedder_available.swap(false, Ordering::Relaxed) {
+            tracing::warn!("Embedding model unavailable, using BM25 only: {}", error);
+        }
+    }
+
+    /// Chunk one file and write it to the store; returns the chunk count
+    fn index_file(&self, relative: &Path, state: FileState) -> Result<usize, IndexError> {
+        let content = match std::fs::read(self.root.join(relative)) {
+            Ok(bytes) => String::from_utf8(bytes).unwrap_or_default(),
+            Err(e) => {
+                tracing::debug!("Skipping {}: {}", relative.display(), e);
+                String::new()
+            }
+        };
+        let chunks = chunk_source(relative, &content, self.config.max_chunk_lines);
+        self.store.lock().replace_file(relative, state, &chunks)?;
+        Ok(chunks.len())
+    }
+
+    /// Current mtime/size of a file relative to the root
+    fn file_state(&self, relative: &Path) -> Option<FileState> {
+        let metadata = std::fs::metadata(self.root.join(relative)).ok()?;
+        if !metadata.is_file() {
+            return None;
+        }
+        let mtime = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos() as i64)
+            .unwrap_or(0);
+        Some(FileState {
+            mtime,
+            size: metadata.len() as i64,
+        })
+    }
+
+    fn is_indexable(&self, relative: &Path) -> bool {
+        let extension_ok = relative
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .is_some_and(|e| self.config.extensions.contains(&e));
+        extension_ok
+            && std::fs::metadata(self.root.join(relative))
+                .map(|m| m.len() <= self.config.max_file_bytes)
+                .unwrap_or(false)
+    }
+
+    /// Indexable files relative to the root
+    fn list_files(&self) -> Vec<PathBuf> {
+        let mut files = self.list_git_files().unwrap_or_else(|| self.walk_files());
+        files.retain(|p| self.is_indexable(p));
+        files.sort();
+        files.dedup();
+        files
+    }
+
+    /// Tracked and untracked-but-not-ignored files from git
+    fn list_git_files(&self) -> Option<Vec<PathBuf>> {
+        let repo = git2::Repository::open(&self.root).ok()?;
+        let workdir = repo.workdir()?.canonicalize().ok()?;
+        if workdir != self.root.canonicalize().ok()? {
+            return None;
+        }
+
+        let mut files: Vec<PathBuf> = repo
+            .index()
+            .ok()?
+            .iter()
+            .map(|entry| PathBuf::from(String::from_utf8_lossy(&entry.path).to_string()))
+            .collect();
+
+        let mut options = git2::StatusOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(true);
+        if let Ok(statuses) = repo.statuses(Some(&mut options)) {
+            files.extend(
+                statuses
+                    .iter()
+                    .filter(|s| s.status().is_wt_new())
+                    .filter_map(|s| s.path().map(PathBuf::from)),
+            );
+        }
+        Some(files)
+    }
+
+    /// Recursive directory walk for projects outside git
+    fn walk_files(&self) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        let mut stack = vec![PathBuf::new()];
+        while let Some(dir) = stack.pop() {
+            let Ok(entries) = std::fs::read_dir(self.root.join(&dir)) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name.starts_with('.') {
+                    continue;
+                }
+                let relative = dir.join(&name);
+                match entry.file_type() {
+                    Ok(t) if t.is_dir() && !self.config.ignored_dirs.contains(&name) => {
+                        stack.push(relative)
+                    }
+                    Ok(t) if t.is_file() => files.push(relative),
+                    _ => {}
+                }
+            }
+        }
+        files
+    }
+}
@@ -0,0 +1,21 @@
+//! Semantic Code Index
+//!
+//! Local retrieval for project source: files are split into chunks along
+//! tree-sitter symbol boundaries, embedded with a local Ollama model when one
+//! is reachable, and stored in SQLite. Queries are answered by vector
+//! similarity fused with BM25 (via FTS5), or by BM25 alone without a model.
+
+mod chunker;
+mod embedder;
+mod indexer;
+mod store;
+mod types;
+
+#[cfg(test)]
+mod tests;
+
+pub use chunker::chunk_source;
+pub use embedder::OllamaEmbedder;
+pub use indexer::CodeIndex;
+pub use store::IndexStore;
+pub use types::{CodeChunk, EmbeddingBackend, IndexConfig, IndexError, IndexStats, SearchHit};
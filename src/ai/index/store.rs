@@ -0,0 +1,307 @@
+//! SQLite storage for chunks, BM25 terms and embeddings
+
+use super::types::{CodeChunk, IndexError};
+use crate::ai::context::split_identifiers;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::{Path, PathBuf};
+
+/// Stored file state used to detect changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FileState {
+    pub mtime: i64,
+    pub size: i64,
+}
+
+/// Index database
+pub struct IndexStore {
+    conn: Connection,
+}
+
+impl IndexStore {
+    /// Open (or create) an index database at `path`
+    pub fn open(path: &Path) -> Result<Self, IndexError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let store = Self {
+            conn: Connection::open(path)?,
+        };
+        store.initialize()?;
+        Ok(store)
+    }
+
+    /// Open an in-memory index
+    pub fn open_in_memory() -> Result<Self, IndexError> {
+        let store = Self {
+            conn: Connection::open_in_memory()?,
+        };
+        store.initialize()?;
+        Ok(store)
+    }
+
+    fn initialize(&self) -> Result<(), IndexError> {
+        self.conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS index_meta (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS indexed_files (
+                path TEXT PRIMARY KEY,
+                mtime INTEGER NOT NULL,
+                size INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS chunks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                path TEXT NOT NULL,
+                start_line INTEGER NOT NULL,
+                end_line INTEGER NOT NULL,
+                symbol TEXT,
+                content TEXT NOT NULL,
+                embedding BLOB
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_chunks_path ON chunks(path);
+
+            -- Pre-split identifiers so BM25 matches parts of snake_case/camelCase names
+            CREATE VIRTUAL TABLE IF NOT EXISTS chunks_fts USING fts5(terms);
+            "#,
+        )?;
+        Ok(())
+    }
+
+    /// Get a metadata value
+    pub(crate) fn meta(&self, key: &str) -> Result<Option<String>, IndexError> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT value FROM index_meta WHERE key = ?1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Set a metadata value
+    pub(crate) fn set_meta(&self, key: &str, value: &str) -> Result<(), IndexError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO index_meta (key, value) VALUES (?1, ?2)",
+            params![key, value],
+        )?;
+        Ok(())
+    }
+
+    /// Stored state of every indexed file
+    pub(crate) fn file_states(&self) -> Result<Vec<(PathBuf, FileState)>, IndexError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT path, mtime, size FROM indexed_files")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                PathBuf::from(row.get::<_, String>(0)?),
+                FileState {
+                    mtime: row.get(1)?,
+                    size: row.get(2)?,
+                },
+            ))
+        })?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Replace all chunks of a file; returns the new chunk ids
+    pub(crate) fn replace_file(
+        &mut self,
+        path: &Path,
+        state: FileState,
+        chunks: &[CodeChunk],
+    ) -> Result<Vec<i64>, IndexError> {
+        let path_str = path.to_string_lossy();
+        let tx = self.conn.transaction()?;
+        delete_chunks(&tx, &path_str)?;
+
+        let mut ids = Vec::with_capacity(chunks.len());
+        for chunk in chunks {
+            tx.execute(
+                "INSERT INTO chunks (path, start_line, end_line, symbol, content) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    path_str,
+                    chunk.start_line as i64,
+                    chunk.end_line as i64,
+                    chunk.symbol,
+                    chunk.content
+                ],
+            )?;
+            let id = tx.last_insert_rowid();
+            tx.execute(
+                "INSERT INTO chunks_fts (rowid, terms) VALUES (?1, ?2)",
+                params![id, chunk_terms(chunk)],
+            )?;
+            ids.push(id);
+        }
+
+        tx.execute(
+            "INSERT OR REPLACE INTO indexed_files (path, mtime, size) VALUES (?1, ?2, ?3)",
+            params![path_str, state.mtime, state.size],
+        )?;
+        tx.commit()?;
+        Ok(ids)
+    }
+
+    /// Remove a file and its chunks
+    pub(crate) fn remove_file(&mut self, path: &Path) -> Result<(), IndexError> {
+        let path_str = path.to_string_lossy();
+        let tx = self.conn.transaction()?;
+        delete_chunks(&tx, &path_str)?;
+        tx.execute(
+            "DELETE FROM indexed_files WHERE path = ?1",
+            params![path_str],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Chunks that still need an embedding
+    pub(crate) fn chunks_without_embedding(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<(i64, String)>, IndexError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, content FROM chunks WHERE embedding IS NULL LIMIT ?1")?;
+        let rows = stmt.query_map(params![limit as i64], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Store embeddings for chunks
+    pub(crate) fn set_embeddings(
+        &mut self,
+        embeddings: &[(i64, Vec<f32>)],
+    ) -> Result<(), IndexError> {
+        let tx = self.conn.transaction()?;
+        for (id, vector) in embeddings {
+            tx.execute(
+                "UPDATE chunks SET embedding = ?1 WHERE id = ?2",
+                params![encode_vector(vector), id],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Drop all embeddings (after the embedding model changed)
+    pub(crate) fn clear_embeddings(&self) -> Result<(), IndexError> {
+        self.conn
+            .execute("UPDATE chunks SET embedding = NULL", [])?;
+        Ok(())
+    }
+
+    /// All stored embeddings
+    pub(crate) fn embeddings(&self) -> Result<Vec<(i64, Vec<f32>)>, IndexError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, embedding FROM chunks WHERE embedding IS NOT NULL")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                decode_vector(&row.get::<_, Vec<u8>>(1)?),
+            ))
+        })?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// BM25 search; returns chunk ids with scores (higher is better)
+    pub(crate) fn search_bm25(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<(i64, f32)>, IndexError> {
+        let mut terms: Vec<String> = split_identifiers(query).filter(|t| t.len() > 1).collect();
+        terms.sort();
+        terms.dedup();
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Terms are alphanumeric/underscore only, so quoting is enough to escape them
+        let match_expr = terms
+            .iter()
+            .map(|t| format!("\"{}\"", t))
+            .collect::<Vec<_>>()
+            .join(" OR ");
+
+        let mut stmt = self.conn.prepare(
+            "SELECT rowid, bm25(chunks_fts) AS rank FROM chunks_fts
+             WHERE chunks_fts MATCH ?1 ORDER BY rank LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![match_expr, limit as i64], |row| {
+            // FTS5's bm25() is negative, lower is better
+            Ok((row.get::<_, i64>(0)?, -(row.get::<_, f64>(1)? as f32)))
+        })?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Load a chunk by id
+    pub(crate) fn chunk(&self, id: i64) -> Result<Option<CodeChunk>, IndexError> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT path, start_line, end_line, symbol, content FROM chunks WHERE id = ?1",
+                params![id],
+                |row| {
+                    Ok(CodeChunk {
+                        path: PathBuf::from(row.get::<_, String>(0)?),
+                        start_line: row.get::<_, i64>(1)? as usize,
+                        end_line: row.get::<_, i64>(2)? as usize,
+                        symbol: row.get(3)?,
+                        content: row.get(4)?,
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// Number of indexed files and chunks
+    pub fn counts(&self) -> Result<(usize, usize), IndexError> {
+        let files: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM indexed_files", [], |row| row.get(0))?;
+        let chunks: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM chunks", [], |row| row.get(0))?;
+        Ok((files as usize, chunks as usize))
+    }
+}
+
+fn delete_chunks(conn: &Connection, path: &str) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "DELETE FROM chunks_fts WHERE rowid IN (SELECT id FROM chunks WHERE path = ?1)",
+        params![path],
+    )?;
+    conn.execute("DELETE FROM chunks WHERE path = ?1", params![path])?;
+    Ok(())
+}
+
+/// Terms indexed for a chunk: path components, symbol and content identifiers
+fn chunk_terms(chunk: &CodeChunk) -> String {
+    let mut text = chunk.path.to_string_lossy().to_string();
+    if let Some(symbol) = &chunk.symbol {
+        text.push(' ');
+        text.push_str(symbol);
+    }
+    text.push(' ');
+    text.push_str(&chunk.content);
+    split_identifiers(&text).collect::<Vec<_>>().join(" ")
+}
+
+fn encode_vector(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn decode_vector(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
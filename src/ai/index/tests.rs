@@ -0,0 +1,223 @@
+//! Tests for the code index (BM25 mode, no embedding model)
+
+use super::*;
+use crate::ai::context::ContextItemType;
+use std::path::{Path, PathBuf};
+
+const CONFIG_RS: &str = r#"use std::path::PathBuf;
+
+/// Load the configuration file
+pub fn load_config(path: PathBuf) -> Config {
+    let text = std::fs::read_to_string(path).unwrap();
+    parse_config(&text)
+}
+
+fn parse_config(text: &str) -> Config {
+    Config { name: text.to_string() }
+}
+"#;
+
+const RENDER_PY: &str = r#"class Renderer:
+    def draw_frame(self, canvas):
+        canvas.clear()
+
+def main():
+    Renderer().draw_frame(None)
+"#;
+
+fn temp_project() -> PathBuf {
+    let root = std::env::temp_dir().join(format!("claude_visual_index_{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::write(root.join("src/config.rs"), CONFIG_RS).unwrap();
+    std::fs::write(root.join("src/render.py"), RENDER_PY).unwrap();
+    std::fs::write(root.join("notes.bin"), "ignored").unwrap();
+    root
+}
+
+fn bm25_index(root: &Path) -> CodeIndex {
+    CodeIndex::with_store(
+        root,
+        IndexConfig::bm25(),
+        IndexStore::open_in_memory().unwrap(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_chunk_source_follows_symbols() {
+    let chunks = chunk_source(Path::new("src/config.rs"), CONFIG_RS, 80);
+    let symbols: Vec<_> = chunks.iter().map(|c| c.symbol.as_deref()).collect();
+    assert_eq!(
+        symbols,
+        vec![None, Some("load_config"), Some("parse_config")]
+    );
+
+    // Doc comments travel with their symbol
+    assert_eq!(chunks[1].start_line, 3);
+    assert!(chunks[1]
+        .content
+        .starts_with("/// Load the configuration file"));
+    assert_eq!(chunks[2].start_line, 9);
+    assert_eq!(chunks[2].end_line, 11);
+}
+
+#[test]
+fn test_chunk_source_splits_large_symbols() {
+    let chunks = chunk_source(Path::new("render.py"), RENDER_PY, 2);
+    assert!(chunks
+        .iter()
+        .any(|c| c.symbol.as_deref() == Some("Renderer::draw_frame")));
+    assert!(chunks.iter().all(|c| c.end_line - c.start_line < 2));
+
+    let plain = "a\nb\nc\nd\ne";
+    let windows = chunk_source(Path::new("notes.txt"), plain, 2);
+    assert_eq!(windows.len(), 3);
+    assert_eq!(windows[2].content, "e");
+}
+
+#[tokio::test]
+async fn test_bm25_search() {
+    let root = temp_project();
+    let index = bm25_index(&root);
+
+    let stats = index.update().await.unwrap();
+    assert_eq!(stats.files_updated, 2);
+    assert_eq!(stats.chunks_embedded, 0);
+
+    let hits = index.search("where is the config parsed", 3).await.unwrap();
+    assert!(!hits.is_empty());
+    assert_eq!(hits[0].chunk.path, PathBuf::from("src/config.rs"));
+
+    let hits = index.search("drawFrame", 3).await.unwrap();
+    assert_eq!(hits[0].chunk.path, PathBuf::from("src/render.py"));
+    assert!(hits[0].chunk.content.contains("draw_frame"));
+
+    assert!(index.search("nonexistentterm", 3).await.unwrap().is_empty());
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[tokio::test]
+async fn test_incremental_update() {
+    let root = temp_project();
+    let index = bm25_index(&root);
+    index.update().await.unwrap();
+
+    // Nothing changed
+    let stats = index.update().await.unwrap();
+    assert_eq!(stats.files_updated, 0);
+    assert_eq!(stats.files_removed, 0);
+
+    // One file changed, one removed
+    std::fs::write(
+        root.join("src/config.rs"),
+        "fn load_settings() -> u32 {\n    42\n}\n",
+    )
+    .unwrap();
+    std::fs::remove_file(root.join("src/render.py")).unwrap();
+
+    let stats = index.update().await.unwrap();
+    assert_eq!(stats.files_updated, 1);
+    assert_eq!(stats.files_removed, 1);
+    assert_eq!(index.counts().unwrap(), (1, 1));
+
+    assert!(index.search("draw_frame", 3).await.unwrap().is_empty());
+    assert!(index.search("read_to_string", 3).await.unwrap().is_empty());
+    assert_eq!(index.search("settings", 3).await.unwrap().len(), 1);
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[tokio::test]
+async fn test_update_paths() {
+    let root = temp_project();
+    let index = bm25_index(&root);
+    index.update().await.unwrap();
+
+    std::fs::write(root.join("src/extra.rs"), "fn telemetry_flush() {}\n").unwrap();
+    let stats = index
+        .update_paths(&[root.join("src/extra.rs"), PathBuf::from("src/gone.rs")])
+        .await
+        .unwrap();
+    assert_eq!(stats.files_updated, 1);
+    assert_eq!(stats.files_removed, 1);
+    assert_eq!(index.search("telemetry", 1).await.unwrap().len(), 1);
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[tokio::test]
+async fn test_update_paths_removes_files_no_longer_indexable() {
+    let root = temp_project();
+    let config = IndexConfig {
+        max_file_bytes: 1024,
+        ..IndexConfig::bm25()
+    };
+    let index =
+        CodeIndex::with_store(&root, config, IndexStore::open_in_memory().unwrap()).unwrap();
+    index.update().await.unwrap();
+
+    std::fs::write(root.join("src/config.rs"), "// big\n".repeat(200)).unwrap();
+    let stats = index
+        .update_paths(&[root.join("src/config.rs")])
+        .await
+        .unwrap();
+    assert_eq!(stats.files_removed, 1);
+    assert_eq!(index.counts().unwrap().0, 1);
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[tokio::test]
+async fn test_reopening_keeps_embeddings() {
+    let root = temp_project();
+    let db = root.join("index.db");
+
+    // Built with an embedding model, then switched to BM25
+    CodeIndex::with_store(
+        &root,
+        IndexConfig::default(),
+        IndexStore::open(&db).unwrap(),
+    )
+    .unwrap();
+    let index =
+        CodeIndex::with_store(&root, IndexConfig::bm25(), IndexStore::open(&db).unwrap()).unwrap();
+    index.update().await.unwrap();
+    drop(index);
+
+    let mut store = IndexStore::open(&db).unwrap();
+    let (id, _) = store.chunks_without_embedding(1).unwrap()[0].clone();
+    store.set_embeddings(&[(id, vec![1.0, 0.0])]).unwrap();
+
+    // Same backend as last time: nothing to invalidate
+    CodeIndex::with_store(&root, IndexConfig::bm25(), store).unwrap();
+    assert_eq!(
+        IndexStore::open(&db).unwrap().embeddings().unwrap().len(),
+        1
+    );
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[tokio::test]
+async fn test_search_context_items() {
+    let root = temp_project();
+    let index = bm25_index(&root);
+    index.update().await.unwrap();
+
+    let items = index.search_context_items("load_config", 1).await.unwrap();
+    assert_eq!(items.len(), 1);
+    let item = &items[0];
+    assert_eq!(item.item_type, ContextItemType::SearchResults);
+    assert_eq!(item.path, Some(root.join("src/config.rs")));
+    assert_eq!(item.start_line, Some(3));
+    assert_eq!(
+        item.metadata.get("symbol").map(|s| s.as_str()),
+        Some("load_config")
+    );
+    assert!(item
+        .format_for_prompt()
+        .starts_with("Search result for \"load_config\""));
+
+    let _ = std::fs::remove_dir_all(&root);
+}
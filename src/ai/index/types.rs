@@ -0,0 +1,130 @@
+//! Type definitions for the code index
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// How chunks are embedded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EmbeddingBackend {
+    /// Keyword retrieval only (BM25 over FTS5)
+    Bm25,
+    /// Embeddings from a local Ollama model, with BM25 as fallback
+    Ollama { base_url: String, model: String },
+}
+
+impl Default for EmbeddingBackend {
+    fn default() -> Self {
+        EmbeddingBackend::Ollama {
+            base_url: "http://localhost:11434".to_string(),
+            model: "nomic-embed-text".to_string(),
+        }
+    }
+}
+
+/// Indexer configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexConfig {
+    /// File extensions to index
+    pub extensions: Vec<String>,
+    /// Directories never descended into (outside of git repositories)
+    pub ignored_dirs: Vec<String>,
+    /// Files larger than this are skipped
+    pub max_file_bytes: u64,
+    /// Maximum lines per chunk
+    pub max_chunk_lines: usize,
+    /// Chunks embedded per model request
+    pub embedding_batch_size: usize,
+    /// Embedding backend
+    pub embedding: EmbeddingBackend,
+}
+
+impl Default for IndexConfig {
+    fn default() -> Self {
+        Self {
+            extensions: [
+                "rs", "py", "js", "jsx", "mjs", "ts", "tsx", "go", "java", "kt", "c", "h", "cpp",
+                "hpp", "cs", "rb", "swift", "sh", "toml", "md",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+            ignored_dirs: [
+                "target",
+                "node_modules",
+                "dist",
+                "build",
+                "vendor",
+                "__pycache__",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+            max_file_bytes: 512 * 1024,
+            max_chunk_lines: 80,
+            embedding_batch_size: 32,
+            embedding: EmbeddingBackend::default(),
+        }
+    }
+}
+
+impl IndexConfig {
+    /// Configuration that never contacts an embedding model
+    pub fn bm25() -> Self {
+        Self {
+            embedding: EmbeddingBackend::Bm25,
+            ..Default::default()
+        }
+    }
+}
+
+/// A chunk of a source file
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeChunk {
+    /// Path relative to the project root
+    pub path: PathBuf,
+    /// Start line (1-based)
+    pub start_line: usize,
+    /// End line (1-based, inclusive)
+    pub end_line: usize,
+    /// Enclosing symbol name, if the chunk follows a symbol boundary
+    pub symbol: Option<String>,
+    /// Chunk text
+    pub content: String,
+}
+
+/// A ranked search result
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    /// Matching chunk
+    pub chunk: CodeChunk,
+    /// Relevance score (higher is better)
+    pub score: f32,
+}
+
+/// Result of an index update
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexStats {
+    /// Files considered
+    pub files_scanned: usize,
+    /// Files (re)indexed because they were new or changed
+    pub files_updated: usize,
+    /// Files removed from the index
+    pub files_removed: usize,
+    /// Chunks written
+    pub chunks_written: usize,
+    /// Chunks embedded
+    pub chunks_embedded: usize,
+}
+
+/// Index errors
+#[derive(Debug, thiserror::Error)]
+pub enum IndexError {
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Embedding error: {0}")]
+    Embedding(String),
+    #[error("Could not determine index location: {0}")]
+    Location(String),
+}
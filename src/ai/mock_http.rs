@@ -0,0 +1,94 @@
+//! Local HTTP server for provider and fetcher tests
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+
+/// Captured HTTP request
+struct CapturedRequest {
+    /// Request line and headers, lowercased
+    head: String,
+    /// Body parsed as JSON (`Null` when empty or not JSON)
+    body: serde_json::Value,
+}
+
+/// Minimal HTTP server answering each connection with the next canned response
+pub(crate) struct MockServer {
+    /// Base URL (`http://127.0.0.1:<port>`)
+    pub(crate) url: String,
+    requests: Arc<Mutex<Vec<CapturedRequest>>>,
+}
+
+impl MockServer {
+    pub(crate) fn start(responses: Vec<String>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = requests.clone();
+
+        std::thread::spawn(move || {
+            for response in responses {
+                let Ok((mut stream, _)) = listener.accept() else {
+                    return;
+                };
+                let mut data = Vec::new();
+                let mut buf = [0u8; 4096];
+                let body_start = loop {
+                    if let Some(pos) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+                        break pos + 4;
+                    }
+                    match stream.read(&mut buf) {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => data.extend_from_slice(&buf[..n]),
+                    }
+                };
+                let head = String::from_utf8_lossy(&data[..body_start]).to_lowercase();
+                let length = head
+                    .lines()
+                    .find_map(|l| l.strip_prefix("content-length:"))
+                    .and_then(|v| v.trim().parse::<usize>().ok())
+                    .unwrap_or(0);
+                while data.len() < body_start + length {
+                    match stream.read(&mut buf) {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => data.extend_from_slice(&buf[..n]),
+                    }
+                }
+                let body = serde_json::from_slice(&data[body_start..]).unwrap_or_default();
+                seen.lock().unwrap().push(CapturedRequest { head, body });
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    /// Number of requests received so far
+    pub(crate) fn request_count(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+
+    /// Request line and headers of a request, lowercased
+    pub(crate) fn head(&self, index: usize) -> String {
+        self.requests.lock().unwrap()[index].head.clone()
+    }
+
+    /// JSON body of a request
+    pub(crate) fn body(&self, index: usize) -> serde_json::Value {
+        self.requests.lock().unwrap()[index].body.clone()
+    }
+}
+
+/// Build a complete `Connection: close` response
+pub(crate) fn http_response(status: &str, headers: &[(&str, &str)], body: &str) -> String {
+    let mut response = format!("HTTP/1.1 {}\r\n", status);
+    for (name, value) in headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str(&format!(
+        "Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    ));
+    response
+}
@@ -4,16 +4,24 @@
 
 pub mod claude;
 pub mod context;
+pub mod index;
 pub mod mention;
 pub mod ollama;
 pub mod openai;
 pub mod provider;
+pub mod router;
 pub mod summarizer;
+pub mod web;
+
+#[cfg(test)]
+mod mock_http;
 
 pub use context::{ContextItem, ContextItemType, ContextManager};
 pub use mention::{get_mention_at_cursor, parse_mentions, Mention, MentionKind, PartialMention};
 pub use provider::{AIError, AIProvider, AIRequest, AIResponse, Message, MessageRole, StreamChunk};
+pub use router::{RetryPolicy, RouteRequirements, RoutedProvider};
 pub use summarizer::{
     ConversationMessage, ConversationSummary, MessageRole as SummaryMessageRole,
     SummarizationConfig, SummarizationRequest, SummarizationStats, Summarizer,
 };
+pub use web::{WebFetchConfig, WebFetchError, WebFetcher};
@@ -171,10 +171,10 @@ impl OllamaProvider {
         let mut messages: Vec<OllamaMessage> = Vec::new();
 
         // Add system message
-        if let Some(system) = &request.system {
+        if let Some(system) = request.system_with_context() {
             messages.push(OllamaMessage {
                 role: "system".to_string(),
-                content: system.clone(),
+                content: system,
             });
         }
 
@@ -132,7 +132,9 @@ impl AIProvider for OllamaProvider {
                 output_tokens: api_response.eval_count.unwrap_or(0),
                 total_tokens: api_response.prompt_eval_count.unwrap_or(0)
                     + api_response.eval_count.unwrap_or(0),
+                ..Default::default()
             }),
+            metadata: Default::default(),
         })
     }
 
@@ -45,14 +45,10 @@ impl AIProvider for OpenAIProvider {
             .map_err(|e| AIError::Network(e.to_string()))?;
 
         if !response.status().is_success() {
-            let status = response.status();
+            let status = response.status().as_u16();
+            let headers = response.headers().clone();
             let error_text = response.text().await.unwrap_or_default();
-
-            return match status.as_u16() {
-                401 => Err(AIError::Auth("Invalid API key".to_string())),
-                429 => Err(AIError::RateLimit),
-                _ => Err(AIError::Provider(format!("{}: {}", status, error_text))),
-            };
+            return Err(AIError::from_status(status, &headers, error_text));
         }
 
         let api_response: OpenAIApiResponse = response
@@ -95,7 +91,9 @@ impl AIProvider for OpenAIProvider {
                 input_tokens: u.prompt_tokens,
                 output_tokens: u.completion_tokens,
                 total_tokens: u.total_tokens,
+                ..Default::default()
             }),
+            metadata: Default::default(),
         })
     }
 
@@ -115,14 +113,10 @@ impl AIProvider for OpenAIProvider {
             .map_err(|e| AIError::Network(e.to_string()))?;
 
         if !response.status().is_success() {
-            let status = response.status();
+            let status = response.status().as_u16();
+            let headers = response.headers().clone();
             let error_text = response.text().await.unwrap_or_default();
-
-            return match status.as_u16() {
-                401 => Err(AIError::Auth("Invalid API key".to_string())),
-                429 => Err(AIError::RateLimit),
-                _ => Err(AIError::Provider(format!("{}: {}", status, error_text))),
-            };
+            return Err(AIError::from_status(status, &headers, error_text));
         }
 
         let stream = response.bytes_stream().map(move |chunk| match chunk {
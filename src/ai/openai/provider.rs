@@ -77,10 +77,10 @@ impl OpenAIProvider {
         let mut messages: Vec<OpenAIMessage> = Vec::new();
 
         // Add system message if present
-        if let Some(system) = &request.system {
+        if let Some(system) = request.system_with_context() {
             messages.push(OpenAIMessage {
                 role: "system".to_string(),
-                content: Some(system.clone()),
+                content: Some(system),
                 name: None,
                 tool_calls: None,
                 tool_call_id: None,
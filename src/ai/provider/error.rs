@@ -1,5 +1,6 @@
 //! AI provider error types
 
+use std::time::Duration;
 use thiserror::Error;
 
 /// AI provider error types
@@ -13,7 +14,20 @@ pub enum AIError {
     Auth(String),
     /// Rate limit exceeded
     #[error("Rate limit exceeded")]
-    RateLimit,
+    RateLimit {
+        /// Delay requested by the server via `Retry-After`
+        retry_after: Option<Duration>,
+    },
+    /// Provider temporarily unavailable (5xx, overloaded)
+    #[error("Service unavailable ({status}): {message}")]
+    Unavailable {
+        /// HTTP status code
+        status: u16,
+        /// Error body
+        message: String,
+        /// Delay requested by the server via `Retry-After`
+        retry_after: Option<Duration>,
+    },
     /// Invalid request
     #[error("Invalid request: {0}")]
     InvalidRequest(String),
@@ -29,7 +43,159 @@ pub enum AIError {
     /// Timeout
     #[error("Request timeout")]
     Timeout,
+    /// Request refused by the user before it was sent
+    #[error("Request denied: {0}")]
+    Denied(String),
     /// Unknown error
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
+
+impl AIError {
+    /// Map a non-success HTTP response to an error
+    pub fn from_status(status: u16, headers: &reqwest::header::HeaderMap, body: String) -> Self {
+        let retry_after = parse_retry_after(headers);
+        match status {
+            401 | 403 => AIError::Auth(if body.is_empty() {
+                "Invalid API key".to_string()
+            } else {
+                body
+            }),
+            429 => AIError::RateLimit { retry_after },
+            // 529 is Anthropic's "overloaded"
+            500..=599 => AIError::Unavailable {
+                status,
+                message: body,
+                retry_after,
+            },
+            _ => AIError::Provider(format!("{}: {}", status, body)),
+        }
+    }
+
+    /// Whether the same request may succeed if retried later
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            AIError::RateLimit { .. }
+                | AIError::Unavailable { .. }
+                | AIError::Network(_)
+                | AIError::Timeout
+        )
+    }
+
+    /// Delay requested by the server before retrying
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            AIError::RateLimit { retry_after } | AIError::Unavailable { retry_after, .. } => {
+                *retry_after
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Parse `retry-after-ms` or `Retry-After` (seconds or HTTP date)
+pub(crate) fn parse_retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    // `inf` and huge values parse as f64 but are not durations
+    if let Some(delay) = headers
+        .get("retry-after-ms")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .and_then(|ms| seconds_to_duration(ms / 1000.0))
+    {
+        return Some(delay);
+    }
+
+    let value = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(secs) = value.parse::<f64>() {
+        return seconds_to_duration(secs);
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let delta = date.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(delta.to_std().unwrap_or(Duration::ZERO))
+}
+
+/// Non-negative delay from seconds; `None` when not finite or too large
+fn seconds_to_duration(secs: f64) -> Option<Duration> {
+    if !secs.is_finite() {
+        return None;
+    }
+    Duration::try_from_secs_f64(secs.max(0.0)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+
+    #[test]
+    fn test_parse_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(7)));
+
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(parse_retry_after(&headers), Some(Duration::ZERO));
+
+        headers.insert("retry-after-ms", HeaderValue::from_static("250"));
+        assert_eq!(
+            parse_retry_after(&headers),
+            Some(Duration::from_millis(250))
+        );
+    }
+
+    #[test]
+    fn test_parse_retry_after_rejects_bad_numbers() {
+        for value in ["inf", "1e300", "NaN"] {
+            let mut headers = HeaderMap::new();
+            headers.insert(RETRY_AFTER, HeaderValue::from_static(value));
+            assert_eq!(parse_retry_after(&headers), None, "Retry-After: {}", value);
+
+            let mut headers = HeaderMap::new();
+            headers.insert("retry-after-ms", HeaderValue::from_static(value));
+            assert_eq!(
+                parse_retry_after(&headers),
+                None,
+                "retry-after-ms: {}",
+                value
+            );
+        }
+
+        // A bad millisecond header falls back to Retry-After
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after-ms", HeaderValue::from_static("inf"));
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("3"));
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(3)));
+
+        // Negative delays mean "now"
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("-5"));
+        assert_eq!(parse_retry_after(&headers), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_from_status() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("2"));
+
+        let err = AIError::from_status(429, &headers, String::new());
+        assert!(err.is_retryable());
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(2)));
+
+        let err = AIError::from_status(529, &headers, "overloaded".to_string());
+        assert!(matches!(err, AIError::Unavailable { status: 529, .. }));
+        assert!(err.is_retryable());
+
+        assert!(!AIError::from_status(401, &headers, String::new()).is_retryable());
+        assert!(!AIError::from_status(400, &headers, "bad".to_string()).is_retryable());
+    }
+}
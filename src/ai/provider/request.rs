@@ -12,6 +12,11 @@ pub struct AIRequest {
     pub messages: Vec<Message>,
     /// System prompt (optional, prepended to messages)
     pub system: Option<String>,
+    /// Stable context sent after the system prompt (e.g. pinned context items)
+    ///
+    /// Providers with prompt caching cache it separately from the system prompt.
+    #[serde(default)]
+    pub context: Option<String>,
     /// Maximum tokens to generate
     pub max_tokens: Option<usize>,
     /// Temperature (0.0 - 2.0)
@@ -32,6 +37,7 @@ impl Default for AIRequest {
             model: String::new(),
             messages: Vec::new(),
             system: None,
+            context: None,
             max_tokens: None,
             temperature: None,
             top_p: None,
@@ -42,6 +48,17 @@ impl Default for AIRequest {
     }
 }
 
+impl AIRequest {
+    /// System prompt and context joined, for providers without separate blocks
+    pub fn system_with_context(&self) -> Option<String> {
+        match (&self.system, &self.context) {
+            (Some(system), Some(context)) => Some(format!("{}\n\n{}", system, context)),
+            (Some(text), None) | (None, Some(text)) => Some(text.clone()),
+            (None, None) => None,
+        }
+    }
+}
+
 /// Tool definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolDefinition {
@@ -1,6 +1,7 @@
 //! AI response types
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Tool call from the model
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +29,9 @@ pub struct AIResponse {
     pub tool_calls: Vec<ToolCall>,
     /// Usage statistics
     pub usage: Option<Usage>,
+    /// Additional metadata (e.g. routing decisions)
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
 }
 
 /// Stop reason
@@ -44,7 +48,7 @@ pub enum StopReason {
 }
 
 /// Token usage statistics
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Usage {
     /// Input tokens
     pub input_tokens: usize,
@@ -52,4 +56,10 @@ pub struct Usage {
     pub output_tokens: usize,
     /// Total tokens
     pub total_tokens: usize,
+    /// Input tokens written to the prompt cache
+    #[serde(default)]
+    pub cache_creation_input_tokens: usize,
+    /// Input tokens read from the prompt cache
+    #[serde(default)]
+    pub cache_read_input_tokens: usize,
 }
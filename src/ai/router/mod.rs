@@ -0,0 +1,14 @@
+//! Provider Routing
+//!
+//! Wraps several providers behind one `AIProvider`: requests go to the
+//! first capable provider/model pair, rate limits and outages are retried
+//! with backoff, and persistent failures fail over to the next target.
+
+mod provider;
+mod types;
+
+#[cfg(test)]
+mod tests;
+
+pub use provider::RoutedProvider;
+pub use types::{RetryPolicy, RouteAttempt, RouteReport, RouteRequirements, RouteTarget};
@@ -0,0 +1,266 @@
+//! Routed provider: priority list, retries and failover
+
+use super::types::{RetryPolicy, RouteAttempt, RouteReport, RouteRequirements, RouteTarget};
+use crate::ai::provider::{AIError, AIProvider, AIRequest, AIResponse, AIStream, ModelInfo};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Provider that spreads requests over a prioritized list of targets
+///
+/// Targets are tried in order, skipping those whose `ModelInfo` lacks a
+/// capability the request needs. Rate limits and outages are retried on the
+/// same target with exponential backoff (honouring `Retry-After`), then the
+/// next target is tried. Routing decisions are reported in the response
+/// metadata under `route.*` keys and via [`RoutedProvider::last_report`].
+pub struct RoutedProvider {
+    /// Display name
+    name: String,
+    /// Targets in priority order
+    targets: Vec<RouteTarget>,
+    /// Retry settings
+    policy: RetryPolicy,
+    /// Report of the most recent request
+    last_report: Mutex<Option<RouteReport>>,
+}
+
+impl RoutedProvider {
+    /// Create a router without targets
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            targets: Vec::new(),
+            policy: RetryPolicy::default(),
+            last_report: Mutex::new(None),
+        }
+    }
+
+    /// Append a target (lower priority than those already added)
+    pub fn with_target(mut self, provider: Arc<dyn AIProvider>, model: impl Into<String>) -> Self {
+        self.targets.push(RouteTarget::new(provider, model));
+        self
+    }
+
+    /// Set the retry policy
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Targets in priority order
+    pub fn targets(&self) -> &[RouteTarget] {
+        &self.targets
+    }
+
+    /// Report of the most recent request (including streams)
+    pub fn last_report(&self) -> Option<RouteReport> {
+        self.last_report.lock().clone()
+    }
+
+    /// Complete a request with explicit requirements (e.g. vision)
+    pub async fn complete_with(
+        &self,
+        request: AIRequest,
+        requirements: RouteRequirements,
+    ) -> Result<AIResponse, AIError> {
+        let (mut response, report) = self
+            .route(request, requirements, |provider, request| async move {
+                provider.complete(request).await
+            })
+            .await?;
+        response.metadata.extend(report.to_metadata());
+        Ok(response)
+    }
+
+    /// Stream a request with explicit requirements
+    ///
+    /// Failover only happens before the stream starts; errors inside the
+    /// stream are passed through.
+    pub async fn stream_with(
+        &self,
+        request: AIRequest,
+        requirements: RouteRequirements,
+    ) -> Result<AIStream, AIError> {
+        let (stream, _) = self
+            .route(request, requirements, |provider, request| async move {
+                provider.stream(request).await
+            })
+            .await?;
+        Ok(stream)
+    }
+
+    /// Targets in the order they will be tried
+    ///
+    /// A target whose model (or `provider/model` label) matches the request's
+    /// model goes first; the rest keep their configured order.
+    fn ordered_targets(&self, requested_model: &str) -> Vec<&RouteTarget> {
+        let mut ordered: Vec<&RouteTarget> = self.targets.iter().collect();
+        if let Some(pos) = ordered
+            .iter()
+            .position(|t| t.model == requested_model || t.label() == requested_model)
+        {
+            let preferred = ordered.remove(pos);
+            ordered.insert(0, preferred);
+        }
+        ordered
+    }
+
+    /// Delay before retrying after `error`, or `None` to fail over
+    fn retry_delay(&self, error: &AIError, attempt: u32) -> Option<Duration> {
+        if !error.is_retryable() || attempt >= self.policy.max_retries {
+            return None;
+        }
+        let delay = match error.retry_after() {
+            Some(delay) => delay,
+            None => {
+                let base = self.policy.backoff(attempt);
+                let jitter = self.policy.jitter.clamp(0.0, 1.0) * rand::random::<f64>();
+                base.mul_f64(1.0 + jitter).min(self.policy.max_backoff)
+            }
+        };
+        (delay <= self.policy.max_retry_after).then_some(delay)
+    }
+
+    async fn route<T, F, Fut>(
+        &self,
+        request: AIRequest,
+        requirements: RouteRequirements,
+        call: F,
+    ) -> Result<(T, RouteReport), AIError>
+    where
+        F: Fn(Arc<dyn AIProvider>, AIRequest) -> Fut,
+        Fut: Future<Output = Result<T, AIError>>,
+    {
+        self.validate_request(&request)?;
+
+        let mut report = RouteReport::default();
+        let mut last_error = None;
+
+        for (index, target) in self.ordered_targets(&request.model).into_iter().enumerate() {
+            if !target.provider.is_configured() {
+                report
+                    .skipped
+                    .push((target.label(), "not configured".to_string()));
+                continue;
+            }
+            if let Some(reason) = target
+                .model_info()
+                .and_then(|info| requirements.unmet_by(&info))
+            {
+                report.skipped.push((target.label(), reason));
+                continue;
+            }
+
+            let mut target_request = request.clone();
+            target_request.model = target.model.clone();
+
+            let mut attempt = 0;
+            loop {
+                match call(target.provider.clone(), target_request.clone()).await {
+                    Ok(value) => {
+                        report.served_by = Some(target.label());
+                        report.target_index = Some(index);
+                        if report.fell_back() {
+                            tracing::info!(
+                                "Routed request to {} after {} failed attempt(s)",
+                                target.label(),
+                                report.attempts.len()
+                            );
+                        }
+                        *self.last_report.lock() = Some(report.clone());
+                        return Ok((value, report));
+                    }
+                    Err(AIError::InvalidRequest(message)) => {
+                        // Malformed everywhere; no point in trying other targets
+                        *self.last_report.lock() = Some(report);
+                        return Err(AIError::InvalidRequest(message));
+                    }
+                    Err(error) => {
+                        let retry_in = self.retry_delay(&error, attempt);
+                        tracing::warn!("Route target {} failed: {}", target.label(), error);
+                        report.attempts.push(RouteAttempt {
+                            target: target.label(),
+                            error: error.to_string(),
+                            retry_in,
+                        });
+                        last_error = Some(error);
+
+                        match retry_in {
+                            Some(delay) => {
+                                tokio::time::sleep(delay).await;
+                                attempt += 1;
+                            }
+                            None => break,
+                        }
+                    }
+                }
+            }
+        }
+
+        let error = last_error.unwrap_or_else(|| {
+            let skipped: Vec<String> = report
+                .skipped
+                .iter()
+                .map(|(target, reason)| format!("{} ({})", target, reason))
+                .collect();
+            AIError::ModelNotAvailable(format!(
+                "no route target can serve the request: {}",
+                skipped.join(", ")
+            ))
+        });
+        *self.last_report.lock() = Some(report);
+        Err(error)
+    }
+}
+
+#[async_trait]
+impl AIProvider for RoutedProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn models(&self) -> Vec<ModelInfo> {
+        let mut models: Vec<ModelInfo> = Vec::new();
+        for info in self.targets.iter().filter_map(|t| t.model_info()) {
+            if !models.iter().any(|m| m.id == info.id) {
+                models.push(info);
+            }
+        }
+        models
+    }
+
+    fn default_model(&self) -> &str {
+        self.targets.first().map(|t| t.model.as_str()).unwrap_or("")
+    }
+
+    fn is_configured(&self) -> bool {
+        self.targets.iter().any(|t| t.provider.is_configured())
+    }
+
+    async fn complete(&self, request: AIRequest) -> Result<AIResponse, AIError> {
+        let requirements = RouteRequirements::for_request(&request, false);
+        self.complete_with(request, requirements).await
+    }
+
+    async fn stream(&self, request: AIRequest) -> Result<AIStream, AIError> {
+        let requirements = RouteRequirements::for_request(&request, true);
+        self.stream_with(request, requirements).await
+    }
+
+    fn count_tokens(&self, text: &str) -> usize {
+        match self.targets.first() {
+            Some(target) => target.provider.count_tokens(text),
+            None => text.len() / 4,
+        }
+    }
+
+    fn validate_request(&self, request: &AIRequest) -> Result<(), AIError> {
+        // The model is chosen per target, so only the messages are checked here
+        if request.messages.is_empty() {
+            return Err(AIError::InvalidRequest("No messages provided".to_string()));
+        }
+        Ok(())
+    }
+}
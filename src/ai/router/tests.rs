@@ -0,0 +1,293 @@
+//! Tests for provider routing
+
+use super::*;
+use crate::ai::provider::{
+    AIError, AIProvider, AIRequest, AIResponse, AIStream, Message, ModelInfo, ToolDefinition,
+};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Scripted outcome of one call
+enum Outcome {
+    Ok,
+    RateLimit(Option<Duration>),
+    Unavailable,
+    Auth,
+    Invalid,
+}
+
+struct ScriptedProvider {
+    name: String,
+    model: ModelInfo,
+    script: Mutex<VecDeque<Outcome>>,
+    calls: Mutex<Vec<String>>,
+}
+
+impl ScriptedProvider {
+    fn new(name: &str, model: &str, tools: bool, script: Vec<Outcome>) -> Arc<Self> {
+        Arc::new(Self {
+            name: name.to_string(),
+            model: ModelInfo {
+                id: model.to_string(),
+                name: model.to_string(),
+                provider: name.to_string(),
+                context_length: 8_000,
+                supports_streaming: true,
+                supports_tools: tools,
+                supports_vision: false,
+                input_cost_per_1k: None,
+                output_cost_per_1k: None,
+            },
+            script: Mutex::new(script.into()),
+            calls: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn calls(&self) -> usize {
+        self.calls.lock().len()
+    }
+}
+
+#[async_trait]
+impl AIProvider for ScriptedProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn models(&self) -> Vec<ModelInfo> {
+        vec![self.model.clone()]
+    }
+
+    fn default_model(&self) -> &str {
+        &self.model.id
+    }
+
+    fn is_configured(&self) -> bool {
+        true
+    }
+
+    async fn complete(&self, request: AIRequest) -> Result<AIResponse, AIError> {
+        self.calls.lock().push(request.model.clone());
+        match self.script.lock().pop_front().unwrap_or(Outcome::Ok) {
+            Outcome::Ok => Ok(AIResponse {
+                id: "resp".to_string(),
+                model: request.model,
+                content: format!("from {}", self.name),
+                stop_reason: None,
+                tool_calls: Vec::new(),
+                usage: None,
+                metadata: Default::default(),
+            }),
+            Outcome::RateLimit(retry_after) => Err(AIError::RateLimit { retry_after }),
+            Outcome::Unavailable => Err(AIError::Unavailable {
+                status: 529,
+                message: "overloaded".to_string(),
+                retry_after: None,
+            }),
+            Outcome::Auth => Err(AIError::Auth("Invalid API key".to_string())),
+            Outcome::Invalid => Err(AIError::InvalidRequest("bad".to_string())),
+        }
+    }
+
+    async fn stream(&self, _request: AIRequest) -> Result<AIStream, AIError> {
+        Err(AIError::Unavailable {
+            status: 503,
+            message: "down".to_string(),
+            retry_after: None,
+        })
+    }
+}
+
+fn fast_policy(max_retries: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_retries,
+        initial_backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_millis(5),
+        jitter: 0.0,
+        max_retry_after: Duration::from_millis(50),
+        ..Default::default()
+    }
+}
+
+fn request() -> AIRequest {
+    AIRequest {
+        messages: vec![Message::user("Hello")],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_backoff_grows_and_caps() {
+    let policy = RetryPolicy {
+        initial_backoff: Duration::from_millis(100),
+        max_backoff: Duration::from_millis(350),
+        ..Default::default()
+    };
+    assert_eq!(policy.backoff(0), Duration::from_millis(100));
+    assert_eq!(policy.backoff(1), Duration::from_millis(200));
+    assert_eq!(policy.backoff(2), Duration::from_millis(350));
+}
+
+#[tokio::test]
+async fn test_retries_same_target_on_rate_limit() {
+    let primary = ScriptedProvider::new(
+        "Primary",
+        "p-1",
+        true,
+        vec![
+            Outcome::RateLimit(Some(Duration::from_millis(1))),
+            Outcome::Ok,
+        ],
+    );
+    let secondary = ScriptedProvider::new("Secondary", "s-1", true, vec![]);
+    let router = RoutedProvider::new("Router")
+        .with_target(primary.clone(), "p-1")
+        .with_target(secondary.clone(), "s-1")
+        .with_retry_policy(fast_policy(2));
+
+    let response = router.complete(request()).await.unwrap();
+    assert_eq!(response.content, "from Primary");
+    assert_eq!(primary.calls(), 2);
+    assert_eq!(secondary.calls(), 0);
+    assert_eq!(response.metadata["route.served_by"], "Primary/p-1");
+    assert_eq!(response.metadata["route.fallback"], "false");
+    assert_eq!(response.metadata["route.retries"], "1");
+}
+
+#[tokio::test]
+async fn test_fails_over_after_retries() {
+    let primary = ScriptedProvider::new(
+        "Primary",
+        "p-1",
+        true,
+        vec![Outcome::Unavailable, Outcome::Unavailable],
+    );
+    let secondary = ScriptedProvider::new("Secondary", "s-1", true, vec![]);
+    let router = RoutedProvider::new("Router")
+        .with_target(primary.clone(), "p-1")
+        .with_target(secondary.clone(), "s-1")
+        .with_retry_policy(fast_policy(1));
+
+    let response = router.complete(request()).await.unwrap();
+    assert_eq!(response.content, "from Secondary");
+    assert_eq!(response.model, "s-1");
+    assert_eq!(primary.calls(), 2);
+    assert_eq!(response.metadata["route.fallback"], "true");
+    assert!(response.metadata["route.errors"].contains("Primary/p-1: Service unavailable"));
+
+    let report = router.last_report().unwrap();
+    assert_eq!(report.attempts.len(), 2);
+    assert_eq!(report.target_index, Some(1));
+}
+
+#[tokio::test]
+async fn test_long_retry_after_fails_over_immediately() {
+    let primary = ScriptedProvider::new(
+        "Primary",
+        "p-1",
+        true,
+        vec![Outcome::RateLimit(Some(Duration::from_secs(60)))],
+    );
+    let secondary = ScriptedProvider::new("Secondary", "s-1", true, vec![]);
+    let router = RoutedProvider::new("Router")
+        .with_target(primary.clone(), "p-1")
+        .with_target(secondary.clone(), "s-1")
+        .with_retry_policy(fast_policy(3));
+
+    let response = router.complete(request()).await.unwrap();
+    assert_eq!(response.content, "from Secondary");
+    assert_eq!(primary.calls(), 1);
+}
+
+#[tokio::test]
+async fn test_capability_routing() {
+    let no_tools = ScriptedProvider::new("Local", "small", false, vec![]);
+    let with_tools = ScriptedProvider::new("Cloud", "large", true, vec![]);
+    let router = RoutedProvider::new("Router")
+        .with_target(no_tools.clone(), "small")
+        .with_target(with_tools.clone(), "large");
+
+    let mut tool_request = request();
+    tool_request.tools = Some(vec![ToolDefinition {
+        name: "read_file".to_string(),
+        description: "Read a file".to_string(),
+        parameters: serde_json::json!({"type": "object"}),
+    }]);
+    let response = router.complete(tool_request).await.unwrap();
+    assert_eq!(response.content, "from Cloud");
+    assert_eq!(no_tools.calls(), 0);
+    assert_eq!(
+        response.metadata["route.skipped"],
+        "Local/small (no tool support)"
+    );
+
+    // Plain requests stay on the first target
+    let response = router.complete(request()).await.unwrap();
+    assert_eq!(response.content, "from Local");
+
+    // Nothing supports vision
+    let err = router
+        .complete_with(request(), RouteRequirements::default().with_vision())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AIError::ModelNotAvailable(_)));
+}
+
+#[tokio::test]
+async fn test_requested_model_goes_first() {
+    let first = ScriptedProvider::new("First", "a", true, vec![]);
+    let second = ScriptedProvider::new("Second", "b", true, vec![]);
+    let router = RoutedProvider::new("Router")
+        .with_target(first.clone(), "a")
+        .with_target(second.clone(), "b");
+
+    let mut req = request();
+    req.model = "b".to_string();
+    let response = router.complete(req).await.unwrap();
+    assert_eq!(response.content, "from Second");
+    assert_eq!(response.metadata["route.fallback"], "false");
+}
+
+#[tokio::test]
+async fn test_errors_when_all_targets_fail() {
+    let first = ScriptedProvider::new("First", "a", true, vec![Outcome::Auth]);
+    let second = ScriptedProvider::new("Second", "b", true, vec![Outcome::RateLimit(None)]);
+    let router = RoutedProvider::new("Router")
+        .with_target(first.clone(), "a")
+        .with_target(second.clone(), "b")
+        .with_retry_policy(RetryPolicy::no_retry());
+
+    let err = router.complete(request()).await.unwrap_err();
+    assert!(matches!(err, AIError::RateLimit { .. }));
+    assert_eq!(first.calls(), 1);
+    assert_eq!(router.last_report().unwrap().attempts.len(), 2);
+
+    // Invalid requests are not retried elsewhere
+    let invalid = ScriptedProvider::new("Invalid", "x", true, vec![Outcome::Invalid]);
+    let fallback = ScriptedProvider::new("Fallback", "y", true, vec![]);
+    let router = RoutedProvider::new("Router")
+        .with_target(invalid, "x")
+        .with_target(fallback.clone(), "y");
+    assert!(matches!(
+        router.complete(request()).await,
+        Err(AIError::InvalidRequest(_))
+    ));
+    assert_eq!(fallback.calls(), 0);
+}
+
+#[tokio::test]
+async fn test_stream_errors_before_start_fail_over() {
+    let first = ScriptedProvider::new("First", "a", true, vec![]);
+    let router = RoutedProvider::new("Router")
+        .with_target(first, "a")
+        .with_retry_policy(RetryPolicy::no_retry());
+
+    assert!(matches!(
+        router.stream(request()).await,
+        Err(AIError::Unavailable { status: 503, .. })
+    ));
+    assert_eq!(router.last_report().unwrap().attempts.len(), 1);
+}
@@ -0,0 +1,218 @@
+//! Type definitions for provider routing
+
+use crate::ai::provider::{AIProvider, AIRequest, ModelInfo};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A provider/model pair the router may send requests to
+#[derive(Clone)]
+pub struct RouteTarget {
+    /// Provider handling the request
+    pub provider: Arc<dyn AIProvider>,
+    /// Model to request from the provider
+    pub model: String,
+}
+
+impl RouteTarget {
+    /// Create a new target
+    pub fn new(provider: Arc<dyn AIProvider>, model: impl Into<String>) -> Self {
+        Self {
+            provider,
+            model: model.into(),
+        }
+    }
+
+    /// Display label, e.g. `Claude/claude-3-haiku-20240307`
+    pub fn label(&self) -> String {
+        format!("{}/{}", self.provider.name(), self.model)
+    }
+
+    /// Model information, if the provider lists the model
+    pub fn model_info(&self) -> Option<ModelInfo> {
+        self.provider
+            .models()
+            .into_iter()
+            .find(|m| m.id == self.model)
+    }
+}
+
+impl std::fmt::Debug for RouteTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RouteTarget")
+            .field("provider", &self.provider.name())
+            .field("model", &self.model)
+            .finish()
+    }
+}
+
+/// Retry and backoff settings, applied per target
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Retries on the same target before failing over
+    pub max_retries: u32,
+    /// Delay before the first retry
+    pub initial_backoff: Duration,
+    /// Upper bound for computed delays
+    pub max_backoff: Duration,
+    /// Backoff growth factor
+    pub multiplier: f64,
+    /// Random jitter as a fraction of the delay (0.0 - 1.0)
+    pub jitter: f64,
+    /// Fail over instead of waiting when `Retry-After` exceeds this
+    pub max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(20),
+            multiplier: 2.0,
+            jitter: 0.2,
+            max_retry_after: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy that never retries (fail over immediately)
+    pub fn no_retry() -> Self {
+        Self {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    /// Exponential delay before retry number `attempt` (0-based), without jitter
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = self.multiplier.max(1.0).powi(attempt as i32);
+        let delay = self.initial_backoff.as_secs_f64() * factor;
+        Duration::from_secs_f64(delay.min(self.max_backoff.as_secs_f64()))
+    }
+}
+
+/// Capabilities a request needs from a model
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RouteRequirements {
+    /// Tool use
+    pub tools: bool,
+    /// Image input
+    pub vision: bool,
+    /// Streaming responses
+    pub streaming: bool,
+    /// Minimum context window in tokens
+    pub min_context: usize,
+}
+
+impl RouteRequirements {
+    /// Requirements implied by a request
+    ///
+    /// Vision cannot be inferred from text messages; callers sending images
+    /// set it explicitly.
+    pub fn for_request(request: &AIRequest, streaming: bool) -> Self {
+        let text_len: usize = request
+            .messages
+            .iter()
+            .map(|m| m.content.len())
+            .chain(request.system.as_ref().map(|s| s.len()))
+            .sum();
+        Self {
+            tools: request.tools.as_ref().is_some_and(|t| !t.is_empty()),
+            vision: false,
+            streaming,
+            min_context: text_len / 4 + request.max_tokens.unwrap_or(0),
+        }
+    }
+
+    /// Require image input
+    pub fn with_vision(mut self) -> Self {
+        self.vision = true;
+        self
+    }
+
+    /// Why `model` cannot serve these requirements, if it cannot
+    pub fn unmet_by(&self, model: &ModelInfo) -> Option<String> {
+        if self.tools && !model.supports_tools {
+            Some("no tool support".to_string())
+        } else if self.vision && !model.supports_vision {
+            Some("no vision support".to_string())
+        } else if self.streaming && !model.supports_streaming {
+            Some("no streaming support".to_string())
+        } else if self.min_context > model.context_length {
+            Some(format!(
+                "context {} < {} tokens",
+                model.context_length, self.min_context
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+/// A failed call to a target
+#[derive(Debug, Clone)]
+pub struct RouteAttempt {
+    /// Target label
+    pub target: String,
+    /// Error message
+    pub error: String,
+    /// Delay before the next call (retry on the same target)
+    pub retry_in: Option<Duration>,
+}
+
+/// How a routed request was served
+#[derive(Debug, Clone, Default)]
+pub struct RouteReport {
+    /// Target that produced the response
+    pub served_by: Option<String>,
+    /// Index of that target in the priority list
+    pub target_index: Option<usize>,
+    /// Failed calls, in order
+    pub attempts: Vec<RouteAttempt>,
+    /// Targets skipped without a call, with the reason
+    pub skipped: Vec<(String, String)>,
+}
+
+impl RouteReport {
+    /// Whether a target other than the first choice served the request
+    pub fn fell_back(&self) -> bool {
+        self.target_index.is_some_and(|i| i > 0)
+    }
+
+    /// Number of retries on the same target
+    pub fn retries(&self) -> usize {
+        self.attempts
+            .iter()
+            .filter(|a| a.retry_in.is_some())
+            .count()
+    }
+
+    /// Metadata entries attached to routed responses
+    pub fn to_metadata(&self) -> HashMap<String, String> {
+        let mut metadata = HashMap::new();
+        if let Some(served_by) = &self.served_by {
+            metadata.insert("route.served_by".to_string(), served_by.clone());
+        }
+        metadata.insert("route.fallback".to_string(), self.fell_back().to_string());
+        metadata.insert("route.retries".to_string(), self.retries().to_string());
+        if !self.attempts.is_empty() {
+            let errors: Vec<String> = self
+                .attempts
+                .iter()
+                .map(|a| format!("{}: {}", a.target, a.error))
+                .collect();
+            metadata.insert("route.errors".to_string(), errors.join("; "));
+        }
+        if !self.skipped.is_empty() {
+            let skipped: Vec<String> = self
+                .skipped
+                .iter()
+                .map(|(target, reason)| format!("{} ({})", target, reason))
+                .collect();
+            metadata.insert("route.skipped".to_string(), skipped.join("; "));
+        }
+        metadata
+    }
+}
@@ -0,0 +1,105 @@
+//! On-disk cache for fetched pages
+
+use super::types::WebFetchError;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// A cached page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CacheEntry {
+    /// Requested URL
+    pub url: String,
+    /// Entity tag used for revalidation
+    pub etag: Option<String>,
+    /// `Last-Modified` header used for revalidation
+    pub last_modified: Option<String>,
+    /// Page title
+    pub title: Option<String>,
+    /// Sanitized markdown (full length, truncated per request)
+    pub markdown: String,
+    /// When the content was downloaded
+    pub fetched_at: chrono::DateTime<chrono::Utc>,
+    /// When the entry must be revalidated
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl CacheEntry {
+    /// Whether the entry can be served without contacting the server
+    pub fn is_fresh(&self) -> bool {
+        chrono::Utc::now() < self.expires_at
+    }
+}
+
+/// Disk cache keyed by URL
+pub struct WebCache {
+    dir: PathBuf,
+}
+
+impl WebCache {
+    /// Create a cache rooted at `dir`
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Default cache directory (`<cache>/claude-visual/web`)
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::cache_dir().map(|d| d.join("claude-visual").join("web"))
+    }
+
+    /// Cache directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn entry_path(&self, url: &str) -> PathBuf {
+        let hash = Sha256::digest(url.as_bytes());
+        self.dir.join(format!("{}.json", hex::encode(hash)))
+    }
+
+    /// Load the entry for a URL, ignoring unreadable files
+    pub(crate) fn get(&self, url: &str) -> Option<CacheEntry> {
+        let data = std::fs::read_to_string(self.entry_path(url)).ok()?;
+        match serde_json::from_str::<CacheEntry>(&data) {
+            Ok(entry) if entry.url == url => Some(entry),
+            Ok(_) => None,
+            Err(e) => {
+                tracing::debug!("Discarding corrupt web cache entry for {}: {}", url, e);
+                None
+            }
+        }
+    }
+
+    /// Store an entry
+    pub(crate) fn put(&self, entry: &CacheEntry) -> Result<(), WebFetchError> {
+        std::fs::create_dir_all(&self.dir).map_err(|e| WebFetchError::Cache(e.to_string()))?;
+        let data = serde_json::to_string(entry).map_err(|e| WebFetchError::Cache(e.to_string()))?;
+
+        // Write to a temporary file first so readers never see a partial entry
+        let path = self.entry_path(&entry.url);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, data).map_err(|e| WebFetchError::Cache(e.to_string()))?;
+        std::fs::rename(&tmp, &path).map_err(|e| WebFetchError::Cache(e.to_string()))
+    }
+
+    /// Remove the entry for a URL
+    pub fn remove(&self, url: &str) -> bool {
+        std::fs::remove_file(self.entry_path(url)).is_ok()
+    }
+
+    /// Remove all cached pages
+    pub fn clear(&self) -> Result<(), WebFetchError> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(WebFetchError::Cache(e.to_string())),
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|e| e == "json") {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+        Ok(())
+    }
+}
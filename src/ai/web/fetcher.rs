@@ -0,0 +1,346 @@
+//! Web page fetcher
+
+use super::cache::{CacheEntry, WebCache};
+use super::sanitize::{extract_title, html_to_markdown, truncate_to_tokens};
+use super::types::{WebFetchConfig, WebFetchError, WebPage};
+use crate::ai::context::ContextItem;
+use crate::ai::mention::{Mention, MentionKind};
+use futures::channel::oneshot;
+use futures::StreamExt;
+use reqwest::header::{
+    CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use reqwest::StatusCode;
+use std::future::Future;
+
+/// Fetches web pages for `@url` mentions
+pub struct WebFetcher {
+    /// HTTP client
+    client: reqwest::Client,
+    /// Configuration
+    config: WebFetchConfig,
+    /// Disk cache (disabled when no cache directory is available)
+    cache: Option<WebCache>,
+}
+
+impl WebFetcher {
+    /// Create a fetcher using the default cache directory
+    pub fn new(config: WebFetchConfig) -> Self {
+        let cache = WebCache::default_dir().map(WebCache::new);
+        Self::with_cache(config, cache)
+    }
+
+    /// Create a fetcher with an explicit cache (or none)
+    pub fn with_cache(config: WebFetchConfig, cache: Option<WebCache>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(config.timeout_secs))
+            .user_agent(config.user_agent.clone())
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            client,
+            config,
+            cache,
+        }
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &WebFetchConfig {
+        &self.config
+    }
+
+    /// Get the cache, if enabled
+    pub fn cache(&self) -> Option<&WebCache> {
+        self.cache.as_ref()
+    }
+
+    /// Fetch a page, serving from or revalidating against the cache
+    pub async fn fetch(&self, url: &str) -> Result<WebPage, WebFetchError> {
+        let parsed = parse_url(url)?;
+        let cached = self.cache.as_ref().and_then(|c| c.get(url));
+
+        if let Some(entry) = cached.as_ref().filter(|e| e.is_fresh()) {
+            return Ok(page_from_entry(entry.clone(), true));
+        }
+
+        let mut request = self.client.get(parsed.clone());
+        if let Some(entry) = &cached {
+            if let Some(etag) = &entry.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(modified) = &entry.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, modified);
+            }
+        }
+
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => {
+                // Serve stale content rather than failing when offline
+                if let Some(entry) = cached {
+                    tracing::warn!("Fetching {} failed, using stale cache: {}", url, e);
+                    return Ok(page_from_entry(entry, true));
+                }
+                return Err(WebFetchError::Network(e.to_string()));
+            }
+        };
+
+        let headers = response.headers().clone();
+        let max_age = headers
+            .get(CACHE_CONTROL)
+            .and_then(|v| v.to_str().ok())
+            .map(parse_cache_control)
+            .unwrap_or(CacheDirective::Default);
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some(mut entry) = cached {
+                entry.expires_at = self.expiry(max_age);
+                if let Some(etag) = header_string(&headers, ETAG) {
+                    entry.etag = Some(etag);
+                }
+                self.store(&entry, max_age);
+                return Ok(page_from_entry(entry, true));
+            }
+        }
+
+        if response.status().is_server_error() {
+            if let Some(entry) = cached {
+                tracing::warn!(
+                    "Fetching {} returned {}, using stale cache",
+                    url,
+                    response.status()
+                );
+                return Ok(page_from_entry(entry, true));
+            }
+        }
+
+        if !response.status().is_success() {
+            return Err(WebFetchError::HttpStatus {
+                status: response.status().as_u16(),
+                url: url.to_string(),
+            });
+        }
+
+        if let Some(len) = response.content_length() {
+            if len as usize > self.config.max_bytes {
+                return Err(WebFetchError::TooLarge {
+                    size: len as usize,
+                    max: self.config.max_bytes,
+                });
+            }
+        }
+
+        let content_type = header_string(&headers, CONTENT_TYPE)
+            .unwrap_or_else(|| "text/html".to_string())
+            .to_ascii_lowercase();
+        let is_html = content_type.contains("html");
+        if !is_html && !content_type.starts_with("text/") && !content_type.contains("json") {
+            return Err(WebFetchError::UnsupportedContent(content_type));
+        }
+
+        // Stop reading once the limit is passed; the length header is optional
+        // and not binding
+        let mut body = Vec::new();
+        let mut chunks = response.bytes_stream();
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk.map_err(|e| WebFetchError::Network(e.to_string()))?;
+            if body.len() + chunk.len() > self.config.max_bytes {
+                return Err(WebFetchError::TooLarge {
+                    size: body.len() + chunk.len(),
+                    max: self.config.max_bytes,
+                });
+            }
+            body.extend_from_slice(&chunk);
+        }
+        let body = String::from_utf8_lossy(&body);
+
+        let (title, markdown) = if is_html {
+            (extract_title(&body), html_to_markdown(&body, Some(&parsed)))
+        } else {
+            (None, body.trim().to_string())
+        };
+
+        let entry = CacheEntry {
+            url: url.to_string(),
+            etag: header_string(&headers, ETAG),
+            last_modified: header_string(&headers, LAST_MODIFIED),
+            title,
+            markdown,
+            fetched_at: chrono::Utc::now(),
+            expires_at: self.expiry(max_age),
+        };
+        self.store(&entry, max_age);
+
+        Ok(page_from_entry(entry, false))
+    }
+
+    /// Fetch a page as a `Web` context item capped to the configured budget
+    pub async fn fetch_context_item(&self, url: &str) -> Result<ContextItem, WebFetchError> {
+        let page = self.fetch(url).await?;
+        Ok(self.to_context_item(page))
+    }
+
+    /// Fetch every `@url` mention in order
+    pub async fn fetch_mentions(
+        &self,
+        mentions: &[Mention],
+    ) -> Vec<(String, Result<ContextItem, WebFetchError>)> {
+        let mut results = Vec::new();
+        for mention in mentions {
+            if let MentionKind::Url(url) = &mention.kind {
+                if results.iter().any(|(u, _)| u == url) {
+                    continue;
+                }
+                let item = self.fetch_context_item(url).await;
+                results.push((url.clone(), item));
+            }
+        }
+        results
+    }
+
+    /// Fetch `@url` mentions on a thread with its own runtime
+    ///
+    /// For callers that are not running inside tokio, such as the UI. URLs
+    /// that could not be fetched come back as network errors.
+    pub fn fetch_mentions_in_background(
+        self,
+        mentions: Vec<Mention>,
+    ) -> impl Future<Output = Vec<(String, Result<ContextItem, WebFetchError>)>> + Send + 'static
+    {
+        let mut urls: Vec<String> = Vec::new();
+        for mention in &mentions {
+            if let MentionKind::Url(url) = &mention.kind {
+                if !urls.contains(url) {
+                    urls.push(url.clone());
+                }
+            }
+        }
+
+        let (tx, rx) = oneshot::channel();
+        let spawned = std::thread::Builder::new()
+            .name("web-fetch".into())
+            .spawn(move || {
+                let runtime = match tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                {
+                    Ok(runtime) => runtime,
+                    Err(e) => {
+                        tracing::warn!("Failed to start web fetch runtime: {}", e);
+                        return;
+                    }
+                };
+                let _ = tx.send(runtime.block_on(self.fetch_mentions(&mentions)));
+            });
+        if let Err(e) = &spawned {
+            tracing::warn!("Failed to spawn web fetch thread: {}", e);
+        }
+        async move {
+            rx.await.unwrap_or_else(|_| {
+                urls.into_iter()
+                    .map(|url| {
+                        let error = WebFetchError::Network("Web fetch was interrupted".into());
+                        (url, Err(error))
+                    })
+                    .collect()
+            })
+        }
+    }
+
+    /// Build a context item from a fetched page
+    pub fn to_context_item(&self, page: WebPage) -> ContextItem {
+        let (content, truncated) = truncate_to_tokens(&page.markdown, self.config.max_tokens);
+        let mut item = ContextItem::web(&page.url, content).with_language("markdown");
+
+        if let Some(title) = &page.title {
+            item.name = title.clone();
+            item.metadata.insert("title".to_string(), title.clone());
+        }
+        item.metadata
+            .insert("fetched_at".to_string(), page.fetched_at.to_rfc3339());
+        item.metadata
+            .insert("truncated".to_string(), truncated.to_string());
+        if page.from_cache {
+            item.metadata
+                .insert("from_cache".to_string(), "true".to_string());
+        }
+        item
+    }
+
+    fn expiry(&self, directive: CacheDirective) -> chrono::DateTime<chrono::Utc> {
+        let secs = match directive {
+            CacheDirective::MaxAge(secs) => secs,
+            CacheDirective::NoCache | CacheDirective::NoStore => 0,
+            CacheDirective::Default => self.config.default_ttl_secs,
+        };
+        chrono::Utc::now() + chrono::Duration::seconds(secs)
+    }
+
+    fn store(&self, entry: &CacheEntry, directive: CacheDirective) {
+        if directive == CacheDirective::NoStore {
+            return;
+        }
+        if let Some(cache) = &self.cache {
+            if let Err(e) = cache.put(entry) {
+                tracing::warn!("Failed to cache {}: {}", entry.url, e);
+            }
+        }
+    }
+}
+
+/// Caching instruction from a `Cache-Control` header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CacheDirective {
+    MaxAge(i64),
+    NoCache,
+    NoStore,
+    Default,
+}
+
+fn parse_cache_control(value: &str) -> CacheDirective {
+    let mut directive = CacheDirective::Default;
+    for part in value.split(',').map(|p| p.trim().to_ascii_lowercase()) {
+        if part == "no-store" {
+            return CacheDirective::NoStore;
+        } else if part == "no-cache" {
+            directive = CacheDirective::NoCache;
+        } else if let Some(secs) = part.strip_prefix("max-age=") {
+            if directive == CacheDirective::Default {
+                if let Ok(secs) = secs.trim_matches('"').parse() {
+                    directive = CacheDirective::MaxAge(secs);
+                }
+            }
+        }
+    }
+    directive
+}
+
+fn parse_url(url: &str) -> Result<reqwest::Url, WebFetchError> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| WebFetchError::InvalidUrl(e.to_string()))?;
+    match parsed.scheme() {
+        "http" | "https" => Ok(parsed),
+        other => Err(WebFetchError::UnsupportedScheme(other.to_string())),
+    }
+}
+
+fn header_string(
+    headers: &reqwest::header::HeaderMap,
+    name: reqwest::header::HeaderName,
+) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string())
+}
+
+fn page_from_entry(entry: CacheEntry, from_cache: bool) -> WebPage {
+    WebPage {
+        url: entry.url,
+        title: entry.title,
+        markdown: entry.markdown,
+        etag: entry.etag,
+        fetched_at: entry.fetched_at,
+        from_cache,
+    }
+}
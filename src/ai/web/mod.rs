@@ -0,0 +1,17 @@
+//! Web Context Fetching
+//!
+//! Resolves `@url:` mentions into `Web` context items: pages are downloaded,
+//! reduced to readable markdown, capped to a token budget and cached on disk.
+
+mod cache;
+mod fetcher;
+mod sanitize;
+mod types;
+
+#[cfg(test)]
+mod tests;
+
+pub use cache::WebCache;
+pub use fetcher::WebFetcher;
+pub use sanitize::{extract_title, html_to_markdown, truncate_to_tokens};
+pub use types::{WebFetchConfig, WebFetchError, WebPage};
@@ -0,0 +1,552 @@
+//! HTML to readable markdown conversion
+//!
+//! A small tolerant tokenizer rather than a full HTML parser: it only needs to
+//! keep the readable text of a page and drop scripts, navigation and other
+//! boilerplate before the content is handed to the model.
+
+use crate::ai::context::estimate_tokens;
+
+/// Elements whose whole subtree is dropped
+const SKIPPED_TAGS: &[&str] = &[
+    "script", "style", "noscript", "nav", "header", "footer", "aside", "form", "svg", "iframe",
+    "template", "button", "select", "canvas", "dialog",
+];
+
+/// Elements that never have a closing tag
+const VOID_TAGS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Class/id/role fragments that mark boilerplate containers
+const BOILERPLATE_MARKERS: &[&str] = &[
+    "navigation",
+    "navbar",
+    "sidebar",
+    "cookie",
+    "banner",
+    "footer",
+    "breadcrumb",
+    "advert",
+    "newsletter",
+    "comments",
+];
+
+/// A parsed tag
+struct Tag {
+    name: String,
+    closing: bool,
+    self_closing: bool,
+    attrs: Vec<(String, String)>,
+}
+
+impl Tag {
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn is_boilerplate(&self) -> bool {
+        ["class", "id", "role"].iter().any(|key| {
+            self.attr(key).is_some_and(|value| {
+                let value = value.to_ascii_lowercase();
+                BOILERPLATE_MARKERS.iter().any(|m| value.contains(m))
+            })
+        }) || self.attr("aria-hidden") == Some("true")
+            || self.attrs.iter().any(|(k, _)| k == "hidden")
+    }
+}
+
+/// Markdown writer state
+struct Writer<'a> {
+    out: String,
+    base_url: Option<&'a reqwest::Url>,
+    /// Name and nesting depth of the element being skipped
+    skip: Option<(String, usize)>,
+    pre_depth: usize,
+    /// Ordered-list counters (`None` for bullet lists)
+    lists: Vec<Option<usize>>,
+    /// Open links: output offset and target
+    links: Vec<(usize, Option<String>)>,
+}
+
+impl<'a> Writer<'a> {
+    fn new(base_url: Option<&'a reqwest::Url>) -> Self {
+        Self {
+            out: String::new(),
+            base_url,
+            skip: None,
+            pre_depth: 0,
+            lists: Vec::new(),
+            links: Vec::new(),
+        }
+    }
+
+    /// Ensure the output ends with at least `count` newlines
+    fn break_lines(&mut self, count: usize) {
+        if self.out.is_empty() {
+            return;
+        }
+        let trailing = self.out.len() - self.out.trim_end_matches('\n').len();
+        for _ in trailing..count {
+            self.out.push('\n');
+        }
+    }
+
+    fn text(&mut self, raw: &str) {
+        let text = decode_entities(raw);
+        if self.pre_depth > 0 {
+            self.out.push_str(&text);
+            return;
+        }
+
+        let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if collapsed.is_empty() {
+            if text.chars().any(char::is_whitespace) && !self.out.ends_with([' ', '\n']) {
+                self.out.push(' ');
+            }
+            return;
+        }
+        if text.starts_with(char::is_whitespace) && !self.out.ends_with([' ', '\n']) {
+            self.out.push(' ');
+        }
+        self.out.push_str(&collapsed);
+        if text.ends_with(char::is_whitespace) {
+            self.out.push(' ');
+        }
+    }
+
+    fn tag(&mut self, tag: Tag) {
+        // Inside a skipped subtree only track nesting of the same element
+        if let Some((name, depth)) = &mut self.skip {
+            if tag.name == *name && !tag.self_closing {
+                if tag.closing {
+                    *depth -= 1;
+                    if *depth == 0 {
+                        self.skip = None;
+                    }
+                } else {
+                    *depth += 1;
+                }
+            }
+            return;
+        }
+
+        let is_void = VOID_TAGS.contains(&tag.name.as_str());
+        if !tag.closing
+            && !tag.self_closing
+            && !is_void
+            && (SKIPPED_TAGS.contains(&tag.name.as_str()) || tag.is_boilerplate())
+        {
+            self.skip = Some((tag.name, 1));
+            return;
+        }
+
+        match (tag.name.as_str(), tag.closing) {
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", false) => {
+                self.break_lines(2);
+                let level = tag.name[1..].parse::<usize>().unwrap_or(1);
+                self.out.push_str(&"#".repeat(level));
+                self.out.push(' ');
+            }
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", true) => self.break_lines(2),
+            ("p" | "div" | "section" | "article" | "main" | "table" | "blockquote", _) => {
+                self.break_lines(2)
+            }
+            ("tr", true) | ("dt", true) | ("dd", true) => self.break_lines(1),
+            ("td" | "th", true) => self.out.push_str(" | "),
+            ("br", _) => self.out.push('\n'),
+            ("hr", _) => {
+                self.break_lines(2);
+                self.out.push_str("---");
+                self.break_lines(2);
+            }
+            ("ul", false) => {
+                self.break_lines(1);
+                self.lists.push(None);
+            }
+            ("ol", false) => {
+                self.break_lines(1);
+                self.lists.push(Some(0));
+            }
+            ("ul" | "ol", true) => {
+                self.lists.pop();
+                self.break_lines(if self.lists.is_empty() { 2 } else { 1 });
+            }
+            ("li", false) => {
+                self.break_lines(1);
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                self.out.push_str(&indent);
+                match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        *n += 1;
+                        let marker = format!("{}. ", n);
+                        self.out.push_str(&marker);
+                    }
+                    _ => self.out.push_str("- "),
+                }
+            }
+            ("pre", false) => {
+                self.break_lines(2);
+                self.out.push_str("```\n");
+                self.pre_depth += 1;
+            }
+            ("pre", true) if self.pre_depth > 0 => {
+                self.pre_depth -= 1;
+                if !self.out.ends_with('\n') {
+                    self.out.push('\n');
+                }
+                self.out.push_str("```");
+                self.break_lines(2);
+            }
+            ("code", _) if self.pre_depth == 0 => self.out.push('`'),
+            ("strong" | "b", _) => self.out.push_str("**"),
+            ("em" | "i", _) => self.out.push('_'),
+            ("a", false) => {
+                let href = tag.attr("href").and_then(|h| self.resolve_href(h));
+                self.links.push((self.out.len(), href));
+            }
+            ("a", true) => {
+                if let Some((start, Some(href))) = self.links.pop() {
+                    if start <= self.out.len() {
+                        let text = self.out[start..].trim().to_string();
+                        if !text.is_empty() {
+                            self.out.truncate(start);
+                            self.out.push_str(&format!("[{}]({})", text, href));
+                        }
+                    }
+                }
+            }
+            ("img", _) => {
+                if let Some(alt) = tag.attr("alt").filter(|a| !a.trim().is_empty()) {
+                    self.out.push_str(&format!("[image: {}]", alt.trim()));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn resolve_href(&self, href: &str) -> Option<String> {
+        let href = decode_entities(href.trim());
+        if href.is_empty() || href.starts_with('#') || href.starts_with("javascript:") {
+            return None;
+        }
+        match self.base_url {
+            Some(base) => base.join(&href).ok().map(|u| u.to_string()),
+            None => Some(href),
+        }
+    }
+
+    fn finish(self) -> String {
+        normalize_blank_lines(&self.out)
+    }
+}
+
+/// Convert an HTML document into readable markdown
+///
+/// Only the `<main>`/`<article>` region is used when the page has one.
+/// Relative links are resolved against `base_url` when given.
+pub fn html_to_markdown(html: &str, base_url: Option<&reqwest::Url>) -> String {
+    let region = main_region(html);
+    let mut writer = Writer::new(base_url);
+
+    let mut rest = region;
+    while !rest.is_empty() {
+        let Some(lt) = rest.find('<') else {
+            if writer.skip.is_none() {
+                writer.text(rest);
+            }
+            break;
+        };
+        if lt > 0 && writer.skip.is_none() {
+            writer.text(&rest[..lt]);
+        }
+        rest = &rest[lt..];
+
+        if let Some(after) = rest.strip_prefix("<!--") {
+            rest = after.find("-->").map(|i| &after[i + 3..]).unwrap_or("");
+            continue;
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map(|i| &rest[i + 1..]).unwrap_or("");
+            continue;
+        }
+
+        match parse_tag(rest) {
+            Some((tag, len)) => {
+                rest = &rest[len..];
+                // Raw text elements: jump straight to the closing tag
+                if !tag.closing && matches!(tag.name.as_str(), "script" | "style") {
+                    let close = format!("</{}", tag.name);
+                    rest = find_ignore_case(rest, &close)
+                        .map(|i| &rest[i..])
+                        .unwrap_or("");
+                    if writer.skip.is_none() {
+                        writer.skip = Some((tag.name, 1));
+                    }
+                    continue;
+                }
+                writer.tag(tag);
+            }
+            None => {
+                if writer.skip.is_none() {
+                    writer.text("<");
+                }
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    writer.finish()
+}
+
+/// Extract the page title from `<title>` or the first `<h1>`
+pub fn extract_title(html: &str) -> Option<String> {
+    ["title", "h1"].iter().find_map(|name| {
+        let open = find_ignore_case(html, &format!("<{}", name))?;
+        let body_start = open + html[open..].find('>')? + 1;
+        let body_end = body_start + find_ignore_case(&html[body_start..], &format!("</{}", name))?;
+        let text = html_to_markdown(&html[body_start..body_end], None);
+        let text = text
+            .trim_matches(|c: char| c == '#' || c.is_whitespace())
+            .to_string();
+        (!text.is_empty()).then_some(text)
+    })
+}
+
+/// Cap content to a token budget
+///
+/// Cuts on a paragraph boundary when one is reasonably close and appends a
+/// marker. Returns the content and whether it was truncated.
+pub fn truncate_to_tokens(content: &str, max_tokens: usize) -> (String, bool) {
+    if estimate_tokens(content) <= max_tokens {
+        return (content.to_string(), false);
+    }
+
+    let max_chars = max_tokens * 4;
+    let mut cut = max_chars.min(content.len());
+    while !content.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    if let Some(para) = content[..cut].rfind("\n\n") {
+        if para >= cut / 2 {
+            cut = para;
+        }
+    }
+
+    let mut truncated = content[..cut].trim_end().to_string();
+    truncated.push_str("\n\n[… content truncated to fit the context budget]");
+    (truncated, true)
+}
+
+/// Narrow the document to `<main>`, `<article>` or `<body>`
+fn main_region(html: &str) -> &str {
+    for name in ["main", "article", "body"] {
+        let Some(open) = find_ignore_case(html, &format!("<{}", name)) else {
+            continue;
+        };
+        // Make sure we matched `<main>` and not `<mainframe>`
+        let after = html[open + name.len() + 1..].chars().next();
+        if !matches!(
+            after,
+            Some('>') | Some(' ') | Some('\t') | Some('\n') | Some('\r')
+        ) {
+            continue;
+        }
+        let Some(gt) = html[open..].find('>') else {
+            continue;
+        };
+        let start = open + gt + 1;
+        let end = rfind_ignore_case(html, &format!("</{}", name))
+            .filter(|&e| e >= start)
+            .unwrap_or(html.len());
+        return &html[start..end];
+    }
+    html
+}
+
+/// Parse a tag at the start of `input` (which begins with `<`)
+///
+/// Returns the tag and the number of bytes it spans.
+fn parse_tag(input: &str) -> Option<(Tag, usize)> {
+    let bytes = input.as_bytes();
+    let mut i = 1;
+    let closing = bytes.get(i) == Some(&b'/');
+    if closing {
+        i += 1;
+    }
+
+    let name_start = i;
+    while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'-') {
+        i += 1;
+    }
+    if i == name_start || !bytes[name_start].is_ascii_alphabetic() {
+        return None;
+    }
+    let name = input[name_start..i].to_ascii_lowercase();
+
+    let mut attrs = Vec::new();
+    let mut self_closing = false;
+    loop {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        match bytes.get(i)? {
+            b'>' => {
+                i += 1;
+                break;
+            }
+            b'/' => {
+                self_closing = true;
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+
+        let key_start = i;
+        while i < bytes.len()
+            && !matches!(bytes[i], b'=' | b'>' | b'/')
+            && !bytes[i].is_ascii_whitespace()
+        {
+            i += 1;
+        }
+        let key = input[key_start..i].to_ascii_lowercase();
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+
+        let mut value = String::new();
+        if bytes.get(i) == Some(&b'=') {
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            match bytes.get(i)? {
+                quote @ (b'"' | b'\'') => {
+                    let end = i + 1 + input[i + 1..].find(*quote as char)?;
+                    value = input[i + 1..end].to_string();
+                    i = end + 1;
+                }
+                _ => {
+                    let value_start = i;
+                    while i < bytes.len() && bytes[i] != b'>' && !bytes[i].is_ascii_whitespace() {
+                        i += 1;
+                    }
+                    value = input[value_start..i].to_string();
+                }
+            }
+        }
+        if !key.is_empty() {
+            attrs.push((key, value));
+        }
+    }
+
+    Some((
+        Tag {
+            name,
+            closing,
+            self_closing,
+            attrs,
+        },
+        i,
+    ))
+}
+
+/// Decode the common named and numeric HTML entities
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .and_then(|end| {
+                let entity = &rest[1..end + 1];
+                let ch = match entity {
+                    "amp" => Some('&'),
+                    "lt" => Some('<'),
+                    "gt" => Some('>'),
+                    "quot" => Some('"'),
+                    "apos" => Some('\''),
+                    "nbsp" => Some(' '),
+                    "mdash" => Some('—'),
+                    "ndash" => Some('–'),
+                    "hellip" => Some('…'),
+                    "copy" => Some('©'),
+                    _ => {
+                        let code = if let Some(hex) = entity
+                            .strip_prefix("#x")
+                            .or_else(|| entity.strip_prefix("#X"))
+                        {
+                            u32::from_str_radix(hex, 16).ok()
+                        } else {
+                            entity.strip_prefix('#').and_then(|d| d.parse().ok())
+                        };
+                        code.and_then(char::from_u32)
+                    }
+                };
+                ch.map(|c| (c, end + 2))
+            });
+
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Trim trailing spaces and collapse runs of blank lines
+fn normalize_blank_lines(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut blank_run = 0;
+    let mut in_fence = false;
+
+    for line in text.lines() {
+        let line = if in_fence { line } else { line.trim() };
+        if line.starts_with("```") {
+            in_fence = !in_fence;
+        }
+        if line.trim().is_empty() && !in_fence {
+            blank_run += 1;
+            if blank_run > 1 {
+                continue;
+            }
+        } else {
+            blank_run = 0;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+
+    out.trim().to_string()
+}
+
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .to_ascii_lowercase()
+        .find(&needle.to_ascii_lowercase())
+}
+
+fn rfind_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .to_ascii_lowercase()
+        .rfind(&needle.to_ascii_lowercase())
+}
@@ -0,0 +1,256 @@
+//! Tests for web fetching
+
+use super::*;
+use crate::ai::context::ContextItemType;
+use crate::ai::mock_http::{http_response, MockServer};
+use std::io::{Read, Write};
+use std::net::TcpListener;
+
+fn temp_cache() -> WebCache {
+    WebCache::new(
+        std::env::temp_dir().join(format!("claude_visual_web_test_{}", uuid::Uuid::new_v4())),
+    )
+}
+
+const PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head><title>Guide &amp; Notes</title><script>var tracking = 1;</script></head>
+<body>
+<nav><a href="/">Home</a> | <a href="/about">About</a></nav>
+<main>
+<h1>Getting started</h1>
+<p>Install the <code>tool</code> and read the <a href="/docs/setup">setup guide</a>.</p>
+<ul><li>First</li><li>Second</li></ul>
+<pre>fn main() {
+    println!("hi");
+}</pre>
+<div class="cookie-banner">Accept cookies</div>
+</main>
+<footer>Copyright</footer>
+</body>
+</html>"#;
+
+#[test]
+fn test_html_to_markdown_strips_boilerplate() {
+    let base = reqwest::Url::parse("https://example.com/guide").unwrap();
+    let markdown = html_to_markdown(PAGE, Some(&base));
+
+    assert!(markdown.starts_with("# Getting started"));
+    assert!(markdown.contains("[setup guide](https://example.com/docs/setup)"));
+    assert!(markdown.contains("`tool`"));
+    assert!(markdown.contains("- First\n- Second"));
+    assert!(markdown.contains("```\nfn main() {\n    println!(\"hi\");\n}\n```"));
+    assert!(!markdown.contains("tracking"));
+    assert!(!markdown.contains("About"));
+    assert!(!markdown.contains("cookies"));
+    assert!(!markdown.contains("Copyright"));
+}
+
+#[test]
+fn test_extract_title() {
+    assert_eq!(extract_title(PAGE), Some("Guide & Notes".to_string()));
+    assert_eq!(
+        extract_title("<h1>Only <em>heading</em></h1>"),
+        Some("Only _heading_".to_string())
+    );
+    assert_eq!(extract_title("<p>none</p>"), None);
+}
+
+#[test]
+fn test_truncate_to_tokens() {
+    let short = "small page";
+    assert_eq!(truncate_to_tokens(short, 100), (short.to_string(), false));
+
+    let long = format!("{}\n\n{}", "a".repeat(300), "b".repeat(300));
+    let (truncated, was_truncated) = truncate_to_tokens(&long, 100);
+    assert!(was_truncated);
+    assert!(truncated.starts_with(&"a".repeat(300)));
+    assert!(!truncated.contains("bbb"));
+    assert!(truncated.ends_with("budget]"));
+}
+
+#[tokio::test]
+async fn test_fetch_context_item() {
+    let server = MockServer::start(vec![http_response(
+        "200 OK",
+        &[("Content-Type", "text/html; charset=utf-8")],
+        PAGE,
+    )]);
+    let cache = temp_cache();
+    let cache_dir = cache.dir().to_path_buf();
+    let fetcher = WebFetcher::with_cache(WebFetchConfig::default(), Some(cache));
+
+    let url = format!("{}/guide", server.url);
+    let item = fetcher.fetch_context_item(&url).await.unwrap();
+
+    assert_eq!(item.item_type, ContextItemType::Web);
+    assert_eq!(item.name, "Guide & Notes");
+    assert_eq!(item.metadata.get("url"), Some(&url));
+    assert_eq!(
+        item.metadata.get("truncated").map(|s| s.as_str()),
+        Some("false")
+    );
+    assert!(item
+        .content
+        .contains(&format!("[setup guide]({}/docs/setup)", server.url)));
+
+    let _ = std::fs::remove_dir_all(&cache_dir);
+}
+
+#[tokio::test]
+async fn test_fresh_cache_skips_network() {
+    let server = MockServer::start(vec![http_response(
+        "200 OK",
+        &[
+            ("Content-Type", "text/html"),
+            ("Cache-Control", "max-age=3600"),
+        ],
+        PAGE,
+    )]);
+    let cache = temp_cache();
+    let cache_dir = cache.dir().to_path_buf();
+    let fetcher = WebFetcher::with_cache(WebFetchConfig::default(), Some(cache));
+    let url = format!("{}/guide", server.url);
+
+    let first = fetcher.fetch(&url).await.unwrap();
+    let second = fetcher.fetch(&url).await.unwrap();
+
+    assert!(!first.from_cache);
+    assert!(second.from_cache);
+    assert_eq!(first.markdown, second.markdown);
+    assert_eq!(server.request_count(), 1);
+
+    let _ = std::fs::remove_dir_all(&cache_dir);
+}
+
+#[tokio::test]
+async fn test_etag_revalidation() {
+    let server = MockServer::start(vec![
+        http_response(
+            "200 OK",
+            &[
+                ("Content-Type", "text/html"),
+                ("Cache-Control", "max-age=0"),
+                ("ETag", "\"v1\""),
+            ],
+            PAGE,
+        ),
+        http_response("304 Not Modified", &[("ETag", "\"v1\"")], ""),
+    ]);
+    let cache = temp_cache();
+    let cache_dir = cache.dir().to_path_buf();
+    let fetcher = WebFetcher::with_cache(WebFetchConfig::default(), Some(cache));
+    let url = format!("{}/guide", server.url);
+
+    let first = fetcher.fetch(&url).await.unwrap();
+    let second = fetcher.fetch(&url).await.unwrap();
+
+    assert_eq!(first.etag.as_deref(), Some("\"v1\""));
+    assert!(second.from_cache);
+    assert_eq!(first.markdown, second.markdown);
+    assert_eq!(server.request_count(), 2);
+    assert!(server.head(1).contains("if-none-match: \"v1\""));
+
+    let _ = std::fs::remove_dir_all(&cache_dir);
+}
+
+#[tokio::test]
+async fn test_fetch_errors() {
+    let server = MockServer::start(vec![
+        http_response("404 Not Found", &[], "missing"),
+        http_response("200 OK", &[("Content-Type", "image/png")], "PNG"),
+    ]);
+    let fetcher = WebFetcher::with_cache(WebFetchConfig::default(), None);
+
+    assert!(matches!(
+        fetcher.fetch(&format!("{}/missing", server.url)).await,
+        Err(WebFetchError::HttpStatus { status: 404, .. })
+    ));
+    assert!(matches!(
+        fetcher.fetch(&format!("{}/logo.png", server.url)).await,
+        Err(WebFetchError::UnsupportedContent(_))
+    ));
+    assert!(matches!(
+        fetcher.fetch("file:///etc/passwd").await,
+        Err(WebFetchError::UnsupportedScheme(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_server_error_serves_stale_cache() {
+    let server = MockServer::start(vec![
+        http_response(
+            "200 OK",
+            &[
+                ("Content-Type", "text/html"),
+                ("Cache-Control", "max-age=0"),
+            ],
+            PAGE,
+        ),
+        http_response("503 Service Unavailable", &[], "down"),
+    ]);
+    let cache = temp_cache();
+    let cache_dir = cache.dir().to_path_buf();
+    let fetcher = WebFetcher::with_cache(WebFetchConfig::default(), Some(cache));
+    let url = format!("{}/guide", server.url);
+
+    let first = fetcher.fetch(&url).await.unwrap();
+    let second = fetcher.fetch(&url).await.unwrap();
+
+    assert!(second.from_cache);
+    assert_eq!(first.markdown, second.markdown);
+    assert_eq!(server.request_count(), 2);
+
+    let _ = std::fs::remove_dir_all(&cache_dir);
+}
+
+#[tokio::test]
+async fn test_body_limit_without_content_length() {
+    // No Content-Length, and the connection stays open after the first
+    // chunk: the fetch has to stop on the bytes it has read
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/big.txt", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        let Ok((mut stream, _)) = listener.accept() else {
+            return;
+        };
+        let mut buf = [0u8; 1024];
+        let _ = stream.read(&mut buf);
+        let _ = stream
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nConnection: close\r\n\r\n");
+        let _ = stream.write_all(&[b'x'; 4096]);
+        std::thread::sleep(std::time::Duration::from_secs(10));
+    });
+    let config = WebFetchConfig {
+        max_bytes: 1024,
+        timeout_secs: 5,
+        ..WebFetchConfig::default()
+    };
+    let fetcher = WebFetcher::with_cache(config, None);
+
+    assert!(matches!(
+        fetcher.fetch(&url).await,
+        Err(WebFetchError::TooLarge { max: 1024, .. })
+    ));
+}
+
+#[test]
+fn test_fetch_mentions_in_background() {
+    let server = MockServer::start(vec![http_response(
+        "200 OK",
+        &[("Content-Type", "text/html")],
+        PAGE,
+    )]);
+    let fetcher = WebFetcher::with_cache(WebFetchConfig::default(), None);
+    let url = format!("{}/guide", server.url);
+    let mentions =
+        crate::ai::mention::parse_mentions(&format!("summarize @url:{} and @url:{}", url, url));
+
+    // No tokio runtime here, as on the UI thread
+    let results = futures::executor::block_on(fetcher.fetch_mentions_in_background(mentions));
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].0, url);
+    assert_eq!(results[0].1.as_ref().unwrap().name, "Guide & Notes");
+    assert_eq!(server.request_count(), 1);
+}
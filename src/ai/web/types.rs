@@ -0,0 +1,66 @@
+//! Type definitions for web fetching
+
+use serde::{Deserialize, Serialize};
+
+/// Web fetcher configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebFetchConfig {
+    /// Maximum tokens of page content placed in a context item
+    pub max_tokens: usize,
+    /// Maximum response body size in bytes
+    pub max_bytes: usize,
+    /// Request timeout in seconds
+    pub timeout_secs: u64,
+    /// Cache lifetime when the server sends no `max-age`
+    pub default_ttl_secs: i64,
+    /// User agent sent with requests
+    pub user_agent: String,
+}
+
+impl Default for WebFetchConfig {
+    fn default() -> Self {
+        Self {
+            max_tokens: 8_000,
+            max_bytes: 5 * 1024 * 1024,
+            timeout_secs: 30,
+            default_ttl_secs: 60 * 60,
+            user_agent: format!("claude-visual/{}", env!("CARGO_PKG_VERSION")),
+        }
+    }
+}
+
+/// A fetched and sanitized web page
+#[derive(Debug, Clone)]
+pub struct WebPage {
+    /// Requested URL
+    pub url: String,
+    /// Page title (from `<title>` or the first heading)
+    pub title: Option<String>,
+    /// Readable markdown content (not yet truncated)
+    pub markdown: String,
+    /// Entity tag reported by the server
+    pub etag: Option<String>,
+    /// When the content was downloaded
+    pub fetched_at: chrono::DateTime<chrono::Utc>,
+    /// Whether the content was served from the disk cache
+    pub from_cache: bool,
+}
+
+/// Web fetch errors
+#[derive(Debug, thiserror::Error)]
+pub enum WebFetchError {
+    #[error("Invalid URL: {0}")]
+    InvalidUrl(String),
+    #[error("Unsupported URL scheme: {0}")]
+    UnsupportedScheme(String),
+    #[error("Network error: {0}")]
+    Network(String),
+    #[error("HTTP {status} for {url}")]
+    HttpStatus { status: u16, url: String },
+    #[error("Unsupported content type: {0}")]
+    UnsupportedContent(String),
+    #[error("Response too large: {size} bytes exceeds {max}")]
+    TooLarge { size: usize, max: usize },
+    #[error("Cache error: {0}")]
+    Cache(String),
+}
@@ -15,6 +15,8 @@ use std::path::PathBuf;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
+use crate::storage::cleanup::CleanupConfig;
+
 /// Application settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserSettings {
@@ -39,6 +41,16 @@ pub struct UserSettings {
     /// Auto-saved draft text (restored on restart)
     #[serde(default)]
     pub draft_text: String,
+    /// Retention policy; nothing is deleted automatically unless set
+    #[serde(default)]
+    pub cleanup: Option<CleanupConfig>,
+    /// Import sessions run with the Claude CLI into history
+    #[serde(default = "default_import_cli_history")]
+    pub import_cli_history: bool,
+}
+
+fn default_import_cli_history() -> bool {
+    true
 }
 
 impl Default for UserSettings {
@@ -52,6 +64,8 @@ impl Default for UserSettings {
             default_project_dir: None,
             keybindings: Keybindings::default(),
             draft_text: String::new(),
+            cleanup: None,
+            import_cli_history: true,
         }
     }
 }
@@ -9,7 +9,9 @@ use parking_lot::RwLock;
 use crate::plugins::icons::IconLoader;
 use crate::plugins::themes::ThemeLoader;
 use crate::project::manager::ProjectManager;
-use crate::storage::database::Database;
+use crate::storage::cleanup::{RetentionPaths, RetentionService};
+use crate::storage::database::{Database, EncryptionStatus, KeySource};
+use crate::storage::encryption::SystemKeyStore;
 
 use super::settings::Settings;
 use super::theme::Theme;
@@ -36,15 +38,26 @@ impl AppState {
     /// Create new application state
     pub fn new(cx: &mut App) -> Arc<Self> {
         // Initialize database
-        let database = Database::open().expect("Failed to open database");
+        let mut database = Database::open().expect("Failed to open database");
+        if let Some(dir) = RetentionPaths::default_dirs().attachments {
+            database = database.with_attachments(dir);
+        }
         database
             .initialize()
             .expect("Failed to initialize database");
+        unlock_database(&database);
         let database = Arc::new(database);
 
         // Load settings
         let settings = cx.new(|_| Settings::load().unwrap_or_default());
 
+        // Apply the retention policy in the background, if one is set
+        if let Some(config) = settings.read(cx).cleanup.clone() {
+            RetentionService::new(database.clone(), config)
+                .with_paths(RetentionPaths::default_dirs())
+                .spawn();
+        }
+
         // Load theme
         let theme = cx.new(|_| Theme::default());
 
@@ -57,12 +70,20 @@ impl AppState {
         // Initialize icon loader
         let icon_loader = Arc::new(RwLock::new(IconLoader::new()));
 
+        let current_directory = Arc::new(RwLock::new(None));
+
+        // Let agents query the app over the local MCP socket
+        crate::mcp::spawn_app_server(
+            crate::mcp::LocalAppSource::new(database.clone())
+                .with_current_directory(current_directory.clone()),
+        );
+
         Arc::new(Self {
             settings,
             theme,
             project_manager,
             database,
-            current_directory: Arc::new(RwLock::new(None)),
+            current_directory,
             theme_loader,
             icon_loader,
         })
@@ -144,3 +165,30 @@ impl AppState {
         themes
     }
 }
+
+/// Environment variable holding the passphrase of an encrypted database
+const PASSPHRASE_VAR: &str = "CLAUDE_VISUAL_PASSPHRASE";
+
+/// Unlock an encrypted database with the keyring, or the passphrase in
+/// [`PASSPHRASE_VAR`]; it stays locked otherwise
+///
+/// The variable is removed either way, so the processes the app starts,
+/// such as the Claude CLI and MCP servers, do not inherit the passphrase.
+fn unlock_database(database: &Database) {
+    let passphrase = std::env::var(PASSPHRASE_VAR).ok();
+    std::env::remove_var(PASSPHRASE_VAR);
+    if database.encryption_status() != EncryptionStatus::Locked {
+        return;
+    }
+    let result = match database.key_in_keyring() {
+        Ok(true) => database.unlock(KeySource::Keyring(&SystemKeyStore)),
+        Ok(false) => match passphrase {
+            Some(passphrase) => database.unlock(KeySource::Passphrase(&passphrase)),
+            None => Err(anyhow::anyhow!("{} is not set", PASSPHRASE_VAR)),
+        },
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        tracing::warn!("Encrypted database stays locked: {}", e);
+    }
+}
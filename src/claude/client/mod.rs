@@ -5,6 +5,7 @@ mod parser;
 mod stream;
 
 pub use core::ClaudeClient;
+pub(crate) use parser::tool_result_text;
 
 /// Options for sending a prompt to Claude
 #[derive(Debug, Clone, Default)]
@@ -15,4 +16,9 @@ pub struct PromptOptions {
     pub model: Option<String>,
     /// Session ID for continuing a conversation
     pub session_id: Option<String>,
+    /// MCP server configuration (JSON) passed as `--mcp-config`
+    pub mcp_config: Option<String>,
+    /// Pinned context passed as `--append-system-prompt`, unchanged between
+    /// turns so it can be cached
+    pub context: Option<String>,
 }
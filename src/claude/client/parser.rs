@@ -1,9 +1,20 @@
 //! Stream JSON parsing for Claude CLI output
 
 use crate::claude::message::{ClaudeEvent, SessionInfo};
+use crate::storage::models::TokenUsage;
 
-/// Parse a stream-json line from Claude CLI
-pub(crate) fn parse_stream_json(json: &serde_json::Value) -> Option<ClaudeEvent> {
+/// Parse a stream-json line from Claude CLI into the events it holds
+pub(crate) fn parse_stream_json(json: &serde_json::Value) -> Vec<ClaudeEvent> {
+    match json.get("type").and_then(|t| t.as_str()) {
+        Some("assistant") => parse_assistant(json),
+        Some("user") => parse_user(json),
+        Some("result") => parse_result(json),
+        _ => parse_event(json).into_iter().collect(),
+    }
+}
+
+/// Parse a line holding at most one event
+fn parse_event(json: &serde_json::Value) -> Option<ClaudeEvent> {
     let event_type = json.get("type")?.as_str()?;
 
     match event_type {
@@ -83,71 +94,6 @@ pub(crate) fn parse_stream_json(json: &serde_json::Value) -> Option<ClaudeEvent>
                 None
             }
         }
-        "assistant" => {
-            // Check for content in the message
-            if let Some(message) = json.get("message") {
-                if let Some(content) = message.get("content") {
-                    if let Some(arr) = content.as_array() {
-                        // Collect all content blocks
-                        let mut text_parts = Vec::new();
-                        let mut tool_uses = Vec::new();
-                        let mut thinking_parts = Vec::new();
-
-                        for item in arr {
-                            let item_type = item.get("type").and_then(|t| t.as_str());
-                            match item_type {
-                                Some("text") => {
-                                    if let Some(text) = item.get("text").and_then(|t| t.as_str()) {
-                                        text_parts.push(text.to_string());
-                                    }
-                                }
-                                Some("tool_use") => {
-                                    let name = item
-                                        .get("name")
-                                        .and_then(|n| n.as_str())
-                                        .unwrap_or("unknown")
-                                        .to_string();
-                                    let input =
-                                        item.get("input").cloned().unwrap_or(serde_json::json!({}));
-                                    tool_uses.push((name, input));
-                                }
-                                Some("thinking") => {
-                                    if let Some(text) =
-                                        item.get("thinking").and_then(|t| t.as_str())
-                                    {
-                                        thinking_parts.push(text.to_string());
-                                    }
-                                }
-                                _ => {
-                                    tracing::debug!(
-                                        "Unknown content type in assistant message: {:?}",
-                                        item_type
-                                    );
-                                }
-                            }
-                        }
-
-                        // Return text content if any
-                        if !text_parts.is_empty() {
-                            return Some(ClaudeEvent::ContentBlockDelta {
-                                delta: text_parts.join("\n"),
-                            });
-                        }
-                        // Return first tool use if any
-                        if let Some((name, input)) = tool_uses.into_iter().next() {
-                            return Some(ClaudeEvent::ToolUse { name, input });
-                        }
-                        // Return thinking if any
-                        if !thinking_parts.is_empty() {
-                            return Some(ClaudeEvent::Thinking {
-                                content: thinking_parts.join("\n"),
-                            });
-                        }
-                    }
-                }
-            }
-            None
-        }
         "content_block_delta" => {
             let delta = json.get("delta")?.get("text")?.as_str()?.to_string();
             Some(ClaudeEvent::ContentBlockDelta { delta })
@@ -158,8 +104,10 @@ pub(crate) fn parse_stream_json(json: &serde_json::Value) -> Option<ClaudeEvent>
                 if content_block.get("type")?.as_str()? == "tool_use" {
                     let name = content_block.get("name")?.as_str()?.to_string();
                     return Some(ClaudeEvent::ToolUse {
+                        id: string_field(content_block, "id"),
                         name,
                         input: serde_json::json!({}),
+                        parent_tool_use_id: string_field(json, "parent_tool_use_id"),
                     });
                 }
             }
@@ -177,8 +125,10 @@ pub(crate) fn parse_stream_json(json: &serde_json::Value) -> Option<ClaudeEvent>
             let input = json.get("input").cloned().unwrap_or(serde_json::json!({}));
 
             Some(ClaudeEvent::ToolUse {
+                id: string_field(json, "id").or_else(|| string_field(json, "tool_use_id")),
                 name: tool_name,
                 input,
+                parent_tool_use_id: string_field(json, "parent_tool_use_id"),
             })
         }
         "tool_result" => {
@@ -193,53 +143,12 @@ pub(crate) fn parse_stream_json(json: &serde_json::Value) -> Option<ClaudeEvent>
                 .and_then(|e| e.as_bool())
                 .unwrap_or(false);
 
-            Some(ClaudeEvent::ToolResult { output, is_error })
-        }
-        "result" => {
-            // Final result - check if success or error
-            let subtype = json.get("subtype").and_then(|s| s.as_str());
-            let is_error = json
-                .get("is_error")
-                .and_then(|e| e.as_bool())
-                .unwrap_or(false);
-
-            // Extract usage info
-            if let Some(usage) = json.get("usage") {
-                let input_tokens = usage
-                    .get("input_tokens")
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0)
-                    + usage
-                        .get("cache_read_input_tokens")
-                        .and_then(|v| v.as_u64())
-                        .unwrap_or(0);
-                let output_tokens = usage
-                    .get("output_tokens")
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0);
-                let cost_usd = json.get("total_cost_usd").and_then(|v| v.as_f64());
-
-                if input_tokens > 0 || output_tokens > 0 {
-                    tracing::info!(
-                        "Session usage: {} input, {} output tokens, cost: ${:.4}",
-                        input_tokens,
-                        output_tokens,
-                        cost_usd.unwrap_or(0.0)
-                    );
-                }
-            }
-
-            if is_error || subtype == Some("error") {
-                let message = json
-                    .get("result")
-                    .and_then(|r| r.as_str())
-                    .unwrap_or("Unknown error")
-                    .to_string();
-                Some(ClaudeEvent::Error { message })
-            } else {
-                // Success - signal end
-                Some(ClaudeEvent::AssistantEnd)
-            }
+            Some(ClaudeEvent::ToolResult {
+                tool_use_id: string_field(json, "tool_use_id"),
+                output,
+                is_error,
+                parent_tool_use_id: string_field(json, "parent_tool_use_id"),
+            })
         }
         "error" => {
             let message = json
@@ -290,3 +199,212 @@ pub(crate) fn parse_stream_json(json: &serde_json::Value) -> Option<ClaudeEvent>
         }
     }
 }
+
+/// Text, tool calls and thinking of an assistant message, in block order
+fn parse_assistant(json: &serde_json::Value) -> Vec<ClaudeEvent> {
+    let Some(blocks) = json
+        .get("message")
+        .and_then(|m| m.get("content"))
+        .and_then(|c| c.as_array())
+    else {
+        return Vec::new();
+    };
+    let parent_tool_use_id = string_field(json, "parent_tool_use_id");
+
+    let mut events = Vec::new();
+    for item in blocks {
+        let item_type = item.get("type").and_then(|t| t.as_str());
+        match item_type {
+            Some("text") => {
+                if let Some(text) = item.get("text").and_then(|t| t.as_str()) {
+                    events.push(ClaudeEvent::ContentBlockDelta {
+                        delta: text.to_string(),
+                    });
+                }
+            }
+            Some("tool_use") => {
+                let name = item
+                    .get("name")
+                    .and_then(|n| n.as_str())
+                    .unwrap_or("unknown")
+                    .to_string();
+                let input = item.get("input").cloned().unwrap_or(serde_json::json!({}));
+                events.push(ClaudeEvent::ToolUse {
+                    id: string_field(item, "id"),
+                    name,
+                    input,
+                    parent_tool_use_id: parent_tool_use_id.clone(),
+                });
+            }
+            Some("thinking") => {
+                if let Some(text) = item.get("thinking").and_then(|t| t.as_str()) {
+                    events.push(ClaudeEvent::Thinking {
+                        content: text.to_string(),
+                    });
+                }
+            }
+            _ => {
+                tracing::debug!("Unknown content type in assistant message: {:?}", item_type);
+            }
+        }
+    }
+    events
+}
+
+/// Tool results, which the CLI reports as user messages
+fn parse_user(json: &serde_json::Value) -> Vec<ClaudeEvent> {
+    let Some(blocks) = json
+        .get("message")
+        .and_then(|m| m.get("content"))
+        .and_then(|c| c.as_array())
+    else {
+        return Vec::new();
+    };
+    let parent_tool_use_id = string_field(json, "parent_tool_use_id");
+
+    blocks
+        .iter()
+        .filter(|item| item.get("type").and_then(|t| t.as_str()) == Some("tool_result"))
+        .map(|item| ClaudeEvent::ToolResult {
+            tool_use_id: string_field(item, "tool_use_id"),
+            output: tool_result_text(item.get("content")),
+            is_error: item
+                .get("is_error")
+                .and_then(|e| e.as_bool())
+                .unwrap_or(false),
+            parent_tool_use_id: parent_tool_use_id.clone(),
+        })
+        .collect()
+}
+
+/// Text of a tool result's content: a string or a list of text blocks
+pub(crate) fn tool_result_text(content: Option<&serde_json::Value>) -> String {
+    match content {
+        Some(serde_json::Value::String(text)) => text.clone(),
+        Some(serde_json::Value::Array(items)) => items
+            .iter()
+            .filter_map(|item| item.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// Usage of the finished turn, then its end or error
+fn parse_result(json: &serde_json::Value) -> Vec<ClaudeEvent> {
+    let mut events = Vec::new();
+    let subtype = json.get("subtype").and_then(|s| s.as_str());
+    let is_error = json
+        .get("is_error")
+        .and_then(|e| e.as_bool())
+        .unwrap_or(false);
+
+    // Extract usage info
+    if let Some(usage) = json.get("usage") {
+        let count = |key: &str| usage.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+        let usage = TokenUsage {
+            input_tokens: count("input_tokens"),
+            output_tokens: count("output_tokens"),
+            cache_creation_input_tokens: count("cache_creation_input_tokens"),
+            cache_read_input_tokens: count("cache_read_input_tokens"),
+            cost_usd: json.get("total_cost_usd").and_then(|v| v.as_f64()),
+        };
+
+        if usage.input_tokens + usage.cache_read_input_tokens > 0 || usage.output_tokens > 0 {
+            tracing::info!(
+                "Session usage: {} input, {} output tokens, cost: ${:.4}",
+                usage.input_tokens + usage.cache_read_input_tokens,
+                usage.output_tokens,
+                usage.cost_usd.unwrap_or(0.0)
+            );
+            events.push(ClaudeEvent::TurnUsage { usage });
+        }
+    }
+
+    if is_error || subtype == Some("error") {
+        let message = json
+            .get("result")
+            .and_then(|r| r.as_str())
+            .unwrap_or("Unknown error")
+            .to_string();
+        events.push(ClaudeEvent::Error { message });
+    } else {
+        // Success - signal end
+        events.push(ClaudeEvent::AssistantEnd);
+    }
+    events
+}
+
+fn string_field(json: &serde_json::Value, key: &str) -> Option<String> {
+    json.get(key).and_then(|v| v.as_str()).map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parses_tool_ids_results_and_turn_usage() {
+        let assistant = json!({
+            "type": "assistant",
+            "parent_tool_use_id": "toolu_task",
+            "message": { "model": "claude-sonnet-4", "content": [
+                { "type": "thinking", "thinking": "Look first." },
+                { "type": "tool_use", "id": "toolu_1", "name": "Bash", "input": { "command": "ls" } },
+                { "type": "tool_use", "id": "toolu_2", "name": "Read", "input": { "file_path": "a" } }
+            ]}
+        });
+        let events = parse_stream_json(&assistant);
+        assert_eq!(events.len(), 3);
+        assert!(
+            matches!(&events[0], ClaudeEvent::Thinking { content } if content == "Look first.")
+        );
+        match &events[2] {
+            ClaudeEvent::ToolUse {
+                id,
+                name,
+                input,
+                parent_tool_use_id,
+            } => {
+                assert_eq!(id.as_deref(), Some("toolu_2"));
+                assert_eq!(name, "Read");
+                assert_eq!(input["file_path"], "a");
+                assert_eq!(parent_tool_use_id.as_deref(), Some("toolu_task"));
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        let user = json!({
+            "type": "user",
+            "message": { "content": [
+                { "type": "tool_result", "tool_use_id": "toolu_1", "content": "Cargo.toml" },
+                { "type": "tool_result", "tool_use_id": "toolu_2", "is_error": true,
+                  "content": [{ "type": "text", "text": "not found" }] }
+            ]}
+        });
+        let events = parse_stream_json(&user);
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            &events[1],
+            ClaudeEvent::ToolResult { tool_use_id: Some(id), output, is_error: true, .. }
+                if id == "toolu_2" && output == "not found"
+        ));
+
+        let result = json!({
+            "type": "result",
+            "subtype": "success",
+            "total_cost_usd": 0.01,
+            "usage": { "input_tokens": 10, "output_tokens": 5, "cache_read_input_tokens": 90 }
+        });
+        let events = parse_stream_json(&result);
+        match events.as_slice() {
+            [ClaudeEvent::TurnUsage { usage }, ClaudeEvent::AssistantEnd] => {
+                assert_eq!(usage.input_tokens, 10);
+                assert_eq!(usage.cache_read_input_tokens, 90);
+                assert_eq!(usage.cost_usd, Some(0.01));
+            }
+            other => panic!("unexpected events: {:?}", other),
+        }
+    }
+}
@@ -75,6 +75,16 @@ impl ClaudeClient {
             cmd.args(["--continue", sid]);
         }
 
+        // Extra MCP servers, such as the app's own
+        if let Some(ref mcp_config) = options.mcp_config {
+            cmd.args(["--mcp-config", mcp_config]);
+        }
+
+        // Pinned context as a stable system prompt suffix
+        if let Some(ref context) = options.context {
+            cmd.args(["--append-system-prompt", context]);
+        }
+
         // Set working directory if provided
         if let Some(dir) = cwd {
             cmd.current_dir(dir);
@@ -103,7 +113,7 @@ impl ClaudeClient {
             tracing::info!("Claude stream started, sending AssistantStart event");
             let _ = tx.send(ClaudeEvent::AssistantStart);
 
-            for line_result in reader.lines() {
+            'lines: for line_result in reader.lines() {
                 match line_result {
                     Ok(line) => {
                         if line.trim().is_empty() {
@@ -113,11 +123,11 @@ impl ClaudeClient {
                         match serde_json::from_str::<serde_json::Value>(&line) {
                             Ok(json) => {
                                 tracing::debug!("Received JSON: {}", json);
-                                if let Some(event) = parse_stream_json(&json) {
+                                for event in parse_stream_json(&json) {
                                     tracing::info!("Parsed event: {:?}", event);
                                     if tx.send(event).is_err() {
                                         tracing::warn!("Receiver dropped, stopping stream");
-                                        break;
+                                        break 'lines;
                                     }
                                 }
                             }
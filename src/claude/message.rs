@@ -1,8 +1,12 @@
 //! Message types for Claude communication
 
+use std::collections::VecDeque;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::storage::models::{Message, MessageMetadata, TokenUsage};
+
 /// Role of a message in the conversation
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MessageRole {
@@ -17,9 +21,41 @@ pub enum MessageRole {
     System,
 }
 
+impl MessageRole {
+    /// Name stored in the database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Assistant => "assistant",
+            Self::ToolUse => "tool_use",
+            Self::ToolResult => "tool_result",
+            Self::Error => "error",
+            Self::Thinking => "thinking",
+            Self::System => "system",
+        }
+    }
+
+    /// Parse a stored name
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "user" => Some(Self::User),
+            "assistant" => Some(Self::Assistant),
+            "tool_use" => Some(Self::ToolUse),
+            "tool_result" => Some(Self::ToolResult),
+            "error" => Some(Self::Error),
+            "thinking" => Some(Self::Thinking),
+            "system" => Some(Self::System),
+            _ => None,
+        }
+    }
+}
+
 /// A message in the conversation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClaudeMessage {
+    /// Unique identifier, shared with the stored message
+    #[serde(default = "new_message_id")]
+    pub id: String,
     /// Role of the message sender
     pub role: MessageRole,
     /// Message content
@@ -30,86 +66,180 @@ pub struct ClaudeMessage {
     pub tool_name: Option<String>,
     /// Whether this is an error
     pub is_error: bool,
+    /// Message this one follows
+    #[serde(default)]
+    pub parent_id: Option<String>,
+    /// ID of the tool call (tool use) or of the call answered (tool result)
+    #[serde(default)]
+    pub tool_use_id: Option<String>,
+    /// Tool input, model, usage and sub-agent parentage
+    #[serde(default)]
+    pub metadata: MessageMetadata,
+    /// Claude CLI session that produced the message
+    #[serde(default)]
+    pub session_id: Option<String>,
+}
+
+fn new_message_id() -> String {
+    uuid::Uuid::new_v4().to_string()
 }
 
 impl ClaudeMessage {
-    /// Create a user message
-    pub fn user(content: impl Into<String>) -> Self {
+    fn new(role: MessageRole, content: impl Into<String>) -> Self {
         Self {
-            role: MessageRole::User,
+            id: new_message_id(),
+            role,
             content: content.into(),
             timestamp: Utc::now(),
             tool_name: None,
             is_error: false,
+            parent_id: None,
+            tool_use_id: None,
+            metadata: MessageMetadata::default(),
+            session_id: None,
         }
     }
 
+    /// Create a user message
+    pub fn user(content: impl Into<String>) -> Self {
+        Self::new(MessageRole::User, content)
+    }
+
     /// Create an assistant message
     pub fn assistant(content: impl Into<String>) -> Self {
-        Self {
-            role: MessageRole::Assistant,
-            content: content.into(),
-            timestamp: Utc::now(),
-            tool_name: None,
-            is_error: false,
-        }
+        Self::new(MessageRole::Assistant, content)
     }
 
     /// Create a tool use message
     pub fn tool_use(name: impl Into<String>, input: serde_json::Value) -> Self {
-        let name = name.into();
-        Self {
-            role: MessageRole::ToolUse,
-            content: serde_json::to_string_pretty(&input).unwrap_or_default(),
-            timestamp: Utc::now(),
-            tool_name: Some(name),
-            is_error: false,
-        }
+        let mut message = Self::new(
+            MessageRole::ToolUse,
+            serde_json::to_string_pretty(&input).unwrap_or_default(),
+        );
+        message.tool_name = Some(name.into());
+        message.metadata.tool_input = Some(input);
+        message
     }
 
     /// Create a tool result message
     pub fn tool_result(output: impl Into<String>, is_error: bool) -> Self {
-        Self {
-            role: MessageRole::ToolResult,
-            content: output.into(),
-            timestamp: Utc::now(),
-            tool_name: None,
-            is_error,
-        }
+        let mut message = Self::new(MessageRole::ToolResult, output);
+        message.is_error = is_error;
+        message
     }
 
     /// Create an error message
     pub fn error(message: impl Into<String>) -> Self {
-        Self {
-            role: MessageRole::Error,
-            content: message.into(),
-            timestamp: Utc::now(),
-            tool_name: None,
-            is_error: true,
-        }
+        let mut message = Self::new(MessageRole::Error, message);
+        message.is_error = true;
+        message
     }
 
     /// Create a thinking message (Claude's reasoning)
     pub fn thinking(content: impl Into<String>) -> Self {
-        Self {
-            role: MessageRole::Thinking,
-            content: content.into(),
-            timestamp: Utc::now(),
-            tool_name: None,
-            is_error: false,
-        }
+        Self::new(MessageRole::Thinking, content)
     }
 
     /// Create a system message
     pub fn system(content: impl Into<String>) -> Self {
+        Self::new(MessageRole::System, content)
+    }
+
+    /// Set the tool call ID (tool use) or the ID of the call answered (tool result)
+    pub fn with_tool_use_id(mut self, id: Option<String>) -> Self {
+        self.tool_use_id = id;
+        self
+    }
+
+    /// Mark the message as produced by the sub-agent started by a tool call
+    pub fn with_parent_tool_use_id(mut self, id: Option<String>) -> Self {
+        self.metadata.parent_tool_use_id = id;
+        self
+    }
+
+    /// Fill in a tool result's call ID and tool name from the call it answers
+    ///
+    /// Results name their call when the CLI reports its ID; others are
+    /// matched to the oldest call in `history` without a result.
+    pub fn pair_with_tool_use(&mut self, history: &[ClaudeMessage]) {
+        if self.role != MessageRole::ToolResult {
+            return;
+        }
+        let call = match &self.tool_use_id {
+            Some(id) => history
+                .iter()
+                .rev()
+                .find(|m| m.role == MessageRole::ToolUse && m.tool_use_id.as_ref() == Some(id)),
+            None => {
+                let mut pending: VecDeque<&ClaudeMessage> = VecDeque::new();
+                for message in history {
+                    match (message.role, &message.tool_use_id) {
+                        (MessageRole::ToolUse, _) => pending.push_back(message),
+                        (MessageRole::ToolResult, Some(id)) => {
+                            pending.retain(|call| call.tool_use_id.as_ref() != Some(id))
+                        }
+                        (MessageRole::ToolResult, None) => {
+                            pending.pop_front();
+                        }
+                        _ => {}
+                    }
+                }
+                pending.pop_front()
+            }
+        };
+        if let Some(call) = call {
+            if self.tool_use_id.is_none() {
+                self.tool_use_id = call.tool_use_id.clone();
+            }
+            if self.tool_name.is_none() {
+                self.tool_name = call.tool_name.clone();
+            }
+        }
+    }
+
+    /// Stored form of the message in a conversation
+    pub fn to_record(&self, conversation_id: impl Into<String>) -> Message {
+        Message {
+            id: self.id.clone(),
+            conversation_id: conversation_id.into(),
+            role: self.role.as_str().to_string(),
+            content: self.content.clone(),
+            tool_name: self.tool_name.clone(),
+            is_error: self.is_error,
+            timestamp: self.timestamp,
+            parent_id: self.parent_id.clone(),
+            tool_use_id: self.tool_use_id.clone(),
+            metadata: self.metadata.clone(),
+            session_id: self.session_id.clone(),
+        }
+    }
+
+    /// Rebuild a message from its stored form
+    pub fn from_record(record: Message) -> Self {
         Self {
-            role: MessageRole::System,
-            content: content.into(),
-            timestamp: Utc::now(),
-            tool_name: None,
-            is_error: false,
+            id: record.id,
+            role: MessageRole::parse(&record.role).unwrap_or(MessageRole::User),
+            content: record.content,
+            timestamp: record.timestamp,
+            tool_name: record.tool_name,
+            is_error: record.is_error,
+            parent_id: record.parent_id,
+            tool_use_id: record.tool_use_id,
+            metadata: record.metadata,
+            session_id: record.session_id,
         }
     }
+
+    /// Rebuild a conversation's messages, pairing tool results with their calls
+    pub fn from_records(records: impl IntoIterator<Item = Message>) -> Vec<Self> {
+        let mut messages: Vec<Self> = Vec::new();
+        for record in records {
+            let mut message = Self::from_record(record);
+            message.pair_with_tool_use(&messages);
+            messages.push(message);
+        }
+        messages
+    }
 }
 
 /// Session information from Claude CLI init
@@ -169,11 +299,22 @@ pub enum ClaudeEvent {
     AssistantEnd,
     /// Tool use started
     ToolUse {
+        /// Call ID, answered by the matching result
+        id: Option<String>,
         name: String,
         input: serde_json::Value,
+        /// Tool call of the sub-agent making this call
+        parent_tool_use_id: Option<String>,
     },
     /// Tool result received
-    ToolResult { output: String, is_error: bool },
+    ToolResult {
+        /// ID of the call answered
+        tool_use_id: Option<String>,
+        output: String,
+        is_error: bool,
+        /// Tool call of the sub-agent the result belongs to
+        parent_tool_use_id: Option<String>,
+    },
     /// Error occurred
     Error { message: String },
     /// System init with session info
@@ -186,6 +327,8 @@ pub enum ClaudeEvent {
         output_tokens: u64,
         cost_usd: Option<f64>,
     },
+    /// Token usage of the finished turn, with cache and cost detail
+    TurnUsage { usage: TokenUsage },
     /// Task started (subagent)
     TaskStarted {
         description: String,
@@ -221,3 +364,59 @@ pub struct PermissionResponsePayload {
     /// Optional message for denial reason
     pub reason: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_records_roundtrip_and_pair_tool_results() {
+        let mut messages = vec![
+            ClaudeMessage::user("check the build"),
+            ClaudeMessage::thinking("Run both."),
+            ClaudeMessage::tool_use("Bash", json!({ "command": "cargo build" }))
+                .with_tool_use_id(Some("toolu_1".into())),
+            ClaudeMessage::tool_use("Read", json!({ "file_path": "Cargo.toml" }))
+                .with_tool_use_id(Some("toolu_2".into()))
+                .with_parent_tool_use_id(Some("toolu_task".into())),
+            ClaudeMessage::tool_result("[package]", false).with_tool_use_id(Some("toolu_2".into())),
+            ClaudeMessage::tool_result("error[E0433]", true),
+        ];
+        for i in 1..messages.len() {
+            messages[i].parent_id = Some(messages[i - 1].id.clone());
+            let (history, rest) = messages.split_at_mut(i);
+            rest[0].pair_with_tool_use(history);
+        }
+        assert_eq!(messages[4].tool_name.as_deref(), Some("Read"));
+        // Without an ID, the oldest unanswered call
+        assert_eq!(messages[5].tool_name.as_deref(), Some("Bash"));
+        assert_eq!(messages[5].tool_use_id.as_deref(), Some("toolu_1"));
+
+        let records: Vec<Message> = messages.iter().map(|m| m.to_record("c1")).collect();
+        assert_eq!(records[1].role, "thinking");
+        let loaded = ClaudeMessage::from_records(records);
+        for (loaded, original) in loaded.iter().zip(&messages) {
+            assert_eq!(loaded.id, original.id);
+            assert_eq!(loaded.role, original.role);
+            assert_eq!(loaded.parent_id, original.parent_id);
+            assert_eq!(loaded.tool_use_id, original.tool_use_id);
+            assert_eq!(loaded.tool_name, original.tool_name);
+            assert_eq!(loaded.metadata, original.metadata);
+        }
+        assert_eq!(
+            loaded[2].metadata.tool_input,
+            Some(json!({ "command": "cargo build" }))
+        );
+
+        // Rows saved before tool IDs were kept pair by position
+        let legacy = ClaudeMessage::from_records([
+            Message::tool_use("c1", "Grep", "{}"),
+            Message::tool_use("c1", "Glob", "{}"),
+            Message::tool_result("c1", "3 matches", false),
+            Message::tool_result("c1", "2 files", false),
+        ]);
+        assert_eq!(legacy[2].tool_name.as_deref(), Some("Grep"));
+        assert_eq!(legacy[3].tool_name.as_deref(), Some("Glob"));
+    }
+}
@@ -5,5 +5,6 @@
 pub mod client;
 pub mod message;
 pub mod streaming;
+pub mod transcripts;
 
 pub use client::ClaudeClient;
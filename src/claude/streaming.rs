@@ -92,7 +92,7 @@ impl MessageAccumulator {
                     self.content.push_str(delta);
                 }
             }
-            ClaudeEvent::ToolUse { name, input: _ } => {
+            ClaudeEvent::ToolUse { name, .. } => {
                 // Start a new tool use
                 self.current_tool = Some(ToolUseAccumulator {
                     name: name.clone(),
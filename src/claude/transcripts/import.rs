@@ -0,0 +1,273 @@
+//! Incremental import of transcript files
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::future::Future;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use futures::channel::oneshot;
+use futures::executor::block_on;
+
+use crate::claude::message::{ClaudeMessage, MessageRole};
+use crate::project::manager::Project;
+use crate::storage::database::{Database, EncryptionStatus};
+use crate::storage::models::{TranscriptBatch, TranscriptImport};
+
+use super::parse::{parse_line, EntryKind};
+
+/// Longest title taken from a session's first prompt, in characters
+const TITLE_LEN: usize = 60;
+
+/// Stores a file's new lines, returning its new state and the number of
+/// messages stored
+type Store<'a> = dyn Fn(&Path, TranscriptBatch) -> Result<(TranscriptImport, usize)> + 'a;
+
+/// Outcome of an import run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// Transcript files found
+    pub files: usize,
+    /// Files with new lines read
+    pub updated: usize,
+    /// Conversations created
+    pub conversations: usize,
+    /// Messages stored
+    pub messages: usize,
+    /// Files left out because their session belongs to the app
+    pub skipped: usize,
+    /// Files that could not be read or stored
+    pub failed: usize,
+}
+
+/// Imports the session transcripts the Claude CLI keeps under
+/// `~/.claude/projects`, one folder per working directory
+///
+/// Each run reads only the lines appended since the last one, so it can be
+/// repeated freely.
+#[derive(Debug, Clone)]
+pub struct TranscriptImporter {
+    root: PathBuf,
+}
+
+impl TranscriptImporter {
+    /// Importer of the transcripts under `root`
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Where the Claude CLI keeps its transcripts
+    pub fn default_root() -> Option<PathBuf> {
+        dirs::home_dir().map(|home| home.join(".claude").join("projects"))
+    }
+
+    /// Import every transcript, continuing from the last run
+    ///
+    /// A file that fails is logged and counted, and retried next run.
+    pub fn import_all(&self, database: &Database) -> Result<ImportReport> {
+        self.import_with(database, &|path, batch| {
+            database.import_transcript(path, batch)
+        })
+    }
+
+    /// Import every transcript on a background thread
+    ///
+    /// Files are found and read there; only storing each file's new lines
+    /// is queued on the database writer, so other writes are not held up
+    /// behind the whole import.
+    pub fn import_async(
+        self,
+        database: Arc<Database>,
+    ) -> impl Future<Output = Result<ImportReport>> + Send + 'static {
+        let (tx, rx) = oneshot::channel();
+        let spawned = std::thread::Builder::new()
+            .name("transcript-import".into())
+            .spawn(move || {
+                let report = self.import_with(&database, &|path, batch| {
+                    let path = path.to_path_buf();
+                    block_on(database.write_async(move |db| db.import_transcript(&path, batch)))
+                });
+                let _ = tx.send(report);
+            })
+            .map(|_| ());
+        async move {
+            spawned?;
+            rx.await
+                .map_err(|_| anyhow!("Transcript import was interrupted"))?
+        }
+    }
+
+    /// Import every transcript, storing each file's new lines with `store`
+    fn import_with(&self, database: &Database, store: &Store<'_>) -> Result<ImportReport> {
+        if database.encryption_status() == EncryptionStatus::Locked {
+            bail!("Cannot import Claude CLI sessions while the database is locked");
+        }
+        let projects = database.list_projects()?;
+
+        let mut report = ImportReport::default();
+        for path in self.transcripts() {
+            report.files += 1;
+            if let Err(e) = self.import_file(database, store, &path, &projects, &mut report) {
+                tracing::warn!("Failed to import {}: {}", path.display(), e);
+                report.failed += 1;
+            }
+        }
+        Ok(report)
+    }
+
+    /// Transcript files, `<root>/<project folder>/<session id>.jsonl`
+    fn transcripts(&self) -> Vec<PathBuf> {
+        let Ok(folders) = std::fs::read_dir(&self.root) else {
+            return Vec::new();
+        };
+        let mut paths: Vec<PathBuf> = folders
+            .flatten()
+            .filter_map(|folder| std::fs::read_dir(folder.path()).ok())
+            .flat_map(|files| files.flatten().map(|file| file.path()))
+            .filter(|path| path.is_file() && path.extension().is_some_and(|e| e == "jsonl"))
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    fn import_file(
+        &self,
+        database: &Database,
+        store: &Store<'_>,
+        path: &Path,
+        projects: &[Project],
+        report: &mut ImportReport,
+    ) -> Result<()> {
+        let state = database.transcript_import(path)?;
+        if state.as_ref().is_some_and(|s| s.skipped) {
+            report.skipped += 1;
+            return Ok(());
+        }
+        let had_conversation = state.as_ref().is_some_and(|s| s.conversation_id.is_some());
+        let Some((text, offset)) = read_new_lines(path, state.map_or(0, |s| s.offset))? else {
+            return Ok(());
+        };
+
+        let session_id = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let batch = batch(&text, session_id, offset, projects);
+        let (state, stored) = store(path, batch)?;
+
+        report.updated += 1;
+        report.messages += stored;
+        if state.skipped {
+            report.skipped += 1;
+        } else if !had_conversation && stored > 0 {
+            report.conversations += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Complete lines of `path` from `offset` and the offset after them, or
+/// `None` if nothing was added
+///
+/// A file shorter than `offset` was rewritten, so is read from the start;
+/// the messages already stored are skipped on import.
+fn read_new_lines(path: &Path, offset: u64) -> Result<Option<(String, u64)>> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    if len == offset {
+        return Ok(None);
+    }
+    let start = if len < offset { 0 } else { offset };
+
+    file.seek(SeekFrom::Start(start))?;
+    let mut bytes = Vec::with_capacity((len - start) as usize);
+    file.read_to_end(&mut bytes)?;
+    // A line still being written is read next time
+    let complete = bytes.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+    if complete == 0 {
+        return Ok(None);
+    }
+    bytes.truncate(complete);
+    Ok(Some((
+        String::from_utf8_lossy(&bytes).into_owned(),
+        start + complete as u64,
+    )))
+}
+
+/// Messages of transcript lines, with parents resolved within the lines
+///
+/// Left-out entries pass their own parent on. Parents outside the lines
+/// keep the entry ID, which the database resolves.
+fn batch(text: &str, session_id: String, offset: u64, projects: &[Project]) -> TranscriptBatch {
+    let mut parents: HashMap<String, Option<String>> = HashMap::new();
+    let mut history: Vec<ClaudeMessage> = Vec::new();
+    let mut summary = None;
+    let mut cwd = None;
+
+    for entry in text.lines().filter_map(parse_line) {
+        if cwd.is_none() {
+            cwd = entry.cwd;
+        }
+        let parent = entry
+            .parent_uuid
+            .and_then(|uuid| parents.get(&uuid).cloned().unwrap_or(Some(uuid)));
+        match entry.kind {
+            EntryKind::Summary(text) => {
+                summary.get_or_insert(text);
+            }
+            EntryKind::Skipped => {
+                if let Some(uuid) = entry.uuid {
+                    parents.insert(uuid, parent);
+                }
+            }
+            EntryKind::Messages(mut messages) => {
+                if let Some(first) = messages.first_mut() {
+                    first.parent_id = parent;
+                }
+                for mut message in messages {
+                    message.pair_with_tool_use(&history);
+                    history.push(message);
+                }
+                if let (Some(uuid), Some(last)) = (entry.uuid, history.last()) {
+                    parents.insert(uuid, Some(last.id.clone()));
+                }
+            }
+        }
+    }
+
+    let title = summary
+        .or_else(|| {
+            history
+                .iter()
+                .find(|m| m.role == MessageRole::User)
+                .map(|m| title_from(&m.content))
+        })
+        .unwrap_or_else(|| "Claude CLI session".to_string());
+    TranscriptBatch {
+        project_id: cwd.and_then(|cwd| project_for(&cwd, projects)),
+        session_id,
+        title,
+        messages: history.iter().map(|m| m.to_record(String::new())).collect(),
+        offset,
+    }
+}
+
+/// First line of a prompt, shortened to [`TITLE_LEN`]
+fn title_from(prompt: &str) -> String {
+    let line = prompt.trim().lines().next().unwrap_or_default().trim();
+    match line.char_indices().nth(TITLE_LEN) {
+        Some((end, _)) => format!("{}…", line[..end].trim_end()),
+        None => line.to_string(),
+    }
+}
+
+/// Project whose folder holds `cwd`, the innermost if they nest
+fn project_for(cwd: &Path, projects: &[Project]) -> Option<String> {
+    projects
+        .iter()
+        .filter(|project| cwd.starts_with(&project.path))
+        .max_by_key(|project| project.path.components().count())
+        .map(|project| project.id.clone())
+}
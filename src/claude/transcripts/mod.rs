@@ -0,0 +1,222 @@
+//! Claude CLI session transcripts
+//!
+//! The CLI writes every session to `~/.claude/projects/<folder>/<session
+//! id>.jsonl`, one JSON entry per line. Importing them brings sessions run
+//! outside the app into history: each becomes a conversation, matched to
+//! the project holding its working directory, whose messages keep the
+//! session ID so it can be resumed with `--resume`.
+//!
+//! Messages take their IDs from the entries, and each file's progress is
+//! recorded, so repeated imports read only new lines and store nothing
+//! twice. Sub-agent and bookkeeping entries are left out. Sessions the app
+//! ran itself are already in history and are not imported.
+
+mod import;
+mod parse;
+
+pub use import::{ImportReport, TranscriptImporter};
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::claude::message::ClaudeMessage;
+    use crate::project::manager::Project;
+    use crate::storage::database::Database;
+    use crate::storage::models::Conversation;
+
+    fn append(path: &Path, text: &str) {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    fn line(entry: serde_json::Value) -> String {
+        format!("{}\n", entry)
+    }
+
+    #[test]
+    fn test_imports_transcripts_incrementally() {
+        let root = std::env::temp_dir().join(format!(
+            "claude_visual_transcripts_{}",
+            uuid::Uuid::new_v4()
+        ));
+        let folder = root.join("-work-app");
+        std::fs::create_dir_all(&folder).unwrap();
+        let path: PathBuf = folder.join("s1.jsonl");
+
+        let database = Arc::new(Database::open_in_memory().unwrap());
+        database.initialize().unwrap();
+        let project = Project::new("app", PathBuf::from("/work/app"));
+        database.insert_project(&project).unwrap();
+
+        let entry = |uuid: &str, parent: Option<&str>, kind: &str, content: serde_json::Value| {
+            line(serde_json::json!({
+                "type": kind, "uuid": uuid, "parentUuid": parent, "sessionId": "s1",
+                "cwd": "/work/app/src", "timestamp": "2025-06-01T10:00:00Z",
+                "message": { "role": kind, "model": "claude-sonnet-4", "content": content },
+            }))
+        };
+        append(
+            &path,
+            &line(serde_json::json!({ "type": "summary", "summary": "Fix the build" })),
+        );
+        append(
+            &path,
+            &entry("u1", None, "user", "Why does the build fail?".into()),
+        );
+        append(
+            &path,
+            &entry(
+                "a1",
+                Some("u1"),
+                "assistant",
+                serde_json::json!([
+                    { "type": "thinking", "thinking": "Check first." },
+                    { "type": "tool_use", "id": "toolu_1", "name": "Bash", "input": { "command": "cargo build" } },
+                ]),
+            ),
+        );
+        append(
+            &path,
+            &entry(
+                "r1",
+                Some("a1"),
+                "user",
+                serde_json::json!([
+                    { "type": "tool_result", "tool_use_id": "toolu_1", "content": "error[E0308]" },
+                ]),
+            ),
+        );
+        append(
+            &path,
+            &line(serde_json::json!({
+                "type": "user", "uuid": "m1", "parentUuid": "r1", "isMeta": true,
+                "sessionId": "s1", "message": { "role": "user", "content": "Caveat" },
+            })),
+        );
+        append(
+            &path,
+            &entry(
+                "a2",
+                Some("m1"),
+                "assistant",
+                serde_json::json!([
+                    { "type": "text", "text": "A type mismatch." },
+                ]),
+            ),
+        );
+        // Still being written
+        append(&path, r#"{"type": "assistant", "uuid": "a3""#);
+
+        let importer = TranscriptImporter::new(&root);
+        let import = importer.clone().import_async(database.clone());
+        let report = futures::executor::block_on(import).unwrap();
+        assert_eq!(report.files, 1);
+        assert_eq!(report.conversations, 1);
+        assert_eq!(report.messages, 5);
+
+        let conversations = database.get_conversations(None).unwrap();
+        assert_eq!(conversations.len(), 1);
+        assert_eq!(conversations[0].title, "Fix the build");
+        assert_eq!(
+            conversations[0].project_id.as_deref(),
+            Some(project.id.as_str())
+        );
+
+        let conversation_id = conversations[0].id.clone();
+        let messages =
+            ClaudeMessage::from_records(database.get_messages(&conversation_id).unwrap());
+        let ids: Vec<&str> = messages.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["u1", "a1", "a1:1", "r1", "a2"]);
+        assert_eq!(messages[2].parent_id.as_deref(), Some("a1"));
+        assert_eq!(messages[3].parent_id.as_deref(), Some("a1:1"));
+        assert_eq!(messages[3].tool_name.as_deref(), Some("Bash"));
+        // The left-out meta entry passes its parent on
+        assert_eq!(messages[4].parent_id.as_deref(), Some("r1"));
+        assert_eq!(
+            messages[4].metadata.model.as_deref(),
+            Some("claude-sonnet-4")
+        );
+        assert!(messages
+            .iter()
+            .all(|m| m.session_id.as_deref() == Some("s1")));
+        assert_eq!(
+            database.resume_session_id("a2").unwrap().as_deref(),
+            Some("s1")
+        );
+
+        // Nothing new
+        let report = importer.import_all(&database).unwrap();
+        assert_eq!((report.updated, report.messages), (0, 0));
+
+        // The partial line is finished, and one more follows
+        append(&path, "}\n");
+        append(&path, &entry("u2", Some("a2"), "user", "Fix it".into()));
+        let report = importer.import_all(&database).unwrap();
+        assert_eq!((report.conversations, report.messages), (0, 1));
+        let thread = database.get_active_thread(&conversation_id).unwrap();
+        assert_eq!(thread.last().map(|m| m.id.as_str()), Some("u2"));
+        assert_eq!(
+            thread.last().and_then(|m| m.parent_id.as_deref()),
+            Some("a2")
+        );
+
+        // Rewritten from scratch: read again, nothing stored twice
+        std::fs::write(
+            &path,
+            entry("u1", None, "user", "Why does the build fail?".into()),
+        )
+        .unwrap();
+        let report = importer.import_all(&database).unwrap();
+        assert_eq!((report.updated, report.messages), (1, 0));
+        assert_eq!(database.get_messages(&conversation_id).unwrap().len(), 6);
+
+        // Resumed in the app: later lines are the app's to store
+        let mut reply = ClaudeMessage::assistant("Done.");
+        reply.session_id = Some("s1".into());
+        reply.parent_id = Some("u2".into());
+        database
+            .insert_message(&reply.to_record(&conversation_id))
+            .unwrap();
+        append(
+            &path,
+            &entry(
+                "a4",
+                Some("u2"),
+                "assistant",
+                serde_json::json!([
+                    { "type": "text", "text": "Done." },
+                ]),
+            ),
+        );
+        let report = importer.import_all(&database).unwrap();
+        assert_eq!((report.skipped, report.messages), (1, 0));
+        assert_eq!(database.get_messages(&conversation_id).unwrap().len(), 7);
+
+        // Sessions started in the app are not imported
+        let conversation = Conversation::new("In the app", None);
+        database.insert_conversation(&conversation).unwrap();
+        let mut message = ClaudeMessage::assistant("Hello");
+        message.session_id = Some("s2".into());
+        database
+            .insert_message(&message.to_record(&conversation.id))
+            .unwrap();
+        let other = root.join("-work-other").join("s2.jsonl");
+        std::fs::create_dir_all(other.parent().unwrap()).unwrap();
+        append(&other, &entry("x1", None, "user", "Hello".into()));
+        let report = importer.import_all(&database).unwrap();
+        assert_eq!(report.files, 2);
+        assert_eq!(report.messages, 0);
+        assert_eq!(database.get_conversations(None).unwrap().len(), 2);
+        assert!(database.get_message("x1").unwrap().is_none());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,206 @@
+//! Transcript lines
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use crate::claude::client::tool_result_text;
+use crate::claude::message::ClaudeMessage;
+
+/// Model named on messages the CLI writes itself, such as API errors
+const SYNTHETIC_MODEL: &str = "<synthetic>";
+
+/// One line of a transcript
+#[derive(Debug)]
+pub(crate) struct Entry {
+    /// ID of the entry, which its messages' IDs derive from
+    pub uuid: Option<String>,
+    /// Entry this one follows
+    pub parent_uuid: Option<String>,
+    /// Session that wrote the entry
+    pub session_id: Option<String>,
+    /// Working directory of the session
+    pub cwd: Option<PathBuf>,
+    pub kind: EntryKind,
+}
+
+#[derive(Debug)]
+pub(crate) enum EntryKind {
+    /// Messages of the entry in block order, each following the one before
+    Messages(Vec<ClaudeMessage>),
+    /// Title the CLI gave the session
+    Summary(String),
+    /// Sub-agent, bookkeeping and empty entries, left out of history
+    Skipped,
+}
+
+/// Parse a transcript line, `None` if it is not a JSON object
+pub(crate) fn parse_line(line: &str) -> Option<Entry> {
+    let json: Value = serde_json::from_str(line).ok()?;
+    if !json.is_object() {
+        return None;
+    }
+
+    let uuid = string_field(&json, "uuid");
+    // Compaction restarts the chain; its logical parent keeps the thread
+    let parent_uuid =
+        string_field(&json, "parentUuid").or_else(|| string_field(&json, "logicalParentUuid"));
+    let session_id = string_field(&json, "sessionId");
+    let cwd = string_field(&json, "cwd").map(PathBuf::from);
+
+    let flag = |key: &str| json.get(key).and_then(|v| v.as_bool()).unwrap_or(false);
+    let messages = match json.get("type").and_then(|t| t.as_str()) {
+        Some("summary") => {
+            let kind = match string_field(&json, "summary") {
+                Some(summary) if !summary.trim().is_empty() => EntryKind::Summary(summary),
+                _ => EntryKind::Skipped,
+            };
+            return Some(Entry {
+                uuid,
+                parent_uuid,
+                session_id,
+                cwd,
+                kind,
+            });
+        }
+        _ if flag("isSidechain") || flag("isMeta") => Vec::new(),
+        Some("user") => user_messages(&json, flag("isCompactSummary")),
+        Some("assistant") if flag("isApiErrorMessage") => {
+            // Same shape as a tool result's content
+            let text = tool_result_text(json.get("message").and_then(|m| m.get("content")));
+            vec![ClaudeMessage::error(text)]
+        }
+        Some("assistant") => assistant_messages(&json),
+        Some("system") => match string_field(&json, "content") {
+            Some(content) => vec![ClaudeMessage::system(content)],
+            None => Vec::new(),
+        },
+        _ => Vec::new(),
+    };
+
+    let kind = match &uuid {
+        Some(uuid) if !messages.is_empty() => {
+            let timestamp = string_field(&json, "timestamp")
+                .and_then(|t| DateTime::parse_from_rfc3339(&t).ok())
+                .map(|t| t.with_timezone(&Utc))
+                .unwrap_or_else(Utc::now);
+            EntryKind::Messages(identify(messages, uuid, timestamp, session_id.as_deref()))
+        }
+        _ => EntryKind::Skipped,
+    };
+    Some(Entry {
+        uuid,
+        parent_uuid,
+        session_id,
+        cwd,
+        kind,
+    })
+}
+
+/// Prompt text and tool results of a user entry
+fn user_messages(json: &Value, compact_summary: bool) -> Vec<ClaudeMessage> {
+    let message = |text: String| {
+        if compact_summary {
+            ClaudeMessage::system(text)
+        } else {
+            ClaudeMessage::user(text)
+        }
+    };
+    match json.get("message").and_then(|m| m.get("content")) {
+        Some(Value::String(text)) if !text.trim().is_empty() => vec![message(text.clone())],
+        Some(Value::Array(blocks)) => blocks
+            .iter()
+            .filter_map(|block| match block.get("type").and_then(|t| t.as_str()) {
+                Some("text") => block
+                    .get("text")
+                    .and_then(|t| t.as_str())
+                    .filter(|text| !text.trim().is_empty())
+                    .map(|text| message(text.to_string())),
+                Some("tool_result") => Some(
+                    ClaudeMessage::tool_result(
+                        tool_result_text(block.get("content")),
+                        block
+                            .get("is_error")
+                            .and_then(|e| e.as_bool())
+                            .unwrap_or(false),
+                    )
+                    .with_tool_use_id(string_field(block, "tool_use_id")),
+                ),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Text, tool calls and thinking of an assistant entry, in block order
+fn assistant_messages(json: &Value) -> Vec<ClaudeMessage> {
+    let Some(message) = json.get("message") else {
+        return Vec::new();
+    };
+    let model = string_field(message, "model").filter(|m| m != SYNTHETIC_MODEL);
+    let Some(blocks) = message.get("content").and_then(|c| c.as_array()) else {
+        return Vec::new();
+    };
+
+    blocks
+        .iter()
+        .filter_map(|block| match block.get("type").and_then(|t| t.as_str()) {
+            Some("text") => block
+                .get("text")
+                .and_then(|t| t.as_str())
+                .filter(|text| !text.trim().is_empty())
+                .map(ClaudeMessage::assistant),
+            Some("tool_use") => Some(
+                ClaudeMessage::tool_use(
+                    block
+                        .get("name")
+                        .and_then(|n| n.as_str())
+                        .unwrap_or("unknown"),
+                    block.get("input").cloned().unwrap_or(serde_json::json!({})),
+                )
+                .with_tool_use_id(string_field(block, "id")),
+            ),
+            Some("thinking") => block
+                .get("thinking")
+                .and_then(|t| t.as_str())
+                .filter(|text| !text.trim().is_empty())
+                .map(ClaudeMessage::thinking),
+            _ => None,
+        })
+        .map(|mut message| {
+            message.metadata.model = model.clone();
+            message
+        })
+        .collect()
+}
+
+/// Give an entry's messages their stored IDs, `<uuid>` then `<uuid>:1`,
+/// `<uuid>:2`, ..., and chain each to the one before
+fn identify(
+    messages: Vec<ClaudeMessage>,
+    uuid: &str,
+    timestamp: DateTime<Utc>,
+    session_id: Option<&str>,
+) -> Vec<ClaudeMessage> {
+    let mut previous: Option<String> = None;
+    messages
+        .into_iter()
+        .enumerate()
+        .map(|(i, mut message)| {
+            message.id = match i {
+                0 => uuid.to_string(),
+                i => format!("{}:{}", uuid, i),
+            };
+            message.timestamp = timestamp;
+            message.session_id = session_id.map(String::from);
+            message.parent_id = previous.replace(message.id.clone());
+            message
+        })
+        .collect()
+}
+
+fn string_field(json: &Value, key: &str) -> Option<String> {
+    json.get(key).and_then(|v| v.as_str()).map(String::from)
+}
@@ -2,6 +2,7 @@
 
 use anyhow::Result;
 use git2::BranchType;
+use serde::Serialize;
 
 use super::Repository;
 use crate::git::status::FileStatus;
@@ -100,7 +101,7 @@ impl Repository {
 }
 
 /// Summary of repository status
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct RepositoryStatusSummary {
     /// Current branch name
     pub branch: String,
@@ -46,10 +46,19 @@ impl LspManager {
 
         // Forward events
         let event_tx = self.event_tx.clone();
+        let latest = self.diagnostics.clone();
         tokio::spawn(async move {
             while let Some(event) = lsp_rx.recv().await {
                 match event {
                     LspEvent::Diagnostics(uri, diagnostics) => {
+                        {
+                            let mut latest = latest.lock().await;
+                            if diagnostics.is_empty() {
+                                latest.remove(&uri);
+                            } else {
+                                latest.insert(uri.clone(), diagnostics.clone());
+                            }
+                        }
                         let _ = event_tx.send(LspManagerEvent::Diagnostics(uri, diagnostics));
                     }
                     LspEvent::Error(err) => {
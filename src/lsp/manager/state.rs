@@ -7,6 +7,7 @@ use std::sync::Arc;
 use tokio::sync::{mpsc, Mutex};
 
 use crate::lsp::client::LspClient;
+use crate::lsp::protocol::Diagnostic;
 
 use super::events::LspManagerEvent;
 use super::language::Language;
@@ -20,6 +21,8 @@ pub struct LspManager {
     pub(super) root_dir: PathBuf,
     /// Open documents by URI
     pub(super) open_documents: Arc<Mutex<HashMap<String, OpenDocument>>>,
+    /// Latest diagnostics by document URI
+    pub(super) diagnostics: Arc<Mutex<HashMap<String, Vec<Diagnostic>>>>,
     /// Event sender
     pub(super) event_tx: mpsc::UnboundedSender<LspManagerEvent>,
     /// Event receiver
@@ -34,6 +37,7 @@ impl LspManager {
             clients: Arc::new(Mutex::new(HashMap::new())),
             root_dir,
             open_documents: Arc::new(Mutex::new(HashMap::new())),
+            diagnostics: Arc::new(Mutex::new(HashMap::new())),
             event_tx,
             event_rx: Some(event_rx),
         }
@@ -49,4 +53,9 @@ impl LspManager {
         let clients = self.clients.lock().await;
         clients.keys().copied().collect()
     }
+
+    /// Latest diagnostics published by the running servers, by document URI
+    pub async fn diagnostics(&self) -> HashMap<String, Vec<Diagnostic>> {
+        self.diagnostics.lock().await.clone()
+    }
 }
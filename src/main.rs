@@ -35,12 +35,16 @@ use app::state::AppState;
 use ui::workspace::Workspace;
 
 fn main() -> Result<()> {
-    // Initialize logging
+    // Initialize logging (on stderr: stdout carries MCP in server mode)
     tracing_subscriber::registry()
         .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
-        .with(tracing_subscriber::fmt::layer())
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
         .init();
 
+    if std::env::args().any(|arg| arg == mcp::APP_SERVER_FLAG) {
+        return mcp::run_stdio_server();
+    }
+
     tracing::info!("Starting Claude Visual");
 
     // Initialize GPUI application
@@ -0,0 +1,368 @@
+//! Tools and resources offered by the app server
+
+use super::super::protocol::*;
+use super::super::schema::{coerce_strings, prepare_arguments};
+use super::types::AppDataSource;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// URI scheme of app resources
+pub const RESOURCE_SCHEME: &str = "claude-visual://";
+
+/// Default number of conversation search results
+const DEFAULT_SEARCH_LIMIT: u64 = 20;
+
+fn tool(name: &str, title: &str, description: &str, input_schema: Value) -> McpTool {
+    McpTool {
+        name: name.to_string(),
+        title: Some(title.to_string()),
+        description: Some(description.to_string()),
+        input_schema: serde_json::from_value(input_schema)
+            .expect("app server tool schemas are valid"),
+        output_schema: None,
+        annotations: Some(ToolAnnotations {
+            read_only_hint: Some(true),
+            destructive_hint: Some(false),
+            idempotent_hint: Some(true),
+            open_world_hint: Some(false),
+            ..Default::default()
+        }),
+    }
+}
+
+/// Tools published by the app server
+pub fn tools() -> Vec<McpTool> {
+    let path = json!({
+        "type": "string",
+        "description": "File or directory path, or a project id or name (default: current directory)"
+    });
+    vec![
+        tool(
+            "list_projects",
+            "List projects",
+            "List the projects open in Claude Visual, most recently used first",
+            json!({
+                "type": "object",
+                "properties": {
+                    "favorites_only": { "type": "boolean", "default": false }
+                },
+                "additionalProperties": false
+            }),
+        ),
+        tool(
+            "git_status",
+            "Git status",
+            "Branch, upstream and changed files of a git repository",
+            json!({
+                "type": "object",
+                "properties": { "path": path },
+                "additionalProperties": false
+            }),
+        ),
+        tool(
+            "get_diagnostics",
+            "LSP diagnostics",
+            "Errors and warnings reported by the running language servers",
+            json!({
+                "type": "object",
+                "properties": {
+                    "path": path,
+                    "min_severity": {
+                        "type": "string",
+                        "enum": ["error", "warning", "information", "hint"],
+                        "default": "hint"
+                    }
+                },
+                "additionalProperties": false
+            }),
+        ),
+        tool(
+            "debug_stack",
+            "Debug call stack",
+            "State, threads and call stack of the active debug session",
+            json!({ "type": "object", "properties": {}, "additionalProperties": false }),
+        ),
+        tool(
+            "debug_variables",
+            "Debug variables",
+            "Variables in scope for a stack frame of the active debug session",
+            json!({
+                "type": "object",
+                "properties": {
+                    "frame_id": {
+                        "type": "integer",
+                        "description": "Stack frame id from debug_stack (default: current frame)"
+                    }
+                },
+                "additionalProperties": false
+            }),
+        ),
+        tool(
+            "search_conversations",
+            "Search conversations",
+            "Full-text search over past conversations. Supports \"phrases\", OR, -exclusions and \
+             role:, tool:, after:, before:, tag: and is:bookmarked|pinned|error filters",
+            json!({
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "minLength": 1 },
+                    "project_id": { "type": "string" },
+                    "limit": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": 100,
+                        "default": DEFAULT_SEARCH_LIMIT
+                    }
+                },
+                "required": ["query"],
+                "additionalProperties": false
+            }),
+        ),
+    ]
+}
+
+fn resource(path: &str, name: &str, description: &str) -> McpResource {
+    McpResource {
+        uri: format!("{}{}", RESOURCE_SCHEME, path),
+        name: name.to_string(),
+        description: Some(description.to_string()),
+        mime_type: Some("application/json".to_string()),
+    }
+}
+
+/// Fixed resources published by the app server
+pub fn resources() -> Vec<McpResource> {
+    vec![
+        resource("projects", "Projects", "Projects open in Claude Visual"),
+        resource(
+            "git/status",
+            "Git status",
+            "Git status of the current directory",
+        ),
+        resource(
+            "diagnostics",
+            "Diagnostics",
+            "Latest diagnostics from the running language servers",
+        ),
+        resource(
+            "debug/stack",
+            "Debug call stack",
+            "Call stack of the active debug session",
+        ),
+        resource(
+            "debug/variables",
+            "Debug variables",
+            "Variables of the current stack frame",
+        ),
+    ]
+}
+
+/// Parameterized resources published by the app server
+pub fn resource_templates() -> Vec<ResourceTemplate> {
+    vec![
+        ResourceTemplate {
+            uri_template: format!("{}projects/{{project}}/git/status", RESOURCE_SCHEME),
+            name: "Project git status".to_string(),
+            title: None,
+            description: Some("Git status of a project, by id or name".to_string()),
+            mime_type: Some("application/json".to_string()),
+        },
+        ResourceTemplate {
+            uri_template: format!("{}conversations/search/{{query}}", RESOURCE_SCHEME),
+            name: "Conversation search".to_string(),
+            title: None,
+            description: Some("Messages matching a full-text query".to_string()),
+            mime_type: Some("application/json".to_string()),
+        },
+    ]
+}
+
+/// Run a tool against the data source
+///
+/// Unknown tools are a protocol error; invalid arguments and failures of
+/// the data source are reported in the result with `isError` set, so the
+/// calling model can see and correct them.
+pub(crate) async fn call_tool(
+    source: &dyn AppDataSource,
+    params: CallToolParams,
+) -> Result<CallToolResult, McpError> {
+    let tool = tools()
+        .into_iter()
+        .find(|t| t.name == params.name)
+        .ok_or_else(|| McpError::Server {
+            code: McpErrorCode::InvalidParams as i32,
+            message: format!("Unknown tool: {}", params.name),
+        })?;
+
+    // Models often quote numbers and booleans
+    let mut arguments = serde_json::to_value(params.arguments.unwrap_or_default())
+        .map_err(|e| McpError::Protocol(e.to_string()))?;
+    coerce_strings(&tool.input_schema.to_value(), &mut arguments);
+    let arguments = serde_json::from_value(arguments).unwrap_or_default();
+
+    let arguments = match prepare_arguments(&tool, arguments) {
+        Ok(arguments) => arguments,
+        Err(e) => return Ok(error_result(e)),
+    };
+
+    let result = match tool.name.as_str() {
+        "list_projects" => {
+            let favorites_only = bool_arg(&arguments, "favorites_only");
+            source.projects().await.and_then(|projects| {
+                structured(
+                    projects
+                        .into_iter()
+                        .filter(|p| p.is_favorite || !favorites_only)
+                        .collect::<Vec<_>>(),
+                )
+            })
+        }
+        "git_status" => source
+            .git_status(str_arg(&arguments, "path"))
+            .await
+            .and_then(structured),
+        "get_diagnostics" => {
+            let max = severity_rank(str_arg(&arguments, "min_severity").unwrap_or("hint"));
+            source
+                .diagnostics(str_arg(&arguments, "path"))
+                .await
+                .and_then(|mut files| {
+                    for file in &mut files {
+                        file.diagnostics.retain(|d| {
+                            d.severity.map_or(severity_rank("error"), |s| s as u8) <= max
+                        });
+                    }
+                    files.retain(|f| !f.diagnostics.is_empty());
+                    structured(files)
+                })
+        }
+        "debug_stack" => source.debug_stack().await.and_then(structured),
+        "debug_variables" => source
+            .debug_variables(arguments.get("frame_id").and_then(Value::as_i64))
+            .await
+            .and_then(structured),
+        "search_conversations" => {
+            let limit = arguments
+                .get("limit")
+                .and_then(Value::as_u64)
+                .unwrap_or(DEFAULT_SEARCH_LIMIT) as usize;
+            source
+                .search_conversations(
+                    str_arg(&arguments, "query").unwrap_or_default(),
+                    str_arg(&arguments, "project_id"),
+                    limit,
+                )
+                .await
+                .and_then(structured)
+        }
+        other => Err(McpError::Unavailable(format!("Tool '{}'", other))),
+    };
+
+    Ok(result.unwrap_or_else(error_result))
+}
+
+/// Read an app resource
+pub(crate) async fn read_resource(
+    source: &dyn AppDataSource,
+    uri: &str,
+) -> Result<ResourceContents, McpError> {
+    let not_found = || McpError::Server {
+        code: McpErrorCode::InvalidParams as i32,
+        message: format!("Unknown resource: {}", uri),
+    };
+    let path = uri.strip_prefix(RESOURCE_SCHEME).ok_or_else(not_found)?;
+    let segments: Vec<&str> = path.split('/').collect();
+
+    let value = match segments.as_slice() {
+        ["projects"] => to_json(source.projects().await?)?,
+        ["git", "status"] => to_json(source.git_status(None).await?)?,
+        ["diagnostics"] => to_json(source.diagnostics(None).await?)?,
+        ["debug", "stack"] => to_json(source.debug_stack().await?)?,
+        ["debug", "variables"] => to_json(source.debug_variables(None).await?)?,
+        ["projects", project, "git", "status"] => {
+            let project = decode(project)?;
+            to_json(source.git_status(Some(&project)).await?)?
+        }
+        ["conversations", "search", query] => {
+            let query = decode(query)?;
+            to_json(
+                source
+                    .search_conversations(&query, None, DEFAULT_SEARCH_LIMIT as usize)
+                    .await?,
+            )?
+        }
+        _ => return Err(not_found()),
+    };
+
+    Ok(ResourceContents {
+        uri: uri.to_string(),
+        mime_type: Some("application/json".to_string()),
+        text: Some(serde_json::to_string_pretty(&value).unwrap_or_default()),
+        blob: None,
+    })
+}
+
+fn decode(segment: &str) -> Result<String, McpError> {
+    urlencoding::decode(segment)
+        .map(|s| s.into_owned())
+        .map_err(|e| McpError::Server {
+            code: McpErrorCode::InvalidParams as i32,
+            message: format!("Invalid URI segment '{}': {}", segment, e),
+        })
+}
+
+fn to_json<T: Serialize>(value: T) -> Result<Value, McpError> {
+    serde_json::to_value(value).map_err(|e| McpError::Protocol(e.to_string()))
+}
+
+/// Successful result carrying the value as structured content and as text
+fn structured<T: Serialize>(value: T) -> Result<CallToolResult, McpError> {
+    let value = to_json(value)?;
+    // Structured content must be an object
+    let structured = if value.is_object() {
+        value
+    } else {
+        json!({ "items": value })
+    };
+    Ok(CallToolResult {
+        content: vec![ToolContent::Text {
+            text: serde_json::to_string_pretty(&structured).unwrap_or_default(),
+        }],
+        structured_content: Some(structured),
+        is_error: None,
+    })
+}
+
+fn error_result(error: McpError) -> CallToolResult {
+    let text = match error {
+        McpError::Server { message, .. } => message,
+        other => other.to_string(),
+    };
+    CallToolResult {
+        content: vec![ToolContent::Text { text }],
+        structured_content: None,
+        is_error: Some(true),
+    }
+}
+
+fn str_arg<'a>(arguments: &'a HashMap<String, Value>, key: &str) -> Option<&'a str> {
+    arguments.get(key).and_then(Value::as_str)
+}
+
+fn bool_arg(arguments: &HashMap<String, Value>, key: &str) -> bool {
+    arguments
+        .get(key)
+        .and_then(Value::as_bool)
+        .unwrap_or_default()
+}
+
+/// LSP severity number for a severity name (errors are most severe)
+fn severity_rank(name: &str) -> u8 {
+    match name {
+        "error" => 1,
+        "warning" => 2,
+        "information" => 3,
+        _ => 4,
+    }
+}
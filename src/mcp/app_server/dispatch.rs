@@ -0,0 +1,128 @@
+//! JSON-RPC dispatch for the app server
+
+use super::super::client::{method_not_found, parse_params, reply, to_value};
+use super::super::protocol::*;
+use super::catalog;
+use super::types::AppDataSource;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// Answers MCP requests with data from an [`AppDataSource`]
+///
+/// Transport-agnostic: feed it inbound messages with
+/// [`handle_message`](Self::handle_message) and send back what it returns.
+pub struct McpAppServer {
+    source: Arc<dyn AppDataSource>,
+}
+
+impl McpAppServer {
+    /// Create a server publishing `source`
+    pub fn new(source: Arc<dyn AppDataSource>) -> Self {
+        Self { source }
+    }
+
+    /// Server identity sent during initialization
+    pub fn server_info() -> ServerInfo {
+        ServerInfo {
+            name: "claude-visual".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            protocol_version: None,
+        }
+    }
+
+    /// Handle one inbound message, returning the reply for requests
+    pub async fn handle_message(&self, message: Value) -> Option<Value> {
+        let method = message.get("method").and_then(|m| m.as_str());
+        let id = message.get("id").cloned().filter(|id| !id.is_null());
+
+        match (method, id) {
+            (Some(method), Some(id)) => {
+                let params = message.get("params").cloned();
+                let result = self.handle_request(method, params).await;
+                if let Err(e) = &result {
+                    tracing::debug!("MCP app server request {} failed: {}", method, e);
+                }
+                Some(reply(id, result))
+            }
+            // Notifications need no reply; we never send requests
+            (Some(_), None) | (None, Some(_)) => None,
+            (None, None) => Some(json!({
+                "jsonrpc": JSONRPC_VERSION,
+                "id": Value::Null,
+                "error": {
+                    "code": McpErrorCode::InvalidRequest as i32,
+                    "message": "Invalid request",
+                }
+            })),
+        }
+    }
+
+    /// Run a request, producing the JSON-RPC result
+    pub async fn handle_request(
+        &self,
+        method: &str,
+        params: Option<Value>,
+    ) -> Result<Value, McpError> {
+        let source = self.source.as_ref();
+        match method {
+            "initialize" => {
+                let params: InitializeParams = parse_params(params)?;
+                to_value(initialize_result(&params.protocol_version))
+            }
+            "ping" => Ok(json!({})),
+            "tools/list" => to_value(ListToolsResult {
+                tools: catalog::tools(),
+                next_cursor: None,
+            }),
+            "tools/call" => to_value(catalog::call_tool(source, parse_params(params)?).await?),
+            "resources/list" => to_value(ListResourcesResult {
+                resources: catalog::resources(),
+                next_cursor: None,
+            }),
+            "resources/templates/list" => to_value(ListResourceTemplatesResult {
+                resource_templates: catalog::resource_templates(),
+                next_cursor: None,
+            }),
+            "resources/read" => {
+                #[derive(serde::Deserialize)]
+                struct ReadResourceParams {
+                    uri: String,
+                }
+
+                let params: ReadResourceParams = parse_params(params)?;
+                let contents = catalog::read_resource(source, &params.uri).await?;
+                Ok(json!({ "contents": [to_value(contents)?] }))
+            }
+            _ => Err(method_not_found(method)),
+        }
+    }
+}
+
+/// Initialize result, answering with the client's version when we speak it
+fn initialize_result(requested: &str) -> InitializeResult {
+    let protocol_version = if is_supported_protocol_version(requested) {
+        requested
+    } else {
+        MCP_PROTOCOL_VERSION
+    };
+
+    InitializeResult {
+        protocol_version: protocol_version.to_string(),
+        capabilities: ServerCapabilities {
+            tools: Some(ToolsCapability {
+                list_changed: Some(false),
+            }),
+            resources: Some(ResourcesCapability {
+                subscribe: Some(false),
+                list_changed: Some(false),
+            }),
+            ..Default::default()
+        },
+        server_info: McpAppServer::server_info(),
+        instructions: Some(
+            "Read-only access to Claude Visual: open projects, git status, LSP \
+             diagnostics, the active debug session and past conversations."
+                .to_string(),
+        ),
+    }
+}
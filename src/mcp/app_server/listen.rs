@@ -0,0 +1,295 @@
+//! Stdio and local socket endpoints for the app server
+//!
+//! Both carry newline-delimited JSON-RPC, like the stdio transport used for
+//! other servers. The socket is a Unix domain socket; other platforms only
+//! get the standalone stdio mode.
+
+use super::super::config::McpServerConfig;
+use super::dispatch::McpAppServer;
+use super::local::LocalAppSource;
+use super::types::AppDataSource;
+use serde_json::{json, Value};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+
+/// Command line flag that runs the app as a stdio MCP server
+pub const APP_SERVER_FLAG: &str = "--mcp-server";
+
+/// Environment variable overriding the socket location
+pub const APP_SOCKET_ENV: &str = "CLAUDE_VISUAL_MCP_SOCKET";
+
+/// Name of the app server in generated MCP configurations
+pub const APP_SERVER_NAME: &str = "claude-visual";
+
+/// Where the running app listens for MCP connections
+pub fn app_socket_path() -> PathBuf {
+    if let Some(path) = std::env::var_os(APP_SOCKET_ENV) {
+        return PathBuf::from(path);
+    }
+    let dir = match dirs::runtime_dir().or_else(dirs::data_local_dir) {
+        Some(dir) => dir.join("claude-visual"),
+        // The temp dir is shared, so each user gets their own folder
+        None => std::env::temp_dir().join(format!("claude-visual-{}", user_id())),
+    };
+    dir.join("mcp.sock")
+}
+
+#[cfg(unix)]
+fn user_id() -> u32 {
+    // SAFETY: geteuid has no preconditions and cannot fail
+    unsafe { libc::geteuid() }
+}
+
+#[cfg(not(unix))]
+fn user_id() -> String {
+    std::env::var("USERNAME").unwrap_or_default()
+}
+
+/// `mcp.json` entry that launches this executable as a stdio server
+pub fn app_server_config() -> Option<McpServerConfig> {
+    let executable = std::env::current_exe().ok()?;
+    Some(super::super::config::claude_visual(&executable))
+}
+
+/// `--mcp-config` value that gives the Claude CLI access to the app server
+pub fn app_server_mcp_config() -> Option<String> {
+    let config = app_server_config()?;
+    // Only the keys the CLI understands
+    let config = json!({
+        "mcpServers": {
+            APP_SERVER_NAME: {
+                "type": "stdio",
+                "command": config.command,
+                "args": config.args,
+            }
+        }
+    });
+    Some(config.to_string())
+}
+
+/// Serve one connection until the peer closes its side
+///
+/// Requests are handled concurrently; replies are written as they complete.
+pub async fn serve_connection<R, W>(
+    server: Arc<McpAppServer>,
+    reader: R,
+    writer: W,
+) -> io::Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    let (tx, mut rx) = mpsc::unbounded_channel::<Value>();
+    let writer_task = tokio::spawn(async move {
+        let mut writer = writer;
+        while let Some(message) = rx.recv().await {
+            let mut line = message.to_string();
+            line.push('\n');
+            if writer.write_all(line.as_bytes()).await.is_err() || writer.flush().await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let message: Value = match serde_json::from_str(&line) {
+            Ok(message) => message,
+            Err(e) => {
+                let _ = tx.send(json!({
+                    "jsonrpc": "2.0",
+                    "id": Value::Null,
+                    "error": { "code": -32700, "message": format!("Parse error: {}", e) }
+                }));
+                continue;
+            }
+        };
+
+        let server = server.clone();
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            if let Some(reply) = server.handle_message(message).await {
+                let _ = tx.send(reply);
+            }
+        });
+    }
+
+    // The writer finishes once in-flight requests have replied
+    drop(tx);
+    let _ = writer_task.await;
+    Ok(())
+}
+
+/// Accept connections on a Unix socket at `path` until an error occurs
+///
+/// A stale socket left by a previous run is replaced; if another instance
+/// is still answering on it, this fails with `AddrInUse`.
+pub async fn serve_socket(server: Arc<McpAppServer>, path: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use tokio::net::{UnixListener, UnixStream};
+
+        if let Some(parent) = path.parent() {
+            ensure_private_dir(parent)?;
+        }
+        if path.exists() {
+            if UnixStream::connect(path).await.is_ok() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    format!("{} is already served", path.display()),
+                ));
+            }
+            std::fs::remove_file(path)?;
+        }
+
+        // Only the current user may connect
+        let listener = UnixListener::bind(path)?;
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+        tracing::info!("MCP app server listening on {}", path.display());
+
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let server = server.clone();
+            tokio::spawn(async move {
+                let (reader, writer) = stream.into_split();
+                if let Err(e) = serve_connection(server, reader, writer).await {
+                    tracing::debug!("MCP app server connection failed: {}", e);
+                }
+            });
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = (server, path);
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "local MCP socket is not supported on this platform",
+        ))
+    }
+}
+
+/// Create `dir` for the current user only, or check that an existing one is
+///
+/// The socket is bound inside it, so other users can neither connect nor
+/// swap the socket before its own permissions are set. A directory owned by
+/// someone else, e.g. under a shared temp dir, is refused.
+#[cfg(unix)]
+fn ensure_private_dir(dir: &Path) -> io::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+
+    std::fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(dir)?;
+    let metadata = std::fs::symlink_metadata(dir)?;
+    if !metadata.is_dir() || metadata.uid() != user_id() {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!(
+                "{} is not a directory owned by the current user",
+                dir.display()
+            ),
+        ));
+    }
+    if metadata.mode() & 0o077 != 0 {
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+    }
+    Ok(())
+}
+
+/// Serve `source` on the app socket from a background thread
+pub fn spawn_app_server(source: impl AppDataSource + 'static) {
+    let server = Arc::new(McpAppServer::new(Arc::new(source)));
+    let path = app_socket_path();
+
+    let spawned = std::thread::Builder::new()
+        .name("mcp-app-server".to_string())
+        .spawn(move || {
+            let runtime = match tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(2)
+                .enable_all()
+                .build()
+            {
+                Ok(runtime) => runtime,
+                Err(e) => {
+                    tracing::warn!("Failed to start MCP app server runtime: {}", e);
+                    return;
+                }
+            };
+            if let Err(e) = runtime.block_on(serve_socket(server, &path)) {
+                tracing::warn!("MCP app server stopped: {}", e);
+            }
+        });
+
+    if let Err(e) = spawned {
+        tracing::warn!("Failed to spawn MCP app server: {}", e);
+    }
+}
+
+/// Run the `--mcp-server` stdio mode
+///
+/// Forwards to the running app's socket so agents see its live state
+/// (language servers, debug session); without a running app, serves the
+/// stored projects and conversations directly.
+pub fn run_stdio_server() -> anyhow::Result<()> {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+
+    let result = runtime.block_on(async {
+        if proxy_to_app(&app_socket_path()).await? {
+            return Ok(());
+        }
+
+        let mut source = LocalAppSource::open()?;
+        if let Ok(cwd) = std::env::current_dir() {
+            source = source.with_root(cwd);
+        }
+        let server = Arc::new(McpAppServer::new(Arc::new(source)));
+        serve_connection(server, tokio::io::stdin(), tokio::io::stdout()).await?;
+        Ok(())
+    });
+    // Reading stdin blocks a thread that would otherwise delay exit
+    runtime.shutdown_background();
+    result
+}
+
+/// Pipe stdio to the app socket; `false` if no app is listening
+async fn proxy_to_app(path: &Path) -> io::Result<bool> {
+    #[cfg(unix)]
+    {
+        let Ok(stream) = tokio::net::UnixStream::connect(path).await else {
+            return Ok(false);
+        };
+        tracing::debug!("Forwarding MCP stdio to {}", path.display());
+
+        let (mut reader, mut writer) = stream.into_split();
+        let upstream = tokio::spawn(async move {
+            tokio::io::copy(&mut tokio::io::stdin(), &mut writer).await?;
+            // Let the app finish replying once our input ends
+            writer.shutdown().await
+        });
+        let mut stdout = tokio::io::stdout();
+        let result = tokio::io::copy(&mut reader, &mut stdout).await;
+        upstream.abort();
+        result?;
+        stdout.flush().await?;
+        Ok(true)
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        Ok(false)
+    }
+}
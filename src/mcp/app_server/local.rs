@@ -0,0 +1,265 @@
+//! App server data read from the app's own managers
+
+use super::super::protocol::{McpError, McpErrorCode};
+use super::types::*;
+use crate::debug::{DebugSession, DebugState};
+use crate::git::Repository;
+use crate::lsp::LspManager;
+use crate::storage::database::{Database, EncryptionStatus};
+use crate::storage::models::SearchFilter;
+use async_trait::async_trait;
+use parking_lot::RwLock;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// [`AppDataSource`] backed by the database, git and, when attached, the
+/// language servers and debug session
+pub struct LocalAppSource {
+    database: Arc<Database>,
+    /// Directory relative paths resolve against
+    current_directory: Arc<RwLock<Option<PathBuf>>>,
+    lsp: Option<Arc<LspManager>>,
+    debug_session: Option<Arc<tokio::sync::Mutex<DebugSession>>>,
+}
+
+impl LocalAppSource {
+    /// Create a source reading from `database`, e.g. the app's shared one
+    pub fn new(database: impl Into<Arc<Database>>) -> Self {
+        Self {
+            database: database.into(),
+            current_directory: Arc::new(RwLock::new(None)),
+            lsp: None,
+            debug_session: None,
+        }
+    }
+
+    /// Open the app database for the `--mcp-server` fallback
+    ///
+    /// Other programs launch the fallback, so it neither migrates the
+    /// database nor removes migration backups, and it refuses to start on a
+    /// database that is out of date or locked.
+    pub fn open() -> anyhow::Result<Self> {
+        Self::serve(Database::open_current()?)
+    }
+
+    /// Open the database at `path` like [`open`](Self::open)
+    pub fn open_at(path: &Path) -> anyhow::Result<Self> {
+        Self::serve(Database::open_current_at(path)?)
+    }
+
+    fn serve(database: Database) -> anyhow::Result<Self> {
+        if database.encryption_status() == EncryptionStatus::Locked {
+            anyhow::bail!(
+                "The Claude Visual database is encrypted and locked; \
+                 use the app's MCP server while the app is running and unlocked"
+            );
+        }
+        Ok(Self::new(database))
+    }
+
+    /// Follow the app's current directory
+    pub fn with_current_directory(
+        mut self,
+        current_directory: Arc<RwLock<Option<PathBuf>>>,
+    ) -> Self {
+        self.current_directory = current_directory;
+        self
+    }
+
+    /// Resolve relative paths against a fixed directory
+    pub fn with_root(self, root: PathBuf) -> Self {
+        *self.current_directory.write() = Some(root);
+        self
+    }
+
+    /// Publish diagnostics from these language servers
+    pub fn with_lsp(mut self, lsp: Arc<LspManager>) -> Self {
+        self.lsp = Some(lsp);
+        self
+    }
+
+    /// Publish this debug session
+    pub fn with_debug_session(mut self, session: Arc<tokio::sync::Mutex<DebugSession>>) -> Self {
+        self.debug_session = Some(session);
+        self
+    }
+
+    fn base_directory(&self) -> Result<PathBuf, McpError> {
+        match self.current_directory.read().clone() {
+            Some(dir) => Ok(dir),
+            None => std::env::current_dir().map_err(|e| McpError::Io(e.to_string())),
+        }
+    }
+
+    /// Resolve a path argument, which may also be a project id or name
+    fn resolve_path(&self, path: Option<&str>) -> Result<PathBuf, McpError> {
+        let Some(path) = path else {
+            return self.base_directory();
+        };
+
+        let projects = self.database.list_projects().map_err(internal)?;
+        if let Some(project) = projects
+            .into_iter()
+            .find(|p| p.id == path || p.name.eq_ignore_ascii_case(path))
+        {
+            return Ok(project.path);
+        }
+
+        let path = Path::new(path);
+        if path.is_absolute() {
+            Ok(path.to_path_buf())
+        } else {
+            Ok(self.base_directory()?.join(path))
+        }
+    }
+
+    fn debug_session(&self) -> Result<&Arc<tokio::sync::Mutex<DebugSession>>, McpError> {
+        self.debug_session
+            .as_ref()
+            .ok_or_else(|| McpError::Unavailable("Debug session".into()))
+    }
+}
+
+fn internal(error: impl std::fmt::Display) -> McpError {
+    McpError::Server {
+        code: McpErrorCode::InternalError as i32,
+        message: error.to_string(),
+    }
+}
+
+fn is_suspended(state: DebugState) -> bool {
+    matches!(state, DebugState::Stopped | DebugState::Paused)
+}
+
+#[async_trait]
+impl AppDataSource for LocalAppSource {
+    async fn projects(&self) -> Result<Vec<ProjectSummary>, McpError> {
+        let mut projects = self.database.list_projects().map_err(internal)?;
+        projects.sort_by_key(|p| std::cmp::Reverse(p.last_accessed));
+        Ok(projects
+            .into_iter()
+            .map(|p| ProjectSummary {
+                id: p.id,
+                name: p.name,
+                path: p.path,
+                is_favorite: p.is_favorite,
+                tags: p.tags,
+                last_accessed: p.last_accessed,
+            })
+            .collect())
+    }
+
+    async fn git_status(&self, path: Option<&str>) -> Result<GitStatusSnapshot, McpError> {
+        let path = self.resolve_path(path)?;
+        let repository = Repository::open(&path).map_err(|e| McpError::Server {
+            code: McpErrorCode::InvalidParams as i32,
+            message: format!("{} is not in a git repository: {}", path.display(), e),
+        })?;
+
+        Ok(GitStatusSnapshot {
+            root: repository.path().map(Path::to_path_buf),
+            summary: repository.status_summary().map_err(internal)?,
+            files: repository.status().map_err(internal)?,
+        })
+    }
+
+    async fn diagnostics(&self, path: Option<&str>) -> Result<Vec<FileDiagnostics>, McpError> {
+        let lsp = self
+            .lsp
+            .as_ref()
+            .ok_or_else(|| McpError::Unavailable("LSP diagnostics".into()))?;
+        let prefix = match path {
+            Some(path) => Some(format!(
+                "file://{}",
+                self.resolve_path(Some(path))?.display()
+            )),
+            None => None,
+        };
+
+        let mut files: Vec<FileDiagnostics> = lsp
+            .diagnostics()
+            .await
+            .into_iter()
+            .filter(|(uri, _)| {
+                prefix.as_deref().is_none_or(|prefix| {
+                    uri == prefix || uri.starts_with(&format!("{}/", prefix.trim_end_matches('/')))
+                })
+            })
+            .map(|(uri, diagnostics)| FileDiagnostics { uri, diagnostics })
+            .collect();
+        files.sort_by(|a, b| a.uri.cmp(&b.uri));
+        Ok(files)
+    }
+
+    async fn debug_stack(&self) -> Result<DebugSnapshot, McpError> {
+        let mut session = self.debug_session()?.lock().await;
+
+        // Running programs have no stack to show; keep the cached one
+        if is_suspended(session.state()) {
+            if let Err(e) = session.refresh_threads().await {
+                tracing::debug!("Failed to refresh debug threads: {}", e);
+            }
+            if let Err(e) = session.refresh_stack_trace().await {
+                tracing::debug!("Failed to refresh debug stack: {}", e);
+            }
+        }
+
+        Ok(DebugSnapshot {
+            state: format!("{:?}", session.state()).to_lowercase(),
+            thread_id: session.current_thread_id(),
+            frame_id: session.current_frame_id(),
+            threads: session.threads().to_vec(),
+            frames: session.stack_frames().to_vec(),
+        })
+    }
+
+    async fn debug_variables(
+        &self,
+        frame_id: Option<i64>,
+    ) -> Result<Vec<ScopeVariables>, McpError> {
+        let mut session = self.debug_session()?.lock().await;
+        if !is_suspended(session.state()) {
+            return Err(McpError::Server {
+                code: McpErrorCode::InvalidRequest as i32,
+                message: "The program is running; pause it to inspect variables".into(),
+            });
+        }
+
+        if let Some(frame_id) = frame_id {
+            session.set_current_frame(frame_id);
+        }
+        let scopes = session.refresh_variables().await.map_err(internal)?;
+        Ok(scopes
+            .into_iter()
+            .map(|(scope, variables)| ScopeVariables { scope, variables })
+            .collect())
+    }
+
+    async fn search_conversations(
+        &self,
+        query: &str,
+        project_id: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<ConversationHit>, McpError> {
+        let filter = SearchFilter {
+            project_id: project_id.map(str::to_string),
+            ..Default::default()
+        };
+        let results = self
+            .database
+            .search_messages_with_filter(query, &filter, limit)
+            .map_err(internal)?;
+
+        Ok(results
+            .into_iter()
+            .map(|r| ConversationHit {
+                conversation_id: r.message.conversation_id,
+                conversation_title: r.conversation_title,
+                message_id: r.message.id,
+                role: r.message.role,
+                snippet: r.snippet,
+                timestamp: r.message.timestamp,
+            })
+            .collect())
+    }
+}
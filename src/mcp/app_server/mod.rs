@@ -0,0 +1,28 @@
+//! Claude Visual as an MCP server
+//!
+//! Publishes the app's own state as read-only MCP tools and resources:
+//! projects, git status, LSP diagnostics, the active debug session and
+//! conversation search. The running app listens on a local socket; the
+//! `--mcp-server` flag serves stdio for `mcp.json` entries, forwarding to
+//! that socket when the app is open.
+
+mod catalog;
+mod dispatch;
+mod listen;
+mod local;
+mod types;
+
+#[cfg(test)]
+mod tests;
+
+pub use catalog::RESOURCE_SCHEME;
+pub use dispatch::McpAppServer;
+pub use listen::{
+    app_server_config, app_server_mcp_config, app_socket_path, run_stdio_server, serve_connection,
+    serve_socket, spawn_app_server, APP_SERVER_FLAG, APP_SERVER_NAME, APP_SOCKET_ENV,
+};
+pub use local::LocalAppSource;
+pub use types::{
+    AppDataSource, ConversationHit, DebugSnapshot, FileDiagnostics, GitStatusSnapshot,
+    ProjectSummary, ScopeVariables,
+};
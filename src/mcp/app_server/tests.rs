@@ -0,0 +1,493 @@
+//! Tests for the app server
+
+use super::*;
+use crate::mcp::protocol::McpError;
+use crate::project::manager::Project;
+use crate::storage::database::Database;
+use crate::storage::models::{Conversation, Message};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+/// Source with two projects and one search hit
+struct FixtureSource;
+
+#[async_trait]
+impl AppDataSource for FixtureSource {
+    async fn projects(&self) -> Result<Vec<ProjectSummary>, McpError> {
+        Ok(["alpha", "beta"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| ProjectSummary {
+                id: format!("p{}", i),
+                name: name.to_string(),
+                path: PathBuf::from(format!("/src/{}", name)),
+                is_favorite: i == 0,
+                tags: Vec::new(),
+                last_accessed: chrono::Utc::now(),
+            })
+            .collect())
+    }
+
+    async fn search_conversations(
+        &self,
+        query: &str,
+        project_id: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<ConversationHit>, McpError> {
+        Ok(vec![ConversationHit {
+            conversation_id: project_id.unwrap_or("c1").to_string(),
+            conversation_title: format!("limit {}", limit),
+            message_id: "m1".to_string(),
+            role: "user".to_string(),
+            snippet: format!("<mark>{}</mark>", query),
+            timestamp: chrono::Utc::now(),
+        }])
+    }
+}
+
+fn server() -> McpAppServer {
+    McpAppServer::new(Arc::new(FixtureSource))
+}
+
+async fn call(server: &McpAppServer, name: &str, arguments: Value) -> Value {
+    server
+        .handle_request(
+            "tools/call",
+            Some(json!({ "name": name, "arguments": arguments })),
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_initialize_negotiates_version() {
+    let server = server();
+    let params = |version: &str| {
+        json!({
+            "protocolVersion": version,
+            "capabilities": {},
+            "clientInfo": { "name": "test", "version": "1" }
+        })
+    };
+
+    let result = server
+        .handle_request("initialize", Some(params("2024-11-05")))
+        .await
+        .unwrap();
+    assert_eq!(result["protocolVersion"], "2024-11-05");
+    assert_eq!(result["serverInfo"]["name"], "claude-visual");
+    assert!(result["capabilities"]["tools"].is_object());
+    assert!(result["capabilities"]["resources"].is_object());
+
+    let result = server
+        .handle_request("initialize", Some(params("2099-01-01")))
+        .await
+        .unwrap();
+    assert_eq!(result["protocolVersion"], crate::mcp::MCP_PROTOCOL_VERSION);
+}
+
+#[tokio::test]
+async fn test_tools_are_listed_read_only() {
+    let result = server().handle_request("tools/list", None).await.unwrap();
+    let tools = result["tools"].as_array().unwrap();
+    let names: Vec<&str> = tools.iter().map(|t| t["name"].as_str().unwrap()).collect();
+    assert_eq!(
+        names,
+        [
+            "list_projects",
+            "git_status",
+            "get_diagnostics",
+            "debug_stack",
+            "debug_variables",
+            "search_conversations"
+        ]
+    );
+    assert!(tools
+        .iter()
+        .all(|t| t["annotations"]["readOnlyHint"] == true));
+}
+
+#[tokio::test]
+async fn test_call_tool_returns_structured_content() {
+    let server = server();
+
+    let result = call(&server, "list_projects", json!({ "favorites_only": true })).await;
+    assert!(result.get("isError").is_none());
+    let items = result["structuredContent"]["items"].as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["name"], "alpha");
+    assert_eq!(items[0]["isFavorite"], true);
+    assert!(result["content"][0]["text"]
+        .as_str()
+        .unwrap()
+        .contains("alpha"));
+
+    // Defaults are applied and strings coerced before the source is called
+    let result = call(
+        &server,
+        "search_conversations",
+        json!({ "query": "lifetimes", "project_id": "p0" }),
+    )
+    .await;
+    let hit = &result["structuredContent"]["items"][0];
+    assert_eq!(hit["conversationId"], "p0");
+    assert_eq!(hit["conversationTitle"], "limit 20");
+    assert_eq!(hit["snippet"], "<mark>lifetimes</mark>");
+
+    let result = call(
+        &server,
+        "search_conversations",
+        json!({ "query": "x", "limit": "5" }),
+    )
+    .await;
+    assert_eq!(
+        result["structuredContent"]["items"][0]["conversationTitle"],
+        "limit 5"
+    );
+}
+
+#[tokio::test]
+async fn test_call_tool_errors() {
+    let server = server();
+
+    // Invalid arguments are reported to the model
+    let result = call(&server, "search_conversations", json!({ "limit": 0 })).await;
+    assert_eq!(result["isError"], true);
+    let text = result["content"][0]["text"].as_str().unwrap();
+    assert!(text.contains("query"));
+    assert!(text.contains("limit"));
+
+    // So is data the source cannot provide
+    let result = call(&server, "debug_stack", json!({})).await;
+    assert_eq!(result["isError"], true);
+    assert_eq!(result["content"][0]["text"], "Debug session not available");
+
+    // Unknown tools are a protocol error
+    let err = server
+        .handle_request("tools/call", Some(json!({ "name": "rm_rf" })))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, McpError::Server { code: -32602, .. }));
+}
+
+#[tokio::test]
+async fn test_read_resources() {
+    let server = server();
+
+    let result = server.handle_request("resources/list", None).await.unwrap();
+    let uris: Vec<&str> = result["resources"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["uri"].as_str().unwrap())
+        .collect();
+    assert!(uris.contains(&"claude-visual://projects"));
+    assert!(uris.contains(&"claude-visual://debug/stack"));
+
+    let result = server
+        .handle_request(
+            "resources/read",
+            Some(json!({ "uri": "claude-visual://projects" })),
+        )
+        .await
+        .unwrap();
+    let contents = &result["contents"][0];
+    assert_eq!(contents["mimeType"], "application/json");
+    let projects: Value = serde_json::from_str(contents["text"].as_str().unwrap()).unwrap();
+    assert_eq!(projects.as_array().unwrap().len(), 2);
+
+    let result = server
+        .handle_request(
+            "resources/read",
+            Some(json!({ "uri": "claude-visual://conversations/search/borrow%20checker" })),
+        )
+        .await
+        .unwrap();
+    assert!(result["contents"][0]["text"]
+        .as_str()
+        .unwrap()
+        .contains("<mark>borrow checker</mark>"));
+
+    let templates = server
+        .handle_request("resources/templates/list", None)
+        .await
+        .unwrap();
+    assert_eq!(templates["resourceTemplates"].as_array().unwrap().len(), 2);
+
+    assert!(server
+        .handle_request(
+            "resources/read",
+            Some(json!({ "uri": "claude-visual://nope" })),
+        )
+        .await
+        .is_err());
+    assert!(server
+        .handle_request(
+            "resources/read",
+            Some(json!({ "uri": "claude-visual://git/status" })),
+        )
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_handle_message_replies_to_requests_only() {
+    let server = server();
+
+    let reply = server
+        .handle_message(json!({ "jsonrpc": "2.0", "id": 7, "method": "ping" }))
+        .await
+        .unwrap();
+    assert_eq!(reply["id"], 7);
+    assert_eq!(reply["result"], json!({}));
+
+    let reply = server
+        .handle_message(json!({ "jsonrpc": "2.0", "id": "a", "method": "bogus" }))
+        .await
+        .unwrap();
+    assert_eq!(reply["error"]["code"], -32601);
+
+    assert!(server
+        .handle_message(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))
+        .await
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_serve_connection() {
+    let (client, server_end) = tokio::io::duplex(4096);
+    let (server_read, server_write) = tokio::io::split(server_end);
+    let task = tokio::spawn(serve_connection(
+        Arc::new(server()),
+        server_read,
+        server_write,
+    ));
+
+    let (client_read, mut client_write) = tokio::io::split(client);
+    client_write
+        .write_all(b"{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"ping\"}\n\nnot json\n")
+        .await
+        .unwrap();
+    client_write.shutdown().await.unwrap();
+
+    let mut replies = Vec::new();
+    let mut lines = BufReader::new(client_read).lines();
+    while let Some(line) = lines.next_line().await.unwrap() {
+        replies.push(serde_json::from_str::<Value>(&line).unwrap());
+    }
+    task.await.unwrap().unwrap();
+
+    replies.sort_by_key(|r| r["id"].as_i64().unwrap_or(0));
+    assert_eq!(replies.len(), 2);
+    assert_eq!(replies[0]["error"]["code"], -32700);
+    assert_eq!(replies[1]["id"], 1);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_serve_socket() {
+    let path = std::env::temp_dir()
+        .join(format!("cv-mcp-{}", uuid::Uuid::new_v4()))
+        .join("mcp.sock");
+    let server = Arc::new(server());
+    let listener = tokio::spawn({
+        let server = server.clone();
+        let path = path.clone();
+        async move { serve_socket(server, &path).await }
+    });
+
+    let stream = loop {
+        match tokio::net::UnixStream::connect(&path).await {
+            Ok(stream) => break stream,
+            Err(_) => tokio::time::sleep(std::time::Duration::from_millis(10)).await,
+        }
+    };
+    let (reader, mut writer) = stream.into_split();
+    writer
+        .write_all(b"{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"tools/list\"}\n")
+        .await
+        .unwrap();
+    let line = BufReader::new(reader)
+        .lines()
+        .next_line()
+        .await
+        .unwrap()
+        .unwrap();
+    let reply: Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(reply["result"]["tools"].as_array().unwrap().len(), 6);
+
+    // A second instance must not take over the live socket
+    let err = serve_socket(server, &path).await.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+
+    listener.abort();
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_serve_socket_keeps_its_directory_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = std::env::temp_dir().join(format!("cv-mcp-{}", uuid::Uuid::new_v4()));
+    let mode =
+        |path: &std::path::Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+    // Created for the current user only
+    let path = dir.join("new").join("mcp.sock");
+    let listener = tokio::spawn({
+        let server = Arc::new(server());
+        let path = path.clone();
+        async move { serve_socket(server, &path).await }
+    });
+    while !path.exists() {
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(mode(path.parent().unwrap()), 0o700);
+    listener.abort();
+
+    // An existing directory open to others is tightened
+    let open = dir.join("open");
+    std::fs::create_dir_all(&open).unwrap();
+    std::fs::set_permissions(&open, std::fs::Permissions::from_mode(0o777)).unwrap();
+    let listener = tokio::spawn({
+        let server = Arc::new(server());
+        let path = open.join("mcp.sock");
+        async move { serve_socket(server, &path).await }
+    });
+    while !open.join("mcp.sock").exists() {
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(mode(&open), 0o700);
+    listener.abort();
+
+    // Another user's directory is refused (needs root to set up)
+    if unsafe { libc::geteuid() } == 0 {
+        let foreign = dir.join("foreign");
+        std::fs::create_dir_all(&foreign).unwrap();
+        std::os::unix::fs::chown(&foreign, Some(65534), Some(65534)).unwrap();
+        let err = serve_socket(Arc::new(server()), &foreign.join("mcp.sock"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+        assert!(!foreign.join("mcp.sock").exists());
+    }
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn test_app_server_config() {
+    let config = app_server_config().unwrap();
+    assert_eq!(config.args, [APP_SERVER_FLAG]);
+
+    let cli: Value = serde_json::from_str(&app_server_mcp_config().unwrap()).unwrap();
+    let entry = &cli["mcpServers"][APP_SERVER_NAME];
+    assert_eq!(entry["command"], config.command);
+    assert_eq!(entry["args"][0], "--mcp-server");
+}
+
+fn memory_database() -> Database {
+    let database = Database::open_in_memory().unwrap();
+    database.initialize().unwrap();
+    database
+}
+
+#[tokio::test]
+async fn test_local_source_reads_database() {
+    let database = memory_database();
+    let project = Project::new("crate", PathBuf::from("/work/crate"));
+    database.insert_project(&project).unwrap();
+    let conversation = Conversation::new("Parsing", Some(project.id.clone()));
+    database.insert_conversation(&conversation).unwrap();
+    database
+        .insert_message(&Message::new(
+            &conversation.id,
+            "user",
+            "How do I parse TOML in Rust?",
+        ))
+        .unwrap();
+
+    let source = LocalAppSource::new(database).with_root(PathBuf::from("/work"));
+
+    let projects = source.projects().await.unwrap();
+    assert_eq!(projects.len(), 1);
+    assert_eq!(projects[0].path, PathBuf::from("/work/crate"));
+
+    let hits = source
+        .search_conversations("toml", Some(&project.id), 10)
+        .await
+        .unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].conversation_title, "Parsing");
+    assert!(hits[0].snippet.contains("<mark>"));
+    assert!(source
+        .search_conversations("toml", Some("other"), 10)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // Not attached, so reported as unavailable
+    assert!(matches!(
+        source.diagnostics(None).await,
+        Err(McpError::Unavailable(_))
+    ));
+    assert!(matches!(
+        source.debug_stack().await,
+        Err(McpError::Unavailable(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_local_source_git_status() {
+    let dir = std::env::temp_dir().join(format!("cv-git-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    git2::Repository::init(&dir).unwrap();
+    std::fs::write(dir.join("notes.txt"), "hello").unwrap();
+
+    let database = memory_database();
+    database
+        .insert_project(&Project::new("notes", dir.clone()))
+        .unwrap();
+    let source = LocalAppSource::new(database);
+
+    // By project name
+    let status = source.git_status(Some("notes")).await.unwrap();
+    assert_eq!(status.summary.untracked_count, 1);
+    assert_eq!(status.files[0].path, "notes.txt");
+
+    let outside = std::env::temp_dir().join(format!("cv-none-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&outside).unwrap();
+    let source = source.with_root(outside.clone());
+    // Only fails when the temp dir itself is not inside a repository
+    if git2::Repository::discover(&outside).is_err() {
+        assert!(source.git_status(None).await.is_err());
+    }
+
+    let _ = std::fs::remove_dir_all(dir);
+    let _ = std::fs::remove_dir_all(outside);
+}
+
+#[test]
+fn test_local_source_refuses_locked_database() {
+    use crate::storage::database::KeySource;
+
+    let dir = std::env::temp_dir().join(format!("cv-locked-{}", uuid::Uuid::new_v4()));
+    let path = dir.join("claude-visual.db");
+    let database = Database::open_at(&path).unwrap();
+    database.initialize().unwrap();
+    assert!(LocalAppSource::open_at(&path).is_ok());
+
+    database
+        .enable_encryption(KeySource::Passphrase("correct horse"))
+        .unwrap();
+    drop(database);
+
+    let error = LocalAppSource::open_at(&path).err().unwrap();
+    assert!(error.to_string().contains("locked"));
+
+    let _ = std::fs::remove_dir_all(dir);
+}
@@ -0,0 +1,122 @@
+//! Data published by the app server
+
+use super::super::protocol::McpError;
+use crate::debug::{Scope, StackFrame, Thread, Variable};
+use crate::git::status::FileStatus;
+use crate::git::RepositoryStatusSummary;
+use crate::lsp::Diagnostic;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Source of the state the app server publishes
+///
+/// Every method reports its data as unavailable by default, so a source
+/// only implements what it can provide. Paths may also name a project by
+/// id or name.
+#[async_trait]
+pub trait AppDataSource: Send + Sync {
+    /// Projects known to the app
+    async fn projects(&self) -> Result<Vec<ProjectSummary>, McpError> {
+        Err(McpError::Unavailable("Projects".into()))
+    }
+
+    /// Git status of the repository containing `path` (default: current directory)
+    async fn git_status(&self, _path: Option<&str>) -> Result<GitStatusSnapshot, McpError> {
+        Err(McpError::Unavailable("Git status".into()))
+    }
+
+    /// Latest LSP diagnostics, limited to files under `path` when given
+    async fn diagnostics(&self, _path: Option<&str>) -> Result<Vec<FileDiagnostics>, McpError> {
+        Err(McpError::Unavailable("LSP diagnostics".into()))
+    }
+
+    /// Threads and call stack of the active debug session
+    async fn debug_stack(&self) -> Result<DebugSnapshot, McpError> {
+        Err(McpError::Unavailable("Debug session".into()))
+    }
+
+    /// Variables of a stack frame (default: the current frame)
+    async fn debug_variables(
+        &self,
+        _frame_id: Option<i64>,
+    ) -> Result<Vec<ScopeVariables>, McpError> {
+        Err(McpError::Unavailable("Debug session".into()))
+    }
+
+    /// Full-text search over stored conversations
+    async fn search_conversations(
+        &self,
+        _query: &str,
+        _project_id: Option<&str>,
+        _limit: usize,
+    ) -> Result<Vec<ConversationHit>, McpError> {
+        Err(McpError::Unavailable("Conversation search".into()))
+    }
+}
+
+/// Project open in the app
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectSummary {
+    pub id: String,
+    pub name: String,
+    pub path: PathBuf,
+    pub is_favorite: bool,
+    pub tags: Vec<String>,
+    pub last_accessed: DateTime<Utc>,
+}
+
+/// Git status of a repository
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitStatusSnapshot {
+    /// Repository working directory
+    pub root: Option<PathBuf>,
+    /// Branch, counts and upstream
+    #[serde(flatten)]
+    pub summary: RepositoryStatusSummary,
+    /// Changed files
+    pub files: Vec<FileStatus>,
+}
+
+/// Diagnostics for one document
+#[derive(Debug, Clone, Serialize)]
+pub struct FileDiagnostics {
+    pub uri: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// State of the active debug session
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugSnapshot {
+    /// Session state (`running`, `stopped`, ...)
+    pub state: String,
+    pub thread_id: Option<i64>,
+    pub frame_id: Option<i64>,
+    pub threads: Vec<Thread>,
+    /// Call stack of the current thread, innermost first
+    pub frames: Vec<StackFrame>,
+}
+
+/// Variables of one scope in a stack frame
+#[derive(Debug, Clone, Serialize)]
+pub struct ScopeVariables {
+    pub scope: Scope,
+    pub variables: Vec<Variable>,
+}
+
+/// Message matching a conversation search
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationHit {
+    pub conversation_id: String,
+    pub conversation_title: String,
+    pub message_id: String,
+    pub role: String,
+    /// Matching content with `<mark>` around the hits
+    pub snippet: String,
+    pub timestamp: DateTime<Utc>,
+}
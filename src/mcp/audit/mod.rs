@@ -0,0 +1,282 @@
+//! Audit log of MCP calls
+//!
+//! Every tool call, resource read and prompt fetch made through an
+//! [`McpManager`](super::McpManager) with an audit log attached is stored in
+//! the `mcp_calls` table: server, arguments (with secrets redacted), a
+//! summary of the result, duration and the approval decision. Recorded
+//! calls can be listed per conversation and re-run.
+
+mod redact;
+
+#[cfg(test)]
+mod tests;
+
+pub use redact::{RedactionRules, REDACTED};
+
+pub(crate) use redact::contains_redacted;
+
+use super::protocol::*;
+use crate::storage::database::Database;
+use crate::storage::models::{McpCallApproval, McpCallFilter, McpCallKind, McpCallRecord};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Longest result summary stored, in characters
+const SUMMARY_LEN: usize = 500;
+
+/// Conversation and approval a call is made under
+#[derive(Debug, Clone, Default)]
+pub struct McpCallContext {
+    /// Conversation the call belongs to
+    pub conversation_id: Option<String>,
+    /// How the call was approved
+    pub approval: McpCallApproval,
+}
+
+impl McpCallContext {
+    /// Context for a call made in a conversation
+    pub fn for_conversation(conversation_id: impl Into<String>) -> Self {
+        Self {
+            conversation_id: Some(conversation_id.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Set the approval decision
+    pub fn with_approval(mut self, approval: McpCallApproval) -> Self {
+        self.approval = approval;
+        self
+    }
+}
+
+/// Result of re-running a recorded call
+#[derive(Debug, Clone)]
+pub enum McpCallOutput {
+    Tool(CallToolResult),
+    Resource(ResourceContents),
+    Prompt(GetPromptResult),
+}
+
+/// Writes MCP calls to the database
+///
+/// Cloning shares the database. Failures to record are logged and never
+/// fail the call itself.
+#[derive(Clone)]
+pub struct McpAuditLog {
+    database: Arc<Database>,
+    rules: Arc<RedactionRules>,
+}
+
+impl McpAuditLog {
+    /// Create a log writing to `database`, e.g. the app's shared one
+    pub fn new(database: impl Into<Arc<Database>>) -> Self {
+        Self {
+            database: database.into(),
+            rules: Arc::new(RedactionRules::default()),
+        }
+    }
+
+    /// Open the app database
+    pub fn open() -> anyhow::Result<Self> {
+        let database = Database::open()?;
+        database.initialize()?;
+        Ok(Self::new(database))
+    }
+
+    /// Replace the redaction rules
+    pub fn with_rules(mut self, rules: RedactionRules) -> Self {
+        self.rules = Arc::new(rules);
+        self
+    }
+
+    /// Redaction rules in use
+    pub fn rules(&self) -> &RedactionRules {
+        &self.rules
+    }
+
+    /// Store a call, redacting its arguments and summary
+    ///
+    /// The insert runs on the database's writer thread, so awaiting this
+    /// never blocks an async worker.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn record(
+        &self,
+        server: &str,
+        kind: McpCallKind,
+        name: &str,
+        arguments: Option<Value>,
+        outcome: Result<String, String>,
+        duration: Duration,
+        context: &McpCallContext,
+    ) -> Option<String> {
+        let record = self.entry(server, kind, name, arguments, outcome, duration, context);
+        let server = record.server.clone();
+        let stored = self
+            .database
+            .write_async(move |db| db.insert_mcp_call(&record).map(|()| record.id))
+            .await;
+        match stored {
+            Ok(id) => Some(id),
+            Err(e) => {
+                tracing::warn!("Failed to record MCP call to {}: {}", server, e);
+                None
+            }
+        }
+    }
+
+    /// Store a call that was refused and never sent
+    pub fn record_denied(
+        &self,
+        server: &str,
+        kind: McpCallKind,
+        name: &str,
+        arguments: Option<Value>,
+        conversation_id: Option<String>,
+    ) -> Option<String> {
+        let context = McpCallContext {
+            conversation_id,
+            approval: McpCallApproval::Denied,
+        };
+        self.store(self.entry(
+            server,
+            kind,
+            name,
+            arguments,
+            Err("Denied by user".to_string()),
+            Duration::ZERO,
+            &context,
+        ))
+    }
+
+    /// The stored form of a call, with secrets redacted
+    #[allow(clippy::too_many_arguments)]
+    fn entry(
+        &self,
+        server: &str,
+        kind: McpCallKind,
+        name: &str,
+        arguments: Option<Value>,
+        outcome: Result<String, String>,
+        duration: Duration,
+        context: &McpCallContext,
+    ) -> McpCallRecord {
+        let (is_error, summary) = match outcome {
+            Ok(summary) => (false, summary),
+            Err(error) => (true, error),
+        };
+        McpCallRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            conversation_id: context.conversation_id.clone(),
+            server: server.to_string(),
+            kind,
+            name: self.rules.redact_text(name),
+            arguments: arguments.map(|a| self.rules.redact_value(&a)),
+            result_summary: truncate(&self.rules.redact_text(&summary)),
+            is_error,
+            duration_ms: duration.as_millis() as u64,
+            approval: context.approval,
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    fn store(&self, record: McpCallRecord) -> Option<String> {
+        match self.database.insert_mcp_call(&record) {
+            Ok(()) => Some(record.id),
+            Err(e) => {
+                tracing::warn!("Failed to record MCP call to {}: {}", record.server, e);
+                None
+            }
+        }
+    }
+
+    /// A recorded call
+    pub fn call(&self, id: &str) -> Result<Option<McpCallRecord>, McpError> {
+        self.database.get_mcp_call(id).map_err(storage_error)
+    }
+
+    /// Recorded calls matching `filter`, newest first
+    pub fn calls(
+        &self,
+        filter: &McpCallFilter,
+        limit: usize,
+    ) -> Result<Vec<McpCallRecord>, McpError> {
+        self.database
+            .list_mcp_calls(filter, limit)
+            .map_err(storage_error)
+    }
+
+    /// Recorded calls of a conversation, newest first
+    pub fn conversation_calls(
+        &self,
+        conversation_id: &str,
+        limit: usize,
+    ) -> Result<Vec<McpCallRecord>, McpError> {
+        self.calls(
+            &McpCallFilter {
+                conversation_id: Some(conversation_id.to_string()),
+                ..Default::default()
+            },
+            limit,
+        )
+    }
+
+    /// Delete calls older than `max_age`; returns how many were removed
+    pub fn prune(&self, max_age: chrono::Duration) -> Result<usize, McpError> {
+        self.database
+            .delete_mcp_calls_before(chrono::Utc::now() - max_age)
+            .map_err(storage_error)
+    }
+}
+
+fn storage_error(error: anyhow::Error) -> McpError {
+    McpError::Io(format!("Audit log: {}", error))
+}
+
+fn truncate(text: &str) -> String {
+    match text.char_indices().nth(SUMMARY_LEN) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// Summary of a tool result: its text, or a note per non-text item
+pub(crate) fn summarize_tool_result(result: &CallToolResult) -> String {
+    let mut parts: Vec<String> = result
+        .content
+        .iter()
+        .map(|content| match content {
+            ToolContent::Text { text } => text.clone(),
+            ToolContent::Image { mime_type, data } => {
+                format!("[image {} ({} bytes)]", mime_type, data.len() * 3 / 4)
+            }
+            ToolContent::Resource { resource } => format!("[resource {}]", resource.uri),
+        })
+        .collect();
+    if parts.is_empty() {
+        if let Some(structured) = &result.structured_content {
+            parts.push(structured.to_string());
+        }
+    }
+    parts.join("\n")
+}
+
+/// Summary of resource contents
+pub(crate) fn summarize_resource(contents: &ResourceContents) -> String {
+    let mime = contents.mime_type.as_deref().unwrap_or("unknown type");
+    match (&contents.text, &contents.blob) {
+        (Some(text), _) => format!("{} ({} chars): {}", mime, text.chars().count(), text),
+        (None, Some(blob)) => format!("{} ({} bytes)", mime, blob.len() * 3 / 4),
+        (None, None) => format!("{} (empty)", mime),
+    }
+}
+
+/// Summary of a fetched prompt
+pub(crate) fn summarize_prompt(result: &GetPromptResult) -> String {
+    let count = result.messages.len();
+    let messages = format!("{} message{}", count, if count == 1 { "" } else { "s" });
+    match &result.description {
+        Some(description) => format!("{}: {}", messages, description),
+        None => messages,
+    }
+}
@@ -0,0 +1,176 @@
+//! Redaction of secrets in recorded arguments and results
+
+use regex::Regex;
+use serde_json::Value;
+
+/// Replacement for redacted values
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Argument names whose values are always redacted
+///
+/// A name matches when it ends with the key's words, whatever its case and
+/// separators: `token` covers `token`, `access_token` and `refreshToken` but
+/// not `max_tokens`, `token_count` or `tokenizer`.
+const SECRET_KEYS: &[&str] = &[
+    "password",
+    "passwd",
+    "secret",
+    "secret_key",
+    "token",
+    "api_key",
+    "apikey",
+    "access_key",
+    "authorization",
+    "credential",
+    "credentials",
+    "private_key",
+    "cookie",
+];
+
+/// Values that look like secrets wherever they appear
+const SECRET_PATTERNS: &[&str] = &[
+    // Bearer and basic auth headers
+    r"(?i)\b(bearer|basic)\s+[A-Za-z0-9._~+/=-]{8,}",
+    // Provider key prefixes (Anthropic, OpenAI, GitHub, Slack, AWS, Google)
+    r"\bsk-[A-Za-z0-9_-]{16,}",
+    r"\bgh[pousr]_[A-Za-z0-9]{20,}",
+    r"\bgithub_pat_[A-Za-z0-9_]{20,}",
+    r"\bxox[abprs]-[A-Za-z0-9-]{10,}",
+    r"\bAKIA[0-9A-Z]{16}\b",
+    r"\bAIza[0-9A-Za-z_-]{35}",
+    // Credentials in URLs
+    r"(?i)(://[^/\s:@]+:)[^@\s/]+@",
+];
+
+/// Rules deciding what is hidden before a call is stored
+#[derive(Debug, Clone)]
+pub struct RedactionRules {
+    /// Argument names as lowercase words
+    keys: Vec<Vec<String>>,
+    /// Patterns matched against string values
+    patterns: Vec<Regex>,
+}
+
+impl Default for RedactionRules {
+    fn default() -> Self {
+        Self {
+            keys: SECRET_KEYS.iter().map(|k| name_words(k)).collect(),
+            patterns: SECRET_PATTERNS
+                .iter()
+                .map(|p| Regex::new(p).expect("built-in redaction patterns are valid"))
+                .collect(),
+        }
+    }
+}
+
+impl RedactionRules {
+    /// Rules that redact nothing
+    pub fn none() -> Self {
+        Self {
+            keys: Vec::new(),
+            patterns: Vec::new(),
+        }
+    }
+
+    /// Also redact arguments whose name ends with the words of `key`
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.keys.push(name_words(&key.into()));
+        self
+    }
+
+    /// Also redact text matching `pattern`
+    pub fn with_pattern(mut self, pattern: &str) -> Result<Self, regex::Error> {
+        self.patterns.push(Regex::new(pattern)?);
+        Ok(self)
+    }
+
+    /// Whether values of an argument with this name are hidden entirely
+    pub fn is_secret_key(&self, name: &str) -> bool {
+        let words = name_words(name);
+        self.keys
+            .iter()
+            .any(|key| !key.is_empty() && words.ends_with(key))
+    }
+
+    /// Copy of `value` with secrets replaced by [`REDACTED`]
+    pub fn redact_value(&self, value: &Value) -> Value {
+        match value {
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(key, value)| {
+                        let value = if self.is_secret_key(key) && !value.is_null() {
+                            Value::String(REDACTED.to_string())
+                        } else {
+                            self.redact_value(value)
+                        };
+                        (key.clone(), value)
+                    })
+                    .collect(),
+            ),
+            Value::Array(items) => {
+                Value::Array(items.iter().map(|v| self.redact_value(v)).collect())
+            }
+            Value::String(text) => Value::String(self.redact_text(text)),
+            other => other.clone(),
+        }
+    }
+
+    /// Copy of `text` with secret-looking parts replaced by [`REDACTED`]
+    pub fn redact_text(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for pattern in &self.patterns {
+            if pattern.is_match(&text) {
+                text = pattern
+                    .replace_all(&text, |caps: &regex::Captures<'_>| {
+                        // Keep a captured prefix (scheme, auth type) for context
+                        match caps.get(1) {
+                            Some(prefix) if prefix.as_str().starts_with("://") => {
+                                format!("{}{}@", prefix.as_str(), REDACTED)
+                            }
+                            Some(prefix) => format!("{} {}", prefix.as_str(), REDACTED),
+                            None => REDACTED.to_string(),
+                        }
+                    })
+                    .into_owned();
+            }
+        }
+        text
+    }
+}
+
+/// Lowercase words of an argument name, split at separators and at
+/// lower-to-upper case changes (`refreshToken` and `REFRESH-TOKEN` both give
+/// `refresh`, `token`)
+fn name_words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut after_lower = false;
+    for c in name.chars() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            after_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && after_lower {
+            words.push(std::mem::take(&mut current));
+        }
+        after_lower = c.is_lowercase() || c.is_numeric();
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// Whether any string in `value` still holds a redacted placeholder
+pub(crate) fn contains_redacted(value: &Value) -> bool {
+    match value {
+        Value::String(text) => text.contains(REDACTED),
+        Value::Array(items) => items.iter().any(contains_redacted),
+        Value::Object(map) => map.values().any(contains_redacted),
+        _ => false,
+    }
+}
@@ -0,0 +1,59 @@
+//! Connection management for MCP client
+
+use super::super::protocol::McpError;
+use super::super::transport;
+use super::types::{McpClient, McpConnection};
+
+impl McpClient {
+    /// Open the connection to the server (spawns the process for stdio servers)
+    pub async fn start(&mut self) -> Result<(), McpError> {
+        if self.connection.is_some() {
+            return Err(McpError::Connection("Server already running".into()));
+        }
+
+        // Expanded here only, so `${VAR}` references are what gets saved
+        let config = self
+            .config
+            .expand_env()
+            .map_err(|e| McpError::Connection(format!("{}: {}", self.name, e)))?;
+        let (transport, inbound) =
+            transport::connect_with_stderr(&config, self.stderr.clone()).await?;
+        self.connection = Some(McpConnection::open(
+            self.name.clone(),
+            transport,
+            inbound,
+            self.event_tx.clone(),
+            self.handler.clone(),
+            self.request_timeout,
+        ));
+
+        Ok(())
+    }
+
+    /// Close the connection to the server
+    pub async fn stop(&mut self) -> Result<(), McpError> {
+        if let Some(connection) = self.connection.take() {
+            connection.close().await;
+        }
+
+        self.initialized = false;
+        self.server_info = None;
+        self.protocol_version = None;
+        self.capabilities = None;
+        self.tools.clear();
+        self.resources.clear();
+        self.resource_templates.clear();
+        self.prompts.clear();
+
+        Ok(())
+    }
+
+    /// Reopen the connection and initialize a fresh session
+    pub async fn reconnect(&mut self) -> Result<(), McpError> {
+        self.stop().await?;
+        self.start().await?;
+        // Boxed: initialize() sends requests that may reconnect
+        Box::pin(self.initialize()).await?;
+        Ok(())
+    }
+}
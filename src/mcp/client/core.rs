@@ -2,8 +2,12 @@
 
 use super::super::config::McpServerConfig;
 use super::super::protocol::*;
-use super::types::McpClient;
-use std::sync::atomic::AtomicU64;
+use super::super::transport::StderrLog;
+use super::handler::McpRequestHandler;
+use super::types::{McpClient, McpConnection, McpEvent, DEFAULT_REQUEST_TIMEOUT};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
 
 impl McpClient {
     /// Create a new MCP client for a server configuration
@@ -11,29 +15,66 @@ impl McpClient {
         Self {
             name: name.into(),
             config,
-            process: None,
-            stdin: None,
-            stdout: None,
-            request_id: AtomicU64::new(1),
+            connection: None,
+            event_tx: None,
+            handler: None,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            stderr: StderrLog::default(),
             server_info: None,
+            protocol_version: None,
             capabilities: None,
             tools: Vec::new(),
             resources: Vec::new(),
+            resource_templates: Vec::new(),
             prompts: Vec::new(),
             initialized: false,
         }
     }
 
+    /// Forward server notifications to a channel
+    pub fn with_event_sender(mut self, event_tx: mpsc::UnboundedSender<McpEvent>) -> Self {
+        self.event_tx = Some(event_tx);
+        self
+    }
+
+    /// Answer server requests (roots, sampling, elicitation) with a handler
+    pub fn with_handler(mut self, handler: Arc<dyn McpRequestHandler>) -> Self {
+        self.handler = Some(handler);
+        self
+    }
+
+    /// Set the default request timeout
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Keep the server's stderr in a shared log
+    pub fn with_stderr_log(mut self, stderr: StderrLog) -> Self {
+        self.stderr = stderr;
+        self
+    }
+
     /// Get the server name
     pub fn name(&self) -> &str {
         &self.name
     }
 
+    /// Stderr output of the server process
+    pub fn stderr_log(&self) -> &StderrLog {
+        &self.stderr
+    }
+
     /// Get server info (available after initialization)
     pub fn server_info(&self) -> Option<&ServerInfo> {
         self.server_info.as_ref()
     }
 
+    /// Protocol version agreed with the server (available after initialization)
+    pub fn protocol_version(&self) -> Option<&str> {
+        self.protocol_version.as_deref()
+    }
+
     /// Get server capabilities
     pub fn capabilities(&self) -> Option<&ServerCapabilities> {
         self.capabilities.as_ref()
@@ -49,6 +90,11 @@ impl McpClient {
         &self.resources
     }
 
+    /// Get available resource templates
+    pub fn resource_templates(&self) -> &[ResourceTemplate] {
+        &self.resource_templates
+    }
+
     /// Get available prompts
     pub fn prompts(&self) -> &[McpPrompt] {
         &self.prompts
@@ -59,14 +105,20 @@ impl McpClient {
         self.initialized
     }
 
-    /// Check if process is running
+    /// Check if the server connection is open
     pub fn is_running(&self) -> bool {
-        self.process.is_some()
+        self.connection.as_ref().is_some_and(|c| c.is_connected())
+    }
+
+    /// Session id assigned by an HTTP server
+    pub fn session_id(&self) -> Option<String> {
+        self.connection.as_ref().and_then(|c| c.session_id())
     }
-}
 
-impl Drop for McpClient {
-    fn drop(&mut self) {
-        let _ = super::process::stop(self);
+    /// Handle for issuing concurrent requests without borrowing the client
+    pub fn connection(&self) -> Result<McpConnection, McpError> {
+        self.connection
+            .clone()
+            .ok_or_else(|| McpError::Connection("Server not running".into()))
     }
 }
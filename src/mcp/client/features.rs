@@ -1,39 +1,58 @@
 //! Feature discovery and operations (tools, resources, prompts)
 
 use super::super::protocol::*;
-use super::messaging::send_request;
-use super::types::McpClient;
+use super::super::schema::prepare_arguments;
+use super::messaging::{parse_result, send_request, send_request_with_timeout};
+use super::types::{McpClient, McpConnection};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::time::Duration;
 
 impl McpClient {
     /// Initialize the MCP connection
-    pub fn initialize(&mut self) -> Result<InitializeResult, McpError> {
+    pub async fn initialize(&mut self) -> Result<InitializeResult, McpError> {
         if !self.is_running() {
             return Err(McpError::NotInitialized);
         }
 
-        let params = InitializeParams::default();
-        let response: InitializeResult = send_request(self, "initialize", Some(params))?;
+        let params = InitializeParams {
+            capabilities: self
+                .handler
+                .as_ref()
+                .map(|h| h.capabilities())
+                .unwrap_or_default(),
+            ..Default::default()
+        };
+        let response: InitializeResult = send_request(self, "initialize", Some(params)).await?;
+
+        // The server answers with our version or one it prefers
+        if !is_supported_protocol_version(&response.protocol_version) {
+            let version = response.protocol_version;
+            self.stop().await?;
+            return Err(McpError::UnsupportedProtocolVersion(version));
+        }
+        self.connection()?
+            .set_protocol_version(&response.protocol_version);
+        self.protocol_version = Some(response.protocol_version.clone());
 
         self.server_info = Some(response.server_info.clone());
         self.capabilities = Some(response.capabilities.clone());
 
         // Send initialized notification
-        super::messaging::send_notification(self, "notifications/initialized", None::<()>)?;
+        super::messaging::send_notification(self, "notifications/initialized", None::<()>).await?;
 
         self.initialized = true;
 
         // Discover available features
-        self.refresh_tools()?;
-        self.refresh_resources()?;
-        self.refresh_prompts()?;
+        self.refresh_tools().await?;
+        self.refresh_resources().await?;
+        self.refresh_prompts().await?;
 
         Ok(response)
     }
 
     /// Refresh the list of available tools
-    pub fn refresh_tools(&mut self) -> Result<(), McpError> {
+    pub async fn refresh_tools(&mut self) -> Result<(), McpError> {
         if !self.initialized {
             return Err(McpError::NotInitialized);
         }
@@ -45,14 +64,13 @@ impl McpClient {
             }
         }
 
-        let result: ListToolsResult = send_request(self, "tools/list", None::<()>)?;
-        self.tools = result.tools;
+        self.tools = list_all::<ListToolsResult>(self, "tools/list").await?;
 
         Ok(())
     }
 
     /// Refresh the list of available resources
-    pub fn refresh_resources(&mut self) -> Result<(), McpError> {
+    pub async fn refresh_resources(&mut self) -> Result<(), McpError> {
         if !self.initialized {
             return Err(McpError::NotInitialized);
         }
@@ -64,14 +82,25 @@ impl McpClient {
             }
         }
 
-        let result: ListResourcesResult = send_request(self, "resources/list", None::<()>)?;
-        self.resources = result.resources;
+        self.resources = list_all::<ListResourcesResult>(self, "resources/list").await?;
+
+        // Templates arrived with 2024-11-05 but not every server implements them
+        self.resource_templates =
+            match list_all::<ListResourceTemplatesResult>(self, "resources/templates/list").await {
+                Ok(templates) => templates,
+                Err(McpError::Server { code, .. })
+                    if code == McpErrorCode::MethodNotFound as i32 =>
+                {
+                    Vec::new()
+                }
+                Err(e) => return Err(e),
+            };
 
         Ok(())
     }
 
     /// Refresh the list of available prompts
-    pub fn refresh_prompts(&mut self) -> Result<(), McpError> {
+    pub async fn refresh_prompts(&mut self) -> Result<(), McpError> {
         if !self.initialized {
             return Err(McpError::NotInitialized);
         }
@@ -83,14 +112,13 @@ impl McpClient {
             }
         }
 
-        let result: ListPromptsResult = send_request(self, "prompts/list", None::<()>)?;
-        self.prompts = result.prompts;
+        self.prompts = list_all::<ListPromptsResult>(self, "prompts/list").await?;
 
         Ok(())
     }
 
     /// Call a tool
-    pub fn call_tool(
+    pub async fn call_tool(
         &mut self,
         name: &str,
         arguments: Option<HashMap<String, Value>>,
@@ -101,14 +129,49 @@ impl McpClient {
 
         let params = CallToolParams {
             name: name.to_string(),
-            arguments,
+            arguments: self.check_arguments(name, arguments)?,
+            meta: None,
+        };
+
+        send_request(self, "tools/call", Some(params)).await
+    }
+
+    /// Call a tool, waiting at most `timeout` for the result
+    pub async fn call_tool_with_timeout(
+        &mut self,
+        name: &str,
+        arguments: Option<HashMap<String, Value>>,
+        timeout: Duration,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.initialized {
+            return Err(McpError::NotInitialized);
+        }
+
+        let params = CallToolParams {
+            name: name.to_string(),
+            arguments: self.check_arguments(name, arguments)?,
+            meta: None,
         };
 
-        send_request(self, "tools/call", Some(params))
+        send_request_with_timeout(self, "tools/call", Some(params), Some(timeout)).await
+    }
+
+    /// Validate arguments against a known tool's schema, filling defaults
+    ///
+    /// Tools missing from the cached list are left for the server to check.
+    pub fn check_arguments(
+        &self,
+        name: &str,
+        arguments: Option<HashMap<String, Value>>,
+    ) -> Result<Option<HashMap<String, Value>>, McpError> {
+        match self.tools.iter().find(|t| t.name == name) {
+            Some(tool) => prepare_arguments(tool, arguments.unwrap_or_default()).map(Some),
+            None => Ok(arguments),
+        }
     }
 
     /// Read a resource
-    pub fn read_resource(&mut self, uri: &str) -> Result<ResourceContents, McpError> {
+    pub async fn read_resource(&mut self, uri: &str) -> Result<ResourceContents, McpError> {
         if !self.initialized {
             return Err(McpError::NotInitialized);
         }
@@ -127,7 +190,7 @@ impl McpClient {
             contents: Vec<ResourceContents>,
         }
 
-        let result: ReadResourceResult = send_request(self, "resources/read", Some(params))?;
+        let result: ReadResourceResult = send_request(self, "resources/read", Some(params)).await?;
         result
             .contents
             .into_iter()
@@ -136,7 +199,7 @@ impl McpClient {
     }
 
     /// Get a prompt
-    pub fn get_prompt(
+    pub async fn get_prompt(
         &mut self,
         name: &str,
         arguments: Option<HashMap<String, String>>,
@@ -157,6 +220,264 @@ impl McpClient {
             arguments,
         };
 
-        send_request(self, "prompts/get", Some(params))
+        send_request(self, "prompts/get", Some(params)).await
+    }
+
+    /// Suggest values for a prompt argument or resource template variable
+    ///
+    /// `context` holds arguments already filled in. Servers that do not
+    /// offer completion yield no suggestions.
+    pub async fn complete(
+        &mut self,
+        reference: CompletionReference,
+        argument: &str,
+        value: &str,
+        context: Option<HashMap<String, String>>,
+    ) -> Result<Completion, McpError> {
+        if !self.initialized {
+            return Err(McpError::NotInitialized);
+        }
+
+        // Declared as a capability since 2025-03-26
+        let declared = self
+            .capabilities
+            .as_ref()
+            .is_some_and(|c| c.completions.is_some())
+            || self.protocol_version.as_deref() == Some("2024-11-05");
+        if !declared {
+            return Ok(Completion::default());
+        }
+
+        let params = CompleteParams {
+            reference,
+            argument: CompletionArgument {
+                name: argument.to_string(),
+                value: value.to_string(),
+            },
+            context: context.map(|arguments| CompletionContext { arguments }),
+        };
+
+        match send_request::<_, CompleteResult>(self, "completion/complete", Some(params)).await {
+            Ok(result) => Ok(result.completion),
+            Err(McpError::Server { code, .. }) if code == McpErrorCode::MethodNotFound as i32 => {
+                Ok(Completion::default())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Suggest values for a prompt argument
+    pub async fn complete_prompt_argument(
+        &mut self,
+        prompt: &str,
+        argument: &str,
+        value: &str,
+    ) -> Result<Completion, McpError> {
+        let reference = CompletionReference::Prompt {
+            name: prompt.to_string(),
+        };
+        self.complete(reference, argument, value, None).await
+    }
+
+    /// Subscribe to `notifications/resources/updated` for a resource
+    pub async fn subscribe_resource(&mut self, uri: &str) -> Result<(), McpError> {
+        self.resource_subscription("resources/subscribe", uri).await
+    }
+
+    /// Stop receiving updates for a resource
+    pub async fn unsubscribe_resource(&mut self, uri: &str) -> Result<(), McpError> {
+        self.resource_subscription("resources/unsubscribe", uri)
+            .await
+    }
+
+    async fn resource_subscription(&mut self, method: &str, uri: &str) -> Result<(), McpError> {
+        if !self.initialized {
+            return Err(McpError::NotInitialized);
+        }
+
+        // Check if server supports subscriptions
+        let supported = self
+            .capabilities
+            .as_ref()
+            .and_then(|c| c.resources.as_ref())
+            .and_then(|r| r.subscribe)
+            .unwrap_or(false);
+        if !supported {
+            return Err(McpError::Protocol(format!(
+                "Server '{}' does not support resource subscriptions",
+                self.name
+            )));
+        }
+
+        let _: Value = send_request(self, method, Some(serde_json::json!({ "uri": uri }))).await?;
+        Ok(())
+    }
+
+    /// Set the minimum level of `notifications/message` the server sends
+    pub async fn set_log_level(&mut self, level: &str) -> Result<(), McpError> {
+        if !self.initialized {
+            return Err(McpError::NotInitialized);
+        }
+
+        // Check if server supports logging
+        if self
+            .capabilities
+            .as_ref()
+            .is_some_and(|c| c.logging.is_none())
+        {
+            return Ok(());
+        }
+
+        let _: Value = send_request(
+            self,
+            "logging/setLevel",
+            Some(serde_json::json!({ "level": level })),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Tell the server the handler's roots changed
+    pub async fn notify_roots_changed(&self) -> Result<(), McpError> {
+        let declared = self
+            .handler
+            .as_ref()
+            .is_some_and(|h| h.capabilities().roots.is_some());
+        if !self.initialized || !declared {
+            return Ok(());
+        }
+        super::messaging::send_notification(self, "notifications/roots/list_changed", None::<()>)
+            .await
+    }
+}
+
+/// Upper bound on pages fetched by one refresh
+const MAX_PAGES: usize = 100;
+
+/// One page of a paginated list result
+trait Page: serde::de::DeserializeOwned {
+    type Item;
+
+    fn into_parts(self) -> (Vec<Self::Item>, Option<String>);
+}
+
+impl Page for ListToolsResult {
+    type Item = McpTool;
+
+    fn into_parts(self) -> (Vec<McpTool>, Option<String>) {
+        (self.tools, self.next_cursor)
+    }
+}
+
+impl Page for ListResourcesResult {
+    type Item = McpResource;
+
+    fn into_parts(self) -> (Vec<McpResource>, Option<String>) {
+        (self.resources, self.next_cursor)
+    }
+}
+
+impl Page for ListResourceTemplatesResult {
+    type Item = ResourceTemplate;
+
+    fn into_parts(self) -> (Vec<ResourceTemplate>, Option<String>) {
+        (self.resource_templates, self.next_cursor)
+    }
+}
+
+impl Page for ListPromptsResult {
+    type Item = McpPrompt;
+
+    fn into_parts(self) -> (Vec<McpPrompt>, Option<String>) {
+        (self.prompts, self.next_cursor)
+    }
+}
+
+/// Fetch every page of a list method, following `nextCursor`
+async fn list_all<P: Page>(client: &mut McpClient, method: &str) -> Result<Vec<P::Item>, McpError> {
+    let mut items = Vec::new();
+    let mut cursor: Option<String> = None;
+
+    for _ in 0..MAX_PAGES {
+        let params = cursor
+            .as_ref()
+            .map(|cursor| serde_json::json!({ "cursor": cursor }));
+        let page: P = send_request(client, method, params).await?;
+        let (page_items, next_cursor) = page.into_parts();
+        items.extend(page_items);
+
+        match next_cursor {
+            // A server repeating its cursor would loop forever
+            Some(next) if cursor.as_ref() != Some(&next) => cursor = Some(next),
+            _ => return Ok(items),
+        }
+    }
+
+    tracing::warn!(
+        "Stopped listing {} from '{}' after {} pages",
+        method,
+        client.name,
+        MAX_PAGES
+    );
+    Ok(items)
+}
+
+impl McpConnection {
+    /// Call a tool without holding the client (calls may run concurrently)
+    pub async fn call_tool(
+        &self,
+        name: &str,
+        arguments: Option<HashMap<String, Value>>,
+    ) -> Result<CallToolResult, McpError> {
+        self.call_tool_with_timeout(name, arguments, self.inner.timeout)
+            .await
+    }
+
+    /// Call a tool, waiting at most `timeout` for the result
+    pub async fn call_tool_with_timeout(
+        &self,
+        name: &str,
+        arguments: Option<HashMap<String, Value>>,
+        timeout: Duration,
+    ) -> Result<CallToolResult, McpError> {
+        self.call_tool_inner(name, arguments, None, timeout).await
+    }
+
+    /// Call a tool, asking the server to report progress
+    ///
+    /// Progress arrives as [`McpEvent::Progress`](super::McpEvent::Progress)
+    /// carrying `progress_token`.
+    pub async fn call_tool_with_progress(
+        &self,
+        name: &str,
+        arguments: Option<HashMap<String, Value>>,
+        progress_token: impl Into<String>,
+    ) -> Result<CallToolResult, McpError> {
+        let meta = RequestMeta {
+            progress_token: Some(Value::String(progress_token.into())),
+        };
+        self.call_tool_inner(name, arguments, Some(meta), self.inner.timeout)
+            .await
+    }
+
+    async fn call_tool_inner(
+        &self,
+        name: &str,
+        arguments: Option<HashMap<String, Value>>,
+        meta: Option<RequestMeta>,
+        timeout: Duration,
+    ) -> Result<CallToolResult, McpError> {
+        let params = CallToolParams {
+            name: name.to_string(),
+            arguments,
+            meta,
+        };
+        let params = serde_json::to_value(params)
+            .map_err(|e| McpError::Protocol(format!("Failed to serialize params: {}", e)))?;
+
+        let result = self
+            .request_with_timeout("tools/call", Some(params), timeout)
+            .await?;
+        parse_result(result)
     }
 }
@@ -0,0 +1,97 @@
+//! Handling of requests servers send to the client
+
+use super::super::protocol::*;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+/// Answers server-initiated requests (`roots/list`, `sampling/createMessage`,
+/// `elicitation/create`)
+///
+/// Each request runs on its own task, so a handler may wait for the user
+/// without holding up other traffic. Only capabilities returned by
+/// [`capabilities`](Self::capabilities) are advertised to servers.
+#[async_trait]
+pub trait McpRequestHandler: Send + Sync {
+    /// Client capabilities to declare during initialization
+    fn capabilities(&self) -> McpCapabilities;
+
+    /// Roots the server may operate on
+    async fn list_roots(&self, _server: &str) -> Result<ListRootsResult, McpError> {
+        Err(method_not_found("roots/list"))
+    }
+
+    /// Generate a completion on the server's behalf
+    async fn create_message(
+        &self,
+        _server: &str,
+        _params: CreateMessageParams,
+    ) -> Result<CreateMessageResult, McpError> {
+        Err(method_not_found("sampling/createMessage"))
+    }
+
+    /// Ask the user for structured input
+    async fn elicit(&self, _server: &str, _params: ElicitParams) -> Result<ElicitResult, McpError> {
+        Err(method_not_found("elicitation/create"))
+    }
+}
+
+pub(crate) fn method_not_found(method: &str) -> McpError {
+    McpError::Server {
+        code: McpErrorCode::MethodNotFound as i32,
+        message: format!("Method not found: {}", method),
+    }
+}
+
+pub(crate) fn parse_params<T: serde::de::DeserializeOwned>(
+    params: Option<Value>,
+) -> Result<T, McpError> {
+    serde_json::from_value(params.unwrap_or(Value::Null)).map_err(|e| McpError::Server {
+        code: McpErrorCode::InvalidParams as i32,
+        message: format!("Invalid params: {}", e),
+    })
+}
+
+pub(crate) fn to_value<T: serde::Serialize>(result: T) -> Result<Value, McpError> {
+    serde_json::to_value(result).map_err(|e| McpError::Protocol(e.to_string()))
+}
+
+/// Run a server request through the handler, producing the JSON-RPC result
+pub(crate) async fn handle_request(
+    server: &str,
+    method: &str,
+    params: Option<Value>,
+    handler: Option<&dyn McpRequestHandler>,
+) -> Result<Value, McpError> {
+    if method == "ping" {
+        return Ok(json!({}));
+    }
+    let handler = handler.ok_or_else(|| method_not_found(method))?;
+    match method {
+        "roots/list" => to_value(handler.list_roots(server).await?),
+        "sampling/createMessage" => to_value(
+            handler
+                .create_message(server, parse_params(params)?)
+                .await?,
+        ),
+        "elicitation/create" => to_value(handler.elicit(server, parse_params(params)?).await?),
+        _ => Err(method_not_found(method)),
+    }
+}
+
+/// JSON-RPC reply for a handled request
+pub(crate) fn reply(id: Value, result: Result<Value, McpError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": JSONRPC_VERSION, "id": id, "result": result }),
+        Err(e) => {
+            let (code, message) = match e {
+                McpError::Server { code, message } => (code, message),
+                other => (McpErrorCode::InternalError as i32, other.to_string()),
+            };
+            json!({
+                "jsonrpc": JSONRPC_VERSION,
+                "id": id,
+                "error": { "code": code, "message": message }
+            })
+        }
+    }
+}
@@ -1,21 +1,58 @@
 //! MCP Manager for handling multiple server connections
 
+use super::super::audit::{
+    contains_redacted, summarize_prompt, summarize_resource, summarize_tool_result, McpAuditLog,
+    McpCallContext, McpCallOutput, REDACTED,
+};
 use super::super::config::McpServerConfig;
 use super::super::protocol::*;
-use super::types::{McpClient, McpManager};
+use super::super::transport::StderrLog;
+use super::handler::McpRequestHandler;
+use super::types::{McpClient, McpConnection, McpEvent, McpManager};
+use crate::storage::models::{McpCallApproval, McpCallKind};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::mpsc;
 
 impl McpManager {
     /// Create a new MCP manager
     pub fn new() -> Self {
         Self {
             clients: HashMap::new(),
+            event_tx: None,
+            handler: None,
+            stderr_logs: HashMap::new(),
+            audit: None,
         }
     }
 
+    /// Forward notifications from every server to a channel
+    pub fn with_event_sender(mut self, event_tx: mpsc::UnboundedSender<McpEvent>) -> Self {
+        self.event_tx = Some(event_tx);
+        self
+    }
+
+    /// Record tool calls, resource reads and prompt fetches
+    pub fn with_audit(mut self, audit: McpAuditLog) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Audit log, if attached
+    pub fn audit(&self) -> Option<&McpAuditLog> {
+        self.audit.as_ref()
+    }
+
+    /// Answer requests from every server with a handler
+    pub fn with_handler(mut self, handler: Arc<dyn McpRequestHandler>) -> Self {
+        self.handler = Some(handler);
+        self
+    }
+
     /// Connect to an MCP server
-    pub fn connect(
+    pub async fn connect(
         &mut self,
         name: impl Into<String>,
         config: McpServerConfig,
@@ -29,26 +66,89 @@ impl McpManager {
             )));
         }
 
-        let mut client = McpClient::new(name.clone(), config);
-        client.start()?;
-        client.initialize()?;
+        let mut client = self.client_for(&name, config);
+        client.start().await?;
+        if let Err(e) = client.initialize().await {
+            let _ = client.stop().await;
+            return Err(e);
+        }
 
         self.clients.insert(name, client);
         Ok(())
     }
 
+    /// Unstarted client sharing this manager's events, handler and stderr log
+    pub(crate) fn client_for(&mut self, name: &str, config: McpServerConfig) -> McpClient {
+        let stderr = self
+            .stderr_logs
+            .entry(name.to_string())
+            .or_default()
+            .clone();
+        let mut client = McpClient::new(name, config).with_stderr_log(stderr);
+        if let Some(event_tx) = &self.event_tx {
+            client = client.with_event_sender(event_tx.clone());
+        }
+        if let Some(handler) = &self.handler {
+            client = client.with_handler(handler.clone());
+        }
+        client
+    }
+
+    /// Add an initialized client, stopping any it replaces
+    pub(crate) async fn insert(&mut self, client: McpClient) {
+        if let Some(mut previous) = self.clients.insert(client.name().to_string(), client) {
+            let _ = previous.stop().await;
+        }
+    }
+
+    /// Stderr output of a stdio server, including earlier runs
+    pub fn stderr_log(&self, name: &str) -> Option<&StderrLog> {
+        self.stderr_logs.get(name)
+    }
+
     /// Disconnect from an MCP server
-    pub fn disconnect(&mut self, name: &str) -> Result<(), McpError> {
+    pub async fn disconnect(&mut self, name: &str) -> Result<(), McpError> {
         if let Some(mut client) = self.clients.remove(name) {
-            client.stop()?;
+            client.stop().await?;
         }
         Ok(())
     }
 
+    /// Take a client out without stopping it
+    pub(crate) fn remove(&mut self, name: &str) -> Option<McpClient> {
+        self.clients.remove(name)
+    }
+
     /// Disconnect from all servers
-    pub fn disconnect_all(&mut self) {
+    pub async fn disconnect_all(&mut self) {
         for (_, mut client) in self.clients.drain() {
-            let _ = client.stop();
+            let _ = client.stop().await;
+        }
+    }
+
+    /// Apply an event from a server, refreshing cached lists when they change
+    pub async fn handle_event(&mut self, event: &McpEvent) -> Result<(), McpError> {
+        let Some(client) = self.clients.get_mut(event.server()) else {
+            return Ok(());
+        };
+        match event {
+            McpEvent::ToolsChanged { .. } => client.refresh_tools().await,
+            McpEvent::ResourcesChanged { .. } => client.refresh_resources().await,
+            McpEvent::PromptsChanged { .. } => client.refresh_prompts().await,
+            _ => Ok(()),
+        }
+    }
+
+    /// Tell every server that the roots changed
+    pub async fn notify_roots_changed(&self) {
+        for client in self.clients.values() {
+            if let Err(e) = client.notify_roots_changed().await {
+                tracing::warn!(
+                    "Failed to notify '{}' of roots change: {}",
+                    client.name(),
+                    e
+                );
+            }
         }
     }
 
@@ -62,6 +162,14 @@ impl McpManager {
         self.clients.get_mut(name)
     }
 
+    /// Connection handle for concurrent requests to a server
+    pub fn connection(&self, name: &str) -> Result<McpConnection, McpError> {
+        self.clients
+            .get(name)
+            .ok_or_else(|| McpError::Connection(format!("Server '{}' not connected", name)))?
+            .connection()
+    }
+
     /// Get all connected client names
     pub fn connected_servers(&self) -> impl Iterator<Item = &String> {
         self.clients.keys()
@@ -88,6 +196,19 @@ impl McpManager {
             .collect()
     }
 
+    /// Get all resource templates from all connected servers
+    pub fn all_resource_templates(&self) -> Vec<(&str, &ResourceTemplate)> {
+        self.clients
+            .iter()
+            .flat_map(|(name, client)| {
+                client
+                    .resource_templates()
+                    .iter()
+                    .map(move |template| (name.as_str(), template))
+            })
+            .collect()
+    }
+
     /// Get all available prompts from all connected servers
     pub fn all_prompts(&self) -> Vec<(&str, &McpPrompt)> {
         self.clients
@@ -102,58 +223,253 @@ impl McpManager {
     }
 
     /// Call a tool on a specific server
-    pub fn call_tool(
+    pub async fn call_tool(
         &mut self,
         server: &str,
         tool_name: &str,
         arguments: Option<HashMap<String, Value>>,
     ) -> Result<CallToolResult, McpError> {
-        let client = self
-            .clients
-            .get_mut(server)
-            .ok_or_else(|| McpError::Connection(format!("Server '{}' not connected", server)))?;
+        self.call_tool_with_context(server, tool_name, arguments, &McpCallContext::default())
+            .await
+    }
 
-        client.call_tool(tool_name, arguments)
+    /// Call a tool, recording the conversation and approval in the audit log
+    pub async fn call_tool_with_context(
+        &mut self,
+        server: &str,
+        tool_name: &str,
+        arguments: Option<HashMap<String, Value>>,
+        context: &McpCallContext,
+    ) -> Result<CallToolResult, McpError> {
+        let recorded = arguments.as_ref().map(|a| serde_json::json!(a));
+        let started = Instant::now();
+        let result = match self.clients.get_mut(server) {
+            Some(client) => client.call_tool(tool_name, arguments).await,
+            None => Err(not_connected(server)),
+        };
+
+        if let Some(audit) = &self.audit {
+            let outcome = match &result {
+                Ok(r) if r.is_error == Some(true) => Err(summarize_tool_result(r)),
+                Ok(r) => Ok(summarize_tool_result(r)),
+                Err(e) => Err(e.to_string()),
+            };
+            audit
+                .record(
+                    server,
+                    McpCallKind::Tool,
+                    tool_name,
+                    recorded,
+                    outcome,
+                    started.elapsed(),
+                    context,
+                )
+                .await;
+        }
+        result
     }
 
     /// Read a resource from a specific server
-    pub fn read_resource(&mut self, server: &str, uri: &str) -> Result<ResourceContents, McpError> {
-        let client = self
-            .clients
-            .get_mut(server)
-            .ok_or_else(|| McpError::Connection(format!("Server '{}' not connected", server)))?;
+    pub async fn read_resource(
+        &mut self,
+        server: &str,
+        uri: &str,
+    ) -> Result<ResourceContents, McpError> {
+        self.read_resource_with_context(server, uri, &McpCallContext::default())
+            .await
+    }
+
+    /// Read a resource, recording the conversation in the audit log
+    pub async fn read_resource_with_context(
+        &mut self,
+        server: &str,
+        uri: &str,
+        context: &McpCallContext,
+    ) -> Result<ResourceContents, McpError> {
+        let started = Instant::now();
+        let result = match self.clients.get_mut(server) {
+            Some(client) => client.read_resource(uri).await,
+            None => Err(not_connected(server)),
+        };
 
-        client.read_resource(uri)
+        if let Some(audit) = &self.audit {
+            audit
+                .record(
+                    server,
+                    McpCallKind::Resource,
+                    uri,
+                    None,
+                    result
+                        .as_ref()
+                        .map(summarize_resource)
+                        .map_err(|e| e.to_string()),
+                    started.elapsed(),
+                    context,
+                )
+                .await;
+        }
+        result
     }
 
     /// Get a prompt from a specific server
-    pub fn get_prompt(
+    pub async fn get_prompt(
+        &mut self,
+        server: &str,
+        prompt_name: &str,
+        arguments: Option<HashMap<String, String>>,
+    ) -> Result<GetPromptResult, McpError> {
+        self.get_prompt_with_context(server, prompt_name, arguments, &McpCallContext::default())
+            .await
+    }
+
+    /// Get a prompt, recording the conversation in the audit log
+    pub async fn get_prompt_with_context(
         &mut self,
         server: &str,
         prompt_name: &str,
         arguments: Option<HashMap<String, String>>,
+        context: &McpCallContext,
     ) -> Result<GetPromptResult, McpError> {
+        let recorded = arguments.as_ref().map(|a| serde_json::json!(a));
+        let started = Instant::now();
+        let result = match self.clients.get_mut(server) {
+            Some(client) => client.get_prompt(prompt_name, arguments).await,
+            None => Err(not_connected(server)),
+        };
+
+        if let Some(audit) = &self.audit {
+            audit
+                .record(
+                    server,
+                    McpCallKind::Prompt,
+                    prompt_name,
+                    recorded,
+                    result
+                        .as_ref()
+                        .map(summarize_prompt)
+                        .map_err(|e| e.to_string()),
+                    started.elapsed(),
+                    context,
+                )
+                .await;
+        }
+        result
+    }
+
+    /// Re-run a call from the audit log
+    ///
+    /// `overrides` replace recorded arguments by name; they are required for
+    /// arguments that were redacted when the call was stored. The new run is
+    /// recorded under the original conversation.
+    pub async fn replay(
+        &mut self,
+        call_id: &str,
+        overrides: HashMap<String, Value>,
+    ) -> Result<McpCallOutput, McpError> {
+        let audit = self
+            .audit
+            .as_ref()
+            .ok_or_else(|| McpError::Unavailable("Audit log".into()))?;
+        let call = audit.call(call_id)?.ok_or_else(|| McpError::Server {
+            code: McpErrorCode::InvalidParams as i32,
+            message: format!("Unknown MCP call: {}", call_id),
+        })?;
+
+        if call.name.contains(REDACTED) {
+            return Err(McpError::Server {
+                code: McpErrorCode::InvalidParams as i32,
+                message: format!(
+                    "Call {} targets a redacted resource and cannot be re-run",
+                    call_id
+                ),
+            });
+        }
+
+        let mut arguments = match call.arguments {
+            Some(Value::Object(map)) => map.into_iter().collect::<HashMap<_, _>>(),
+            _ => HashMap::new(),
+        };
+        arguments.extend(overrides);
+        let redacted: Vec<&str> = arguments
+            .iter()
+            .filter(|(_, value)| contains_redacted(value))
+            .map(|(name, _)| name.as_str())
+            .collect();
+        if !redacted.is_empty() {
+            return Err(McpError::Server {
+                code: McpErrorCode::InvalidParams as i32,
+                message: format!(
+                    "Call {} had redacted values; pass them as overrides: {}",
+                    call_id,
+                    redacted.join(", ")
+                ),
+            });
+        }
+
+        let context = McpCallContext {
+            conversation_id: call.conversation_id,
+            approval: McpCallApproval::Approved,
+        };
+        let output = match call.kind {
+            McpCallKind::Tool => McpCallOutput::Tool(
+                self.call_tool_with_context(&call.server, &call.name, Some(arguments), &context)
+                    .await?,
+            ),
+            McpCallKind::Resource => McpCallOutput::Resource(
+                self.read_resource_with_context(&call.server, &call.name, &context)
+                    .await?,
+            ),
+            McpCallKind::Prompt => {
+                let arguments = arguments
+                    .into_iter()
+                    .map(|(name, value)| match value {
+                        Value::String(text) => (name, text),
+                        other => (name, other.to_string()),
+                    })
+                    .collect();
+                McpCallOutput::Prompt(
+                    self.get_prompt_with_context(
+                        &call.server,
+                        &call.name,
+                        Some(arguments),
+                        &context,
+                    )
+                    .await?,
+                )
+            }
+        };
+        Ok(output)
+    }
+
+    /// Suggest values for a prompt argument on a specific server
+    pub async fn complete_prompt_argument(
+        &mut self,
+        server: &str,
+        prompt_name: &str,
+        argument: &str,
+        value: &str,
+    ) -> Result<Completion, McpError> {
         let client = self
             .clients
             .get_mut(server)
             .ok_or_else(|| McpError::Connection(format!("Server '{}' not connected", server)))?;
 
-        client.get_prompt(prompt_name, arguments)
+        client
+            .complete_prompt_argument(prompt_name, argument, value)
+            .await
     }
 }
 
+fn not_connected(server: &str) -> McpError {
+    McpError::Connection(format!("Server '{}' not connected", server))
+}
+
 impl Default for McpManager {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl Drop for McpManager {
-    fn drop(&mut self) {
-        self.disconnect_all();
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::super::types::*;
@@ -1,12 +1,302 @@
 //! JSON-RPC messaging for MCP client
+//!
+//! A reader task owns the inbound channel. Responses are routed to the
+//! waiting caller through the pending table; notifications are forwarded as
+//! [`McpEvent`]s and server requests are answered through the
+//! [`McpRequestHandler`].
 
 use super::super::protocol::*;
-use super::types::McpClient;
-use std::io::{BufRead, Write};
-use std::sync::atomic::Ordering;
+use super::super::transport::{McpInbound, McpTransport};
+use super::handler::{handle_request, reply, McpRequestHandler};
+use super::types::*;
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot};
 
-/// Send a JSON-RPC request and wait for response
-pub(super) fn send_request<P, R>(
+impl McpConnection {
+    /// Wrap a transport and start reading its inbound messages
+    pub(crate) fn open(
+        server: impl Into<String>,
+        transport: Box<dyn McpTransport>,
+        inbound: McpInbound,
+        event_tx: Option<mpsc::UnboundedSender<McpEvent>>,
+        handler: Option<Arc<dyn McpRequestHandler>>,
+        timeout: Duration,
+    ) -> Self {
+        let server = server.into();
+        let transport: Arc<dyn McpTransport> = Arc::from(transport);
+        let pending: Arc<Mutex<HashMap<u64, PendingRequest>>> =
+            Arc::new(Mutex::new(HashMap::new()));
+
+        let reader = tokio::spawn(read_loop(
+            server.clone(),
+            inbound,
+            pending.clone(),
+            transport.clone(),
+            event_tx,
+            handler,
+        ));
+
+        Self {
+            inner: Arc::new(ConnectionInner {
+                server,
+                transport,
+                next_id: AtomicU64::new(1),
+                pending,
+                timeout,
+                reader: Mutex::new(Some(reader)),
+            }),
+        }
+    }
+
+    /// Send a request and wait for its result, using the default timeout
+    pub async fn request(&self, method: &str, params: Option<Value>) -> Result<Value, McpError> {
+        self.request_with_timeout(method, params, self.inner.timeout)
+            .await
+    }
+
+    /// Send a request and wait at most `timeout` for its result
+    ///
+    /// On timeout, or if the returned future is dropped before completing,
+    /// the server is sent `notifications/cancelled` for the request.
+    pub async fn request_with_timeout(
+        &self,
+        method: &str,
+        params: Option<Value>,
+        timeout: Duration,
+    ) -> Result<Value, McpError> {
+        let id = self.inner.next_id.fetch_add(1, Ordering::SeqCst);
+        let request = JsonRpcRequest::new(id, method, params);
+        let message = serde_json::to_value(&request)
+            .map_err(|e| McpError::Protocol(format!("Failed to serialize request: {}", e)))?;
+
+        let (tx, rx) = oneshot::channel();
+        self.inner
+            .pending
+            .lock()
+            .insert(id, PendingRequest { sender: tx });
+        let mut guard = PendingGuard {
+            connection: self,
+            id,
+            // `initialize` must never be cancelled
+            cancellable: method != "initialize",
+        };
+
+        if let Err(e) = self.inner.transport.send(message).await {
+            guard.disarm();
+            return Err(e);
+        }
+
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(result)) => {
+                guard.disarm();
+                result
+            }
+            Ok(Err(_)) => {
+                guard.disarm();
+                Err(McpError::Connection("Server closed connection".into()))
+            }
+            Err(_) => {
+                self.cancel(id, "Request timed out").await;
+                Err(McpError::Timeout)
+            }
+        }
+    }
+
+    /// Send `ping` and return the round-trip time
+    pub async fn ping(&self, timeout: Duration) -> Result<Duration, McpError> {
+        let started = Instant::now();
+        self.request_with_timeout("ping", None, timeout).await?;
+        Ok(started.elapsed())
+    }
+
+    /// Send a notification (no response expected)
+    pub async fn notify(&self, method: &str, params: Option<Value>) -> Result<(), McpError> {
+        let notification = JsonRpcNotification::new(method, params);
+        let message = serde_json::to_value(&notification)
+            .map_err(|e| McpError::Protocol(format!("Failed to serialize notification: {}", e)))?;
+        self.inner.transport.send(message).await
+    }
+
+    /// Stop waiting for a request and tell the server to abandon it
+    pub async fn cancel(&self, id: u64, reason: &str) {
+        if self.inner.pending.lock().remove(&id).is_none() {
+            return;
+        }
+        send_cancelled(self.inner.transport.as_ref(), id, reason).await;
+    }
+
+    /// Name of the server this connection belongs to
+    pub fn server(&self) -> &str {
+        &self.inner.server
+    }
+
+    /// Number of requests awaiting a response
+    pub fn pending_count(&self) -> usize {
+        self.inner.pending.lock().len()
+    }
+
+    /// Whether the transport is still usable
+    pub fn is_connected(&self) -> bool {
+        self.inner.transport.is_connected()
+    }
+
+    /// Session id assigned by an HTTP server
+    pub fn session_id(&self) -> Option<String> {
+        self.inner.transport.session_id()
+    }
+
+    /// Tell the transport which protocol version was negotiated
+    pub(crate) fn set_protocol_version(&self, version: &str) {
+        self.inner.transport.set_protocol_version(version);
+    }
+
+    /// Close the transport and fail outstanding requests
+    pub(crate) async fn close(&self) {
+        if let Some(reader) = self.inner.reader.lock().take() {
+            reader.abort();
+        }
+        fail_pending(&self.inner.pending, "Connection closed");
+        let _ = self.inner.transport.close().await;
+    }
+}
+
+impl Drop for ConnectionInner {
+    fn drop(&mut self) {
+        if let Some(reader) = self.reader.lock().take() {
+            reader.abort();
+        }
+    }
+}
+
+/// Cancels a request whose caller stopped waiting
+struct PendingGuard<'a> {
+    connection: &'a McpConnection,
+    id: u64,
+    cancellable: bool,
+}
+
+impl PendingGuard<'_> {
+    fn disarm(&mut self) {
+        self.cancellable = false;
+        self.connection.inner.pending.lock().remove(&self.id);
+    }
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        let removed = self.connection.inner.pending.lock().remove(&self.id);
+        if removed.is_none() || !self.cancellable {
+            return;
+        }
+        let transport = self.connection.inner.transport.clone();
+        let id = self.id;
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            handle.spawn(async move {
+                send_cancelled(transport.as_ref(), id, "Request cancelled by client").await;
+            });
+        }
+    }
+}
+
+async fn send_cancelled(transport: &dyn McpTransport, id: u64, reason: &str) {
+    let notification = JsonRpcNotification::new(
+        "notifications/cancelled",
+        Some(json!({ "requestId": id, "reason": reason })),
+    );
+    if let Ok(message) = serde_json::to_value(&notification) {
+        let _ = transport.send(message).await;
+    }
+}
+
+fn fail_pending(pending: &Mutex<HashMap<u64, PendingRequest>>, reason: &str) {
+    for (_, request) in pending.lock().drain() {
+        let _ = request
+            .sender
+            .send(Err(McpError::Connection(reason.to_string())));
+    }
+}
+
+/// Route inbound messages until the server goes away
+async fn read_loop(
+    server: String,
+    mut inbound: McpInbound,
+    pending: Arc<Mutex<HashMap<u64, PendingRequest>>>,
+    transport: Arc<dyn McpTransport>,
+    event_tx: Option<mpsc::UnboundedSender<McpEvent>>,
+    handler: Option<Arc<dyn McpRequestHandler>>,
+) {
+    while let Some(message) = inbound.recv().await {
+        let method = message
+            .get("method")
+            .and_then(|m| m.as_str())
+            .map(str::to_string);
+        let id = message.get("id").cloned().filter(|id| !id.is_null());
+
+        match (method, id) {
+            // Request from the server
+            (Some(method), Some(id)) => {
+                let server = server.clone();
+                let handler = handler.clone();
+                let transport = transport.clone();
+                let params = message.get("params").cloned();
+                // Own task: handlers may wait on the user
+                tokio::spawn(async move {
+                    let result = handle_request(&server, &method, params, handler.as_deref()).await;
+                    if let Err(e) = &result {
+                        tracing::debug!("MCP request {} from '{}' failed: {}", method, server, e);
+                    }
+                    let _ = transport.send(reply(id, result)).await;
+                });
+            }
+            // Notification
+            (Some(method), None) => {
+                if let Some(tx) = &event_tx {
+                    let params = message.get("params").cloned();
+                    let _ = tx.send(McpEvent::from_notification(&server, method, params));
+                }
+            }
+            // Response
+            (None, Some(_)) => {
+                let response: JsonRpcResponse = match serde_json::from_value(message) {
+                    Ok(response) => response,
+                    Err(e) => {
+                        tracing::warn!("Ignoring invalid MCP response: {}", e);
+                        continue;
+                    }
+                };
+                let Some(request) = pending.lock().remove(&response.id) else {
+                    // Timed out or cancelled
+                    continue;
+                };
+                let result = match response.error {
+                    Some(error) => Err(McpError::Server {
+                        code: error.code,
+                        message: error.message,
+                    }),
+                    None => Ok(response.result.unwrap_or(Value::Null)),
+                };
+                let _ = request.sender.send(result);
+            }
+            (None, None) => tracing::warn!("Ignoring invalid MCP message from '{}'", server),
+        }
+    }
+
+    fail_pending(&pending, "Server closed connection");
+    if let Some(tx) = &event_tx {
+        let _ = tx.send(McpEvent::Closed { server });
+    }
+}
+
+/// Send a request and wait for response
+///
+/// If the server reports that the session expired, the client reconnects
+/// once and retries the request on the new session.
+pub(super) async fn send_request<P, R>(
     client: &mut McpClient,
     method: &str,
     params: Option<P>,
@@ -15,87 +305,48 @@ where
     P: serde::Serialize,
     R: serde::de::DeserializeOwned,
 {
-    let id = client.request_id.fetch_add(1, Ordering::SeqCst);
+    send_request_with_timeout(client, method, params, None).await
+}
 
+/// [`send_request`] with an explicit timeout
+pub(super) async fn send_request_with_timeout<P, R>(
+    client: &mut McpClient,
+    method: &str,
+    params: Option<P>,
+    timeout: Option<Duration>,
+) -> Result<R, McpError>
+where
+    P: serde::Serialize,
+    R: serde::de::DeserializeOwned,
+{
     let params_value = params
         .map(|p| serde_json::to_value(p))
         .transpose()
         .map_err(|e| McpError::Protocol(format!("Failed to serialize params: {}", e)))?;
+    let timeout = timeout.unwrap_or(client.request_timeout);
 
-    let request = JsonRpcRequest::new(id, method, params_value);
-    let request_json = serde_json::to_string(&request)
-        .map_err(|e| McpError::Protocol(format!("Failed to serialize request: {}", e)))?;
-
-    // Write request
+    let result = match client
+        .connection()?
+        .request_with_timeout(method, params_value.clone(), timeout)
+        .await
     {
-        let stdin = client
-            .stdin
-            .as_ref()
-            .ok_or_else(|| McpError::Connection("No stdin available".into()))?;
-        let mut stdin = stdin
-            .lock()
-            .map_err(|_| McpError::Connection("Failed to lock stdin".into()))?;
-
-        writeln!(stdin, "{}", request_json)
-            .map_err(|e| McpError::Io(format!("Failed to write request: {}", e)))?;
-        stdin
-            .flush()
-            .map_err(|e| McpError::Io(format!("Failed to flush: {}", e)))?;
-    }
-
-    // Read response
-    let stdout = client
-        .stdout
-        .as_mut()
-        .ok_or_else(|| McpError::Connection("No stdout available".into()))?;
-
-    let mut line = String::new();
-    loop {
-        line.clear();
-        let bytes_read = stdout
-            .read_line(&mut line)
-            .map_err(|e| McpError::Io(format!("Failed to read response: {}", e)))?;
-
-        if bytes_read == 0 {
-            return Err(McpError::Connection("Server closed connection".into()));
-        }
-
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
+        Err(McpError::SessionExpired) if method != "initialize" => {
+            tracing::info!("MCP session for '{}' expired, reconnecting", client.name);
+            client.reconnect().await?;
+            client
+                .connection()?
+                .request_with_timeout(method, params_value, timeout)
+                .await?
         }
+        result => result?,
+    };
 
-        // Try to parse as response
-        let response: JsonRpcResponse = serde_json::from_str(line)
-            .map_err(|e| McpError::Protocol(format!("Failed to parse response: {}", e)))?;
-
-        // Check if this is our response
-        if response.id != id {
-            // Could be a notification or response to different request
-            continue;
-        }
-
-        // Check for error
-        if let Some(error) = response.error {
-            return Err(McpError::Server {
-                code: error.code,
-                message: error.message,
-            });
-        }
-
-        // Parse result
-        let result = response
-            .result
-            .ok_or_else(|| McpError::Protocol("Missing result in response".into()))?;
-
-        return serde_json::from_value(result)
-            .map_err(|e| McpError::Protocol(format!("Failed to parse result: {}", e)));
-    }
+    parse_result(result)
 }
 
 /// Send a JSON-RPC notification (no response expected)
-pub(super) fn send_notification<P>(
-    client: &mut McpClient,
+pub(super) async fn send_notification<P>(
+    client: &McpClient,
     method: &str,
     params: Option<P>,
 ) -> Result<(), McpError>
@@ -107,23 +358,11 @@ where
         .transpose()
         .map_err(|e| McpError::Protocol(format!("Failed to serialize params: {}", e)))?;
 
-    let notification = JsonRpcNotification::new(method, params_value);
-    let notification_json = serde_json::to_string(&notification)
-        .map_err(|e| McpError::Protocol(format!("Failed to serialize notification: {}", e)))?;
-
-    let stdin = client
-        .stdin
-        .as_ref()
-        .ok_or_else(|| McpError::Connection("No stdin available".into()))?;
-    let mut stdin = stdin
-        .lock()
-        .map_err(|_| McpError::Connection("Failed to lock stdin".into()))?;
-
-    writeln!(stdin, "{}", notification_json)
-        .map_err(|e| McpError::Io(format!("Failed to write notification: {}", e)))?;
-    stdin
-        .flush()
-        .map_err(|e| McpError::Io(format!("Failed to flush: {}", e)))?;
-
-    Ok(())
+    client.connection()?.notify(method, params_value).await
+}
+
+/// Deserialize a request result
+pub(super) fn parse_result<R: serde::de::DeserializeOwned>(result: Value) -> Result<R, McpError> {
+    serde_json::from_value(result)
+        .map_err(|e| McpError::Protocol(format!("Failed to parse result: {}", e)))
 }
@@ -1,13 +1,20 @@
 //! MCP Client Implementation
 //!
-//! Handles communication with MCP servers via JSON-RPC 2.0 over stdio.
+//! Handles communication with MCP servers via JSON-RPC 2.0 over stdio,
+//! Streamable HTTP or legacy SSE.
 
+mod connection;
 mod core;
 mod features;
+mod handler;
 mod manager;
 mod messaging;
-mod process;
 mod types;
 
+#[cfg(test)]
+mod tests;
+
 // Re-export public types
-pub use types::{McpClient, McpManager};
+pub use handler::McpRequestHandler;
+pub(crate) use handler::{method_not_found, parse_params, reply, to_value};
+pub use types::{McpClient, McpConnection, McpEvent, McpManager};
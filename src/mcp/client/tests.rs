@@ -0,0 +1,441 @@
+//! Tests for request multiplexing over an in-memory transport
+
+use super::super::config::{McpServerConfig, McpTransportKind};
+use super::super::protocol::{
+    CreateMessageParams, CreateMessageResult, ListRootsResult, McpCapabilities, McpError, McpRoot,
+    RootsCapability, SamplingCapability, ToolContent,
+};
+use super::super::transport::McpTransport;
+use super::handler::McpRequestHandler;
+use super::types::{McpClient, McpConnection, McpEvent};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Transport whose other end is driven by the test
+struct ChannelTransport {
+    outbound: mpsc::UnboundedSender<Value>,
+}
+
+#[async_trait]
+impl McpTransport for ChannelTransport {
+    fn kind(&self) -> McpTransportKind {
+        McpTransportKind::Stdio
+    }
+
+    async fn send(&self, message: Value) -> Result<(), McpError> {
+        self.outbound
+            .send(message)
+            .map_err(|_| McpError::Connection("closed".into()))
+    }
+
+    async fn close(&self) -> Result<(), McpError> {
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        !self.outbound.is_closed()
+    }
+}
+
+/// Test side of a connection
+struct FakeServer {
+    /// Messages sent by the client
+    received: mpsc::UnboundedReceiver<Value>,
+    /// Messages delivered to the client
+    inbound: mpsc::UnboundedSender<Value>,
+}
+
+impl FakeServer {
+    async fn next(&mut self) -> Value {
+        tokio::time::timeout(Duration::from_secs(5), self.received.recv())
+            .await
+            .expect("client sent nothing")
+            .expect("client closed")
+    }
+
+    fn reply(&self, id: &Value, result: Value) {
+        self.inbound
+            .send(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+            .unwrap();
+    }
+}
+
+fn open(timeout: Duration) -> (McpConnection, FakeServer, mpsc::UnboundedReceiver<McpEvent>) {
+    open_with_handler(timeout, None)
+}
+
+fn open_with_handler(
+    timeout: Duration,
+    handler: Option<Arc<dyn McpRequestHandler>>,
+) -> (McpConnection, FakeServer, mpsc::UnboundedReceiver<McpEvent>) {
+    let (out_tx, out_rx) = mpsc::unbounded_channel();
+    let (in_tx, in_rx) = mpsc::unbounded_channel();
+    let (event_tx, event_rx) = mpsc::unbounded_channel();
+    let connection = McpConnection::open(
+        "fake",
+        Box::new(ChannelTransport { outbound: out_tx }),
+        in_rx,
+        Some(event_tx),
+        handler,
+        timeout,
+    );
+    let server = FakeServer {
+        received: out_rx,
+        inbound: in_tx,
+    };
+    (connection, server, event_rx)
+}
+
+fn text_result(text: &str) -> Value {
+    json!({ "content": [{ "type": "text", "text": text }] })
+}
+
+fn text_of(content: &[ToolContent]) -> &str {
+    match content.first() {
+        Some(ToolContent::Text { text }) => text,
+        other => panic!("unexpected content: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_concurrent_requests_out_of_order() {
+    let (connection, mut server, _events) = open(Duration::from_secs(5));
+
+    let slow = tokio::spawn({
+        let connection = connection.clone();
+        async move { connection.call_tool("slow", None).await }
+    });
+    let first = server.next().await;
+
+    let fast = tokio::spawn({
+        let connection = connection.clone();
+        async move { connection.call_tool("fast", None).await }
+    });
+    let second = server.next().await;
+    assert_ne!(first["id"], second["id"]);
+    assert_eq!(connection.pending_count(), 2);
+
+    // Answer the later request first
+    server.reply(&second["id"], text_result("fast done"));
+    let fast = fast.await.unwrap().unwrap();
+    assert_eq!(text_of(&fast.content), "fast done");
+    assert!(!slow.is_finished());
+
+    server.reply(&first["id"], text_result("slow done"));
+    let slow = slow.await.unwrap().unwrap();
+    assert_eq!(text_of(&slow.content), "slow done");
+    assert_eq!(connection.pending_count(), 0);
+}
+
+#[tokio::test]
+async fn test_timeout_sends_cancelled() {
+    let (connection, mut server, _events) = open(Duration::from_secs(5));
+
+    let result = connection
+        .call_tool_with_timeout("hang", None, Duration::from_millis(50))
+        .await;
+    assert!(matches!(result, Err(McpError::Timeout)));
+
+    let request = server.next().await;
+    let cancelled = server.next().await;
+    assert_eq!(cancelled["method"], "notifications/cancelled");
+    assert_eq!(cancelled["params"]["requestId"], request["id"]);
+    assert_eq!(connection.pending_count(), 0);
+
+    // A late response is ignored
+    server.reply(&request["id"], text_result("too late"));
+    let echo = tokio::spawn({
+        let connection = connection.clone();
+        async move { connection.request("ping", None).await }
+    });
+    let ping = server.next().await;
+    server.reply(&ping["id"], json!({}));
+    assert_eq!(echo.await.unwrap().unwrap(), json!({}));
+}
+
+#[tokio::test]
+async fn test_dropped_request_sends_cancelled() {
+    let (connection, mut server, _events) = open(Duration::from_secs(5));
+
+    let call = tokio::spawn({
+        let connection = connection.clone();
+        async move { connection.call_tool("hang", None).await }
+    });
+    let request = server.next().await;
+    call.abort();
+
+    let cancelled = server.next().await;
+    assert_eq!(cancelled["method"], "notifications/cancelled");
+    assert_eq!(cancelled["params"]["requestId"], request["id"]);
+    assert_eq!(connection.pending_count(), 0);
+}
+
+#[tokio::test]
+async fn test_notifications_and_server_requests() {
+    let (connection, mut server, mut events) = open(Duration::from_secs(5));
+
+    server
+        .inbound
+        .send(json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/tools/list_changed"
+        }))
+        .unwrap();
+    server
+        .inbound
+        .send(json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/progress",
+            "params": { "progressToken": "tok", "progress": 1, "total": 4 }
+        }))
+        .unwrap();
+    server
+        .inbound
+        .send(json!({ "jsonrpc": "2.0", "method": "notifications/custom" }))
+        .unwrap();
+    match events.recv().await.unwrap() {
+        McpEvent::ToolsChanged { server } => assert_eq!(server, "fake"),
+        other => panic!("unexpected event: {:?}", other),
+    }
+    match events.recv().await.unwrap() {
+        McpEvent::Progress { progress, .. } => {
+            assert_eq!(progress.token(), "tok");
+            assert_eq!(progress.percent(), Some(25));
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+    match events.recv().await.unwrap() {
+        McpEvent::Notification { method, .. } => assert_eq!(method, "notifications/custom"),
+        other => panic!("unexpected event: {:?}", other),
+    }
+
+    // Requests from the server are answered, not mistaken for responses
+    server
+        .inbound
+        .send(json!({ "jsonrpc": "2.0", "id": "srv-1", "method": "ping" }))
+        .unwrap();
+    server
+        .inbound
+        .send(json!({ "jsonrpc": "2.0", "id": 7, "method": "unknown/method" }))
+        .unwrap();
+    // Each request is handled on its own task, so replies may arrive in any order
+    let mut replies = [server.next().await, server.next().await];
+    replies.sort_by_key(|r| r["id"].is_number());
+    let (pong, unknown) = (&replies[0], &replies[1]);
+    assert_eq!(pong["id"], "srv-1");
+    assert_eq!(pong["result"], json!({}));
+    assert_eq!(unknown["id"], 7);
+    assert_eq!(unknown["error"]["code"], -32601);
+
+    let args = HashMap::from([("x".to_string(), json!(1))]);
+    let call = tokio::spawn({
+        let connection = connection.clone();
+        async move { connection.call_tool("t", Some(args)).await }
+    });
+    let request = server.next().await;
+    server
+        .inbound
+        .send(json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "error": { "code": -32000, "message": "boom" }
+        }))
+        .unwrap();
+    assert!(matches!(
+        call.await.unwrap(),
+        Err(McpError::Server { code: -32000, .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_server_exit_fails_pending_requests() {
+    let (connection, mut server, mut events) = open(Duration::from_secs(5));
+
+    let call = tokio::spawn({
+        let connection = connection.clone();
+        async move { connection.call_tool("t", None).await }
+    });
+    server.next().await;
+    drop(server.inbound);
+
+    assert!(matches!(call.await.unwrap(), Err(McpError::Connection(_))));
+    assert!(matches!(
+        events.recv().await.unwrap(),
+        McpEvent::Closed { .. }
+    ));
+}
+
+/// Handler answering roots and echoing sampling requests
+struct EchoHandler;
+
+#[async_trait]
+impl McpRequestHandler for EchoHandler {
+    fn capabilities(&self) -> McpCapabilities {
+        McpCapabilities {
+            roots: Some(RootsCapability::default()),
+            sampling: Some(SamplingCapability {}),
+            ..Default::default()
+        }
+    }
+
+    async fn list_roots(&self, _server: &str) -> Result<ListRootsResult, McpError> {
+        Ok(ListRootsResult {
+            roots: vec![McpRoot {
+                uri: "file:///work".to_string(),
+                name: Some("work".to_string()),
+            }],
+        })
+    }
+
+    async fn create_message(
+        &self,
+        server: &str,
+        params: CreateMessageParams,
+    ) -> Result<CreateMessageResult, McpError> {
+        Ok(CreateMessageResult {
+            role: "assistant".to_string(),
+            content: params.messages[0].content.clone(),
+            model: server.to_string(),
+            stop_reason: Some("endTurn".to_string()),
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_server_requests_use_handler() {
+    let (_connection, mut server, _events) =
+        open_with_handler(Duration::from_secs(5), Some(Arc::new(EchoHandler)));
+
+    server
+        .inbound
+        .send(json!({ "jsonrpc": "2.0", "id": 1, "method": "roots/list" }))
+        .unwrap();
+    let roots = server.next().await;
+    assert_eq!(roots["id"], 1);
+    assert_eq!(roots["result"]["roots"][0]["uri"], "file:///work");
+
+    server
+        .inbound
+        .send(json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "sampling/createMessage",
+            "params": {
+                "messages": [{ "role": "user", "content": { "type": "text", "text": "hi" } }],
+                "maxTokens": 10
+            }
+        }))
+        .unwrap();
+    let sampled = server.next().await;
+    assert_eq!(sampled["id"], 2);
+    assert_eq!(sampled["result"]["content"]["text"], "hi");
+    assert_eq!(sampled["result"]["model"], "fake");
+
+    // Not implemented by the handler
+    server
+        .inbound
+        .send(json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "elicitation/create",
+            "params": { "message": "?", "requestedSchema": {} }
+        }))
+        .unwrap();
+    let elicit = server.next().await;
+    assert_eq!(elicit["error"]["code"], -32601);
+
+    // Malformed params
+    server
+        .inbound
+        .send(json!({ "jsonrpc": "2.0", "id": 4, "method": "sampling/createMessage" }))
+        .unwrap();
+    let invalid = server.next().await;
+    assert_eq!(invalid["error"]["code"], -32602);
+}
+
+/// Answer a client's discovery requests, paginating the tool list
+async fn serve_discovery(mut server: FakeServer, version: &'static str) {
+    while let Some(message) = server.received.recv().await {
+        let id = message["id"].clone();
+        let cursor = message["params"]["cursor"].as_str();
+        let result = match message["method"].as_str().unwrap() {
+            "initialize" => json!({
+                "protocolVersion": version,
+                "capabilities": { "tools": {}, "resources": {}, "prompts": {}, "completions": {} },
+                "serverInfo": { "name": "fake", "version": "1.0.0" }
+            }),
+            "notifications/initialized" => continue,
+            "tools/list" => match cursor {
+                None => json!({
+                    "tools": [{ "name": "a", "inputSchema": { "type": "object" } }],
+                    "nextCursor": "2"
+                }),
+                Some(_) => json!({
+                    "tools": [{ "name": "b", "inputSchema": { "type": "object" } }]
+                }),
+            },
+            "resources/list" => json!({ "resources": [] }),
+            "resources/templates/list" => {
+                server
+                    .inbound
+                    .send(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": -32601, "message": "Method not found" }
+                    }))
+                    .unwrap();
+                continue;
+            }
+            // Repeats its cursor; the client must not loop
+            "prompts/list" => json!({ "prompts": [], "nextCursor": "same" }),
+            "completion/complete" => {
+                let value = message["params"]["argument"]["value"].as_str().unwrap();
+                json!({ "completion": { "values": [format!("{}thon", value)] } })
+            }
+            _ => continue,
+        };
+        server.reply(&id, result);
+    }
+}
+
+#[tokio::test]
+async fn test_initialize_negotiates_and_paginates() {
+    let (connection, server, _events) = open(Duration::from_secs(5));
+    let mut client = McpClient::new("fake", McpServerConfig::http("http://localhost"));
+    client.connection = Some(connection);
+    let script = tokio::spawn(serve_discovery(server, "2025-03-26"));
+
+    client.initialize().await.unwrap();
+    assert_eq!(client.protocol_version(), Some("2025-03-26"));
+    let names: Vec<_> = client.tools().iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, vec!["a", "b"]);
+    assert!(client.resource_templates().is_empty());
+
+    let completion = client
+        .complete_prompt_argument("review", "language", "py")
+        .await
+        .unwrap();
+    assert_eq!(completion.values, vec!["python"]);
+
+    client.stop().await.unwrap();
+    script.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_initialize_rejects_unsupported_version() {
+    let (connection, server, _events) = open(Duration::from_secs(5));
+    let mut client = McpClient::new("fake", McpServerConfig::http("http://localhost"));
+    client.connection = Some(connection);
+    tokio::spawn(serve_discovery(server, "1999-01-01"));
+
+    match client.initialize().await {
+        Err(McpError::UnsupportedProtocolVersion(version)) => assert_eq!(version, "1999-01-01"),
+        other => panic!("unexpected result: {:?}", other.map(|r| r.protocol_version)),
+    }
+    assert!(!client.is_running());
+    assert!(client.protocol_version().is_none());
+}
@@ -1,11 +1,21 @@
 //! Type definitions for MCP client
 
+use super::super::audit::McpAuditLog;
 use super::super::config::McpServerConfig;
 use super::super::protocol::*;
-use std::io::BufReader;
-use std::process::Child;
+use super::super::transport::{McpTransport, StderrLog};
+use super::handler::McpRequestHandler;
+use parking_lot::Mutex;
+use serde_json::Value;
+use std::collections::HashMap;
 use std::sync::atomic::AtomicU64;
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+/// Default time to wait for a response
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
 
 /// MCP Client for communicating with an MCP server
 pub struct McpClient {
@@ -13,32 +23,165 @@ pub struct McpClient {
     pub(crate) config: McpServerConfig,
     /// Server name
     pub(crate) name: String,
-    /// Child process handle
-    pub(crate) process: Option<Child>,
-    /// Stdin writer (wrapped in Arc<Mutex> for thread safety)
-    pub(crate) stdin: Option<Arc<Mutex<std::process::ChildStdin>>>,
-    /// Stdout reader
-    pub(crate) stdout: Option<BufReader<std::process::ChildStdout>>,
-    /// Request ID counter
-    pub(crate) request_id: AtomicU64,
+    /// Open connection, if started
+    pub(crate) connection: Option<McpConnection>,
+    /// Receives server notifications
+    pub(crate) event_tx: Option<mpsc::UnboundedSender<McpEvent>>,
+    /// Answers server requests
+    pub(crate) handler: Option<Arc<dyn McpRequestHandler>>,
+    /// Default request timeout
+    pub(crate) request_timeout: Duration,
+    /// Stderr of the server process, kept across restarts
+    pub(crate) stderr: StderrLog,
     /// Server info after initialization
     pub(crate) server_info: Option<ServerInfo>,
+    /// Protocol version agreed during initialization
+    pub(crate) protocol_version: Option<String>,
     /// Server capabilities
     pub(crate) capabilities: Option<ServerCapabilities>,
     /// Available tools
     pub(crate) tools: Vec<McpTool>,
     /// Available resources
     pub(crate) resources: Vec<McpResource>,
+    /// Available resource templates
+    pub(crate) resource_templates: Vec<ResourceTemplate>,
     /// Available prompts
     pub(crate) prompts: Vec<McpPrompt>,
     /// Whether the client is initialized
     pub(crate) initialized: bool,
 }
 
-use std::collections::HashMap;
+/// Cloneable handle to an open connection
+///
+/// Requests only need `&self`, so several can be in flight on one server at
+/// once; responses are matched to callers by id on a background reader task.
+#[derive(Clone)]
+pub struct McpConnection {
+    pub(crate) inner: Arc<ConnectionInner>,
+}
+
+pub(crate) struct ConnectionInner {
+    /// Server name (for events and logs)
+    pub(crate) server: String,
+    /// Transport carrying the messages
+    pub(crate) transport: Arc<dyn McpTransport>,
+    /// Request ID counter
+    pub(crate) next_id: AtomicU64,
+    /// Requests awaiting a response
+    pub(crate) pending: Arc<Mutex<HashMap<u64, PendingRequest>>>,
+    /// Default request timeout
+    pub(crate) timeout: Duration,
+    /// Inbound message reader
+    pub(crate) reader: Mutex<Option<JoinHandle<()>>>,
+}
+
+/// Pending request
+pub(crate) struct PendingRequest {
+    pub(crate) sender: oneshot::Sender<Result<Value, McpError>>,
+}
+
+/// Event from a connected server
+#[derive(Debug, Clone)]
+pub enum McpEvent {
+    /// Tool list changed; refresh with [`McpManager::handle_event`]
+    ToolsChanged { server: String },
+    /// Resource list changed
+    ResourcesChanged { server: String },
+    /// Prompt list changed
+    PromptsChanged { server: String },
+    /// A subscribed resource changed
+    ResourceUpdated { server: String, uri: String },
+    /// Progress for a request sent with a progress token
+    Progress {
+        server: String,
+        progress: ProgressNotification,
+    },
+    /// Log message from the server
+    Log {
+        server: String,
+        message: LoggingMessage,
+    },
+    /// Any other notification
+    Notification {
+        server: String,
+        method: String,
+        params: Option<Value>,
+    },
+    /// Connection closed by the server
+    Closed { server: String },
+}
+
+impl McpEvent {
+    /// Classify a server notification
+    pub(crate) fn from_notification(server: &str, method: String, params: Option<Value>) -> Self {
+        let server = server.to_string();
+        let value = params.clone().unwrap_or(Value::Null);
+        let typed = match method.as_str() {
+            "notifications/tools/list_changed" => Some(Self::ToolsChanged {
+                server: server.clone(),
+            }),
+            "notifications/resources/list_changed" => Some(Self::ResourcesChanged {
+                server: server.clone(),
+            }),
+            "notifications/prompts/list_changed" => Some(Self::PromptsChanged {
+                server: server.clone(),
+            }),
+            "notifications/resources/updated" => {
+                serde_json::from_value::<ResourceUpdatedNotification>(value)
+                    .ok()
+                    .map(|updated| Self::ResourceUpdated {
+                        server: server.clone(),
+                        uri: updated.uri,
+                    })
+            }
+            "notifications/progress" => {
+                serde_json::from_value(value)
+                    .ok()
+                    .map(|progress| Self::Progress {
+                        server: server.clone(),
+                        progress,
+                    })
+            }
+            "notifications/message" => {
+                serde_json::from_value(value).ok().map(|message| Self::Log {
+                    server: server.clone(),
+                    message,
+                })
+            }
+            _ => None,
+        };
+        typed.unwrap_or(Self::Notification {
+            server,
+            method,
+            params,
+        })
+    }
+
+    /// Server the event came from
+    pub fn server(&self) -> &str {
+        match self {
+            Self::ToolsChanged { server }
+            | Self::ResourcesChanged { server }
+            | Self::PromptsChanged { server }
+            | Self::ResourceUpdated { server, .. }
+            | Self::Progress { server, .. }
+            | Self::Log { server, .. }
+            | Self::Notification { server, .. }
+            | Self::Closed { server } => server,
+        }
+    }
+}
 
 /// MCP Manager for handling multiple MCP server connections
 pub struct McpManager {
     /// Connected clients
     pub(crate) clients: HashMap<String, McpClient>,
+    /// Receives notifications from all servers
+    pub(crate) event_tx: Option<mpsc::UnboundedSender<McpEvent>>,
+    /// Answers requests from all servers
+    pub(crate) handler: Option<Arc<dyn McpRequestHandler>>,
+    /// Stderr per server name, kept after disconnects and failed starts
+    pub(crate) stderr_logs: HashMap<String, StderrLog>,
+    /// Records calls made through the manager
+    pub(crate) audit: Option<McpAuditLog>,
 }
@@ -1,12 +1,15 @@
 //! MCP Server Configuration
 //!
-//! Handles loading and managing MCP server configurations from mcp.json.
+//! Handles loading and managing MCP server configurations from mcp.json,
+//! merged with those of the Claude CLI and other MCP clients.
 
 mod presets;
+mod sources;
 mod types;
 
 #[cfg(test)]
 mod tests;
 
 pub use presets::*;
-pub use types::{McpConfig, McpServerConfig};
+pub use sources::{McpConfigLocations, McpConfigSource, McpServerOrigin, McpSourceKind};
+pub use types::{McpConfig, McpSandbox, McpServerConfig, McpTransportKind, SANDBOX_BASE_ENV};
@@ -16,6 +16,16 @@ pub fn filesystem(allowed_dirs: Vec<PathBuf>) -> McpServerConfig {
         .with_description("Filesystem access MCP server")
 }
 
+/// Claude Visual's own MCP server (projects, git, diagnostics, debugger,
+/// conversation search)
+pub fn claude_visual(executable: &Path) -> McpServerConfig {
+    McpServerConfig::new(executable.to_string_lossy().to_string())
+        .with_arg("--mcp-server")
+        .with_description(
+            "Claude Visual projects, git status, diagnostics, debugger and conversations",
+        )
+}
+
 /// GitHub MCP server
 pub fn github(token: Option<String>) -> McpServerConfig {
     let mut config = McpServerConfig::new("npx")
@@ -0,0 +1,395 @@
+//! Discovery of MCP configurations written for Claude Visual, the Claude
+//! CLI and other MCP clients
+//!
+//! Sources are merged by precedence: a server defined in a higher source
+//! hides entries of the same name further down. Project scopes come before
+//! user scopes; within a scope, Claude Visual's own files come first, then
+//! the Claude CLI's, then other editors'. The order is that of
+//! [`McpSourceKind`].
+
+use super::types::McpServerConfig;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Kind of file an MCP configuration was found in, highest precedence first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum McpSourceKind {
+    /// `mcp.json` or `.mcp/mcp.json` in the working directory or project
+    ClaudeVisualProject,
+    /// Claude CLI local scope: `projects.<path>.mcpServers` in `~/.claude.json`
+    ClaudeLocal,
+    /// Claude CLI project scope: `.mcp.json` in the project
+    ClaudeProject,
+    /// Cursor project configuration: `.cursor/mcp.json`
+    CursorProject,
+    /// VS Code workspace configuration: `.vscode/mcp.json`
+    VsCodeProject,
+    /// `~/.config/claude-visual/mcp.json`
+    ClaudeVisualUser,
+    /// Claude CLI user scope: `mcpServers` in `~/.claude.json`
+    ClaudeUser,
+    /// Cursor global configuration: `~/.cursor/mcp.json`
+    CursorUser,
+    /// Windsurf: `~/.codeium/windsurf/mcp_config.json`
+    Windsurf,
+    /// Claude Desktop: `claude_desktop_config.json`
+    ClaudeDesktop,
+}
+
+impl McpSourceKind {
+    /// Short label for the UI
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::ClaudeVisualProject => "Project",
+            Self::ClaudeLocal => "Claude CLI (local)",
+            Self::ClaudeProject => "Claude CLI (project)",
+            Self::CursorProject => "Cursor (project)",
+            Self::VsCodeProject => "VS Code (workspace)",
+            Self::ClaudeVisualUser => "User",
+            Self::ClaudeUser => "Claude CLI (user)",
+            Self::CursorUser => "Cursor (user)",
+            Self::Windsurf => "Windsurf",
+            Self::ClaudeDesktop => "Claude Desktop",
+        }
+    }
+
+    /// Whether the source only applies to one project
+    pub fn is_project_scope(&self) -> bool {
+        *self <= Self::VsCodeProject
+    }
+
+    /// Key holding the server map
+    fn servers_key(&self) -> &'static str {
+        match self {
+            Self::VsCodeProject => "servers",
+            _ => "mcpServers",
+        }
+    }
+}
+
+/// A file MCP servers are read from and written back to
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct McpConfigSource {
+    pub kind: McpSourceKind,
+    pub path: PathBuf,
+    /// Project the entries belong to, for files shared by several projects
+    /// (`~/.claude.json` local scope)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<PathBuf>,
+}
+
+/// Directories sources are looked up in
+#[derive(Debug, Clone, Default)]
+pub struct McpConfigLocations {
+    /// Working directory
+    pub cwd: Option<PathBuf>,
+    /// Project root (defaults to the working directory)
+    pub project_root: Option<PathBuf>,
+    /// Home directory
+    pub home: Option<PathBuf>,
+    /// Platform configuration directory
+    pub config_dir: Option<PathBuf>,
+}
+
+impl McpConfigLocations {
+    /// Locations of the current process and user
+    pub fn current(project_root: Option<&Path>) -> Self {
+        Self {
+            cwd: std::env::current_dir().ok(),
+            project_root: project_root.map(Path::to_path_buf),
+            home: dirs::home_dir(),
+            config_dir: dirs::config_dir(),
+        }
+    }
+
+    /// Claude Visual's own user configuration
+    pub fn user_source(&self) -> Option<McpConfigSource> {
+        self.config_dir.as_ref().map(|dir| {
+            McpConfigSource::new(
+                McpSourceKind::ClaudeVisualUser,
+                dir.join("claude-visual").join("mcp.json"),
+            )
+        })
+    }
+
+    /// Every place a configuration may live, in precedence order
+    pub fn candidates(&self) -> Vec<McpConfigSource> {
+        use McpSourceKind::*;
+
+        let project = self.project_root.as_ref().or(self.cwd.as_ref());
+        let mut sources = Vec::new();
+
+        if let Some(cwd) = &self.cwd {
+            sources.push(McpConfigSource::new(
+                ClaudeVisualProject,
+                cwd.join("mcp.json"),
+            ));
+        }
+        if let Some(root) = project {
+            sources.push(McpConfigSource::new(
+                ClaudeVisualProject,
+                root.join(".mcp").join("mcp.json"),
+            ));
+            sources.push(McpConfigSource::new(
+                ClaudeVisualProject,
+                root.join("mcp.json"),
+            ));
+            if let Some(home) = &self.home {
+                sources.push(McpConfigSource {
+                    kind: ClaudeLocal,
+                    path: home.join(".claude.json"),
+                    project: Some(root.clone()),
+                });
+            }
+            sources.push(McpConfigSource::new(ClaudeProject, root.join(".mcp.json")));
+            sources.push(McpConfigSource::new(
+                CursorProject,
+                root.join(".cursor").join("mcp.json"),
+            ));
+            sources.push(McpConfigSource::new(
+                VsCodeProject,
+                root.join(".vscode").join("mcp.json"),
+            ));
+        }
+        sources.extend(self.user_source());
+        if let Some(home) = &self.home {
+            sources.push(McpConfigSource::new(ClaudeUser, home.join(".claude.json")));
+            sources.push(McpConfigSource::new(
+                CursorUser,
+                home.join(".cursor").join("mcp.json"),
+            ));
+            sources.push(McpConfigSource::new(
+                Windsurf,
+                home.join(".codeium")
+                    .join("windsurf")
+                    .join("mcp_config.json"),
+            ));
+        }
+        if let Some(dir) = &self.config_dir {
+            sources.push(McpConfigSource::new(
+                ClaudeDesktop,
+                dir.join("Claude").join("claude_desktop_config.json"),
+            ));
+        }
+
+        // The working directory is often the project root
+        let mut seen = std::collections::HashSet::new();
+        sources.retain(|source| seen.insert(source.clone()));
+        sources
+    }
+}
+
+impl McpConfigSource {
+    pub fn new(kind: McpSourceKind, path: impl Into<PathBuf>) -> Self {
+        Self {
+            kind,
+            path: path.into(),
+            project: None,
+        }
+    }
+
+    /// Label with the file, e.g. `Claude CLI (project) · /repo/.mcp.json`
+    pub fn describe(&self) -> String {
+        format!("{} · {}", self.kind.label(), self.path.display())
+    }
+
+    /// Read the servers defined in this source
+    ///
+    /// A missing file has no servers. Entries that do not parse are skipped
+    /// with a warning rather than hiding the rest of the file.
+    pub fn read(&self) -> anyhow::Result<HashMap<String, McpServerConfig>> {
+        if !self.path.exists() {
+            return Ok(HashMap::new());
+        }
+        let content = std::fs::read_to_string(&self.path)?;
+        let document: Value = serde_json::from_str(&content)?;
+
+        let mut servers = HashMap::new();
+        let Some(entries) = self.servers(&document).and_then(Value::as_object) else {
+            return Ok(servers);
+        };
+        for (name, entry) in entries {
+            match serde_json::from_value::<McpServerConfig>(entry.clone()) {
+                Ok(config) => {
+                    servers.insert(name.clone(), config);
+                }
+                Err(e) => tracing::warn!(
+                    "Skipping MCP server '{}' in {}: {}",
+                    name,
+                    self.path.display(),
+                    e
+                ),
+            }
+        }
+        Ok(servers)
+    }
+
+    /// Add, replace (`Some`) or remove (`None`) a server, keeping the rest
+    /// of the file intact, in its original key order
+    pub fn write_server(&self, name: &str, config: Option<&McpServerConfig>) -> anyhow::Result<()> {
+        let mut document: Value = if self.path.exists() {
+            serde_json::from_str(&std::fs::read_to_string(&self.path)?)?
+        } else {
+            Value::Object(Map::new())
+        };
+
+        let servers = self.servers_mut(&mut document)?;
+        match config {
+            Some(config) => {
+                servers.insert(name.to_string(), self.entry(config)?);
+            }
+            None => {
+                servers.remove(name);
+            }
+        }
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // Written beside the file and renamed over it, so the CLI and other
+        // clients sharing it never read half a file
+        let name = self.path.file_name().unwrap_or_default().to_string_lossy();
+        let tmp = self
+            .path
+            .with_file_name(format!("{}.{}.tmp", name, std::process::id()));
+        std::fs::write(&tmp, serde_json::to_string_pretty(&document)?)?;
+        if let Ok(metadata) = std::fs::metadata(&self.path) {
+            std::fs::set_permissions(&tmp, metadata.permissions())?;
+        }
+        if let Err(e) = std::fs::rename(&tmp, &self.path) {
+            let _ = std::fs::remove_file(&tmp);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    fn servers<'a>(&self, document: &'a Value) -> Option<&'a Value> {
+        let scope = match (&self.kind, &self.project) {
+            (McpSourceKind::ClaudeLocal, Some(project)) => document
+                .get("projects")?
+                .get(project.to_string_lossy().as_ref())?,
+            _ => document,
+        };
+        scope.get(self.kind.servers_key())
+    }
+
+    fn servers_mut<'a>(
+        &self,
+        document: &'a mut Value,
+    ) -> anyhow::Result<&'a mut Map<String, Value>> {
+        let mut scope = document;
+        if let (McpSourceKind::ClaudeLocal, Some(project)) = (&self.kind, &self.project) {
+            scope = object_entry(scope, "projects")?;
+            scope = object_entry(scope, &project.to_string_lossy())?;
+        }
+        object_entry(scope, self.kind.servers_key())?
+            .as_object_mut()
+            .ok_or_else(|| anyhow::anyhow!("{} is not a JSON object", self.path.display()))
+    }
+
+    /// Entry as stored in this file; other clients get only the keys they know
+    fn entry(&self, config: &McpServerConfig) -> anyhow::Result<Value> {
+        let mut entry = serde_json::to_value(config)?;
+        if let (Value::Object(entry), false) = (
+            &mut entry,
+            self.kind == McpSourceKind::ClaudeVisualProject
+                || self.kind == McpSourceKind::ClaudeVisualUser,
+        ) {
+            for key in ["enabled", "autoApprove", "description", "sandbox"] {
+                entry.remove(key);
+            }
+            for key in ["args", "env"] {
+                if entry.get(key).is_some_and(is_empty) {
+                    entry.remove(key);
+                }
+            }
+        }
+        Ok(entry)
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Array(items) => items.is_empty(),
+        Value::Object(map) => map.is_empty(),
+        _ => false,
+    }
+}
+
+/// Object under `key`, created if missing
+fn object_entry<'a>(value: &'a mut Value, key: &str) -> anyhow::Result<&'a mut Value> {
+    let object = value
+        .as_object_mut()
+        .ok_or_else(|| anyhow::anyhow!("expected a JSON object around '{}'", key))?;
+    let entry = object
+        .entry(key.to_string())
+        .or_insert_with(|| Value::Object(Map::new()));
+    if !entry.is_object() {
+        anyhow::bail!("'{}' is not a JSON object", key);
+    }
+    Ok(entry)
+}
+
+/// Where a merged server entry came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct McpServerOrigin {
+    /// Source the entry was taken from
+    pub source: McpConfigSource,
+    /// Lower-precedence sources defining the same name
+    pub shadowed: Vec<McpConfigSource>,
+}
+
+/// Expand `${VAR}` and `${VAR:-default}` references using `lookup`
+///
+/// Other `$` sequences are left alone. Returns the names of variables that
+/// are unset and have no default.
+pub(crate) fn expand_vars(
+    text: &str,
+    lookup: &dyn Fn(&str) -> Option<String>,
+    missing: &mut Vec<String>,
+) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        let body = &after[..end];
+        let (name, default) = match body.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (body, None),
+        };
+
+        if is_var_name(name) {
+            match lookup(name).or_else(|| default.map(str::to_string)) {
+                Some(value) => out.push_str(&value),
+                None => {
+                    if !missing.iter().any(|m| m == name) {
+                        missing.push(name.to_string());
+                    }
+                }
+            }
+        } else {
+            // Not ours, e.g. VS Code's `${input:token}`
+            out.push_str(&rest[start..start + 2 + end + 1]);
+        }
+        rest = &after[end + 1..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+fn is_var_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
@@ -3,8 +3,9 @@
 #![cfg(test)]
 
 use super::presets;
-use super::types::{McpConfig, McpServerConfig};
-use std::path::PathBuf;
+use super::sources::{McpConfigLocations, McpConfigSource, McpSourceKind};
+use super::types::{McpConfig, McpSandbox, McpServerConfig, McpTransportKind};
+use std::path::{Path, PathBuf};
 
 #[test]
 fn test_config_serialization() {
@@ -75,3 +76,322 @@ fn test_presets() {
         Some(&"token123".to_string())
     );
 }
+
+#[test]
+fn test_http_server_config() {
+    let json = r#"{
+        "mcpServers": {
+            "shared": {
+                "type": "http",
+                "url": "https://mcp.example.com/mcp",
+                "headers": { "Authorization": "Bearer abc" }
+            },
+            "legacy": {
+                "type": "sse",
+                "url": "https://mcp.example.com/sse"
+            },
+            "inferred": {
+                "url": "https://mcp.example.com/other"
+            },
+            "local": {
+                "command": "node",
+                "args": ["server.js"]
+            }
+        }
+    }"#;
+
+    let config: McpConfig = serde_json::from_str(json).unwrap();
+    let shared = config.get_server("shared").unwrap();
+    assert_eq!(shared.transport_kind(), McpTransportKind::Http);
+    assert_eq!(
+        shared.headers.get("Authorization"),
+        Some(&"Bearer abc".to_string())
+    );
+    assert_eq!(shared.command_line(), "https://mcp.example.com/mcp");
+    assert_eq!(
+        config.get_server("legacy").unwrap().transport_kind(),
+        McpTransportKind::Sse
+    );
+    assert_eq!(
+        config.get_server("inferred").unwrap().transport_kind(),
+        McpTransportKind::Http
+    );
+    assert_eq!(
+        config.get_server("local").unwrap().transport_kind(),
+        McpTransportKind::Stdio
+    );
+
+    // Round trip keeps the url form and omits the empty command
+    let json = serde_json::to_value(McpServerConfig::http("https://x.test/mcp")).unwrap();
+    assert_eq!(json["type"], "http");
+    assert_eq!(json["url"], "https://x.test/mcp");
+    assert!(json.get("command").is_none());
+}
+
+/// Project, home and config directories under a fresh temp directory
+fn locations() -> McpConfigLocations {
+    let base = std::env::temp_dir().join(format!("cv-mcp-config-{}", uuid::Uuid::new_v4()));
+    let project = base.join("project");
+    std::fs::create_dir_all(&project).unwrap();
+    McpConfigLocations {
+        cwd: Some(project.clone()),
+        project_root: Some(project),
+        home: Some(base.join("home")),
+        config_dir: Some(base.join("config")),
+    }
+}
+
+fn write_json(path: &Path, value: serde_json::Value) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, serde_json::to_string_pretty(&value).unwrap()).unwrap();
+}
+
+fn read_json(path: &Path) -> serde_json::Value {
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[test]
+fn test_discover_merges_by_precedence() {
+    let locations = locations();
+    let project = locations.project_root.clone().unwrap();
+    let home = locations.home.clone().unwrap();
+
+    write_json(
+        &home.join(".claude.json"),
+        serde_json::json!({
+            "numStartups": 3,
+            "mcpServers": {
+                "github": { "command": "user-github" },
+                "memory": { "command": "user-memory" }
+            },
+            "projects": {
+                project.to_string_lossy(): {
+                    "mcpServers": { "github": { "command": "local-github" } }
+                }
+            }
+        }),
+    );
+    write_json(
+        &project.join(".mcp.json"),
+        serde_json::json!({ "mcpServers": {
+            "github": { "command": "project-github" },
+            "db": { "type": "http", "url": "http://localhost:3000/mcp" }
+        }}),
+    );
+    write_json(
+        &project.join(".vscode").join("mcp.json"),
+        serde_json::json!({ "servers": { "vscode-only": { "command": "code-server" } } }),
+    );
+
+    let config = McpConfig::discover(&locations);
+    assert_eq!(config.mcp_servers.len(), 4);
+    assert_eq!(config.get_server("github").unwrap().command, "local-github");
+    assert_eq!(config.get_server("memory").unwrap().command, "user-memory");
+    assert_eq!(
+        config.get_server("db").unwrap().transport_kind(),
+        McpTransportKind::Http
+    );
+
+    let origin = config.origin("github").unwrap();
+    assert_eq!(origin.source.kind, McpSourceKind::ClaudeLocal);
+    let shadowed: Vec<_> = origin.shadowed.iter().map(|s| s.kind).collect();
+    assert_eq!(
+        shadowed,
+        vec![McpSourceKind::ClaudeProject, McpSourceKind::ClaudeUser]
+    );
+    assert_eq!(
+        config.origin("vscode-only").unwrap().source.kind,
+        McpSourceKind::VsCodeProject
+    );
+}
+
+#[test]
+fn test_discover_skips_invalid_files_and_entries() {
+    let locations = locations();
+    let project = locations.project_root.clone().unwrap();
+    std::fs::write(project.join(".mcp.json"), "{ not json").unwrap();
+    write_json(
+        &project.join("mcp.json"),
+        serde_json::json!({ "mcpServers": {
+            "good": { "command": "node" },
+            "bad": { "command": 42 }
+        }}),
+    );
+
+    let config = McpConfig::discover(&locations);
+    assert!(config.get_server("good").is_some());
+    assert!(config.get_server("bad").is_none());
+}
+
+#[test]
+fn test_save_server_writes_back_to_origin() {
+    let locations = locations();
+    let project = locations.project_root.clone().unwrap();
+    let home = locations.home.clone().unwrap();
+    let claude_json = home.join(".claude.json");
+    write_json(
+        &claude_json,
+        serde_json::json!({
+            "theme": "dark",
+            "projects": {
+                project.to_string_lossy(): {
+                    "allowedTools": [],
+                    "mcpServers": { "local": { "command": "old" } }
+                }
+            }
+        }),
+    );
+
+    let mut config = McpConfig::discover(&locations);
+    config
+        .save_server("local", McpServerConfig::new("new").with_arg("--flag"))
+        .unwrap();
+
+    let written = read_json(&claude_json);
+    assert_eq!(written["theme"], "dark");
+    // Keys keep their order, and no temporary file is left behind
+    let text = std::fs::read_to_string(&claude_json).unwrap();
+    assert!(text.find("\"theme\"") < text.find("\"projects\""));
+    assert_eq!(std::fs::read_dir(&home).unwrap().count(), 1);
+    let scope = &written["projects"][project.to_string_lossy().as_ref()];
+    assert_eq!(scope["allowedTools"], serde_json::json!([]));
+    assert_eq!(
+        scope["mcpServers"]["local"],
+        serde_json::json!({ "command": "new", "args": ["--flag"] })
+    );
+
+    // New servers go to the user configuration
+    config
+        .save_server("fresh", McpServerConfig::new("fresh"))
+        .unwrap();
+    let user = locations.user_source().unwrap();
+    assert!(user.read().unwrap().contains_key("fresh"));
+    assert_eq!(
+        config.origin("fresh").unwrap().source.kind,
+        McpSourceKind::ClaudeVisualUser
+    );
+}
+
+#[test]
+fn test_delete_server_reveals_shadowed_definition() {
+    let locations = locations();
+    let project = locations.project_root.clone().unwrap();
+    let home = locations.home.clone().unwrap();
+    write_json(
+        &project.join(".mcp.json"),
+        serde_json::json!({ "mcpServers": { "git": { "command": "project-git" } } }),
+    );
+    write_json(
+        &home.join(".cursor").join("mcp.json"),
+        serde_json::json!({ "mcpServers": { "git": { "command": "cursor-git" } } }),
+    );
+
+    let mut config = McpConfig::discover(&locations);
+    let removed = config.delete_server("git").unwrap().unwrap();
+    assert_eq!(removed.command, "project-git");
+
+    let project_file = read_json(&project.join(".mcp.json"));
+    assert_eq!(project_file["mcpServers"], serde_json::json!({}));
+    assert_eq!(config.get_server("git").unwrap().command, "cursor-git");
+    assert_eq!(
+        config.origin("git").unwrap().source.kind,
+        McpSourceKind::CursorUser
+    );
+}
+
+#[test]
+fn test_write_server_keeps_own_fields_in_own_files() {
+    let dir = std::env::temp_dir().join(format!("cv-mcp-write-{}", uuid::Uuid::new_v4()));
+    let server = McpServerConfig::new("node")
+        .with_description("Test")
+        .enabled(false);
+
+    let own = McpConfigSource::new(McpSourceKind::ClaudeVisualUser, dir.join("mcp.json"));
+    own.write_server("test", Some(&server)).unwrap();
+    let entry = &read_json(&own.path)["mcpServers"]["test"];
+    assert_eq!(entry["enabled"], false);
+    assert_eq!(entry["description"], "Test");
+
+    let vscode = McpConfigSource::new(McpSourceKind::VsCodeProject, dir.join("vscode.json"));
+    vscode.write_server("test", Some(&server)).unwrap();
+    assert_eq!(
+        read_json(&vscode.path),
+        serde_json::json!({ "servers": { "test": { "command": "node" } } })
+    );
+}
+
+#[test]
+fn test_expand_env() {
+    let lookup = |name: &str| match name {
+        "TOKEN" => Some("secret".to_string()),
+        "HOME" => Some("/home/me".to_string()),
+        _ => None,
+    };
+    let server = McpServerConfig::new("${HOME}/bin/server")
+        .with_args(["--port", "${PORT:-8080}", "${input:workspace}", "$literal"])
+        .with_env("API_KEY", "${TOKEN}")
+        .with_header("Authorization", "Bearer ${TOKEN}");
+
+    let expanded = server.expand_env_with(lookup).unwrap();
+    assert_eq!(expanded.command, "/home/me/bin/server");
+    assert_eq!(
+        expanded.args,
+        vec!["--port", "8080", "${input:workspace}", "$literal"]
+    );
+    assert_eq!(expanded.env["API_KEY"], "secret");
+    assert_eq!(expanded.headers["Authorization"], "Bearer secret");
+    // The stored configuration keeps the references
+    assert_eq!(server.env["API_KEY"], "${TOKEN}");
+
+    let missing = McpServerConfig::http("https://${HOST}/mcp")
+        .with_header("X-Key", "${KEY}")
+        .expand_env_with(lookup)
+        .unwrap_err();
+    assert_eq!(
+        missing.to_string(),
+        "Environment variables not set: HOST, KEY"
+    );
+}
+
+#[test]
+fn test_candidates_dedupe_cwd_and_root() {
+    let locations = locations();
+    let candidates = locations.candidates();
+    let project_json = locations.project_root.clone().unwrap().join("mcp.json");
+    assert_eq!(
+        candidates.iter().filter(|s| s.path == project_json).count(),
+        1
+    );
+    assert!(candidates.windows(2).all(|w| w[0].kind <= w[1].kind));
+}
+
+#[test]
+fn test_sandbox_config() {
+    let sandbox = McpSandbox::default()
+        .inherit("GITHUB_TOKEN")
+        .inherit("AWS_*")
+        .with_memory_mb(512)
+        .without_network();
+    assert!(sandbox.inherits("PATH"));
+    assert!(sandbox.inherits("LC_ALL"));
+    assert!(sandbox.inherits("GITHUB_TOKEN"));
+    assert!(sandbox.inherits("AWS_REGION"));
+    assert!(!sandbox.inherits("OPENAI_API_KEY"));
+
+    let server = McpServerConfig::new("npx").with_sandbox(sandbox.clone());
+    let json = serde_json::to_value(&server).unwrap();
+    assert_eq!(
+        json["sandbox"],
+        serde_json::json!({
+            "inheritEnv": ["GITHUB_TOKEN", "AWS_*"],
+            "memoryMb": 512,
+            "noNetwork": true
+        })
+    );
+    let parsed: McpServerConfig = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed.sandbox, Some(sandbox));
+
+    // Unsandboxed servers keep the field out of the file
+    let json = serde_json::to_value(McpServerConfig::new("node")).unwrap();
+    assert!(json.get("sandbox").is_none());
+}
@@ -1,8 +1,9 @@
 //! Core MCP configuration types
 
+use super::sources::{expand_vars, McpConfigLocations, McpConfigSource, McpServerOrigin};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// MCP configuration file (mcp.json)
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -11,13 +12,36 @@ pub struct McpConfig {
     /// MCP servers configuration
     #[serde(default)]
     pub mcp_servers: HashMap<String, McpServerConfig>,
+    /// Where each discovered server is defined
+    #[serde(skip)]
+    origins: HashMap<String, McpServerOrigin>,
+    /// Where servers without an origin are saved
+    #[serde(skip)]
+    default_source: Option<McpConfigSource>,
+}
+
+/// How the client talks to an MCP server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum McpTransportKind {
+    /// Child process over stdin/stdout
+    Stdio,
+    /// Streamable HTTP (single endpoint, JSON or SSE responses)
+    #[serde(alias = "streamable-http", alias = "streamableHttp")]
+    Http,
+    /// Legacy HTTP+SSE (event stream plus a POST endpoint)
+    Sse,
 }
 
 /// Configuration for a single MCP server
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct McpServerConfig {
-    /// Command to execute to start the server
+    /// Transport; inferred from `url`/`command` when absent
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub transport: Option<McpTransportKind>,
+    /// Command to execute to start the server (stdio servers)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub command: String,
     /// Arguments to pass to the command
     #[serde(default)]
@@ -25,6 +49,12 @@ pub struct McpServerConfig {
     /// Environment variables to set
     #[serde(default)]
     pub env: HashMap<String, String>,
+    /// Server URL (HTTP servers)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// HTTP headers sent with every request (e.g. Authorization)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
     /// Whether the server is enabled
     #[serde(default = "default_enabled")]
     pub enabled: bool,
@@ -34,12 +64,122 @@ pub struct McpServerConfig {
     /// Auto-approve tool calls (dangerous, use with caution)
     #[serde(default)]
     pub auto_approve: Vec<String>,
+    /// Isolation for the server process (stdio servers)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<McpSandbox>,
 }
 
 fn default_enabled() -> bool {
     true
 }
 
+/// Isolation and resource limits for a stdio server process
+///
+/// The server gets a cleaned environment: only [`SANDBOX_BASE_ENV`], the
+/// variables listed in `inherit_env` and the server's own `env` entries.
+/// Limits are applied with `setrlimit` and network isolation with a Linux
+/// network namespace. On Linux, `working_dir` also confines writes with
+/// Landlock; reads are not restricted, so this is not a full jail. Other
+/// platforms skip what they cannot enforce with a warning.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpSandbox {
+    /// Extra variables passed through from the app's environment
+    /// (`NAME`, or `PREFIX_*` for all variables starting with `PREFIX_`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inherit_env: Vec<String>,
+    /// Directory the server runs in; `HOME` and `TMPDIR` point inside it
+    ///
+    /// On Linux the server can only create, change or remove files here
+    /// (writing to existing devices such as `/dev/null` aside), and the
+    /// launch fails on kernels without Landlock.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<PathBuf>,
+    /// CPU time limit in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_seconds: Option<u64>,
+    /// Address space limit in MiB
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_mb: Option<u64>,
+    /// Maximum number of open file descriptors
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_open_files: Option<u64>,
+    /// Run without network access
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub no_network: bool,
+}
+
+/// Variables every sandboxed server inherits
+pub const SANDBOX_BASE_ENV: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "SHELL",
+    "LANG",
+    "LC_*",
+    "TERM",
+    "TZ",
+    "TMPDIR",
+    "SYSTEMROOT",
+    "SystemRoot",
+    "USERPROFILE",
+    "APPDATA",
+    "LOCALAPPDATA",
+    "TEMP",
+    "TMP",
+];
+
+impl McpSandbox {
+    /// Pass a variable (or `PREFIX_*`) through from the app's environment
+    pub fn inherit(mut self, pattern: impl Into<String>) -> Self {
+        self.inherit_env.push(pattern.into());
+        self
+    }
+
+    /// Run the server in `dir`
+    pub fn with_working_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.working_dir = Some(dir.into());
+        self
+    }
+
+    /// Limit CPU time
+    pub fn with_cpu_seconds(mut self, seconds: u64) -> Self {
+        self.cpu_seconds = Some(seconds);
+        self
+    }
+
+    /// Limit the address space
+    pub fn with_memory_mb(mut self, megabytes: u64) -> Self {
+        self.memory_mb = Some(megabytes);
+        self
+    }
+
+    /// Limit open file descriptors
+    pub fn with_max_open_files(mut self, count: u64) -> Self {
+        self.max_open_files = Some(count);
+        self
+    }
+
+    /// Cut off network access
+    pub fn without_network(mut self) -> Self {
+        self.no_network = true;
+        self
+    }
+
+    /// Whether a variable of the app's environment is passed to the server
+    pub fn inherits(&self, name: &str) -> bool {
+        SANDBOX_BASE_ENV
+            .iter()
+            .copied()
+            .chain(self.inherit_env.iter().map(String::as_str))
+            .any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => name.starts_with(prefix),
+                None => name == pattern,
+            })
+    }
+}
+
 impl McpConfig {
     /// Load MCP configuration from a file
     pub fn load(path: &Path) -> anyhow::Result<Self> {
@@ -52,43 +192,123 @@ impl McpConfig {
         Ok(config)
     }
 
-    /// Load MCP configuration from the default location
-    /// Searches in order:
-    /// 1. Current directory ./mcp.json
-    /// 2. Project root .mcp/mcp.json
-    /// 3. User config ~/.config/claude-visual/mcp.json
+    /// Load and merge MCP configurations from the default locations
+    ///
+    /// Reads Claude Visual's `mcp.json` files together with those of the
+    /// Claude CLI (`.mcp.json`, `~/.claude.json`), Cursor, VS Code, Windsurf
+    /// and Claude Desktop. Project scopes win over user scopes; see
+    /// [`McpSourceKind`](super::McpSourceKind) for the full order.
     pub fn load_default(project_root: Option<&Path>) -> anyhow::Result<Self> {
-        // Try current directory first
-        let cwd = std::env::current_dir()?;
-        let cwd_config = cwd.join("mcp.json");
-        if cwd_config.exists() {
-            return Self::load(&cwd_config);
-        }
+        Ok(Self::discover(&McpConfigLocations::current(project_root)))
+    }
 
-        // Try project root
-        if let Some(root) = project_root {
-            let project_config = root.join(".mcp").join("mcp.json");
-            if project_config.exists() {
-                return Self::load(&project_config);
-            }
+    /// Merge the configurations found at `locations`
+    ///
+    /// Unreadable files are skipped with a warning.
+    pub fn discover(locations: &McpConfigLocations) -> Self {
+        let mut config = Self {
+            default_source: locations.user_source(),
+            ..Self::default()
+        };
 
-            // Also try mcp.json directly in project root
-            let root_config = root.join("mcp.json");
-            if root_config.exists() {
-                return Self::load(&root_config);
+        for source in locations.candidates() {
+            let servers = match source.read() {
+                Ok(servers) => servers,
+                Err(e) => {
+                    tracing::warn!("Skipping MCP config {}: {}", source.path.display(), e);
+                    continue;
+                }
+            };
+            for (name, server) in servers {
+                match config.origins.get_mut(&name) {
+                    Some(origin) => origin.shadowed.push(source.clone()),
+                    None => {
+                        config.origins.insert(
+                            name.clone(),
+                            McpServerOrigin {
+                                source: source.clone(),
+                                shadowed: Vec::new(),
+                            },
+                        );
+                        config.mcp_servers.insert(name, server);
+                    }
+                }
             }
         }
 
-        // Try user config directory
-        if let Some(config_dir) = dirs::config_dir() {
-            let user_config = config_dir.join("claude-visual").join("mcp.json");
-            if user_config.exists() {
-                return Self::load(&user_config);
+        config
+    }
+
+    /// Where a discovered server is defined
+    pub fn origin(&self, name: &str) -> Option<&McpServerOrigin> {
+        self.origins.get(name)
+    }
+
+    /// Add or update a server and write it back to the file it came from
+    ///
+    /// New servers go to Claude Visual's user configuration.
+    pub fn save_server(&mut self, name: &str, config: McpServerConfig) -> anyhow::Result<()> {
+        let source = match self.origins.get(name) {
+            Some(origin) => origin.source.clone(),
+            None => self
+                .default_source
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("No configuration directory to save to"))?,
+        };
+        self.save_server_to(name, config, source)
+    }
+
+    /// Add or update a server in a specific file
+    pub fn save_server_to(
+        &mut self,
+        name: &str,
+        config: McpServerConfig,
+        source: McpConfigSource,
+    ) -> anyhow::Result<()> {
+        source.write_server(name, Some(&config))?;
+
+        let shadowed = match self.origins.remove(name) {
+            Some(origin) if origin.source == source => origin.shadowed,
+            Some(origin) => {
+                let mut shadowed = origin.shadowed;
+                shadowed.retain(|s| *s != source);
+                shadowed.push(origin.source);
+                shadowed.sort_by_key(|s| s.kind);
+                shadowed
             }
-        }
+            None => Vec::new(),
+        };
+        self.origins
+            .insert(name.to_string(), McpServerOrigin { source, shadowed });
+        self.mcp_servers.insert(name.to_string(), config);
+        Ok(())
+    }
+
+    /// Remove a server from the file defining it
+    ///
+    /// A definition it shadowed, if any, takes its place.
+    pub fn delete_server(&mut self, name: &str) -> anyhow::Result<Option<McpServerConfig>> {
+        let Some(origin) = self.origins.remove(name) else {
+            return Ok(self.mcp_servers.remove(name));
+        };
+        origin.source.write_server(name, None)?;
+        let removed = self.mcp_servers.remove(name);
 
-        // Return empty config if no file found
-        Ok(Self::default())
+        let mut shadowed = origin.shadowed.into_iter();
+        while let Some(source) = shadowed.next() {
+            if let Some(server) = source.read()?.remove(name) {
+                self.origins.insert(
+                    name.to_string(),
+                    McpServerOrigin {
+                        source,
+                        shadowed: shadowed.collect(),
+                    },
+                );
+                self.mcp_servers.insert(name.to_string(), server);
+                break;
+            }
+        }
+        Ok(removed)
     }
 
     /// Save MCP configuration to a file
@@ -147,12 +367,34 @@ impl McpServerConfig {
     /// Create a new server configuration
     pub fn new(command: impl Into<String>) -> Self {
         Self {
+            transport: None,
             command: command.into(),
             args: Vec::new(),
             env: HashMap::new(),
+            url: None,
+            headers: HashMap::new(),
             enabled: true,
             description: None,
             auto_approve: Vec::new(),
+            sandbox: None,
+        }
+    }
+
+    /// Create a configuration for a Streamable HTTP server
+    pub fn http(url: impl Into<String>) -> Self {
+        Self {
+            transport: Some(McpTransportKind::Http),
+            url: Some(url.into()),
+            ..Self::new("")
+        }
+    }
+
+    /// Create a configuration for a legacy HTTP+SSE server
+    pub fn sse(url: impl Into<String>) -> Self {
+        Self {
+            transport: Some(McpTransportKind::Sse),
+            url: Some(url.into()),
+            ..Self::new("")
         }
     }
 
@@ -174,20 +416,77 @@ impl McpServerConfig {
         self
     }
 
+    /// Add an HTTP header
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+        self
+    }
+
     /// Set description
     pub fn with_description(mut self, desc: impl Into<String>) -> Self {
         self.description = Some(desc.into());
         self
     }
 
+    /// Run the server process in a sandbox
+    pub fn with_sandbox(mut self, sandbox: McpSandbox) -> Self {
+        self.sandbox = Some(sandbox);
+        self
+    }
+
     /// Set enabled state
     pub fn enabled(mut self, enabled: bool) -> Self {
         self.enabled = enabled;
         self
     }
 
-    /// Get the full command line
+    /// Effective transport
+    pub fn transport_kind(&self) -> McpTransportKind {
+        match self.transport {
+            Some(kind) => kind,
+            None if self.url.is_some() && self.command.is_empty() => McpTransportKind::Http,
+            None => McpTransportKind::Stdio,
+        }
+    }
+
+    /// Copy with `${VAR}` and `${VAR:-default}` in the command, arguments,
+    /// environment, URL and headers replaced from the process environment
+    pub fn expand_env(&self) -> anyhow::Result<Self> {
+        self.expand_env_with(|name| std::env::var(name).ok())
+    }
+
+    /// Like [`expand_env`](Self::expand_env) with a custom variable lookup
+    pub fn expand_env_with(&self, lookup: impl Fn(&str) -> Option<String>) -> anyhow::Result<Self> {
+        let mut missing = Vec::new();
+        let mut expand = |text: &str| expand_vars(text, &lookup, &mut missing);
+
+        let mut expanded = self.clone();
+        expanded.command = expand(&self.command);
+        expanded.args = self.args.iter().map(|a| expand(a)).collect();
+        expanded.url = self.url.as_deref().map(&mut expand);
+        for value in expanded
+            .env
+            .values_mut()
+            .chain(expanded.headers.values_mut())
+        {
+            *value = expand(value);
+        }
+
+        if !missing.is_empty() {
+            anyhow::bail!(
+                "Environment variable{} not set: {}",
+                if missing.len() == 1 { "" } else { "s" },
+                missing.join(", ")
+            );
+        }
+        Ok(expanded)
+    }
+
+    /// Get the full command line (or the URL for HTTP servers)
     pub fn command_line(&self) -> String {
+        if self.transport_kind() != McpTransportKind::Stdio {
+            return self.url.clone().unwrap_or_default();
+        }
         let mut parts = vec![self.command.clone()];
         parts.extend(self.args.clone());
         parts.join(" ")
@@ -0,0 +1,159 @@
+//! MCP host side: answers what servers ask of the client
+//!
+//! [`McpHost`] is the [`McpRequestHandler`] the app installs on its
+//! [`McpManager`](super::McpManager). It lists the open project as the roots
+//! servers may work in, runs sampling requests through an
+//! [`ApprovalGatedProvider`] so nothing reaches a model without the user's
+//! consent, and forwards elicitations to the UI.
+
+mod sampling;
+mod types;
+
+#[cfg(test)]
+mod tests;
+
+pub use sampling::{sampling_request, sampling_result, ApprovalGatedProvider};
+pub use types::{ApprovalDecision, ApprovalRequest, ElicitationRequest};
+
+use super::client::McpRequestHandler;
+use super::protocol::*;
+use crate::ai::provider::AIError;
+use crate::project::manager::Project;
+use async_trait::async_trait;
+use parking_lot::RwLock;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot};
+
+/// JSON-RPC error code for a request the user rejected
+const USER_REJECTED: i32 = -1;
+
+/// Client-side handler for server requests
+#[derive(Default)]
+pub struct McpHost {
+    /// Roots exposed to servers
+    roots: RwLock<Vec<McpRoot>>,
+    /// Provider used for sampling
+    sampling: Option<Arc<ApprovalGatedProvider>>,
+    /// Where elicitations are sent for the user to answer
+    elicitation: Option<mpsc::UnboundedSender<ElicitationRequest>>,
+}
+
+impl McpHost {
+    /// Create a host exposing no roots, with sampling and elicitation off
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enable sampling through an approval-gated provider
+    pub fn with_sampling(mut self, provider: Arc<ApprovalGatedProvider>) -> Self {
+        self.sampling = Some(provider);
+        self
+    }
+
+    /// Enable elicitation, delivering requests to the UI through a channel
+    pub fn with_elicitation(mut self, requests: mpsc::UnboundedSender<ElicitationRequest>) -> Self {
+        self.elicitation = Some(requests);
+        self
+    }
+
+    /// Current roots
+    pub fn roots(&self) -> Vec<McpRoot> {
+        self.roots.read().clone()
+    }
+
+    /// Replace the roots, returning whether they changed
+    ///
+    /// When they did, call
+    /// [`McpManager::notify_roots_changed`](super::McpManager::notify_roots_changed).
+    pub fn set_roots(&self, roots: Vec<McpRoot>) -> bool {
+        let mut current = self.roots.write();
+        if *current == roots {
+            return false;
+        }
+        *current = roots;
+        true
+    }
+
+    /// Expose the open project (or nothing) as the only root
+    pub fn set_project(&self, project: Option<&Project>) -> bool {
+        let roots = project
+            .and_then(|p| root_for_path(&p.path, Some(p.name.clone())))
+            .into_iter()
+            .collect();
+        self.set_roots(roots)
+    }
+}
+
+/// Root for a local directory (must be absolute)
+pub fn root_for_path(path: &Path, name: Option<String>) -> Option<McpRoot> {
+    let uri = reqwest::Url::from_directory_path(path).ok()?;
+    Some(McpRoot {
+        // Servers compare roots as plain URIs; drop the trailing slash
+        uri: uri.as_str().trim_end_matches('/').to_string(),
+        name,
+    })
+}
+
+#[async_trait]
+impl McpRequestHandler for McpHost {
+    fn capabilities(&self) -> McpCapabilities {
+        McpCapabilities {
+            roots: Some(RootsCapability {
+                list_changed: Some(true),
+            }),
+            sampling: self.sampling.as_ref().map(|_| SamplingCapability {}),
+            elicitation: self.elicitation.as_ref().map(|_| ElicitationCapability {}),
+            experimental: None,
+        }
+    }
+
+    async fn list_roots(&self, _server: &str) -> Result<ListRootsResult, McpError> {
+        Ok(ListRootsResult {
+            roots: self.roots(),
+        })
+    }
+
+    async fn create_message(
+        &self,
+        server: &str,
+        params: CreateMessageParams,
+    ) -> Result<CreateMessageResult, McpError> {
+        let provider = self.sampling.as_ref().ok_or_else(|| McpError::Server {
+            code: McpErrorCode::MethodNotFound as i32,
+            message: "Sampling is not enabled".to_string(),
+        })?;
+
+        let request = sampling_request(&params, provider.as_ref())?;
+        match provider.complete_for(Some(server), request).await {
+            Ok(response) => Ok(sampling_result(response)),
+            Err(AIError::Denied(reason)) => Err(McpError::Server {
+                code: USER_REJECTED,
+                message: format!("User rejected sampling request: {}", reason),
+            }),
+            Err(e) => Err(McpError::Server {
+                code: McpErrorCode::InternalError as i32,
+                message: e.to_string(),
+            }),
+        }
+    }
+
+    async fn elicit(&self, server: &str, params: ElicitParams) -> Result<ElicitResult, McpError> {
+        let requests = self.elicitation.as_ref().ok_or_else(|| McpError::Server {
+            code: McpErrorCode::MethodNotFound as i32,
+            message: "Elicitation is not enabled".to_string(),
+        })?;
+
+        let (respond, response) = oneshot::channel();
+        let request = ElicitationRequest {
+            server: server.to_string(),
+            params,
+            respond,
+        };
+        if requests.send(request).is_err() {
+            return Ok(ElicitResult::cancel());
+        }
+        // A request dropped without an answer counts as dismissed
+        Ok(response.await.unwrap_or_else(|_| ElicitResult::cancel()))
+    }
+}
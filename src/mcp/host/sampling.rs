@@ -0,0 +1,171 @@
+//! Sampling: model requests made on behalf of MCP servers
+
+use super::super::protocol::*;
+use super::types::{ApprovalDecision, ApprovalRequest};
+use crate::ai::provider::{
+    AIError, AIProvider, AIRequest, AIResponse, AIStream, Message, ModelInfo, StopReason,
+};
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot};
+
+/// Provider that asks the user before every request
+///
+/// Each request is sent as an [`ApprovalRequest`] on the approvals channel
+/// and only forwarded to the inner provider once approved. A denied,
+/// dropped or unanswerable request fails with [`AIError::Denied`].
+pub struct ApprovalGatedProvider {
+    /// Provider that actually runs approved requests
+    inner: Arc<dyn AIProvider>,
+    /// Where approval requests are sent
+    approvals: mpsc::UnboundedSender<ApprovalRequest>,
+}
+
+impl ApprovalGatedProvider {
+    /// Gate `inner` behind approvals delivered on `approvals`
+    pub fn new(
+        inner: Arc<dyn AIProvider>,
+        approvals: mpsc::UnboundedSender<ApprovalRequest>,
+    ) -> Self {
+        Self { inner, approvals }
+    }
+
+    /// Complete a request made on behalf of `origin`
+    pub async fn complete_for(
+        &self,
+        origin: Option<&str>,
+        request: AIRequest,
+    ) -> Result<AIResponse, AIError> {
+        let request = self.approve(origin, request).await?;
+        self.inner.complete(request).await
+    }
+
+    /// Wait for the user's decision
+    async fn approve(
+        &self,
+        origin: Option<&str>,
+        request: AIRequest,
+    ) -> Result<AIRequest, AIError> {
+        let (respond, decision) = oneshot::channel();
+        let pending = ApprovalRequest {
+            origin: origin.map(str::to_string),
+            request: request.clone(),
+            respond,
+        };
+        if self.approvals.send(pending).is_err() {
+            return Err(AIError::Denied("No one to approve the request".to_string()));
+        }
+
+        match decision.await {
+            Ok(ApprovalDecision::Approve) => Ok(request),
+            Ok(ApprovalDecision::Deny) => Err(AIError::Denied("Denied by user".to_string())),
+            Err(_) => Err(AIError::Denied("Dismissed without approval".to_string())),
+        }
+    }
+}
+
+#[async_trait]
+impl AIProvider for ApprovalGatedProvider {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn models(&self) -> Vec<ModelInfo> {
+        self.inner.models()
+    }
+
+    fn default_model(&self) -> &str {
+        self.inner.default_model()
+    }
+
+    fn is_configured(&self) -> bool {
+        self.inner.is_configured()
+    }
+
+    async fn complete(&self, request: AIRequest) -> Result<AIResponse, AIError> {
+        self.complete_for(None, request).await
+    }
+
+    async fn stream(&self, request: AIRequest) -> Result<AIStream, AIError> {
+        let request = self.approve(None, request).await?;
+        self.inner.stream(request).await
+    }
+
+    fn count_tokens(&self, text: &str) -> usize {
+        self.inner.count_tokens(text)
+    }
+}
+
+/// Build a provider request from `sampling/createMessage` parameters
+///
+/// Only text content is supported. The model is the first provider model
+/// matching a hint (case-insensitive substring), else the default model.
+pub fn sampling_request(
+    params: &CreateMessageParams,
+    provider: &dyn AIProvider,
+) -> Result<AIRequest, McpError> {
+    let messages = params
+        .messages
+        .iter()
+        .map(|message| {
+            let SamplingContent::Text { text } = &message.content else {
+                return Err(McpError::Server {
+                    code: McpErrorCode::InvalidParams as i32,
+                    message: "Only text sampling content is supported".to_string(),
+                });
+            };
+            Ok(match message.role.as_str() {
+                "assistant" => Message::assistant(text.clone()),
+                _ => Message::user(text.clone()),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(AIRequest {
+        model: choose_model(params.model_preferences.as_ref(), provider),
+        messages,
+        system: params.system_prompt.clone(),
+        max_tokens: Some(params.max_tokens),
+        temperature: params.temperature,
+        stop: params.stop_sequences.clone(),
+        stream: false,
+        ..Default::default()
+    })
+}
+
+fn choose_model(preferences: Option<&ModelPreferences>, provider: &dyn AIProvider) -> String {
+    let models = provider.models();
+    preferences
+        .into_iter()
+        .flat_map(|p| p.hints.iter())
+        .filter_map(|hint| hint.name.as_deref())
+        .find_map(|hint| {
+            let hint = hint.to_lowercase();
+            models
+                .iter()
+                .find(|m| m.id.to_lowercase().contains(&hint))
+                .map(|m| m.id.clone())
+        })
+        .unwrap_or_else(|| provider.default_model().to_string())
+}
+
+/// Convert a provider response to a `sampling/createMessage` result
+pub fn sampling_result(response: AIResponse) -> CreateMessageResult {
+    let stop_reason = response.stop_reason.map(|reason| {
+        match reason {
+            StopReason::EndTurn => "endTurn",
+            StopReason::StopSequence => "stopSequence",
+            StopReason::MaxTokens => "maxTokens",
+            StopReason::ToolUse => "toolUse",
+        }
+        .to_string()
+    });
+    CreateMessageResult {
+        role: "assistant".to_string(),
+        content: SamplingContent::Text {
+            text: response.content,
+        },
+        model: response.model,
+        stop_reason,
+    }
+}
@@ -0,0 +1,234 @@
+//! Tests for the MCP host
+
+use super::*;
+use crate::ai::provider::{AIProvider, AIRequest, AIResponse, AIStream, ModelInfo, StopReason};
+use serde_json::json;
+
+/// Provider echoing the last message
+struct EchoProvider {
+    models: Vec<ModelInfo>,
+}
+
+impl EchoProvider {
+    fn new(ids: &[&str]) -> Arc<Self> {
+        let models = ids
+            .iter()
+            .map(|id| ModelInfo {
+                id: id.to_string(),
+                name: id.to_string(),
+                provider: "echo".to_string(),
+                context_length: 8_000,
+                supports_streaming: true,
+                supports_tools: false,
+                supports_vision: false,
+                input_cost_per_1k: None,
+                output_cost_per_1k: None,
+            })
+            .collect();
+        Arc::new(Self { models })
+    }
+}
+
+#[async_trait]
+impl AIProvider for EchoProvider {
+    fn name(&self) -> &str {
+        "echo"
+    }
+
+    fn models(&self) -> Vec<ModelInfo> {
+        self.models.clone()
+    }
+
+    fn default_model(&self) -> &str {
+        &self.models[0].id
+    }
+
+    fn is_configured(&self) -> bool {
+        true
+    }
+
+    async fn complete(&self, request: AIRequest) -> Result<AIResponse, AIError> {
+        Ok(AIResponse {
+            id: "resp".to_string(),
+            model: request.model,
+            content: request.messages.last().unwrap().content.clone(),
+            stop_reason: Some(StopReason::MaxTokens),
+            tool_calls: Vec::new(),
+            usage: None,
+            metadata: Default::default(),
+        })
+    }
+
+    async fn stream(&self, _request: AIRequest) -> Result<AIStream, AIError> {
+        Err(AIError::InvalidRequest("not streaming".to_string()))
+    }
+}
+
+fn params(value: serde_json::Value) -> CreateMessageParams {
+    serde_json::from_value(value).unwrap()
+}
+
+fn hello() -> CreateMessageParams {
+    params(json!({
+        "messages": [{ "role": "user", "content": { "type": "text", "text": "hello" } }],
+        "maxTokens": 100
+    }))
+}
+
+fn sampling_host() -> (McpHost, mpsc::UnboundedReceiver<ApprovalRequest>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let provider = ApprovalGatedProvider::new(EchoProvider::new(&["small-1", "large-2"]), tx);
+    (McpHost::new().with_sampling(Arc::new(provider)), rx)
+}
+
+#[test]
+fn test_capabilities_follow_configuration() {
+    let caps = McpHost::new().capabilities();
+    assert!(caps.roots.is_some());
+    assert!(caps.sampling.is_none());
+    assert!(caps.elicitation.is_none());
+
+    let (host, _approvals) = sampling_host();
+    let (tx, _rx) = mpsc::unbounded_channel();
+    let caps = host.with_elicitation(tx).capabilities();
+    assert!(caps.sampling.is_some());
+    assert!(caps.elicitation.is_some());
+}
+
+#[tokio::test]
+async fn test_roots() {
+    let host = McpHost::new();
+    let dir = std::env::temp_dir().join("mcp-root");
+    let root = root_for_path(&dir, Some("mcp-root".to_string())).unwrap();
+    assert!(root.uri.starts_with("file://"));
+    assert!(!root.uri.ends_with('/'));
+
+    assert!(host.set_roots(vec![root.clone()]));
+    assert!(!host.set_roots(vec![root.clone()]));
+    assert_eq!(host.list_roots("s").await.unwrap().roots, vec![root]);
+
+    assert!(root_for_path(Path::new("relative"), None).is_none());
+}
+
+#[test]
+fn test_sampling_request_model_hints() {
+    let provider = EchoProvider::new(&["small-1", "large-2"]);
+
+    let request = sampling_request(&hello(), provider.as_ref()).unwrap();
+    assert_eq!(request.model, "small-1");
+    assert_eq!(request.max_tokens, Some(100));
+    assert!(!request.stream);
+
+    let mut hinted = hello();
+    hinted.model_preferences = Some(ModelPreferences {
+        hints: vec![
+            ModelHint {
+                name: Some("missing".to_string()),
+            },
+            ModelHint {
+                name: Some("LARGE".to_string()),
+            },
+        ],
+        ..Default::default()
+    });
+    hinted.system_prompt = Some("Be brief".to_string());
+    let request = sampling_request(&hinted, provider.as_ref()).unwrap();
+    assert_eq!(request.model, "large-2");
+    assert_eq!(request.system.as_deref(), Some("Be brief"));
+
+    let image = params(json!({
+        "messages": [{
+            "role": "user",
+            "content": { "type": "image", "data": "AA==", "mimeType": "image/png" }
+        }],
+        "maxTokens": 10
+    }));
+    assert!(matches!(
+        sampling_request(&image, provider.as_ref()),
+        Err(McpError::Server { code: -32602, .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_sampling_requires_approval() {
+    let (host, mut approvals) = sampling_host();
+    let host = Arc::new(host);
+
+    let call = tokio::spawn({
+        let host = host.clone();
+        async move { host.create_message("fs", hello()).await }
+    });
+    let pending = approvals.recv().await.unwrap();
+    assert_eq!(pending.origin.as_deref(), Some("fs"));
+    assert_eq!(pending.request.messages[0].content, "hello");
+    pending.approve();
+
+    let result = call.await.unwrap().unwrap();
+    assert_eq!(result.role, "assistant");
+    assert_eq!(result.model, "small-1");
+    assert_eq!(result.stop_reason.as_deref(), Some("maxTokens"));
+    assert!(matches!(result.content, SamplingContent::Text { ref text } if text == "hello"));
+}
+
+#[tokio::test]
+async fn test_sampling_denied() {
+    let (host, mut approvals) = sampling_host();
+    let host = Arc::new(host);
+
+    let call = tokio::spawn({
+        let host = host.clone();
+        async move { host.create_message("fs", hello()).await }
+    });
+    approvals.recv().await.unwrap().deny();
+    assert!(matches!(
+        call.await.unwrap(),
+        Err(McpError::Server {
+            code: USER_REJECTED,
+            ..
+        })
+    ));
+
+    // Dismissing the prompt also denies
+    let call = tokio::spawn({
+        let host = host.clone();
+        async move { host.create_message("fs", hello()).await }
+    });
+    drop(approvals.recv().await.unwrap());
+    assert!(call.await.unwrap().is_err());
+
+    // Sampling is refused outright when not enabled
+    assert!(matches!(
+        McpHost::new().create_message("fs", hello()).await,
+        Err(McpError::Server { code: -32601, .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_elicitation() {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let host = Arc::new(McpHost::new().with_elicitation(tx));
+    let ask = ElicitParams {
+        message: "Which branch?".to_string(),
+        requested_schema: json!({ "type": "object" }),
+    };
+
+    let call = tokio::spawn({
+        let host = host.clone();
+        let ask = ask.clone();
+        async move { host.elicit("git", ask).await }
+    });
+    let request = rx.recv().await.unwrap();
+    assert_eq!(request.server, "git");
+    let mut content = serde_json::Map::new();
+    content.insert("branch".to_string(), json!("main"));
+    request.respond(ElicitResult::accept(content));
+    let result = call.await.unwrap().unwrap();
+    assert_eq!(result.action, ElicitAction::Accept);
+
+    let call = tokio::spawn({
+        let host = host.clone();
+        async move { host.elicit("git", ask).await }
+    });
+    drop(rx.recv().await.unwrap());
+    assert_eq!(call.await.unwrap().unwrap().action, ElicitAction::Cancel);
+}
@@ -0,0 +1,60 @@
+//! Requests the host passes to the UI
+
+use super::super::protocol::{ElicitParams, ElicitResult};
+use crate::ai::provider::AIRequest;
+use tokio::sync::oneshot;
+
+/// User decision on a gated model request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApprovalDecision {
+    /// Send the request
+    Approve,
+    /// Refuse it
+    Deny,
+}
+
+/// A model request waiting for the user's approval
+///
+/// Dropping it without answering denies the request.
+pub struct ApprovalRequest {
+    /// Who is asking (MCP server name), if known
+    pub origin: Option<String>,
+    /// The request that would be sent
+    pub request: AIRequest,
+    pub(crate) respond: oneshot::Sender<ApprovalDecision>,
+}
+
+impl ApprovalRequest {
+    /// Answer the request
+    pub fn decide(self, decision: ApprovalDecision) {
+        let _ = self.respond.send(decision);
+    }
+
+    /// Allow the request
+    pub fn approve(self) {
+        self.decide(ApprovalDecision::Approve);
+    }
+
+    /// Refuse the request
+    pub fn deny(self) {
+        self.decide(ApprovalDecision::Deny);
+    }
+}
+
+/// A server asking the user for input
+///
+/// Dropping it without answering reports `cancel` to the server.
+pub struct ElicitationRequest {
+    /// Server asking
+    pub server: String,
+    /// Message and requested schema
+    pub params: ElicitParams,
+    pub(crate) respond: oneshot::Sender<ElicitResult>,
+}
+
+impl ElicitationRequest {
+    /// Send the user's answer
+    pub fn respond(self, result: ElicitResult) {
+        let _ = self.respond.send(result);
+    }
+}
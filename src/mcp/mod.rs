@@ -1,23 +1,61 @@
 //! Model Context Protocol (MCP) Integration
 //!
 //! Implements the MCP client for connecting to MCP servers,
-//! discovering tools, resources, and prompts.
+//! discovering tools, resources, and prompts, and the host side that
+//! answers server requests for roots, sampling and elicitation. The app
+//! can also act as an MCP server itself (see `app_server`), and calls made
+//! through the manager can be kept in an audit log (see `audit`).
+//! Connected servers are pinged and restarted on failure by the
+//! `supervisor`.
 //!
-//! MCP uses JSON-RPC 2.0 over stdio for communication.
+//! MCP uses JSON-RPC 2.0, carried over stdio, Streamable HTTP or the
+//! legacy HTTP+SSE transport.
 
+mod app_server;
+mod audit;
 mod client;
 mod config;
+mod host;
 mod protocol;
+mod schema;
 mod server;
+mod supervisor;
 mod tools;
+mod transport;
 
-pub use client::{McpClient, McpManager};
-pub use config::{McpConfig, McpServerConfig};
+pub use app_server::{
+    app_server_config, app_server_mcp_config, app_socket_path, run_stdio_server, serve_connection,
+    serve_socket, spawn_app_server, AppDataSource, ConversationHit, DebugSnapshot, FileDiagnostics,
+    GitStatusSnapshot, LocalAppSource, McpAppServer, ProjectSummary, ScopeVariables,
+    APP_SERVER_FLAG, APP_SERVER_NAME, APP_SOCKET_ENV, RESOURCE_SCHEME,
+};
+pub use audit::{McpAuditLog, McpCallContext, McpCallOutput, RedactionRules, REDACTED};
+pub use client::{McpClient, McpConnection, McpEvent, McpManager, McpRequestHandler};
+pub use config::{
+    McpConfig, McpConfigLocations, McpConfigSource, McpSandbox, McpServerConfig, McpServerOrigin,
+    McpSourceKind, McpTransportKind, SANDBOX_BASE_ENV,
+};
+pub use host::{
+    root_for_path, sampling_request, sampling_result, ApprovalDecision, ApprovalGatedProvider,
+    ApprovalRequest, ElicitationRequest, McpHost,
+};
 pub use protocol::{
-    JsonRpcRequest, JsonRpcResponse, McpCapabilities, McpError, McpPrompt, McpResource, McpTool,
-    ServerInfo,
+    ArgumentError, Completion, CompletionReference, CreateMessageParams, CreateMessageResult,
+    ElicitAction, ElicitParams, ElicitResult, JsonRpcRequest, JsonRpcResponse, ListRootsResult,
+    LoggingMessage, McpCapabilities, McpError, McpPrompt, McpResource, McpRoot, McpTool,
+    ProgressNotification, ResourceTemplate, SamplingContent, SamplingMessage, ServerInfo,
+    ToolAnnotations, MCP_PROTOCOL_VERSION, SUPPORTED_PROTOCOL_VERSIONS,
+};
+pub use schema::{
+    complete_tool_command, parse_arguments, prepare_arguments, ArgumentField, ArgumentForm,
+    FieldKind,
 };
 pub use server::{
     create_shared_registry, McpServerRegistry, ServerHealth, ServerStatus, SharedMcpRegistry,
 };
+pub use supervisor::{McpHealthEvent, McpSupervisor, SupervisorPolicy};
 pub use tools::{build_arguments, EnrichedTool, ToolCategory, ToolRegistry};
+pub use transport::{
+    McpTransport, SseTransport, StderrLog, StdioTransport, StreamableHttpTransport,
+    DEFAULT_STDERR_LINES,
+};
@@ -0,0 +1,188 @@
+//! Requests servers send to the client: roots, sampling and elicitation
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// A root directory exposed to servers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct McpRoot {
+    /// `file://` URI
+    pub uri: String,
+    /// Display name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// `roots/list` result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListRootsResult {
+    pub roots: Vec<McpRoot>,
+}
+
+/// Content of a sampling message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SamplingContent {
+    #[serde(rename = "text")]
+    Text { text: String },
+    #[serde(rename = "image", rename_all = "camelCase")]
+    Image { data: String, mime_type: String },
+    #[serde(rename = "audio", rename_all = "camelCase")]
+    Audio { data: String, mime_type: String },
+}
+
+/// A message in a sampling request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SamplingMessage {
+    /// `user` or `assistant`
+    pub role: String,
+    pub content: SamplingContent,
+}
+
+/// Model name hint
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelHint {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// Server preferences for model selection
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPreferences {
+    #[serde(default)]
+    pub hints: Vec<ModelHint>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost_priority: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed_priority: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub intelligence_priority: Option<f64>,
+}
+
+/// `sampling/createMessage` parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMessageParams {
+    pub messages: Vec<SamplingMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_preferences: Option<ModelPreferences>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    /// `none`, `thisServer` or `allServers`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_context: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    pub max_tokens: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_sequences: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Value>,
+}
+
+/// `sampling/createMessage` result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMessageResult {
+    pub role: String,
+    pub content: SamplingContent,
+    /// Model that produced the message
+    pub model: String,
+    /// `endTurn`, `stopSequence` or `maxTokens`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<String>,
+}
+
+/// `elicitation/create` parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElicitParams {
+    /// What the server is asking for
+    pub message: String,
+    /// Flat JSON Schema object describing the requested fields
+    pub requested_schema: Value,
+}
+
+/// User response to an elicitation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ElicitAction {
+    Accept,
+    Decline,
+    Cancel,
+}
+
+/// `elicitation/create` result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElicitResult {
+    pub action: ElicitAction,
+    /// Submitted values (only with `accept`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<Map<String, Value>>,
+}
+
+impl ElicitResult {
+    /// Accept with the given values
+    pub fn accept(content: Map<String, Value>) -> Self {
+        Self {
+            action: ElicitAction::Accept,
+            content: Some(content),
+        }
+    }
+
+    /// Explicitly decline
+    pub fn decline() -> Self {
+        Self {
+            action: ElicitAction::Decline,
+            content: None,
+        }
+    }
+
+    /// Dismissed without a choice
+    pub fn cancel() -> Self {
+        Self {
+            action: ElicitAction::Cancel,
+            content: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_message_params() {
+        let json = r#"{
+            "messages": [{ "role": "user", "content": { "type": "text", "text": "Summarize" } }],
+            "modelPreferences": { "hints": [{ "name": "claude-3-5-sonnet" }], "speedPriority": 0.5 },
+            "systemPrompt": "Be brief",
+            "maxTokens": 100
+        }"#;
+        let params: CreateMessageParams = serde_json::from_str(json).unwrap();
+        assert_eq!(params.max_tokens, 100);
+        assert!(matches!(
+            &params.messages[0].content,
+            SamplingContent::Text { text } if text == "Summarize"
+        ));
+
+        let result = CreateMessageResult {
+            role: "assistant".to_string(),
+            content: SamplingContent::Text {
+                text: "Done".to_string(),
+            },
+            model: "m".to_string(),
+            stop_reason: Some("endTurn".to_string()),
+        };
+        let value = serde_json::to_value(result).unwrap();
+        assert_eq!(value["content"]["type"], "text");
+        assert_eq!(value["stopReason"], "endTurn");
+    }
+
+    #[test]
+    fn test_elicit_result_serialization() {
+        let value = serde_json::to_value(ElicitResult::decline()).unwrap();
+        assert_eq!(value, serde_json::json!({ "action": "decline" }));
+    }
+}
@@ -0,0 +1,95 @@
+//! Argument completion (`completion/complete`)
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// What is being completed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum CompletionReference {
+    /// Argument of a prompt
+    #[serde(rename = "ref/prompt")]
+    Prompt { name: String },
+    /// Variable of a resource template
+    #[serde(rename = "ref/resource")]
+    Resource { uri: String },
+}
+
+/// Argument being typed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompletionArgument {
+    pub name: String,
+    /// Text entered so far
+    pub value: String,
+}
+
+/// Values of other arguments already filled in
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompletionContext {
+    #[serde(default)]
+    pub arguments: HashMap<String, String>,
+}
+
+/// `completion/complete` parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompleteParams {
+    #[serde(rename = "ref")]
+    pub reference: CompletionReference,
+    pub argument: CompletionArgument,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<CompletionContext>,
+}
+
+/// Suggested values
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Completion {
+    /// At most 100 suggestions
+    pub values: Vec<String>,
+    /// Total number of matches, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<u32>,
+    /// More matches exist than were returned
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub has_more: Option<bool>,
+}
+
+/// `completion/complete` result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompleteResult {
+    pub completion: Completion,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_complete_params() {
+        let params = CompleteParams {
+            reference: CompletionReference::Prompt {
+                name: "code_review".to_string(),
+            },
+            argument: CompletionArgument {
+                name: "language".to_string(),
+                value: "py".to_string(),
+            },
+            context: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&params).unwrap(),
+            json!({
+                "ref": { "type": "ref/prompt", "name": "code_review" },
+                "argument": { "name": "language", "value": "py" }
+            })
+        );
+
+        let result: CompleteResult = serde_json::from_value(json!({
+            "completion": { "values": ["python", "pytorch"], "total": 10, "hasMore": true }
+        }))
+        .unwrap();
+        assert_eq!(result.completion.values.len(), 2);
+        assert_eq!(result.completion.has_more, Some(true));
+    }
+}
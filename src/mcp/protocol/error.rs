@@ -26,8 +26,55 @@ pub enum McpError {
     Server { code: i32, message: String },
     #[error("Timeout waiting for response")]
     Timeout,
+    #[error("Session expired")]
+    SessionExpired,
+    #[error("Invalid arguments for '{tool}': {}", join_errors(.errors))]
+    InvalidArguments {
+        tool: String,
+        errors: Vec<ArgumentError>,
+    },
+    #[error("Unsupported protocol version: {0}")]
+    UnsupportedProtocolVersion(String),
     #[error("Server not initialized")]
     NotInitialized,
+    #[error("{0} not available")]
+    Unavailable(String),
     #[error("IO error: {0}")]
     Io(String),
 }
+
+/// Tool argument that does not match the tool's input schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArgumentError {
+    /// Location in the arguments, e.g. `files[0].path` (empty for the whole object)
+    pub path: String,
+    /// What is wrong
+    pub message: String,
+}
+
+impl ArgumentError {
+    pub fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ArgumentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+fn join_errors(errors: &[ArgumentError]) -> String {
+    errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
@@ -4,8 +4,16 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 
-/// MCP protocol version
-pub const MCP_PROTOCOL_VERSION: &str = "2024-11-05";
+/// Latest MCP protocol version, requested during initialize
+pub const MCP_PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// Protocol versions the client can speak, newest first
+pub const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// Check whether a server's protocol version is supported
+pub fn is_supported_protocol_version(version: &str) -> bool {
+    SUPPORTED_PROTOCOL_VERSIONS.contains(&version)
+}
 
 /// Server information returned by initialize
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +52,9 @@ pub struct McpCapabilities {
     /// Sampling capability
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sampling: Option<SamplingCapability>,
+    /// Elicitation support
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elicitation: Option<ElicitationCapability>,
     /// Experimental capabilities
     #[serde(skip_serializing_if = "Option::is_none")]
     pub experimental: Option<HashMap<String, Value>>,
@@ -59,6 +70,10 @@ pub struct RootsCapability {
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SamplingCapability {}
 
+/// Elicitation capability
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ElicitationCapability {}
+
 /// Server capabilities returned by initialize
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -75,6 +90,9 @@ pub struct ServerCapabilities {
     /// Logging capability
     #[serde(skip_serializing_if = "Option::is_none")]
     pub logging: Option<LoggingCapability>,
+    /// Argument completion (`completion/complete`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completions: Option<CompletionsCapability>,
     /// Experimental capabilities
     #[serde(skip_serializing_if = "Option::is_none")]
     pub experimental: Option<HashMap<String, Value>>,
@@ -106,6 +124,9 @@ pub struct PromptsCapability {
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct LoggingCapability {}
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompletionsCapability {}
+
 /// Initialize request params
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -135,3 +156,25 @@ pub struct InitializeResult {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub instructions: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_supported_protocol_versions() {
+        assert_eq!(SUPPORTED_PROTOCOL_VERSIONS[0], MCP_PROTOCOL_VERSION);
+        assert!(is_supported_protocol_version("2024-11-05"));
+        assert!(is_supported_protocol_version("2025-03-26"));
+        assert!(!is_supported_protocol_version("2099-01-01"));
+    }
+
+    #[test]
+    fn test_server_capabilities_completions() {
+        let caps: ServerCapabilities =
+            serde_json::from_str(r#"{ "tools": {}, "completions": {} }"#).unwrap();
+        assert!(caps.tools.is_some());
+        assert!(caps.completions.is_some());
+        assert!(caps.prompts.is_none());
+    }
+}
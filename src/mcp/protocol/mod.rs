@@ -2,27 +2,46 @@
 //!
 //! Defines JSON-RPC 2.0 types and MCP-specific structures.
 
+mod client_features;
+mod completion;
 mod error;
 mod init;
 mod jsonrpc;
+mod notifications;
 mod prompts;
 mod resources;
 mod tools;
 
 // Re-export all public types
-pub use error::{McpError, McpErrorCode};
+pub use client_features::{
+    CreateMessageParams, CreateMessageResult, ElicitAction, ElicitParams, ElicitResult,
+    ListRootsResult, McpRoot, ModelHint, ModelPreferences, SamplingContent, SamplingMessage,
+};
+pub use completion::{
+    CompleteParams, CompleteResult, Completion, CompletionArgument, CompletionContext,
+    CompletionReference,
+};
+pub use error::{ArgumentError, McpError, McpErrorCode};
 pub use init::{
-    ClientInfo, InitializeParams, InitializeResult, LoggingCapability, McpCapabilities,
-    PromptsCapability, ResourcesCapability, RootsCapability, SamplingCapability,
-    ServerCapabilities, ServerInfo, ToolsCapability, MCP_PROTOCOL_VERSION,
+    is_supported_protocol_version, ClientInfo, CompletionsCapability, ElicitationCapability,
+    InitializeParams, InitializeResult, LoggingCapability, McpCapabilities, PromptsCapability,
+    ResourcesCapability, RootsCapability, SamplingCapability, ServerCapabilities, ServerInfo,
+    ToolsCapability, MCP_PROTOCOL_VERSION, SUPPORTED_PROTOCOL_VERSIONS,
 };
 pub use jsonrpc::{
     JsonRpcError, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, JSONRPC_VERSION,
 };
+pub use notifications::{
+    LoggingMessage, ProgressNotification, RequestMeta, ResourceUpdatedNotification,
+};
 pub use prompts::{
     GetPromptResult, ListPromptsResult, McpPrompt, PromptArgument, PromptContent, PromptMessage,
 };
-pub use resources::{ListResourcesResult, McpResource, ResourceContents, ResourceReference};
+pub use resources::{
+    ListResourceTemplatesResult, ListResourcesResult, McpResource, ResourceContents,
+    ResourceReference, ResourceTemplate,
+};
 pub use tools::{
-    CallToolParams, CallToolResult, ListToolsResult, McpTool, ToolContent, ToolInputSchema,
+    CallToolParams, CallToolResult, ListToolsResult, McpTool, ToolAnnotations, ToolContent,
+    ToolInputSchema,
 };
@@ -0,0 +1,93 @@
+//! MCP notification payloads sent by servers
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// `notifications/progress` parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressNotification {
+    /// Token from the request's `_meta.progressToken` (string or number)
+    pub progress_token: Value,
+    /// Progress so far
+    pub progress: f64,
+    /// Total, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<f64>,
+    /// Status message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl ProgressNotification {
+    /// Token as a string, whichever JSON type the server used
+    pub fn token(&self) -> String {
+        match &self.progress_token {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        }
+    }
+
+    /// Percentage complete (0-100), when a total is known
+    pub fn percent(&self) -> Option<u8> {
+        let total = self.total.filter(|t| *t > 0.0)?;
+        Some((self.progress / total * 100.0).clamp(0.0, 100.0) as u8)
+    }
+}
+
+/// `notifications/message` parameters (server logging)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggingMessage {
+    /// Syslog severity (`debug` ... `emergency`)
+    pub level: String,
+    /// Logger name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logger: Option<String>,
+    /// Message or structured data
+    pub data: Value,
+}
+
+impl LoggingMessage {
+    /// Data as display text
+    pub fn text(&self) -> String {
+        match &self.data {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        }
+    }
+}
+
+/// `notifications/resources/updated` parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceUpdatedNotification {
+    pub uri: String,
+}
+
+/// `_meta` attached to a request
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestMeta {
+    /// Token the server echoes in progress notifications
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress_token: Option<Value>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_percent() {
+        let progress: ProgressNotification = serde_json::from_str(
+            r#"{"progressToken": 7, "progress": 3, "total": 4, "message": "Indexing"}"#,
+        )
+        .unwrap();
+        assert_eq!(progress.token(), "7");
+        assert_eq!(progress.percent(), Some(75));
+
+        let progress: ProgressNotification =
+            serde_json::from_str(r#"{"progressToken": "call-1", "progress": 12}"#).unwrap();
+        assert_eq!(progress.token(), "call-1");
+        assert_eq!(progress.percent(), None);
+    }
+}
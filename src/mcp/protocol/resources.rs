@@ -1,6 +1,7 @@
 //! MCP resource types
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Reference to a resource
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +31,7 @@ pub struct McpResource {
 
 /// Resource contents result
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ResourceContents {
     pub uri: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -42,8 +44,104 @@ pub struct ResourceContents {
 
 /// List resources result
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ListResourcesResult {
     pub resources: Vec<McpResource>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// Parameterized resource (RFC 6570 URI template)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceTemplate {
+    /// URI template, e.g. `file:///{path}`
+    pub uri_template: String,
+    /// Human-readable name
+    pub name: String,
+    /// Display name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Description of the resources
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// MIME type of matching resources
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+}
+
+impl ResourceTemplate {
+    /// Names of the template's variables, in order
+    pub fn variables(&self) -> Vec<&str> {
+        let mut variables = Vec::new();
+        let mut rest = self.uri_template.as_str();
+        while let Some(start) = rest.find('{') {
+            let Some(end) = rest[start..].find('}') else {
+                break;
+            };
+            let expression = &rest[start + 1..start + end];
+            // Strip RFC 6570 operators and modifiers
+            let expression = expression.trim_start_matches(['+', '#', '.', '/', ';', '?', '&']);
+            for name in expression.split(',') {
+                let name = name.trim_end_matches('*');
+                let name = name.split(':').next().unwrap_or(name);
+                if !name.is_empty() && !variables.contains(&name) {
+                    variables.push(name);
+                }
+            }
+            rest = &rest[start + end + 1..];
+        }
+        variables
+    }
+
+    /// Fill simple `{name}` variables, leaving unknown ones in place
+    pub fn expand(&self, values: &HashMap<String, String>) -> String {
+        let mut uri = self.uri_template.clone();
+        for (name, value) in values {
+            uri = uri.replace(&format!("{{{}}}", name), value);
+        }
+        uri
+    }
+}
+
+/// List resource templates result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListResourceTemplatesResult {
+    pub resource_templates: Vec<ResourceTemplate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub next_cursor: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_template() {
+        let json = r#"{
+            "resourceTemplates": [{
+                "uriTemplate": "repo://{owner}/{name}/issues{?state,labels*}",
+                "name": "Issues",
+                "mimeType": "application/json"
+            }],
+            "nextCursor": "2"
+        }"#;
+        let result: ListResourceTemplatesResult = serde_json::from_str(json).unwrap();
+        assert_eq!(result.next_cursor.as_deref(), Some("2"));
+
+        let template = &result.resource_templates[0];
+        assert_eq!(
+            template.variables(),
+            vec!["owner", "name", "state", "labels"]
+        );
+
+        let values = HashMap::from([
+            ("owner".to_string(), "acme".to_string()),
+            ("name".to_string(), "app".to_string()),
+        ]);
+        assert!(template
+            .expand(&values)
+            .starts_with("repo://acme/app/issues"));
+    }
+}
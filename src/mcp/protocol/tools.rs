@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 
+use super::notifications::RequestMeta;
 use super::resources::ResourceReference;
 
 /// MCP Tool definition
@@ -12,11 +13,85 @@ use super::resources::ResourceReference;
 pub struct McpTool {
     /// Unique name for the tool
     pub name: String,
+    /// Display name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
     /// Human-readable description
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
     /// JSON Schema for the tool's parameters
     pub input_schema: ToolInputSchema,
+    /// JSON Schema for `structuredContent` in results
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<Value>,
+    /// Behaviour hints
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<ToolAnnotations>,
+}
+
+impl McpTool {
+    /// Title to show, falling back to the annotation title and the name
+    pub fn display_name(&self) -> &str {
+        self.title
+            .as_deref()
+            .or_else(|| self.annotations.as_ref().and_then(|a| a.title.as_deref()))
+            .unwrap_or(&self.name)
+    }
+
+    /// Whether the server says the tool does not modify anything
+    pub fn is_read_only(&self) -> bool {
+        self.annotations.as_ref().is_some_and(|a| a.is_read_only())
+    }
+
+    /// Whether the tool may make destructive changes
+    pub fn is_destructive(&self) -> bool {
+        self.annotations.as_ref().is_none_or(|a| a.is_destructive())
+    }
+}
+
+/// Tool behaviour hints
+///
+/// Hints come from the server and are not guaranteed; missing values take
+/// the spec's cautious defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolAnnotations {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Tool does not modify its environment (default false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_only_hint: Option<bool>,
+    /// Modifications may be destructive (default true, ignored if read-only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destructive_hint: Option<bool>,
+    /// Repeating a call has no further effect (default false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idempotent_hint: Option<bool>,
+    /// Tool interacts with external entities (default true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub open_world_hint: Option<bool>,
+}
+
+impl ToolAnnotations {
+    /// Whether the tool does not modify its environment
+    pub fn is_read_only(&self) -> bool {
+        self.read_only_hint.unwrap_or(false)
+    }
+
+    /// Whether the tool may make destructive changes
+    pub fn is_destructive(&self) -> bool {
+        !self.is_read_only() && self.destructive_hint.unwrap_or(true)
+    }
+
+    /// Whether repeated calls are safe
+    pub fn is_idempotent(&self) -> bool {
+        self.is_read_only() || self.idempotent_hint.unwrap_or(false)
+    }
+
+    /// Whether the tool reaches outside the local environment
+    pub fn is_open_world(&self) -> bool {
+        self.open_world_hint.unwrap_or(true)
+    }
 }
 
 /// Tool input schema (JSON Schema subset)
@@ -29,6 +104,16 @@ pub struct ToolInputSchema {
     pub properties: Option<HashMap<String, Value>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub required: Option<Vec<String>>,
+    /// Remaining keywords (`additionalProperties`, `$defs`, ...)
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, Value>,
+}
+
+impl ToolInputSchema {
+    /// The complete schema as JSON
+    pub fn to_value(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
 }
 
 /// Tool call request
@@ -37,13 +122,20 @@ pub struct CallToolParams {
     pub name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub arguments: Option<HashMap<String, Value>>,
+    /// Request metadata (progress token)
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<RequestMeta>,
 }
 
 /// Tool call result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CallToolResult {
+    #[serde(default)]
     pub content: Vec<ToolContent>,
+    /// Structured result matching the tool's `outputSchema`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_error: Option<bool>,
 }
@@ -62,9 +154,10 @@ pub enum ToolContent {
 
 /// List tools result
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ListToolsResult {
     pub tools: Vec<McpTool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub next_cursor: Option<String>,
 }
 
@@ -89,5 +182,42 @@ mod tests {
         let tool: McpTool = serde_json::from_str(json).unwrap();
         assert_eq!(tool.name, "read_file");
         assert!(tool.description.is_some());
+        assert!(tool.annotations.is_none());
+        assert!(!tool.is_read_only());
+        assert!(tool.is_destructive());
+    }
+
+    #[test]
+    fn test_tool_annotations_and_output_schema() {
+        let json = r#"{
+            "name": "get_weather",
+            "title": "Weather",
+            "inputSchema": { "type": "object" },
+            "outputSchema": {
+                "type": "object",
+                "properties": { "temperature": { "type": "number" } }
+            },
+            "annotations": { "readOnlyHint": true, "openWorldHint": true }
+        }"#;
+
+        let tool: McpTool = serde_json::from_str(json).unwrap();
+        assert_eq!(tool.display_name(), "Weather");
+        assert!(tool.is_read_only());
+        assert!(!tool.is_destructive());
+        assert!(tool.annotations.as_ref().unwrap().is_idempotent());
+        assert!(tool.output_schema.is_some());
+
+        let result: CallToolResult = serde_json::from_str(
+            r#"{
+                "content": [{ "type": "text", "text": "{\"temperature\": 21}" }],
+                "structuredContent": { "temperature": 21 }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(result.structured_content.unwrap()["temperature"], 21);
+
+        let page: ListToolsResult =
+            serde_json::from_str(r#"{ "tools": [], "nextCursor": "page-2" }"#).unwrap();
+        assert_eq!(page.next_cursor.as_deref(), Some("page-2"));
     }
 }
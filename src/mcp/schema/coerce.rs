@@ -0,0 +1,202 @@
+//! Coercion of command-line strings into typed arguments
+
+use super::{invalid, prepare_arguments, properties, required, resolve, schema_types};
+use crate::mcp::protocol::{McpError, McpTool};
+use serde_json::{Map, Number, Value};
+use std::collections::HashMap;
+
+/// Convert string values to the types the schema asks for, recursively
+///
+/// `"42"` becomes `42` for an integer, `"yes"` becomes `true` for a
+/// boolean, `"a,b"` or `"[\"a\",\"b\"]"` becomes an array, and so on.
+/// Values that cannot be converted are left alone for validation to report.
+pub fn coerce_strings(schema: &Value, value: &mut Value) {
+    coerce(schema, schema, value);
+}
+
+fn coerce(root: &Value, schema: &Value, value: &mut Value) {
+    let schema = resolve(root, schema);
+
+    match value {
+        Value::String(text) => {
+            if let Some(converted) = convert(root, schema, text) {
+                *value = converted;
+            }
+        }
+        Value::Object(object) => {
+            if let Some(props) = properties(schema) {
+                for (key, child) in object.iter_mut() {
+                    if let Some(prop) = props.get(key) {
+                        coerce(root, prop, child);
+                    }
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items").filter(|s| s.is_object()) {
+                for item in items {
+                    coerce(root, item_schema, item);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn convert(root: &Value, schema: &Value, text: &str) -> Option<Value> {
+    let types = schema_types(schema);
+    // Strings are already what the schema wants
+    if types.is_empty() || types.contains(&"string") {
+        return None;
+    }
+
+    let trimmed = text.trim();
+    types.iter().find_map(|t| match *t {
+        "integer" => trimmed.parse::<i64>().ok().map(Value::from),
+        "number" => trimmed
+            .parse::<f64>()
+            .ok()
+            .and_then(Number::from_f64)
+            .map(Value::Number),
+        "boolean" => match trimmed.to_lowercase().as_str() {
+            "true" | "yes" | "y" | "on" | "1" => Some(Value::Bool(true)),
+            "false" | "no" | "n" | "off" | "0" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        "null" if trimmed == "null" => Some(Value::Null),
+        "array" => {
+            let mut array = if trimmed.starts_with('[') {
+                serde_json::from_str::<Value>(trimmed).ok()?
+            } else if trimmed.is_empty() {
+                Value::Array(Vec::new())
+            } else {
+                Value::Array(
+                    trimmed
+                        .split(',')
+                        .map(|item| Value::String(item.trim().to_string()))
+                        .collect(),
+                )
+            };
+            coerce(root, schema, &mut array);
+            Some(array)
+        }
+        "object" => {
+            let mut object = serde_json::from_str::<Value>(trimmed)
+                .ok()
+                .filter(Value::is_object)?;
+            coerce(root, schema, &mut object);
+            Some(object)
+        }
+        _ => None,
+    })
+}
+
+/// Parse `/mcp-tool` arguments for a tool, then fill defaults and validate
+///
+/// Accepts a JSON object, or `key=value` pairs with shell-style quoting.
+/// A single bare value is given to the tool's only required parameter.
+pub fn parse_arguments(tool: &McpTool, input: &str) -> Result<HashMap<String, Value>, McpError> {
+    let input = input.trim();
+    let schema = tool.input_schema.to_value();
+
+    let mut value = if input.starts_with('{') {
+        let value: Value = serde_json::from_str(input)
+            .map_err(|e| invalid(tool, "", format!("invalid JSON: {}", e)))?;
+        if !value.is_object() {
+            return Err(invalid(tool, "", "expected a JSON object"));
+        }
+        value
+    } else {
+        Value::Object(parse_pairs(tool, &schema, input)?)
+    };
+
+    coerce_strings(&schema, &mut value);
+    prepare_arguments(tool, super::into_map(value))
+}
+
+fn parse_pairs(
+    tool: &McpTool,
+    schema: &Value,
+    input: &str,
+) -> Result<Map<String, Value>, McpError> {
+    let tokens = split_words(input).map_err(|message| invalid(tool, "", message))?;
+    let mut arguments = Map::new();
+
+    for token in &tokens {
+        let (key, value) = match token.split_once('=') {
+            Some((key, value)) if !key.is_empty() => (key.to_string(), value.to_string()),
+            _ => {
+                let target = sole_parameter(schema).ok_or_else(|| {
+                    invalid(tool, "", format!("expected key=value, got '{}'", token))
+                })?;
+                if tokens.len() > 1 {
+                    return Err(invalid(
+                        tool,
+                        "",
+                        format!("expected key=value, got '{}'", token),
+                    ));
+                }
+                (target, token.clone())
+            }
+        };
+        arguments.insert(key, Value::String(value));
+    }
+
+    Ok(arguments)
+}
+
+/// Parameter a lone bare value is assigned to
+fn sole_parameter(schema: &Value) -> Option<String> {
+    let required = required(schema);
+    if required.len() == 1 {
+        return Some(required[0].to_string());
+    }
+    match properties(schema) {
+        Some(props) if props.len() == 1 && required.is_empty() => props.keys().next().cloned(),
+        _ => None,
+    }
+}
+
+/// Split on whitespace, honouring single/double quotes and backslash escapes
+pub(crate) fn split_words(input: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+    let mut chars = input.chars();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') | (None, '\\') => {
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+                in_word = true;
+            }
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+
+    if quote.is_some() {
+        return Err("unterminated quote".to_string());
+    }
+    if in_word {
+        words.push(current);
+    }
+    Ok(words)
+}
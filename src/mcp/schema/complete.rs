@@ -0,0 +1,114 @@
+//! `/mcp-tool` autocomplete
+
+use super::form::quote;
+use super::{properties, required, resolve, schema_types};
+use crate::mcp::protocol::McpTool;
+use serde_json::Value;
+
+/// Completions for the text after `/mcp-tool `
+///
+/// Suggests server names, then tool names, then `key=` for parameters not
+/// yet given and values for enum and boolean parameters. Each suggestion is
+/// the whole input with the last word completed; a suggestion ending in `=`
+/// expects a value to follow directly.
+pub fn complete_tool_command(tools: &[(String, McpTool)], input: &str) -> Vec<String> {
+    let words: Vec<&str> = input.split_whitespace().collect();
+    let partial = if input.is_empty() || input.ends_with(char::is_whitespace) {
+        ""
+    } else {
+        words.last().copied().unwrap_or_default()
+    };
+    let prefix = &input[..input.len() - partial.len()];
+    let done = if partial.is_empty() {
+        &words[..]
+    } else {
+        &words[..words.len() - 1]
+    };
+
+    let suggestions: Vec<String> = match done {
+        [] => {
+            let mut servers: Vec<&str> = tools.iter().map(|(server, _)| server.as_str()).collect();
+            servers.sort_unstable();
+            servers.dedup();
+            starting_with(servers, partial)
+        }
+        [server] => starting_with(
+            tools
+                .iter()
+                .filter(|(s, _)| s == server)
+                .map(|(_, tool)| tool.name.as_str())
+                .collect(),
+            partial,
+        ),
+        [server, tool, given @ ..] => {
+            let Some((_, tool)) = tools.iter().find(|(s, t)| s == server && t.name == *tool) else {
+                return Vec::new();
+            };
+            complete_argument(tool, given, partial)
+        }
+    };
+
+    suggestions
+        .into_iter()
+        .map(|suggestion| format!("{}{}", prefix, suggestion))
+        .collect()
+}
+
+fn complete_argument(tool: &McpTool, given: &[&str], partial: &str) -> Vec<String> {
+    let schema = tool.input_schema.to_value();
+    let Some(props) = properties(&schema) else {
+        return Vec::new();
+    };
+
+    // Value for a known key
+    if let Some((key, value)) = partial.split_once('=') {
+        let Some(prop) = props.get(key).map(|p| resolve(&schema, p)) else {
+            return Vec::new();
+        };
+        let options: Vec<String> = match prop.get("enum").and_then(Value::as_array) {
+            Some(options) => options
+                .iter()
+                .map(|o| match o {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect(),
+            None if schema_types(prop).contains(&"boolean") => {
+                vec!["true".to_string(), "false".to_string()]
+            }
+            None => Vec::new(),
+        };
+        return options
+            .iter()
+            .filter(|o| o.to_lowercase().starts_with(&value.to_lowercase()))
+            .map(|o| format!("{}={}", key, quote(o)))
+            .collect();
+    }
+
+    // Parameter names not given yet, required first
+    let used: Vec<&str> = given
+        .iter()
+        .filter_map(|word| word.split_once('=').map(|(key, _)| key))
+        .collect();
+    let required = required(&schema);
+    let mut names: Vec<&str> = props
+        .keys()
+        .map(String::as_str)
+        .filter(|name| !used.contains(name))
+        .collect();
+    names.sort_by_key(|name| (!required.contains(name), *name));
+
+    starting_with(names, partial)
+        .into_iter()
+        .map(|name| format!("{}=", name))
+        .collect()
+}
+
+fn starting_with(candidates: Vec<&str>, partial: &str) -> Vec<String> {
+    let partial = partial.to_lowercase();
+    candidates
+        .into_iter()
+        .filter(|c| c.to_lowercase().starts_with(&partial))
+        .map(str::to_string)
+        .collect()
+}
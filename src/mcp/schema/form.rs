@@ -0,0 +1,230 @@
+//! Argument form generated from a tool's input schema
+
+use super::{coerce_strings, prepare_arguments, properties, required, resolve, schema_types};
+use crate::mcp::protocol::{McpError, McpTool};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Input widget suited to a parameter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldKind {
+    /// Free text
+    Text,
+    /// Whole number
+    Integer,
+    /// Any number
+    Number,
+    /// Checkbox
+    Boolean,
+    /// One of fixed values
+    Choice(Vec<String>),
+    /// Comma-separated list
+    List,
+    /// Raw JSON (objects and anything else)
+    Json,
+}
+
+/// One parameter in the form
+#[derive(Debug, Clone)]
+pub struct ArgumentField {
+    /// Parameter name
+    pub name: String,
+    /// Label to show (schema `title`, else the name)
+    pub label: String,
+    /// Help text
+    pub description: Option<String>,
+    /// Widget kind
+    pub kind: FieldKind,
+    /// Whether a value must be given
+    pub required: bool,
+    /// Default, as text, shown as a placeholder
+    pub placeholder: Option<String>,
+    /// Current text
+    pub value: String,
+    /// Validation message from the last [`ArgumentForm::submit`]
+    pub error: Option<String>,
+}
+
+/// Form state for calling a tool
+#[derive(Debug, Clone)]
+pub struct ArgumentForm {
+    tool: McpTool,
+    fields: Vec<ArgumentField>,
+    /// Error not tied to a single field
+    error: Option<String>,
+}
+
+impl ArgumentForm {
+    /// Build a form for a tool (required parameters first)
+    pub fn new(tool: &McpTool) -> Self {
+        let schema = tool.input_schema.to_value();
+        let required = required(&schema);
+
+        let mut fields: Vec<ArgumentField> = properties(&schema)
+            .map(|props| {
+                props
+                    .iter()
+                    .map(|(name, prop)| {
+                        let prop = resolve(&schema, prop);
+                        ArgumentField {
+                            name: name.clone(),
+                            label: prop
+                                .get("title")
+                                .and_then(Value::as_str)
+                                .unwrap_or(name)
+                                .to_string(),
+                            description: prop
+                                .get("description")
+                                .and_then(Value::as_str)
+                                .map(str::to_string),
+                            kind: field_kind(prop),
+                            required: required.contains(&name.as_str()),
+                            placeholder: prop.get("default").map(value_text),
+                            value: String::new(),
+                            error: None,
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        fields.sort_by(|a, b| b.required.cmp(&a.required).then(a.name.cmp(&b.name)));
+
+        Self {
+            tool: tool.clone(),
+            fields,
+            error: None,
+        }
+    }
+
+    /// Tool the form calls
+    pub fn tool(&self) -> &McpTool {
+        &self.tool
+    }
+
+    /// Fields in display order
+    pub fn fields(&self) -> &[ArgumentField] {
+        &self.fields
+    }
+
+    /// Look up a field
+    pub fn field(&self, name: &str) -> Option<&ArgumentField> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+
+    /// Error not tied to a single field
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Update a field's text, clearing its error
+    pub fn set_value(&mut self, name: &str, value: impl Into<String>) {
+        if let Some(field) = self.fields.iter_mut().find(|f| f.name == name) {
+            field.value = value.into();
+            field.error = None;
+        }
+    }
+
+    /// Fill fields from existing arguments
+    pub fn set_arguments(&mut self, arguments: &HashMap<String, Value>) {
+        for (name, value) in arguments {
+            self.set_value(name, value_text(value));
+        }
+    }
+
+    /// Validate the form, returning the arguments to send
+    ///
+    /// Empty fields are omitted so defaults apply. On failure, errors are
+    /// attached to their fields.
+    pub fn submit(&mut self) -> Result<HashMap<String, Value>, McpError> {
+        let schema = self.tool.input_schema.to_value();
+        let mut value = Value::Object(
+            self.fields
+                .iter()
+                .filter(|f| !f.value.trim().is_empty())
+                .map(|f| (f.name.clone(), Value::String(f.value.clone())))
+                .collect::<Map<_, _>>(),
+        );
+        coerce_strings(&schema, &mut value);
+
+        for field in &mut self.fields {
+            field.error = None;
+        }
+        self.error = None;
+
+        let result = prepare_arguments(&self.tool, super::into_map(value));
+        if let Err(McpError::InvalidArguments { errors, .. }) = &result {
+            for error in errors {
+                let name = error.path.split(['.', '[']).next().unwrap_or_default();
+                match self.fields.iter_mut().find(|f| f.name == name) {
+                    Some(field) if field.error.is_none() => {
+                        field.error = Some(error.message.clone())
+                    }
+                    Some(_) => {}
+                    None => self.error = Some(error.to_string()),
+                }
+            }
+        }
+        result
+    }
+
+    /// Equivalent `/mcp-tool` command line
+    pub fn to_command(&self, server: &str) -> String {
+        let mut command = format!("/mcp-tool {} {}", server, self.tool.name);
+        for field in self.fields.iter().filter(|f| !f.value.is_empty()) {
+            command.push(' ');
+            command.push_str(&field.name);
+            command.push('=');
+            command.push_str(&quote(&field.value));
+        }
+        command
+    }
+}
+
+fn field_kind(schema: &Value) -> FieldKind {
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        return FieldKind::Choice(options.iter().map(value_text).collect());
+    }
+    let types = schema_types(schema);
+    match types.iter().find(|t| **t != "null").copied() {
+        Some("string") => FieldKind::Text,
+        Some("integer") => FieldKind::Integer,
+        Some("number") => FieldKind::Number,
+        Some("boolean") => FieldKind::Boolean,
+        Some("array") => {
+            let simple_items = schema
+                .get("items")
+                .map(schema_types)
+                .is_some_and(|t| t.iter().all(|t| *t != "object" && *t != "array"));
+            if simple_items {
+                FieldKind::List
+            } else {
+                FieldKind::Json
+            }
+        }
+        Some(_) => FieldKind::Json,
+        None => FieldKind::Text,
+    }
+}
+
+/// Value as the text a user would type
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(items) if items.iter().all(|i| i.is_string() || i.is_number()) => {
+            items.iter().map(value_text).collect::<Vec<_>>().join(",")
+        }
+        other => other.to_string(),
+    }
+}
+
+/// Quote a value for the command line when needed
+pub(crate) fn quote(value: &str) -> String {
+    if !value.is_empty()
+        && !value
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '"' | '\'' | '\\'))
+    {
+        return value.to_string();
+    }
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
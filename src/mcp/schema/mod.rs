@@ -0,0 +1,116 @@
+//! Tool argument checking against `inputSchema`
+//!
+//! Arguments are validated before they reach the server so mistakes come
+//! back with a path and a hint instead of an opaque server error. The same
+//! schema drives default filling, coercion of command-line strings, the
+//! generated argument form and `/mcp-tool` autocomplete.
+//!
+//! Supports the JSON Schema keywords tool schemas use in practice: `type`,
+//! `properties`, `required`, `additionalProperties`, `items`, `enum`,
+//! `const`, string/number/array bounds, `pattern`, `allOf`/`anyOf`/`oneOf`
+//! and local `$ref`s.
+
+mod coerce;
+mod complete;
+mod form;
+mod validate;
+
+#[cfg(test)]
+mod tests;
+
+pub use coerce::{coerce_strings, parse_arguments};
+pub use complete::complete_tool_command;
+pub use form::{ArgumentField, ArgumentForm, FieldKind};
+pub use validate::{apply_defaults, validate};
+
+use super::protocol::{ArgumentError, McpError, McpTool};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Fill defaults and validate arguments for a tool
+pub fn prepare_arguments(
+    tool: &McpTool,
+    arguments: HashMap<String, Value>,
+) -> Result<HashMap<String, Value>, McpError> {
+    let schema = tool.input_schema.to_value();
+    let mut value = Value::Object(arguments.into_iter().collect());
+    apply_defaults(&schema, &mut value);
+
+    let errors = validate(&schema, &value);
+    if !errors.is_empty() {
+        return Err(McpError::InvalidArguments {
+            tool: tool.name.clone(),
+            errors,
+        });
+    }
+
+    Ok(into_map(value))
+}
+
+fn into_map(value: Value) -> HashMap<String, Value> {
+    match value {
+        Value::Object(map) => map.into_iter().collect(),
+        _ => HashMap::new(),
+    }
+}
+
+/// Resolve a local `$ref` (`#/$defs/...`) against the root schema
+fn resolve<'a>(root: &'a Value, schema: &'a Value) -> &'a Value {
+    let mut schema = schema;
+    // Bounded to survive reference cycles
+    for _ in 0..16 {
+        let Some(reference) = schema.get("$ref").and_then(Value::as_str) else {
+            break;
+        };
+        match reference
+            .strip_prefix('#')
+            .and_then(|pointer| root.pointer(pointer))
+        {
+            Some(target) => schema = target,
+            None => break,
+        }
+    }
+    schema
+}
+
+/// Types allowed by a schema (`type` may be a string or a list)
+fn schema_types(schema: &Value) -> Vec<&str> {
+    match schema.get("type") {
+        Some(Value::String(t)) => vec![t.as_str()],
+        Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn properties(schema: &Value) -> Option<&Map<String, Value>> {
+    schema.get("properties").and_then(Value::as_object)
+}
+
+fn required(schema: &Value) -> Vec<&str> {
+    schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+/// Path of a property below `path`
+fn child_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// Path of an array item below `path`
+fn index_path(path: &str, index: usize) -> String {
+    format!("{}[{}]", path, index)
+}
+
+fn invalid(tool: &McpTool, path: &str, message: impl Into<String>) -> McpError {
+    McpError::InvalidArguments {
+        tool: tool.name.clone(),
+        errors: vec![ArgumentError::new(path, message)],
+    }
+}
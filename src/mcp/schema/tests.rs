@@ -0,0 +1,205 @@
+//! Tests for tool argument checking
+
+use super::*;
+use serde_json::json;
+
+fn tool(schema: Value) -> McpTool {
+    serde_json::from_value(json!({
+        "name": "search",
+        "inputSchema": schema
+    }))
+    .unwrap()
+}
+
+fn search_tool() -> McpTool {
+    tool(json!({
+        "type": "object",
+        "properties": {
+            "query": { "type": "string", "minLength": 1, "description": "Text to find" },
+            "limit": { "type": "integer", "minimum": 1, "maximum": 100, "default": 10 },
+            "case_sensitive": { "type": "boolean", "default": false },
+            "mode": { "type": "string", "enum": ["literal", "regex"] },
+            "paths": { "type": "array", "items": { "type": "string" } },
+            "filter": { "$ref": "#/$defs/filter" }
+        },
+        "required": ["query"],
+        "additionalProperties": false,
+        "$defs": {
+            "filter": {
+                "type": "object",
+                "properties": { "ext": { "type": "string", "pattern": "^\\.[a-z]+$" } }
+            }
+        }
+    }))
+}
+
+fn args(value: Value) -> HashMap<String, Value> {
+    serde_json::from_value(value).unwrap()
+}
+
+fn errors(result: Result<HashMap<String, Value>, McpError>) -> Vec<String> {
+    match result {
+        Err(McpError::InvalidArguments { errors, .. }) => {
+            errors.iter().map(ToString::to_string).collect()
+        }
+        other => panic!("expected invalid arguments, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_prepare_fills_defaults() {
+    let prepared = prepare_arguments(&search_tool(), args(json!({ "query": "todo" }))).unwrap();
+    assert_eq!(prepared["limit"], 10);
+    assert_eq!(prepared["case_sensitive"], false);
+    assert!(!prepared.contains_key("mode"));
+}
+
+#[test]
+fn test_validation_errors_have_paths() {
+    let result = prepare_arguments(
+        &search_tool(),
+        args(json!({
+            "limit": "ten",
+            "mode": "fuzzy",
+            "paths": ["src", 3],
+            "filter": { "ext": "rs" },
+            "qurey": "typo"
+        })),
+    );
+    let errors = errors(result);
+    assert!(errors.contains(&"query: is required".to_string()));
+    assert!(errors.contains(&"limit: expected integer, got string".to_string()));
+    assert!(errors.contains(&"mode: must be one of: literal, regex".to_string()));
+    assert!(errors.contains(&"paths[1]: expected string, got integer".to_string()));
+    assert!(errors.contains(&"filter.ext: must match pattern ^\\.[a-z]+$".to_string()));
+    assert!(errors.contains(&"qurey: unknown property (did you mean 'query'?)".to_string()));
+}
+
+#[test]
+fn test_bounds_and_combinators() {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "n": { "type": "number", "exclusiveMinimum": 0, "multipleOf": 0.5 },
+            "tags": { "type": "array", "maxItems": 2, "uniqueItems": true },
+            "id": { "oneOf": [{ "type": "integer" }, { "type": "string", "minLength": 3 }] }
+        }
+    });
+    assert!(validate(&schema, &json!({ "n": 1.5, "tags": ["a"], "id": 7 })).is_empty());
+
+    let found: Vec<String> = validate(
+        &schema,
+        &json!({ "n": 0.3, "tags": ["a", "a", "b"], "id": "x" }),
+    )
+    .iter()
+    .map(ToString::to_string)
+    .collect();
+    assert!(found.contains(&"n: must be a multiple of 0.5".to_string()));
+    assert!(found.contains(&"tags: must have at most 2 items".to_string()));
+    assert!(found.contains(&"tags: items must be unique".to_string()));
+    assert!(found
+        .iter()
+        .any(|e| e.starts_with("id: must match exactly one")));
+}
+
+#[test]
+fn test_parse_key_value_arguments() {
+    let tool = search_tool();
+    let parsed = parse_arguments(
+        &tool,
+        r#"query="fn main" limit=5 case_sensitive=yes paths=src,tests filter='{"ext":".rs"}'"#,
+    )
+    .unwrap();
+    assert_eq!(parsed["query"], "fn main");
+    assert_eq!(parsed["limit"], 5);
+    assert_eq!(parsed["case_sensitive"], true);
+    assert_eq!(parsed["paths"], json!(["src", "tests"]));
+    assert_eq!(parsed["filter"]["ext"], ".rs");
+
+    // A lone value goes to the only required parameter
+    let parsed = parse_arguments(&tool, "TODO").unwrap();
+    assert_eq!(parsed["query"], "TODO");
+    assert_eq!(parsed["limit"], 10);
+
+    // JSON objects are still accepted, with coercion
+    let parsed = parse_arguments(&tool, r#"{"query": "x", "limit": "7"}"#).unwrap();
+    assert_eq!(parsed["limit"], 7);
+
+    assert_eq!(
+        errors(parse_arguments(&tool, "query=x limit=0")),
+        vec!["limit: must be >= 1"]
+    );
+    assert!(parse_arguments(&tool, "a b").is_err());
+    assert!(parse_arguments(&tool, "query=\"open").is_err());
+}
+
+#[test]
+fn test_argument_form() {
+    let mut form = ArgumentForm::new(&search_tool());
+    let names: Vec<&str> = form.fields().iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names[0], "query");
+    assert_eq!(
+        form.field("mode").unwrap().kind,
+        FieldKind::Choice(vec!["literal".to_string(), "regex".to_string()])
+    );
+    assert_eq!(
+        form.field("limit").unwrap().placeholder.as_deref(),
+        Some("10")
+    );
+    assert_eq!(form.field("paths").unwrap().kind, FieldKind::List);
+    assert_eq!(form.field("filter").unwrap().kind, FieldKind::Json);
+
+    form.set_value("limit", "500");
+    assert!(form.submit().is_err());
+    assert_eq!(
+        form.field("query").unwrap().error.as_deref(),
+        Some("is required")
+    );
+    assert_eq!(
+        form.field("limit").unwrap().error.as_deref(),
+        Some("must be <= 100")
+    );
+
+    form.set_value("query", "two words");
+    form.set_value("limit", "50");
+    let arguments = form.submit().unwrap();
+    assert_eq!(arguments["limit"], 50);
+    assert!(form.field("limit").unwrap().error.is_none());
+    assert_eq!(
+        form.to_command("fs"),
+        "/mcp-tool fs search query=\"two words\" limit=50"
+    );
+}
+
+#[test]
+fn test_complete_tool_command() {
+    let tools = vec![
+        ("fs".to_string(), search_tool()),
+        ("git".to_string(), tool(json!({ "type": "object" }))),
+    ];
+
+    assert_eq!(complete_tool_command(&tools, ""), vec!["fs", "git"]);
+    assert_eq!(complete_tool_command(&tools, "g"), vec!["git"]);
+    assert_eq!(complete_tool_command(&tools, "fs "), vec!["fs search"]);
+    assert_eq!(
+        complete_tool_command(&tools, "fs search q"),
+        vec!["fs search query="]
+    );
+
+    let keys = complete_tool_command(&tools, "fs search query=x ");
+    assert!(!keys.iter().any(|k| k.ends_with("query=")));
+    assert!(keys.contains(&"fs search query=x limit=".to_string()));
+
+    assert_eq!(
+        complete_tool_command(&tools, "fs search mode=r"),
+        vec!["fs search mode=regex"]
+    );
+    assert_eq!(
+        complete_tool_command(&tools, "fs search case_sensitive="),
+        vec![
+            "fs search case_sensitive=true",
+            "fs search case_sensitive=false"
+        ]
+    );
+    assert!(complete_tool_command(&tools, "fs missing ").is_empty());
+}
@@ -0,0 +1,354 @@
+//! JSON Schema validation and default filling
+
+use super::{child_path, index_path, properties, required, resolve, schema_types};
+use crate::mcp::protocol::ArgumentError;
+use regex::Regex;
+use serde_json::Value;
+
+/// Deepest nesting checked (guards against recursive schemas)
+const MAX_DEPTH: usize = 32;
+
+/// Check a value against a schema, returning every problem found
+pub fn validate(schema: &Value, value: &Value) -> Vec<ArgumentError> {
+    let mut validator = Validator {
+        root: schema,
+        errors: Vec::new(),
+    };
+    validator.check(schema, value, "", 0);
+    validator.errors
+}
+
+/// Insert `default` values for missing properties, recursively
+pub fn apply_defaults(schema: &Value, value: &mut Value) {
+    fill_defaults(schema, schema, value, 0);
+}
+
+fn fill_defaults(root: &Value, schema: &Value, value: &mut Value, depth: usize) {
+    if depth > MAX_DEPTH {
+        return;
+    }
+    let schema = resolve(root, schema);
+
+    if let Some(all_of) = schema.get("allOf").and_then(Value::as_array) {
+        for sub in all_of {
+            fill_defaults(root, sub, value, depth + 1);
+        }
+    }
+
+    match value {
+        Value::Object(object) => {
+            let Some(props) = properties(schema) else {
+                return;
+            };
+            for (key, prop) in props {
+                let prop = resolve(root, prop);
+                match object.get_mut(key) {
+                    Some(child) => fill_defaults(root, prop, child, depth + 1),
+                    None => {
+                        if let Some(default) = prop.get("default") {
+                            object.insert(key.clone(), default.clone());
+                        }
+                    }
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items").filter(|s| s.is_object()) {
+                for item in items {
+                    fill_defaults(root, item_schema, item, depth + 1);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+struct Validator<'a> {
+    root: &'a Value,
+    errors: Vec<ArgumentError>,
+}
+
+impl<'a> Validator<'a> {
+    fn error(&mut self, path: &str, message: impl Into<String>) {
+        self.errors.push(ArgumentError::new(path, message));
+    }
+
+    /// Whether a value matches a schema, without recording errors
+    fn matches(&self, schema: &'a Value, value: &Value, depth: usize) -> bool {
+        let mut probe = Validator {
+            root: self.root,
+            errors: Vec::new(),
+        };
+        probe.check(schema, value, "", depth);
+        probe.errors.is_empty()
+    }
+
+    fn check(&mut self, schema: &'a Value, value: &Value, path: &str, depth: usize) {
+        if depth > MAX_DEPTH {
+            return;
+        }
+        let schema = resolve(self.root, schema);
+
+        match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => {
+                self.error(path, "is not allowed");
+                return;
+            }
+            Value::Object(_) => {}
+            _ => return,
+        }
+
+        let types = schema_types(schema);
+        if !types.is_empty() && !types.iter().any(|t| type_matches(t, value)) {
+            self.error(
+                path,
+                format!("expected {}, got {}", types.join(" or "), type_name(value)),
+            );
+            return;
+        }
+
+        if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+            if !options.contains(value) {
+                let options: Vec<String> = options.iter().map(display_value).collect();
+                self.error(path, format!("must be one of: {}", options.join(", ")));
+            }
+        }
+        if let Some(expected) = schema.get("const") {
+            if expected != value {
+                self.error(path, format!("must be {}", display_value(expected)));
+            }
+        }
+
+        match value {
+            Value::String(text) => self.check_string(schema, text, path),
+            Value::Number(_) => self.check_number(schema, value, path),
+            Value::Array(items) => self.check_array(schema, items, path, depth),
+            Value::Object(object) => self.check_object(schema, object, path, depth),
+            _ => {}
+        }
+
+        self.check_combinators(schema, value, path, depth);
+    }
+
+    fn check_string(&mut self, schema: &Value, text: &str, path: &str) {
+        let length = text.chars().count() as u64;
+        if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+            if length < min {
+                self.error(path, format!("must be at least {} characters", min));
+            }
+        }
+        if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+            if length > max {
+                self.error(path, format!("must be at most {} characters", max));
+            }
+        }
+        if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+            // Invalid patterns are the server's problem, not the user's
+            if let Ok(regex) = Regex::new(pattern) {
+                if !regex.is_match(text) {
+                    self.error(path, format!("must match pattern {}", pattern));
+                }
+            }
+        }
+    }
+
+    fn check_number(&mut self, schema: &Value, value: &Value, path: &str) {
+        let Some(number) = value.as_f64() else {
+            return;
+        };
+        let bound = |key: &str| schema.get(key).and_then(Value::as_f64);
+
+        if let Some(min) = bound("minimum") {
+            if number < min {
+                self.error(path, format!("must be >= {}", min));
+            }
+        }
+        if let Some(max) = bound("maximum") {
+            if number > max {
+                self.error(path, format!("must be <= {}", max));
+            }
+        }
+        if let Some(min) = bound("exclusiveMinimum") {
+            if number <= min {
+                self.error(path, format!("must be > {}", min));
+            }
+        }
+        if let Some(max) = bound("exclusiveMaximum") {
+            if number >= max {
+                self.error(path, format!("must be < {}", max));
+            }
+        }
+        if let Some(step) = bound("multipleOf").filter(|s| *s > 0.0) {
+            let ratio = number / step;
+            if (ratio - ratio.round()).abs() > 1e-9 {
+                self.error(path, format!("must be a multiple of {}", step));
+            }
+        }
+    }
+
+    fn check_array(&mut self, schema: &'a Value, items: &[Value], path: &str, depth: usize) {
+        let count = items.len() as u64;
+        if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+            if count < min {
+                self.error(path, format!("must have at least {} items", min));
+            }
+        }
+        if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+            if count > max {
+                self.error(path, format!("must have at most {} items", max));
+            }
+        }
+        if schema.get("uniqueItems").and_then(Value::as_bool) == Some(true) {
+            let duplicate = items
+                .iter()
+                .enumerate()
+                .any(|(i, item)| items[..i].contains(item));
+            if duplicate {
+                self.error(path, "items must be unique");
+            }
+        }
+
+        match schema.get("items") {
+            // Tuple form
+            Some(Value::Array(schemas)) => {
+                for (index, (item, item_schema)) in items.iter().zip(schemas).enumerate() {
+                    self.check(item_schema, item, &index_path(path, index), depth + 1);
+                }
+            }
+            Some(item_schema) => {
+                for (index, item) in items.iter().enumerate() {
+                    self.check(item_schema, item, &index_path(path, index), depth + 1);
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn check_object(
+        &mut self,
+        schema: &'a Value,
+        object: &serde_json::Map<String, Value>,
+        path: &str,
+        depth: usize,
+    ) {
+        for name in required(schema) {
+            if !object.contains_key(name) {
+                self.error(&child_path(path, name), "is required");
+            }
+        }
+
+        let props = properties(schema);
+        for (key, child) in object {
+            let child_path = child_path(path, key);
+            if let Some(prop) = props.and_then(|p| p.get(key)) {
+                self.check(prop, child, &child_path, depth + 1);
+                continue;
+            }
+            match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => {
+                    let message = match props.and_then(|p| closest(key, p.keys())) {
+                        Some(suggestion) => {
+                            format!("unknown property (did you mean '{}'?)", suggestion)
+                        }
+                        None => "unknown property".to_string(),
+                    };
+                    self.error(&child_path, message);
+                }
+                Some(extra @ Value::Object(_)) => self.check(extra, child, &child_path, depth + 1),
+                _ => {}
+            }
+        }
+    }
+
+    fn check_combinators(&mut self, schema: &'a Value, value: &Value, path: &str, depth: usize) {
+        if let Some(all_of) = schema.get("allOf").and_then(Value::as_array) {
+            for sub in all_of {
+                self.check(sub, value, path, depth + 1);
+            }
+        }
+        if let Some(any_of) = schema.get("anyOf").and_then(Value::as_array) {
+            if !any_of.iter().any(|sub| self.matches(sub, value, depth + 1)) {
+                self.error(path, "does not match any of the allowed forms");
+            }
+        }
+        if let Some(one_of) = schema.get("oneOf").and_then(Value::as_array) {
+            let matching = one_of
+                .iter()
+                .filter(|sub| self.matches(sub, value, depth + 1))
+                .count();
+            if matching != 1 {
+                self.error(
+                    path,
+                    format!("must match exactly one allowed form (matched {})", matching),
+                );
+            }
+        }
+        if let Some(not) = schema.get("not") {
+            if self.matches(not, value, depth + 1) {
+                self.error(path, "matches a disallowed form");
+            }
+        }
+    }
+}
+
+fn type_matches(expected: &str, value: &Value) -> bool {
+    match expected {
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        "boolean" => value.is_boolean(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Closest known name within two edits, for typo hints
+fn closest<'k>(name: &str, candidates: impl Iterator<Item = &'k String>) -> Option<&'k str> {
+    candidates
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.as_str())
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            current.push(
+                (previous[j] + cost)
+                    .min(previous[j + 1] + 1)
+                    .min(current[j] + 1),
+            );
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
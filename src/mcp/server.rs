@@ -2,12 +2,13 @@
 //!
 //! Provides high-level server connection, status tracking, and reconnection logic.
 
-use super::client::McpManager;
+use super::client::{McpEvent, McpManager};
 use super::config::McpServerConfig;
 use super::protocol::McpError;
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
 
 /// Server connection status
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -22,6 +23,8 @@ pub enum ServerStatus {
     Error,
     /// Server is reconnecting after failure
     Reconnecting,
+    /// Restarts were stopped after repeated failures
+    CircuitOpen,
 }
 
 /// Server health information
@@ -37,6 +40,12 @@ pub struct ServerHealth {
     pub last_error: Option<String>,
     /// Uptime since last connection
     pub connected_since: Option<Instant>,
+    /// Round-trip time of the last successful ping
+    pub latency: Option<Duration>,
+    /// Restarts since the server was first connected
+    pub restarts: u32,
+    /// When the next restart is due
+    pub next_retry: Option<Instant>,
 }
 
 impl Default for ServerHealth {
@@ -47,6 +56,9 @@ impl Default for ServerHealth {
             failure_count: 0,
             last_error: None,
             connected_since: None,
+            latency: None,
+            restarts: 0,
+            next_retry: None,
         }
     }
 }
@@ -70,8 +82,13 @@ pub struct McpServerRegistry {
 impl McpServerRegistry {
     /// Create a new server registry
     pub fn new() -> Self {
+        Self::with_manager(McpManager::new())
+    }
+
+    /// Create a registry around a configured manager (events, request handler)
+    pub fn with_manager(manager: McpManager) -> Self {
         Self {
-            manager: McpManager::new(),
+            manager,
             configs: HashMap::new(),
             health: HashMap::new(),
             auto_reconnect: true,
@@ -81,7 +98,7 @@ impl McpServerRegistry {
     }
 
     /// Connect to an MCP server
-    pub fn connect(
+    pub async fn connect(
         &mut self,
         name: impl Into<String>,
         config: McpServerConfig,
@@ -101,7 +118,7 @@ impl McpServerRegistry {
         );
 
         // Attempt connection
-        match self.manager.connect(&name, config) {
+        match self.manager.connect(&name, config).await {
             Ok(()) => {
                 // Update health to connected
                 if let Some(health) = self.health.get_mut(&name) {
@@ -125,8 +142,8 @@ impl McpServerRegistry {
     }
 
     /// Disconnect from an MCP server
-    pub fn disconnect(&mut self, name: &str) -> Result<(), McpError> {
-        self.manager.disconnect(name)?;
+    pub async fn disconnect(&mut self, name: &str) -> Result<(), McpError> {
+        self.manager.disconnect(name).await?;
         if let Some(health) = self.health.get_mut(name) {
             health.status = ServerStatus::Disconnected;
             health.connected_since = None;
@@ -135,8 +152,8 @@ impl McpServerRegistry {
     }
 
     /// Disconnect from all servers
-    pub fn disconnect_all(&mut self) {
-        self.manager.disconnect_all();
+    pub async fn disconnect_all(&mut self) {
+        self.manager.disconnect_all().await;
         for health in self.health.values_mut() {
             health.status = ServerStatus::Disconnected;
             health.connected_since = None;
@@ -144,7 +161,7 @@ impl McpServerRegistry {
     }
 
     /// Attempt to reconnect a failed server
-    pub fn reconnect(&mut self, name: &str) -> Result<(), McpError> {
+    pub async fn reconnect(&mut self, name: &str) -> Result<(), McpError> {
         let config = self
             .configs
             .get(name)
@@ -167,10 +184,27 @@ impl McpServerRegistry {
         }
 
         // Disconnect if still connected
-        let _ = self.manager.disconnect(name);
+        let _ = self.manager.disconnect(name).await;
 
         // Reconnect
-        self.connect(name, config)
+        self.connect(name, config).await
+    }
+
+    /// Apply a server event: refresh lists on change notices and mark
+    /// servers that closed their connection as failed
+    pub async fn handle_event(&mut self, event: &McpEvent) -> Result<(), McpError> {
+        if let McpEvent::Closed { server } = event {
+            if let Some(health) = self.health.get_mut(server) {
+                if health.status == ServerStatus::Connected {
+                    health.status = ServerStatus::Error;
+                    health.failure_count += 1;
+                    health.last_error = Some("Server closed connection".to_string());
+                    health.connected_since = None;
+                }
+            }
+            return Ok(());
+        }
+        self.manager.handle_event(event).await
     }
 
     /// Get server health status
@@ -178,6 +212,16 @@ impl McpServerRegistry {
         self.health.get(name)
     }
 
+    /// Mutable health of a server, created if missing
+    pub(crate) fn health_mut(&mut self, name: &str) -> &mut ServerHealth {
+        self.health.entry(name.to_string()).or_default()
+    }
+
+    /// Configuration a server was connected with
+    pub fn config(&self, name: &str) -> Option<&McpServerConfig> {
+        self.configs.get(name)
+    }
+
     /// Get all server health statuses
     pub fn all_health(&self) -> impl Iterator<Item = (&String, &ServerHealth)> {
         self.health.iter()
@@ -219,13 +263,18 @@ impl McpServerRegistry {
         self.auto_reconnect = enabled;
     }
 
+    /// Whether failed servers are restarted
+    pub fn auto_reconnect(&self) -> bool {
+        self.auto_reconnect
+    }
+
     /// Get server names
     pub fn server_names(&self) -> impl Iterator<Item = &String> {
         self.configs.keys()
     }
 
     /// Check and attempt reconnection for failed servers (call periodically)
-    pub fn check_and_reconnect(&mut self) -> Vec<(String, Result<(), McpError>)> {
+    pub async fn check_and_reconnect(&mut self) -> Vec<(String, Result<(), McpError>)> {
         if !self.auto_reconnect {
             return Vec::new();
         }
@@ -239,13 +288,12 @@ impl McpServerRegistry {
             .map(|(name, _)| name.clone())
             .collect();
 
-        failed_servers
-            .into_iter()
-            .map(|name| {
-                let result = self.reconnect(&name);
-                (name, result)
-            })
-            .collect()
+        let mut results = Vec::with_capacity(failed_servers.len());
+        for name in failed_servers {
+            let result = self.reconnect(&name).await;
+            results.push((name, result));
+        }
+        results
     }
 }
 
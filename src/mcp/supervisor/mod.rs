@@ -0,0 +1,349 @@
+//! Supervision of connected MCP servers
+//!
+//! A background task pings every connected server, notices crashed
+//! processes and unanswered pings, and restarts failed servers with
+//! exponential backoff. Servers that keep failing have their circuit opened
+//! and are only tried again after a cool-down. Status changes are published
+//! as [`McpHealthEvent`]s and mirrored in the registry's [`ServerHealth`].
+//!
+//! [`ServerHealth`]: super::ServerHealth
+
+mod types;
+
+#[cfg(test)]
+mod tests;
+
+pub use types::{McpHealthEvent, SupervisorPolicy};
+
+use super::client::McpConnection;
+use super::server::{ServerStatus, SharedMcpRegistry};
+use futures::future::join_all;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::{mpsc, Notify};
+use tokio::task::JoinHandle;
+
+/// Handle to a running supervisor; dropping it stops supervision
+pub struct McpSupervisor {
+    wake: Arc<Notify>,
+    task: JoinHandle<()>,
+}
+
+impl McpSupervisor {
+    /// Start supervising the servers in `registry`
+    pub fn spawn(
+        registry: SharedMcpRegistry,
+        policy: SupervisorPolicy,
+        events: mpsc::UnboundedSender<McpHealthEvent>,
+    ) -> Self {
+        let wake = Arc::new(Notify::new());
+        let mut supervision = Supervision {
+            registry,
+            policy,
+            events,
+            states: HashMap::new(),
+        };
+        let notified = wake.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                supervision.check().await;
+                tokio::select! {
+                    _ = tokio::time::sleep(supervision.policy.check_interval) => {}
+                    _ = notified.notified() => {}
+                }
+            }
+        });
+        Self { wake, task }
+    }
+
+    /// Check servers now, e.g. after an [`McpEvent::Closed`](super::McpEvent::Closed)
+    pub fn wake(&self) {
+        self.wake.notify_one();
+    }
+
+    /// Whether the supervisor task is still running
+    pub fn is_running(&self) -> bool {
+        !self.task.is_finished()
+    }
+
+    /// Stop supervising
+    pub fn stop(&self) {
+        self.task.abort();
+    }
+}
+
+impl Drop for McpSupervisor {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// What the supervisor tracks per server
+#[derive(Default)]
+struct ServerState {
+    /// Status last published
+    status: Option<ServerStatus>,
+    /// When the server was last pinged
+    last_probe: Option<Instant>,
+    /// Consecutive unanswered pings
+    missed_pings: u32,
+    /// Consecutive failed restarts
+    attempt: u32,
+    /// When the next restart is due
+    next_retry: Option<Instant>,
+    /// Recent failures, oldest first
+    failures: VecDeque<Instant>,
+    /// When the circuit was opened
+    circuit_opened: Option<Instant>,
+}
+
+/// A server as seen at the start of a check
+struct Snapshot {
+    name: String,
+    status: ServerStatus,
+    error: Option<String>,
+    /// Open connection of a running client
+    connection: Option<McpConnection>,
+}
+
+struct Supervision {
+    registry: SharedMcpRegistry,
+    policy: SupervisorPolicy,
+    events: mpsc::UnboundedSender<McpHealthEvent>,
+    states: HashMap<String, ServerState>,
+}
+
+impl Supervision {
+    /// Probe and restart servers as due
+    ///
+    /// The registry is only locked to read and update state; pings and
+    /// restarts run without holding it.
+    async fn check(&mut self) {
+        let (snapshots, auto_reconnect) = {
+            let registry = self.registry.lock().await;
+            let snapshots: Vec<Snapshot> = registry
+                .server_names()
+                .map(|name| {
+                    let health = registry.health(name);
+                    Snapshot {
+                        name: name.clone(),
+                        status: health.map_or(ServerStatus::Disconnected, |h| h.status),
+                        error: health.and_then(|h| h.last_error.clone()),
+                        connection: registry
+                            .manager()
+                            .get(name)
+                            .filter(|client| client.is_running())
+                            .and_then(|client| client.connection().ok()),
+                    }
+                })
+                .collect();
+            (snapshots, registry.auto_reconnect())
+        };
+        self.states
+            .retain(|name, _| snapshots.iter().any(|s| &s.name == name));
+
+        let now = Instant::now();
+        let mut probes = Vec::new();
+        for snapshot in snapshots {
+            let name = snapshot.name;
+            self.publish_status(&name, snapshot.status, snapshot.error.clone());
+            let state = self.states.entry(name.clone()).or_default();
+
+            match snapshot.status {
+                ServerStatus::Connected => match snapshot.connection {
+                    None => self.fail(&name, "Server process exited".into()).await,
+                    Some(connection) => {
+                        let due = state
+                            .last_probe
+                            .is_none_or(|at| now >= at + self.policy.ping_interval);
+                        if due {
+                            state.last_probe = Some(now);
+                            probes.push((name, connection));
+                        }
+                    }
+                },
+                ServerStatus::Error | ServerStatus::Reconnecting if auto_reconnect => {
+                    match state.next_retry {
+                        // Failed outside the supervisor (closed, failed to connect)
+                        None => {
+                            let error = snapshot
+                                .error
+                                .unwrap_or_else(|| "Server failed".to_string());
+                            self.fail(&name, error).await;
+                        }
+                        Some(at) if now >= at => self.restart(&name).await,
+                        Some(_) => {}
+                    }
+                }
+                ServerStatus::CircuitOpen if auto_reconnect => {
+                    let cooled = state
+                        .circuit_opened
+                        .is_none_or(|at| now >= at + self.policy.circuit_reset);
+                    if cooled {
+                        self.restart(&name).await;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let timeout = self.policy.ping_timeout;
+        let results = join_all(probes.into_iter().map(|(name, connection)| async move {
+            let result = connection.ping(timeout).await;
+            (name, result)
+        }))
+        .await;
+        for (name, result) in results {
+            match result {
+                Ok(latency) => {
+                    if let Some(state) = self.states.get_mut(&name) {
+                        state.missed_pings = 0;
+                    }
+                    let mut registry = self.registry.lock().await;
+                    let health = registry.health_mut(&name);
+                    health.last_ping = Some(Instant::now());
+                    health.latency = Some(latency);
+                }
+                Err(e) => {
+                    let state = self.states.entry(name.clone()).or_default();
+                    state.missed_pings += 1;
+                    tracing::debug!("MCP server '{}' missed a ping: {}", name, e);
+                    if state.missed_pings >= self.policy.max_missed_pings {
+                        self.fail(&name, format!("No response to ping: {}", e))
+                            .await;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Record a failure and schedule a restart, or open the circuit
+    async fn fail(&mut self, name: &str, error: String) {
+        let now = Instant::now();
+        let state = self.states.entry(name.to_string()).or_default();
+        state.missed_pings = 0;
+        state.failures.push_back(now);
+        while state
+            .failures
+            .front()
+            .is_some_and(|at| now.duration_since(*at) > self.policy.failure_window)
+        {
+            state.failures.pop_front();
+        }
+
+        let failures = state.failures.len();
+        let attempt = state.attempt;
+        let (status, next_retry) = if failures >= self.policy.max_failures {
+            state.circuit_opened = Some(now);
+            (ServerStatus::CircuitOpen, None)
+        } else {
+            let delay = self.policy.restart_delay(attempt);
+            (ServerStatus::Reconnecting, Some((now + delay, delay)))
+        };
+        state.next_retry = next_retry.map(|(at, _)| at);
+
+        tracing::warn!("MCP server '{}' failed: {}", name, error);
+        let client = {
+            let mut registry = self.registry.lock().await;
+            let health = registry.health_mut(name);
+            health.status = status;
+            health.failure_count += 1;
+            health.last_error = Some(error.clone());
+            health.connected_since = None;
+            health.next_retry = state.next_retry;
+            registry.manager_mut().remove(name)
+        };
+        if let Some(mut client) = client {
+            let _ = client.stop().await;
+        }
+
+        self.publish_status(name, status, Some(error));
+        match next_retry {
+            Some((_, delay)) => self.send(McpHealthEvent::Restarting {
+                server: name.to_string(),
+                attempt: attempt + 1,
+                delay,
+            }),
+            None => self.send(McpHealthEvent::CircuitOpen {
+                server: name.to_string(),
+                failures,
+            }),
+        }
+    }
+
+    /// Start the server again and fetch its lists
+    async fn restart(&mut self, name: &str) {
+        let client = {
+            let mut registry = self.registry.lock().await;
+            let Some(config) = registry.config(name).cloned() else {
+                return;
+            };
+            registry.health_mut(name).next_retry = None;
+            registry.manager_mut().client_for(name, config)
+        };
+        if let Some(state) = self.states.get_mut(name) {
+            state.next_retry = None;
+        }
+
+        let mut client = client;
+        let result = match client.start().await {
+            Ok(()) => client.initialize().await.map(|_| ()),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            let _ = client.stop().await;
+            self.states.entry(name.to_string()).or_default().attempt += 1;
+            self.fail(name, e.to_string()).await;
+            return;
+        }
+
+        let tools = client.tools().len();
+        {
+            let mut registry = self.registry.lock().await;
+            // Disconnected by the user while restarting
+            if registry.health(name).map(|h| h.status) == Some(ServerStatus::Disconnected) {
+                drop(registry);
+                let _ = client.stop().await;
+                return;
+            }
+            registry.manager_mut().insert(client).await;
+            let health = registry.health_mut(name);
+            health.status = ServerStatus::Connected;
+            health.connected_since = Some(Instant::now());
+            health.failure_count = 0;
+            health.last_error = None;
+            health.restarts += 1;
+        }
+
+        let state = self.states.entry(name.to_string()).or_default();
+        state.attempt = 0;
+        state.circuit_opened = None;
+        state.last_probe = Some(Instant::now());
+        tracing::info!("MCP server '{}' restarted", name);
+        self.publish_status(name, ServerStatus::Connected, None);
+        self.send(McpHealthEvent::Restored {
+            server: name.to_string(),
+            tools,
+        });
+    }
+
+    /// Publish `status` if it differs from the last one published
+    fn publish_status(&mut self, name: &str, status: ServerStatus, error: Option<String>) {
+        let state = self.states.entry(name.to_string()).or_default();
+        if state.status == Some(status) {
+            return;
+        }
+        state.status = Some(status);
+        self.send(McpHealthEvent::StatusChanged {
+            server: name.to_string(),
+            status,
+            error: error.filter(|_| status != ServerStatus::Connected),
+        });
+    }
+
+    fn send(&self, event: McpHealthEvent) {
+        // Nobody listening is fine; supervision goes on
+        let _ = self.events.send(event);
+    }
+}
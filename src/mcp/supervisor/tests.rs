@@ -0,0 +1,158 @@
+//! Tests for MCP server supervision
+
+use super::*;
+use crate::mcp::config::McpServerConfig;
+use crate::mcp::server::McpServerRegistry;
+use crate::mcp::transport::mock::{MockMcpServer, MockMode};
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+fn fast_policy(max_failures: usize) -> SupervisorPolicy {
+    SupervisorPolicy {
+        check_interval: Duration::from_millis(10),
+        ping_interval: Duration::from_millis(20),
+        ping_timeout: Duration::from_millis(500),
+        max_missed_pings: 2,
+        initial_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(40),
+        jitter: 0.0,
+        max_failures,
+        circuit_reset: Duration::from_secs(3600),
+        ..Default::default()
+    }
+}
+
+async fn connected_registry(url: &str) -> SharedMcpRegistry {
+    let mut registry = McpServerRegistry::new();
+    registry
+        .connect("mock", McpServerConfig::http(url))
+        .await
+        .unwrap();
+    Arc::new(Mutex::new(registry))
+}
+
+/// Next event matching `pred`, failing after a few seconds
+async fn wait_for(
+    events: &mut mpsc::UnboundedReceiver<McpHealthEvent>,
+    pred: impl Fn(&McpHealthEvent) -> bool,
+) -> McpHealthEvent {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let event = events.recv().await.expect("supervisor stopped");
+            if pred(&event) {
+                return event;
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for health event")
+}
+
+#[test]
+fn test_backoff_grows_and_caps() {
+    let policy = SupervisorPolicy {
+        initial_backoff: Duration::from_secs(1),
+        max_backoff: Duration::from_secs(5),
+        jitter: 0.5,
+        ..Default::default()
+    };
+    assert_eq!(policy.backoff(0), Duration::from_secs(1));
+    assert_eq!(policy.backoff(1), Duration::from_secs(2));
+    assert_eq!(policy.backoff(2), Duration::from_secs(4));
+    assert_eq!(policy.backoff(3), Duration::from_secs(5));
+
+    for _ in 0..20 {
+        let delay = policy.restart_delay(1);
+        assert!(delay >= Duration::from_secs(2) && delay <= Duration::from_secs(3));
+    }
+    assert_eq!(policy.restart_delay(10), Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn test_pings_and_restores_failed_server() {
+    let server = MockMcpServer::start(MockMode::Json).await;
+    let registry = connected_registry(&server.url).await;
+    let (tx, mut events) = mpsc::unbounded_channel();
+    let _supervisor = McpSupervisor::spawn(registry.clone(), fast_policy(10), tx);
+
+    wait_for(&mut events, |e| {
+        matches!(
+            e,
+            McpHealthEvent::StatusChanged {
+                status: ServerStatus::Connected,
+                ..
+            }
+        )
+    })
+    .await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    {
+        let registry = registry.lock().await;
+        let health = registry.health("mock").unwrap();
+        assert!(health.last_ping.is_some());
+        assert!(health.latency.is_some());
+    }
+    assert!(server.methods().iter().any(|m| m == "ping"));
+
+    server.set_down(true);
+    wait_for(&mut events, |e| {
+        matches!(e, McpHealthEvent::Restarting { .. })
+    })
+    .await;
+    assert!(registry.lock().await.manager().get("mock").is_none());
+
+    server.set_down(false);
+    let restored = wait_for(&mut events, |e| {
+        matches!(e, McpHealthEvent::Restored { .. })
+    })
+    .await;
+    assert!(matches!(
+        restored,
+        McpHealthEvent::Restored { tools: 1, .. }
+    ));
+
+    let registry = registry.lock().await;
+    assert!(registry.is_connected("mock"));
+    assert_eq!(registry.health("mock").unwrap().restarts, 1);
+    assert_eq!(registry.manager().all_tools().len(), 1);
+}
+
+#[tokio::test]
+async fn test_opens_circuit_after_repeated_failures() {
+    let server = MockMcpServer::start(MockMode::Json).await;
+    let registry = connected_registry(&server.url).await;
+    let (tx, mut events) = mpsc::unbounded_channel();
+    let _supervisor = McpSupervisor::spawn(registry.clone(), fast_policy(3), tx);
+
+    server.set_down(true);
+    let mut attempts = Vec::new();
+    let opened = wait_for(&mut events, |e| {
+        matches!(
+            e,
+            McpHealthEvent::CircuitOpen { .. } | McpHealthEvent::Restarting { .. }
+        )
+    })
+    .await;
+    let mut event = opened;
+    while let McpHealthEvent::Restarting { attempt, .. } = event {
+        attempts.push(attempt);
+        event = wait_for(&mut events, |e| {
+            matches!(
+                e,
+                McpHealthEvent::CircuitOpen { .. } | McpHealthEvent::Restarting { .. }
+            )
+        })
+        .await;
+    }
+
+    assert_eq!(attempts, vec![1, 2]);
+    assert!(matches!(
+        event,
+        McpHealthEvent::CircuitOpen { failures: 3, .. }
+    ));
+    let registry = registry.lock().await;
+    let health = registry.health("mock").unwrap();
+    assert_eq!(health.status, ServerStatus::CircuitOpen);
+    assert!(health.next_retry.is_none());
+    assert!(health.last_error.is_some());
+}
@@ -0,0 +1,99 @@
+//! Supervisor policy and health events
+
+use super::super::server::ServerStatus;
+use std::time::Duration;
+
+/// Probe, restart and circuit-breaker settings
+#[derive(Debug, Clone)]
+pub struct SupervisorPolicy {
+    /// How often servers are checked
+    pub check_interval: Duration,
+    /// Time between pings to a connected server
+    pub ping_interval: Duration,
+    /// Wait for a ping response at most this long
+    pub ping_timeout: Duration,
+    /// Consecutive unanswered pings before a server is restarted
+    pub max_missed_pings: u32,
+    /// Delay before the first restart
+    pub initial_backoff: Duration,
+    /// Upper bound for computed delays
+    pub max_backoff: Duration,
+    /// Backoff growth factor
+    pub multiplier: f64,
+    /// Random jitter as a fraction of the delay (0.0 - 1.0)
+    pub jitter: f64,
+    /// Failures within `failure_window` that open the circuit
+    pub max_failures: usize,
+    /// Window failures are counted in
+    pub failure_window: Duration,
+    /// Wait before trying a server with an open circuit once more
+    pub circuit_reset: Duration,
+}
+
+impl Default for SupervisorPolicy {
+    fn default() -> Self {
+        Self {
+            check_interval: Duration::from_secs(1),
+            ping_interval: Duration::from_secs(30),
+            ping_timeout: Duration::from_secs(10),
+            max_missed_pings: 2,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            multiplier: 2.0,
+            jitter: 0.2,
+            max_failures: 5,
+            failure_window: Duration::from_secs(600),
+            circuit_reset: Duration::from_secs(300),
+        }
+    }
+}
+
+impl SupervisorPolicy {
+    /// Exponential delay before restart number `attempt` (0-based), without jitter
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = self.multiplier.max(1.0).powi(attempt as i32);
+        let delay = self.initial_backoff.as_secs_f64() * factor;
+        Duration::from_secs_f64(delay.min(self.max_backoff.as_secs_f64()))
+    }
+
+    /// Delay before restart number `attempt`, with jitter
+    pub fn restart_delay(&self, attempt: u32) -> Duration {
+        let jitter = self.jitter.clamp(0.0, 1.0) * rand::random::<f64>();
+        self.backoff(attempt)
+            .mul_f64(1.0 + jitter)
+            .min(self.max_backoff)
+    }
+}
+
+/// Change in a supervised server's health
+#[derive(Debug, Clone)]
+pub enum McpHealthEvent {
+    /// The server's status changed
+    StatusChanged {
+        server: String,
+        status: ServerStatus,
+        error: Option<String>,
+    },
+    /// A restart is scheduled after `delay`
+    Restarting {
+        server: String,
+        attempt: u32,
+        delay: Duration,
+    },
+    /// The server was restarted and its lists fetched again
+    Restored { server: String, tools: usize },
+    /// Restarts stopped after `failures` recent failures
+    CircuitOpen { server: String, failures: usize },
+}
+
+impl McpHealthEvent {
+    /// Server the event is about
+    pub fn server(&self) -> &str {
+        match self {
+            Self::StatusChanged { server, .. }
+            | Self::Restarting { server, .. }
+            | Self::Restored { server, .. }
+            | Self::CircuitOpen { server, .. } => server,
+        }
+    }
+}
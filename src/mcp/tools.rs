@@ -3,6 +3,7 @@
 //! Provides tool discovery, categorization, and invocation helpers.
 
 use super::protocol::{McpError, McpTool};
+use super::schema::prepare_arguments;
 use serde_json::Value;
 use std::collections::HashMap;
 
@@ -352,28 +353,19 @@ impl Default for ToolRegistry {
 }
 
 /// Build tool call arguments from a template
+///
+/// Fills schema defaults and validates against the tool's `inputSchema`.
 pub fn build_arguments(
     tool: &McpTool,
     values: HashMap<String, Value>,
 ) -> Result<HashMap<String, Value>, McpError> {
-    // Validate required parameters
-    if let Some(ref required) = tool.input_schema.required {
-        for param_name in required {
-            if !values.contains_key(param_name) {
-                return Err(McpError::Protocol(format!(
-                    "Missing required parameter: {}",
-                    param_name
-                )));
-            }
-        }
-    }
-
-    Ok(values)
+    prepare_arguments(tool, values)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mcp::protocol::ToolInputSchema;
 
     #[test]
     fn test_tool_categorization() {
@@ -405,8 +397,16 @@ mod tests {
 
         let tool = McpTool {
             name: "read_file".to_string(),
+            title: None,
             description: Some("Read a file".to_string()),
-            input_schema: None,
+            input_schema: ToolInputSchema {
+                schema_type: "object".to_string(),
+                properties: None,
+                required: None,
+                extra: Default::default(),
+            },
+            output_schema: None,
+            annotations: None,
         };
 
         registry.register(tool, "filesystem");
@@ -0,0 +1,121 @@
+//! Server-sent events parsing
+
+use super::super::protocol::McpError;
+
+/// A server-sent event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SseEvent {
+    /// Event type (`message` when not given)
+    pub event: String,
+    /// Data lines joined with `\n`
+    pub data: String,
+    /// Event id, if any
+    pub id: Option<String>,
+}
+
+/// Incremental `text/event-stream` parser
+#[derive(Debug, Default)]
+pub(crate) struct SseParser {
+    buffer: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+    id: Option<String>,
+}
+
+impl SseParser {
+    /// Feed bytes, returning the events completed by them
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(bytes);
+        let mut events = Vec::new();
+
+        while let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
+            let raw: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&raw);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if line.is_empty() {
+                if let Some(event) = self.dispatch() {
+                    events.push(event);
+                }
+                continue;
+            }
+            if line.starts_with(':') {
+                // Comment / keep-alive
+                continue;
+            }
+
+            let (field, value) = match line.split_once(':') {
+                Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+                None => (line, ""),
+            };
+            match field {
+                "event" => self.event = Some(value.to_string()),
+                "data" => self.data.push(value.to_string()),
+                "id" => self.id = Some(value.to_string()),
+                _ => {}
+            }
+        }
+
+        events
+    }
+
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        let event = self.event.take();
+        if self.data.is_empty() {
+            return None;
+        }
+        Some(SseEvent {
+            event: event.unwrap_or_else(|| "message".to_string()),
+            data: std::mem::take(&mut self.data).join("\n"),
+            id: self.id.clone(),
+        })
+    }
+}
+
+/// Read events from a streaming response until it ends or `on_event` returns false
+pub(crate) async fn read_events(
+    mut response: reqwest::Response,
+    mut on_event: impl FnMut(SseEvent) -> bool,
+) -> Result<(), McpError> {
+    let mut parser = SseParser::default();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| McpError::Connection(format!("Event stream error: {}", e)))?
+    {
+        for event in parser.push(&chunk) {
+            if !on_event(event) {
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_events_across_chunks() {
+        let mut parser = SseParser::default();
+        assert!(parser.push(b"event: endpoint\r\nda").is_empty());
+        let events =
+            parser.push(b"ta: /messages?id=1\r\n\r\n: ping\n\nid: 7\ndata: {\"a\":\ndata: 1}\n\n");
+        assert_eq!(
+            events,
+            vec![
+                SseEvent {
+                    event: "endpoint".to_string(),
+                    data: "/messages?id=1".to_string(),
+                    id: None,
+                },
+                SseEvent {
+                    event: "message".to_string(),
+                    data: "{\"a\":\n1}".to_string(),
+                    id: Some("7".to_string()),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,289 @@
+//! In-process MCP server over HTTP for transport tests
+
+use super::streamable_http::SESSION_HEADER;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+
+/// How the mock answers POSTed requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MockMode {
+    /// Streamable HTTP, responses as JSON bodies
+    Json,
+    /// Streamable HTTP, responses as SSE streams preceded by a progress notification
+    EventStream,
+    /// Legacy HTTP+SSE: GET stream plus POST endpoint
+    LegacySse,
+}
+
+#[derive(Default)]
+struct MockState {
+    /// Session currently accepted
+    session: Option<String>,
+    /// Sessions handed out so far
+    sessions_created: u32,
+    /// Methods received, in order
+    methods: Vec<String>,
+    /// Headers of every request (lowercased)
+    heads: Vec<String>,
+    /// Legacy SSE stream to push responses onto
+    stream: Option<mpsc::UnboundedSender<String>>,
+    /// Answer every request with 503
+    down: bool,
+}
+
+/// Mock MCP server exposing an `echo` tool
+pub(crate) struct MockMcpServer {
+    /// Base URL (`/mcp` for Streamable HTTP, `/sse` for legacy)
+    pub url: String,
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockMcpServer {
+    pub async fn start(mode: MockMode) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let state = Arc::new(Mutex::new(MockState::default()));
+
+        let shared = state.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(handle_connection(stream, mode, shared.clone()));
+            }
+        });
+
+        let path = if mode == MockMode::LegacySse {
+            "sse"
+        } else {
+            "mcp"
+        };
+        Self {
+            url: format!("http://{}/{}", addr, path),
+            state,
+        }
+    }
+
+    /// Forget the current session so the next request gets a 404
+    pub fn expire_session(&self) {
+        self.state.lock().unwrap().session = None;
+    }
+
+    /// Refuse (or accept again) every request, as a crashed server would
+    pub fn set_down(&self, down: bool) {
+        self.state.lock().unwrap().down = down;
+    }
+
+    pub fn sessions_created(&self) -> u32 {
+        self.state.lock().unwrap().sessions_created
+    }
+
+    pub fn methods(&self) -> Vec<String> {
+        self.state.lock().unwrap().methods.clone()
+    }
+
+    pub fn heads(&self) -> Vec<String> {
+        self.state.lock().unwrap().heads.clone()
+    }
+}
+
+/// Result for a request the mock understands
+fn result_for(method: &str, params: &Value) -> Result<Value, (i32, String)> {
+    match method {
+        "initialize" => Ok(json!({
+            "protocolVersion": params["protocolVersion"],
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "mock", "version": "1.0.0" }
+        })),
+        "tools/list" => Ok(json!({
+            "tools": [{
+                "name": "echo",
+                "description": "Echo the given text",
+                "inputSchema": { "type": "object", "properties": { "text": { "type": "string" } } }
+            }]
+        })),
+        "ping" => Ok(json!({})),
+        "tools/call" => Ok(json!({
+            "content": [{ "type": "text", "text": params["arguments"]["text"] }]
+        })),
+        _ => Err((-32601, format!("Method not found: {}", method))),
+    }
+}
+
+fn response_for(message: &Value) -> Value {
+    let method = message["method"].as_str().unwrap_or_default();
+    match result_for(method, &message["params"]) {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": message["id"], "result": result }),
+        Err((code, text)) => json!({
+            "jsonrpc": "2.0",
+            "id": message["id"],
+            "error": { "code": code, "message": text }
+        }),
+    }
+}
+
+fn sse_event(event: &str, data: &str) -> String {
+    format!("event: {}\ndata: {}\n\n", event, data)
+}
+
+async fn write_response(
+    stream: &mut TcpStream,
+    status: &str,
+    headers: &[(&str, &str)],
+    body: &str,
+) {
+    let mut response = format!("HTTP/1.1 {}\r\nconnection: close\r\n", status);
+    for (name, value) in headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str(&format!("content-length: {}\r\n\r\n{}", body.len(), body));
+    let _ = stream.write_all(response.as_bytes()).await;
+}
+
+/// Read one request, returning its lowercased head and body
+async fn read_request(stream: &mut TcpStream) -> Option<(String, Vec<u8>)> {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 4096];
+    let body_start = loop {
+        if let Some(pos) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        match stream.read(&mut buf).await {
+            Ok(0) | Err(_) => return None,
+            Ok(n) => data.extend_from_slice(&buf[..n]),
+        }
+    };
+    let head = String::from_utf8_lossy(&data[..body_start]).to_lowercase();
+    let length = head
+        .lines()
+        .find_map(|l| l.strip_prefix("content-length:"))
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+    while data.len() < body_start + length {
+        match stream.read(&mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => data.extend_from_slice(&buf[..n]),
+        }
+    }
+    Some((head, data[body_start..].to_vec()))
+}
+
+async fn handle_connection(mut stream: TcpStream, mode: MockMode, state: Arc<Mutex<MockState>>) {
+    let Some((head, body)) = read_request(&mut stream).await else {
+        return;
+    };
+    let verb = head
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    let session = head
+        .lines()
+        .find_map(|l| l.strip_prefix(&format!("{}:", SESSION_HEADER)))
+        .map(|v| v.trim().to_string());
+    let down = {
+        let mut state = state.lock().unwrap();
+        state.heads.push(head);
+        state.down
+    };
+    if down {
+        write_response(&mut stream, "503 Service Unavailable", &[], "").await;
+        return;
+    }
+
+    match (mode, verb.as_str()) {
+        (MockMode::LegacySse, "get") => {
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            state.lock().unwrap().stream = Some(tx);
+            let start = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\n\r\n{}",
+                sse_event("endpoint", "/messages?session=1")
+            );
+            if stream.write_all(start.as_bytes()).await.is_err() {
+                return;
+            }
+            while let Some(event) = rx.recv().await {
+                if stream.write_all(event.as_bytes()).await.is_err() {
+                    return;
+                }
+            }
+        }
+        (MockMode::LegacySse, "post") => {
+            let message: Value = serde_json::from_slice(&body).unwrap_or_default();
+            let sender = {
+                let mut state = state.lock().unwrap();
+                state
+                    .methods
+                    .push(message["method"].as_str().unwrap_or_default().to_string());
+                state.stream.clone()
+            };
+            write_response(&mut stream, "202 Accepted", &[], "").await;
+            if message.get("id").is_some() {
+                if let Some(sender) = sender {
+                    let _ = sender.send(sse_event("message", &response_for(&message).to_string()));
+                }
+            }
+        }
+        (_, "get") => write_response(&mut stream, "405 Method Not Allowed", &[], "").await,
+        (_, "delete") => {
+            state.lock().unwrap().session = None;
+            write_response(&mut stream, "200 OK", &[], "").await;
+        }
+        (_, "post") => {
+            let message: Value = serde_json::from_slice(&body).unwrap_or_default();
+            let method = message["method"].as_str().unwrap_or_default().to_string();
+
+            let new_session = {
+                let mut state = state.lock().unwrap();
+                state.methods.push(method.clone());
+                if method == "initialize" {
+                    state.sessions_created += 1;
+                    let id = format!("session-{}", state.sessions_created);
+                    state.session = Some(id.clone());
+                    Ok(Some(id))
+                } else if session.is_none() || session != state.session {
+                    Err(())
+                } else {
+                    Ok(None)
+                }
+            };
+            let Ok(new_session) = new_session else {
+                write_response(&mut stream, "404 Not Found", &[], "").await;
+                return;
+            };
+
+            let mut headers = Vec::new();
+            if let Some(id) = &new_session {
+                headers.push((SESSION_HEADER, id.as_str()));
+            }
+
+            if message.get("id").is_none() {
+                write_response(&mut stream, "202 Accepted", &headers, "").await;
+            } else if mode == MockMode::EventStream {
+                let progress = json!({
+                    "jsonrpc": "2.0",
+                    "method": "notifications/progress",
+                    "params": { "progressToken": message["id"], "progress": 1 }
+                });
+                let events = format!(
+                    "{}{}",
+                    sse_event("message", &progress.to_string()),
+                    sse_event("message", &response_for(&message).to_string())
+                );
+                headers.push(("content-type", "text/event-stream"));
+                write_response(&mut stream, "200 OK", &headers, &events).await;
+            } else {
+                headers.push(("content-type", "application/json"));
+                write_response(
+                    &mut stream,
+                    "200 OK",
+                    &headers,
+                    &response_for(&message).to_string(),
+                )
+                .await;
+            }
+        }
+        _ => write_response(&mut stream, "400 Bad Request", &[], "").await,
+    }
+}
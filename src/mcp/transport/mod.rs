@@ -0,0 +1,126 @@
+//! MCP Transports
+//!
+//! Moves JSON-RPC messages between the client and a server. Outgoing
+//! messages go through [`McpTransport::send`]; everything the server sends
+//! (responses, notifications, requests) arrives on the inbound channel
+//! returned by [`connect`].
+
+mod event_stream;
+mod sandbox;
+mod sse;
+mod stdio;
+mod streamable_http;
+
+#[cfg(test)]
+pub(crate) mod mock;
+#[cfg(test)]
+mod tests;
+
+pub use sandbox::{StderrLog, DEFAULT_STDERR_LINES};
+pub use sse::SseTransport;
+pub use stdio::StdioTransport;
+pub use streamable_http::StreamableHttpTransport;
+
+use super::config::{McpServerConfig, McpTransportKind};
+use super::protocol::McpError;
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+/// Messages received from a server
+pub type McpInbound = mpsc::UnboundedReceiver<Value>;
+
+/// A connection to an MCP server
+#[async_trait]
+pub trait McpTransport: Send + Sync {
+    /// Transport type
+    fn kind(&self) -> McpTransportKind;
+
+    /// Send one JSON-RPC message
+    async fn send(&self, message: Value) -> Result<(), McpError>;
+
+    /// Close the connection
+    async fn close(&self) -> Result<(), McpError>;
+
+    /// Whether the connection is still usable
+    fn is_connected(&self) -> bool;
+
+    /// Session id assigned by the server, if any
+    fn session_id(&self) -> Option<String> {
+        None
+    }
+
+    /// Record the protocol version negotiated during initialize
+    ///
+    /// HTTP transports echo it on every later request.
+    fn set_protocol_version(&self, _version: &str) {}
+}
+
+/// Open a transport for a server configuration
+pub async fn connect(
+    config: &McpServerConfig,
+) -> Result<(Box<dyn McpTransport>, McpInbound), McpError> {
+    connect_with_stderr(config, StderrLog::default()).await
+}
+
+/// Open a transport, keeping the stderr of stdio servers in `stderr`
+pub async fn connect_with_stderr(
+    config: &McpServerConfig,
+    stderr: StderrLog,
+) -> Result<(Box<dyn McpTransport>, McpInbound), McpError> {
+    match config.transport_kind() {
+        McpTransportKind::Stdio => {
+            let (transport, inbound) = StdioTransport::spawn_with_log(config, stderr)?;
+            Ok((Box::new(transport), inbound))
+        }
+        McpTransportKind::Http => {
+            let (transport, inbound) = StreamableHttpTransport::new(config)?;
+            Ok((Box::new(transport), inbound))
+        }
+        McpTransportKind::Sse => {
+            let (transport, inbound) = SseTransport::connect(config).await?;
+            Ok((Box::new(transport), inbound))
+        }
+    }
+}
+
+/// URL of an HTTP server configuration
+fn server_url(config: &McpServerConfig) -> Result<reqwest::Url, McpError> {
+    let url = config
+        .url
+        .as_deref()
+        .ok_or_else(|| McpError::Connection("No URL configured for HTTP server".into()))?;
+    reqwest::Url::parse(url).map_err(|e| McpError::Connection(format!("Invalid URL: {}", e)))
+}
+
+/// Configured headers as a header map
+fn header_map(config: &McpServerConfig) -> Result<HeaderMap, McpError> {
+    let mut headers = HeaderMap::new();
+    for (name, value) in &config.headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| McpError::Connection(format!("Invalid header name '{}': {}", name, e)))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|e| McpError::Connection(format!("Invalid header value: {}", e)))?;
+        headers.insert(name, value);
+    }
+    Ok(headers)
+}
+
+/// Parse a JSON body holding one message or a batch, forwarding each message
+fn forward_json(body: &[u8], inbound: &mpsc::UnboundedSender<Value>) -> Result<(), McpError> {
+    if body.iter().all(|b| b.is_ascii_whitespace()) {
+        return Ok(());
+    }
+    let value: Value = serde_json::from_slice(body)
+        .map_err(|e| McpError::Protocol(format!("Invalid JSON from server: {}", e)))?;
+    match value {
+        Value::Array(messages) => messages.into_iter().for_each(|m| {
+            let _ = inbound.send(m);
+        }),
+        message => {
+            let _ = inbound.send(message);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,371 @@
+//! Sandboxed launch of stdio servers
+
+use super::super::config::McpSandbox;
+use super::super::protocol::McpError;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::process::Command;
+
+/// Lines kept per server by default
+pub const DEFAULT_STDERR_LINES: usize = 500;
+
+/// Longest stderr line kept, in bytes
+const MAX_LINE_LEN: usize = 4096;
+
+/// Ring buffer of a server's stderr output
+///
+/// Cloning shares the buffer, so the log outlives the process and the
+/// connection that wrote to it.
+#[derive(Debug, Clone)]
+pub struct StderrLog {
+    lines: Arc<Mutex<VecDeque<String>>>,
+    capacity: usize,
+}
+
+impl Default for StderrLog {
+    fn default() -> Self {
+        Self::new(DEFAULT_STDERR_LINES)
+    }
+}
+
+impl StderrLog {
+    /// Create a log keeping the last `capacity` lines
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lines: Arc::new(Mutex::new(VecDeque::with_capacity(capacity.min(64)))),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Append a line, dropping the oldest when full
+    pub fn push(&self, line: impl Into<String>) {
+        let mut line = line.into();
+        if line.len() > MAX_LINE_LEN {
+            let mut end = MAX_LINE_LEN;
+            while !line.is_char_boundary(end) {
+                end -= 1;
+            }
+            line.truncate(end);
+            line.push('…');
+        }
+
+        let mut lines = self.lines.lock();
+        if lines.len() == self.capacity {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+
+    /// All kept lines, oldest first
+    pub fn lines(&self) -> Vec<String> {
+        self.lines.lock().iter().cloned().collect()
+    }
+
+    /// The last `count` lines, oldest first
+    pub fn tail(&self, count: usize) -> Vec<String> {
+        let lines = self.lines.lock();
+        lines
+            .iter()
+            .skip(lines.len().saturating_sub(count))
+            .cloned()
+            .collect()
+    }
+
+    /// Number of kept lines
+    pub fn len(&self) -> usize {
+        self.lines.lock().len()
+    }
+
+    /// Whether nothing was logged
+    pub fn is_empty(&self) -> bool {
+        self.lines.lock().is_empty()
+    }
+
+    /// Drop all lines
+    pub fn clear(&self) {
+        self.lines.lock().clear();
+    }
+}
+
+/// Configure `cmd` to run inside `sandbox`
+///
+/// Must run before the server's own `env` entries are added, since the
+/// environment is cleared here.
+pub(crate) fn apply(cmd: &mut Command, sandbox: &McpSandbox) -> Result<(), McpError> {
+    cmd.env_clear();
+    cmd.envs(std::env::vars().filter(|(name, _)| sandbox.inherits(name)));
+
+    let mut jail = None;
+    if let Some(dir) = &sandbox.working_dir {
+        let tmp = dir.join(".tmp");
+        let dir = std::fs::create_dir_all(&tmp)
+            .and_then(|_| dir.canonicalize())
+            .map_err(|e| {
+                McpError::Connection(format!(
+                    "Failed to create sandbox directory {}: {}",
+                    dir.display(),
+                    e
+                ))
+            })?;
+        let tmp = dir.join(".tmp");
+        cmd.current_dir(&dir)
+            .env("HOME", &dir)
+            .env("TMPDIR", &tmp)
+            .env("TEMP", &tmp)
+            .env("TMP", &tmp);
+        jail = Some(dir);
+    }
+
+    apply_limits(cmd, sandbox, jail.as_deref())
+}
+
+#[cfg(unix)]
+fn apply_limits(
+    cmd: &mut Command,
+    sandbox: &McpSandbox,
+    jail: Option<&std::path::Path>,
+) -> Result<(), McpError> {
+    let limits = [
+        (libc::RLIMIT_CPU, sandbox.cpu_seconds),
+        (
+            libc::RLIMIT_AS,
+            sandbox.memory_mb.map(|mb| mb.saturating_mul(1024 * 1024)),
+        ),
+        (libc::RLIMIT_NOFILE, sandbox.max_open_files),
+    ];
+    let no_network = sandbox.no_network;
+    if no_network && !cfg!(target_os = "linux") {
+        tracing::warn!("Network isolation for MCP servers is only available on Linux");
+    }
+
+    #[cfg(target_os = "linux")]
+    let write_jail = jail.map(landlock::WriteJail::new).transpose()?;
+    #[cfg(not(target_os = "linux"))]
+    if jail.is_some() {
+        tracing::warn!("Confining MCP server writes to a directory is only available on Linux");
+    }
+
+    #[cfg(target_os = "linux")]
+    let jailed = write_jail.is_some();
+    #[cfg(not(target_os = "linux"))]
+    let jailed = false;
+    if limits.iter().all(|(_, value)| value.is_none()) && !no_network && !jailed {
+        return Ok(());
+    }
+
+    // SAFETY: the closure runs in the forked child before exec and only
+    // makes async-signal-safe system calls, without allocating.
+    unsafe {
+        cmd.pre_exec(move || {
+            for (resource, value) in limits {
+                if let Some(value) = value {
+                    let limit = libc::rlimit {
+                        rlim_cur: value as libc::rlim_t,
+                        rlim_max: value as libc::rlim_t,
+                    };
+                    if libc::setrlimit(resource, &limit) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+            }
+            #[cfg(target_os = "linux")]
+            if no_network {
+                isolate_network()?;
+            }
+            #[cfg(target_os = "linux")]
+            if let Some(write_jail) = &write_jail {
+                write_jail.restrict_self()?;
+            }
+            Ok(())
+        });
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn apply_limits(
+    _cmd: &mut Command,
+    sandbox: &McpSandbox,
+    jail: Option<&std::path::Path>,
+) -> Result<(), McpError> {
+    if sandbox.cpu_seconds.is_some()
+        || sandbox.memory_mb.is_some()
+        || sandbox.max_open_files.is_some()
+        || sandbox.no_network
+        || jail.is_some()
+    {
+        tracing::warn!(
+            "Resource limits, network isolation and write confinement are not supported on this platform"
+        );
+    }
+    Ok(())
+}
+
+/// Move the calling process into a new, empty network namespace
+///
+/// Without `CAP_SYS_ADMIN` this needs an unprivileged user namespace; the
+/// launch fails rather than running the server with network access.
+#[cfg(target_os = "linux")]
+fn isolate_network() -> std::io::Result<()> {
+    // SAFETY: plain system calls on the child process's own namespaces
+    unsafe {
+        if libc::unshare(libc::CLONE_NEWNET) == 0
+            || libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) == 0
+        {
+            Ok(())
+        } else {
+            Err(std::io::Error::last_os_error())
+        }
+    }
+}
+
+/// Write confinement with Landlock (Linux 5.13+)
+///
+/// Only writes are restricted: files can be created, changed and removed
+/// inside the jail directory, and existing device files such as `/dev/null`
+/// can be written. Reading and executing files elsewhere is still allowed.
+#[cfg(target_os = "linux")]
+mod landlock {
+    use super::McpError;
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    const CREATE_RULESET_VERSION: libc::c_uint = 1;
+    const RULE_PATH_BENEATH: libc::c_int = 1;
+
+    const ACCESS_FS_WRITE_FILE: u64 = 1 << 1;
+    const ACCESS_FS_REMOVE_DIR: u64 = 1 << 4;
+    const ACCESS_FS_REMOVE_FILE: u64 = 1 << 5;
+    const ACCESS_FS_MAKE_CHAR: u64 = 1 << 6;
+    const ACCESS_FS_MAKE_DIR: u64 = 1 << 7;
+    const ACCESS_FS_MAKE_REG: u64 = 1 << 8;
+    const ACCESS_FS_MAKE_SOCK: u64 = 1 << 9;
+    const ACCESS_FS_MAKE_FIFO: u64 = 1 << 10;
+    const ACCESS_FS_MAKE_BLOCK: u64 = 1 << 11;
+    const ACCESS_FS_MAKE_SYM: u64 = 1 << 12;
+    /// Linking and renaming across directories (ABI 2)
+    const ACCESS_FS_REFER: u64 = 1 << 13;
+    /// Truncating files (ABI 3)
+    const ACCESS_FS_TRUNCATE: u64 = 1 << 14;
+
+    /// Write rights known to every Landlock version
+    const ACCESS_FS_WRITE: u64 = ACCESS_FS_WRITE_FILE
+        | ACCESS_FS_REMOVE_DIR
+        | ACCESS_FS_REMOVE_FILE
+        | ACCESS_FS_MAKE_CHAR
+        | ACCESS_FS_MAKE_DIR
+        | ACCESS_FS_MAKE_REG
+        | ACCESS_FS_MAKE_SOCK
+        | ACCESS_FS_MAKE_FIFO
+        | ACCESS_FS_MAKE_BLOCK
+        | ACCESS_FS_MAKE_SYM;
+
+    /// `struct landlock_ruleset_attr`, ABI 1 fields only
+    #[repr(C)]
+    struct RulesetAttr {
+        handled_access_fs: u64,
+    }
+
+    /// `struct landlock_path_beneath_attr`
+    #[repr(C, packed)]
+    struct PathBeneathAttr {
+        allowed_access: u64,
+        parent_fd: libc::c_int,
+    }
+
+    /// Paths and rights prepared before fork, so the child does not allocate
+    pub(super) struct WriteJail {
+        rules: Vec<(CString, u64)>,
+    }
+
+    impl WriteJail {
+        pub(super) fn new(dir: &Path) -> Result<Self, McpError> {
+            let dir = CString::new(dir.as_os_str().as_bytes()).map_err(|_| {
+                McpError::Connection(format!("Invalid sandbox directory {}", dir.display()))
+            })?;
+            let dev = CString::new("/dev").expect("no interior NUL");
+            Ok(Self {
+                rules: vec![
+                    (dir, u64::MAX),
+                    (dev, ACCESS_FS_WRITE_FILE | ACCESS_FS_TRUNCATE),
+                ],
+            })
+        }
+
+        /// Confine the calling process; fails when Landlock is unavailable
+        /// rather than leaving the server unconfined
+        pub(super) fn restrict_self(&self) -> std::io::Result<()> {
+            // SAFETY: Landlock system calls with pointers to live, correctly
+            // laid out structs; every descriptor opened here is closed.
+            unsafe {
+                let abi = libc::syscall(
+                    libc::SYS_landlock_create_ruleset,
+                    std::ptr::null::<RulesetAttr>(),
+                    0usize,
+                    CREATE_RULESET_VERSION,
+                );
+                if abi < 1 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                let mut handled = ACCESS_FS_WRITE;
+                if abi >= 2 {
+                    handled |= ACCESS_FS_REFER;
+                }
+                if abi >= 3 {
+                    handled |= ACCESS_FS_TRUNCATE;
+                }
+
+                let attr = RulesetAttr {
+                    handled_access_fs: handled,
+                };
+                let ruleset = libc::syscall(
+                    libc::SYS_landlock_create_ruleset,
+                    &attr as *const RulesetAttr,
+                    std::mem::size_of::<RulesetAttr>(),
+                    0u32,
+                ) as libc::c_int;
+                if ruleset < 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+
+                let result = self.add_rules(ruleset, handled).and_then(|_| {
+                    if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0
+                        || libc::syscall(libc::SYS_landlock_restrict_self, ruleset, 0u32) != 0
+                    {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+                libc::close(ruleset);
+                result
+            }
+        }
+
+        unsafe fn add_rules(&self, ruleset: libc::c_int, handled: u64) -> std::io::Result<()> {
+            for (path, access) in &self.rules {
+                let fd = libc::open(path.as_ptr(), libc::O_PATH | libc::O_CLOEXEC);
+                if fd < 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                let rule = PathBeneathAttr {
+                    allowed_access: access & handled,
+                    parent_fd: fd,
+                };
+                let added = libc::syscall(
+                    libc::SYS_landlock_add_rule,
+                    ruleset,
+                    RULE_PATH_BENEATH,
+                    &rule as *const PathBeneathAttr,
+                    0u32,
+                );
+                libc::close(fd);
+                if added != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        }
+    }
+}
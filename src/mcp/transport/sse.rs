@@ -0,0 +1,168 @@
+//! Legacy HTTP+SSE transport
+//!
+//! The client opens a GET event stream; the server's first `endpoint` event
+//! names the URL that client messages are POSTed to. Every server message
+//! arrives as a `message` event on the stream.
+
+use super::super::config::{McpServerConfig, McpTransportKind};
+use super::super::protocol::McpError;
+use super::event_stream::read_events;
+use super::{header_map, server_url, McpInbound, McpTransport};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use reqwest::header::{HeaderMap, ACCEPT, CONTENT_TYPE};
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+/// How long to wait for the `endpoint` event
+const ENDPOINT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Transport for servers speaking the HTTP+SSE protocol
+pub struct SseTransport {
+    client: reqwest::Client,
+    headers: HeaderMap,
+    /// URL messages are POSTed to
+    endpoint: reqwest::Url,
+    /// Cleared when the event stream ends
+    connected: Arc<AtomicBool>,
+    /// Event stream reader
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl SseTransport {
+    /// Open the event stream and wait for the message endpoint
+    pub async fn connect(config: &McpServerConfig) -> Result<(Self, McpInbound), McpError> {
+        let url = server_url(config)?;
+        let headers = header_map(config)?;
+        let client = reqwest::Client::new();
+
+        let response = client
+            .get(url.clone())
+            .headers(headers.clone())
+            .header(ACCEPT, "text/event-stream")
+            .send()
+            .await
+            .map_err(|e| McpError::Connection(format!("HTTP request failed: {}", e)))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(McpError::Connection(format!("HTTP {}: {}", status, body)));
+        }
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let (endpoint_tx, endpoint_rx) = oneshot::channel::<String>();
+        let connected = Arc::new(AtomicBool::new(true));
+
+        let connected_clone = connected.clone();
+        let task = tokio::spawn(async move {
+            let mut endpoint_tx = Some(endpoint_tx);
+            let result = read_events(response, |event| match event.event.as_str() {
+                "endpoint" => {
+                    if let Some(sender) = endpoint_tx.take() {
+                        let _ = sender.send(event.data);
+                    }
+                    true
+                }
+                "message" => match serde_json::from_str::<Value>(&event.data) {
+                    Ok(message) => tx.send(message).is_ok(),
+                    Err(e) => {
+                        tracing::warn!("Ignoring invalid MCP event: {}", e);
+                        true
+                    }
+                },
+                _ => true,
+            })
+            .await;
+            if let Err(e) = result {
+                tracing::warn!("MCP event stream failed: {}", e);
+            }
+            connected_clone.store(false, Ordering::SeqCst);
+        });
+
+        let endpoint = match tokio::time::timeout(ENDPOINT_TIMEOUT, endpoint_rx).await {
+            Ok(Ok(endpoint)) => endpoint,
+            Ok(Err(_)) => {
+                return Err(McpError::Connection(
+                    "Event stream closed before endpoint was sent".into(),
+                ))
+            }
+            Err(_) => {
+                task.abort();
+                return Err(McpError::Timeout);
+            }
+        };
+        let endpoint = url
+            .join(&endpoint)
+            .map_err(|e| McpError::Connection(format!("Invalid endpoint URL: {}", e)))?;
+
+        Ok((
+            Self {
+                client,
+                headers,
+                endpoint,
+                connected,
+                task: Mutex::new(Some(task)),
+            },
+            rx,
+        ))
+    }
+}
+
+#[async_trait]
+impl McpTransport for SseTransport {
+    fn kind(&self) -> McpTransportKind {
+        McpTransportKind::Sse
+    }
+
+    async fn send(&self, message: Value) -> Result<(), McpError> {
+        if !self.is_connected() {
+            return Err(McpError::Connection("Event stream closed".into()));
+        }
+
+        let response = self
+            .client
+            .post(self.endpoint.clone())
+            .headers(self.headers.clone())
+            .header(CONTENT_TYPE, "application/json")
+            .json(&message)
+            .send()
+            .await
+            .map_err(|e| McpError::Connection(format!("HTTP request failed: {}", e)))?;
+
+        let status = response.status();
+        if status == StatusCode::NOT_FOUND {
+            // The endpoint is tied to the stream's session
+            return Err(McpError::SessionExpired);
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(McpError::Connection(format!("HTTP {}: {}", status, body)));
+        }
+        Ok(())
+    }
+
+    async fn close(&self) -> Result<(), McpError> {
+        self.connected.store(false, Ordering::SeqCst);
+        if let Some(task) = self.task.lock().take() {
+            task.abort();
+        }
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for SseTransport {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.lock().take() {
+            task.abort();
+        }
+    }
+}
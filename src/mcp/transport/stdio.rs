@@ -0,0 +1,148 @@
+//! Stdio transport: newline-delimited JSON over a child process
+
+use super::super::config::{McpServerConfig, McpTransportKind};
+use super::super::protocol::McpError;
+use super::sandbox::{self, StderrLog};
+use super::{McpInbound, McpTransport};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::{mpsc, Mutex};
+
+/// Transport over a child process's stdin/stdout
+pub struct StdioTransport {
+    /// Server process
+    child: Mutex<Option<Child>>,
+    /// Process stdin
+    stdin: Mutex<Option<ChildStdin>>,
+    /// Cleared when stdout closes
+    connected: Arc<AtomicBool>,
+}
+
+impl StdioTransport {
+    /// Spawn the server process
+    pub fn spawn(config: &McpServerConfig) -> Result<(Self, McpInbound), McpError> {
+        Self::spawn_with_log(config, StderrLog::default())
+    }
+
+    /// Spawn the server process, keeping its stderr in `stderr`
+    pub fn spawn_with_log(
+        config: &McpServerConfig,
+        stderr: StderrLog,
+    ) -> Result<(Self, McpInbound), McpError> {
+        if config.command.is_empty() {
+            return Err(McpError::Connection("No command configured".into()));
+        }
+
+        let mut cmd = Command::new(&config.command);
+        cmd.args(&config.args);
+        if let Some(sandbox) = &config.sandbox {
+            sandbox::apply(&mut cmd, sandbox)?;
+        }
+        cmd.envs(&config.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| McpError::Connection(format!("Failed to spawn server: {}", e)))?;
+
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| McpError::Connection("Failed to get stdin".into()))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| McpError::Connection("Failed to get stdout".into()))?;
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let connected = Arc::new(AtomicBool::new(true));
+
+        // Reader task: one JSON-RPC message per line
+        let connected_clone = connected.clone();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stdout).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let line = line.trim();
+                if line.is_empty() {
+                    continue;
+                }
+                match serde_json::from_str::<Value>(line) {
+                    Ok(message) => {
+                        if tx.send(message).is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => tracing::warn!("Ignoring invalid MCP message: {}", e),
+                }
+            }
+            connected_clone.store(false, Ordering::SeqCst);
+        });
+
+        if let Some(output) = child.stderr.take() {
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(output).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    tracing::debug!("MCP server stderr: {}", line);
+                    stderr.push(line);
+                }
+            });
+        }
+
+        Ok((
+            Self {
+                child: Mutex::new(Some(child)),
+                stdin: Mutex::new(Some(stdin)),
+                connected,
+            },
+            rx,
+        ))
+    }
+}
+
+#[async_trait]
+impl McpTransport for StdioTransport {
+    fn kind(&self) -> McpTransportKind {
+        McpTransportKind::Stdio
+    }
+
+    async fn send(&self, message: Value) -> Result<(), McpError> {
+        let mut line = serde_json::to_string(&message)
+            .map_err(|e| McpError::Protocol(format!("Failed to serialize message: {}", e)))?;
+        line.push('\n');
+
+        let mut stdin = self.stdin.lock().await;
+        let stdin = stdin
+            .as_mut()
+            .ok_or_else(|| McpError::Connection("No stdin available".into()))?;
+        stdin
+            .write_all(line.as_bytes())
+            .await
+            .map_err(|e| McpError::Io(format!("Failed to write message: {}", e)))?;
+        stdin
+            .flush()
+            .await
+            .map_err(|e| McpError::Io(format!("Failed to flush: {}", e)))
+    }
+
+    async fn close(&self) -> Result<(), McpError> {
+        self.connected.store(false, Ordering::SeqCst);
+        // Closing stdin lets well-behaved servers exit on their own
+        self.stdin.lock().await.take();
+        if let Some(mut child) = self.child.lock().await.take() {
+            let _ = child.kill().await;
+        }
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,281 @@
+//! Streamable HTTP transport
+//!
+//! Every client message is POSTed to a single endpoint. The server answers
+//! with a JSON body, an SSE stream carrying the response (and any related
+//! messages), or 202 for notifications. A session id handed out on
+//! initialization is echoed on later requests. After initialization a GET
+//! stream is kept open for server-initiated messages, resuming with
+//! `Last-Event-ID` when it drops.
+
+use super::super::config::{McpServerConfig, McpTransportKind};
+use super::super::protocol::McpError;
+use super::event_stream::read_events;
+use super::{forward_json, header_map, server_url, McpInbound, McpTransport};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use reqwest::header::{HeaderMap, ACCEPT, CONTENT_TYPE};
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Session id header
+pub(crate) const SESSION_HEADER: &str = "mcp-session-id";
+
+/// Negotiated protocol version header
+pub(crate) const PROTOCOL_VERSION_HEADER: &str = "mcp-protocol-version";
+
+/// Maximum delay between GET stream reconnects
+const MAX_LISTEN_BACKOFF: Duration = Duration::from_secs(30);
+
+/// State shared with background tasks
+struct Shared {
+    client: reqwest::Client,
+    url: reqwest::Url,
+    headers: HeaderMap,
+    session_id: Mutex<Option<String>>,
+    protocol_version: Mutex<Option<String>>,
+    connected: AtomicBool,
+    inbound: mpsc::UnboundedSender<Value>,
+}
+
+impl Shared {
+    /// Configured headers plus the session id and protocol version
+    fn request_headers(&self) -> HeaderMap {
+        let mut headers = self.headers.clone();
+        if let Some(session) = self.session_id.lock().as_deref() {
+            if let Ok(value) = session.parse() {
+                headers.insert(SESSION_HEADER, value);
+            }
+        }
+        if let Some(version) = self.protocol_version.lock().as_deref() {
+            if let Ok(value) = version.parse() {
+                headers.insert(PROTOCOL_VERSION_HEADER, value);
+            }
+        }
+        headers
+    }
+
+    /// Forward SSE `message` events, returning the last event id seen
+    async fn forward_stream(
+        &self,
+        response: reqwest::Response,
+    ) -> (Result<(), McpError>, Option<String>) {
+        let mut last_id = None;
+        let result = read_events(response, |event| {
+            if event.id.is_some() {
+                last_id = event.id.clone();
+            }
+            if event.event == "message" {
+                match serde_json::from_str::<Value>(&event.data) {
+                    Ok(message) => {
+                        if self.inbound.send(message).is_err() {
+                            return false;
+                        }
+                    }
+                    Err(e) => tracing::warn!("Ignoring invalid MCP event: {}", e),
+                }
+            }
+            self.connected.load(Ordering::SeqCst)
+        })
+        .await;
+        (result, last_id)
+    }
+}
+
+/// Transport for servers speaking Streamable HTTP
+pub struct StreamableHttpTransport {
+    shared: Arc<Shared>,
+    /// Response streams and the GET listener
+    tasks: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl StreamableHttpTransport {
+    /// Create a transport (no request is made until the first message)
+    pub fn new(config: &McpServerConfig) -> Result<(Self, McpInbound), McpError> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let shared = Shared {
+            client: reqwest::Client::new(),
+            url: server_url(config)?,
+            headers: header_map(config)?,
+            session_id: Mutex::new(None),
+            protocol_version: Mutex::new(None),
+            connected: AtomicBool::new(true),
+            inbound: tx,
+        };
+        Ok((
+            Self {
+                shared: Arc::new(shared),
+                tasks: Mutex::new(Vec::new()),
+            },
+            rx,
+        ))
+    }
+
+    fn spawn(&self, task: impl std::future::Future<Output = ()> + Send + 'static) {
+        let mut tasks = self.tasks.lock();
+        tasks.retain(|t| !t.is_finished());
+        tasks.push(tokio::spawn(task));
+    }
+
+    /// Keep a GET stream open for server-initiated messages
+    fn start_listening(&self) {
+        let shared = self.shared.clone();
+        self.spawn(async move {
+            let mut last_event_id: Option<String> = None;
+            let mut backoff = Duration::from_millis(500);
+
+            while shared.connected.load(Ordering::SeqCst) {
+                let mut request = shared
+                    .client
+                    .get(shared.url.clone())
+                    .headers(shared.request_headers())
+                    .header(ACCEPT, "text/event-stream");
+                if let Some(id) = &last_event_id {
+                    request = request.header("last-event-id", id.as_str());
+                }
+
+                match request.send().await {
+                    Ok(response) if response.status() == StatusCode::METHOD_NOT_ALLOWED => {
+                        // Server does not offer a standalone stream
+                        return;
+                    }
+                    Ok(response) if response.status().is_success() => {
+                        backoff = Duration::from_millis(500);
+                        let (_, last_id) = shared.forward_stream(response).await;
+                        if last_id.is_some() {
+                            last_event_id = last_id;
+                        }
+                    }
+                    Ok(response) => {
+                        tracing::debug!("MCP listen stream refused: {}", response.status());
+                        if response.status() == StatusCode::NOT_FOUND {
+                            return;
+                        }
+                    }
+                    Err(e) => tracing::debug!("MCP listen stream failed: {}", e),
+                }
+
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_LISTEN_BACKOFF);
+            }
+        });
+    }
+}
+
+#[async_trait]
+impl McpTransport for StreamableHttpTransport {
+    fn kind(&self) -> McpTransportKind {
+        McpTransportKind::Http
+    }
+
+    async fn send(&self, message: Value) -> Result<(), McpError> {
+        if !self.is_connected() {
+            return Err(McpError::Connection("Transport closed".into()));
+        }
+
+        let had_session = self.shared.session_id.lock().is_some();
+        let response = self
+            .shared
+            .client
+            .post(self.shared.url.clone())
+            .headers(self.shared.request_headers())
+            .header(ACCEPT, "application/json, text/event-stream")
+            .header(CONTENT_TYPE, "application/json")
+            .json(&message)
+            .send()
+            .await
+            .map_err(|e| McpError::Connection(format!("HTTP request failed: {}", e)))?;
+
+        let status = response.status();
+        if status == StatusCode::NOT_FOUND && had_session {
+            // The server dropped our session; a new one starts with `initialize`
+            self.shared.session_id.lock().take();
+            self.shared.protocol_version.lock().take();
+            return Err(McpError::SessionExpired);
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(McpError::Connection(format!("HTTP {}: {}", status, body)));
+        }
+
+        if let Some(session) = response
+            .headers()
+            .get(SESSION_HEADER)
+            .and_then(|v| v.to_str().ok())
+        {
+            *self.shared.session_id.lock() = Some(session.to_string());
+        }
+
+        let is_stream = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("text/event-stream"));
+
+        if is_stream {
+            let shared = self.shared.clone();
+            self.spawn(async move {
+                if let (Err(e), _) = shared.forward_stream(response).await {
+                    tracing::warn!("MCP response stream failed: {}", e);
+                }
+            });
+        } else if status != StatusCode::ACCEPTED {
+            let body = response
+                .bytes()
+                .await
+                .map_err(|e| McpError::Connection(format!("Failed to read response: {}", e)))?;
+            forward_json(&body, &self.shared.inbound)?;
+        }
+
+        if message.get("method").and_then(|m| m.as_str()) == Some("notifications/initialized") {
+            self.start_listening();
+        }
+
+        Ok(())
+    }
+
+    async fn close(&self) -> Result<(), McpError> {
+        self.shared.connected.store(false, Ordering::SeqCst);
+        for task in self.tasks.lock().drain(..) {
+            task.abort();
+        }
+
+        // Explicitly end the session; servers may not support this
+        if self.shared.session_id.lock().is_some() {
+            let _ = self
+                .shared
+                .client
+                .delete(self.shared.url.clone())
+                .headers(self.shared.request_headers())
+                .send()
+                .await;
+        }
+        self.shared.session_id.lock().take();
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.shared.connected.load(Ordering::SeqCst)
+    }
+
+    fn session_id(&self) -> Option<String> {
+        self.shared.session_id.lock().clone()
+    }
+
+    fn set_protocol_version(&self, version: &str) {
+        *self.shared.protocol_version.lock() = Some(version.to_string());
+    }
+}
+
+impl Drop for StreamableHttpTransport {
+    fn drop(&mut self) {
+        self.shared.connected.store(false, Ordering::SeqCst);
+        for task in self.tasks.lock().drain(..) {
+            task.abort();
+        }
+    }
+}
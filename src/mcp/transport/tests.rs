@@ -0,0 +1,270 @@
+//! Tests for MCP transports against an in-process server
+
+use super::super::client::McpClient;
+use super::super::config::{McpSandbox, McpServerConfig, McpTransportKind};
+use super::super::protocol::{ToolContent, MCP_PROTOCOL_VERSION};
+use super::mock::{MockMcpServer, MockMode};
+use super::{connect, forward_json, McpTransport, StderrLog, StdioTransport};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+fn echo_args(text: &str) -> Option<HashMap<String, Value>> {
+    Some(HashMap::from([("text".to_string(), json!(text))]))
+}
+
+fn text_of(content: &[ToolContent]) -> &str {
+    match content.first() {
+        Some(ToolContent::Text { text }) => text,
+        other => panic!("unexpected content: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_streamable_http_json_responses() {
+    let server = MockMcpServer::start(MockMode::Json).await;
+    let mut client = McpClient::new("mock", McpServerConfig::http(&server.url));
+
+    client.start().await.unwrap();
+    client.initialize().await.unwrap();
+    assert_eq!(client.session_id().as_deref(), Some("session-1"));
+    assert_eq!(client.tools().len(), 1);
+
+    let result = client.call_tool("echo", echo_args("hello")).await.unwrap();
+    assert_eq!(text_of(&result.content), "hello");
+
+    let heads = server.heads();
+    let posts: Vec<_> = heads.iter().filter(|h| h.starts_with("post")).collect();
+    assert!(!posts[0].contains("mcp-session-id"));
+    assert!(posts[1..]
+        .iter()
+        .all(|h| h.contains("mcp-session-id: session-1")));
+    assert!(posts[0].contains("accept: application/json, text/event-stream"));
+
+    // Negotiated version is echoed after initialize
+    assert_eq!(client.protocol_version(), Some(MCP_PROTOCOL_VERSION));
+    assert!(!posts[0].contains("mcp-protocol-version"));
+    assert!(posts[1..]
+        .iter()
+        .all(|h| h.contains(&format!("mcp-protocol-version: {}", MCP_PROTOCOL_VERSION))));
+
+    client.stop().await.unwrap();
+    assert!(server.heads().iter().any(|h| h.starts_with("delete")));
+}
+
+#[tokio::test]
+async fn test_streamable_http_event_stream_responses() {
+    let server = MockMcpServer::start(MockMode::EventStream).await;
+    let mut client = McpClient::new("mock", McpServerConfig::http(&server.url));
+
+    client.start().await.unwrap();
+    client.initialize().await.unwrap();
+
+    // Each response stream carries a progress notification before the result
+    let result = client
+        .call_tool("echo", echo_args("streamed"))
+        .await
+        .unwrap();
+    assert_eq!(text_of(&result.content), "streamed");
+}
+
+#[tokio::test]
+async fn test_streamable_http_reconnects_after_session_expiry() {
+    let server = MockMcpServer::start(MockMode::Json).await;
+    let mut client = McpClient::new("mock", McpServerConfig::http(&server.url));
+
+    client.start().await.unwrap();
+    client.initialize().await.unwrap();
+    server.expire_session();
+
+    let result = client.call_tool("echo", echo_args("again")).await.unwrap();
+    assert_eq!(text_of(&result.content), "again");
+    assert_eq!(server.sessions_created(), 2);
+    assert_eq!(client.session_id().as_deref(), Some("session-2"));
+    assert_eq!(
+        server
+            .methods()
+            .iter()
+            .filter(|m| *m == "initialize")
+            .count(),
+        2
+    );
+}
+
+#[tokio::test]
+async fn test_streamable_http_custom_headers() {
+    let server = MockMcpServer::start(MockMode::Json).await;
+    let config = McpServerConfig::http(&server.url).with_header("Authorization", "Bearer secret");
+    let mut client = McpClient::new("mock", config);
+
+    client.start().await.unwrap();
+    client.initialize().await.unwrap();
+    assert!(server
+        .heads()
+        .iter()
+        .all(|h| h.contains("authorization: bearer secret")));
+}
+
+#[tokio::test]
+async fn test_legacy_sse_transport() {
+    let server = MockMcpServer::start(MockMode::LegacySse).await;
+    let config = McpServerConfig::sse(&server.url);
+    let mut client = McpClient::new("mock", config);
+
+    client.start().await.unwrap();
+    client.initialize().await.unwrap();
+    assert_eq!(client.tools()[0].name, "echo");
+
+    let result = client.call_tool("echo", echo_args("legacy")).await.unwrap();
+    assert_eq!(text_of(&result.content), "legacy");
+    assert!(server
+        .heads()
+        .iter()
+        .any(|h| h.starts_with("post /messages?session=1")));
+}
+
+#[tokio::test]
+async fn test_connect_selects_transport() {
+    let server = MockMcpServer::start(MockMode::Json).await;
+    let config = McpServerConfig {
+        url: Some(server.url.clone()),
+        ..McpServerConfig::new("")
+    };
+    assert_eq!(config.transport_kind(), McpTransportKind::Http);
+
+    let (transport, _inbound) = connect(&config).await.unwrap();
+    assert_eq!(transport.kind(), McpTransportKind::Http);
+    assert!(transport.is_connected());
+
+    assert!(connect(&McpServerConfig::http("not a url")).await.is_err());
+}
+
+#[test]
+fn test_forward_json_batch() {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    forward_json(br#"[{"id":1},{"id":2}]"#, &tx).unwrap();
+    forward_json(b"  ", &tx).unwrap();
+    assert_eq!(rx.try_recv().unwrap()["id"], 1);
+    assert_eq!(rx.try_recv().unwrap()["id"], 2);
+    assert!(rx.try_recv().is_err());
+    assert!(forward_json(b"{", &tx).is_err());
+}
+
+#[test]
+fn test_stderr_log_ring_buffer() {
+    let log = StderrLog::new(3);
+    for i in 0..5 {
+        log.push(format!("line {}", i));
+    }
+    assert_eq!(log.lines(), vec!["line 2", "line 3", "line 4"]);
+    assert_eq!(log.tail(2), vec!["line 3", "line 4"]);
+    assert_eq!(log.tail(10).len(), 3);
+
+    log.push("é".repeat(5000));
+    let long = log.tail(1).remove(0);
+    assert!(long.len() <= 4096 + '…'.len_utf8());
+    assert!(long.ends_with('…'));
+
+    log.clear();
+    assert!(log.is_empty());
+}
+
+/// Run a shell script as a stdio server and collect its stderr
+#[cfg(unix)]
+async fn stderr_of(config: McpServerConfig) -> Vec<String> {
+    let log = StderrLog::default();
+    let (transport, _inbound) = StdioTransport::spawn_with_log(&config, log.clone()).unwrap();
+
+    // The scripts end with a marker line once everything is written
+    for _ in 0..200 {
+        if log.lines().iter().any(|l| l == "done") {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    transport.close().await.unwrap();
+    log.lines()
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_stdio_captures_stderr() {
+    let config = McpServerConfig::new("sh").with_args(["-c", "echo starting >&2; echo done >&2"]);
+    assert_eq!(stderr_of(config).await, vec!["starting", "done"]);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_stdio_sandbox_environment_and_limits() {
+    let dir = std::env::temp_dir().join(format!("cv-sandbox-{}", uuid::Uuid::new_v4()));
+    let script = "echo \"path=${PATH:+set}\" >&2; \
+                  echo \"cargo=${CARGO_MANIFEST_DIR:+set}\" >&2; \
+                  echo \"token=$TOKEN\" >&2; \
+                  echo \"pwd=$(pwd)\" >&2; \
+                  echo \"home=$HOME\" >&2; \
+                  echo \"files=$(ulimit -n)\" >&2; \
+                  echo done >&2";
+
+    let sandbox = McpSandbox::default()
+        .with_working_dir(&dir)
+        .with_max_open_files(64)
+        .with_cpu_seconds(30);
+    let config = McpServerConfig::new("sh")
+        .with_args(["-c", script])
+        .with_env("TOKEN", "abc")
+        .with_sandbox(sandbox.clone());
+    let lines = stderr_of(config).await;
+
+    let dir = dir.canonicalize().unwrap();
+    assert!(lines.contains(&"path=set".to_string()));
+    assert!(lines.contains(&"cargo=".to_string()));
+    assert!(lines.contains(&"token=abc".to_string()));
+    assert!(lines.contains(&format!("pwd={}", dir.display())));
+    assert!(
+        lines
+            .iter()
+            .any(|l| l.starts_with("home=")
+                && l.ends_with(dir.file_name().unwrap().to_str().unwrap()))
+    );
+    assert!(lines.contains(&"files=64".to_string()));
+
+    // Allow-listed variables pass through
+    if std::env::var_os("CARGO_MANIFEST_DIR").is_some() {
+        let config = McpServerConfig::new("sh")
+            .with_args(["-c", script])
+            .with_sandbox(sandbox.inherit("CARGO_*"));
+        assert!(stderr_of(config).await.contains(&"cargo=set".to_string()));
+    }
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_stdio_sandbox_confines_writes() {
+    let dir = std::env::temp_dir().join(format!("cv-sandbox-{}", uuid::Uuid::new_v4()));
+    let outside = std::env::temp_dir().join(format!("cv-outside-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&outside).unwrap();
+    let script = format!(
+        "echo inside > inside.txt && echo \"inside=ok\" >&2; \
+         echo outside > {}/outside.txt 2>/dev/null || echo \"outside=denied\" >&2; \
+         cat /etc/passwd >/dev/null && echo \"read=ok\" >&2; \
+         echo done >&2",
+        outside.display()
+    );
+
+    let config = McpServerConfig::new("sh")
+        .with_args(["-c", script.as_str()])
+        .with_sandbox(McpSandbox::default().with_working_dir(&dir));
+    let lines = stderr_of(config).await;
+
+    assert!(lines.contains(&"inside=ok".to_string()));
+    assert!(lines.contains(&"outside=denied".to_string()));
+    assert!(lines.contains(&"read=ok".to_string()));
+    assert!(dir.join("inside.txt").exists());
+    assert!(!outside.join("outside.txt").exists());
+
+    let _ = std::fs::remove_dir_all(dir);
+    let _ = std::fs::remove_dir_all(outside);
+}
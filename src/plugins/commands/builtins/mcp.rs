@@ -101,9 +101,11 @@ pub(crate) fn register_mcp_commands(register: impl Fn(SlashCommand, CommandHandl
             name: "mcp-tool".to_string(),
             description: "Execute an MCP tool".to_string(),
             help: Some(
-                "Usage: /mcp-tool <server> <tool> [args_json]\n\n\
-                 Execute a tool from an MCP server.\n\n\
+                "Usage: /mcp-tool <server> <tool> [key=value ... | args_json]\n\n\
+                 Execute a tool from an MCP server. Arguments are checked against\n\
+                 the tool's input schema; values are converted to the expected types.\n\n\
                  Examples:\n\
+                   /mcp-tool filesystem read_file path=/tmp/test.txt\n\
                    /mcp-tool filesystem read_file {\"path\": \"/tmp/test.txt\"}\n\
                    /mcp-tool github list_repos"
                     .to_string(),
@@ -126,7 +128,7 @@ pub(crate) fn register_mcp_commands(register: impl Fn(SlashCommand, CommandHandl
                 CommandArg {
                     name: "args".to_string(),
                     required: false,
-                    description: Some("JSON arguments for the tool".to_string()),
+                    description: Some("key=value pairs or JSON arguments for the tool".to_string()),
                     completions: None,
                 },
             ],
@@ -135,7 +137,7 @@ pub(crate) fn register_mcp_commands(register: impl Fn(SlashCommand, CommandHandl
             let parts: Vec<&str> = args.splitn(3, char::is_whitespace).collect();
             if parts.len() < 2 {
                 return CommandResult::Error(
-                    "Usage: /mcp-tool <server> <tool> [args_json]".to_string(),
+                    "Usage: /mcp-tool <server> <tool> [key=value ... | args_json]".to_string(),
                 );
             }
             let server = parts[0].to_string();
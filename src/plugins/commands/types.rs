@@ -45,7 +45,10 @@ pub enum CommandResult {
     Error(String),
     /// No visible output
     Silent,
-    /// MCP tool call request (server, tool, args_json)
+    /// MCP tool call request (server, tool, raw arguments)
+    ///
+    /// Arguments are `key=value` pairs or a JSON object; parse them against
+    /// the tool's schema with [`crate::mcp::parse_arguments`].
     McpToolCall {
         server: String,
         tool: String,
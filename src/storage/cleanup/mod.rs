@@ -4,12 +4,14 @@
 //! to manage disk space and improve performance.
 
 mod job;
+mod retention;
 mod scheduler;
 mod types;
 mod utils;
 
 // Re-export public types
 pub use job::CleanupJob;
+pub use retention::{RetentionPaths, RetentionReport, RetentionService};
 pub use scheduler::CleanupScheduler;
 pub use types::{CleanupConfig, CleanupItem, CleanupStats, CleanupTarget};
 pub use utils::{get_available_space_mb, needs_disk_space_cleanup};
@@ -87,4 +89,132 @@ mod tests {
         scheduler.complete_cleanup();
         assert!(scheduler.last_cleanup().is_some());
     }
+
+    #[test]
+    fn test_default_retention_paths_leave_updates_alone() {
+        let paths = RetentionPaths::default_dirs();
+        let Some(cache_dir) = dirs::cache_dir() else {
+            return;
+        };
+        let updates = cache_dir.join("claude-visual").join("updates");
+
+        assert!(!paths.caches.is_empty());
+        for dir in &paths.caches {
+            assert!(!updates.starts_with(dir), "{} holds updates", dir.display());
+        }
+    }
+
+    #[test]
+    fn test_retention_protects_and_deletes_in_one_run() {
+        use crate::project::manager::Project;
+        use crate::storage::database::Database;
+        use crate::storage::models::{Conversation, Message};
+        use std::path::{Path, PathBuf};
+        use std::sync::Arc;
+
+        let database = Arc::new(Database::open_in_memory().unwrap());
+        database.initialize().unwrap();
+
+        let old_time = Utc::now() - Duration::days(200);
+        let conversation = |title: &str, project_id: Option<String>, old: bool| {
+            let mut conversation = Conversation::new(title, project_id);
+            let mut message = Message::user(&conversation.id, title);
+            if old {
+                conversation.updated_at = old_time;
+                message.timestamp = old_time;
+            }
+            database.insert_conversation(&conversation).unwrap();
+            database.insert_message(&message).unwrap();
+            (conversation.id, message.id)
+        };
+        let mut favorite = Project::new("favorite", PathBuf::from("/tmp/retention-favorite"));
+        favorite.is_favorite = true;
+        database.insert_project(&favorite).unwrap();
+
+        let (stale, _) = conversation("stale", None, true);
+        let (bookmarked, bookmarked_message) = conversation("bookmarked", None, true);
+        database.set_bookmark(&bookmarked_message, None).unwrap();
+        let (in_favorite, _) = conversation("favorite", Some(favorite.id.clone()), true);
+        let (recent, _) = conversation("recent", None, false);
+
+        let root =
+            std::env::temp_dir().join(format!("claude_visual_retention_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let write = |path: PathBuf, age_days: i64| {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, b"data").unwrap();
+            let modified = std::time::SystemTime::now()
+                - std::time::Duration::from_secs(age_days as u64 * 86_400);
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+            path
+        };
+        let attachments = root.join("attachments");
+        let stale_file = write(attachments.join(&stale).join("a.png"), 200);
+        let orphan_file = write(attachments.join("gone").join("b.png"), 1);
+        let kept_file = write(attachments.join(&recent).join("c.png"), 1);
+        let bookmarked_file = write(attachments.join(&bookmarked).join("d.png"), 200);
+        let old_cache = write(root.join("cache").join("web").join("old.json"), 30);
+        let new_cache = write(root.join("cache").join("web").join("new.json"), 1);
+
+        let mut service = RetentionService::new(database.clone(), CleanupConfig::default())
+            .with_paths(RetentionPaths {
+                attachments: Some(attachments.clone()),
+                caches: vec![root.join("cache")],
+                ..Default::default()
+            });
+        let config = CleanupConfig::default();
+
+        let exists = |path: &Path| path.exists();
+        let stored = |id: &String| {
+            database
+                .get_conversations(None)
+                .unwrap()
+                .iter()
+                .any(|c| &c.id == id)
+        };
+        let report = service.dry_run(&config).unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.count(CleanupTarget::Conversations), 1);
+        assert_eq!(report.count(CleanupTarget::Attachments), 2);
+        assert_eq!(report.count(CleanupTarget::Cache), 1);
+        assert_eq!(report.protected.len(), 3);
+        assert_eq!(
+            report.summary(),
+            "Would delete 1 conversations, 2 attachments, 1 cache files (17 B); 3 protected"
+        );
+        assert!(exists(&stale_file) && exists(&orphan_file) && exists(&old_cache));
+        assert!(stored(&stale));
+
+        let report = service.run(&config).unwrap();
+        assert!(!report.dry_run);
+        assert_eq!(report.stats.items_cleaned, 4);
+        assert_eq!(report.stats.errors, 0);
+        assert!(report.database_bytes.is_some());
+        assert!(!stored(&stale));
+        for id in [&bookmarked, &in_favorite, &recent] {
+            assert!(stored(id));
+        }
+        assert!(!exists(&stale_file) && !exists(stale_file.parent().unwrap()));
+        assert!(!exists(&orphan_file) && !exists(&old_cache));
+        assert!(exists(&kept_file) && exists(&bookmarked_file) && exists(&new_cache));
+        assert!(service.run_if_due().unwrap().is_none());
+
+        // Past the conversation limit, age does not matter
+        let limited = CleanupConfig {
+            max_conversations: 0,
+            ..Default::default()
+        };
+        let report = service.run(&limited).unwrap();
+        assert_eq!(report.count(CleanupTarget::Conversations), 1);
+        assert_eq!(report.count(CleanupTarget::Attachments), 1);
+        assert!(!stored(&recent));
+        assert!(stored(&bookmarked));
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
 }
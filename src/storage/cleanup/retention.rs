@@ -0,0 +1,398 @@
+//! Retention policies over the database and the app's files
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+
+use crate::ai::web::WebCache;
+use crate::storage::database::{Database, EncryptionStatus};
+
+use super::job::CleanupJob;
+use super::scheduler::CleanupScheduler;
+use super::types::{CleanupConfig, CleanupItem, CleanupStats, CleanupTarget};
+use super::utils::needs_disk_space_cleanup;
+
+/// How often the background thread checks whether cleanup is due
+const CHECK_INTERVAL: StdDuration = StdDuration::from_secs(15 * 60);
+
+/// Directories holding files retention may delete
+#[derive(Debug, Clone, Default)]
+pub struct RetentionPaths {
+    /// Directory of the database, checked for free space
+    pub data_dir: Option<PathBuf>,
+    /// Attachments, in one folder per conversation named by its ID
+    pub attachments: Option<PathBuf>,
+    /// Cache directories, whose files may all be deleted
+    pub caches: Vec<PathBuf>,
+    /// Log directories
+    pub logs: Vec<PathBuf>,
+    /// Temporary file directories
+    pub temporary: Vec<PathBuf>,
+}
+
+impl RetentionPaths {
+    /// The app's own directories
+    pub fn default_dirs() -> Self {
+        let data_dir = dirs::data_dir().map(|d| d.join("claude-visual"));
+        Self {
+            attachments: data_dir.as_ref().map(|d| d.join("attachments")),
+            data_dir,
+            // Only disposable caches: `<cache>/claude-visual` also holds
+            // downloaded updates
+            caches: WebCache::default_dir().into_iter().collect(),
+            logs: dirs::data_local_dir()
+                .map(|d| d.join("claude-visual").join("logs"))
+                .into_iter()
+                .collect(),
+            temporary: vec![std::env::temp_dir().join("claude-visual")],
+        }
+    }
+}
+
+/// Outcome of a cleanup run, or what a dry run would do
+#[derive(Debug, Clone)]
+pub struct RetentionReport {
+    /// Nothing was deleted
+    pub dry_run: bool,
+    /// Run because free disk space was low, with the aggressive policy
+    pub low_disk_space: bool,
+    /// Items deleted, or that would be
+    pub cleaned: Vec<CleanupItem>,
+    /// Items the policy would delete but are protected
+    pub protected: Vec<CleanupItem>,
+    /// Job statistics
+    pub stats: CleanupStats,
+    /// Database file size before and after optimizing, if it ran
+    pub database_bytes: Option<(u64, u64)>,
+}
+
+impl RetentionReport {
+    /// Number of cleaned items of a kind
+    pub fn count(&self, target: CleanupTarget) -> usize {
+        self.cleaned.iter().filter(|i| i.target == target).count()
+    }
+
+    /// One-line summary, e.g. "Deleted 3 conversations, 2 cache files (1.5 MB)"
+    pub fn summary(&self) -> String {
+        let targets = [
+            CleanupTarget::Conversations,
+            CleanupTarget::Attachments,
+            CleanupTarget::Cache,
+            CleanupTarget::Logs,
+            CleanupTarget::Temporary,
+        ];
+        let parts: Vec<String> = targets
+            .iter()
+            .map(|&target| (target, self.count(target)))
+            .filter(|&(_, count)| count > 0)
+            .map(|(target, count)| format!("{} {}", count, target.label()))
+            .collect();
+
+        let verb = if self.dry_run {
+            "Would delete"
+        } else {
+            "Deleted"
+        };
+        let mut summary = if parts.is_empty() {
+            "Nothing to clean up".to_string()
+        } else {
+            format!(
+                "{} {} ({})",
+                verb,
+                parts.join(", "),
+                self.stats.format_space_freed()
+            )
+        };
+        if !self.protected.is_empty() {
+            summary.push_str(&format!("; {} protected", self.protected.len()));
+        }
+        summary
+    }
+}
+
+/// Applies a [`CleanupConfig`] to stored conversations and the app's files
+///
+/// Conversations in favorite projects or with bookmarked or pinned
+/// messages are never deleted, and neither are the attachments of
+/// conversations that are kept, unless they are older than the policy
+/// allows. Attachments of deleted conversations are deleted with them.
+pub struct RetentionService {
+    database: Arc<Database>,
+    paths: RetentionPaths,
+    config: CleanupConfig,
+    scheduler: CleanupScheduler,
+}
+
+impl RetentionService {
+    /// Create a service applying `config`
+    pub fn new(database: Arc<Database>, config: CleanupConfig) -> Self {
+        Self {
+            database,
+            paths: RetentionPaths::default(),
+            scheduler: CleanupScheduler::new(config.clone()),
+            config,
+        }
+    }
+
+    /// Clean up files in `paths` too
+    pub fn with_paths(mut self, paths: RetentionPaths) -> Self {
+        self.paths = paths;
+        self
+    }
+
+    /// Policy applied on schedule
+    pub fn config(&self) -> &CleanupConfig {
+        &self.config
+    }
+
+    /// Everything the policy covers, protected items included
+    pub fn scan(&self, config: &CleanupConfig) -> Result<Vec<CleanupItem>> {
+        let mut items = self.database.conversation_cleanup_items()?;
+
+        // Beyond the newest conversations allowed, age does not matter
+        let mut kept = 0;
+        for item in items.iter_mut().filter(|i| !i.is_protected) {
+            if kept >= config.max_conversations {
+                item.is_expired = true;
+            } else if !item.should_cleanup(config) {
+                kept += 1;
+            }
+        }
+
+        let remaining: HashSet<&str> = items
+            .iter()
+            .filter(|i| !i.should_cleanup(config))
+            .map(|i| i.id.as_str())
+            .collect();
+        let protected: HashSet<&str> = items
+            .iter()
+            .filter(|i| i.is_protected)
+            .map(|i| i.id.as_str())
+            .collect();
+        let mut files = Vec::new();
+        if let Some(dir) = &self.paths.attachments {
+            files.extend(scan_attachments(dir, &remaining, &protected));
+        }
+        for (target, dirs) in [
+            (CleanupTarget::Cache, &self.paths.caches),
+            (CleanupTarget::Logs, &self.paths.logs),
+            (CleanupTarget::Temporary, &self.paths.temporary),
+        ] {
+            for dir in dirs {
+                for (path, size, modified) in list_files(dir) {
+                    files.push(CleanupItem::file(target, path, size, modified));
+                }
+            }
+        }
+
+        items.extend(files);
+        Ok(items)
+    }
+
+    /// Report what running `config` would delete, without deleting
+    pub fn dry_run(&self, config: &CleanupConfig) -> Result<RetentionReport> {
+        self.run_with(config, true)
+    }
+
+    /// Apply `config` now, then optimize the database
+    pub fn run(&mut self, config: &CleanupConfig) -> Result<RetentionReport> {
+        let report = self.run_with(config, false)?;
+        self.scheduler.complete_cleanup();
+        Ok(report)
+    }
+
+    /// Clean up if the schedule says so, or with the aggressive policy if
+    /// free disk space is below the configured minimum
+    ///
+    /// Nothing runs while an encrypted database is locked; cleanup that is
+    /// due then runs on the first check after it is unlocked.
+    pub fn run_if_due(&mut self) -> Result<Option<RetentionReport>> {
+        if self.database.encryption_status() == EncryptionStatus::Locked {
+            return Ok(None);
+        }
+        let low_disk_space = self.paths.data_dir.as_deref().is_some_and(|dir| {
+            self.config.auto_cleanup_enabled
+                && needs_disk_space_cleanup(dir, self.config.min_free_space_mb)
+        });
+        if low_disk_space {
+            let mut report = self.run(&CleanupConfig::aggressive())?;
+            report.low_disk_space = true;
+            return Ok(Some(report));
+        }
+        if self.scheduler.is_cleanup_due() {
+            let config = self.config.clone();
+            return self.run(&config).map(Some);
+        }
+        Ok(None)
+    }
+
+    /// Check on a background thread, for as long as the app runs
+    pub fn spawn(mut self) {
+        let spawned = std::thread::Builder::new()
+            .name("retention".into())
+            .spawn(move || loop {
+                match self.run_if_due() {
+                    Ok(Some(report)) => tracing::info!("Cleanup: {}", report.summary()),
+                    Ok(None) => {}
+                    Err(e) => {
+                        tracing::error!("Cleanup failed: {}", e);
+                        // Try again on the next schedule, not every check
+                        self.scheduler.complete_cleanup();
+                    }
+                }
+                std::thread::sleep(CHECK_INTERVAL);
+            });
+
+        if let Err(e) = spawned {
+            tracing::warn!("Failed to spawn retention thread: {}", e);
+        }
+    }
+
+    fn run_with(&self, config: &CleanupConfig, dry_run: bool) -> Result<RetentionReport> {
+        let items = self.scan(config)?;
+        let protected = items
+            .iter()
+            .filter(|i| i.is_protected && due_unless_protected(i, config))
+            .cloned()
+            .collect();
+
+        let mut job = CleanupJob::new(config.clone());
+        if dry_run {
+            job = job.dry_run();
+        }
+        job.add_items(items);
+
+        // Conversations go in one transaction before any of their files
+        let due: Vec<String> = job
+            .preview()
+            .into_iter()
+            .filter(|i| i.target == CleanupTarget::Conversations)
+            .map(|i| i.id.clone())
+            .collect();
+        let deleted = if dry_run {
+            HashSet::new()
+        } else {
+            self.database.delete_unprotected_conversations(&due)?
+        };
+
+        let mut cleaned = Vec::new();
+        let stats = job
+            .execute(|item| {
+                let result = match item.target {
+                    CleanupTarget::Conversations if deleted.contains(&item.id) => Ok(()),
+                    CleanupTarget::Conversations => Err("protected since the scan".to_string()),
+                    _ => delete_file(item),
+                };
+                if result.is_ok() {
+                    cleaned.push(item.clone());
+                }
+                result
+            })
+            .clone();
+        if dry_run {
+            cleaned = job.preview().into_iter().cloned().collect();
+        }
+
+        let database_bytes = if dry_run || cleaned.is_empty() {
+            None
+        } else {
+            let before = self.database.database_size()?;
+            self.database.optimize_storage()?;
+            Some((before, self.database.database_size()?))
+        };
+
+        Ok(RetentionReport {
+            dry_run,
+            low_disk_space: false,
+            cleaned,
+            protected,
+            stats,
+            database_bytes,
+        })
+    }
+}
+
+/// Whether the policy would delete `item` if it were not protected
+fn due_unless_protected(item: &CleanupItem, config: &CleanupConfig) -> bool {
+    CleanupItem {
+        is_protected: false,
+        ..item.clone()
+    }
+    .should_cleanup(config)
+}
+
+/// Attachment files, each in a folder named by its conversation's ID
+///
+/// Files of conversations that are not kept are due whatever their age;
+/// those of protected conversations are protected.
+fn scan_attachments(
+    dir: &Path,
+    remaining: &HashSet<&str>,
+    protected: &HashSet<&str>,
+) -> Vec<CleanupItem> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut items = Vec::new();
+    for entry in entries.flatten() {
+        let conversation_id = entry.file_name().to_string_lossy().to_string();
+        for (path, size, modified) in list_files(&entry.path()) {
+            let item =
+                CleanupItem::attachment(path.to_string_lossy(), path.clone(), size, modified);
+            items.push(if protected.contains(conversation_id.as_str()) {
+                item.protect()
+            } else if !remaining.contains(conversation_id.as_str()) {
+                item.expire()
+            } else {
+                item
+            });
+        }
+    }
+    items
+}
+
+/// Files under `dir` with their size and modification time
+fn list_files(dir: &Path) -> Vec<(PathBuf, u64, DateTime<Utc>)> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else if metadata.is_file() {
+                let modified = metadata
+                    .modified()
+                    .map(DateTime::<Utc>::from)
+                    .unwrap_or_else(|_| Utc::now());
+                files.push((entry.path(), metadata.len(), modified));
+            }
+        }
+    }
+    files
+}
+
+/// Delete a file item, and its conversation's folder once empty
+fn delete_file(item: &CleanupItem) -> Result<(), String> {
+    let Some(path) = &item.path else {
+        return Err("no path".to_string());
+    };
+    std::fs::remove_file(path).map_err(|e| e.to_string())?;
+    if item.target == CleanupTarget::Attachments {
+        if let Some(parent) = path.parent() {
+            // Fails, as intended, while other files remain
+            let _ = std::fs::remove_dir(parent);
+        }
+    }
+    Ok(())
+}
@@ -6,6 +6,7 @@ use std::path::PathBuf;
 
 /// Cleanup configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CleanupConfig {
     /// Maximum age for inactive conversations (days)
     pub max_conversation_age_days: u32,
@@ -87,6 +88,20 @@ pub enum CleanupTarget {
     Temporary,
 }
 
+impl CleanupTarget {
+    /// Plural name for reports
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Conversations => "conversations",
+            Self::Messages => "messages",
+            Self::Attachments => "attachments",
+            Self::Cache => "cache files",
+            Self::Logs => "log files",
+            Self::Temporary => "temporary files",
+        }
+    }
+}
+
 /// Item to be cleaned up
 #[derive(Debug, Clone)]
 pub struct CleanupItem {
@@ -104,6 +119,8 @@ pub struct CleanupItem {
     pub path: Option<PathBuf>,
     /// Whether this item is protected
     pub is_protected: bool,
+    /// Whether a limit other than age already applies, e.g. an orphan
+    pub is_expired: bool,
 }
 
 impl CleanupItem {
@@ -122,6 +139,7 @@ impl CleanupItem {
             last_accessed,
             path: None,
             is_protected: false,
+            is_expired: false,
         }
     }
 
@@ -145,6 +163,31 @@ impl CleanupItem {
             last_accessed,
             path: Some(path),
             is_protected: false,
+            is_expired: false,
+        }
+    }
+
+    /// Create a cache, log or temporary file cleanup item
+    pub fn file(
+        target: CleanupTarget,
+        path: PathBuf,
+        size: u64,
+        last_accessed: DateTime<Utc>,
+    ) -> Self {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        Self {
+            id: path.to_string_lossy().to_string(),
+            target,
+            name,
+            size_bytes: size,
+            last_accessed,
+            path: Some(path),
+            is_protected: false,
+            is_expired: false,
         }
     }
 
@@ -154,11 +197,20 @@ impl CleanupItem {
         self
     }
 
+    /// Mark for cleanup whatever its age
+    pub fn expire(mut self) -> Self {
+        self.is_expired = true;
+        self
+    }
+
     /// Check if item should be cleaned up based on config
     pub fn should_cleanup(&self, config: &CleanupConfig) -> bool {
         if self.is_protected {
             return false;
         }
+        if self.is_expired {
+            return true;
+        }
 
         let now = Utc::now();
         let age = now.signed_duration_since(self.last_accessed);
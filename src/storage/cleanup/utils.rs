@@ -2,18 +2,26 @@
 
 /// Check available disk space
 pub fn get_available_space_mb(path: &std::path::Path) -> Result<u64, std::io::Error> {
-    // This is a simplified implementation
-    // In production, you'd use platform-specific APIs
     #[cfg(unix)]
     {
-        use std::os::unix::fs::MetadataExt;
-        let metadata = std::fs::metadata(path)?;
-        // This is a rough estimate
-        Ok(metadata.blksize() / 1_000_000)
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = std::ffi::CString::new(path.as_os_str().as_bytes())
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        let mut stats: libc::statvfs = unsafe { std::mem::zeroed() };
+        // SAFETY: `path` is NUL-terminated and `stats` is a valid out pointer
+        if unsafe { libc::statvfs(path.as_ptr(), &mut stats) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        // The field types differ between platforms
+        #[allow(clippy::useless_conversion)]
+        let bytes = u64::from(stats.f_bavail) * u64::from(stats.f_frsize);
+        Ok(bytes / 1_000_000)
     }
     #[cfg(not(unix))]
     {
         // Fallback for other platforms
+        let _ = path;
         Ok(1000) // Return 1GB as default
     }
 }
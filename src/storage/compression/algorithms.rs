@@ -1,143 +1,86 @@
-use super::types::CompressionError;
-use std::io::Read;
+//! Codecs behind [`CompressionAlgorithm`]
+//!
+//! Output uses the standard formats, so data can be read by other tools:
+//! the LZ4 frame format, Zstandard frames and raw Deflate streams
+//! (RFC 1951).
 
-/// Simple deflate encoder (for demonstration)
-pub(crate) struct DeflateEncoder<R> {
-    pub(crate) reader: R,
-}
-
-impl<R: Read> DeflateEncoder<R> {
-    pub(crate) fn new(reader: R) -> Self {
-        Self { reader }
-    }
-}
-
-impl<R: Read> Read for DeflateEncoder<R> {
-    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        // For demo, just pass through (no actual compression)
-        self.reader.read(buf)
-    }
-}
-
-/// Simple deflate decoder (for demonstration)
-pub(crate) struct DeflateDecoder<R> {
-    pub(crate) reader: R,
-}
-
-impl<R: Read> DeflateDecoder<R> {
-    pub(crate) fn new(reader: R) -> Self {
-        Self { reader }
-    }
-}
-
-impl<R: Read> Read for DeflateDecoder<R> {
-    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        // For demo, just pass through
-        self.reader.read(buf)
-    }
-}
-
-// LZ4 compression (using simple run-length encoding as fallback)
-pub(crate) fn compress_lz4(data: &[u8]) -> Result<Vec<u8>, CompressionError> {
-    // Simple RLE-like compression for demonstration
-    // In production, you'd use the lz4 crate
-    let mut output = Vec::with_capacity(data.len());
-    let mut i = 0;
-
-    while i < data.len() {
-        let byte = data[i];
-        let mut count = 1u8;
+use std::io::{Read, Write};
 
-        while i + (count as usize) < data.len() && data[i + (count as usize)] == byte && count < 255
-        {
-            count += 1;
-        }
+use super::types::{CompressionAlgorithm, CompressionError};
 
-        if count >= 4 {
-            // Run of 4+ same bytes: store as escape + count + byte
-            output.push(0xFF); // Escape marker
-            output.push(count);
-            output.push(byte);
-            i += count as usize;
-        } else {
-            // Literal byte
-            if byte == 0xFF {
-                output.push(0xFF);
-                output.push(0);
-                output.push(0xFF);
-            } else {
-                output.push(byte);
-            }
-            i += 1;
-        }
+/// Compress `data` with `algorithm`; `level` is clamped to its range
+pub(crate) fn compress(
+    algorithm: CompressionAlgorithm,
+    data: &[u8],
+    level: u32,
+) -> Result<Vec<u8>, CompressionError> {
+    match algorithm {
+        CompressionAlgorithm::None => Ok(data.to_vec()),
+        CompressionAlgorithm::Lz4 => compress_lz4(data),
+        CompressionAlgorithm::Zstd => compress_zstd(data, level),
+        CompressionAlgorithm::Deflate => compress_deflate(data, level),
     }
-
-    Ok(output)
 }
 
-pub(crate) fn decompress_lz4(
+/// Decompress data produced by [`compress`] with the same algorithm
+pub(crate) fn decompress(
+    algorithm: CompressionAlgorithm,
     data: &[u8],
-    _original_size: usize,
 ) -> Result<Vec<u8>, CompressionError> {
-    let mut output = Vec::new();
-    let mut i = 0;
-
-    while i < data.len() {
-        if data[i] == 0xFF && i + 2 < data.len() {
-            let count = data[i + 1];
-            if count == 0 {
-                // Escaped 0xFF literal
-                output.push(0xFF);
-                i += 3;
-            } else {
-                // Run of bytes
-                let byte = data[i + 2];
-                for _ in 0..count {
-                    output.push(byte);
-                }
-                i += 3;
-            }
-        } else {
-            output.push(data[i]);
-            i += 1;
-        }
+    match algorithm {
+        CompressionAlgorithm::None => Ok(data.to_vec()),
+        CompressionAlgorithm::Lz4 => decompress_lz4(data),
+        CompressionAlgorithm::Zstd => decompress_zstd(data),
+        CompressionAlgorithm::Deflate => decompress_deflate(data),
     }
+}
 
-    Ok(output)
+// LZ4 frames with a content checksum; lz4_flex has a single level
+fn compress_lz4(data: &[u8]) -> Result<Vec<u8>, CompressionError> {
+    let mut info = lz4_flex::frame::FrameInfo::new();
+    info.content_checksum = true;
+    info.content_size = Some(data.len() as u64);
+    let mut encoder = lz4_flex::frame::FrameEncoder::with_frame_info(info, Vec::new());
+    encoder
+        .write_all(data)
+        .map_err(|_| CompressionError::CompressionFailed)?;
+    encoder
+        .finish()
+        .map_err(|_| CompressionError::CompressionFailed)
 }
 
-// ZSTD compression (placeholder - use zstd crate in production)
-pub(crate) fn compress_zstd(data: &[u8]) -> Result<Vec<u8>, CompressionError> {
-    // Fallback to deflate for now
-    compress_deflate(data)
+fn decompress_lz4(data: &[u8]) -> Result<Vec<u8>, CompressionError> {
+    let mut decompressed = Vec::new();
+    lz4_flex::frame::FrameDecoder::new(data)
+        .read_to_end(&mut decompressed)
+        .map_err(|_| CompressionError::DecompressionFailed)?;
+    Ok(decompressed)
 }
 
-pub(crate) fn decompress_zstd(data: &[u8]) -> Result<Vec<u8>, CompressionError> {
-    decompress_deflate(data)
+fn compress_zstd(data: &[u8], level: u32) -> Result<Vec<u8>, CompressionError> {
+    let level = (level as i32).clamp(1, *zstd::compression_level_range().end());
+    zstd::stream::encode_all(data, level).map_err(|_| CompressionError::CompressionFailed)
 }
 
-// Deflate compression using flate2-compatible algorithm
-pub(crate) fn compress_deflate(data: &[u8]) -> Result<Vec<u8>, CompressionError> {
-    use std::io::Cursor;
+fn decompress_zstd(data: &[u8]) -> Result<Vec<u8>, CompressionError> {
+    zstd::stream::decode_all(data).map_err(|_| CompressionError::DecompressionFailed)
+}
 
-    // Simple deflate implementation
-    let mut encoder = DeflateEncoder::new(Cursor::new(data));
-    let mut compressed = Vec::new();
+fn compress_deflate(data: &[u8], level: u32) -> Result<Vec<u8>, CompressionError> {
+    let mut encoder =
+        flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::new(level.min(9)));
     encoder
-        .read_to_end(&mut compressed)
+        .write_all(data)
         .map_err(|_| CompressionError::CompressionFailed)?;
-
-    Ok(compressed)
+    encoder
+        .finish()
+        .map_err(|_| CompressionError::CompressionFailed)
 }
 
-pub(crate) fn decompress_deflate(data: &[u8]) -> Result<Vec<u8>, CompressionError> {
-    use std::io::Cursor;
-
-    let mut decoder = DeflateDecoder::new(Cursor::new(data));
+fn decompress_deflate(data: &[u8]) -> Result<Vec<u8>, CompressionError> {
     let mut decompressed = Vec::new();
-    decoder
+    flate2::read::DeflateDecoder::new(data)
         .read_to_end(&mut decompressed)
         .map_err(|_| CompressionError::DecompressionFailed)?;
-
     Ok(decompressed)
 }
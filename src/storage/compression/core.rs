@@ -34,12 +34,7 @@ impl Compressor {
             });
         }
 
-        let compressed = match self.config.algorithm {
-            CompressionAlgorithm::None => data.to_vec(),
-            CompressionAlgorithm::Lz4 => algorithms::compress_lz4(data)?,
-            CompressionAlgorithm::Zstd => algorithms::compress_zstd(data)?,
-            CompressionAlgorithm::Deflate => algorithms::compress_deflate(data)?,
-        };
+        let compressed = algorithms::compress(self.config.algorithm, data, self.config.level)?;
 
         let checksum = crc32(&compressed);
 
@@ -59,14 +54,11 @@ impl Compressor {
             return Err(CompressionError::ChecksumMismatch);
         }
 
-        match compressed.algorithm {
-            CompressionAlgorithm::None => Ok(compressed.data.clone()),
-            CompressionAlgorithm::Lz4 => {
-                algorithms::decompress_lz4(&compressed.data, compressed.original_size)
-            }
-            CompressionAlgorithm::Zstd => algorithms::decompress_zstd(&compressed.data),
-            CompressionAlgorithm::Deflate => algorithms::decompress_deflate(&compressed.data),
+        let data = algorithms::decompress(compressed.algorithm, &compressed.data)?;
+        if data.len() != compressed.original_size {
+            return Err(CompressionError::DecompressionFailed);
         }
+        Ok(data)
     }
 
     /// Compress string (convenience method)
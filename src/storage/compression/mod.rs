@@ -2,13 +2,18 @@
 //!
 //! Provides compression for archived messages to reduce storage space.
 //! Uses LZ4 for fast compression and optional ZSTD for better ratios.
+//! Large message bodies are stored compressed in the database and decoded
+//! in SQL by the `decompress_text` function.
 
 mod algorithms;
 mod core;
+mod sql;
 mod types;
 mod utils;
 
+pub(crate) use algorithms::decompress;
 pub use core::Compressor;
+pub(crate) use sql::register_functions;
 pub use types::{
     CompressedData, CompressionAlgorithm, CompressionConfig, CompressionError, CompressionStats,
 };
@@ -28,6 +33,45 @@ mod tests {
         assert_eq!(original, decompressed);
     }
 
+    #[test]
+    fn test_algorithms_roundtrip_in_standard_formats() {
+        let original = "fn main() { println!(\"hello\"); }\n".repeat(200);
+        for (algorithm, magic) in [
+            (CompressionAlgorithm::Lz4, &[0x04, 0x22, 0x4D, 0x18][..]),
+            (CompressionAlgorithm::Zstd, &[0x28, 0xB5, 0x2F, 0xFD][..]),
+            (CompressionAlgorithm::Deflate, &[][..]),
+        ] {
+            let compressor = Compressor::new(CompressionConfig {
+                algorithm,
+                ..Default::default()
+            });
+            let compressed = compressor.compress_string(&original).unwrap();
+            assert_eq!(compressed.algorithm, algorithm);
+            assert!(compressed.data.starts_with(magic), "{:?}", algorithm);
+            assert!(compressed.ratio() < 0.2, "{:?}", algorithm);
+            assert_eq!(compressor.decompress_string(&compressed).unwrap(), original);
+        }
+
+        // Deflate output is a raw stream any inflater reads
+        let compressed = Compressor::new(CompressionConfig {
+            algorithm: CompressionAlgorithm::Deflate,
+            ..Default::default()
+        })
+        .compress_string(&original)
+        .unwrap();
+        let mut inflated = String::new();
+        std::io::Read::read_to_string(
+            &mut flate2::read::DeflateDecoder::new(&compressed.data[..]),
+            &mut inflated,
+        )
+        .unwrap();
+        assert_eq!(inflated, original);
+
+        let mut corrupt = compressed.clone();
+        corrupt.algorithm = CompressionAlgorithm::Zstd;
+        assert!(Compressor::default().decompress(&corrupt).is_err());
+    }
+
     #[test]
     fn test_compression_small_data() {
         let compressor = Compressor::default();
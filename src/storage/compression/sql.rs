@@ -0,0 +1,77 @@
+//! SQL functions for reading compressed columns
+
+use std::sync::Arc;
+
+use rusqlite::functions::{Context, FunctionFlags};
+use rusqlite::types::{Value, ValueRef};
+use rusqlite::{Connection, Error};
+
+use crate::storage::encryption::content::{decode, Codec};
+use crate::storage::encryption::{index, EncryptionError, KeyChain};
+
+use super::types::CompressionError;
+
+/// Register `decompress_text(value, codec)` and `index_text(value, codec)`
+/// on a connection
+///
+/// `decompress_text` returns `value` unchanged when `codec` is NULL,
+/// otherwise decodes the blob the codec names into text, using `keys` for
+/// sealed bodies. `index_text` is the text full-text search indexes: the
+/// same for plain rows, hashed words for sealed ones. Views, triggers and
+/// queries on compressed columns depend on them.
+pub(crate) fn register_functions(conn: &Connection, keys: Arc<KeyChain>) -> rusqlite::Result<()> {
+    let flags = FunctionFlags::SQLITE_UTF8
+        | FunctionFlags::SQLITE_DETERMINISTIC
+        | FunctionFlags::SQLITE_INNOCUOUS;
+
+    let decode_keys = keys.clone();
+    conn.create_scalar_function("decompress_text", 2, flags, move |ctx| {
+        match ctx.get_raw(1) {
+            ValueRef::Null => Ok(Value::from(ctx.get_raw(0))),
+            _ => text(ctx, &decode_keys).map(|(text, _)| text.map_or(Value::Null, Value::Text)),
+        }
+    })?;
+
+    conn.create_scalar_function("index_text", 2, flags, move |ctx| {
+        if let ValueRef::Null = ctx.get_raw(1) {
+            return Ok(Value::from(ctx.get_raw(0)));
+        }
+        let (text, codec) = text(ctx, &keys)?;
+        let Some(text) = text else {
+            return Ok(Value::Null);
+        };
+        match codec.generation {
+            Some(generation) => {
+                let key = keys.key(generation).map_err(user_error)?;
+                Ok(Value::Text(index::blind_document(&key, &text)))
+            }
+            None => Ok(Value::Text(text)),
+        }
+    })
+}
+
+/// Decoded text of the `(value, codec)` arguments, and their codec
+fn text(ctx: &Context<'_>, keys: &KeyChain) -> rusqlite::Result<(Option<String>, Codec)> {
+    let codec = match ctx.get_raw(1) {
+        ValueRef::Null => None,
+        ValueRef::Text(name) => Some(String::from_utf8_lossy(name).into_owned()),
+        _ => {
+            return Err(user_error(
+                CompressionError::UnknownAlgorithm("?".into()).into(),
+            ))
+        }
+    };
+    let codec = Codec::parse(codec.as_deref()).map_err(user_error)?;
+    let data = match ctx.get_raw(0) {
+        ValueRef::Blob(data) => data,
+        ValueRef::Text(text) => text,
+        ValueRef::Null => return Ok((None, codec)),
+        _ => return Err(user_error(CompressionError::DecompressionFailed.into())),
+    };
+    let text = decode(data, &codec, keys).map_err(user_error)?;
+    Ok((Some(text), codec))
+}
+
+fn user_error(error: EncryptionError) -> Error {
+    Error::UserFunctionError(Box::new(error))
+}
@@ -47,7 +47,7 @@ impl CompressionAlgorithm {
 pub struct CompressionConfig {
     /// Algorithm to use
     pub algorithm: CompressionAlgorithm,
-    /// Compression level (1-22 for zstd, 1-16 for lz4)
+    /// Compression level (1-22 for zstd, 0-9 for deflate; lz4 has one level)
     pub level: u32,
     /// Minimum size to compress (bytes)
     pub min_size: usize,
@@ -0,0 +1,334 @@
+//! Bookmark, pin, tag, reaction and note operations
+
+use anyhow::{ensure, Result};
+use chrono::Utc;
+use rusqlite::{params, Connection};
+
+use crate::storage::models::{Annotations, MessageBookmark, Note, Reaction};
+
+use super::helpers::{parse_timestamp, prefix_query};
+use super::Database;
+
+const NOTE_COLUMNS: &str =
+    "n.id, n.conversation_id, n.message_id, n.content, n.created_at, n.updated_at";
+
+impl Database {
+    // ==================== Bookmarks ====================
+
+    /// Bookmark a message, replacing the label of an existing bookmark
+    pub fn set_bookmark(&self, message_id: &str, label: Option<&str>) -> Result<()> {
+        self.writer()?.execute(
+            "INSERT INTO message_bookmarks (message_id, label, created_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(message_id) DO UPDATE SET label = excluded.label",
+            params![message_id, label, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Remove a message's bookmark
+    pub fn remove_bookmark(&self, message_id: &str) -> Result<()> {
+        self.writer()?.execute(
+            "DELETE FROM message_bookmarks WHERE message_id = ?1",
+            params![message_id],
+        )?;
+        Ok(())
+    }
+
+    /// Bookmarked messages of a conversation, in message order
+    pub fn get_bookmarks(&self, conversation_id: &str) -> Result<Vec<MessageBookmark>> {
+        bookmarks(&*self.reader()?, conversation_id)
+    }
+
+    // ==================== Pins ====================
+
+    /// Pin a message
+    pub fn pin_message(&self, message_id: &str) -> Result<()> {
+        self.writer()?.execute(
+            "INSERT OR IGNORE INTO pinned_messages (message_id, created_at) VALUES (?1, ?2)",
+            params![message_id, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Unpin a message
+    pub fn unpin_message(&self, message_id: &str) -> Result<()> {
+        self.writer()?.execute(
+            "DELETE FROM pinned_messages WHERE message_id = ?1",
+            params![message_id],
+        )?;
+        Ok(())
+    }
+
+    /// IDs of a conversation's pinned messages, in message order
+    pub fn get_pinned_messages(&self, conversation_id: &str) -> Result<Vec<String>> {
+        pinned(&*self.reader()?, conversation_id)
+    }
+
+    // ==================== Tags ====================
+
+    /// Tag a conversation; tags are trimmed and compared case-insensitively
+    pub fn add_tag(&self, conversation_id: &str, tag: &str) -> Result<()> {
+        let tag = tag.trim();
+        ensure!(!tag.is_empty(), "Tag must not be empty");
+        self.writer()?.execute(
+            "INSERT OR IGNORE INTO conversation_tags (conversation_id, tag, created_at)
+             VALUES (?1, ?2, ?3)",
+            params![conversation_id, tag, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Remove a tag from a conversation
+    pub fn remove_tag(&self, conversation_id: &str, tag: &str) -> Result<()> {
+        self.writer()?.execute(
+            "DELETE FROM conversation_tags WHERE conversation_id = ?1 AND tag = ?2",
+            params![conversation_id, tag.trim()],
+        )?;
+        Ok(())
+    }
+
+    /// Replace all tags of a conversation
+    pub fn set_tags(&self, conversation_id: &str, tags: &[String]) -> Result<()> {
+        let conn = self.writer()?;
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM conversation_tags WHERE conversation_id = ?1",
+            params![conversation_id],
+        )?;
+        let now = Utc::now().to_rfc3339();
+        for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+            tx.execute(
+                "INSERT OR IGNORE INTO conversation_tags (conversation_id, tag, created_at)
+                 VALUES (?1, ?2, ?3)",
+                params![conversation_id, tag, now],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Tags of a conversation, alphabetically
+    pub fn get_tags(&self, conversation_id: &str) -> Result<Vec<String>> {
+        tags(&*self.reader()?, conversation_id)
+    }
+
+    /// Every tag in use with its number of conversations, most used first
+    pub fn list_tags(&self) -> Result<Vec<(String, usize)>> {
+        let conn = self.reader()?;
+        let mut stmt = conn.prepare(
+            "SELECT MIN(tag), COUNT(*) FROM conversation_tags
+             GROUP BY tag ORDER BY COUNT(*) DESC, MIN(tag) COLLATE NOCASE",
+        )?;
+        let tags = stmt
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get::<_, i64>(1)?.max(0) as usize))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(tags)
+    }
+
+    // ==================== Reactions ====================
+
+    /// React to a message; reacting twice with the same emoji is a no-op
+    pub fn add_reaction(&self, message_id: &str, emoji: &str) -> Result<()> {
+        self.writer()?.execute(
+            "INSERT OR IGNORE INTO message_reactions (message_id, emoji, created_at)
+             VALUES (?1, ?2, ?3)",
+            params![message_id, emoji, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Remove a reaction from a message
+    pub fn remove_reaction(&self, message_id: &str, emoji: &str) -> Result<()> {
+        self.writer()?.execute(
+            "DELETE FROM message_reactions WHERE message_id = ?1 AND emoji = ?2",
+            params![message_id, emoji],
+        )?;
+        Ok(())
+    }
+
+    /// Reactions to a conversation's messages
+    pub fn get_reactions(&self, conversation_id: &str) -> Result<Vec<Reaction>> {
+        reactions(&*self.reader()?, conversation_id)
+    }
+
+    // ==================== Notes ====================
+
+    /// Insert a new note
+    pub fn insert_note(&self, note: &Note) -> Result<()> {
+        self.writer()?.execute(
+            "INSERT INTO notes (id, conversation_id, message_id, content, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                note.id,
+                note.conversation_id,
+                note.message_id,
+                note.content,
+                note.created_at.to_rfc3339(),
+                note.updated_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Change a note's text; returns whether the note exists
+    pub fn update_note(&self, id: &str, content: &str) -> Result<bool> {
+        let updated = self.writer()?.execute(
+            "UPDATE notes SET content = ?1, updated_at = ?2 WHERE id = ?3",
+            params![content, Utc::now().to_rfc3339(), id],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Delete a note
+    pub fn delete_note(&self, id: &str) -> Result<()> {
+        self.writer()?
+            .execute("DELETE FROM notes WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    /// Set the note on the conversation as a whole; blank text removes it
+    pub fn set_conversation_note(&self, conversation_id: &str, content: &str) -> Result<()> {
+        let conn = self.writer()?;
+        if content.trim().is_empty() {
+            conn.execute(
+                "DELETE FROM notes WHERE conversation_id = ?1 AND message_id IS NULL",
+                params![conversation_id],
+            )?;
+        } else {
+            let note = Note::new(conversation_id, content);
+            conn.execute(
+                "INSERT INTO notes (id, conversation_id, message_id, content, created_at, updated_at)
+                 VALUES (?1, ?2, NULL, ?3, ?4, ?4)
+                 ON CONFLICT(conversation_id) WHERE message_id IS NULL
+                 DO UPDATE SET content = excluded.content, updated_at = excluded.updated_at",
+                params![
+                    note.id,
+                    note.conversation_id,
+                    note.content,
+                    note.created_at.to_rfc3339(),
+                ],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Notes of a conversation, oldest first
+    pub fn get_notes(&self, conversation_id: &str) -> Result<Vec<Note>> {
+        notes(&*self.reader()?, conversation_id)
+    }
+
+    /// Search notes using full-text search, best match first
+    pub fn search_notes(&self, query: &str, limit: usize) -> Result<Vec<Note>> {
+        let conn = self.reader()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM notes_fts
+             JOIN notes n ON notes_fts.rowid = n.rowid
+             WHERE notes_fts MATCH ?1
+             ORDER BY rank
+             LIMIT ?2",
+            NOTE_COLUMNS
+        ))?;
+        let notes = stmt
+            .query_map(params![prefix_query(query), limit], row_to_note)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(notes)
+    }
+
+    // ==================== All ====================
+
+    /// Bookmarks, pins, tags, reactions and notes of a conversation
+    pub fn get_annotations(&self, conversation_id: &str) -> Result<Annotations> {
+        let conn = self.reader()?;
+        Ok(Annotations {
+            bookmarks: bookmarks(&conn, conversation_id)?,
+            pinned: pinned(&conn, conversation_id)?,
+            tags: tags(&conn, conversation_id)?,
+            reactions: reactions(&conn, conversation_id)?,
+            notes: notes(&conn, conversation_id)?,
+        })
+    }
+}
+
+fn bookmarks(conn: &Connection, conversation_id: &str) -> Result<Vec<MessageBookmark>> {
+    let mut stmt = conn.prepare(
+        "SELECT b.message_id, b.label, b.created_at FROM message_bookmarks b
+         JOIN messages m ON m.id = b.message_id
+         WHERE m.conversation_id = ?1
+         ORDER BY m.timestamp, m.rowid",
+    )?;
+    let bookmarks = stmt
+        .query_map(params![conversation_id], |row| {
+            Ok(MessageBookmark {
+                message_id: row.get(0)?,
+                label: row.get(1)?,
+                created_at: parse_timestamp(&row.get::<_, String>(2)?),
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(bookmarks)
+}
+
+fn pinned(conn: &Connection, conversation_id: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT p.message_id FROM pinned_messages p
+         JOIN messages m ON m.id = p.message_id
+         WHERE m.conversation_id = ?1
+         ORDER BY m.timestamp, m.rowid",
+    )?;
+    let pinned = stmt
+        .query_map(params![conversation_id], |row| row.get(0))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(pinned)
+}
+
+fn tags(conn: &Connection, conversation_id: &str) -> Result<Vec<String>> {
+    let mut stmt =
+        conn.prepare("SELECT tag FROM conversation_tags WHERE conversation_id = ?1 ORDER BY tag")?;
+    let tags = stmt
+        .query_map(params![conversation_id], |row| row.get(0))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(tags)
+}
+
+fn reactions(conn: &Connection, conversation_id: &str) -> Result<Vec<Reaction>> {
+    let mut stmt = conn.prepare(
+        "SELECT r.message_id, r.emoji, r.created_at FROM message_reactions r
+         JOIN messages m ON m.id = r.message_id
+         WHERE m.conversation_id = ?1
+         ORDER BY m.timestamp, m.rowid, r.created_at, r.rowid",
+    )?;
+    let reactions = stmt
+        .query_map(params![conversation_id], |row| {
+            Ok(Reaction {
+                message_id: row.get(0)?,
+                emoji: row.get(1)?,
+                created_at: parse_timestamp(&row.get::<_, String>(2)?),
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(reactions)
+}
+
+fn notes(conn: &Connection, conversation_id: &str) -> Result<Vec<Note>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM notes n WHERE n.conversation_id = ?1 ORDER BY n.created_at, n.rowid",
+        NOTE_COLUMNS
+    ))?;
+    let notes = stmt
+        .query_map(params![conversation_id], row_to_note)?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(notes)
+}
+
+fn row_to_note(row: &rusqlite::Row<'_>) -> rusqlite::Result<Note> {
+    Ok(Note {
+        id: row.get(0)?,
+        conversation_id: row.get(1)?,
+        message_id: row.get(2)?,
+        content: row.get(3)?,
+        created_at: parse_timestamp(&row.get::<_, String>(4)?),
+        updated_at: parse_timestamp(&row.get::<_, String>(5)?),
+    })
+}
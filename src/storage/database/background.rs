@@ -0,0 +1,110 @@
+//! Database work off the calling thread
+//!
+//! SQLite calls block, so the UI hands them to other threads: writes to a
+//! single writer thread that runs them in the order they were queued, and
+//! reads to a short-lived thread each. Neither needs a particular async
+//! runtime; the returned futures can be awaited from GPUI tasks.
+
+use std::future::Future;
+use std::sync::{mpsc, Arc};
+
+use anyhow::{anyhow, Result};
+use futures::channel::oneshot;
+
+use super::Database;
+
+/// Work queued for the writer thread
+pub(crate) type Job = Box<dyn FnOnce() + Send>;
+
+impl Database {
+    /// Run `f` on the writer thread, after any write queued before it
+    ///
+    /// The job is queued immediately; the future only delivers its result.
+    pub fn write_async<T, F>(
+        self: &Arc<Self>,
+        f: F,
+    ) -> impl Future<Output = Result<T>> + Send + 'static
+    where
+        T: Send + 'static,
+        F: FnOnce(&Database) -> Result<T> + Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        let database = self.clone();
+        self.enqueue(Box::new(move || {
+            let _ = tx.send(f(&database));
+        }));
+        async move { rx.await.map_err(|_| anyhow!("Database writer stopped"))? }
+    }
+
+    /// Queue a write whose failure is only logged
+    ///
+    /// `action` completes "Failed to ..." in the log message.
+    pub fn spawn_write<F>(self: &Arc<Self>, action: &'static str, f: F)
+    where
+        F: FnOnce(&Database) -> Result<()> + Send + 'static,
+    {
+        let database = self.clone();
+        self.enqueue(Box::new(move || {
+            if let Err(e) = f(&database) {
+                tracing::error!("Failed to {}: {}", action, e);
+            }
+        }));
+    }
+
+    /// Run `f` on a background thread with a read-only connection
+    pub fn read_async<T, F>(
+        self: &Arc<Self>,
+        f: F,
+    ) -> impl Future<Output = Result<T>> + Send + 'static
+    where
+        T: Send + 'static,
+        F: FnOnce(&Database) -> Result<T> + Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        let database = self.clone();
+        let spawned = std::thread::Builder::new()
+            .name("db-read".into())
+            .spawn(move || {
+                let _ = tx.send(f(&database));
+            })
+            .map(|_| ());
+        async move {
+            spawned?;
+            rx.await
+                .map_err(|_| anyhow!("Database read was interrupted"))?
+        }
+    }
+
+    /// Hand a job to the writer thread, starting it if needed
+    fn enqueue(&self, job: Job) {
+        let mut jobs = self.jobs.lock();
+        let job = match jobs.as_ref() {
+            Some(sender) => match sender.send(job) {
+                Ok(()) => return,
+                // The thread stopped after a job panicked
+                Err(mpsc::SendError(job)) => job,
+            },
+            None => job,
+        };
+
+        let (tx, rx) = mpsc::channel::<Job>();
+        let started = std::thread::Builder::new()
+            .name("db-writer".into())
+            .spawn(move || {
+                for job in rx {
+                    job();
+                }
+            });
+        match started {
+            Ok(_) => {
+                let _ = tx.send(job);
+                *jobs = Some(tx);
+            }
+            Err(e) => {
+                tracing::error!("Failed to start database writer thread: {}", e);
+                drop(jobs);
+                job();
+            }
+        }
+    }
+}
@@ -0,0 +1,233 @@
+//! Conversation branch operations
+//!
+//! Messages form a tree through `parent_id`: editing or regenerating a turn
+//! adds a sibling instead of replacing it. Each conversation points at the
+//! leaf of its active branch, which every new message takes over.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, Result};
+use rusqlite::params;
+
+use crate::storage::models::{Branch, Conversation, Message};
+
+use super::helpers::{row_to_message, MESSAGE_COLUMNS};
+use super::Database;
+
+/// Longest branch preview, in characters
+const PREVIEW_LEN: usize = 80;
+
+impl Database {
+    /// Messages from the start of the conversation to `leaf_id`
+    pub fn get_thread(&self, leaf_id: &str) -> Result<Vec<Message>> {
+        let conn = self.reader()?;
+        let mut stmt = conn.prepare(&format!(
+            "WITH RECURSIVE thread(id, depth) AS (
+                 SELECT id, 0 FROM messages WHERE id = ?1
+                 UNION ALL
+                 SELECT m.parent_id, thread.depth + 1
+                 FROM thread JOIN messages m ON m.id = thread.id
+                 WHERE m.parent_id IS NOT NULL
+             )
+             SELECT {} FROM thread JOIN messages m ON m.id = thread.id
+             ORDER BY thread.depth DESC",
+            MESSAGE_COLUMNS
+        ))?;
+
+        let messages = stmt
+            .query_map(params![leaf_id], row_to_message)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(messages)
+    }
+
+    /// Last message of the active branch
+    ///
+    /// Conversations without a valid pointer use their newest message.
+    pub fn active_leaf_id(&self, conversation_id: &str) -> Result<Option<String>> {
+        let conn = self.reader()?;
+        let leaf = conn.query_row(
+            "SELECT COALESCE(
+                 (SELECT m.id FROM conversations c
+                  JOIN messages m ON m.id = c.active_leaf_id AND m.conversation_id = c.id
+                  WHERE c.id = ?1),
+                 (SELECT id FROM messages WHERE conversation_id = ?1
+                  ORDER BY timestamp DESC, rowid DESC LIMIT 1)
+             )",
+            params![conversation_id],
+            |row| row.get(0),
+        )?;
+        Ok(leaf)
+    }
+
+    /// Messages of the active branch, oldest first
+    pub fn get_active_thread(&self, conversation_id: &str) -> Result<Vec<Message>> {
+        match self.active_leaf_id(conversation_id)? {
+            Some(leaf) => self.get_thread(&leaf),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Branches of a conversation, in the order they were started
+    pub fn list_branches(&self, conversation_id: &str) -> Result<Vec<Branch>> {
+        let messages = self.get_messages(conversation_id)?;
+        let active = self.active_leaf_id(conversation_id)?;
+        Ok(branches(&messages, active.as_deref()))
+    }
+
+    /// Make the branch through `message_id` active; returns its leaf
+    ///
+    /// When the message has replies, the newest branch below it is chosen.
+    pub fn switch_branch(&self, conversation_id: &str, message_id: &str) -> Result<String> {
+        let messages = self.get_messages(conversation_id)?;
+        let leaf = newest_leaf_below(&messages, message_id).ok_or_else(|| {
+            anyhow!(
+                "Message {} is not in conversation {}",
+                message_id,
+                conversation_id
+            )
+        })?;
+        self.writer()?.execute(
+            "UPDATE conversations SET active_leaf_id = ?1 WHERE id = ?2",
+            params![leaf, conversation_id],
+        )?;
+        Ok(leaf)
+    }
+
+    /// Copy the branch ending at `leaf_id` into a new conversation
+    ///
+    /// Copies get new IDs but keep their timestamps and CLI session IDs.
+    pub fn fork_branch(&self, leaf_id: &str, title: impl Into<String>) -> Result<Conversation> {
+        let thread = self.get_thread(leaf_id)?;
+        let first = thread
+            .first()
+            .ok_or_else(|| anyhow!("Message {} not found", leaf_id))?;
+        let project_id: Option<String> = self.reader()?.query_row(
+            "SELECT project_id FROM conversations WHERE id = ?1",
+            params![first.conversation_id],
+            |row| row.get(0),
+        )?;
+        let conversation = Conversation::new(title, project_id);
+
+        let conn = self.writer()?;
+        let tx = conn.unchecked_transaction()?;
+        self.insert_conversation_with(&tx, &conversation)?;
+        let mut parent_id = None;
+        for message in thread {
+            let copy = Message {
+                id: uuid::Uuid::new_v4().to_string(),
+                conversation_id: conversation.id.clone(),
+                parent_id: parent_id.take(),
+                ..message
+            };
+            self.insert_message_with(&tx, &copy)?;
+            parent_id = Some(copy.id);
+        }
+        tx.commit()?;
+        Ok(conversation)
+    }
+
+    /// Claude CLI session to resume when continuing the branch ending at
+    /// `leaf_id`
+    ///
+    /// That is the session of the branch's last message that has one, unless
+    /// the session went on in another branch: resuming it would bring that
+    /// branch's turns along, so the branch starts a new session instead.
+    pub fn resume_session_id(&self, leaf_id: &str) -> Result<Option<String>> {
+        let thread = self.get_thread(leaf_id)?;
+        let Some(last) = thread.iter().rev().find(|m| m.session_id.is_some()) else {
+            return Ok(None);
+        };
+        let continued_elsewhere: bool = self.reader()?.query_row(
+            "SELECT EXISTS (
+                 SELECT 1 FROM messages
+                 WHERE session_id = ?1
+                   AND timestamp > (SELECT timestamp FROM messages WHERE id = ?2)
+             )",
+            params![last.session_id, last.id],
+            |row| row.get(0),
+        )?;
+        Ok(if continued_elsewhere {
+            None
+        } else {
+            last.session_id.clone()
+        })
+    }
+}
+
+/// Branches of a conversation's messages, given oldest first
+fn branches(messages: &[Message], active_leaf: Option<&str>) -> Vec<Branch> {
+    let index: HashMap<&str, usize> = messages
+        .iter()
+        .enumerate()
+        .map(|(i, m)| (m.id.as_str(), i))
+        .collect();
+    // Messages whose parent is missing start the tree
+    let parent_of = |i: usize| {
+        messages[i]
+            .parent_id
+            .as_deref()
+            .and_then(|id| index.get(id).copied())
+    };
+    let mut children: HashMap<Option<usize>, usize> = HashMap::new();
+    for i in 0..messages.len() {
+        *children.entry(parent_of(i)).or_default() += 1;
+    }
+
+    let mut branches: Vec<(usize, Branch)> = (0..messages.len())
+        .filter(|&i| !children.contains_key(&Some(i)))
+        .map(|leaf| {
+            let mut path = vec![leaf];
+            while let Some(parent) = parent_of(*path.last().unwrap()) {
+                if path.len() > messages.len() {
+                    break;
+                }
+                path.push(parent);
+            }
+            // Deepest message on the path with siblings
+            let fork = path
+                .iter()
+                .copied()
+                .find(|&i| children.get(&parent_of(i)).copied().unwrap_or(0) > 1)
+                .unwrap_or(*path.last().unwrap());
+            let branch = Branch {
+                leaf_id: messages[leaf].id.clone(),
+                fork_id: messages[fork].id.clone(),
+                preview: preview(&messages[fork].content),
+                length: path.len(),
+                updated_at: messages[leaf].timestamp,
+                is_active: active_leaf == Some(messages[leaf].id.as_str()),
+            };
+            (fork, branch)
+        })
+        .collect();
+    branches.sort_by_key(|(fork, branch)| (*fork, branch.updated_at));
+    branches.into_iter().map(|(_, branch)| branch).collect()
+}
+
+/// Newest leaf at or below `id` in messages given oldest first
+fn newest_leaf_below(messages: &[Message], id: &str) -> Option<String> {
+    let mut below: HashSet<&str> = HashSet::new();
+    let mut newest = None;
+    for message in messages {
+        let in_subtree = message.id == id
+            || message
+                .parent_id
+                .as_deref()
+                .is_some_and(|p| below.contains(p));
+        if in_subtree {
+            below.insert(message.id.as_str());
+            newest = Some(message.id.as_str());
+        }
+    }
+    // The newest message below is a leaf: children come after their parent
+    newest.map(String::from)
+}
+
+fn preview(content: &str) -> String {
+    let line = content.lines().next().unwrap_or("").trim();
+    match line.char_indices().nth(PREVIEW_LEN) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line.to_string(),
+    }
+}
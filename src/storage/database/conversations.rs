@@ -1,7 +1,7 @@
 //! Conversation database operations
 
 use anyhow::Result;
-use rusqlite::params;
+use rusqlite::{params, Connection};
 
 use crate::storage::models::Conversation;
 
@@ -11,7 +11,16 @@ use super::Database;
 impl Database {
     /// Insert a new conversation
     pub fn insert_conversation(&self, conversation: &Conversation) -> Result<()> {
-        self.conn.execute(
+        self.insert_conversation_with(&*self.writer()?, conversation)
+    }
+
+    /// Insert a conversation using `conn`, e.g. inside a transaction
+    pub(crate) fn insert_conversation_with(
+        &self,
+        conn: &Connection,
+        conversation: &Conversation,
+    ) -> Result<()> {
+        conn.execute(
             "INSERT INTO conversations (id, project_id, title, created_at, updated_at)
              VALUES (?1, ?2, ?3, ?4, ?5)",
             params![
@@ -27,12 +36,13 @@ impl Database {
 
     /// Get conversations for a project
     pub fn get_conversations(&self, project_id: Option<&str>) -> Result<Vec<Conversation>> {
+        let conn = self.reader()?;
         let mut stmt = if project_id.is_some() {
-            self.conn.prepare(
+            conn.prepare(
                 "SELECT id, project_id, title, created_at, updated_at FROM conversations WHERE project_id = ?1 ORDER BY updated_at DESC",
             )?
         } else {
-            self.conn.prepare(
+            conn.prepare(
                 "SELECT id, project_id, title, created_at, updated_at FROM conversations ORDER BY updated_at DESC",
             )?
         };
@@ -49,8 +59,11 @@ impl Database {
     }
 
     /// Delete a conversation
+    ///
+    /// Fails while an encrypted database is locked.
     pub fn delete_conversation(&self, id: &str) -> Result<()> {
-        self.conn
+        self.ensure_unlocked()?;
+        self.writer()?
             .execute("DELETE FROM conversations WHERE id = ?1", params![id])?;
         Ok(())
     }
@@ -1,24 +1,141 @@
 //! Core database operations
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
 
-use anyhow::Result;
-use rusqlite::Connection;
+use anyhow::{bail, Result};
+use parking_lot::Mutex;
 
-/// SQLite database wrapper
+use crate::storage::compression::{CompressionConfig, Compressor};
+use crate::storage::encryption::{AttachmentStore, KeyChain};
+use crate::storage::pool::{DatabasePool, PoolConfig, PoolStats, PooledConnectionGuard};
+
+use super::background::Job;
+use super::migrations::SCHEMA_VERSION;
+
+/// SQLite database
+///
+/// Writes go through a single writer connection, so they never wait on
+/// each other inside SQLite; reads use a pool of `query_only` connections
+/// that, with WAL journaling, are not blocked by a write in progress.
 pub struct Database {
-    pub(crate) conn: Connection,
+    /// Single connection for writes and migrations
+    pub(crate) writer: DatabasePool,
+    /// Read-only connections
+    pub(crate) readers: DatabasePool,
+    /// Queue of the background writer thread, started on first use
+    pub(crate) jobs: Mutex<Option<mpsc::Sender<Job>>>,
+    /// Compression of large message bodies
+    pub(crate) compressor: Compressor,
+    /// Keys of an encrypted database, shared with both pools
+    pub(crate) keys: Arc<KeyChain>,
+    /// Folder of attachment files, sealed with the messages
+    pub(crate) attachments_dir: Option<PathBuf>,
 }
 
 impl Database {
     /// Open the database (creates if doesn't exist)
     pub fn open() -> Result<Self> {
-        let path = Self::db_path()?;
+        Self::open_at(&Self::db_path()?)
+    }
+
+    /// Open a database file at `path` (creates if doesn't exist)
+    pub fn open_at(path: &Path) -> Result<Self> {
+        Self::open_with(path, PoolConfig::default())
+    }
+
+    /// Open a database file with custom pool settings
+    pub fn open_with(path: &Path, config: PoolConfig) -> Result<Self> {
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)?;
         }
-        let conn = Connection::open(&path)?;
-        Ok(Self { conn })
+        Self::from_pools(path.to_path_buf(), config)
+    }
+
+    /// Open the app database for another process, leaving it unchanged
+    pub fn open_current() -> Result<Self> {
+        Self::open_current_at(&Self::db_path()?)
+    }
+
+    /// Open an existing database whose schema is already up to date
+    ///
+    /// Migrating, and the backups that come with it, is left to the app:
+    /// this fails when the file is missing or its schema is older or newer
+    /// than this build's. Encryption is loaded, so a sealed database opens
+    /// locked.
+    pub fn open_current_at(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            bail!(
+                "No database at {}; start Claude Visual once to create it",
+                path.display()
+            );
+        }
+        let database = Self::open_at(path)?;
+        let version = database.schema_version()?;
+        if version != SCHEMA_VERSION {
+            bail!(
+                "Database schema version {} does not match this build ({}); \
+                 open Claude Visual to bring them in line",
+                version,
+                SCHEMA_VERSION
+            );
+        }
+        database.load_encryption()?;
+        Ok(database)
+    }
+
+    /// Open a private in-memory database, e.g. for tests
+    pub fn open_in_memory() -> Result<Self> {
+        // Shared cache so the writer and readers see the same database
+        let uri = format!(
+            "file:claude-visual-{}?mode=memory&cache=shared",
+            uuid::Uuid::new_v4()
+        );
+        Self::from_pools(
+            PathBuf::from(uri),
+            PoolConfig {
+                enable_wal: false,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn from_pools(path: PathBuf, config: PoolConfig) -> Result<Self> {
+        // The writer goes first: it creates the file and switches it to WAL
+        let keys = Arc::new(KeyChain::default());
+        let writer = DatabasePool::with_keys(path.clone(), config.writer(), keys.clone())?;
+        let readers = DatabasePool::with_keys(path, config.readers(), keys.clone())?;
+        Ok(Self {
+            writer,
+            readers,
+            jobs: Mutex::new(None),
+            compressor: Compressor::new(CompressionConfig::balanced()),
+            keys,
+            attachments_dir: None,
+        })
+    }
+
+    /// Compress message bodies with `config` from now on
+    ///
+    /// Rows already stored keep their encoding; use
+    /// [`CompressionAlgorithm::None`](crate::storage::compression::CompressionAlgorithm::None)
+    /// to store new messages as plain text.
+    pub fn with_compression(mut self, config: CompressionConfig) -> Self {
+        self.compressor = Compressor::new(config);
+        self
+    }
+
+    /// Keep attachments in `dir`, encrypted along with the messages
+    pub fn with_attachments(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.attachments_dir = Some(dir.into());
+        self
+    }
+
+    /// Attachment files, if a folder was set
+    pub fn attachments(&self) -> Option<AttachmentStore> {
+        self.attachments_dir
+            .clone()
+            .map(|dir| AttachmentStore::new(dir, self.keys.clone()))
     }
 
     /// Get database path
@@ -28,72 +145,30 @@ impl Database {
         Ok(data_dir.join("claude-visual").join("claude-visual.db"))
     }
 
-    /// Initialize database schema
+    /// Initialize database schema, applying any pending migrations
+    ///
+    /// The backup a migration makes is kept until the next launch that
+    /// has nothing to migrate, i.e. once the migrated database has opened.
     pub fn initialize(&self) -> Result<()> {
-        self.conn.execute_batch(
-            r#"
-            -- Projects table
-            CREATE TABLE IF NOT EXISTS projects (
-                id TEXT PRIMARY KEY,
-                name TEXT NOT NULL,
-                path TEXT NOT NULL UNIQUE,
-                is_favorite INTEGER NOT NULL DEFAULT 0,
-                tags TEXT NOT NULL DEFAULT '[]',
-                last_accessed TEXT NOT NULL,
-                created_at TEXT NOT NULL
-            );
-
-            -- Conversations table
-            CREATE TABLE IF NOT EXISTS conversations (
-                id TEXT PRIMARY KEY,
-                project_id TEXT,
-                title TEXT NOT NULL,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL,
-                FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE SET NULL
-            );
+        if self.migrate()?.is_none() {
+            super::migrations::remove_backups(&*self.writer()?)?;
+        }
+        self.load_encryption()?;
+        Ok(())
+    }
 
-            -- Messages table
-            CREATE TABLE IF NOT EXISTS messages (
-                id TEXT PRIMARY KEY,
-                conversation_id TEXT NOT NULL,
-                role TEXT NOT NULL,
-                content TEXT NOT NULL,
-                tool_name TEXT,
-                is_error INTEGER NOT NULL DEFAULT 0,
-                timestamp TEXT NOT NULL,
-                FOREIGN KEY (conversation_id) REFERENCES conversations(id) ON DELETE CASCADE
-            );
+    /// The writer connection, waiting while another write holds it
+    pub(crate) fn writer(&self) -> Result<PooledConnectionGuard<'_>> {
+        self.writer.acquire()
+    }
 
-            -- Indexes
-            CREATE INDEX IF NOT EXISTS idx_projects_last_accessed ON projects(last_accessed DESC);
-            CREATE INDEX IF NOT EXISTS idx_conversations_project ON conversations(project_id);
-            CREATE INDEX IF NOT EXISTS idx_conversations_updated ON conversations(updated_at DESC);
-            CREATE INDEX IF NOT EXISTS idx_messages_conversation ON messages(conversation_id);
-            CREATE INDEX IF NOT EXISTS idx_messages_timestamp ON messages(timestamp);
-
-            -- FTS5 virtual table for full-text search on messages
-            CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
-                content,
-                content='messages',
-                content_rowid='rowid'
-            );
+    /// A read-only connection
+    pub(crate) fn reader(&self) -> Result<PooledConnectionGuard<'_>> {
+        self.readers.acquire()
+    }
 
-            -- Triggers to keep FTS index in sync
-            CREATE TRIGGER IF NOT EXISTS messages_ai AFTER INSERT ON messages BEGIN
-                INSERT INTO messages_fts(rowid, content) VALUES (new.rowid, new.content);
-            END;
-
-            CREATE TRIGGER IF NOT EXISTS messages_ad AFTER DELETE ON messages BEGIN
-                INSERT INTO messages_fts(messages_fts, rowid, content) VALUES('delete', old.rowid, old.content);
-            END;
-
-            CREATE TRIGGER IF NOT EXISTS messages_au AFTER UPDATE ON messages BEGIN
-                INSERT INTO messages_fts(messages_fts, rowid, content) VALUES('delete', old.rowid, old.content);
-                INSERT INTO messages_fts(rowid, content) VALUES (new.rowid, new.content);
-            END;
-            "#,
-        )?;
-        Ok(())
+    /// Statistics of the writer and reader pools
+    pub fn pool_stats(&self) -> (PoolStats, PoolStats) {
+        (self.writer.stats(), self.readers.stats())
     }
 }
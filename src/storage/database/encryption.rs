@@ -0,0 +1,474 @@
+//! Encryption at rest database operations
+
+use anyhow::{bail, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::storage::encryption::content::{self, Codec};
+use crate::storage::encryption::{
+    derive_key, hmac_sha256, open, random_bytes, seal, DataKey, EncryptionError, KeyStore,
+    SecretKey, SALT_LEN,
+};
+
+use super::Database;
+
+/// Input of the check value that tells a wrong key from a right one
+const KEY_CHECK: &[u8] = b"claude-visual key check";
+
+/// Whether message content is encrypted, and if it can be read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncryptionStatus {
+    /// Stored in plain text
+    Disabled,
+    /// Encrypted, and the key has not been supplied
+    Locked,
+    /// Encrypted, and readable
+    Unlocked,
+}
+
+/// Where the key-encryption key comes from
+#[derive(Clone, Copy)]
+pub enum KeySource<'a> {
+    /// Derived from a passphrase
+    Passphrase(&'a str),
+    /// Random, kept in a keyring
+    Keyring(&'a dyn KeyStore),
+}
+
+/// The `encryption` row
+struct Header {
+    key_source: String,
+    kdf_salt: Option<Vec<u8>>,
+    keyring_account: Option<String>,
+    key_check: Vec<u8>,
+    current_generation: u32,
+}
+
+/// New key-encryption key and how to record it
+struct Wrapping {
+    key: SecretKey,
+    key_source: &'static str,
+    kdf_salt: Option<Vec<u8>>,
+    keyring_account: Option<String>,
+}
+
+impl Database {
+    /// Whether message content is encrypted, and if it can be read
+    pub fn encryption_status(&self) -> EncryptionStatus {
+        if !self.keys.is_enabled() {
+            EncryptionStatus::Disabled
+        } else if self.keys.is_unlocked() {
+            EncryptionStatus::Unlocked
+        } else {
+            EncryptionStatus::Locked
+        }
+    }
+
+    /// Fail with [`EncryptionError::Locked`] until the key is supplied
+    ///
+    /// Deleting messages takes them out of the search index, which needs
+    /// the key to hash their words again.
+    pub(crate) fn ensure_unlocked(&self) -> Result<()> {
+        if self.encryption_status() == EncryptionStatus::Locked {
+            return Err(EncryptionError::Locked.into());
+        }
+        Ok(())
+    }
+
+    /// Check if the key of the encrypted database is kept in a keyring
+    pub fn key_in_keyring(&self) -> Result<bool> {
+        Ok(encryption_header(&*self.reader()?)?
+            .is_some_and(|header| header.key_source == "keyring"))
+    }
+
+    /// Start out locked if the database is encrypted
+    pub(crate) fn load_encryption(&self) -> Result<()> {
+        let encrypted = encryption_header(&*self.reader()?)?.is_some();
+        self.keys.set_enabled(encrypted);
+        if encrypted {
+            self.writer()?.pragma_update(None, "secure_delete", true)?;
+        }
+        Ok(())
+    }
+
+    /// Encrypt every message and attachment, and those stored from now on
+    ///
+    /// Plain text left over in the database file and WAL is wiped, and
+    /// migration backups are removed. The database is left unlocked.
+    pub fn enable_encryption(&self, source: KeySource<'_>) -> Result<()> {
+        if self.keys.is_enabled() {
+            bail!("The database is already encrypted");
+        }
+        let wrapping = new_wrapping(source)?;
+        let data_key = SecretKey::generate();
+        self.keys.insert(1, &data_key);
+
+        let result = (|| -> Result<()> {
+            let conn = self.writer()?;
+            let tx = conn.unchecked_transaction()?;
+            let now = chrono::Utc::now().to_rfc3339();
+            tx.execute(
+                "INSERT INTO encryption (id, key_source, kdf_salt, keyring_account, key_check,
+                     current_generation, created_at)
+                 VALUES (1, ?1, ?2, ?3, ?4, 1, ?5)",
+                params![
+                    wrapping.key_source,
+                    wrapping.kdf_salt,
+                    wrapping.keyring_account,
+                    hmac_sha256(wrapping.key.as_bytes(), KEY_CHECK).to_vec(),
+                    now,
+                ],
+            )?;
+            tx.execute(
+                "INSERT INTO encryption_keys (generation, wrapped_key, created_at)
+                 VALUES (1, ?1, ?2)",
+                params![seal(&wrapping.key, data_key.as_bytes())?, now],
+            )?;
+            self.reencode_messages(&tx, Some((1, &*self.keys.key(1)?)))?;
+            tx.commit()?;
+            Ok(())
+        })();
+        if let Err(e) = result {
+            self.keys.set_enabled(false);
+            forget_keyring_entry(source, wrapping.keyring_account.as_deref());
+            return Err(e);
+        }
+
+        self.keys.set_enabled(true);
+        self.keys.unlock(wrapping.key, vec![(1, data_key)], 1);
+        if let Some(attachments) = self.attachments() {
+            attachments.reseal_all(Some((1, &*self.keys.key(1)?)))?;
+        }
+        self.scrub()
+    }
+
+    /// Supply the key of an encrypted database
+    pub fn unlock(&self, source: KeySource<'_>) -> Result<()> {
+        let conn = self.reader()?;
+        let Some(header) = encryption_header(&conn)? else {
+            bail!("The database is not encrypted");
+        };
+        let wrapping = wrapping_key(&header, source)?;
+
+        let mut keys = Vec::new();
+        for (generation, wrapped_key) in wrapped_keys(&conn)? {
+            let mut bytes = open(&wrapping, &wrapped_key)?;
+            keys.push((generation, SecretKey::from_bytes(&bytes)?));
+            bytes.fill(0);
+        }
+
+        self.keys.set_enabled(true);
+        self.keys.unlock(wrapping, keys, header.current_generation);
+        Ok(())
+    }
+
+    /// Forget the keys until the next [`unlock`](Self::unlock)
+    pub fn lock(&self) {
+        self.keys.lock();
+    }
+
+    /// Protect the data keys with a new passphrase or keyring entry
+    ///
+    /// `current` must unlock the database. Content is not re-encrypted.
+    pub fn change_key_source(&self, current: KeySource<'_>, new: KeySource<'_>) -> Result<()> {
+        let Some(header) = encryption_header(&*self.reader()?)? else {
+            bail!("The database is not encrypted");
+        };
+        let old = wrapping_key(&header, current)?;
+        let wrapping = new_wrapping(new)?;
+
+        let result = (|| -> Result<()> {
+            let conn = self.writer()?;
+            let tx = conn.unchecked_transaction()?;
+            for (generation, wrapped_key) in wrapped_keys(&tx)? {
+                let mut bytes = open(&old, &wrapped_key)?;
+                let rewrapped = seal(&wrapping.key, &bytes)?;
+                bytes.fill(0);
+                tx.execute(
+                    "UPDATE encryption_keys SET wrapped_key = ?1 WHERE generation = ?2",
+                    params![rewrapped, generation],
+                )?;
+            }
+            tx.execute(
+                "UPDATE encryption SET key_source = ?1, kdf_salt = ?2, keyring_account = ?3,
+                     key_check = ?4
+                 WHERE id = 1",
+                params![
+                    wrapping.key_source,
+                    wrapping.kdf_salt,
+                    wrapping.keyring_account,
+                    hmac_sha256(wrapping.key.as_bytes(), KEY_CHECK).to_vec(),
+                ],
+            )?;
+            tx.commit()?;
+            Ok(())
+        })();
+        if let Err(e) = result {
+            forget_keyring_entry(new, wrapping.keyring_account.as_deref());
+            return Err(e);
+        }
+
+        if self.keys.is_unlocked() {
+            self.keys.set_wrapping(wrapping.key);
+        }
+        forget_keyring_entry(current, header.keyring_account.as_deref());
+        Ok(())
+    }
+
+    /// Re-encrypt everything with a new data key and drop the old ones,
+    /// returning the new key generation
+    ///
+    /// The search index is rebuilt with hashes under the new key, and what
+    /// was sealed with the old ones is wiped from the file. If
+    /// re-encryption stops part way, content stays readable and the next
+    /// rotation finishes it.
+    pub fn rotate_key(&self) -> Result<u32> {
+        if !self.keys.is_enabled() {
+            bail!("The database is not encrypted");
+        }
+        let wrapping = self.keys.wrapping()?;
+        let conn = self.writer()?;
+        let generation: u32 = conn.query_row(
+            "SELECT COALESCE(MAX(generation), 0) + 1 FROM encryption_keys",
+            [],
+            |row| row.get(0),
+        )?;
+        let data_key = SecretKey::generate();
+
+        // Stored on its own first, so anything sealed with it stays readable
+        conn.execute(
+            "INSERT INTO encryption_keys (generation, wrapped_key, created_at)
+             VALUES (?1, ?2, ?3)",
+            params![
+                generation,
+                seal(&wrapping, data_key.as_bytes())?,
+                chrono::Utc::now().to_rfc3339(),
+            ],
+        )?;
+        self.keys.insert(generation, &data_key);
+        self.keys.make_current(generation);
+        let key = self.keys.key(generation)?;
+
+        if let Some(attachments) = self.attachments() {
+            attachments.reseal_all(Some((generation, &key)))?;
+        }
+
+        let tx = conn.unchecked_transaction()?;
+        self.reencode_messages(&tx, Some((generation, &key)))?;
+        tx.execute(
+            "UPDATE encryption SET current_generation = ?1, rotated_at = ?2 WHERE id = 1",
+            params![generation, chrono::Utc::now().to_rfc3339()],
+        )?;
+        let retired = {
+            let mut stmt =
+                tx.prepare("SELECT generation FROM encryption_keys WHERE generation != ?1")?;
+            let rows = stmt
+                .query_map(params![generation], |row| row.get::<_, u32>(0))?
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            rows
+        };
+        tx.execute(
+            "DELETE FROM encryption_keys WHERE generation != ?1",
+            params![generation],
+        )?;
+        tx.commit()?;
+
+        drop(conn);
+        for old in retired {
+            self.keys.remove(old);
+        }
+        self.scrub()?;
+        Ok(generation)
+    }
+
+    /// Decrypt every message and attachment and store content in plain
+    /// text from now on
+    ///
+    /// `source` must unlock the database.
+    pub fn disable_encryption(&self, source: KeySource<'_>) -> Result<()> {
+        let Some(header) = encryption_header(&*self.reader()?)? else {
+            bail!("The database is not encrypted");
+        };
+        self.unlock(source)?;
+
+        // Files first: plain files are readable whatever happens next
+        if let Some(attachments) = self.attachments() {
+            attachments.reseal_all(None)?;
+        }
+
+        let conn = self.writer()?;
+        let tx = conn.unchecked_transaction()?;
+        self.reencode_messages(&tx, None)?;
+        tx.execute_batch("DELETE FROM encryption_keys; DELETE FROM encryption;")?;
+        tx.commit()?;
+
+        self.keys.set_enabled(false);
+        forget_keyring_entry(source, header.keyring_account.as_deref());
+        Ok(())
+    }
+
+    /// Wipe what the database still holds from before the last re-encoding
+    ///
+    /// Replaced rows linger in free pages and the WAL, and replaced words in
+    /// the search index's old segments, so the index is rebuilt, the file
+    /// rewritten and the WAL emptied, with deleted content overwritten from
+    /// now on. Migration backups are plain copies, and are removed.
+    fn scrub(&self) -> Result<()> {
+        let conn = self.writer()?;
+        conn.pragma_update(None, "secure_delete", true)?;
+        conn.execute_batch(
+            "INSERT INTO messages_fts(messages_fts) VALUES('rebuild');
+             VACUUM;",
+        )?;
+        // A no-op for other journal modes
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+        super::migrations::remove_backups(&conn)?;
+        Ok(())
+    }
+
+    /// Store the content and metadata of every message not yet sealed with
+    /// `key` again with it, or in plain if `None`, returning the number of
+    /// messages rewritten
+    ///
+    /// The update trigger re-indexes each row, hashed under its new key.
+    fn reencode_messages(&self, conn: &Connection, key: Option<(u32, &DataKey)>) -> Result<usize> {
+        let rows = {
+            let mut stmt = conn.prepare(
+                "SELECT rowid, content_codec, metadata IS NOT NULL, metadata_codec FROM messages",
+            )?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, Option<String>>(1)?,
+                        row.get::<_, bool>(2)?,
+                        row.get::<_, Option<String>>(3)?,
+                    ))
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            rows
+        };
+
+        let target = key.map(|(generation, _)| generation);
+        let mut read_content = conn.prepare(
+            "SELECT decompress_text(content, content_codec) FROM messages WHERE rowid = ?1",
+        )?;
+        let mut write_content =
+            conn.prepare("UPDATE messages SET content = ?1, content_codec = ?2 WHERE rowid = ?3")?;
+        let mut read_metadata = conn.prepare(
+            "SELECT decompress_text(metadata, metadata_codec) FROM messages WHERE rowid = ?1",
+        )?;
+        let mut write_metadata = conn
+            .prepare("UPDATE messages SET metadata = ?1, metadata_codec = ?2 WHERE rowid = ?3")?;
+        let mut rewritten = 0;
+        for (rowid, content_codec, has_metadata, metadata_codec) in rows {
+            let mut changed = false;
+            if Codec::parse(content_codec.as_deref())?.generation != target {
+                let text: Option<String> =
+                    read_content.query_row(params![rowid], |row| row.get(0))?;
+                let (value, codec) =
+                    content::encode(&text.unwrap_or_default(), &self.compressor, key)?;
+                write_content.execute(params![value, codec, rowid])?;
+                changed = true;
+            }
+            if has_metadata && Codec::parse(metadata_codec.as_deref())?.generation != target {
+                let text: Option<String> =
+                    read_metadata.query_row(params![rowid], |row| row.get(0))?;
+                let (value, codec) =
+                    content::encode(&text.unwrap_or_default(), &self.compressor, key)?;
+                write_metadata.execute(params![value, codec, rowid])?;
+                changed = true;
+            }
+            rewritten += changed as usize;
+        }
+        Ok(rewritten)
+    }
+}
+
+fn encryption_header(conn: &Connection) -> Result<Option<Header>> {
+    Ok(conn
+        .query_row(
+            "SELECT key_source, kdf_salt, keyring_account, key_check, current_generation
+             FROM encryption WHERE id = 1",
+            [],
+            |row| {
+                Ok(Header {
+                    key_source: row.get(0)?,
+                    kdf_salt: row.get(1)?,
+                    keyring_account: row.get(2)?,
+                    key_check: row.get(3)?,
+                    current_generation: row.get(4)?,
+                })
+            },
+        )
+        .optional()?)
+}
+
+/// Data key generations, each sealed with the key-encryption key
+fn wrapped_keys(conn: &Connection) -> Result<Vec<(u32, Vec<u8>)>> {
+    let mut stmt = conn.prepare("SELECT generation, wrapped_key FROM encryption_keys")?;
+    let keys = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(keys)
+}
+
+/// The key-encryption key `source` yields, checked against the database
+fn wrapping_key(header: &Header, source: KeySource<'_>) -> Result<SecretKey> {
+    let key = match (source, header.key_source.as_str()) {
+        (KeySource::Passphrase(passphrase), "passphrase") => {
+            let salt = header.kdf_salt.as_deref().unwrap_or_default();
+            derive_key(passphrase, salt)?
+        }
+        (KeySource::Keyring(store), "keyring") => {
+            let account = header.keyring_account.as_deref().unwrap_or_default();
+            store.load(account)?.ok_or_else(|| {
+                EncryptionError::Keyring("the database key is not in the keyring".into())
+            })?
+        }
+        (_, "keyring") => bail!("The database key is kept in the keyring"),
+        _ => bail!("The database is protected by a passphrase"),
+    };
+    if hmac_sha256(key.as_bytes(), KEY_CHECK)[..] != header.key_check[..] {
+        return Err(EncryptionError::WrongKey.into());
+    }
+    Ok(key)
+}
+
+/// A fresh key-encryption key from `source`, stored in the keyring if
+/// that is where it is kept
+fn new_wrapping(source: KeySource<'_>) -> Result<Wrapping> {
+    match source {
+        KeySource::Passphrase(passphrase) => {
+            if passphrase.is_empty() {
+                bail!("The passphrase is empty");
+            }
+            let salt: [u8; SALT_LEN] = random_bytes();
+            Ok(Wrapping {
+                key: derive_key(passphrase, &salt)?,
+                key_source: "passphrase",
+                kdf_salt: Some(salt.to_vec()),
+                keyring_account: None,
+            })
+        }
+        KeySource::Keyring(store) => {
+            let key = SecretKey::generate();
+            let account = format!("database-{}", uuid::Uuid::new_v4());
+            store.store(&account, &key)?;
+            Ok(Wrapping {
+                key,
+                key_source: "keyring",
+                kdf_salt: None,
+                keyring_account: Some(account),
+            })
+        }
+    }
+}
+
+/// Remove a keyring entry that is no longer used, if `source` has one
+fn forget_keyring_entry(source: KeySource<'_>, account: Option<&str>) {
+    if let (KeySource::Keyring(store), Some(account)) = (source, account) {
+        if let Err(e) = store.delete(account) {
+            tracing::warn!("Failed to remove unused key from the keyring: {}", e);
+        }
+    }
+}
@@ -0,0 +1,72 @@
+-- Database as created before schema versioning (user_version 0, v1 schema)
+
+CREATE TABLE projects (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    path TEXT NOT NULL UNIQUE,
+    is_favorite INTEGER NOT NULL DEFAULT 0,
+    tags TEXT NOT NULL DEFAULT '[]',
+    last_accessed TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE TABLE conversations (
+    id TEXT PRIMARY KEY,
+    project_id TEXT,
+    title TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE SET NULL
+);
+
+CREATE TABLE messages (
+    id TEXT PRIMARY KEY,
+    conversation_id TEXT NOT NULL,
+    role TEXT NOT NULL,
+    content TEXT NOT NULL,
+    tool_name TEXT,
+    is_error INTEGER NOT NULL DEFAULT 0,
+    timestamp TEXT NOT NULL,
+    FOREIGN KEY (conversation_id) REFERENCES conversations(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_projects_last_accessed ON projects(last_accessed DESC);
+CREATE INDEX idx_conversations_project ON conversations(project_id);
+CREATE INDEX idx_conversations_updated ON conversations(updated_at DESC);
+CREATE INDEX idx_messages_conversation ON messages(conversation_id);
+CREATE INDEX idx_messages_timestamp ON messages(timestamp);
+
+CREATE VIRTUAL TABLE messages_fts USING fts5(
+    content,
+    content='messages',
+    content_rowid='rowid'
+);
+
+CREATE TRIGGER messages_ai AFTER INSERT ON messages BEGIN
+    INSERT INTO messages_fts(rowid, content) VALUES (new.rowid, new.content);
+END;
+
+CREATE TRIGGER messages_ad AFTER DELETE ON messages BEGIN
+    INSERT INTO messages_fts(messages_fts, rowid, content) VALUES('delete', old.rowid, old.content);
+END;
+
+CREATE TRIGGER messages_au AFTER UPDATE ON messages BEGIN
+    INSERT INTO messages_fts(messages_fts, rowid, content) VALUES('delete', old.rowid, old.content);
+    INSERT INTO messages_fts(rowid, content) VALUES (new.rowid, new.content);
+END;
+
+INSERT INTO projects VALUES (
+    'p1', 'claude-visual', '/home/user/claude-visual', 1, '["rust"]',
+    '2024-05-01T10:00:00+00:00', '2024-04-01T09:00:00+00:00'
+);
+INSERT INTO conversations VALUES (
+    'c1', 'p1', 'Fix the parser', '2024-05-01T10:00:00+00:00', '2024-05-01T10:05:00+00:00'
+);
+INSERT INTO messages VALUES (
+    'm1', 'c1', 'user', 'Why does the tokenizer panic on emoji?', NULL, 0,
+    '2024-05-01T10:00:00+00:00'
+);
+INSERT INTO messages VALUES (
+    'm2', 'c1', 'assistant', 'The tokenizer slices bytes instead of chars.', NULL, 0,
+    '2024-05-01T10:01:00+00:00'
+);
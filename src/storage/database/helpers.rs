@@ -1,6 +1,16 @@
 //! Helper functions for database operations
 
-use crate::storage::models::Conversation;
+use chrono::{DateTime, Utc};
+
+use crate::storage::models::{Conversation, Message};
+
+/// Columns read by [`row_to_message`], from `messages` aliased as `m`
+pub(crate) const MESSAGE_COLUMNS: &str = "m.id, m.conversation_id, m.role, \
+     decompress_text(m.content, m.content_codec), m.tool_name, m.is_error, m.timestamp, \
+     m.parent_id, m.tool_use_id, decompress_text(m.metadata, m.metadata_codec), m.session_id";
+
+/// Number of columns in [`MESSAGE_COLUMNS`]
+pub(crate) const MESSAGE_COLUMN_COUNT: usize = 11;
 
 pub(crate) fn row_to_conversation(row: &rusqlite::Row<'_>) -> rusqlite::Result<Conversation> {
     let created_at: String = row.get(3)?;
@@ -10,11 +20,47 @@ pub(crate) fn row_to_conversation(row: &rusqlite::Row<'_>) -> rusqlite::Result<C
         id: row.get(0)?,
         project_id: row.get(1)?,
         title: row.get(2)?,
-        created_at: chrono::DateTime::parse_from_rfc3339(&created_at)
-            .map(|dt| dt.with_timezone(&chrono::Utc))
-            .unwrap_or_else(|_| chrono::Utc::now()),
-        updated_at: chrono::DateTime::parse_from_rfc3339(&updated_at)
-            .map(|dt| dt.with_timezone(&chrono::Utc))
-            .unwrap_or_else(|_| chrono::Utc::now()),
+        created_at: parse_timestamp(&created_at),
+        updated_at: parse_timestamp(&updated_at),
+    })
+}
+
+pub(crate) fn row_to_message(row: &rusqlite::Row<'_>) -> rusqlite::Result<Message> {
+    let timestamp: String = row.get(6)?;
+    let metadata: Option<String> = row.get(9)?;
+
+    Ok(Message {
+        id: row.get(0)?,
+        conversation_id: row.get(1)?,
+        role: row.get(2)?,
+        content: row.get(3)?,
+        tool_name: row.get(4)?,
+        is_error: row.get::<_, i32>(5)? != 0,
+        timestamp: parse_timestamp(&timestamp),
+        parent_id: row.get(7)?,
+        tool_use_id: row.get(8)?,
+        session_id: row.get(10)?,
+        metadata: metadata
+            .and_then(|m| serde_json::from_str(&m).ok())
+            .unwrap_or_default(),
     })
 }
+
+/// Parse a stored RFC 3339 timestamp, falling back to now
+pub(crate) fn parse_timestamp(value: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+}
+
+/// FTS5 query matching every word of `query` as a prefix
+///
+/// Quoting each word keeps FTS5 syntax in user input from being interpreted.
+pub(crate) fn prefix_query(query: &str) -> String {
+    query
+        .replace('"', "\"\"")
+        .split_whitespace()
+        .map(|word| format!("\"{}\"*", word))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,155 @@
+//! MCP call audit log operations
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, params_from_iter};
+
+use crate::storage::models::{McpCallApproval, McpCallFilter, McpCallKind, McpCallRecord};
+
+use super::Database;
+
+const MCP_CALL_COLUMNS: &str = "id, conversation_id, server, kind, name, arguments, \
+     result_summary, is_error, duration_ms, approval, timestamp";
+
+impl Database {
+    /// Record an MCP call
+    pub fn insert_mcp_call(&self, call: &McpCallRecord) -> Result<()> {
+        let arguments = call
+            .arguments
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+        self.writer()?.execute(
+            "INSERT INTO mcp_calls (id, conversation_id, server, kind, name, arguments,
+                 result_summary, is_error, duration_ms, approval, timestamp)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                call.id,
+                call.conversation_id,
+                call.server,
+                call.kind.as_str(),
+                call.name,
+                arguments,
+                call.result_summary,
+                call.is_error as i32,
+                call.duration_ms as i64,
+                call.approval.as_str(),
+                call.timestamp.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Get a recorded MCP call by ID
+    pub fn get_mcp_call(&self, id: &str) -> Result<Option<McpCallRecord>> {
+        let conn = self.reader()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM mcp_calls WHERE id = ?1",
+            MCP_CALL_COLUMNS
+        ))?;
+
+        match stmt.query_row(params![id], Self::row_to_mcp_call) {
+            Ok(call) => Ok(Some(call)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// List recorded MCP calls matching a filter, newest first
+    pub fn list_mcp_calls(
+        &self,
+        filter: &McpCallFilter,
+        limit: usize,
+    ) -> Result<Vec<McpCallRecord>> {
+        let mut where_clauses = Vec::new();
+        let mut values: Vec<SqlValue> = Vec::new();
+        let mut push = |clause: &str, value: String| {
+            values.push(SqlValue::Text(value));
+            where_clauses.push(clause.replace('?', &format!("?{}", values.len())));
+        };
+
+        if let Some(conversation_id) = &filter.conversation_id {
+            push("conversation_id = ?", conversation_id.clone());
+        }
+        if let Some(server) = &filter.server {
+            push("server = ?", server.clone());
+        }
+        if let Some(kind) = filter.kind {
+            push("kind = ?", kind.as_str().to_string());
+        }
+        if let Some(start) = filter.date_range.start_date() {
+            push("timestamp >= ?", start.to_rfc3339());
+        }
+        if let Some(query) = filter.query.as_deref().filter(|q| !q.trim().is_empty()) {
+            let pattern = format!(
+                "%{}%",
+                query
+                    .trim()
+                    .replace('\\', "\\\\")
+                    .replace('%', "\\%")
+                    .replace('_', "\\_")
+            );
+            push(
+                "(name LIKE ? ESCAPE '\\' OR arguments LIKE ? ESCAPE '\\' \
+                 OR result_summary LIKE ? ESCAPE '\\')",
+                pattern,
+            );
+        }
+        if filter.errors_only {
+            where_clauses.push("is_error = 1".to_string());
+        }
+
+        let where_clause = if where_clauses.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", where_clauses.join(" AND "))
+        };
+        values.push(SqlValue::Integer(limit as i64));
+        let sql = format!(
+            "SELECT {} FROM mcp_calls {} ORDER BY timestamp DESC LIMIT ?{}",
+            MCP_CALL_COLUMNS,
+            where_clause,
+            values.len()
+        );
+
+        let conn = self.reader()?;
+        let mut stmt = conn.prepare(&sql)?;
+        let calls = stmt
+            .query_map(params_from_iter(values), Self::row_to_mcp_call)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(calls)
+    }
+
+    /// Delete calls recorded before a date; returns how many were removed
+    pub fn delete_mcp_calls_before(&self, before: DateTime<Utc>) -> Result<usize> {
+        let deleted = self.writer()?.execute(
+            "DELETE FROM mcp_calls WHERE timestamp < ?1",
+            params![before.to_rfc3339()],
+        )?;
+        Ok(deleted)
+    }
+
+    fn row_to_mcp_call(row: &rusqlite::Row<'_>) -> rusqlite::Result<McpCallRecord> {
+        let kind: String = row.get(3)?;
+        let arguments: Option<String> = row.get(5)?;
+        let approval: String = row.get(9)?;
+        let timestamp: String = row.get(10)?;
+
+        Ok(McpCallRecord {
+            id: row.get(0)?,
+            conversation_id: row.get(1)?,
+            server: row.get(2)?,
+            kind: McpCallKind::parse(&kind).unwrap_or(McpCallKind::Tool),
+            name: row.get(4)?,
+            arguments: arguments.and_then(|a| serde_json::from_str(&a).ok()),
+            result_summary: row.get(6)?,
+            is_error: row.get::<_, i32>(7)? != 0,
+            duration_ms: row.get::<_, i64>(8)?.max(0) as u64,
+            approval: McpCallApproval::parse(&approval).unwrap_or_default(),
+            timestamp: chrono::DateTime::parse_from_rfc3339(&timestamp)
+                .map(|dt| dt.with_timezone(&chrono::Utc))
+                .unwrap_or_else(|_| chrono::Utc::now()),
+        })
+    }
+}
@@ -0,0 +1,488 @@
+//! Versioned schema migrations
+//!
+//! The schema version is kept in `PRAGMA user_version`. Each migration runs
+//! in its own transaction together with the version bump, so a failed step
+//! leaves the database at the previous version. Databases created before
+//! versioning report version 0 and hold the version 1 schema; migration 1
+//! only creates what is missing, so it is safe to run on them.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use rusqlite::Connection;
+
+use super::Database;
+
+/// One step of the schema history
+pub(crate) struct Migration {
+    /// Version the database is at after this step
+    pub version: u32,
+    /// What the step changes
+    pub description: &'static str,
+    /// Statements to run
+    pub sql: &'static str,
+}
+
+/// All migrations, in order; append only, never edit a released step
+pub(crate) const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "projects, conversations and messages with full-text search",
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS projects (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                path TEXT NOT NULL UNIQUE,
+                is_favorite INTEGER NOT NULL DEFAULT 0,
+                tags TEXT NOT NULL DEFAULT '[]',
+                last_accessed TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS conversations (
+                id TEXT PRIMARY KEY,
+                project_id TEXT,
+                title TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE SET NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS messages (
+                id TEXT PRIMARY KEY,
+                conversation_id TEXT NOT NULL,
+                role TEXT NOT NULL,
+                content TEXT NOT NULL,
+                tool_name TEXT,
+                is_error INTEGER NOT NULL DEFAULT 0,
+                timestamp TEXT NOT NULL,
+                FOREIGN KEY (conversation_id) REFERENCES conversations(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_projects_last_accessed ON projects(last_accessed DESC);
+            CREATE INDEX IF NOT EXISTS idx_conversations_project ON conversations(project_id);
+            CREATE INDEX IF NOT EXISTS idx_conversations_updated ON conversations(updated_at DESC);
+            CREATE INDEX IF NOT EXISTS idx_messages_conversation ON messages(conversation_id);
+            CREATE INDEX IF NOT EXISTS idx_messages_timestamp ON messages(timestamp);
+
+            -- FTS5 virtual table for full-text search on messages
+            CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
+                content,
+                content='messages',
+                content_rowid='rowid'
+            );
+
+            -- Triggers to keep FTS index in sync
+            CREATE TRIGGER IF NOT EXISTS messages_ai AFTER INSERT ON messages BEGIN
+                INSERT INTO messages_fts(rowid, content) VALUES (new.rowid, new.content);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS messages_ad AFTER DELETE ON messages BEGIN
+                INSERT INTO messages_fts(messages_fts, rowid, content) VALUES('delete', old.rowid, old.content);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS messages_au AFTER UPDATE ON messages BEGIN
+                INSERT INTO messages_fts(messages_fts, rowid, content) VALUES('delete', old.rowid, old.content);
+                INSERT INTO messages_fts(rowid, content) VALUES (new.rowid, new.content);
+            END;
+        "#,
+    },
+    Migration {
+        version: 2,
+        description: "MCP call audit log",
+        // No foreign key: calls may outlive or precede their conversation
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS mcp_calls (
+                id TEXT PRIMARY KEY,
+                conversation_id TEXT,
+                server TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                name TEXT NOT NULL,
+                arguments TEXT,
+                result_summary TEXT NOT NULL,
+                is_error INTEGER NOT NULL DEFAULT 0,
+                duration_ms INTEGER NOT NULL,
+                approval TEXT NOT NULL,
+                timestamp TEXT NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_mcp_calls_conversation ON mcp_calls(conversation_id, timestamp);
+            CREATE INDEX IF NOT EXISTS idx_mcp_calls_timestamp ON mcp_calls(timestamp DESC);
+        "#,
+    },
+    Migration {
+        version: 3,
+        description: "compressed message content",
+        // Compressed rows hold a blob in `content` and the codec name in
+        // `content_codec`; full-text search indexes the decoded text through
+        // the `messages_text` view
+        sql: r#"
+            ALTER TABLE messages ADD COLUMN content_codec TEXT;
+
+            DROP TRIGGER IF EXISTS messages_ai;
+            DROP TRIGGER IF EXISTS messages_ad;
+            DROP TRIGGER IF EXISTS messages_au;
+            DROP TABLE IF EXISTS messages_fts;
+
+            CREATE VIEW messages_text AS
+                SELECT rowid AS message_rowid, decompress_text(content, content_codec) AS content
+                FROM messages;
+
+            CREATE VIRTUAL TABLE messages_fts USING fts5(
+                content,
+                content='messages_text',
+                content_rowid='message_rowid'
+            );
+
+            CREATE TRIGGER messages_ai AFTER INSERT ON messages BEGIN
+                INSERT INTO messages_fts(rowid, content)
+                VALUES (new.rowid, decompress_text(new.content, new.content_codec));
+            END;
+
+            CREATE TRIGGER messages_ad AFTER DELETE ON messages BEGIN
+                INSERT INTO messages_fts(messages_fts, rowid, content)
+                VALUES('delete', old.rowid, decompress_text(old.content, old.content_codec));
+            END;
+
+            CREATE TRIGGER messages_au AFTER UPDATE ON messages BEGIN
+                INSERT INTO messages_fts(messages_fts, rowid, content)
+                VALUES('delete', old.rowid, decompress_text(old.content, old.content_codec));
+                INSERT INTO messages_fts(rowid, content)
+                VALUES (new.rowid, decompress_text(new.content, new.content_codec));
+            END;
+
+            INSERT INTO messages_fts(messages_fts) VALUES('rebuild');
+        "#,
+    },
+    Migration {
+        version: 4,
+        description: "message parent links, tool call ids and metadata",
+        // Existing messages are chained in timestamp order. The update
+        // trigger is narrowed first so linking does not re-index every row.
+        sql: r#"
+            ALTER TABLE messages ADD COLUMN parent_id TEXT
+                REFERENCES messages(id) ON DELETE SET NULL;
+            ALTER TABLE messages ADD COLUMN tool_use_id TEXT;
+            ALTER TABLE messages ADD COLUMN metadata TEXT;
+
+            CREATE INDEX IF NOT EXISTS idx_messages_parent ON messages(parent_id);
+            CREATE INDEX IF NOT EXISTS idx_messages_tool_use ON messages(conversation_id, tool_use_id);
+
+            DROP TRIGGER IF EXISTS messages_au;
+            CREATE TRIGGER messages_au AFTER UPDATE OF content, content_codec ON messages BEGIN
+                INSERT INTO messages_fts(messages_fts, rowid, content)
+                VALUES('delete', old.rowid, decompress_text(old.content, old.content_codec));
+                INSERT INTO messages_fts(rowid, content)
+                VALUES (new.rowid, decompress_text(new.content, new.content_codec));
+            END;
+
+            UPDATE messages SET parent_id = (
+                SELECT p.id FROM messages p
+                WHERE p.conversation_id = messages.conversation_id
+                  AND (p.timestamp < messages.timestamp
+                       OR (p.timestamp = messages.timestamp AND p.rowid < messages.rowid))
+                ORDER BY p.timestamp DESC, p.rowid DESC
+                LIMIT 1
+            );
+        "#,
+    },
+    Migration {
+        version: 5,
+        description: "conversation branches and CLI session ids",
+        // No foreign key on the pointer: readers fall back to the newest
+        // message when it is missing or stale
+        sql: r#"
+            ALTER TABLE conversations ADD COLUMN active_leaf_id TEXT;
+            ALTER TABLE messages ADD COLUMN session_id TEXT;
+
+            CREATE INDEX IF NOT EXISTS idx_messages_session ON messages(session_id);
+
+            -- A new message continues, and so activates, its branch
+            CREATE TRIGGER messages_active_leaf AFTER INSERT ON messages BEGIN
+                UPDATE conversations SET active_leaf_id = new.id WHERE id = new.conversation_id;
+            END;
+        "#,
+    },
+    Migration {
+        version: 6,
+        description: "bookmarks, pins, tags, reactions and notes",
+        // Everything cascades from its message or conversation. A
+        // conversation has at most one note not attached to a message.
+        sql: r#"
+            CREATE TABLE message_bookmarks (
+                message_id TEXT PRIMARY KEY REFERENCES messages(id) ON DELETE CASCADE,
+                label TEXT,
+                created_at TEXT NOT NULL
+            );
+
+            CREATE TABLE pinned_messages (
+                message_id TEXT PRIMARY KEY REFERENCES messages(id) ON DELETE CASCADE,
+                created_at TEXT NOT NULL
+            );
+
+            CREATE TABLE conversation_tags (
+                conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+                tag TEXT NOT NULL COLLATE NOCASE,
+                created_at TEXT NOT NULL,
+                PRIMARY KEY (conversation_id, tag)
+            );
+
+            CREATE TABLE message_reactions (
+                message_id TEXT NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+                emoji TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                PRIMARY KEY (message_id, emoji)
+            );
+
+            CREATE TABLE notes (
+                id TEXT PRIMARY KEY,
+                conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+                message_id TEXT REFERENCES messages(id) ON DELETE CASCADE,
+                content TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+
+            CREATE INDEX idx_conversation_tags_tag ON conversation_tags(tag);
+            CREATE INDEX idx_notes_conversation ON notes(conversation_id);
+            CREATE INDEX idx_notes_message ON notes(message_id);
+            CREATE UNIQUE INDEX idx_notes_conversation_note ON notes(conversation_id)
+                WHERE message_id IS NULL;
+
+            CREATE VIRTUAL TABLE notes_fts USING fts5(
+                content,
+                content='notes',
+                content_rowid='rowid'
+            );
+
+            CREATE TRIGGER notes_ai AFTER INSERT ON notes BEGIN
+                INSERT INTO notes_fts(rowid, content) VALUES (new.rowid, new.content);
+            END;
+
+            CREATE TRIGGER notes_ad AFTER DELETE ON notes BEGIN
+                INSERT INTO notes_fts(notes_fts, rowid, content) VALUES('delete', old.rowid, old.content);
+            END;
+
+            CREATE TRIGGER notes_au AFTER UPDATE OF content ON notes BEGIN
+                INSERT INTO notes_fts(notes_fts, rowid, content) VALUES('delete', old.rowid, old.content);
+                INSERT INTO notes_fts(rowid, content) VALUES (new.rowid, new.content);
+            END;
+        "#,
+    },
+    Migration {
+        version: 7,
+        description: "encryption at rest",
+        // `encryption` has a row only while the database is encrypted. Data
+        // keys are wrapped by the key-encryption key; older generations stay
+        // until nothing is sealed with them. Search indexes `index_text`,
+        // which is the text of plain rows, so nothing is re-indexed here.
+        sql: r#"
+            CREATE TABLE encryption (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                key_source TEXT NOT NULL,
+                kdf_salt BLOB,
+                keyring_account TEXT,
+                key_check BLOB NOT NULL,
+                current_generation INTEGER NOT NULL,
+                created_at TEXT NOT NULL,
+                rotated_at TEXT
+            );
+
+            CREATE TABLE encryption_keys (
+                generation INTEGER PRIMARY KEY,
+                wrapped_key BLOB NOT NULL,
+                created_at TEXT NOT NULL
+            );
+
+            DROP TRIGGER IF EXISTS messages_ai;
+            DROP TRIGGER IF EXISTS messages_ad;
+            DROP TRIGGER IF EXISTS messages_au;
+            DROP VIEW IF EXISTS messages_text;
+
+            CREATE VIEW messages_text AS
+                SELECT rowid AS message_rowid, index_text(content, content_codec) AS content
+                FROM messages;
+
+            CREATE TRIGGER messages_ai AFTER INSERT ON messages BEGIN
+                INSERT INTO messages_fts(rowid, content)
+                VALUES (new.rowid, index_text(new.content, new.content_codec));
+            END;
+
+            CREATE TRIGGER messages_ad AFTER DELETE ON messages BEGIN
+                INSERT INTO messages_fts(messages_fts, rowid, content)
+                VALUES('delete', old.rowid, index_text(old.content, old.content_codec));
+            END;
+
+            CREATE TRIGGER messages_au AFTER UPDATE OF content, content_codec ON messages BEGIN
+                INSERT INTO messages_fts(messages_fts, rowid, content)
+                VALUES('delete', old.rowid, index_text(old.content, old.content_codec));
+                INSERT INTO messages_fts(rowid, content)
+                VALUES (new.rowid, index_text(new.content, new.content_codec));
+            END;
+        "#,
+    },
+    Migration {
+        version: 8,
+        description: "Claude CLI transcript imports",
+        // One row per transcript file read. No foreign key on the
+        // conversation: when it is deleted the row stays, so the session
+        // is not imported again.
+        sql: r#"
+            CREATE TABLE transcript_imports (
+                path TEXT PRIMARY KEY,
+                session_id TEXT NOT NULL,
+                conversation_id TEXT,
+                byte_offset INTEGER NOT NULL,
+                last_message_id TEXT,
+                last_timestamp TEXT,
+                skipped INTEGER NOT NULL DEFAULT 0,
+                imported_at TEXT NOT NULL
+            );
+        "#,
+    },
+    Migration {
+        version: 9,
+        description: "sealed message metadata",
+        // Metadata carries tool inputs such as file bodies and commands, so
+        // it is stored like the content, in the form `metadata_codec` names
+        sql: r#"
+            ALTER TABLE messages ADD COLUMN metadata_codec TEXT;
+        "#,
+    },
+];
+
+/// Schema version this build creates and understands
+pub const SCHEMA_VERSION: u32 = MIGRATIONS[MIGRATIONS.len() - 1].version;
+
+impl Database {
+    /// Schema version recorded in the database
+    pub fn schema_version(&self) -> Result<u32> {
+        let conn = self.reader()?;
+        user_version(&conn)
+    }
+
+    /// Bring the schema up to [`SCHEMA_VERSION`]
+    ///
+    /// Databases with data are copied to a backup next to the database file
+    /// first. Returns the path of the backup, if one was made.
+    pub fn migrate(&self) -> Result<Option<PathBuf>> {
+        self.migrate_to(SCHEMA_VERSION)
+    }
+
+    /// Apply pending migrations up to and including `target`
+    pub(crate) fn migrate_to(&self, target: u32) -> Result<Option<PathBuf>> {
+        let conn = self.writer()?;
+        let current = user_version(&conn)?;
+        if current > SCHEMA_VERSION {
+            bail!(
+                "Database schema version {} is newer than this version of Claude Visual supports ({}); \
+                 please update the app",
+                current,
+                SCHEMA_VERSION
+            );
+        }
+
+        let pending: Vec<&Migration> = MIGRATIONS
+            .iter()
+            .filter(|m| m.version > current && m.version <= target)
+            .collect();
+        if pending.is_empty() {
+            return Ok(None);
+        }
+
+        let backup = if has_tables(&conn)? {
+            backup(&conn, current)?
+        } else {
+            None
+        };
+
+        for migration in pending {
+            let tx = conn.unchecked_transaction()?;
+            tx.execute_batch(migration.sql).with_context(|| {
+                format!(
+                    "Migration {} ({}) failed",
+                    migration.version, migration.description
+                )
+            })?;
+            tx.pragma_update(None, "user_version", migration.version)?;
+            tx.commit()?;
+            tracing::info!(
+                "Migrated database to version {}: {}",
+                migration.version,
+                migration.description
+            );
+        }
+        Ok(backup)
+    }
+}
+
+/// Copy the database file before migrating from `version`, replacing
+/// backups of earlier migrations
+fn backup(conn: &Connection, version: u32) -> Result<Option<PathBuf>> {
+    let Some(path) = conn.path().filter(|p| !p.is_empty()) else {
+        return Ok(None);
+    };
+    remove_backups(conn)?;
+    let backup = PathBuf::from(format!(
+        "{}.v{}-{}.bak",
+        path,
+        version,
+        chrono::Utc::now().format("%Y%m%d%H%M%S")
+    ));
+    if backup.exists() {
+        std::fs::remove_file(&backup)?;
+    }
+    conn.execute("VACUUM INTO ?1", [backup.to_string_lossy()])
+        .with_context(|| format!("Failed to back up database to {}", backup.display()))?;
+    tracing::info!("Backed up database to {}", backup.display());
+    Ok(Some(backup))
+}
+
+/// Backups made before migrations, `<database>.v<version>-<time>.bak`
+fn backups(conn: &Connection) -> Result<Vec<PathBuf>> {
+    let Some(path) = conn.path().filter(|p| !p.is_empty()).map(Path::new) else {
+        return Ok(Vec::new());
+    };
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return Ok(Vec::new());
+    };
+    let prefix = format!("{}.v", name.to_string_lossy());
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Ok(Vec::new());
+    };
+    Ok(entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|backup| {
+            backup.file_name().is_some_and(|file| {
+                let file = file.to_string_lossy();
+                file.starts_with(&prefix) && file.ends_with(".bak")
+            })
+        })
+        .collect())
+}
+
+/// Delete the backups made before migrations, returning how many there were
+pub(crate) fn remove_backups(conn: &Connection) -> Result<usize> {
+    let backups = backups(conn)?;
+    for backup in &backups {
+        std::fs::remove_file(backup)
+            .with_context(|| format!("Failed to remove backup {}", backup.display()))?;
+        tracing::info!("Removed database backup {}", backup.display());
+    }
+    Ok(backups.len())
+}
+
+/// Whether the database holds any tables yet
+fn has_tables(conn: &Connection) -> Result<bool> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table'",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+fn user_version(conn: &Connection) -> Result<u32> {
+    Ok(conn.query_row("PRAGMA user_version", [], |row| row.get(0))?)
+}
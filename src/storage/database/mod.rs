@@ -1,9 +1,23 @@
 //! SQLite database operations
 
+mod annotations;
+mod background;
+mod branches;
 mod conversations;
 mod core;
+mod encryption;
 mod helpers;
+mod mcp_calls;
 mod messages;
+mod migrations;
 mod projects;
+mod retention;
+mod search;
+mod transcripts;
+
+#[cfg(test)]
+mod tests;
 
 pub use core::Database;
+pub use encryption::{EncryptionStatus, KeySource};
+pub use migrations::SCHEMA_VERSION;
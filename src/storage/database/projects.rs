@@ -13,7 +13,7 @@ impl Database {
     /// Insert a new project
     pub fn insert_project(&self, project: &Project) -> Result<()> {
         let tags_json = serde_json::to_string(&project.tags)?;
-        self.conn.execute(
+        self.writer()?.execute(
             "INSERT INTO projects (id, name, path, is_favorite, tags, last_accessed, created_at)
              VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
             params![
@@ -31,7 +31,8 @@ impl Database {
 
     /// Get a project by ID
     pub fn get_project(&self, id: &str) -> Result<Option<Project>> {
-        let mut stmt = self.conn.prepare(
+        let conn = self.reader()?;
+        let mut stmt = conn.prepare(
             "SELECT id, name, path, is_favorite, tags, last_accessed, created_at FROM projects WHERE id = ?1",
         )?;
 
@@ -66,7 +67,8 @@ impl Database {
 
     /// List all projects
     pub fn list_projects(&self) -> Result<Vec<Project>> {
-        let mut stmt = self.conn.prepare(
+        let conn = self.reader()?;
+        let mut stmt = conn.prepare(
             "SELECT id, name, path, is_favorite, tags, last_accessed, created_at FROM projects ORDER BY last_accessed DESC",
         )?;
 
@@ -100,7 +102,7 @@ impl Database {
     /// Update a project
     pub fn update_project(&self, project: &Project) -> Result<()> {
         let tags_json = serde_json::to_string(&project.tags)?;
-        self.conn.execute(
+        self.writer()?.execute(
             "UPDATE projects SET name = ?1, path = ?2, is_favorite = ?3, tags = ?4, last_accessed = ?5 WHERE id = ?6",
             params![
                 project.name,
@@ -116,7 +118,7 @@ impl Database {
 
     /// Delete a project
     pub fn delete_project(&self, id: &str) -> Result<()> {
-        self.conn
+        self.writer()?
             .execute("DELETE FROM projects WHERE id = ?1", params![id])?;
         Ok(())
     }
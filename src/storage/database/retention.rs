@@ -0,0 +1,127 @@
+//! Retention database operations
+
+use std::collections::HashSet;
+
+use anyhow::Result;
+use rusqlite::params;
+
+use crate::storage::cleanup::CleanupItem;
+
+use super::helpers::parse_timestamp;
+use super::Database;
+
+/// IDs of conversations retention never deletes: those in favorite
+/// projects and those with bookmarked or pinned messages
+const PROTECTED_CONVERSATIONS: &str = "
+    SELECT c.id FROM conversations c
+    JOIN projects p ON p.id = c.project_id
+    WHERE p.is_favorite != 0
+    UNION
+    SELECT m.conversation_id FROM message_bookmarks b
+    JOIN messages m ON m.id = b.message_id
+    UNION
+    SELECT m.conversation_id FROM pinned_messages p
+    JOIN messages m ON m.id = p.message_id";
+
+impl Database {
+    /// Every conversation as a cleanup item, most recently active first
+    ///
+    /// The size counts stored message bytes; activity is the later of the
+    /// conversation's update and its last message.
+    pub fn conversation_cleanup_items(&self) -> Result<Vec<CleanupItem>> {
+        let sql = format!(
+            r#"
+            SELECT
+                c.id,
+                c.title,
+                COALESCE(s.bytes, 0),
+                MAX(c.updated_at, COALESCE(s.last_message, '')) AS last_active,
+                c.id IN ({})
+            FROM conversations c
+            LEFT JOIN (
+                SELECT
+                    conversation_id,
+                    SUM(LENGTH(CAST(content AS BLOB))) AS bytes,
+                    MAX(timestamp) AS last_message
+                FROM messages
+                GROUP BY conversation_id
+            ) s ON s.conversation_id = c.id
+            ORDER BY last_active DESC
+            "#,
+            PROTECTED_CONVERSATIONS
+        );
+
+        let conn = self.reader()?;
+        let mut stmt = conn.prepare(&sql)?;
+        let items = stmt
+            .query_map([], |row| {
+                let last_active: String = row.get(3)?;
+                let item = CleanupItem::conversation(
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)? as u64,
+                    parse_timestamp(&last_active),
+                );
+                Ok(if row.get::<_, bool>(4)? {
+                    item.protect()
+                } else {
+                    item
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(items)
+    }
+
+    /// Delete conversations in one transaction, skipping protected ones
+    ///
+    /// Protection is checked again inside the transaction, so a conversation
+    /// bookmarked since it was listed survives. Returns the deleted IDs.
+    /// Fails while an encrypted database is locked.
+    pub fn delete_unprotected_conversations(&self, ids: &[String]) -> Result<HashSet<String>> {
+        self.ensure_unlocked()?;
+        let sql = format!(
+            "DELETE FROM conversations WHERE id = ?1 AND id NOT IN ({})",
+            PROTECTED_CONVERSATIONS
+        );
+
+        let conn = self.writer()?;
+        let tx = conn.unchecked_transaction()?;
+        let mut deleted = HashSet::new();
+        {
+            let mut stmt = tx.prepare(&sql)?;
+            for id in ids {
+                if stmt.execute(params![id])? > 0 {
+                    deleted.insert(id.clone());
+                }
+            }
+        }
+        tx.commit()?;
+
+        Ok(deleted)
+    }
+
+    /// Size of the database file in bytes, without the WAL
+    pub fn database_size(&self) -> Result<u64> {
+        let size: i64 = self.reader()?.query_row(
+            "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(size as u64)
+    }
+
+    /// Merge the search indexes and give free pages back to the filesystem
+    pub fn optimize_storage(&self) -> Result<()> {
+        let conn = self.writer()?;
+        conn.execute_batch(
+            "INSERT INTO messages_fts(messages_fts) VALUES('optimize');
+             INSERT INTO notes_fts(notes_fts) VALUES('optimize');
+             PRAGMA optimize;
+             VACUUM;",
+        )?;
+        // Truncates the WAL too; a no-op for other journal modes
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+        Ok(())
+    }
+}
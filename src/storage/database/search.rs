@@ -0,0 +1,292 @@
+//! Conversation search operations
+
+use anyhow::Result;
+use rusqlite::params_from_iter;
+use rusqlite::types::Value as SqlValue;
+
+use crate::storage::encryption::index;
+use crate::storage::models::{SearchFilter, SearchResult};
+use crate::storage::pagination::{Cursor, PageInfo, PaginatedResult, PaginationRequest};
+use crate::storage::search::SearchQuery;
+
+use super::helpers::{row_to_message, MESSAGE_COLUMNS, MESSAGE_COLUMN_COUNT};
+use super::Database;
+
+/// Age in days at which a match gets half the recency boost of a new one
+const RECENCY_DAYS: f64 = 30.0;
+
+/// Tokens around the matches in a snippet
+const SNIPPET_TOKENS: usize = 16;
+
+/// Characters shown of a message matched by filters alone
+const PLAIN_SNIPPET_CHARS: usize = 160;
+
+impl Database {
+    /// Search messages using full-text search
+    pub fn search_messages(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
+        self.search_messages_with_filter(query, &SearchFilter::default(), limit)
+    }
+
+    /// Search messages with optional filters
+    pub fn search_messages_with_filter(
+        &self,
+        query: &str,
+        filter: &SearchFilter,
+        limit: usize,
+    ) -> Result<Vec<SearchResult>> {
+        Ok(self
+            .search(query, filter, &PaginationRequest::first(limit))?
+            .items)
+    }
+
+    /// Search messages with the query language of [`SearchQuery::parse`]
+    ///
+    /// Text matches are ranked by BM25, boosted for recent messages, and
+    /// messages also match through the text of notes attached to them.
+    /// Queries of filters alone list the newest messages first. Pages use
+    /// offset cursors; as results are ranked, a request without a cursor
+    /// starts at the best match in either direction.
+    ///
+    /// In an encrypted database message text is matched through its blind
+    /// index (see [`crate::storage::encryption`]) and highlighted after
+    /// decryption; searching fails while it is locked.
+    pub fn search(
+        &self,
+        query: &str,
+        filter: &SearchFilter,
+        page: &PaginationRequest,
+    ) -> Result<PaginatedResult<SearchResult>> {
+        let query = SearchQuery::parse(query)?;
+        if query.is_empty() || page.limit == 0 {
+            return Ok(PaginatedResult::empty());
+        }
+        let query = query.with_filter(filter);
+        let fts = query.fts_expression();
+
+        // Encrypted messages are indexed as hashes of their words
+        let index_key = if self.keys.is_enabled() {
+            Some(self.keys.key(self.keys.current_generation()?)?)
+        } else {
+            None
+        };
+        let (message_fts, excluded, words) = match &index_key {
+            Some(key) => (
+                query
+                    .fts_expression_with(|term| index::blind_term(key, term))
+                    .or_else(|| fts.clone()),
+                query.excluded_expression_with(|term| index::blind_term(key, term)),
+                query
+                    .clauses
+                    .iter()
+                    .flatten()
+                    .flat_map(|term| index::words(term.text()))
+                    .collect(),
+            ),
+            None => (fts.clone(), query.excluded_expression(), Vec::new()),
+        };
+        let message_highlights = if index_key.is_some() {
+            "NULL AS highlighted, NULL AS snippet".to_string()
+        } else {
+            format!(
+                "highlight(messages_fts, 0, '<mark>', '</mark>') AS highlighted, \
+                 snippet(messages_fts, 0, '<mark>', '</mark>', '…', {}) AS snippet",
+                SNIPPET_TOKENS
+            )
+        };
+
+        // ?1 and ?2 are the message and note FTS queries when there are
+        // any; filters follow, then the page
+        let mut values = Vec::new();
+        if let (Some(message_fts), Some(fts)) = (&message_fts, &fts) {
+            values.push(SqlValue::Text(message_fts.clone()));
+            values.push(SqlValue::Text(fts.clone()));
+        }
+        let mut where_clauses = Vec::new();
+        let mut push = |clause: &str, value: Option<SqlValue>| {
+            let clause = match value {
+                Some(value) => {
+                    values.push(value);
+                    clause.replace('?', &format!("?{}", values.len()))
+                }
+                None => clause.to_string(),
+            };
+            where_clauses.push(clause);
+        };
+
+        for filter in &query.filters {
+            let (clause, value) = filter.predicate.sql();
+            if filter.negated {
+                push(&format!("NOT ({})", clause), value);
+            } else {
+                push(clause, value);
+            }
+        }
+        if let Some(excluded) = excluded {
+            push(
+                "m.rowid NOT IN (SELECT rowid FROM messages_fts WHERE messages_fts MATCH ?)",
+                Some(SqlValue::Text(excluded)),
+            );
+        }
+
+        let filters: String = where_clauses
+            .iter()
+            .map(|clause| format!(" AND {}", clause))
+            .collect();
+
+        let (offset, limit) = page_bounds(page);
+        values.push(SqlValue::Integer(limit as i64));
+        let limit_param = values.len();
+        values.push(SqlValue::Integer(offset as i64));
+        let offset_param = values.len();
+
+        let sql = match fts {
+            // Bare columns next to MIN() come from the row with the minimum,
+            // so each message keeps the snippet of its best hit
+            Some(_) => format!(
+                r#"
+                WITH hits AS (
+                    SELECT
+                        rowid AS message_rowid,
+                        bm25(messages_fts) AS score,
+                        {message_highlights}
+                    FROM messages_fts
+                    WHERE messages_fts MATCH ?1
+                    UNION ALL
+                    SELECT
+                        m.rowid,
+                        bm25(notes_fts),
+                        highlight(notes_fts, 0, '<mark>', '</mark>'),
+                        snippet(notes_fts, 0, '<mark>', '</mark>', '…', {tokens})
+                    FROM notes_fts
+                    JOIN notes n ON notes_fts.rowid = n.rowid
+                    JOIN messages m ON n.message_id = m.id
+                    WHERE notes_fts MATCH ?2
+                ),
+                best AS (
+                    SELECT message_rowid, MIN(score) AS score, highlighted, snippet
+                    FROM hits
+                    GROUP BY message_rowid
+                )
+                SELECT
+                    {columns},
+                    c.title,
+                    best.highlighted,
+                    best.snippet,
+                    best.score * (1.0 + 1.0 / (1.0 + MAX(julianday('now') - julianday(m.timestamp), 0.0) / {days})) AS rank,
+                    COUNT(*) OVER () AS total
+                FROM best
+                JOIN messages m ON m.rowid = best.message_rowid
+                JOIN conversations c ON m.conversation_id = c.id
+                WHERE 1{filters}
+                ORDER BY rank, m.rowid
+                LIMIT ?{limit_param} OFFSET ?{offset_param}
+                "#,
+                tokens = SNIPPET_TOKENS,
+                columns = MESSAGE_COLUMNS,
+                days = RECENCY_DAYS,
+            ),
+            None => format!(
+                r#"
+                SELECT
+                    {columns},
+                    c.title,
+                    NULL,
+                    NULL,
+                    0.0 AS rank,
+                    COUNT(*) OVER () AS total
+                FROM messages m
+                JOIN conversations c ON m.conversation_id = c.id
+                WHERE 1{filters}
+                ORDER BY m.timestamp DESC, m.rowid DESC
+                LIMIT ?{limit_param} OFFSET ?{offset_param}
+                "#,
+                columns = MESSAGE_COLUMNS,
+            ),
+        };
+
+        let conn = self.reader()?;
+        let mut stmt = conn.prepare(&sql)?;
+        let mut total = None;
+        let items = stmt
+            .query_map(params_from_iter(&values), |row| {
+                Ok((
+                    Self::parse_search_row(row, &words)?,
+                    row.get::<_, i64>(MESSAGE_COLUMN_COUNT + 4)?,
+                ))
+            })?
+            .map(|row| {
+                row.map(|(result, count)| {
+                    total = Some(count as usize);
+                    result
+                })
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let end = offset + items.len();
+        let page_info = PageInfo {
+            has_previous_page: offset > 0,
+            has_next_page: total.is_some_and(|total| end < total),
+            start_cursor: (!items.is_empty()).then(|| Cursor::Offset(offset).to_string()),
+            end_cursor: (!items.is_empty()).then(|| Cursor::Offset(end).to_string()),
+            total_count: total.or((offset == 0).then_some(0)),
+        };
+        Ok(PaginatedResult::new(items, page_info))
+    }
+
+    /// Parse a search result row
+    ///
+    /// Encrypted messages are highlighted here, for `words`. Rows without
+    /// highlights, matched by filters alone, show the start of the message
+    /// instead.
+    fn parse_search_row(
+        row: &rusqlite::Row<'_>,
+        words: &[String],
+    ) -> rusqlite::Result<SearchResult> {
+        let message = row_to_message(row)?;
+        let mut highlighted: Option<String> = row.get(MESSAGE_COLUMN_COUNT + 1)?;
+        let mut snippet: Option<String> = row.get(MESSAGE_COLUMN_COUNT + 2)?;
+        if highlighted.is_none() && !words.is_empty() {
+            let (marked, cut) = index::highlight(&message.content, words, SNIPPET_TOKENS);
+            highlighted = Some(marked);
+            snippet = Some(cut);
+        }
+        Ok(SearchResult {
+            highlighted: highlighted.unwrap_or_else(|| message.content.clone()),
+            snippet: snippet.unwrap_or_else(|| plain_snippet(&message.content)),
+            conversation_title: row.get(MESSAGE_COLUMN_COUNT)?,
+            rank: row.get(MESSAGE_COLUMN_COUNT + 3)?,
+            message,
+        })
+    }
+}
+
+/// Offset and size of the requested page
+///
+/// Cursors are the `offset:N` strings of [`PageInfo`], passed as is or
+/// wrapped by [`PaginationRequest::after`] and [`PaginationRequest::before`].
+/// A page before a cursor ends there.
+fn page_bounds(page: &PaginationRequest) -> (usize, usize) {
+    let offset = |cursor: &str| match Cursor::parse(cursor) {
+        Some(Cursor::Offset(offset)) => offset,
+        _ => cursor.parse().unwrap_or(0),
+    };
+    match &page.cursor {
+        None => (0, page.limit),
+        Some(Cursor::Offset(start)) => (*start, page.limit),
+        Some(Cursor::After(cursor)) => (offset(cursor), page.limit),
+        Some(Cursor::Before(cursor)) => {
+            let end = offset(cursor);
+            let start = end.saturating_sub(page.limit);
+            (start, end - start)
+        }
+    }
+}
+
+/// Start of `content` on one line, cut at a character boundary
+fn plain_snippet(content: &str) -> String {
+    let line = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    match line.char_indices().nth(PLAIN_SNIPPET_CHARS) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line,
+    }
+}
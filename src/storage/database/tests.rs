@@ -0,0 +1,1037 @@
+//! Database tests
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use crate::project::manager::Project;
+use crate::storage::compression::{CompressionAlgorithm, CompressionConfig};
+use crate::storage::models::{
+    Conversation, Message, MessageMetadata, Note, SearchFilter, SearchResult, TokenUsage,
+};
+use crate::storage::pagination::{PaginatedResult, PaginationRequest};
+
+use super::migrations::{MIGRATIONS, SCHEMA_VERSION};
+use super::Database;
+
+const V1_FIXTURE: &str = include_str!("fixtures/v1.sql");
+
+fn temp_db_path() -> PathBuf {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    let n = COUNTER.fetch_add(1, Ordering::SeqCst);
+    let dir = std::env::temp_dir().join(format!(
+        "claude_visual_db_test_{}_{}",
+        std::process::id(),
+        n
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir.join("claude-visual.db")
+}
+
+fn memory_database() -> Database {
+    Database::open_in_memory().unwrap()
+}
+
+/// Tables, indexes and triggers, sorted by name
+fn schema(database: &Database) -> Vec<(String, String)> {
+    let conn = database.reader().unwrap();
+    let mut stmt = conn
+        .prepare(
+            "SELECT type, name FROM sqlite_master
+             WHERE name NOT LIKE 'sqlite_%' ORDER BY name",
+        )
+        .unwrap();
+    stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+fn integrity_ok(database: &Database) -> bool {
+    let result: String = database
+        .reader()
+        .unwrap()
+        .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .unwrap();
+    result == "ok"
+}
+
+#[test]
+fn test_migrates_empty_database_step_by_step() {
+    let database = memory_database();
+    assert_eq!(database.schema_version().unwrap(), 0);
+
+    for migration in MIGRATIONS {
+        database.migrate_to(migration.version).unwrap();
+        assert_eq!(database.schema_version().unwrap(), migration.version);
+        assert!(integrity_ok(&database), "after step {}", migration.version);
+    }
+    assert_eq!(database.schema_version().unwrap(), SCHEMA_VERSION);
+
+    // Nothing left to do, and in-memory databases are never backed up
+    assert!(database.migrate().unwrap().is_none());
+
+    let fresh = memory_database();
+    fresh.initialize().unwrap();
+    assert_eq!(schema(&fresh), schema(&database));
+}
+
+#[test]
+fn test_migrates_v1_fixture_with_backup() {
+    let path = temp_db_path();
+    {
+        let database = Database::open_at(&path).unwrap();
+        database
+            .writer()
+            .unwrap()
+            .execute_batch(V1_FIXTURE)
+            .unwrap();
+    }
+
+    let database = Database::open_at(&path).unwrap();
+    assert_eq!(database.schema_version().unwrap(), 0);
+    let backup = database
+        .migrate()
+        .unwrap()
+        .expect("backup of existing data");
+    assert_eq!(database.schema_version().unwrap(), SCHEMA_VERSION);
+    assert!(integrity_ok(&database));
+
+    // Data survives, including the full-text index
+    let messages = database.get_messages("c1").unwrap();
+    assert_eq!(messages.len(), 2);
+    // Legacy messages are chained in order
+    assert_eq!(messages[0].parent_id, None);
+    assert_eq!(
+        messages[1].parent_id.as_deref(),
+        Some(messages[0].id.as_str())
+    );
+    let hits = database.search_messages("emoji", 10).unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].message.id, "m1");
+    assert_eq!(hits[0].conversation_title, "Fix the parser");
+
+    let fresh = memory_database();
+    fresh.initialize().unwrap();
+    assert_eq!(schema(&fresh), schema(&database));
+
+    // The backup is the untouched pre-migration database
+    let saved = Database::open_at(&backup).unwrap();
+    assert_eq!(saved.schema_version().unwrap(), 0);
+    let saved_messages: i64 = saved
+        .reader()
+        .unwrap()
+        .query_row("SELECT COUNT(*) FROM messages", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(saved_messages, 2);
+    assert!(!schema(&saved).iter().any(|(_, name)| name == "mcp_calls"));
+
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn test_keeps_one_backup_until_the_next_launch() {
+    let path = temp_db_path();
+    {
+        let database = Database::open_at(&path).unwrap();
+        database
+            .writer()
+            .unwrap()
+            .execute_batch(V1_FIXTURE)
+            .unwrap();
+    }
+    // Left by an earlier upgrade
+    let stale = PathBuf::from(format!("{}.v0-20240101000000.bak", path.display()));
+    std::fs::write(&stale, b"stale").unwrap();
+
+    let backup = {
+        let database = Database::open_at(&path).unwrap();
+        database
+            .migrate()
+            .unwrap()
+            .expect("backup of existing data")
+    };
+    assert!(backup.exists());
+    assert!(!stale.exists());
+
+    // Opens migrated, so the backup is no longer needed
+    let database = Database::open_at(&path).unwrap();
+    database.initialize().unwrap();
+    assert!(!backup.exists());
+
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn test_failed_migration_rolls_back() {
+    let database = memory_database();
+    database.migrate_to(1).unwrap();
+    // A table in the way of migration 2's indexes
+    database
+        .writer()
+        .unwrap()
+        .execute_batch("CREATE TABLE mcp_calls (id TEXT PRIMARY KEY)")
+        .unwrap();
+
+    let error = database.migrate().unwrap_err();
+    assert!(error.to_string().contains("Migration 2"));
+    assert_eq!(database.schema_version().unwrap(), 1);
+    assert!(!schema(&database)
+        .iter()
+        .any(|(_, name)| name == "idx_mcp_calls_conversation"));
+}
+
+#[test]
+fn test_refuses_newer_database() {
+    let database = memory_database();
+    database.initialize().unwrap();
+    database
+        .writer()
+        .unwrap()
+        .pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+        .unwrap();
+
+    let error = database.initialize().unwrap_err();
+    assert!(error.to_string().contains("newer"));
+    assert_eq!(database.schema_version().unwrap(), SCHEMA_VERSION + 1);
+}
+
+#[test]
+fn test_open_current_leaves_migrations_to_the_app() {
+    let path = temp_db_path();
+    assert!(Database::open_current_at(&path).is_err());
+    assert!(!path.exists());
+
+    Database::open_at(&path)
+        .unwrap()
+        .migrate_to(SCHEMA_VERSION - 1)
+        .unwrap();
+    let backup = PathBuf::from(format!("{}.v1-20240101000000.bak", path.display()));
+    std::fs::write(&backup, b"backup").unwrap();
+
+    let error = Database::open_current_at(&path).err().unwrap();
+    assert!(error.to_string().contains("does not match"));
+    let database = Database::open_at(&path).unwrap();
+    assert_eq!(database.schema_version().unwrap(), SCHEMA_VERSION - 1);
+    database.migrate().unwrap();
+    drop(database);
+    std::fs::write(&backup, b"backup").unwrap();
+
+    let database = Database::open_current_at(&path).unwrap();
+    assert!(database.get_conversations(None).unwrap().is_empty());
+    assert!(backup.exists());
+
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn test_file_database_uses_wal_and_read_only_readers() {
+    let path = temp_db_path();
+    let database = Database::open_at(&path).unwrap();
+    database.initialize().unwrap();
+
+    let mode: String = database
+        .writer()
+        .unwrap()
+        .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(mode, "wal");
+    assert!(database
+        .reader()
+        .unwrap()
+        .execute("DELETE FROM messages", [])
+        .is_err());
+
+    // Readers are not blocked by an open write transaction
+    let conversation = Conversation::new("Pooled", None);
+    database.insert_conversation(&conversation).unwrap();
+    let writer = database.writer().unwrap();
+    writer.execute_batch("BEGIN IMMEDIATE").unwrap();
+    writer.execute("DELETE FROM conversations", []).unwrap();
+    assert_eq!(database.get_conversations(None).unwrap().len(), 1);
+    writer.execute_batch("ROLLBACK").unwrap();
+    drop(writer);
+
+    drop(database);
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn test_enforces_foreign_keys() {
+    let database = memory_database();
+    database.initialize().unwrap();
+
+    assert!(database
+        .insert_message(&Message::new("missing", "user", "orphan"))
+        .is_err());
+
+    let conversation = Conversation::new("Cascade", None);
+    database.insert_conversation(&conversation).unwrap();
+    database
+        .insert_message(&Message::new(&conversation.id, "user", "hello"))
+        .unwrap();
+    database.delete_conversation(&conversation.id).unwrap();
+    assert!(database.get_messages(&conversation.id).unwrap().is_empty());
+}
+
+#[test]
+fn test_stores_message_metadata_and_parents() {
+    let database = memory_database();
+    database.initialize().unwrap();
+    let conversation = Conversation::new("Tools", None);
+    database.insert_conversation(&conversation).unwrap();
+
+    let prompt = Message::user(&conversation.id, "list the files");
+    let mut call = Message::tool_use(&conversation.id, "Bash", "{\"command\": \"ls\"}")
+        .with_parent(&prompt.id);
+    call.tool_use_id = Some("toolu_1".into());
+    call.metadata = MessageMetadata {
+        tool_input: Some(serde_json::json!({ "command": "ls" })),
+        model: Some("claude-sonnet-4".into()),
+        parent_tool_use_id: Some("toolu_task".into()),
+        ..Default::default()
+    };
+    let mut result =
+        Message::tool_result(&conversation.id, "Cargo.toml\nsrc", false).with_parent(&call.id);
+    result.tool_use_id = Some("toolu_1".into());
+    let mut answer = Message::assistant(&conversation.id, "Two entries.").with_parent(&result.id);
+    answer.metadata.usage = Some(TokenUsage {
+        input_tokens: 120,
+        output_tokens: 8,
+        cache_read_input_tokens: 100,
+        cost_usd: Some(0.002),
+        ..Default::default()
+    });
+    for message in [&prompt, &call, &result, &answer] {
+        database.insert_message(message).unwrap();
+    }
+
+    let loaded = database.get_messages(&conversation.id).unwrap();
+    let ids: Vec<_> = loaded.iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(ids, [&prompt.id, &call.id, &result.id, &answer.id]);
+    assert_eq!(loaded[1].metadata, call.metadata);
+    assert_eq!(loaded[1].tool_use_id.as_deref(), Some("toolu_1"));
+    assert_eq!(loaded[2].parent_id.as_deref(), Some(call.id.as_str()));
+    assert_eq!(loaded[3].metadata, answer.metadata);
+    assert!(loaded[0].metadata.is_empty());
+
+    // Empty metadata is stored as NULL
+    let stored: Option<String> = database
+        .reader()
+        .unwrap()
+        .query_row(
+            "SELECT metadata FROM messages WHERE id = ?1",
+            [&prompt.id],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(stored, None);
+    assert_eq!(
+        database.get_message(&answer.id).unwrap().unwrap().content,
+        "Two entries."
+    );
+    assert!(database.get_message("missing").unwrap().is_none());
+}
+
+/// Codec and SQLite type of each stored message body
+fn stored_encodings(database: &Database) -> Vec<(String, Option<String>, String)> {
+    let conn = database.reader().unwrap();
+    let mut stmt = conn
+        .prepare("SELECT id, content_codec, typeof(content) FROM messages ORDER BY id")
+        .unwrap();
+    stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+#[test]
+fn test_compresses_large_messages() {
+    let database = memory_database();
+    database.initialize().unwrap();
+    let conversation = Conversation::new("Build log", None);
+    database.insert_conversation(&conversation).unwrap();
+
+    let log = (0..400)
+        .map(|n| format!("compiling crate_{} v0.1.{}\n", n % 40, n))
+        .collect::<String>()
+        + "error: linker not found";
+    let mut large = Message::tool_result(&conversation.id, log.clone(), true);
+    large.id = "a-large".into();
+    let mut small = Message::user(&conversation.id, "why did the linker fail?");
+    small.id = "b-small".into();
+    database.insert_message(&large).unwrap();
+    database.insert_message(&small).unwrap();
+
+    assert_eq!(
+        stored_encodings(&database),
+        vec![
+            ("a-large".into(), Some("zstd".into()), "blob".into()),
+            ("b-small".into(), None, "text".into()),
+        ]
+    );
+    let messages = database.get_messages(&conversation.id).unwrap();
+    assert_eq!(
+        messages.iter().find(|m| m.id == "a-large").unwrap().content,
+        log
+    );
+
+    // Full-text search sees the decoded text
+    let hits = database.search_messages("linker", 10).unwrap();
+    assert_eq!(hits.len(), 2);
+    let hit = hits.iter().find(|h| h.message.id == "a-large").unwrap();
+    assert_eq!(hit.message.content, log);
+    assert!(hit
+        .highlighted
+        .ends_with("error: <mark>linker</mark> not found"));
+
+    database.delete_conversation(&conversation.id).unwrap();
+    assert!(database.search_messages("linker", 10).unwrap().is_empty());
+    database
+        .writer()
+        .unwrap()
+        .execute_batch("INSERT INTO messages_fts(messages_fts) VALUES('integrity-check')")
+        .unwrap();
+
+    // Turning compression off only affects new rows
+    let plain = memory_database().with_compression(CompressionConfig {
+        algorithm: CompressionAlgorithm::None,
+        ..Default::default()
+    });
+    plain.initialize().unwrap();
+    plain.insert_conversation(&conversation).unwrap();
+    plain.insert_message(&large).unwrap();
+    assert_eq!(stored_encodings(&plain)[0].1, None);
+}
+
+#[tokio::test]
+async fn test_background_writes_run_in_order() {
+    let database = Arc::new(memory_database());
+    database.initialize().unwrap();
+
+    // Queued without waiting; the message depends on the conversation
+    let conversation = Conversation::new("Background", None);
+    let id = conversation.id.clone();
+    database.spawn_write("save conversation", move |db| {
+        db.insert_conversation(&conversation)
+    });
+    for n in 0..5 {
+        let message = Message::new(&id, "user", format!("message {}", n));
+        database.spawn_write("save message", move |db| db.insert_message(&message));
+    }
+    let count = database
+        .write_async({
+            let id = id.clone();
+            move |db| Ok(db.get_messages(&id)?.len())
+        })
+        .await
+        .unwrap();
+    assert_eq!(count, 5);
+
+    let messages = database
+        .read_async(move |db| db.get_messages(&id))
+        .await
+        .unwrap();
+    assert_eq!(messages.len(), 5);
+
+    let error = database
+        .write_async(|_| -> anyhow::Result<()> { anyhow::bail!("boom") })
+        .await
+        .unwrap_err();
+    assert_eq!(error.to_string(), "boom");
+}
+
+#[test]
+fn test_branches_switch_fork_and_resume() {
+    let database = memory_database();
+    database.initialize().unwrap();
+    let project = Project::new("parser", PathBuf::from("/src/parser"));
+    database.insert_project(&project).unwrap();
+    let conversation = Conversation::new("Branches", Some(project.id.clone()));
+    database.insert_conversation(&conversation).unwrap();
+
+    let start = chrono::Utc::now();
+    let mut count = 0;
+    let mut add = |message: Message, session: Option<&str>| {
+        let mut message = message;
+        count += 1;
+        message.timestamp = start + chrono::Duration::seconds(count);
+        message.session_id = session.map(String::from);
+        database.insert_message(&message).unwrap();
+        message.id
+    };
+    let prompt = add(Message::user(&conversation.id, "write a parser"), None);
+    let answer = add(
+        Message::assistant(&conversation.id, "Here it is").with_parent(&prompt),
+        Some("s1"),
+    );
+    let follow_up = add(
+        Message::user(&conversation.id, "add tests").with_parent(&answer),
+        None,
+    );
+    let tests = add(
+        Message::assistant(&conversation.id, "Added").with_parent(&follow_up),
+        Some("s1"),
+    );
+    // Editing the follow-up starts a second branch in a new session
+    let edited = add(
+        Message::user(&conversation.id, "add benchmarks\nwith criterion").with_parent(&answer),
+        None,
+    );
+    let benches = add(
+        Message::assistant(&conversation.id, "Benchmarked").with_parent(&edited),
+        Some("s2"),
+    );
+
+    let thread_ids = |leaf: &str| -> Vec<String> {
+        database
+            .get_thread(leaf)
+            .unwrap()
+            .into_iter()
+            .map(|m| m.id)
+            .collect()
+    };
+    let active: Vec<_> = database
+        .get_active_thread(&conversation.id)
+        .unwrap()
+        .into_iter()
+        .map(|m| m.id)
+        .collect();
+    assert_eq!(
+        active,
+        [
+            prompt.clone(),
+            answer.clone(),
+            edited.clone(),
+            benches.clone()
+        ]
+    );
+
+    let branches = database.list_branches(&conversation.id).unwrap();
+    assert_eq!(branches.len(), 2);
+    assert_eq!(branches[0].leaf_id, tests);
+    assert_eq!(branches[0].fork_id, follow_up);
+    assert_eq!(branches[0].length, 4);
+    assert!(!branches[0].is_active);
+    assert_eq!(branches[1].fork_id, edited);
+    assert_eq!(branches[1].preview, "add benchmarks");
+    assert!(branches[1].is_active);
+
+    assert_eq!(
+        database.switch_branch(&conversation.id, &answer).unwrap(),
+        benches
+    );
+    assert_eq!(
+        database
+            .switch_branch(&conversation.id, &follow_up)
+            .unwrap(),
+        tests
+    );
+    assert_eq!(
+        database.active_leaf_id(&conversation.id).unwrap(),
+        Some(tests.clone())
+    );
+    assert!(database.switch_branch("other", &follow_up).is_err());
+
+    // A session can only be resumed from where it was left
+    assert_eq!(
+        database.resume_session_id(&tests).unwrap().as_deref(),
+        Some("s1")
+    );
+    assert_eq!(
+        database.resume_session_id(&benches).unwrap().as_deref(),
+        Some("s2")
+    );
+    assert_eq!(database.resume_session_id(&answer).unwrap(), None);
+    assert_eq!(database.resume_session_id(&prompt).unwrap(), None);
+
+    let fork = database.fork_branch(&tests, "Parser with tests").unwrap();
+    assert_eq!(fork.project_id, Some(project.id));
+    let copied = database.get_active_thread(&fork.id).unwrap();
+    let contents: Vec<_> = copied.iter().map(|m| m.content.as_str()).collect();
+    assert_eq!(
+        contents,
+        ["write a parser", "Here it is", "add tests", "Added"]
+    );
+    assert!(copied.iter().all(|m| !thread_ids(&tests).contains(&m.id)));
+    assert_eq!(thread_ids(&copied[3].id).len(), 4);
+    assert_eq!(database.list_branches(&fork.id).unwrap().len(), 1);
+    assert_eq!(database.get_messages(&conversation.id).unwrap().len(), 6);
+}
+
+#[test]
+fn test_annotations_filter_search_and_cascade() {
+    let database = memory_database();
+    database.initialize().unwrap();
+    let conversation = Conversation::new("Parser", None);
+    let other = Conversation::new("Lexer", None);
+    database.insert_conversation(&conversation).unwrap();
+    database.insert_conversation(&other).unwrap();
+
+    let question = Message::user(&conversation.id, "how should the parser recover?");
+    let answer = Message::assistant(&conversation.id, "Skip tokens until a statement boundary.")
+        .with_parent(&question.id);
+    let elsewhere = Message::user(&other.id, "the parser test is flaky");
+    for message in [&question, &answer, &elsewhere] {
+        database.insert_message(message).unwrap();
+    }
+
+    database.set_bookmark(&answer.id, None).unwrap();
+    database.set_bookmark(&answer.id, Some("recovery")).unwrap();
+    database.pin_message(&question.id).unwrap();
+    database.pin_message(&question.id).unwrap();
+    database.add_tag(&conversation.id, " Design ").unwrap();
+    database.add_tag(&conversation.id, "design").unwrap();
+    database.add_tag(&other.id, "testing").unwrap();
+    assert!(database.add_tag(&other.id, "  ").is_err());
+    database.add_reaction(&answer.id, "👍").unwrap();
+    database.add_reaction(&answer.id, "💡").unwrap();
+    database.remove_reaction(&answer.id, "💡").unwrap();
+    database
+        .set_conversation_note(&conversation.id, "Decide by Friday")
+        .unwrap();
+    database
+        .set_conversation_note(&conversation.id, "Decided: panic mode")
+        .unwrap();
+    let note =
+        Note::new(&conversation.id, "compare with synchronisation sets").with_message(&answer.id);
+    database.insert_note(&note).unwrap();
+
+    let annotations = database.get_annotations(&conversation.id).unwrap();
+    assert_eq!(annotations.bookmarks.len(), 1);
+    assert_eq!(annotations.bookmarks[0].label.as_deref(), Some("recovery"));
+    assert_eq!(annotations.pinned, vec![question.id.clone()]);
+    assert_eq!(annotations.tags, ["Design"]);
+    assert_eq!(annotations.reactions.len(), 1);
+    assert_eq!(annotations.reactions[0].emoji, "👍");
+    let contents: Vec<_> = annotations
+        .notes
+        .iter()
+        .map(|n| n.content.as_str())
+        .collect();
+    assert_eq!(
+        contents,
+        ["Decided: panic mode", "compare with synchronisation sets"]
+    );
+    assert_eq!(
+        database.list_tags().unwrap(),
+        [("Design".to_string(), 1), ("testing".to_string(), 1)]
+    );
+
+    let search = |query: &str, filter: SearchFilter| -> Vec<String> {
+        database
+            .search_messages_with_filter(query, &filter, 10)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.message.id)
+            .collect()
+    };
+    assert_eq!(search("parser", SearchFilter::default()).len(), 2);
+    assert_eq!(
+        search(
+            "parser",
+            SearchFilter {
+                tags: vec!["DESIGN".into()],
+                ..Default::default()
+            }
+        ),
+        vec![question.id.clone()]
+    );
+    assert_eq!(
+        search(
+            "statement",
+            SearchFilter {
+                bookmarked_only: true,
+                reaction: Some("👍".into()),
+                ..Default::default()
+            }
+        ),
+        vec![answer.id.clone()]
+    );
+    assert!(search(
+        "statement",
+        SearchFilter {
+            pinned_only: true,
+            ..Default::default()
+        }
+    )
+    .is_empty());
+
+    // Notes on a message find the message
+    let hits = database.search_messages("synchronisation", 10).unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].message.id, answer.id);
+    assert!(hits[0].highlighted.contains("<mark>synchronisation</mark>"));
+    assert_eq!(database.search_notes("panic", 10).unwrap().len(), 1);
+
+    assert!(database
+        .update_note(&note.id, "use error productions")
+        .unwrap());
+    assert!(database
+        .search_messages("synchronisation", 10)
+        .unwrap()
+        .is_empty());
+    database
+        .set_conversation_note(&conversation.id, " ")
+        .unwrap();
+    assert_eq!(database.get_notes(&conversation.id).unwrap().len(), 1);
+
+    database.delete_conversation(&conversation.id).unwrap();
+    let conn = database.reader().unwrap();
+    for table in [
+        "message_bookmarks",
+        "pinned_messages",
+        "message_reactions",
+        "notes",
+    ] {
+        let count: i64 = conn
+            .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(count, 0, "{} not cleaned up", table);
+    }
+    let tags: i64 = conn
+        .query_row("SELECT COUNT(*) FROM conversation_tags", [], |row| {
+            row.get(0)
+        })
+        .unwrap();
+    assert_eq!(tags, 1);
+    assert!(database.search_notes("error", 10).unwrap().is_empty());
+}
+
+#[test]
+fn test_search_query_language_ranking_and_pages() {
+    let database = memory_database();
+    database.initialize().unwrap();
+    let conversation = Conversation::new("Build", None);
+    database.insert_conversation(&conversation).unwrap();
+
+    let days_ago = |days: i64| chrono::Utc::now() - chrono::Duration::days(days);
+    let mut old = Message::assistant(&conversation.id, "Run cargo build to compile the crate");
+    old.timestamp = days_ago(400);
+    let recent = Message::assistant(&conversation.id, "Run cargo build to compile the crate");
+    let mut bash = Message::new(&conversation.id, "tool_use", "cargo build --release");
+    bash.tool_name = Some("Bash".to_string());
+    bash.timestamp = days_ago(10);
+    let mut failed = Message::new(&conversation.id, "tool_result", "error: cargo build failed");
+    failed.tool_name = Some("Bash".to_string());
+    failed.is_error = true;
+    failed.timestamp = days_ago(9);
+    let mut question = Message::user(&conversation.id, "why does npm build fail?");
+    question.timestamp = days_ago(8);
+    for message in [&old, &recent, &bash, &failed, &question] {
+        database.insert_message(message).unwrap();
+    }
+
+    let ids = |query: &str| -> Vec<String> {
+        database
+            .search_messages(query, 10)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.message.id)
+            .collect()
+    };
+
+    // Equal text ranks the newer message first
+    assert_eq!(
+        ids(r#""compile the crate""#),
+        vec![recent.id.clone(), old.id.clone()]
+    );
+    assert_eq!(ids("role:tool_use tool:bash cargo"), vec![bash.id.clone()]);
+    assert_eq!(ids("cargo -error -role:assistant"), vec![bash.id.clone()]);
+    assert_eq!(ids("is:error"), vec![failed.id.clone()]);
+    assert_eq!(ids("tool:Bash -is:error"), vec![bash.id.clone()]);
+    // Messages without a tool are not excluded
+    let mut untooled = ids("build -tool:bash");
+    untooled.sort();
+    let mut expected = vec![old.id.clone(), recent.id.clone(), question.id.clone()];
+    expected.sort();
+    assert_eq!(untooled, expected);
+    let mut either = ids("npm OR release build");
+    either.sort();
+    let mut expected = vec![bash.id.clone(), question.id.clone()];
+    expected.sort();
+    assert_eq!(either, expected);
+    let before = days_ago(100).format("%Y-%m-%d");
+    assert_eq!(
+        ids(&format!("crate before:{}", before)),
+        vec![old.id.clone()]
+    );
+    assert_eq!(
+        ids(&format!("crate after:{}", before)),
+        vec![recent.id.clone()]
+    );
+    // Filters alone list the newest first
+    assert_eq!(
+        ids("role:assistant"),
+        vec![recent.id.clone(), old.id.clone()]
+    );
+    assert!(ids("").is_empty());
+    assert!(database.search_messages("role:robot", 10).is_err());
+
+    let hit = &database.search_messages("release", 10).unwrap()[0];
+    assert_eq!(hit.snippet, "cargo build --<mark>release</mark>");
+
+    // Offset cursors walk the results both ways
+    let filter = SearchFilter::default();
+    let first = database
+        .search("build", &filter, &PaginationRequest::first(3))
+        .unwrap();
+    assert_eq!(first.items.len(), 3);
+    assert_eq!(first.page_info.total_count, Some(5));
+    assert!(first.page_info.has_next_page);
+    assert!(!first.page_info.has_previous_page);
+
+    let end = first.page_info.end_cursor.clone().unwrap();
+    let second = database
+        .search("build", &filter, &PaginationRequest::after(end, 3))
+        .unwrap();
+    assert_eq!(second.items.len(), 2);
+    assert!(!second.page_info.has_next_page);
+    assert!(second.page_info.has_previous_page);
+
+    let start = second.page_info.start_cursor.clone().unwrap();
+    let back = database
+        .search("build", &filter, &PaginationRequest::before(start, 3))
+        .unwrap();
+    let page_ids = |page: &PaginatedResult<SearchResult>| -> Vec<String> {
+        page.items.iter().map(|r| r.message.id.clone()).collect()
+    };
+    assert_eq!(page_ids(&back), page_ids(&first));
+}
+
+/// Terms in the full-text index of messages
+fn indexed_terms(database: &Database) -> Vec<String> {
+    let conn = database.writer().unwrap();
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS temp.message_terms
+             USING fts5vocab(main, messages_fts, row)",
+    )
+    .unwrap();
+    let mut stmt = conn.prepare("SELECT term FROM temp.message_terms").unwrap();
+    let terms = stmt
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<Vec<String>, _>>()
+        .unwrap();
+    terms
+}
+
+#[test]
+fn test_encryption_locks_searches_rotates_and_disables() {
+    use super::{EncryptionStatus, KeySource};
+    use crate::storage::encryption::{EncryptionError, MemoryKeyStore};
+
+    let path = temp_db_path();
+    let attachments = path.parent().unwrap().join("attachments");
+    let open = || {
+        let database = Database::open_at(&path)
+            .unwrap()
+            .with_attachments(&attachments);
+        database.initialize().unwrap();
+        database
+    };
+    let database = open();
+    let conversation = Conversation::new("Parser work", None);
+    database.insert_conversation(&conversation).unwrap();
+    let short = Message::user(&conversation.id, "Refactor the zebrafish parser");
+    let long = Message::assistant(&conversation.id, "The parser handles tokens. ".repeat(50));
+    let mut call = Message::tool_use(&conversation.id, "Write", "{}");
+    call.metadata.tool_input = Some(serde_json::json!({ "content": "okapi notes" }));
+    database.insert_message(&short).unwrap();
+    database.insert_message(&long).unwrap();
+    database.insert_message(&call).unwrap();
+    let store = database.attachments().unwrap();
+    let file = store
+        .write(&conversation.id, "diagram.txt", b"zebrafish sketch")
+        .unwrap();
+    assert!(indexed_terms(&database).contains(&"zebrafish".to_string()));
+
+    database
+        .enable_encryption(KeySource::Passphrase("correct horse"))
+        .unwrap();
+    assert_eq!(database.encryption_status(), EncryptionStatus::Unlocked);
+    assert!(stored_encodings(&database)
+        .iter()
+        .all(
+            |(_, codec, kind)| codec.as_deref().is_some_and(|c| c.starts_with("enc:1"))
+                && kind == "blob"
+        ));
+    let (metadata_codec, metadata_kind): (Option<String>, String) = database
+        .reader()
+        .unwrap()
+        .query_row(
+            "SELECT metadata_codec, typeof(metadata) FROM messages WHERE id = ?1",
+            [&call.id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    assert!(metadata_codec.is_some_and(|c| c.starts_with("enc:1")));
+    assert_eq!(metadata_kind, "blob");
+    assert_eq!(
+        database.get_message(&call.id).unwrap().unwrap().metadata,
+        call.metadata
+    );
+    assert!(!std::fs::read(&file).unwrap().ends_with(b"sketch"));
+    assert_eq!(store.read(&file).unwrap(), b"zebrafish sketch");
+
+    // Only hashes reach the index, and search still finds words, prefixes
+    // and phrases
+    database.optimize_storage().unwrap();
+    let hashed = regex::Regex::new("^[wp][0-9a-f]{16}$").unwrap();
+    assert!(indexed_terms(&database).iter().all(|t| hashed.is_match(t)));
+    let search = |database: &Database, query: &str| -> Vec<SearchResult> {
+        database.search_messages(query, 10).unwrap()
+    };
+    let ids = |results: Vec<SearchResult>| -> Vec<String> {
+        results.into_iter().map(|r| r.message.id).collect()
+    };
+    assert_eq!(ids(search(&database, "zebra")), vec![short.id.clone()]);
+    assert_eq!(
+        ids(search(&database, "\"zebrafish parser\"")),
+        vec![short.id.clone()]
+    );
+    assert_eq!(
+        ids(search(&database, "parser -zebrafish")),
+        vec![long.id.clone()]
+    );
+    assert!(search(&database, "\"parser zebrafish\"").is_empty());
+    let hit = &search(&database, "zebrafish")[0];
+    assert_eq!(
+        hit.highlighted,
+        "Refactor the <mark>zebrafish</mark> parser"
+    );
+    assert_eq!(hit.message.content, short.content);
+
+    // Reopened, it is locked until the passphrase is given
+    drop(database);
+    let database = open();
+    assert_eq!(database.encryption_status(), EncryptionStatus::Locked);
+    assert!(database.get_messages(&conversation.id).is_err());
+    assert!(database.search_messages("zebrafish", 10).is_err());
+    assert!(database
+        .insert_message(&Message::user(&conversation.id, "while locked"))
+        .is_err());
+    assert!(database.attachments().unwrap().read(&file).is_err());
+    let error = database.delete_conversation(&conversation.id).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<EncryptionError>(),
+        Some(EncryptionError::Locked)
+    ));
+    assert!(database
+        .delete_unprotected_conversations(std::slice::from_ref(&conversation.id))
+        .is_err());
+    assert!(database.unlock(KeySource::Passphrase("wrong")).is_err());
+    database
+        .unlock(KeySource::Passphrase("correct horse"))
+        .unwrap();
+    assert_eq!(database.get_messages(&conversation.id).unwrap().len(), 3);
+
+    // Rotation re-seals everything and drops the old key
+    let added = Message::user(&conversation.id, "Added before rotating");
+    database.insert_message(&added).unwrap();
+    assert_eq!(database.rotate_key().unwrap(), 2);
+    assert!(stored_encodings(&database)
+        .iter()
+        .all(|(_, codec, _)| codec.as_deref().is_some_and(|c| c.starts_with("enc:2"))));
+    let generations: i64 = database
+        .reader()
+        .unwrap()
+        .query_row("SELECT COUNT(*) FROM encryption_keys", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(generations, 1);
+    assert_eq!(ids(search(&database, "zebra")), vec![short.id.clone()]);
+    assert_eq!(ids(search(&database, "rotating")), vec![added.id.clone()]);
+    assert_eq!(
+        database.attachments().unwrap().read(&file).unwrap(),
+        b"zebrafish sketch"
+    );
+
+    // Moved to the keyring, the passphrase no longer unlocks it
+    let keyring = MemoryKeyStore::default();
+    database
+        .change_key_source(
+            KeySource::Passphrase("correct horse"),
+            KeySource::Keyring(&keyring),
+        )
+        .unwrap();
+    assert!(database.key_in_keyring().unwrap());
+    database.lock();
+    assert!(database
+        .unlock(KeySource::Passphrase("correct horse"))
+        .is_err());
+    database.unlock(KeySource::Keyring(&keyring)).unwrap();
+    assert_eq!(ids(search(&database, "zebrafish")), vec![short.id.clone()]);
+
+    // Disabled, everything is plain again
+    database
+        .disable_encryption(KeySource::Keyring(&keyring))
+        .unwrap();
+    assert_eq!(database.encryption_status(), EncryptionStatus::Disabled);
+    assert!(stored_encodings(&database)
+        .iter()
+        .all(|(_, codec, _)| !codec.as_deref().is_some_and(|c| c.starts_with("enc:"))));
+    assert_eq!(std::fs::read(&file).unwrap(), b"zebrafish sketch");
+    assert_eq!(
+        database.get_message(&call.id).unwrap().unwrap().metadata,
+        call.metadata
+    );
+    assert!(indexed_terms(&database).contains(&"zebrafish".to_string()));
+    let hit = &search(&database, "zebrafish")[0];
+    assert_eq!(
+        hit.highlighted,
+        "Refactor the <mark>zebrafish</mark> parser"
+    );
+    drop(database);
+    assert_eq!(open().encryption_status(), EncryptionStatus::Disabled);
+
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn test_enabling_encryption_leaves_no_plain_text_behind() {
+    use super::KeySource;
+
+    let path = temp_db_path();
+    let database = Database::open_at(&path).unwrap();
+    database.initialize().unwrap();
+    let conversation = Conversation::new("Zoo trip", None);
+    database.insert_conversation(&conversation).unwrap();
+    let mut call = Message::tool_use(&conversation.id, "Write", "Feed the okapi");
+    call.metadata.tool_input = Some(serde_json::json!({ "content": "wombat diet" }));
+    database.insert_message(&call).unwrap();
+    let removed = Conversation::new("Removed", None);
+    database.insert_conversation(&removed).unwrap();
+    database
+        .insert_message(&Message::user(&removed.id, "Where is the quokka?"))
+        .unwrap();
+    database.delete_conversation(&removed.id).unwrap();
+    let backup = PathBuf::from(format!("{}.v6-20250101T000000.bak", path.display()));
+    std::fs::write(&backup, "okapi").unwrap();
+
+    database
+        .enable_encryption(KeySource::Passphrase("correct horse"))
+        .unwrap();
+    assert!(!backup.exists());
+
+    let mut raw = std::fs::read(&path).unwrap();
+    raw.extend(std::fs::read(path.with_extension("db-wal")).unwrap_or_default());
+    let contains = |word: &[u8]| raw.windows(word.len()).any(|w| w == word);
+    for word in [&b"okapi"[..], b"wombat", b"quokka"] {
+        assert!(
+            !contains(word),
+            "{} found in plain text",
+            String::from_utf8_lossy(word)
+        );
+    }
+    // Titles are not encrypted
+    assert!(contains(b"Zoo trip"));
+    assert_eq!(
+        database.get_message(&call.id).unwrap().unwrap().metadata,
+        call.metadata
+    );
+
+    drop(database);
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
@@ -0,0 +1,247 @@
+//! Claude CLI transcript import state
+//!
+//! Transcripts are read incrementally: each file's row records how far it
+//! was read and where its messages went. Message IDs come from the
+//! transcript, so entries already stored are skipped rather than
+//! duplicated.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use anyhow::Result;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::storage::models::{Conversation, TranscriptBatch, TranscriptImport};
+
+use super::helpers::parse_timestamp;
+use super::Database;
+
+impl Database {
+    /// Import state of a transcript file, if it was read before
+    pub fn transcript_import(&self, path: &Path) -> Result<Option<TranscriptImport>> {
+        read_state(&*self.reader()?, path)
+    }
+
+    /// Store the messages read from a transcript and advance its state
+    ///
+    /// Messages go to the conversation earlier reads of the file created,
+    /// else to the one holding the messages they continue, else to a new
+    /// one. Parents outside the batch are the last message stored for that
+    /// entry, or the file's last message if the entry was left out. Nothing
+    /// more is stored once the app has messages of the session of its own,
+    /// i.e. it was started or resumed in the app, or the conversation was
+    /// deleted. Returns the new state and the number of messages stored.
+    pub fn import_transcript(
+        &self,
+        path: &Path,
+        batch: TranscriptBatch,
+    ) -> Result<(TranscriptImport, usize)> {
+        let conn = self.writer()?;
+        let tx = conn.unchecked_transaction()?;
+
+        let mut state = read_state(&tx, path)?.unwrap_or_else(|| TranscriptImport {
+            path: path.to_path_buf(),
+            session_id: batch.session_id.clone(),
+            conversation_id: None,
+            offset: 0,
+            last_message_id: None,
+            last_timestamp: None,
+            skipped: false,
+            imported_at: Utc::now(),
+        });
+        if !state.skipped {
+            state.skipped = match &state.conversation_id {
+                Some(id) => !conversation_exists(&tx, id)? || owned_by_app(&tx, &state)?,
+                None => owned_by_app(&tx, &state)?,
+            };
+        }
+
+        let offset = batch.offset;
+        let stored = if state.skipped {
+            0
+        } else {
+            self.store_batch(&tx, &mut state, batch)?
+        };
+        state.offset = offset;
+        state.imported_at = Utc::now();
+        write_state(&tx, &state)?;
+        tx.commit()?;
+        Ok((state, stored))
+    }
+
+    /// Insert the new messages of a batch, creating their conversation if
+    /// needed, and record where they went in `state`
+    fn store_batch(
+        &self,
+        conn: &Connection,
+        state: &mut TranscriptImport,
+        batch: TranscriptBatch,
+    ) -> Result<usize> {
+        let mut conversation_id = state.conversation_id.clone();
+        let mut known: HashSet<String> = HashSet::new();
+        let mut new = Vec::new();
+
+        for mut message in batch.messages {
+            if let Some(conversation) = message_conversation(conn, &message.id)? {
+                conversation_id.get_or_insert(conversation);
+                known.insert(message.id);
+                continue;
+            }
+            message.parent_id = match message.parent_id.take() {
+                Some(parent) if known.contains(&parent) => Some(parent),
+                Some(parent) => match last_entry_message(conn, &parent)? {
+                    Some((id, conversation)) => {
+                        conversation_id.get_or_insert(conversation);
+                        Some(id)
+                    }
+                    None => match &state.last_message_id {
+                        Some(last) if message_conversation(conn, last)?.is_some() => {
+                            Some(last.clone())
+                        }
+                        _ => None,
+                    },
+                },
+                None => None,
+            };
+            known.insert(message.id.clone());
+            new.push(message);
+        }
+
+        state.conversation_id = conversation_id.clone();
+        let (Some(first), Some(last)) = (new.first(), new.last()) else {
+            return Ok(0);
+        };
+        let (created, last_id) = (first.timestamp, last.id.clone());
+        let newest = new.iter().map(|m| m.timestamp).max().unwrap_or(created);
+
+        let conversation_id = match conversation_id {
+            Some(id) => id,
+            None => {
+                let mut conversation = Conversation::new(batch.title, batch.project_id);
+                conversation.created_at = created;
+                conversation.updated_at = newest;
+                self.insert_conversation_with(conn, &conversation)?;
+                conversation.id
+            }
+        };
+        for message in &mut new {
+            message.conversation_id = conversation_id.clone();
+            self.insert_message_with(conn, message)?;
+        }
+        conn.execute(
+            "UPDATE conversations SET updated_at = ?2 WHERE id = ?1 AND updated_at < ?2",
+            params![conversation_id, newest.to_rfc3339()],
+        )?;
+
+        state.conversation_id = Some(conversation_id);
+        state.last_message_id = Some(last_id);
+        state.last_timestamp = Some(state.last_timestamp.map_or(newest, |t| t.max(newest)));
+        Ok(new.len())
+    }
+}
+
+fn read_state(conn: &Connection, path: &Path) -> Result<Option<TranscriptImport>> {
+    Ok(conn
+        .query_row(
+            "SELECT session_id, conversation_id, byte_offset, last_message_id, last_timestamp,
+                 skipped, imported_at
+             FROM transcript_imports WHERE path = ?1",
+            params![path.to_string_lossy()],
+            |row| {
+                let last_timestamp: Option<String> = row.get(4)?;
+                let imported_at: String = row.get(6)?;
+                Ok(TranscriptImport {
+                    path: path.to_path_buf(),
+                    session_id: row.get(0)?,
+                    conversation_id: row.get(1)?,
+                    offset: row.get::<_, i64>(2)? as u64,
+                    last_message_id: row.get(3)?,
+                    last_timestamp: last_timestamp.as_deref().map(parse_timestamp),
+                    skipped: row.get::<_, i32>(5)? != 0,
+                    imported_at: parse_timestamp(&imported_at),
+                })
+            },
+        )
+        .optional()?)
+}
+
+fn write_state(conn: &Connection, state: &TranscriptImport) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO transcript_imports (path, session_id, conversation_id,
+             byte_offset, last_message_id, last_timestamp, skipped, imported_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            state.path.to_string_lossy(),
+            state.session_id,
+            state.conversation_id,
+            state.offset as i64,
+            state.last_message_id,
+            state.last_timestamp.map(|t| t.to_rfc3339()),
+            state.skipped as i32,
+            state.imported_at.to_rfc3339(),
+        ],
+    )?;
+    Ok(())
+}
+
+/// Whether the app has messages of the session that the import did not
+/// store: any at all before the first import, otherwise ones elsewhere or
+/// newer than the last imported
+fn owned_by_app(conn: &Connection, state: &TranscriptImport) -> Result<bool> {
+    let owned = match &state.conversation_id {
+        Some(conversation_id) => conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM messages WHERE session_id = ?1
+                 AND (conversation_id <> ?2 OR timestamp > ?3))",
+            params![
+                state.session_id,
+                conversation_id,
+                state
+                    .last_timestamp
+                    .map(|t| t.to_rfc3339())
+                    .unwrap_or_default(),
+            ],
+            |row| row.get(0),
+        )?,
+        None => conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM messages WHERE session_id = ?1)",
+            params![state.session_id],
+            |row| row.get(0),
+        )?,
+    };
+    Ok(owned)
+}
+
+fn conversation_exists(conn: &Connection, id: &str) -> Result<bool> {
+    Ok(conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM conversations WHERE id = ?1)",
+        params![id],
+        |row| row.get(0),
+    )?)
+}
+
+/// Conversation of a stored message
+fn message_conversation(conn: &Connection, id: &str) -> Result<Option<String>> {
+    Ok(conn
+        .query_row(
+            "SELECT conversation_id FROM messages WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+/// Last message stored for a transcript entry, and its conversation
+///
+/// An entry's messages are its ID followed by `<id>:1`, `<id>:2`, ...
+fn last_entry_message(conn: &Connection, entry: &str) -> Result<Option<(String, String)>> {
+    Ok(conn
+        .query_row(
+            "SELECT id, conversation_id FROM messages
+             WHERE id = ?1 OR (id > ?1 || ':' AND id < ?1 || ';')
+             ORDER BY rowid DESC LIMIT 1",
+            params![entry],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?)
+}
@@ -0,0 +1,127 @@
+//! Attachment files, sealed while the database is encrypted
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::cipher::{open, seal};
+use super::keys::{DataKey, KeyChain};
+use super::EncryptionError;
+
+/// Marks a sealed file; the key generation and sealed bytes follow
+const MAGIC: &[u8; 8] = b"CVSEAL01";
+
+/// Attachments in one folder per conversation, named by its ID
+///
+/// Files are sealed with the database's current data key while it is
+/// encrypted. Files written before encryption was enabled are read as is.
+pub struct AttachmentStore {
+    dir: PathBuf,
+    keys: Arc<KeyChain>,
+}
+
+impl AttachmentStore {
+    pub(crate) fn new(dir: PathBuf, keys: Arc<KeyChain>) -> Self {
+        Self { dir, keys }
+    }
+
+    /// Root folder of the attachments
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Save an attachment of a conversation, returning its path
+    pub fn write(
+        &self,
+        conversation_id: &str,
+        name: &str,
+        data: &[u8],
+    ) -> Result<PathBuf, EncryptionError> {
+        let folder = self.dir.join(file_name(conversation_id)?);
+        std::fs::create_dir_all(&folder)?;
+        let path = folder.join(file_name(name)?);
+        let key = self.keys.sealing_key()?;
+        write_atomic(&path, &encode(data, key.as_ref().map(|(g, k)| (*g, &**k)))?)?;
+        Ok(path)
+    }
+
+    /// Contents of an attachment
+    pub fn read(&self, path: &Path) -> Result<Vec<u8>, EncryptionError> {
+        let data = std::fs::read(path)?;
+        match sealed_generation(&data) {
+            Some(generation) => open(
+                &self.keys.key(generation)?.content,
+                &data[MAGIC.len() + 4..],
+            ),
+            None => Ok(data),
+        }
+    }
+
+    /// Rewrite every file sealed with `key`, or in plain if `None`,
+    /// returning the number rewritten
+    pub(crate) fn reseal_all(
+        &self,
+        key: Option<(u32, &DataKey)>,
+    ) -> Result<usize, EncryptionError> {
+        let Ok(folders) = std::fs::read_dir(&self.dir) else {
+            return Ok(0);
+        };
+
+        let mut rewritten = 0;
+        for folder in folders.flatten() {
+            let Ok(files) = std::fs::read_dir(folder.path()) else {
+                continue;
+            };
+            for file in files.flatten() {
+                let path = file.path();
+                if !path.is_file() {
+                    continue;
+                }
+                let data = std::fs::read(&path)?;
+                if sealed_generation(&data) == key.map(|(generation, _)| generation) {
+                    continue;
+                }
+                let plain = self.read(&path)?;
+                write_atomic(&path, &encode(&plain, key)?)?;
+                rewritten += 1;
+            }
+        }
+        Ok(rewritten)
+    }
+}
+
+/// File contents for `data`, sealed with `key` if given
+fn encode(data: &[u8], key: Option<(u32, &DataKey)>) -> Result<Vec<u8>, EncryptionError> {
+    let Some((generation, key)) = key else {
+        return Ok(data.to_vec());
+    };
+    let mut out = MAGIC.to_vec();
+    out.extend_from_slice(&generation.to_le_bytes());
+    out.extend_from_slice(&seal(&key.content, data)?);
+    Ok(out)
+}
+
+/// Key generation of a sealed file, `None` for plain files
+fn sealed_generation(data: &[u8]) -> Option<u32> {
+    let header = data.strip_prefix(MAGIC)?.get(..4)?;
+    Some(u32::from_le_bytes(header.try_into().ok()?))
+}
+
+/// `name` if it is a single path component
+fn file_name(name: &str) -> Result<&str, EncryptionError> {
+    match Path::new(name).file_name() {
+        Some(file) if file == name => Ok(name),
+        _ => Err(EncryptionError::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("invalid attachment name: {}", name),
+        ))),
+    }
+}
+
+/// Replace `path` without leaving a partly written file
+fn write_atomic(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    let temporary = PathBuf::from(temporary);
+    std::fs::write(&temporary, data)?;
+    std::fs::rename(&temporary, path)
+}
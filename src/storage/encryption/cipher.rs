@@ -0,0 +1,67 @@
+//! Primitives: AES-256-GCM sealing, Argon2id key derivation and HMAC-SHA256
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::Sha256;
+
+use super::keys::{SecretKey, KEY_LEN};
+use super::EncryptionError;
+
+/// Bytes of the random nonce in front of each sealed value
+const NONCE_LEN: usize = 12;
+
+/// Bytes of a passphrase salt
+pub(crate) const SALT_LEN: usize = 16;
+
+/// Encrypt `plaintext` as nonce followed by ciphertext and tag
+pub(crate) fn seal(key: &SecretKey, plaintext: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+    let cipher = Aes256Gcm::new(key.as_bytes().into());
+    let nonce: [u8; NONCE_LEN] = random_bytes();
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|_| EncryptionError::Corrupt)?;
+
+    let mut sealed = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+/// Decrypt a value made by [`seal`], failing if it was altered
+pub(crate) fn open(key: &SecretKey, sealed: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+    if sealed.len() < NONCE_LEN {
+        return Err(EncryptionError::Corrupt);
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    Aes256Gcm::new(key.as_bytes().into())
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| EncryptionError::WrongKey)
+}
+
+/// Key from a passphrase with Argon2id at its default cost
+pub(crate) fn derive_key(passphrase: &str, salt: &[u8]) -> Result<SecretKey, EncryptionError> {
+    let mut key = [0u8; KEY_LEN];
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| EncryptionError::KeyDerivation(e.to_string()))?;
+    let secret = SecretKey::from_bytes(&key);
+    key.fill(0);
+    secret
+}
+
+/// HMAC-SHA256 (RFC 2104)
+pub(crate) fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut mac =
+        <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().into()
+}
+
+/// Bytes from a cryptographically secure generator
+pub(crate) fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes
+}
@@ -0,0 +1,118 @@
+//! Stored form of message bodies
+
+use rusqlite::types::Value as SqlValue;
+
+use crate::storage::compression::{self, CompressionAlgorithm, Compressor};
+
+use super::cipher::{open, seal};
+use super::keys::{DataKey, KeyChain};
+use super::EncryptionError;
+
+/// Prefix of the codec of sealed bodies
+const SEALED_PREFIX: &str = "enc:";
+
+/// How a message body is stored, as named in `content_codec`
+///
+/// NULL is plain text and an algorithm name a compressed blob. Sealed
+/// bodies are named `enc:<generation>`, or `enc:<generation>:<algorithm>`
+/// when compressed before sealing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct Codec {
+    /// Data key generation the body is sealed with
+    pub generation: Option<u32>,
+    /// Compression applied to the text
+    pub compression: Option<CompressionAlgorithm>,
+}
+
+impl Codec {
+    /// Parse a `content_codec` value
+    pub(crate) fn parse(name: Option<&str>) -> Result<Self, EncryptionError> {
+        let Some(name) = name else {
+            return Ok(Self::default());
+        };
+        let unknown = || EncryptionError::UnknownCodec(name.to_string());
+        let algorithm = |name: &str| CompressionAlgorithm::from_name(name).ok_or_else(unknown);
+
+        match name.strip_prefix(SEALED_PREFIX) {
+            Some(sealed) => {
+                let (generation, compression) = match sealed.split_once(':') {
+                    Some((generation, compression)) => (generation, Some(algorithm(compression)?)),
+                    None => (sealed, None),
+                };
+                Ok(Self {
+                    generation: Some(generation.parse().map_err(|_| unknown())?),
+                    compression,
+                })
+            }
+            None => Ok(Self {
+                generation: None,
+                compression: Some(algorithm(name)?),
+            }),
+        }
+    }
+
+    /// The `content_codec` value
+    pub(crate) fn name(&self) -> Option<String> {
+        let compression = self.compression.map(|algorithm| algorithm.name());
+        match (self.generation, compression) {
+            (Some(generation), Some(compression)) => {
+                Some(format!("{}{}:{}", SEALED_PREFIX, generation, compression))
+            }
+            (Some(generation), None) => Some(format!("{}{}", SEALED_PREFIX, generation)),
+            (None, compression) => compression.map(str::to_string),
+        }
+    }
+}
+
+/// Body to store for `content`, compressed when that pays off and sealed
+/// with `key` if given
+pub(crate) fn encode(
+    content: &str,
+    compressor: &Compressor,
+    key: Option<(u32, &DataKey)>,
+) -> Result<(SqlValue, Option<String>), EncryptionError> {
+    let (bytes, compression) = match compressor.compress_string(content) {
+        Ok(compressed)
+            if compressed.algorithm != CompressionAlgorithm::None && compressed.is_effective() =>
+        {
+            (compressed.data, Some(compressed.algorithm))
+        }
+        Ok(_) => (content.as_bytes().to_vec(), None),
+        Err(e) => {
+            tracing::warn!("Failed to compress message, storing it uncompressed: {}", e);
+            (content.as_bytes().to_vec(), None)
+        }
+    };
+
+    let codec = Codec {
+        generation: key.map(|(generation, _)| generation),
+        compression,
+    };
+    let value = match (key, compression) {
+        (Some((_, key)), _) => SqlValue::Blob(seal(&key.content, &bytes)?),
+        (None, Some(_)) => SqlValue::Blob(bytes),
+        (None, None) => SqlValue::Text(content.to_string()),
+    };
+    Ok((value, codec.name()))
+}
+
+/// Text of a stored body that is not plain text
+pub(crate) fn decode(
+    data: &[u8],
+    codec: &Codec,
+    keys: &KeyChain,
+) -> Result<String, EncryptionError> {
+    let opened;
+    let data = match codec.generation {
+        Some(generation) => {
+            opened = open(&keys.key(generation)?.content, data)?;
+            &opened[..]
+        }
+        None => data,
+    };
+    let bytes = match codec.compression {
+        Some(algorithm) => compression::decompress(algorithm, data)?,
+        None => data.to_vec(),
+    };
+    String::from_utf8(bytes).map_err(|_| compression::CompressionError::InvalidUtf8.into())
+}
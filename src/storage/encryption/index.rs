@@ -0,0 +1,152 @@
+//! Search index of encrypted messages
+
+use crate::storage::search::Term;
+
+use super::cipher::hmac_sha256;
+use super::keys::DataKey;
+
+/// Shortest word prefix indexed
+pub(crate) const MIN_PREFIX: usize = 3;
+
+/// Longest word prefix indexed
+pub(crate) const MAX_PREFIX: usize = 10;
+
+/// Words of `text` as the index sees them: runs of letters and digits,
+/// lowercased
+pub(crate) fn words(text: &str) -> Vec<String> {
+    spans(text)
+        .into_iter()
+        .map(|(start, end)| text[start..end].to_lowercase())
+        .collect()
+}
+
+/// Document indexed for `text`: its hashed words in order, so phrases
+/// still match, followed by hashes of their prefixes
+pub(crate) fn blind_document(key: &DataKey, text: &str) -> String {
+    let words = words(text);
+    let mut tokens: Vec<String> = words.iter().map(|word| token(key, 'w', word)).collect();
+    for word in &words {
+        let ends: Vec<usize> = word
+            .char_indices()
+            .map(|(i, _)| i)
+            .skip(MIN_PREFIX)
+            .collect();
+        // Whole words are matched by their own token
+        for &end in ends.iter().take(MAX_PREFIX - MIN_PREFIX + 1) {
+            tokens.push(token(key, 'p', &word[..end]));
+        }
+    }
+    tokens.join(" ")
+}
+
+/// FTS5 form of a search term against blind documents, if it has words
+///
+/// A single word of [`MIN_PREFIX`] to [`MAX_PREFIX`] characters matches as
+/// a prefix, other words only in full; several words match as a phrase.
+pub(crate) fn blind_term(key: &DataKey, term: &Term) -> Option<String> {
+    let (text, is_word) = match term {
+        Term::Word(word) => (word, true),
+        Term::Phrase(phrase) => (phrase, false),
+    };
+    match words(text).as_slice() {
+        [] => None,
+        [word] if is_word && (MIN_PREFIX..=MAX_PREFIX).contains(&word.chars().count()) => {
+            Some(format!(
+                "(\"{}\" OR \"{}\")",
+                token(key, 'w', word),
+                token(key, 'p', word)
+            ))
+        }
+        words => Some(format!(
+            "\"{}\"",
+            words
+                .iter()
+                .map(|word| token(key, 'w', word))
+                .collect::<Vec<_>>()
+                .join(" ")
+        )),
+    }
+}
+
+/// `text` with the words starting with any of `words` in `<mark>` tags,
+/// and a snippet of up to `tokens` words around the first of them
+///
+/// Stands in for FTS5's `highlight()` and `snippet()`, which only see the
+/// hashes of encrypted messages.
+pub(crate) fn highlight(text: &str, words: &[String], tokens: usize) -> (String, String) {
+    let spans = spans(text);
+    let matched: Vec<bool> = spans
+        .iter()
+        .map(|&(start, end)| {
+            let word = text[start..end].to_lowercase();
+            words
+                .iter()
+                .any(|w| !w.is_empty() && word.starts_with(w.as_str()))
+        })
+        .collect();
+
+    let highlighted = mark(text, &spans, &matched, 0, text.len());
+
+    let first = matched.iter().position(|&m| m).unwrap_or(0);
+    let start = first - first.min(tokens / 4);
+    let end = spans.len().min(start + tokens);
+    let snippet = match (
+        spans.get(start),
+        end.checked_sub(1).and_then(|i| spans.get(i)),
+    ) {
+        (Some(&(from, _)), Some(&(_, to))) => format!(
+            "{}{}{}",
+            if start > 0 { "…" } else { "" },
+            mark(text, &spans, &matched, from, to),
+            if end < spans.len() { "…" } else { "" },
+        ),
+        _ => String::new(),
+    };
+    (highlighted, snippet)
+}
+
+/// Keyed hash of a word (`w`) or prefix (`p`), readable as one FTS5 token
+fn token(key: &DataKey, kind: char, text: &str) -> String {
+    let input = format!("{}:{}", kind, text);
+    let hash = hmac_sha256(key.index.as_bytes(), input.as_bytes());
+    format!("{}{}", kind, hex::encode(&hash[..8]))
+}
+
+/// Byte ranges of the words in `text`
+fn spans(text: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                spans.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        spans.push((s, text.len()));
+    }
+    spans
+}
+
+/// `text[from..to]` with the matched spans in it marked
+fn mark(text: &str, spans: &[(usize, usize)], matched: &[bool], from: usize, to: usize) -> String {
+    let mut out = String::with_capacity(to - from);
+    let mut at = from;
+    for (&(start, end), _) in spans
+        .iter()
+        .zip(matched)
+        .filter(|(&(start, end), &m)| m && start >= from && end <= to)
+    {
+        out.push_str(&text[at..start]);
+        out.push_str("<mark>");
+        out.push_str(&text[start..end]);
+        out.push_str("</mark>");
+        at = end;
+    }
+    out.push_str(&text[at..to]);
+    out
+}
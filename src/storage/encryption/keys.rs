@@ -0,0 +1,191 @@
+//! Keys held in memory while a database is unlocked
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+use super::cipher::{hmac_sha256, random_bytes};
+use super::EncryptionError;
+
+/// Bytes of every key
+pub const KEY_LEN: usize = 32;
+
+/// Secret key bytes, zeroed when dropped
+#[derive(Clone, PartialEq, Eq)]
+pub struct SecretKey([u8; KEY_LEN]);
+
+impl SecretKey {
+    /// New random key
+    pub fn generate() -> Self {
+        Self(random_bytes())
+    }
+
+    /// Key from raw bytes, e.g. read back from the keyring
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, EncryptionError> {
+        bytes
+            .try_into()
+            .map(Self)
+            .map_err(|_| EncryptionError::Corrupt)
+    }
+
+    /// Raw key bytes
+    pub fn as_bytes(&self) -> &[u8; KEY_LEN] {
+        &self.0
+    }
+
+    /// Subkey for `purpose`, so that no key serves two uses
+    pub(crate) fn derive(&self, purpose: &str) -> SecretKey {
+        Self(hmac_sha256(&self.0, purpose.as_bytes()))
+    }
+}
+
+impl Drop for SecretKey {
+    fn drop(&mut self) {
+        // Volatile so the compiler cannot drop the writes as dead stores
+        for byte in self.0.iter_mut() {
+            unsafe { std::ptr::write_volatile(byte, 0) };
+        }
+    }
+}
+
+impl fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretKey(..)")
+    }
+}
+
+/// Data key of one generation, split into its subkeys
+pub(crate) struct DataKey {
+    /// Seals message bodies and attachments
+    pub content: SecretKey,
+    /// Hashes words for the search index
+    pub index: SecretKey,
+}
+
+impl DataKey {
+    pub(crate) fn new(key: &SecretKey) -> Self {
+        Self {
+            content: key.derive("content"),
+            index: key.derive("search index"),
+        }
+    }
+}
+
+/// Keys of an encrypted database, shared with the SQL functions of its
+/// connections
+///
+/// Holds the key-encryption key and every data key generation still in
+/// use while unlocked, and nothing while locked.
+#[derive(Default)]
+pub struct KeyChain {
+    state: RwLock<KeyChainState>,
+}
+
+#[derive(Default)]
+struct KeyChainState {
+    enabled: bool,
+    wrapping: Option<SecretKey>,
+    current: Option<u32>,
+    keys: HashMap<u32, Arc<DataKey>>,
+}
+
+impl KeyChain {
+    /// Check if the database is encrypted
+    pub fn is_enabled(&self) -> bool {
+        self.state.read().enabled
+    }
+
+    /// Check if encrypted content can be read and written
+    pub fn is_unlocked(&self) -> bool {
+        let state = self.state.read();
+        state.enabled && state.current.is_some()
+    }
+
+    /// Forget every key; encrypted content can no longer be read
+    pub fn lock(&self) {
+        let mut state = self.state.write();
+        *state = KeyChainState {
+            enabled: state.enabled,
+            ..Default::default()
+        };
+    }
+
+    /// Record whether the database is encrypted, forgetting keys if not
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        let mut state = self.state.write();
+        if !enabled {
+            *state = KeyChainState::default();
+        }
+        state.enabled = enabled;
+    }
+
+    /// Hold the keys of an unlocked database
+    pub(crate) fn unlock(&self, wrapping: SecretKey, keys: Vec<(u32, SecretKey)>, current: u32) {
+        let mut state = self.state.write();
+        state.wrapping = Some(wrapping);
+        state.keys = keys
+            .iter()
+            .map(|(generation, key)| (*generation, Arc::new(DataKey::new(key))))
+            .collect();
+        state.current = Some(current);
+    }
+
+    /// Add a data key generation, readable before it is made current
+    pub(crate) fn insert(&self, generation: u32, key: &SecretKey) {
+        self.state
+            .write()
+            .keys
+            .insert(generation, Arc::new(DataKey::new(key)));
+    }
+
+    /// Seal new content with `generation` from now on
+    pub(crate) fn make_current(&self, generation: u32) {
+        self.state.write().current = Some(generation);
+    }
+
+    /// Forget a data key generation
+    pub(crate) fn remove(&self, generation: u32) {
+        self.state.write().keys.remove(&generation);
+    }
+
+    /// Replace the key-encryption key
+    pub(crate) fn set_wrapping(&self, wrapping: SecretKey) {
+        self.state.write().wrapping = Some(wrapping);
+    }
+
+    /// Key-encryption key of the unlocked database
+    pub(crate) fn wrapping(&self) -> Result<SecretKey, EncryptionError> {
+        self.state
+            .read()
+            .wrapping
+            .clone()
+            .ok_or(EncryptionError::Locked)
+    }
+
+    /// Generation new content is sealed with
+    pub(crate) fn current_generation(&self) -> Result<u32, EncryptionError> {
+        self.state.read().current.ok_or(EncryptionError::Locked)
+    }
+
+    /// Data key of `generation`
+    pub(crate) fn key(&self, generation: u32) -> Result<Arc<DataKey>, EncryptionError> {
+        let state = self.state.read();
+        match state.keys.get(&generation) {
+            Some(key) => Ok(key.clone()),
+            None if state.keys.is_empty() => Err(EncryptionError::Locked),
+            None => Err(EncryptionError::MissingKey(generation)),
+        }
+    }
+
+    /// Key new content is sealed with, or `None` if the database is not
+    /// encrypted
+    pub(crate) fn sealing_key(&self) -> Result<Option<(u32, Arc<DataKey>)>, EncryptionError> {
+        if !self.is_enabled() {
+            return Ok(None);
+        }
+        let generation = self.current_generation()?;
+        Ok(Some((generation, self.key(generation)?)))
+    }
+}
@@ -0,0 +1,161 @@
+//! Keys kept by the operating system
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use parking_lot::Mutex;
+
+use super::keys::SecretKey;
+use super::EncryptionError;
+
+/// Service name of the app's keyring entries
+const SERVICE: &str = "claude-visual";
+
+/// Secret storage holding keys by account name
+pub trait KeyStore: Send + Sync {
+    /// Key stored for `account`, if any
+    fn load(&self, account: &str) -> Result<Option<SecretKey>, EncryptionError>;
+    /// Store `key` for `account`, replacing any previous one
+    fn store(&self, account: &str, key: &SecretKey) -> Result<(), EncryptionError>;
+    /// Remove the key of `account`, if any
+    fn delete(&self, account: &str) -> Result<(), EncryptionError>;
+}
+
+/// The OS keyring: the login keychain on macOS, the Secret Service on Linux
+///
+/// Uses the `security` and `secret-tool` commands. Keys are passed on
+/// standard input, never as arguments, so they do not show in the process
+/// list.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemKeyStore;
+
+impl KeyStore for SystemKeyStore {
+    fn load(&self, account: &str) -> Result<Option<SecretKey>, EncryptionError> {
+        let output = if cfg!(target_os = "macos") {
+            run(
+                "security",
+                &["find-generic-password", "-s", SERVICE, "-a", account, "-w"],
+                None,
+            )?
+        } else {
+            run(
+                "secret-tool",
+                &["lookup", "service", SERVICE, "account", account],
+                None,
+            )?
+        };
+        match output {
+            Some(hex_key) => {
+                let bytes = hex::decode(hex_key.trim())
+                    .map_err(|_| EncryptionError::Keyring("stored key is not valid".into()))?;
+                SecretKey::from_bytes(&bytes).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn store(&self, account: &str, key: &SecretKey) -> Result<(), EncryptionError> {
+        let hex_key = hex::encode(key.as_bytes());
+        let stored = if cfg!(target_os = "macos") {
+            // Interactive mode reads the command from stdin, and reports
+            // success whatever it does, so the entry is read back
+            let command = format!(
+                "add-generic-password -U -s {} -a {} -w {}\n",
+                SERVICE, account, hex_key
+            );
+            run("security", &["-i"], Some(&command))?;
+            let loaded = self.load(account)?;
+            loaded
+                .filter(|loaded| loaded.as_bytes() == key.as_bytes())
+                .map(|_| String::new())
+        } else {
+            let label = format!("Claude Visual database key ({})", account);
+            run(
+                "secret-tool",
+                &[
+                    "store", "--label", &label, "service", SERVICE, "account", account,
+                ],
+                Some(&hex_key),
+            )?
+        };
+        stored
+            .map(|_| ())
+            .ok_or_else(|| EncryptionError::Keyring("the keyring refused the key".into()))
+    }
+
+    fn delete(&self, account: &str) -> Result<(), EncryptionError> {
+        if cfg!(target_os = "macos") {
+            run(
+                "security",
+                &["delete-generic-password", "-s", SERVICE, "-a", account],
+                None,
+            )?;
+        } else {
+            run(
+                "secret-tool",
+                &["clear", "service", SERVICE, "account", account],
+                None,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Run a keyring command, returning its output if it succeeded
+fn run(
+    program: &str,
+    args: &[&str],
+    input: Option<&str>,
+) -> Result<Option<String>, EncryptionError> {
+    if cfg!(windows) {
+        return Err(EncryptionError::Keyring(
+            "no supported keyring on this platform".into(),
+        ));
+    }
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| EncryptionError::Keyring(format!("{}: {}", program, e)))?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        stdin
+            .write_all(input.as_bytes())
+            .map_err(|e| EncryptionError::Keyring(format!("{}: {}", program, e)))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| EncryptionError::Keyring(format!("{}: {}", program, e)))?;
+    Ok(output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned()))
+}
+
+/// Keys held in memory, for tests and sessions without a keyring
+#[derive(Default)]
+pub struct MemoryKeyStore {
+    keys: Mutex<HashMap<String, SecretKey>>,
+}
+
+impl KeyStore for MemoryKeyStore {
+    fn load(&self, account: &str) -> Result<Option<SecretKey>, EncryptionError> {
+        Ok(self.keys.lock().get(account).cloned())
+    }
+
+    fn store(&self, account: &str, key: &SecretKey) -> Result<(), EncryptionError> {
+        self.keys.lock().insert(account.to_string(), key.clone());
+        Ok(())
+    }
+
+    fn delete(&self, account: &str) -> Result<(), EncryptionError> {
+        self.keys.lock().remove(account);
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+//! Encryption at Rest
+//!
+//! Opt-in encryption of message bodies, their metadata (which holds tool
+//! inputs) and attachments. Content is sealed with AES-256-GCM under a
+//! random data key, which is kept in the database wrapped by a
+//! key-encryption key. That key is derived from a passphrase
+//! with Argon2id or held in the OS keyring. Until it is supplied the
+//! database is locked: encrypted messages cannot be read and no message can
+//! be written. Rotation seals everything with a new data key generation;
+//! rows name the generation they are sealed with in their codec.
+//!
+//! # Search
+//!
+//! FTS5 keeps its index in plain shadow tables, so the words of encrypted
+//! messages must not reach it. Instead each word is indexed as a keyed hash
+//! (HMAC-SHA256 under a key derived from the data key), in order, followed
+//! by hashes of its prefixes of 3 to 10 characters. Queries are hashed the
+//! same way, so words, prefixes, phrases, `OR` and exclusions all keep
+//! working, and BM25 ranks hashes as it would words. Prefix matching is
+//! limited to those lengths, and accents are not folded as FTS5 does for
+//! plain text. Snippets are cut from the decrypted message after the
+//! search. The index reveals how often each hashed word occurs, not the
+//! words themselves, and it is rebuilt with new hashes on rotation.
+//!
+//! Everything else stays in plain text: conversation titles, notes, tags,
+//! tool names, projects, and the arguments and results of MCP calls kept
+//! for audit.
+
+mod attachments;
+mod cipher;
+pub(crate) mod content;
+pub(crate) mod index;
+mod keys;
+mod keystore;
+
+pub use attachments::AttachmentStore;
+pub(crate) use cipher::{derive_key, hmac_sha256, open, random_bytes, seal, SALT_LEN};
+pub(crate) use keys::DataKey;
+pub use keys::{KeyChain, SecretKey, KEY_LEN};
+pub use keystore::{KeyStore, MemoryKeyStore, SystemKeyStore};
+
+use crate::storage::compression::CompressionError;
+
+/// Error of encrypted storage
+#[derive(Debug, thiserror::Error)]
+pub enum EncryptionError {
+    #[error("The encrypted database is locked")]
+    Locked,
+    #[error("Wrong passphrase or key")]
+    WrongKey,
+    #[error("No key for generation {0}")]
+    MissingKey(u32),
+    #[error("Encrypted data is corrupt")]
+    Corrupt,
+    #[error("Unknown content codec: {0}")]
+    UnknownCodec(String),
+    #[error("Key derivation failed: {0}")]
+    KeyDerivation(String),
+    #[error("Keyring: {0}")]
+    Keyring(String),
+    #[error(transparent)]
+    Compression(#[from] CompressionError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::content::{decode, encode, Codec};
+    use super::*;
+    use crate::storage::compression::{CompressionAlgorithm, Compressor};
+    use crate::storage::search::Term;
+
+    #[test]
+    fn test_hmac_sha256_matches_rfc_4231() {
+        let mac = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(
+            hex::encode(mac),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_seal_and_codecs_roundtrip() {
+        let key = SecretKey::generate();
+        let sealed = seal(&key, b"secret").unwrap();
+        assert_eq!(open(&key, &sealed).unwrap(), b"secret");
+        assert!(matches!(
+            open(&SecretKey::generate(), &sealed),
+            Err(EncryptionError::WrongKey)
+        ));
+
+        for name in [None, Some("lz4"), Some("enc:3"), Some("enc:12:zstd")] {
+            assert_eq!(Codec::parse(name).unwrap().name().as_deref(), name);
+        }
+        assert!(Codec::parse(Some("enc:x")).is_err());
+        assert!(Codec::parse(Some("rot13")).is_err());
+
+        let keys = KeyChain::default();
+        keys.unlock(SecretKey::generate(), vec![(2, key.clone())], 2);
+        let data_key = keys.key(2).unwrap();
+        let compressor = Compressor::default();
+        for text in ["short", &"long and repetitive ".repeat(100)] {
+            let (value, codec) = encode(text, &compressor, Some((2, &data_key))).unwrap();
+            let codec = Codec::parse(codec.as_deref()).unwrap();
+            assert_eq!(codec.generation, Some(2));
+            let rusqlite::types::Value::Blob(data) = value else {
+                panic!("sealed bodies are blobs");
+            };
+            assert!(!String::from_utf8_lossy(&data).contains("repetitive"));
+            assert_eq!(decode(&data, &codec, &keys).unwrap(), text);
+        }
+        let (_, codec) = encode(&"x".repeat(4000), &compressor, None).unwrap();
+        assert_eq!(codec.as_deref(), Some(CompressionAlgorithm::Lz4.name()));
+
+        keys.lock();
+        assert!(matches!(keys.key(2), Err(EncryptionError::Locked)));
+    }
+
+    #[test]
+    fn test_blind_index_terms_match_document_tokens() {
+        let key = DataKey::new(&SecretKey::generate());
+        let document = index::blind_document(&key, "Refactor the Parser quickly");
+        let tokens: Vec<&str> = document.split(' ').collect();
+        assert!(!document.to_lowercase().contains("parser"));
+
+        let term = |term: Term| index::blind_term(&key, &term).unwrap();
+        // A prefix of a longer word, and a whole short word
+        let pars = term(Term::Word("pars".into()));
+        assert!(tokens.iter().any(|t| pars.contains(t)));
+        let the = term(Term::Word("THE".into()));
+        assert!(tokens.iter().any(|t| the.contains(t)));
+        // Phrases are the word tokens in order
+        let phrase = term(Term::Phrase("the parser".into()));
+        assert_eq!(phrase, format!("\"{} {}\"", tokens[1], tokens[2]));
+        assert_eq!(index::blind_term(&key, &Term::Word("--".into())), None);
+
+        // Other keys hash differently
+        let other = DataKey::new(&SecretKey::generate());
+        assert!(!index::blind_document(&other, "parser")
+            .split(' ')
+            .any(|t| t == tokens[2]));
+
+        let (highlighted, snippet) =
+            index::highlight("Fix the parser, then parse more", &["pars".into()], 4);
+        assert_eq!(
+            highlighted,
+            "Fix the <mark>parser</mark>, then <mark>parse</mark> more"
+        );
+        assert_eq!(
+            snippet,
+            "…the <mark>parser</mark>, then <mark>parse</mark>…"
+        );
+    }
+
+    #[test]
+    fn test_attachments_are_sealed_and_resealed() {
+        let dir = std::env::temp_dir().join(format!(
+            "claude_visual_attachments_test_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        let keys = Arc::new(KeyChain::default());
+        let store = AttachmentStore::new(dir.clone(), keys.clone());
+
+        let plain = store.write("c1", "notes.txt", b"plain text").unwrap();
+        assert_eq!(std::fs::read(&plain).unwrap(), b"plain text");
+        assert!(store.write("c1", "../escape", b"x").is_err());
+
+        keys.set_enabled(true);
+        keys.unlock(SecretKey::generate(), vec![(1, SecretKey::generate())], 1);
+        let sealed = store.write("c1", "image.png", b"pixels").unwrap();
+        assert!(!std::fs::read(&sealed).unwrap().ends_with(b"pixels"));
+        assert_eq!(store.read(&sealed).unwrap(), b"pixels");
+
+        let key = keys.key(1).unwrap();
+        assert_eq!(store.reseal_all(Some((1, &key))).unwrap(), 1);
+        assert_eq!(store.read(&plain).unwrap(), b"plain text");
+        assert!(!std::fs::read(&plain).unwrap().ends_with(b"plain text"));
+
+        keys.lock();
+        assert!(matches!(store.read(&sealed), Err(EncryptionError::Locked)));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -3,6 +3,8 @@
 pub mod cleanup;
 pub mod compression;
 pub mod database;
+pub mod encryption;
 pub mod models;
 pub mod pagination;
 pub mod pool;
+pub mod search;
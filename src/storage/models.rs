@@ -1,5 +1,7 @@
 //! Database models
 
+use std::path::PathBuf;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -38,7 +40,7 @@ pub struct Message {
     pub id: String,
     /// Parent conversation ID
     pub conversation_id: String,
-    /// Role (user, assistant, tool_use, tool_result, error)
+    /// Role (user, assistant, tool_use, tool_result, error, thinking, system)
     pub role: String,
     /// Message content
     pub content: String,
@@ -48,6 +50,78 @@ pub struct Message {
     pub is_error: bool,
     /// Timestamp
     pub timestamp: DateTime<Utc>,
+    /// Message this one follows in the conversation
+    #[serde(default)]
+    pub parent_id: Option<String>,
+    /// ID of the tool call (tool use) or of the call answered (tool result)
+    #[serde(default)]
+    pub tool_use_id: Option<String>,
+    /// Claude CLI session that produced the message
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// Structured details, stored as JSON
+    #[serde(default)]
+    pub metadata: MessageMetadata,
+}
+
+/// One path through a conversation's message tree, from its first message
+/// to a leaf
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Branch {
+    /// Last message of the branch
+    pub leaf_id: String,
+    /// First message not shared with other branches
+    pub fork_id: String,
+    /// Preview of the first message not shared with other branches
+    pub preview: String,
+    /// Number of messages from the start of the conversation
+    pub length: usize,
+    /// Time of the last message
+    pub updated_at: DateTime<Utc>,
+    /// Whether this is the conversation's active branch
+    pub is_active: bool,
+}
+
+/// Token usage of one turn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub cache_creation_input_tokens: u64,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub cache_read_input_tokens: u64,
+    /// Cost in USD, when reported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+}
+
+fn is_zero(n: &u64) -> bool {
+    *n == 0
+}
+
+/// Details of a message beyond its text
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MessageMetadata {
+    /// Input of a tool call
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_input: Option<serde_json::Value>,
+    /// Model that wrote the message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Token usage of the turn the message ends
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TokenUsage>,
+    /// Tool call of the sub-agent (Task) that produced the message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_tool_use_id: Option<String>,
+}
+
+impl MessageMetadata {
+    /// Whether nothing is set, so no JSON needs storing
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
 }
 
 impl Message {
@@ -65,9 +139,19 @@ impl Message {
             tool_name: None,
             is_error: false,
             timestamp: Utc::now(),
+            parent_id: None,
+            tool_use_id: None,
+            session_id: None,
+            metadata: MessageMetadata::default(),
         }
     }
 
+    /// Set the message this one follows
+    pub fn with_parent(mut self, parent_id: impl Into<String>) -> Self {
+        self.parent_id = Some(parent_id.into());
+        self
+    }
+
     /// Create a user message
     pub fn user(conversation_id: impl Into<String>, content: impl Into<String>) -> Self {
         Self::new(conversation_id, "user", content)
@@ -108,6 +192,81 @@ impl Message {
     }
 }
 
+/// A bookmarked message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MessageBookmark {
+    /// Bookmarked message
+    pub message_id: String,
+    /// Optional label
+    pub label: Option<String>,
+    /// When the bookmark was added
+    pub created_at: DateTime<Utc>,
+}
+
+/// An emoji reaction to a message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reaction {
+    /// Message reacted to
+    pub message_id: String,
+    /// The emoji
+    pub emoji: String,
+    /// When the reaction was added
+    pub created_at: DateTime<Utc>,
+}
+
+/// A free-form note on a conversation or one of its messages
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Note {
+    /// Unique identifier
+    pub id: String,
+    /// Conversation the note belongs to
+    pub conversation_id: String,
+    /// Message the note is attached to (None = the whole conversation)
+    pub message_id: Option<String>,
+    /// Note text
+    pub content: String,
+    /// Created timestamp
+    pub created_at: DateTime<Utc>,
+    /// Last updated timestamp
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Note {
+    /// Create a note on a conversation
+    pub fn new(conversation_id: impl Into<String>, content: impl Into<String>) -> Self {
+        let now = Utc::now();
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            conversation_id: conversation_id.into(),
+            message_id: None,
+            content: content.into(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Attach the note to a message
+    pub fn with_message(mut self, message_id: impl Into<String>) -> Self {
+        self.message_id = Some(message_id.into());
+        self
+    }
+}
+
+/// Bookmarks, pins, tags, reactions and notes of a conversation
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Annotations {
+    /// Bookmarked messages, in message order
+    pub bookmarks: Vec<MessageBookmark>,
+    /// IDs of pinned messages, in message order
+    pub pinned: Vec<String>,
+    /// Conversation tags, alphabetically
+    pub tags: Vec<String>,
+    /// Reactions, in message order then as added
+    pub reactions: Vec<Reaction>,
+    /// Notes, oldest first
+    pub notes: Vec<Note>,
+}
+
 /// Date range filter for search
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub enum DateRangeFilter {
@@ -166,24 +325,31 @@ impl DateRangeFilter {
 }
 
 /// Search filter options
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct SearchFilter {
     /// Date range filter
     pub date_range: DateRangeFilter,
     /// Filter by project ID (None = all projects)
     pub project_id: Option<String>,
+    /// Only conversations with all of these tags
+    pub tags: Vec<String>,
+    /// Only bookmarked messages
+    pub bookmarked_only: bool,
+    /// Only pinned messages
+    pub pinned_only: bool,
+    /// Only messages with this reaction
+    pub reaction: Option<String>,
 }
 
 impl SearchFilter {
     /// Check if any filters are active
     pub fn is_active(&self) -> bool {
-        self.date_range != DateRangeFilter::AllTime || self.project_id.is_some()
+        *self != Self::default()
     }
 
     /// Clear all filters
     pub fn clear(&mut self) {
-        self.date_range = DateRangeFilter::AllTime;
-        self.project_id = None;
+        *self = Self::default();
     }
 }
 
@@ -196,6 +362,8 @@ pub struct SearchResult {
     pub conversation_title: String,
     /// Highlighted content with matches
     pub highlighted: String,
+    /// Highlighted window of the content around the matches
+    pub snippet: String,
     /// Search rank (lower is better)
     pub rank: f64,
 }
@@ -237,3 +405,155 @@ impl SavedSession {
         serde_json::from_str(json)
     }
 }
+
+/// Kind of MCP operation in the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum McpCallKind {
+    /// `tools/call`
+    Tool,
+    /// `resources/read`
+    Resource,
+    /// `prompts/get`
+    Prompt,
+}
+
+impl McpCallKind {
+    /// Name stored in the database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            McpCallKind::Tool => "tool",
+            McpCallKind::Resource => "resource",
+            McpCallKind::Prompt => "prompt",
+        }
+    }
+
+    /// Parse a stored name
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "tool" => Some(McpCallKind::Tool),
+            "resource" => Some(McpCallKind::Resource),
+            "prompt" => Some(McpCallKind::Prompt),
+            _ => None,
+        }
+    }
+}
+
+/// How an MCP call was allowed (or refused)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum McpCallApproval {
+    /// No approval step applied (resources, prompts, app-initiated calls)
+    #[default]
+    NotRequired,
+    /// Matched the server's `autoApprove` list
+    AutoApproved,
+    /// Approved by the user
+    Approved,
+    /// Refused by the user; the call was not sent
+    Denied,
+}
+
+impl McpCallApproval {
+    /// Name stored in the database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            McpCallApproval::NotRequired => "not_required",
+            McpCallApproval::AutoApproved => "auto_approved",
+            McpCallApproval::Approved => "approved",
+            McpCallApproval::Denied => "denied",
+        }
+    }
+
+    /// Parse a stored name
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "not_required" => Some(McpCallApproval::NotRequired),
+            "auto_approved" => Some(McpCallApproval::AutoApproved),
+            "approved" => Some(McpCallApproval::Approved),
+            "denied" => Some(McpCallApproval::Denied),
+            _ => None,
+        }
+    }
+}
+
+/// MCP call recorded in the audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpCallRecord {
+    /// Unique identifier
+    pub id: String,
+    /// Conversation the call was made for, if any
+    pub conversation_id: Option<String>,
+    /// Server name
+    pub server: String,
+    /// Operation
+    pub kind: McpCallKind,
+    /// Tool or prompt name, or resource URI
+    pub name: String,
+    /// Arguments, with secrets redacted
+    pub arguments: Option<serde_json::Value>,
+    /// Short description of the result or error
+    pub result_summary: String,
+    /// Whether the call failed or the tool reported an error
+    pub is_error: bool,
+    /// Time from request to response
+    pub duration_ms: u64,
+    /// Approval decision
+    pub approval: McpCallApproval,
+    /// When the call was made
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Filter for listing MCP calls
+#[derive(Debug, Clone, Default)]
+pub struct McpCallFilter {
+    /// Only calls made for this conversation
+    pub conversation_id: Option<String>,
+    /// Only calls to this server
+    pub server: Option<String>,
+    /// Only this kind of operation
+    pub kind: Option<McpCallKind>,
+    /// Text contained in the name, arguments or result
+    pub query: Option<String>,
+    /// Only failed calls
+    pub errors_only: bool,
+    /// Date range filter
+    pub date_range: DateRangeFilter,
+}
+
+/// Import progress of a Claude CLI session transcript
+#[derive(Debug, Clone)]
+pub struct TranscriptImport {
+    /// Transcript file
+    pub path: PathBuf,
+    /// Claude CLI session the transcript records
+    pub session_id: String,
+    /// Conversation its messages went to, once there is one
+    pub conversation_id: Option<String>,
+    /// Bytes of the file read so far
+    pub offset: u64,
+    /// Last message stored, which entries with unknown parents follow
+    pub last_message_id: Option<String>,
+    /// Time of the newest message stored
+    pub last_timestamp: Option<DateTime<Utc>>,
+    /// Whether the transcript is no longer imported, because the app has
+    /// messages of the session or its conversation was deleted
+    pub skipped: bool,
+    /// When the file was last read
+    pub imported_at: DateTime<Utc>,
+}
+
+/// Entries read from a transcript since its last import
+#[derive(Debug, Clone)]
+pub struct TranscriptBatch {
+    /// Claude CLI session the transcript records
+    pub session_id: String,
+    /// Project the session ran in
+    pub project_id: Option<String>,
+    /// Title of the conversation, if one is created
+    pub title: String,
+    /// Messages in file order; their conversation is set on import
+    pub messages: Vec<Message>,
+    /// Offset of the file after the last complete line read
+    pub offset: u64,
+}
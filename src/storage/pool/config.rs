@@ -19,6 +19,10 @@ pub struct PoolConfig {
     pub enable_wal: bool,
     /// Busy timeout in milliseconds
     pub busy_timeout_ms: u32,
+    /// Enforce foreign key constraints
+    pub foreign_keys: bool,
+    /// Open connections with `query_only`, refusing writes
+    pub read_only: bool,
 }
 
 impl Default for PoolConfig {
@@ -31,11 +35,31 @@ impl Default for PoolConfig {
             health_check_interval: Duration::from_secs(60),
             enable_wal: true,
             busy_timeout_ms: 5000,
+            foreign_keys: true,
+            read_only: false,
         }
     }
 }
 
 impl PoolConfig {
+    /// Same settings for connections that only read
+    pub fn readers(&self) -> Self {
+        Self {
+            read_only: true,
+            ..self.clone()
+        }
+    }
+
+    /// Same settings with a single connection, so writes are serialized
+    pub fn writer(&self) -> Self {
+        Self {
+            min_connections: 1,
+            max_connections: 1,
+            read_only: false,
+            ..self.clone()
+        }
+    }
+
     /// Configuration for high concurrency
     pub fn high_concurrency() -> Self {
         Self {
@@ -46,6 +70,8 @@ impl PoolConfig {
             health_check_interval: Duration::from_secs(30),
             enable_wal: true,
             busy_timeout_ms: 10000,
+            foreign_keys: true,
+            read_only: false,
         }
     }
 
@@ -59,6 +85,8 @@ impl PoolConfig {
             health_check_interval: Duration::from_secs(120),
             enable_wal: true,
             busy_timeout_ms: 3000,
+            foreign_keys: true,
+            read_only: false,
         }
     }
 }
@@ -3,12 +3,15 @@
 use std::collections::VecDeque;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use anyhow::Result;
 use rusqlite::Connection;
 
+use crate::storage::compression;
+use crate::storage::encryption::KeyChain;
+
 use super::config::PoolConfig;
 use super::connection::PooledConnection;
 use super::guard::PooledConnectionGuard;
@@ -36,11 +39,18 @@ pub struct DatabasePool {
     pub(crate) inner: Mutex<PoolInner>,
     /// Total connections (atomic for fast reads)
     pub(crate) total_connections: AtomicUsize,
+    /// Keys the SQL functions read encrypted messages with
+    pub(crate) keys: Arc<KeyChain>,
 }
 
 impl DatabasePool {
     /// Create a new connection pool
     pub fn new(db_path: PathBuf, config: PoolConfig) -> Result<Self> {
+        Self::with_keys(db_path, config, Arc::default())
+    }
+
+    /// Create a connection pool reading encrypted messages with `keys`
+    pub fn with_keys(db_path: PathBuf, config: PoolConfig, keys: Arc<KeyChain>) -> Result<Self> {
         let pool = Self {
             config,
             db_path,
@@ -51,6 +61,7 @@ impl DatabasePool {
                 closed: false,
             }),
             total_connections: AtomicUsize::new(0),
+            keys,
         };
 
         // Initialize minimum connections
@@ -84,6 +95,17 @@ impl DatabasePool {
         if self.config.enable_wal {
             conn.execute_batch("PRAGMA journal_mode=WAL")?;
         }
+        if self.config.foreign_keys {
+            conn.execute_batch("PRAGMA foreign_keys=ON")?;
+        }
+        if self.config.read_only {
+            conn.execute_batch("PRAGMA query_only=ON")?;
+        }
+        // Deleted rows of an encrypted database may be plain text from
+        // before it was encrypted
+        if self.keys.is_enabled() {
+            conn.execute_batch("PRAGMA secure_delete=ON")?;
+        }
 
         // Additional performance settings
         conn.execute_batch(
@@ -94,6 +116,9 @@ impl DatabasePool {
             "#,
         )?;
 
+        // Functions the schema relies on, e.g. to index compressed messages
+        compression::register_functions(&conn, self.keys.clone())?;
+
         Ok(conn)
     }
 
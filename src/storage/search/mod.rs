@@ -0,0 +1,132 @@
+//! Conversation Search
+//!
+//! A small query language for the history search box, such as
+//! `role:assistant tool:Bash after:2026-01-01 "exact phrase" -error`.
+//! Queries compile to quoted FTS5 expressions plus bound SQL predicates,
+//! so no user input reaches either as syntax.
+
+mod parser;
+mod query;
+
+pub use query::{Filter, Predicate, QueryError, SearchQuery, Term, ROLES};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_parse_terms_and_filters() {
+        let query = SearchQuery::parse(
+            r#"role:assistant tool:Bash after:2026-01-01 "exact phrase" -error cargo OR npm"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            query.clauses,
+            vec![
+                vec![Term::Phrase("exact phrase".to_string())],
+                vec![
+                    Term::Word("cargo".to_string()),
+                    Term::Word("npm".to_string())
+                ],
+            ]
+        );
+        assert_eq!(query.excluded, vec![Term::Word("error".to_string())]);
+        assert_eq!(
+            query.filters,
+            vec![
+                Filter {
+                    predicate: Predicate::Role("assistant".to_string()),
+                    negated: false,
+                },
+                Filter {
+                    predicate: Predicate::Tool("Bash".to_string()),
+                    negated: false,
+                },
+                Filter {
+                    predicate: Predicate::After(Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap()),
+                    negated: false,
+                },
+            ]
+        );
+        assert_eq!(
+            query.fts_expression().as_deref(),
+            Some(r#""exact phrase" AND ("cargo"* OR "npm"*)"#)
+        );
+        assert_eq!(query.excluded_expression().as_deref(), Some(r#""error"*"#));
+    }
+
+    #[test]
+    fn test_parse_negation_and_flags() {
+        let query =
+            SearchQuery::parse(r#"NOT draft -is:pinned is:bookmarked tag:"my tag" -"so far""#)
+                .unwrap();
+
+        assert!(query.clauses.is_empty());
+        assert_eq!(
+            query.excluded,
+            vec![
+                Term::Word("draft".to_string()),
+                Term::Phrase("so far".to_string())
+            ]
+        );
+        assert_eq!(
+            query.filters,
+            vec![
+                Filter {
+                    predicate: Predicate::Pinned,
+                    negated: true,
+                },
+                Filter {
+                    predicate: Predicate::Bookmarked,
+                    negated: false,
+                },
+                Filter {
+                    predicate: Predicate::Tag("my tag".to_string()),
+                    negated: false,
+                },
+            ]
+        );
+        assert_eq!(query.fts_expression(), None);
+    }
+
+    #[test]
+    fn test_fts_syntax_in_input_is_quoted() {
+        let query = SearchQuery::parse(r#"NEAR(a b) col:x* say"hi" - -- ::"#).unwrap();
+
+        assert_eq!(
+            query.fts_expression().as_deref(),
+            Some(r#""NEAR(a"* AND "b)"* AND "col:x*"* AND "say""hi"""*"#)
+        );
+        assert!(query.excluded.is_empty());
+        assert!(query.filters.is_empty());
+        assert!(SearchQuery::parse("  ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            SearchQuery::parse("role:robot"),
+            Err(QueryError::UnknownRole("robot".to_string()))
+        );
+        assert_eq!(
+            SearchQuery::parse("before:yesterday"),
+            Err(QueryError::InvalidDate("yesterday".to_string()))
+        );
+        assert_eq!(
+            SearchQuery::parse("is:starred"),
+            Err(QueryError::UnknownFlag("starred".to_string()))
+        );
+        assert_eq!(
+            SearchQuery::parse("tool:"),
+            Err(QueryError::MissingValue("tool".to_string()))
+        );
+
+        let query = SearchQuery::parse("before:2026-03-01T12:30:00+02:00").unwrap();
+        assert_eq!(
+            query.filters[0].predicate,
+            Predicate::Before(Utc.with_ymd_and_hms(2026, 3, 1, 10, 30, 0).unwrap())
+        );
+    }
+}
@@ -0,0 +1,183 @@
+//! Search query parser
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+use super::query::{Filter, Predicate, QueryError, SearchQuery, Term, ROLES};
+
+/// One whitespace-separated piece of the query
+#[derive(Debug)]
+struct Token {
+    /// Preceded by `-`
+    negated: bool,
+    /// `key` of `key:value`
+    key: Option<String>,
+    value: String,
+    /// Value was in double quotes
+    quoted: bool,
+}
+
+/// Split the query into tokens, keeping quoted text together
+fn tokenize(input: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let negated = chars.next_if_eq(&'-').is_some();
+        let mut key = None;
+        let mut value = String::new();
+        let mut quoted = false;
+
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if value.is_empty() => {
+                    // An unclosed quote runs to the end of the query
+                    quoted = true;
+                    value.extend(chars.by_ref().take_while(|&c| c != '"'));
+                    break;
+                }
+                ':' if key.is_none() && !value.is_empty() => {
+                    key = Some(std::mem::take(&mut value));
+                }
+                c if c.is_whitespace() => break,
+                c => value.push(c),
+            }
+        }
+
+        tokens.push(Token {
+            negated,
+            key,
+            value,
+            quoted,
+        });
+    }
+
+    tokens
+}
+
+/// Parse a date as the start of that day (UTC), or an RFC 3339 time
+fn parse_date(value: &str) -> Result<DateTime<Utc>, QueryError> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        if let Some(time) = date.and_hms_opt(0, 0, 0) {
+            return Ok(time.and_utc());
+        }
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|_| QueryError::InvalidDate(value.to_string()))
+}
+
+/// Predicate for a `key:value` token, or None if `key` is not a filter
+fn parse_predicate(key: &str, value: &str) -> Result<Option<Predicate>, QueryError> {
+    let key = key.to_lowercase();
+    let known = matches!(
+        key.as_str(),
+        "role" | "tool" | "after" | "before" | "tag" | "project" | "reaction" | "is"
+    );
+    if !known {
+        return Ok(None);
+    }
+    if value.is_empty() {
+        return Err(QueryError::MissingValue(key));
+    }
+
+    let predicate = match key.as_str() {
+        "role" => {
+            let role = value.to_lowercase();
+            if !ROLES.contains(&role.as_str()) {
+                return Err(QueryError::UnknownRole(value.to_string()));
+            }
+            Predicate::Role(role)
+        }
+        "tool" => Predicate::Tool(value.to_string()),
+        "after" => Predicate::After(parse_date(value)?),
+        "before" => Predicate::Before(parse_date(value)?),
+        "tag" => Predicate::Tag(value.to_string()),
+        "project" => Predicate::Project(value.to_string()),
+        "reaction" => Predicate::Reaction(value.to_string()),
+        _ => match value.to_lowercase().as_str() {
+            "bookmarked" => Predicate::Bookmarked,
+            "pinned" => Predicate::Pinned,
+            "error" => Predicate::Error,
+            _ => return Err(QueryError::UnknownFlag(value.to_string())),
+        },
+    };
+    Ok(Some(predicate))
+}
+
+/// Text term for a token, if it has any searchable characters
+fn term(token: &Token) -> Option<Term> {
+    let text = match &token.key {
+        Some(key) => format!("{}:{}", key, token.value),
+        None => token.value.clone(),
+    };
+    if !text.chars().any(char::is_alphanumeric) {
+        return None;
+    }
+    Some(if token.quoted {
+        Term::Phrase(text)
+    } else {
+        Term::Word(text)
+    })
+}
+
+impl SearchQuery {
+    /// Parse the search box query language
+    ///
+    /// Words match as prefixes and must all appear, `"quoted text"` matches
+    /// as a phrase, `a OR b` accepts either, and `-word` or `NOT word`
+    /// excludes. `role:`, `tool:`, `after:`, `before:`, `tag:`, `project:`,
+    /// `reaction:` and `is:bookmarked|pinned|error` filter, and can be
+    /// negated with `-` too. Other `key:value` pairs are searched as text.
+    pub fn parse(input: &str) -> Result<Self, QueryError> {
+        let mut query = SearchQuery::default();
+        let mut negate_next = false;
+        let mut or_next = false;
+
+        for token in tokenize(input) {
+            if !token.quoted && !token.negated && token.key.is_none() {
+                match token.value.as_str() {
+                    "OR" => {
+                        or_next = !query.clauses.is_empty();
+                        continue;
+                    }
+                    "NOT" => {
+                        negate_next = true;
+                        continue;
+                    }
+                    "AND" => continue,
+                    _ => {}
+                }
+            }
+
+            let negated = token.negated || std::mem::take(&mut negate_next);
+            let joins_previous = std::mem::take(&mut or_next);
+
+            if let Some(key) = &token.key {
+                if let Some(predicate) = parse_predicate(key, &token.value)? {
+                    query.filters.push(Filter { predicate, negated });
+                    continue;
+                }
+            }
+
+            let Some(term) = term(&token) else {
+                continue;
+            };
+            if negated {
+                query.excluded.push(term);
+            } else if joins_previous {
+                if let Some(clause) = query.clauses.last_mut() {
+                    clause.push(term);
+                }
+            } else {
+                query.clauses.push(vec![term]);
+            }
+        }
+
+        Ok(query)
+    }
+}
@@ -0,0 +1,228 @@
+//! Parsed search query and its compiled forms
+
+use chrono::{DateTime, Utc};
+use rusqlite::types::Value as SqlValue;
+
+use crate::storage::models::SearchFilter;
+
+/// Message roles accepted by `role:`
+pub const ROLES: &[&str] = &[
+    "user",
+    "assistant",
+    "tool_use",
+    "tool_result",
+    "error",
+    "thinking",
+    "system",
+];
+
+/// Error parsing a search query
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum QueryError {
+    #[error("Unknown role: {0}")]
+    UnknownRole(String),
+    #[error("Invalid date: {0} (expected YYYY-MM-DD or RFC 3339)")]
+    InvalidDate(String),
+    #[error("Unknown flag: is:{0}")]
+    UnknownFlag(String),
+    #[error("Missing value for {0}:")]
+    MissingValue(String),
+}
+
+/// Text to look for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Term {
+    /// Word matched as a prefix
+    Word(String),
+    /// Exact phrase
+    Phrase(String),
+}
+
+impl Term {
+    /// Text of the word or phrase
+    pub fn text(&self) -> &str {
+        match self {
+            Term::Word(text) | Term::Phrase(text) => text,
+        }
+    }
+
+    /// FTS5 form, quoted so user input is never read as FTS5 syntax
+    fn fts(&self) -> String {
+        match self {
+            Term::Word(word) => format!("\"{}\"*", word.replace('"', "\"\"")),
+            Term::Phrase(phrase) => format!("\"{}\"", phrase.replace('"', "\"\"")),
+        }
+    }
+}
+
+/// Condition on a message outside its text
+#[derive(Debug, Clone, PartialEq)]
+pub enum Predicate {
+    /// Message role, one of [`ROLES`]
+    Role(String),
+    /// Tool name, case-insensitive
+    Tool(String),
+    /// Sent at or after this time
+    After(DateTime<Utc>),
+    /// Sent before this time
+    Before(DateTime<Utc>),
+    /// Conversation has this tag
+    Tag(String),
+    /// Conversation belongs to this project, by ID or name
+    Project(String),
+    /// Message has this reaction
+    Reaction(String),
+    /// Message is bookmarked
+    Bookmarked,
+    /// Message is pinned
+    Pinned,
+    /// Message is an error
+    Error,
+}
+
+impl Predicate {
+    /// SQL condition on `messages m` joined to `conversations c`
+    ///
+    /// Every `?` in the condition stands for the returned value.
+    pub(crate) fn sql(&self) -> (&'static str, Option<SqlValue>) {
+        let text = |s: &str| Some(SqlValue::Text(s.to_string()));
+        match self {
+            Predicate::Role(role) => ("m.role = ?", text(role)),
+            // Never NULL, so negated it keeps messages without a tool
+            Predicate::Tool(tool) => (
+                "(m.tool_name IS NOT NULL AND m.tool_name = ? COLLATE NOCASE)",
+                text(tool),
+            ),
+            Predicate::After(time) => ("m.timestamp >= ?", text(&time.to_rfc3339())),
+            Predicate::Before(time) => ("m.timestamp < ?", text(&time.to_rfc3339())),
+            Predicate::Tag(tag) => (
+                "EXISTS (SELECT 1 FROM conversation_tags t \
+                 WHERE t.conversation_id = m.conversation_id AND t.tag = ?)",
+                text(tag),
+            ),
+            Predicate::Project(project) => (
+                "EXISTS (SELECT 1 FROM projects p WHERE p.id = c.project_id \
+                 AND (p.id = ? OR p.name = ? COLLATE NOCASE))",
+                text(project),
+            ),
+            Predicate::Reaction(emoji) => (
+                "EXISTS (SELECT 1 FROM message_reactions r \
+                 WHERE r.message_id = m.id AND r.emoji = ?)",
+                text(emoji),
+            ),
+            Predicate::Bookmarked => (
+                "EXISTS (SELECT 1 FROM message_bookmarks b WHERE b.message_id = m.id)",
+                None,
+            ),
+            Predicate::Pinned => (
+                "EXISTS (SELECT 1 FROM pinned_messages p WHERE p.message_id = m.id)",
+                None,
+            ),
+            Predicate::Error => ("m.is_error != 0", None),
+        }
+    }
+}
+
+/// A predicate, possibly negated
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    pub predicate: Predicate,
+    pub negated: bool,
+}
+
+/// Parsed search query
+///
+/// A message matches when it contains a term of every clause, none of
+/// the excluded terms, and passes every filter.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchQuery {
+    /// Clauses that must all match, each a choice of terms
+    pub clauses: Vec<Vec<Term>>,
+    /// Terms that must not match
+    pub excluded: Vec<Term>,
+    /// Conditions outside the text
+    pub filters: Vec<Filter>,
+}
+
+impl SearchQuery {
+    /// Check if the query matches nothing in particular
+    pub fn is_empty(&self) -> bool {
+        self.clauses.is_empty() && self.excluded.is_empty() && self.filters.is_empty()
+    }
+
+    /// Add a condition
+    pub fn with(mut self, predicate: Predicate) -> Self {
+        self.filters.push(Filter {
+            predicate,
+            negated: false,
+        });
+        self
+    }
+
+    /// Add the conditions set in the search filter panel
+    pub fn with_filter(mut self, filter: &SearchFilter) -> Self {
+        if let Some(start) = filter.date_range.start_date() {
+            self = self.with(Predicate::After(start));
+        }
+        if let Some(project_id) = &filter.project_id {
+            self = self.with(Predicate::Project(project_id.clone()));
+        }
+        for tag in &filter.tags {
+            self = self.with(Predicate::Tag(tag.trim().to_string()));
+        }
+        if let Some(emoji) = &filter.reaction {
+            self = self.with(Predicate::Reaction(emoji.clone()));
+        }
+        if filter.bookmarked_only {
+            self = self.with(Predicate::Bookmarked);
+        }
+        if filter.pinned_only {
+            self = self.with(Predicate::Pinned);
+        }
+        self
+    }
+
+    /// FTS5 expression for the wanted terms, if there are any
+    pub fn fts_expression(&self) -> Option<String> {
+        self.fts_expression_with(|term| Some(term.fts()))
+    }
+
+    /// FTS5 expression for the wanted terms, each compiled by `compile`
+    ///
+    /// Terms `compile` returns `None` for are left out.
+    pub fn fts_expression_with(&self, compile: impl Fn(&Term) -> Option<String>) -> Option<String> {
+        Self::conjunction(&self.clauses, &compile)
+    }
+
+    /// FTS5 expression matching any excluded term, if there are any
+    ///
+    /// FTS5 has no stand-alone `NOT`, so excluded terms are matched on their
+    /// own and the hits removed.
+    pub fn excluded_expression(&self) -> Option<String> {
+        self.excluded_expression_with(|term| Some(term.fts()))
+    }
+
+    /// FTS5 expression matching any excluded term, each compiled by `compile`
+    pub fn excluded_expression_with(
+        &self,
+        compile: impl Fn(&Term) -> Option<String>,
+    ) -> Option<String> {
+        Self::conjunction(std::slice::from_ref(&self.excluded), &compile)
+    }
+
+    fn conjunction(
+        clauses: &[Vec<Term>],
+        compile: &dyn Fn(&Term) -> Option<String>,
+    ) -> Option<String> {
+        let parts: Vec<String> = clauses
+            .iter()
+            .map(|terms| terms.iter().filter_map(compile).collect::<Vec<_>>())
+            .filter(|terms| !terms.is_empty())
+            .map(|terms| match terms.as_slice() {
+                [term] => term.clone(),
+                terms => format!("({})", terms.join(" OR ")),
+            })
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(" AND "))
+    }
+}
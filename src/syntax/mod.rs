@@ -2,9 +2,11 @@
 
 pub mod highlighter;
 pub mod queries;
+pub mod symbols;
 
 pub use highlighter::{HighlightedSpan, Highlighter, SyntaxHighlighter};
 pub use queries::{
     get_query, is_language_supported, prewarm_queries, query_cache, CompiledQuery, PrewarmResult,
     QueryCache, QueryCacheStats, QueryError,
 };
+pub use symbols::{extract_symbols, supports_symbols, Symbol, SymbolKind};
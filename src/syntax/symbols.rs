@@ -0,0 +1,241 @@
+//! Symbol extraction using tree-sitter
+//!
+//! Finds top-level and nested definitions (functions, types, impls, classes)
+//! so callers can outline a file or cut it along symbol boundaries.
+
+use tree_sitter::{Language, Node, Parser};
+
+/// Kind of extracted symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Function,
+    Method,
+    Struct,
+    Enum,
+    Trait,
+    Impl,
+    Class,
+    Interface,
+    Module,
+    Constant,
+    Type,
+}
+
+/// A definition found in source code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    /// Symbol name
+    pub name: String,
+    /// Symbol kind
+    pub kind: SymbolKind,
+    /// First line of the definition, trimmed
+    pub signature: String,
+    /// Start line (1-based)
+    pub start_line: usize,
+    /// End line (1-based, inclusive)
+    pub end_line: usize,
+    /// Start byte offset
+    pub start_byte: usize,
+    /// End byte offset
+    pub end_byte: usize,
+    /// Nesting depth (0 for top level)
+    pub depth: usize,
+}
+
+/// Resolve a language name or file extension to a tree-sitter grammar
+fn language_for(language: &str) -> Option<Language> {
+    match language.to_lowercase().as_str() {
+        "rust" | "rs" => Some(tree_sitter_rust::LANGUAGE.into()),
+        "python" | "py" => Some(tree_sitter_python::LANGUAGE.into()),
+        "javascript" | "js" | "jsx" | "mjs" | "cjs" => {
+            Some(tree_sitter_javascript::LANGUAGE.into())
+        }
+        "typescript" | "ts" => Some(tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into()),
+        "tsx" => Some(tree_sitter_typescript::LANGUAGE_TSX.into()),
+        _ => None,
+    }
+}
+
+/// Whether symbols can be extracted for a language or extension
+pub fn supports_symbols(language: &str) -> bool {
+    language_for(language).is_some()
+}
+
+/// Map a node kind to a symbol kind
+fn symbol_kind(node_kind: &str, in_container: bool) -> Option<SymbolKind> {
+    let kind = match node_kind {
+        "function_item"
+        | "function_definition"
+        | "function_declaration"
+        | "generator_function_declaration" => {
+            if in_container {
+                SymbolKind::Method
+            } else {
+                SymbolKind::Function
+            }
+        }
+        "method_definition" | "function_signature_item" | "method_signature" => SymbolKind::Method,
+        "struct_item" | "union_item" => SymbolKind::Struct,
+        "enum_item" | "enum_declaration" => SymbolKind::Enum,
+        "trait_item" => SymbolKind::Trait,
+        "impl_item" => SymbolKind::Impl,
+        "class_definition" | "class_declaration" | "abstract_class_declaration" => {
+            SymbolKind::Class
+        }
+        "interface_declaration" => SymbolKind::Interface,
+        "mod_item" | "module" | "internal_module" => SymbolKind::Module,
+        "const_item" | "static_item" => SymbolKind::Constant,
+        "type_item" | "type_alias_declaration" => SymbolKind::Type,
+        _ => return None,
+    };
+    Some(kind)
+}
+
+/// Extract the display name of a definition node
+fn symbol_name(node: Node, source: &[u8]) -> Option<String> {
+    let name_node = node
+        .child_by_field_name("name")
+        .or_else(|| node.child_by_field_name("type"))?;
+    let mut name = name_node.utf8_text(source).ok()?.to_string();
+
+    // `impl Trait for Type` reads better as "Type: Trait"
+    if node.kind() == "impl_item" {
+        if let Some(tr) = node
+            .child_by_field_name("trait")
+            .and_then(|t| t.utf8_text(source).ok())
+        {
+            name = format!("{}: {}", name, tr);
+        }
+    }
+    Some(name)
+}
+
+/// First line of a definition without its body opener
+fn signature(node: Node, source: &str) -> String {
+    let text = &source[node.start_byte()..node.end_byte()];
+    let line = text.lines().next().unwrap_or("").trim();
+    line.trim_end_matches('{').trim_end().to_string()
+}
+
+fn collect(node: Node, source: &str, depth: usize, in_container: bool, out: &mut Vec<Symbol>) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        // Exported JS/TS declarations and Python decorators wrap the definition
+        if matches!(child.kind(), "export_statement" | "decorated_definition") {
+            collect(child, source, depth, in_container, out);
+            continue;
+        }
+
+        let Some(kind) = symbol_kind(child.kind(), in_container) else {
+            // Descend into bodies of non-symbol nodes (e.g. `declaration_list`)
+            if matches!(
+                child.kind(),
+                "declaration_list" | "class_body" | "block" | "statement_block"
+            ) && in_container
+            {
+                collect(child, source, depth, in_container, out);
+            }
+            continue;
+        };
+
+        let Some(name) = symbol_name(child, source.as_bytes()) else {
+            continue;
+        };
+
+        out.push(Symbol {
+            name,
+            kind,
+            signature: signature(child, source),
+            start_line: child.start_position().row + 1,
+            end_line: child.end_position().row + 1,
+            start_byte: child.start_byte(),
+            end_byte: child.end_byte(),
+            depth,
+        });
+
+        let is_container = matches!(
+            kind,
+            SymbolKind::Impl
+                | SymbolKind::Trait
+                | SymbolKind::Class
+                | SymbolKind::Interface
+                | SymbolKind::Module
+        );
+        if is_container {
+            if let Some(body) = child.child_by_field_name("body") {
+                let methods = kind != SymbolKind::Module;
+                collect(body, source, depth + 1, methods, out);
+            }
+        }
+    }
+}
+
+/// Extract symbols from source code
+///
+/// Returns an empty list for unsupported languages or unparsable input.
+/// Symbols are ordered by position.
+pub fn extract_symbols(source: &str, language: &str) -> Vec<Symbol> {
+    let Some(language) = language_for(language) else {
+        return Vec::new();
+    };
+    let mut parser = Parser::new();
+    if parser.set_language(&language).is_err() {
+        return Vec::new();
+    }
+    let Some(tree) = parser.parse(source, None) else {
+        return Vec::new();
+    };
+
+    let mut symbols = Vec::new();
+    collect(tree.root_node(), source, 0, false, &mut symbols);
+    symbols.sort_by_key(|s| s.start_byte);
+    symbols
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rust_symbols() {
+        let source = r#"
+pub struct Config {
+    name: String,
+}
+
+impl Config {
+    pub fn new() -> Self {
+        todo!()
+    }
+}
+
+fn helper(x: u32) -> u32 {
+    x
+}
+"#;
+        let symbols = extract_symbols(source, "rs");
+        let names: Vec<_> = symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["Config", "Config", "new", "helper"]);
+        assert_eq!(symbols[1].kind, SymbolKind::Impl);
+        assert_eq!(symbols[2].kind, SymbolKind::Method);
+        assert_eq!(symbols[2].depth, 1);
+        assert_eq!(symbols[3].signature, "fn helper(x: u32) -> u32");
+        assert_eq!(symbols[3].start_line, 12);
+    }
+
+    #[test]
+    fn test_python_symbols() {
+        let source =
+            "class Greeter:\n    def greet(self):\n        pass\n\ndef main():\n    pass\n";
+        let symbols = extract_symbols(source, "python");
+        let names: Vec<_> = symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["Greeter", "greet", "main"]);
+        assert_eq!(symbols[1].kind, SymbolKind::Method);
+    }
+
+    #[test]
+    fn test_unsupported_language() {
+        assert!(extract_symbols("whatever", "cobol").is_empty());
+        assert!(!supports_symbols("cobol"));
+    }
+}
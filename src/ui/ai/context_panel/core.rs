@@ -2,7 +2,9 @@
 
 use gpui::*;
 
-use crate::ai::context::{ContextItem, ContextItemType, ContextManager};
+use crate::ai::context::{
+    ContextItem, ContextItemType, ContextManager, ContextSelection, SelectionEntry,
+};
 
 use super::types::ContextPanelEvent;
 
@@ -18,6 +20,8 @@ pub struct ContextPanel {
     pub(crate) focus_handle: FocusHandle,
     /// Show token counts
     pub(crate) show_token_counts: bool,
+    /// Result of the last selection pass (what was dropped or trimmed)
+    pub(crate) last_selection: Option<ContextSelection>,
 }
 
 impl ContextPanel {
@@ -28,6 +32,7 @@ impl ContextPanel {
             selected_item_id: None,
             focus_handle: cx.focus_handle(),
             show_token_counts: true,
+            last_selection: None,
         }
     }
 
@@ -49,9 +54,8 @@ impl ContextPanel {
 
     /// Add a context item
     pub fn add_item(&mut self, item: ContextItem, cx: &mut Context<Self>) {
-        if self.context.add(item).is_ok() {
-            cx.notify();
-        }
+        self.context.add(item);
+        cx.notify();
     }
 
     /// Remove a context item
@@ -90,6 +94,37 @@ impl ContextPanel {
         self.context.format_for_prompt()
     }
 
+    /// Format pinned items, which are sent apart from the per-prompt selection
+    pub fn format_pinned_for_prompt(&self) -> String {
+        self.context.format_pinned_for_prompt()
+    }
+
+    /// Select context relevant to a prompt and format its unpinned items
+    ///
+    /// The selection report is kept so the panel can show which items were
+    /// dropped or trimmed for this prompt.
+    pub fn format_unpinned_selection(&mut self, prompt: &str, cx: &mut Context<Self>) -> String {
+        let selection = self.context.select_for_prompt(prompt);
+        let output = selection.format_unpinned_for_prompt();
+        self.last_selection = Some(selection);
+        cx.notify();
+        output
+    }
+
+    /// Get the last selection result
+    pub fn last_selection(&self) -> Option<&ContextSelection> {
+        self.last_selection.as_ref()
+    }
+
+    /// Selection report entry for an item, if it was dropped or trimmed
+    pub(crate) fn selection_entry(&self, id: &str) -> Option<&SelectionEntry> {
+        self.last_selection
+            .as_ref()?
+            .entries
+            .iter()
+            .find(|e| e.id == id && e.tokens != e.original_tokens)
+    }
+
     /// Get icon for item type
     pub(crate) fn icon_for_type(item_type: &ContextItemType) -> &'static str {
         match item_type {
@@ -3,7 +3,7 @@
 use gpui::prelude::*;
 use gpui::*;
 
-use crate::ai::context::ContextItem;
+use crate::ai::context::{ContextItem, SelectionOutcome};
 
 use super::core::ContextPanel;
 use super::types::{ContextPanelEvent, SimpleColors};
@@ -103,6 +103,20 @@ impl ContextPanel {
         let item_id_for_remove = item.id.clone();
         let is_selected = self.selected_item_id.as_deref() == Some(&item.id);
         let is_pinned = item.pinned;
+        let selection_note = self
+            .selection_entry(&item.id)
+            .map(|entry| match &entry.outcome {
+                SelectionOutcome::Trimmed(strategy) => (
+                    format!(
+                        "{} · {}/{} tokens",
+                        strategy.label(),
+                        entry.tokens,
+                        entry.original_tokens
+                    ),
+                    theme.warning,
+                ),
+                _ => ("dropped from last prompt".to_string(), theme.error),
+            });
 
         div()
             .id(SharedString::from(format!("context-item-{}", item.id)))
@@ -159,7 +173,10 @@ impl ContextPanel {
                             .text_xs()
                             .text_color(theme.text_muted)
                             .child(format!("{} tokens", item.token_count)),
-                    ),
+                    )
+                    .when_some(selection_note, |el, (note, color)| {
+                        el.child(div().text_xs().text_color(color).child(note))
+                    }),
             )
             .child(
                 // Pin button
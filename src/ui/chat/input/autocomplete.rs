@@ -2,8 +2,12 @@
 
 use super::utils::{fuzzy_match_commands, CommandMatch};
 use super::ChatInput;
+use crate::mcp::{complete_tool_command, McpTool};
 use gpui::*;
 
+/// Slash command whose arguments are completed from MCP tool schemas
+const MCP_TOOL_COMMAND: &str = "mcp-tool ";
+
 impl ChatInput {
     /// Set available slash commands (from Claude CLI session info)
     pub fn set_available_commands(&mut self, commands: Vec<String>, cx: &mut Context<Self>) {
@@ -82,9 +86,36 @@ impl ChatInput {
         cx.notify();
     }
 
+    /// Set MCP tools (by server) used to complete `/mcp-tool` arguments
+    pub fn set_mcp_tools(&mut self, tools: Vec<(String, McpTool)>, cx: &mut Context<Self>) {
+        self.mcp_tools = tools;
+        cx.notify();
+    }
+
     /// Check if text starts with "/" and update autocomplete state with fuzzy matching
     pub(super) fn update_command_autocomplete(&mut self) {
-        if self.text.starts_with('/') && !self.text.contains(' ') {
+        if let Some(args) = self
+            .text
+            .strip_prefix('/')
+            .and_then(|rest| rest.strip_prefix(MCP_TOOL_COMMAND))
+        {
+            // Servers, tools, then parameters from the tool's input schema
+            self.command_matches = complete_tool_command(&self.mcp_tools, args)
+                .into_iter()
+                .map(|completion| CommandMatch {
+                    command: format!("{}{}", MCP_TOOL_COMMAND, completion),
+                    score: 0,
+                    matched_indices: Vec::new(),
+                })
+                .collect();
+            self.filtered_commands = self
+                .command_matches
+                .iter()
+                .map(|m| m.command.clone())
+                .collect();
+            self.show_command_autocomplete = !self.filtered_commands.is_empty();
+            self.selected_command_index = 0;
+        } else if self.text.starts_with('/') && !self.text.contains(' ') {
             // Extract the partial command (without the leading /)
             let partial = &self.text[1..];
 
@@ -142,11 +173,21 @@ impl ChatInput {
             .get(self.selected_command_index)
             .cloned()
         {
-            self.text = format!("/{} ", cmd);
-            self.cursor_position = self.text.len();
-            self.show_command_autocomplete = false;
+            self.apply_command_completion(&cmd);
             self.filtered_commands.clear();
             cx.notify();
         }
     }
+
+    /// Replace the input with a chosen completion
+    pub(super) fn apply_command_completion(&mut self, cmd: &str) {
+        self.text = if cmd.ends_with('=') {
+            // Parameter name; the value follows directly
+            format!("/{}", cmd)
+        } else {
+            format!("/{} ", cmd)
+        };
+        self.cursor_position = self.text.len();
+        self.show_command_autocomplete = false;
+    }
 }
@@ -197,9 +197,7 @@ impl ChatInput {
                     .when(is_selected, |d| d.border_l_2().border_color(accent))
                     .hover(|s| s.bg(theme.colors.surface_hover))
                     .on_click(cx.listener(move |this, _, _window, cx| {
-                        this.text = format!("/{} ", cmd_clone);
-                        this.cursor_position = this.text.len();
-                        this.show_command_autocomplete = false;
+                        this.apply_command_completion(&cmd_clone);
                         this.filtered_commands.clear();
                         this.command_matches.clear();
                         cx.notify();
@@ -37,6 +37,17 @@ impl ChatInput {
             .collect()
     }
 
+    /// Get URLs from mentions
+    pub fn url_mentions(&self) -> Vec<String> {
+        self.mentions
+            .iter()
+            .filter_map(|m| match &m.kind {
+                MentionKind::Url(url) => Some(url.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
     /// Remove a file mention by path
     pub fn remove_file_mention(&mut self, path: &std::path::Path, cx: &mut Context<Self>) {
         // Find the mention to remove
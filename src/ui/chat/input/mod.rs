@@ -64,6 +64,8 @@ pub struct ChatInput {
     pub(crate) filtered_commands: Vec<String>,
     /// Fuzzy match data for filtered commands (parallel to filtered_commands)
     pub(crate) command_matches: Vec<utils::CommandMatch>,
+    /// MCP tools by server, for `/mcp-tool` argument completion
+    pub(crate) mcp_tools: Vec<(String, crate::mcp::McpTool)>,
     /// Show file mention autocomplete
     pub(crate) show_file_autocomplete: bool,
     /// Selected file index in autocomplete
@@ -115,6 +117,7 @@ impl ChatInput {
             selected_command_index: 0,
             filtered_commands: Vec::new(),
             command_matches: Vec::new(),
+            mcp_tools: Vec::new(),
             show_file_autocomplete: false,
             selected_file_index: 0,
             filtered_files: Vec::new(),
@@ -12,12 +12,43 @@ use super::super::types::NotificationType;
 impl ChatView {
     /// Toggle bookmark on a message
     pub fn toggle_bookmark(&mut self, index: usize, cx: &mut Context<Self>) {
-        if self.bookmarked_messages.contains(&index) {
-            self.bookmarked_messages.remove(&index);
-            self.show_notification("Bookmark removed", NotificationType::Info, cx);
+        let bookmarked = !self.bookmarked_messages.contains(&index);
+        self.set_message_bookmark(index, bookmarked, None, cx);
+        if let Some(view) = self.message_views.get(index) {
+            view.update(cx, |view, cx| view.set_bookmark(bookmarked, None, cx));
+        }
+        if bookmarked {
+            self.show_notification("Message bookmarked", NotificationType::Success, cx);
         } else {
+            self.show_notification("Bookmark removed", NotificationType::Info, cx);
+        }
+        cx.notify();
+    }
+
+    /// Record a bookmark change on the message at `index` and save it
+    pub(crate) fn set_message_bookmark(
+        &mut self,
+        index: usize,
+        bookmarked: bool,
+        label: Option<String>,
+        cx: &mut Context<Self>,
+    ) {
+        if bookmarked == self.bookmarked_messages.contains(&index) {
+            return;
+        }
+        let Some(message_id) = self.message_id_at(index) else {
+            return;
+        };
+        if bookmarked {
             self.bookmarked_messages.insert(index);
-            self.show_notification("Message bookmarked", NotificationType::Success, cx);
+            self.save_annotation("save bookmark", move |db, _| {
+                db.set_bookmark(&message_id, label.as_deref())
+            });
+        } else {
+            self.bookmarked_messages.remove(&index);
+            self.save_annotation("remove bookmark", move |db, _| {
+                db.remove_bookmark(&message_id)
+            });
         }
         cx.notify();
     }
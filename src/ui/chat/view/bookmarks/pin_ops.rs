@@ -11,11 +11,16 @@ use super::super::types::NotificationType;
 impl ChatView {
     /// Pin/unpin a message
     pub fn toggle_pin(&mut self, index: usize, cx: &mut Context<Self>) {
+        let Some(message_id) = self.message_id_at(index) else {
+            return;
+        };
         if self.pinned_messages.contains(&index) {
             self.pinned_messages.remove(&index);
+            self.save_annotation("unpin message", move |db, _| db.unpin_message(&message_id));
             self.show_notification("Message unpinned".to_string(), NotificationType::Info, cx);
         } else {
             self.pinned_messages.insert(index);
+            self.save_annotation("pin message", move |db, _| db.pin_message(&message_id));
             self.show_notification("Message pinned".to_string(), NotificationType::Success, cx);
         }
         cx.notify();
@@ -17,6 +17,7 @@ impl ChatView {
                 self.current_thinking = None;
                 // Track response start time for latency measurement
                 self.streaming.response_start_time = Some(chrono::Utc::now());
+                self.streaming.turn_usage = None;
                 // Reset streaming metrics for new response
                 self.reset_streaming_metrics();
                 self.stats.total_api_requests += 1;
@@ -25,13 +26,7 @@ impl ChatView {
                     input.set_disabled(true, cx);
                 });
                 // Create a streaming message view
-                let streaming_msg = ClaudeMessage {
-                    role: MessageRole::Assistant,
-                    content: String::new(),
-                    timestamp: chrono::Utc::now(),
-                    tool_name: None,
-                    is_error: false,
-                };
+                let streaming_msg = ClaudeMessage::assistant(String::new());
                 self.streaming_message_view = Some(self.create_message_view(streaming_msg, cx));
                 // Start animation
                 self.start_streaming_animation(cx);
@@ -60,13 +55,7 @@ impl ChatView {
                     }
                 } else if let Some(ref content) = self.streaming.current_message {
                     // Create new view only if one doesn't exist
-                    let streaming_msg = ClaudeMessage {
-                        role: MessageRole::Assistant,
-                        content: content.clone(),
-                        timestamp: chrono::Utc::now(),
-                        tool_name: None,
-                        is_error: false,
-                    };
+                    let streaming_msg = ClaudeMessage::assistant(content.clone());
                     self.streaming_message_view = Some(self.create_message_view(streaming_msg, cx));
                 }
                 cx.notify();
@@ -82,8 +71,14 @@ impl ChatView {
                     input.set_disabled(false, cx);
                 });
                 if let Some(content) = self.streaming.current_message.take() {
-                    let message = ClaudeMessage::assistant(content);
-                    self.save_message(&message);
+                    let mut message = ClaudeMessage::assistant(content);
+                    message.metadata.model = self
+                        .session_info
+                        .as_ref()
+                        .map(|info| info.model.clone())
+                        .filter(|model| !model.is_empty());
+                    message.metadata.usage = self.streaming.turn_usage.take();
+                    self.save_message(&mut message);
                     let view = self.create_message_view(message.clone(), cx);
                     self.message_views.push(view);
                     self.messages.push(message);
@@ -111,21 +106,36 @@ impl ChatView {
                 }
                 cx.notify();
             }
-            ClaudeEvent::ToolUse { name, input } => {
+            ClaudeEvent::ToolUse {
+                id,
+                name,
+                input,
+                parent_tool_use_id,
+            } => {
                 // Track current tool for display
                 self.current_tool_name = Some(name.clone());
-                let message = ClaudeMessage::tool_use(name, input);
-                self.save_message(&message);
+                let mut message = ClaudeMessage::tool_use(name, input)
+                    .with_tool_use_id(id)
+                    .with_parent_tool_use_id(parent_tool_use_id);
+                self.save_message(&mut message);
                 let view = self.create_message_view(message.clone(), cx);
                 self.message_views.push(view);
                 self.messages.push(message);
                 cx.notify();
             }
-            ClaudeEvent::ToolResult { output, is_error } => {
+            ClaudeEvent::ToolResult {
+                tool_use_id,
+                output,
+                is_error,
+                parent_tool_use_id,
+            } => {
                 // Clear current tool (tool execution finished)
                 self.current_tool_name = None;
-                let message = ClaudeMessage::tool_result(output, is_error);
-                self.save_message(&message);
+                let mut message = ClaudeMessage::tool_result(output, is_error)
+                    .with_tool_use_id(tool_use_id)
+                    .with_parent_tool_use_id(parent_tool_use_id);
+                message.pair_with_tool_use(&self.messages);
+                self.save_message(&mut message);
                 let view = self.create_message_view(message.clone(), cx);
                 self.message_views.push(view);
                 self.messages.push(message);
@@ -149,8 +159,8 @@ impl ChatView {
                     .map(|m| m.content.clone());
                 self.record_error(msg.clone(), original_prompt, cx);
 
-                let message = ClaudeMessage::error(msg);
-                self.save_message(&message);
+                let mut message = ClaudeMessage::error(msg);
+                self.save_message(&mut message);
                 let view = self.create_message_view(message.clone(), cx);
                 self.message_views.push(view);
                 self.messages.push(message);
@@ -165,9 +175,10 @@ impl ChatView {
                     // Store current thinking for display
                     self.current_thinking = Some(content.clone());
 
-                    // Only add to messages if show_thinking is enabled
+                    // Always saved; only added to messages if show_thinking is enabled
+                    let mut message = ClaudeMessage::thinking(content);
+                    self.save_message(&mut message);
                     if self.show_thinking {
-                        let message = ClaudeMessage::thinking(content);
                         let view = self.create_message_view(message.clone(), cx);
                         // Auto-collapse thinking messages
                         view.update(cx, |v, cx| v.set_collapsed(true, cx));
@@ -216,6 +227,9 @@ impl ChatView {
                 );
                 cx.notify();
             }
+            ClaudeEvent::TurnUsage { usage } => {
+                self.streaming.turn_usage = Some(usage);
+            }
             ClaudeEvent::TaskStarted {
                 description,
                 task_id,
@@ -274,6 +288,7 @@ impl ChatView {
                 let capabilities = self.format_session_capabilities(&info);
                 self.show_notification(capabilities, NotificationType::Success, cx);
 
+                self.resume_session_id = None;
                 self.session_info = Some(info);
                 cx.notify();
             }
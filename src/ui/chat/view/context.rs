@@ -10,8 +10,9 @@
 
 use super::core::ChatView;
 use super::types::{ContextFile, FilePickerItem, RecentFile};
+use crate::ai::context::{ContextItem, ContextItemType};
 use gpui::*;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 impl ChatView {
     // ==================== Context Panel ====================
@@ -26,24 +27,111 @@ impl ChatView {
     pub fn add_context_file(&mut self, path: impl Into<String>, cx: &mut Context<Self>) {
         let path = path.into();
         // Avoid duplicates
-        if !self.context_files.iter().any(|f| f.path == path) {
-            self.context_files.push(ContextFile::from_path(path));
-            cx.notify();
+        if self.context_files.iter().any(|f| f.path == path) {
+            return;
+        }
+
+        let mut file = ContextFile::from_path(path.clone());
+        let full_path = match self.app_state.current_directory() {
+            Some(dir) => dir.join(&path),
+            None => PathBuf::from(&path),
+        };
+        match std::fs::read_to_string(&full_path) {
+            Ok(content) => {
+                let item = ContextItem::file(&path, content);
+                file.tokens = item.token_count as u64;
+                self.context_items
+                    .update(cx, |panel, cx| panel.add_item(item, cx));
+            }
+            Err(e) => {
+                tracing::warn!("Could not read context file {}: {}", full_path.display(), e);
+            }
         }
+        self.context_files.push(file);
+        cx.notify();
     }
 
     /// Remove a file from context
     pub fn remove_context_file(&mut self, path: &str, cx: &mut Context<Self>) {
         self.context_files.retain(|f| f.path != path);
+        self.context_items.update(cx, |panel, cx| {
+            let ids: Vec<String> = panel
+                .context()
+                .items()
+                .iter()
+                .filter(|item| item.path.as_deref() == Some(Path::new(path)))
+                .map(|item| item.id.clone())
+                .collect();
+            for id in ids {
+                panel.remove_item(&id, cx);
+            }
+        });
         cx.notify();
     }
 
-    /// Clear all context files
+    /// Clear context files, keeping pinned files and other context items
     pub fn clear_context_files(&mut self, cx: &mut Context<Self>) {
-        self.context_files.clear();
+        self.context_items.update(cx, |panel, cx| {
+            let ids: Vec<String> = panel
+                .context()
+                .items()
+                .iter()
+                .filter(|item| item.item_type == ContextItemType::File && !item.pinned)
+                .map(|item| item.id.clone())
+                .collect();
+            for id in ids {
+                panel.remove_item(&id, cx);
+            }
+        });
+        self.sync_context_files(cx);
+    }
+
+    /// Add a fetched web page to context, replacing an earlier copy
+    pub fn add_web_context(&mut self, item: ContextItem, cx: &mut Context<Self>) {
+        let url = item.metadata.get("url").cloned();
+        self.context_items.update(cx, |panel, cx| {
+            let stale: Vec<String> = panel
+                .context()
+                .items()
+                .iter()
+                .filter(|existing| url.is_some() && existing.metadata.get("url") == url.as_ref())
+                .map(|existing| existing.id.clone())
+                .collect();
+            for id in stale {
+                panel.remove_item(&id, cx);
+            }
+            panel.add_item(item, cx);
+        });
+    }
+
+    /// Drop attached files whose items were removed from the context panel
+    pub(crate) fn sync_context_files(&mut self, cx: &mut Context<Self>) {
+        let context = self.context_items.read(cx).context();
+        let paths: Vec<PathBuf> = context
+            .items()
+            .iter()
+            .filter_map(|item| item.path.clone())
+            .collect();
+        self.context_files
+            .retain(|f| paths.iter().any(|p| p == Path::new(&f.path)));
         cx.notify();
     }
 
+    /// Pinned context items, sent with every prompt as a stable prefix
+    pub fn pinned_context(&self, cx: &App) -> Option<String> {
+        let pinned = self.context_items.read(cx).format_pinned_for_prompt();
+        (!pinned.is_empty()).then_some(pinned)
+    }
+
+    /// Unpinned context selected for a prompt, formatted to go before it
+    ///
+    /// Items that do not fit the token limit are trimmed or dropped, and the
+    /// context panel reports which.
+    pub fn context_for_prompt(&mut self, prompt: &str, cx: &mut Context<Self>) -> String {
+        self.context_items
+            .update(cx, |panel, cx| panel.format_unpinned_selection(prompt, cx))
+    }
+
     /// Get total tokens in context files
     pub fn context_files_tokens(&self) -> u64 {
         self.context_files.iter().map(|f| f.tokens).sum()
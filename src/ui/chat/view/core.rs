@@ -9,6 +9,7 @@ use gpui::*;
 use crate::app::state::AppState;
 use crate::claude::message::{ClaudeEvent, ClaudeMessage, MessageRole};
 use crate::storage::models::{Conversation, Message};
+use crate::ui::ai::{ContextPanel, ContextPanelEvent};
 use crate::ui::pct;
 
 use crate::ui::chat::input::{ChatInput, ChatInputEvent};
@@ -32,6 +33,10 @@ pub struct ChatView {
     pub(crate) streaming_message_view: Option<Entity<MessageView>>,
     /// Current conversation ID (if saved)
     pub(crate) current_conversation_id: Option<String>,
+    /// Last saved message, the parent of the next one
+    pub(crate) last_message_id: Option<String>,
+    /// CLI session continuing the shown branch, until the CLI reports one
+    pub(crate) resume_session_id: Option<String>,
     /// Whether to show the stats bar
     pub(crate) show_stats: bool,
     /// Search query
@@ -198,6 +203,8 @@ pub struct ChatView {
     pub(crate) show_context_panel: bool,
     /// Files mentioned/attached in this session
     pub(crate) context_files: Vec<ContextFile>,
+    /// Context items sent with each prompt, trimmed to the token limit
+    pub(crate) context_items: Entity<ContextPanel>,
     /// Whether to show export panel
     pub(crate) show_export_panel: bool,
     /// Selected export format
@@ -398,6 +405,18 @@ impl ChatView {
         })
         .detach();
 
+        // Keep attached files in step with items removed from the panel
+        let context_items = cx.new(ContextPanel::new);
+        cx.subscribe(&context_items, |this, _, event: &ContextPanelEvent, cx| {
+            if matches!(
+                event,
+                ContextPanelEvent::RemoveItem(_) | ContextPanelEvent::ClearAll
+            ) {
+                this.sync_context_files(cx);
+            }
+        })
+        .detach();
+
         Self {
             app_state,
             message_views: Vec::new(),
@@ -406,6 +425,8 @@ impl ChatView {
             is_streaming: false,
             streaming_message_view: None,
             current_conversation_id: None,
+            last_message_id: None,
+            resume_session_id: None,
             show_stats: true, // Show by default
             search_query: String::new(),
             show_search: false,
@@ -494,6 +515,7 @@ impl ChatView {
             templates_filter: String::new(),
             show_context_panel: false,
             context_files: Vec::new(),
+            context_items,
             show_export_panel: false,
             export_format: ExportFormat::default(),
             export_include_metadata: true,
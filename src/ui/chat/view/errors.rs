@@ -47,24 +47,15 @@ impl ChatView {
         }
 
         // Find the last user message
-        let last_user_prompt = self
+        let last_user = self
             .messages
             .iter()
-            .rev()
-            .find(|m| m.role == MessageRole::User)
-            .map(|m| m.content.clone());
-
-        if let Some(prompt) = last_user_prompt {
-            // Remove the last assistant response (and any tool use/results after it)
-            while let Some(last_msg) = self.messages.last() {
-                if last_msg.role == MessageRole::User {
-                    break;
-                }
-                self.messages.pop();
-                self.message_views.pop();
-            }
+            .rposition(|m| m.role == MessageRole::User);
 
-            // Resend the prompt
+        if let Some(index) = last_user {
+            // Resend the prompt on a new branch; the old response stays stored
+            let prompt = self.messages[index].content.clone();
+            self.branch_from(index, cx);
             cx.emit(ChatViewEvent::Submit(prompt));
             self.show_notification("Regenerating response...", NotificationType::Info, cx);
         } else {
@@ -29,6 +29,16 @@ impl ChatView {
         cx.notify();
     }
 
+    /// Provide MCP tool schemas for `/mcp-tool` autocomplete
+    pub fn set_mcp_tools(
+        &mut self,
+        tools: Vec<(String, crate::mcp::McpTool)>,
+        cx: &mut Context<Self>,
+    ) {
+        self.input
+            .update(cx, |input, cx| input.set_mcp_tools(tools, cx));
+    }
+
     /// Get MCP server count
     pub fn mcp_server_count(&self) -> usize {
         self.session_info
@@ -27,8 +27,14 @@ impl ChatView {
             return;
         }
 
-        if let Some(content) = self.get_last_user_message() {
-            let content = content.to_string();
+        let last_user = self
+            .messages
+            .iter()
+            .rposition(|m| m.role == MessageRole::User);
+        if let Some(index) = last_user {
+            let content = self.messages[index].content.clone();
+            // The edited message is sent on a new branch
+            self.branch_from(index, cx);
             // Set the input to the last message content
             self.input.update(cx, |input, cx| {
                 input.set_text(content.clone(), cx);
@@ -69,7 +75,7 @@ impl ChatView {
     }
 
     /// Branch conversation from a specific message index
-    /// This removes all messages after the specified index and puts the message content in input for editing
+    /// This hides the message and those after it (they stay stored as another branch) and puts the message content in input for editing
     pub fn branch_from_message(&mut self, message_index: usize, cx: &mut Context<Self>) {
         if self.streaming.is_streaming {
             self.show_notification(
@@ -99,10 +105,9 @@ impl ChatView {
 
         let content = message.content.clone();
 
-        // Remove messages from this point onwards
+        // Start a new branch from this point onwards
         let removed_count = self.messages.len() - message_index;
-        self.messages.truncate(message_index);
-        self.message_views.truncate(message_index);
+        self.branch_from(message_index, cx);
 
         // Put content in input for editing
         self.input.update(cx, |input, cx| {
@@ -41,9 +41,8 @@ impl ChatView {
 
         let content = message.content.clone();
 
-        // Remove all messages from this point
-        self.messages.truncate(message_index);
-        self.message_views.truncate(message_index);
+        // Start a new branch from this point
+        self.branch_from(message_index, cx);
 
         // Re-submit the message
         cx.emit(ChatViewEvent::Submit(content));
@@ -1,6 +1,5 @@
 //! Context panel render functions
 
-mod files_section;
 mod footer;
 mod header;
 mod session_info;
@@ -57,8 +56,8 @@ impl ChatView {
                             .flex_1()
                             .overflow_y_scroll()
                             .p_4()
-                            // Files section
-                            .child(self.render_files_section(&theme_clone, cx))
+                            // Context items, with what the last prompt dropped or trimmed
+                            .child(self.context_items.clone())
                             // Tools section
                             .when_some(session_info, |d, info| {
                                 d.child(self.render_tools_section(&theme_clone, info))
@@ -17,6 +17,9 @@ impl ChatView {
         emoji: &'static str,
         cx: &mut Context<Self>,
     ) {
+        let Some(message_id) = self.message_id_at(message_index) else {
+            return;
+        };
         let reactions = self
             .message_reactions
             .entry(message_index)
@@ -29,8 +32,14 @@ impl ChatView {
             if reactions.is_empty() {
                 self.message_reactions.remove(&message_index);
             }
+            self.save_annotation("remove reaction", move |db, _| {
+                db.remove_reaction(&message_id, emoji)
+            });
         } else {
             reactions.push(MessageReaction::new(emoji));
+            self.save_annotation("save reaction", move |db, _| {
+                db.add_reaction(&message_id, emoji)
+            });
         }
         cx.notify();
     }
@@ -143,9 +152,8 @@ impl ChatView {
                 if let Some(msg) = self.messages.get(message_index) {
                     if matches!(msg.role, MessageRole::User) {
                         let prompt = msg.content.clone();
-                        // Truncate conversation up to this message
-                        self.messages.truncate(message_index);
-                        self.message_views.truncate(message_index);
+                        // Start a new branch at this message
+                        self.branch_from(message_index, cx);
                         // Re-submit
                         cx.emit(ChatViewEvent::Submit(prompt));
                     }
@@ -238,6 +246,10 @@ impl ChatView {
     /// Set session notes
     pub(crate) fn set_session_notes(&mut self, notes: impl Into<String>, cx: &mut Context<Self>) {
         self.session_notes = notes.into();
+        let notes = self.session_notes.clone();
+        self.save_annotation("save notes", move |db, conversation_id| {
+            db.set_conversation_note(conversation_id, &notes)
+        });
         cx.notify();
     }
 
@@ -0,0 +1,101 @@
+//! Bookmark, pin, tag, reaction and note persistence
+
+use std::collections::HashMap;
+
+use gpui::*;
+
+use crate::storage::database::Database;
+use crate::storage::models::Annotations;
+
+use super::super::core::ChatView;
+use super::super::types::{MessageReaction, QUICK_REACTIONS};
+
+impl ChatView {
+    /// Queue a write of annotations on the current conversation
+    ///
+    /// `f` gets the conversation ID. Nothing is written before the
+    /// conversation is saved, as there is nothing to attach to yet.
+    pub(crate) fn save_annotation<F>(&self, action: &'static str, f: F)
+    where
+        F: FnOnce(&Database, &str) -> anyhow::Result<()> + Send + 'static,
+    {
+        let Some(conversation_id) = self.current_conversation_id.clone() else {
+            return;
+        };
+        self.app_state
+            .database
+            .spawn_write(action, move |db| f(db, &conversation_id));
+    }
+
+    /// Stored ID of the message at `index`
+    pub(crate) fn message_id_at(&self, index: usize) -> Option<String> {
+        self.messages.get(index).map(|m| m.id.clone())
+    }
+
+    /// Forget the annotations shown for the previous conversation
+    pub(crate) fn clear_annotations(&mut self) {
+        self.bookmarked_messages.clear();
+        self.pinned_messages.clear();
+        self.message_reactions.clear();
+        self.conversation_tags.clear();
+        self.session_notes.clear();
+    }
+
+    /// Forget annotations of messages from `index` on
+    pub(crate) fn truncate_annotations(&mut self, index: usize) {
+        self.bookmarked_messages.retain(|&i| i < index);
+        self.pinned_messages.retain(|&i| i < index);
+        self.message_reactions.retain(|&i, _| i < index);
+    }
+
+    /// Show the stored annotations of the loaded messages
+    pub(crate) fn apply_annotations(&mut self, annotations: Annotations, cx: &mut Context<Self>) {
+        self.clear_annotations();
+        let index_of: HashMap<&str, usize> = self
+            .messages
+            .iter()
+            .enumerate()
+            .map(|(i, m)| (m.id.as_str(), i))
+            .collect();
+
+        for bookmark in annotations.bookmarks {
+            if let Some(&index) = index_of.get(bookmark.message_id.as_str()) {
+                self.bookmarked_messages.insert(index);
+                if let Some(view) = self.message_views.get(index) {
+                    view.update(cx, |view, cx| view.set_bookmark(true, bookmark.label, cx));
+                }
+            }
+        }
+        for message_id in &annotations.pinned {
+            if let Some(&index) = index_of.get(message_id.as_str()) {
+                self.pinned_messages.insert(index);
+            }
+        }
+        for reaction in annotations.reactions {
+            // Only the quick reactions can be shown
+            let emoji = QUICK_REACTIONS
+                .iter()
+                .copied()
+                .find(|e| *e == reaction.emoji);
+            if let (Some(&index), Some(emoji)) = (index_of.get(reaction.message_id.as_str()), emoji)
+            {
+                self.message_reactions
+                    .entry(index)
+                    .or_default()
+                    .push(MessageReaction {
+                        emoji,
+                        added_at: reaction.created_at,
+                    });
+            }
+        }
+        self.conversation_tags = annotations.tags;
+        if let Some(note) = annotations
+            .notes
+            .into_iter()
+            .find(|n| n.message_id.is_none())
+        {
+            self.session_notes = note.content;
+        }
+        cx.notify();
+    }
+}
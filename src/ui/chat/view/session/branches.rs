@@ -0,0 +1,115 @@
+//! Conversation branch methods
+
+use gpui::*;
+
+use super::super::core::ChatView;
+use super::super::types::NotificationType;
+
+impl ChatView {
+    /// Start a new branch in place of the message at `index`
+    ///
+    /// The message and everything after it leave the view but stay stored;
+    /// the next message is saved as its sibling.
+    pub(crate) fn branch_from(&mut self, index: usize, cx: &mut Context<Self>) {
+        let Some(message) = self.messages.get(index) else {
+            return;
+        };
+        self.last_message_id = message.parent_id.clone();
+        self.messages.truncate(index);
+        self.message_views.truncate(index);
+        self.truncate_annotations(index);
+
+        // The running CLI session has seen the dropped turns
+        if let Some(info) = &mut self.session_info {
+            info.session_id.clear();
+        }
+        self.resume_session_id = None;
+        if let Some(leaf) = self.last_message_id.clone() {
+            // Queued after the writes of the messages being branched from
+            let lookup = {
+                let leaf = leaf.clone();
+                self.app_state
+                    .database
+                    .write_async(move |db| db.resume_session_id(&leaf))
+            };
+            cx.spawn(async move |this, cx| {
+                let result = lookup.await;
+                let _ = this.update(cx, |view, _| match result {
+                    Ok(session_id) => {
+                        if view.last_message_id.as_deref() == Some(leaf.as_str()) {
+                            view.resume_session_id = session_id;
+                        }
+                    }
+                    Err(e) => tracing::warn!("Failed to find session to resume: {}", e),
+                });
+            })
+            .detach();
+        }
+        cx.notify();
+    }
+
+    /// Show the branch through `message_id` and make it active
+    pub fn switch_branch(&mut self, message_id: &str, cx: &mut Context<Self>) {
+        let Some(conversation_id) = self.current_conversation_id.clone() else {
+            return;
+        };
+        if self.streaming.is_streaming {
+            self.show_notification(
+                "Cannot switch branches while streaming",
+                NotificationType::Warning,
+                cx,
+            );
+            return;
+        }
+
+        let switch = {
+            let conversation_id = conversation_id.clone();
+            let message_id = message_id.to_string();
+            self.app_state
+                .database
+                .write_async(move |db| db.switch_branch(&conversation_id, &message_id))
+        };
+        cx.spawn(async move |this, cx| {
+            let result = switch.await;
+            let _ = this.update(cx, |view, cx| match result {
+                Ok(_) => view.load_conversation(&conversation_id, cx),
+                Err(e) => view.show_notification(
+                    format!("Failed to switch branch: {}", e),
+                    NotificationType::Error,
+                    cx,
+                ),
+            });
+        })
+        .detach();
+    }
+
+    /// Copy the shown branch into a new conversation and open it
+    pub fn fork_branch(&mut self, cx: &mut Context<Self>) {
+        let Some(leaf) = self.last_message_id.clone() else {
+            self.show_notification("No messages to fork", NotificationType::Warning, cx);
+            return;
+        };
+
+        let title = format!("{} (fork)", self.display_title());
+        let fork = self
+            .app_state
+            .database
+            .write_async(move |db| db.fork_branch(&leaf, title));
+        cx.spawn(async move |this, cx| {
+            let result = fork.await;
+            let _ = this.update(cx, |view, cx| match result {
+                Ok(conversation) => {
+                    view.conversation_title = Some(conversation.title.clone());
+                    view.load_conversation(&conversation.id, cx);
+                    view.show_notification("Branch forked", NotificationType::Success, cx);
+                }
+                Err(e) => view.show_notification(
+                    format!("Failed to fork branch: {}", e),
+                    NotificationType::Error,
+                    cx,
+                ),
+            });
+        })
+        .detach();
+    }
+}
@@ -3,7 +3,7 @@
 use gpui::*;
 
 use crate::claude::message::{ClaudeMessage, MessageRole};
-use crate::storage::models::{Conversation, Message};
+use crate::storage::models::Conversation;
 
 use super::super::core::ChatView;
 use super::super::types::{ConnectionStatus, MessageFilter, NotificationType};
@@ -29,45 +29,38 @@ impl ChatView {
         let conversation = Conversation::new(title, project_id);
         let conv_id = conversation.id.clone();
 
-        // Save to database
-        if let Err(e) = self.app_state.database.insert_conversation(&conversation) {
-            tracing::error!("Failed to save conversation: {}", e);
-        }
+        // Save to database, with tags and notes added before the first
+        // message; queued ahead of the conversation's messages
+        let tags = self.conversation_tags.clone();
+        let notes = self.session_notes.clone();
+        self.app_state
+            .database
+            .spawn_write("save conversation", move |db| {
+                db.insert_conversation(&conversation)?;
+                db.set_tags(&conversation.id, &tags)?;
+                db.set_conversation_note(&conversation.id, &notes)
+            });
 
         self.current_conversation_id = Some(conv_id.clone());
         conv_id
     }
 
-    /// Save a message to the database
-    pub(crate) fn save_message(&self, message: &ClaudeMessage) {
+    /// Save a message to the database, linking it to the previous one
+    pub(crate) fn save_message(&mut self, message: &mut ClaudeMessage) {
         let conv_id = match &self.current_conversation_id {
             Some(id) => id.clone(),
             None => return,
         };
 
-        let role = match message.role {
-            MessageRole::User => "user",
-            MessageRole::Assistant => "assistant",
-            MessageRole::ToolUse => "tool_use",
-            MessageRole::ToolResult => "tool_result",
-            MessageRole::Error => "error",
-            MessageRole::Thinking => "thinking",
-            MessageRole::System => "system",
-        };
-
-        let db_message = Message {
-            id: uuid::Uuid::new_v4().to_string(),
-            conversation_id: conv_id,
-            role: role.to_string(),
-            content: message.content.clone(),
-            tool_name: message.tool_name.clone(),
-            is_error: message.is_error,
-            timestamp: message.timestamp,
-        };
-
-        if let Err(e) = self.app_state.database.insert_message(&db_message) {
-            tracing::error!("Failed to save message: {}", e);
+        message.parent_id = self.last_message_id.replace(message.id.clone());
+        if message.role != MessageRole::User && message.session_id.is_none() {
+            message.session_id = self.current_session_id();
         }
+        let db_message = message.to_record(conv_id);
+
+        self.app_state
+            .database
+            .spawn_write("save message", move |db| db.insert_message(&db_message));
     }
 
     /// Load a conversation by ID
@@ -79,54 +72,79 @@ impl ChatView {
         self.streaming_message_view = None;
         self.streaming.is_streaming = false;
         self.current_conversation_id = Some(conversation_id.to_string());
-
-        // Load messages from database
-        match self.app_state.database.get_messages(conversation_id) {
-            Ok(db_messages) => {
-                for db_msg in db_messages {
-                    let role = match db_msg.role.as_str() {
-                        "user" => MessageRole::User,
-                        "assistant" => MessageRole::Assistant,
-                        "tool_use" => MessageRole::ToolUse,
-                        "tool_result" => MessageRole::ToolResult,
-                        "error" => MessageRole::Error,
-                        _ => MessageRole::User,
-                    };
-
-                    let message = ClaudeMessage {
-                        role,
-                        content: db_msg.content,
-                        timestamp: db_msg.timestamp,
-                        tool_name: db_msg.tool_name,
-                        is_error: db_msg.is_error,
-                    };
-
-                    // Create entity for this message
-                    let view = self.create_message_view(message.clone(), cx);
-                    self.message_views.push(view);
-                    self.messages.push(message);
-                }
-                tracing::info!(
-                    "Loaded {} messages from conversation {}",
-                    self.messages.len(),
-                    conversation_id
-                );
-            }
-            Err(e) => {
-                tracing::error!("Failed to load conversation: {}", e);
-            }
+        self.last_message_id = None;
+        self.resume_session_id = None;
+        self.clear_annotations();
+        if let Some(info) = &mut self.session_info {
+            info.session_id.clear();
         }
 
         cx.notify();
+
+        // Load the active branch and its CLI session off the main thread
+        let conversation_id = conversation_id.to_string();
+        let load = {
+            let conversation_id = conversation_id.clone();
+            self.app_state.database.read_async(move |db| {
+                let thread = db.get_active_thread(&conversation_id)?;
+                let session_id = match thread.last() {
+                    Some(leaf) => db.resume_session_id(&leaf.id)?,
+                    None => None,
+                };
+                let annotations = db.get_annotations(&conversation_id)?;
+                Ok((thread, session_id, annotations))
+            })
+        };
+        cx.spawn(async move |this, cx| {
+            let result = load.await;
+            let _ = this.update(cx, |view, cx| {
+                // Another conversation was opened meanwhile
+                if view.current_conversation_id.as_deref() != Some(conversation_id.as_str()) {
+                    return;
+                }
+                match result {
+                    Ok((db_messages, session_id, annotations)) => {
+                        view.last_message_id = db_messages.last().map(|m| m.id.clone());
+                        view.resume_session_id = session_id;
+                        for message in ClaudeMessage::from_records(db_messages) {
+                            // Thinking is shown as when it streamed in
+                            let is_thinking = message.role == MessageRole::Thinking;
+                            if is_thinking && !view.show_thinking {
+                                continue;
+                            }
+
+                            // Create entity for this message
+                            let view_entity = view.create_message_view(message.clone(), cx);
+                            if is_thinking {
+                                view_entity.update(cx, |v, cx| v.set_collapsed(true, cx));
+                            }
+                            view.message_views.push(view_entity);
+                            view.messages.push(message);
+                        }
+                        view.apply_annotations(annotations, cx);
+                        tracing::info!(
+                            "Loaded {} messages from conversation {}",
+                            view.messages.len(),
+                            conversation_id
+                        );
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to load conversation: {}", e);
+                    }
+                }
+                cx.notify();
+            });
+        })
+        .detach();
     }
 
     /// Add a message to the chat
-    pub fn add_message(&mut self, message: ClaudeMessage, cx: &mut Context<Self>) {
+    pub fn add_message(&mut self, mut message: ClaudeMessage, cx: &mut Context<Self>) {
         // Ensure we have a conversation (creates one on first message)
         self.ensure_conversation();
 
         // Save to database
-        self.save_message(&message);
+        self.save_message(&mut message);
 
         // Create entity and add to local state
         let view = self.create_message_view(message.clone(), cx);
@@ -155,6 +173,9 @@ impl ChatView {
         self.streaming_message_view = None;
         self.streaming.current_message = None;
         self.current_conversation_id = None;
+        self.last_message_id = None;
+        self.resume_session_id = None;
+        self.clear_annotations();
 
         // Reset search state
         self.search.query.clear();
@@ -245,7 +266,10 @@ impl ChatView {
         self.streaming_message_view = None;
         self.streaming.is_streaming = false;
         self.current_conversation_id = None; // Will create new conversation on next message
-                                             // Reset session stats
+        self.last_message_id = None;
+        self.resume_session_id = None;
+        self.clear_annotations();
+        // Reset session stats
         self.session_info = None;
         self.stats.cost = 0.0;
         self.stats.input_tokens = 0;
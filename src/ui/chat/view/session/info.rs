@@ -7,10 +7,12 @@ use super::super::core::ChatView;
 impl ChatView {
     /// Get current session ID for session continuity
     pub fn current_session_id(&self) -> Option<String> {
-        self.session_info
-            .as_ref()
-            .map(|info| info.session_id.clone())
-            .filter(|id| !id.is_empty())
+        self.resume_session_id.clone().or_else(|| {
+            self.session_info
+                .as_ref()
+                .map(|info| info.session_id.clone())
+                .filter(|id| !id.is_empty())
+        })
     }
 
     /// Toggle session details panel
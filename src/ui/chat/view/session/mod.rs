@@ -1,12 +1,16 @@
 //! Session management modules for ChatView
 //!
 //! This module contains all methods related to session management split by functionality:
+//! - annotations: Bookmark, pin, tag, reaction and note persistence
+//! - branches: Conversation branches, switching and forking
 //! - history: Session history and resume
 //! - info: Session info and details
 //! - health: Session health tracking, token and cost tracking
 //! - conversation: Conversation save/load and management
 //! - export: Export functionality in various formats
 
+mod annotations;
+mod branches;
 mod conversation;
 mod export;
 mod health;
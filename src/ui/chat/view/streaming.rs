@@ -56,8 +56,14 @@ impl ChatView {
         let view = cx.new(|cx| MessageView::new(message, app_state, cx));
 
         // Subscribe to message view events
-        cx.subscribe(&view, |this, _, event: &MessageViewEvent, cx| {
+        cx.subscribe(&view, |this, view, event: &MessageViewEvent, cx| {
             match event {
+                MessageViewEvent::BookmarkToggled(bookmarked) => {
+                    if let Some(index) = this.message_views.iter().position(|v| *v == view) {
+                        let label = view.read(cx).bookmark_note().map(String::from);
+                        this.set_message_bookmark(index, *bookmarked, label, cx);
+                    }
+                }
                 MessageViewEvent::RerunCommand(cmd) => {
                     // Insert command as a prompt suggestion
                     let prompt = format!("Run this command: {}", cmd);
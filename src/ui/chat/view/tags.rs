@@ -14,7 +14,10 @@ impl ChatView {
     pub fn add_tag(&mut self, tag: impl Into<String>, cx: &mut Context<Self>) {
         let tag = tag.into();
         if !self.conversation_tags.contains(&tag) {
-            self.conversation_tags.push(tag);
+            self.conversation_tags.push(tag.clone());
+            self.save_annotation("save tag", move |db, conversation_id| {
+                db.add_tag(conversation_id, &tag)
+            });
             cx.notify();
         }
     }
@@ -22,6 +25,10 @@ impl ChatView {
     /// Remove a tag from the conversation
     pub fn remove_tag(&mut self, tag: &str, cx: &mut Context<Self>) {
         self.conversation_tags.retain(|t| t != tag);
+        let tag = tag.to_string();
+        self.save_annotation("remove tag", move |db, conversation_id| {
+            db.remove_tag(conversation_id, &tag)
+        });
         cx.notify();
     }
 
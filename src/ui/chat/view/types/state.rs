@@ -2,6 +2,7 @@
 
 use super::{ConversationSearchResult, ExportFormat, FilePickerItem, MessageFilter};
 use crate::app::theme::Theme;
+use crate::storage::models::TokenUsage;
 
 /// Connection status to Claude CLI
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -36,6 +37,8 @@ pub struct StreamingState {
     pub peak_speed: f64,
     pub response_start_time: Option<chrono::DateTime<chrono::Utc>>,
     pub last_response_time_ms: Option<u64>,
+    /// Usage of the turn in progress, stored with its final message
+    pub turn_usage: Option<TokenUsage>,
 }
 
 /// Panel visibility state
@@ -101,26 +101,14 @@ impl McpContextAttachPanel {
             // Try to add to context manager
             if let Some(ref manager) = self.context_manager {
                 if let Ok(mut mgr) = manager.write() {
-                    match mgr.add(item) {
-                        Ok(()) => {
-                            resource.status = AttachmentStatus::Attached;
-                            self.attached_items.push(item_id.clone());
-                            cx.emit(McpContextAttachEvent::Attached {
-                                item_id,
-                                server: server.to_string(),
-                                uri: uri.to_string(),
-                            });
-                        }
-                        Err(e) => {
-                            resource.status =
-                                AttachmentStatus::Failed(format!("Context error: {:?}", e));
-                            cx.emit(McpContextAttachEvent::AttachFailed {
-                                server: server.to_string(),
-                                uri: uri.to_string(),
-                                error: format!("{:?}", e),
-                            });
-                        }
-                    }
+                    mgr.add(item);
+                    resource.status = AttachmentStatus::Attached;
+                    self.attached_items.push(item_id.clone());
+                    cx.emit(McpContextAttachEvent::Attached {
+                        item_id,
+                        server: server.to_string(),
+                        uri: uri.to_string(),
+                    });
                 }
             } else {
                 resource.status = AttachmentStatus::Failed("No context manager".to_string());
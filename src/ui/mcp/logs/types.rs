@@ -1,5 +1,6 @@
 //! Types and data structures for MCP logs
 
+use crate::mcp::LoggingMessage;
 use gpui::Hsla;
 use std::time::{Duration, Instant};
 
@@ -40,7 +41,7 @@ impl LogLevel {
             "debug" | "trace" => Self::Debug,
             "info" => Self::Info,
             "warn" | "warning" => Self::Warning,
-            "error" | "err" | "fatal" => Self::Error,
+            "error" | "err" | "fatal" | "critical" | "alert" | "emergency" => Self::Error,
             _ => Self::Info,
         }
     }
@@ -73,6 +74,15 @@ impl LogEntry {
         }
     }
 
+    /// Create from a server's `notifications/message`
+    pub fn from_mcp(server: impl Into<String>, message: &LoggingMessage) -> Self {
+        let entry = Self::new(server, LogLevel::from_str(&message.level), message.text());
+        match &message.logger {
+            Some(logger) => entry.with_context(logger.clone()),
+            None => entry,
+        }
+    }
+
     /// Create with context
     pub fn with_context(mut self, context: impl Into<String>) -> Self {
         self.context = Some(context.into());
@@ -7,6 +7,7 @@ use gpui::*;
 
 use super::types::{ActiveExecution, ExecutionPhase};
 use crate::app::state::AppState;
+use crate::mcp::ProgressNotification;
 
 /// Panel for displaying active tool executions
 pub struct ToolProgressPanel {
@@ -70,6 +71,16 @@ impl ToolProgressPanel {
         }
     }
 
+    /// Apply an MCP progress notification
+    ///
+    /// Executions are keyed by the progress token the call was sent with.
+    pub fn apply_progress(&mut self, progress: &ProgressNotification, cx: &mut Context<Self>) {
+        if let Some(execution) = self.executions.get_mut(&progress.token()) {
+            execution.apply_progress(progress);
+            cx.notify();
+        }
+    }
+
     /// Mark execution as completed
     pub fn complete_execution(&mut self, id: &str, cx: &mut Context<Self>) {
         if let Some(execution) = self.executions.get_mut(id) {
@@ -1,6 +1,8 @@
 //! Tests for tool progress tracking
 
 use super::types::{ActiveExecution, ExecutionPhase};
+use crate::mcp::ProgressNotification;
+use serde_json::json;
 
 #[test]
 fn test_active_execution() {
@@ -33,3 +35,21 @@ fn test_execution_phase_display() {
     assert_eq!(ExecutionPhase::Failed.as_str(), "Failed");
     assert_eq!(ExecutionPhase::Cancelled.as_str(), "Cancelled");
 }
+
+#[test]
+fn test_apply_progress() {
+    let mut execution =
+        ActiveExecution::new("tok".to_string(), "index".to_string(), "fs".to_string());
+    let progress: ProgressNotification = serde_json::from_value(json!({
+        "progressToken": "tok",
+        "progress": 3,
+        "total": 4,
+        "message": "Indexing"
+    }))
+    .unwrap();
+
+    execution.apply_progress(&progress);
+    assert_eq!(execution.phase, ExecutionPhase::Executing);
+    assert_eq!(execution.progress, Some(75));
+    assert_eq!(execution.status_message.as_deref(), Some("Indexing"));
+}
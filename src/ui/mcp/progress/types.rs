@@ -1,5 +1,6 @@
 //! Data types for MCP tool execution progress tracking
 
+use crate::mcp::ProgressNotification;
 use std::time::{Duration, Instant};
 
 /// Status of an active tool execution
@@ -74,6 +75,19 @@ impl ActiveExecution {
         }
     }
 
+    /// Apply a server progress notification
+    pub fn apply_progress(&mut self, progress: &ProgressNotification) {
+        if self.phase == ExecutionPhase::Preparing {
+            self.phase = ExecutionPhase::Executing;
+        }
+        if let Some(percent) = progress.percent() {
+            self.progress = Some(percent);
+        }
+        if progress.message.is_some() {
+            self.status_message = progress.message.clone();
+        }
+    }
+
     /// Get elapsed time
     pub fn elapsed(&self) -> Duration {
         self.started_at.elapsed()
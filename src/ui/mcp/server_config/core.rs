@@ -77,6 +77,8 @@ impl ServerConfigEditor {
             EditingField::Command => self.config.command = value,
             EditingField::Args => self.config.args = value,
             EditingField::Env => self.config.env = value,
+            EditingField::Url => self.config.url = value,
+            EditingField::Headers => self.config.headers = value,
             EditingField::Description => self.config.description = value,
             EditingField::AutoApprove => self.config.auto_approve = value,
         }
@@ -39,7 +39,7 @@ impl ServerConfigEditor {
             ))
             // Command
             .child(self.render_field(
-                "Command",
+                "Command (stdio servers)",
                 EditingField::Command,
                 &self.config.command,
                 "e.g., npx, node, python",
@@ -67,6 +67,26 @@ impl ServerConfigEditor {
                 &theme,
                 cx,
             ))
+            // URL
+            .child(self.render_field(
+                "URL (HTTP servers, instead of a command)",
+                EditingField::Url,
+                &self.config.url,
+                "https://example.com/mcp",
+                false,
+                &theme,
+                cx,
+            ))
+            // HTTP headers
+            .child(self.render_field(
+                "HTTP Headers (Name: Value per line)",
+                EditingField::Headers,
+                &self.config.headers,
+                "Authorization: Bearer xxx",
+                true,
+                &theme,
+                cx,
+            ))
             // Description
             .child(self.render_field(
                 "Description (optional)",
@@ -1,7 +1,7 @@
 //! Tests for MCP server configuration editor
 
 use super::types::EditingServerConfig;
-use crate::mcp::McpServerConfig;
+use crate::mcp::{McpServerConfig, McpTransportKind};
 use std::collections::HashMap;
 
 #[test]
@@ -16,6 +16,7 @@ fn test_editing_config_new() {
 #[test]
 fn test_editing_config_from_config() {
     let server_config = McpServerConfig {
+        transport: None,
         command: "npx".to_string(),
         args: vec![
             "-y".to_string(),
@@ -26,9 +27,12 @@ fn test_editing_config_from_config() {
             env.insert("DEBUG".to_string(), "true".to_string());
             env
         },
+        url: None,
+        headers: HashMap::new(),
         enabled: true,
         description: Some("File system server".to_string()),
         auto_approve: vec!["read_*".to_string()],
+        sandbox: None,
     };
 
     let editing = EditingServerConfig::from_config("filesystem".to_string(), &server_config);
@@ -48,9 +52,13 @@ fn test_editing_config_to_config() {
         command: "python".to_string(),
         args: "server.py\n--port\n8080".to_string(),
         env: "API_KEY=secret\nDEBUG=1".to_string(),
+        url: String::new(),
+        headers: String::new(),
+        transport: None,
         enabled: true,
         description: "Test server".to_string(),
         auto_approve: "read_*\nlist_*".to_string(),
+        sandbox: None,
         is_new: false,
     };
 
@@ -86,3 +94,28 @@ fn test_validate() {
     config.env = "VALID=value".to_string();
     assert!(config.validate().is_ok());
 }
+
+#[test]
+fn test_http_server_round_trip() {
+    let server_config = McpServerConfig::sse("https://mcp.example.com/sse")
+        .with_header("Authorization", "Bearer abc");
+
+    let mut editing = EditingServerConfig::from_config("remote".to_string(), &server_config);
+    assert!(editing.command.is_empty());
+    assert_eq!(editing.headers, "Authorization: Bearer abc");
+    assert!(editing.validate().is_ok());
+
+    let config = editing.to_config();
+    assert_eq!(config.transport_kind(), McpTransportKind::Sse);
+    assert_eq!(config.url.as_deref(), Some("https://mcp.example.com/sse"));
+    assert_eq!(
+        config.headers.get("Authorization"),
+        Some(&"Bearer abc".to_string())
+    );
+
+    editing.url = "mcp.example.com".to_string();
+    assert!(editing.validate().is_err());
+    editing.url = "https://mcp.example.com/mcp".to_string();
+    editing.headers = "Authorization Bearer".to_string();
+    assert!(editing.validate().is_err());
+}
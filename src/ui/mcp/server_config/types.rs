@@ -1,6 +1,6 @@
 //! Type definitions for MCP server configuration editor
 
-use crate::mcp::McpServerConfig;
+use crate::mcp::{McpSandbox, McpServerConfig, McpTransportKind};
 use std::collections::HashMap;
 
 /// Server configuration being edited
@@ -14,12 +14,20 @@ pub struct EditingServerConfig {
     pub(crate) args: String,
     /// Environment variables (KEY=VALUE per line)
     pub(crate) env: String,
+    /// Server URL (HTTP servers)
+    pub(crate) url: String,
+    /// HTTP headers (Name: Value per line)
+    pub(crate) headers: String,
+    /// Explicit transport from the loaded config, kept as-is
+    pub(crate) transport: Option<McpTransportKind>,
     /// Whether the server is enabled
     pub(crate) enabled: bool,
     /// Description
     pub(crate) description: String,
     /// Auto-approve patterns (one per line)
     pub(crate) auto_approve: String,
+    /// Sandbox from the loaded config, kept as-is
+    pub(crate) sandbox: Option<McpSandbox>,
     /// Whether this is a new server
     pub(crate) is_new: bool,
 }
@@ -37,9 +45,18 @@ impl EditingServerConfig {
                 .map(|(k, v)| format!("{}={}", k, v))
                 .collect::<Vec<_>>()
                 .join("\n"),
+            url: config.url.clone().unwrap_or_default(),
+            headers: config
+                .headers
+                .iter()
+                .map(|(k, v)| format!("{}: {}", k, v))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            transport: config.transport,
             enabled: config.enabled,
             description: config.description.clone().unwrap_or_default(),
             auto_approve: config.auto_approve.join("\n"),
+            sandbox: config.sandbox.clone(),
             is_new: false,
         }
     }
@@ -51,16 +68,24 @@ impl EditingServerConfig {
             command: String::new(),
             args: String::new(),
             env: String::new(),
+            url: String::new(),
+            headers: String::new(),
+            transport: None,
             enabled: true,
             description: String::new(),
             auto_approve: String::new(),
+            sandbox: None,
             is_new: true,
         }
     }
 
     /// Convert to McpServerConfig
     pub fn to_config(&self) -> McpServerConfig {
+        let url = self.url.trim();
         McpServerConfig {
+            // Without a URL the server is stdio; otherwise keep an explicit
+            // kind and let the rest be inferred
+            transport: if url.is_empty() { None } else { self.transport },
             command: self.command.clone(),
             args: self
                 .args
@@ -84,6 +109,23 @@ impl EditingServerConfig {
                     }
                 })
                 .collect(),
+            url: if url.is_empty() {
+                None
+            } else {
+                Some(url.to_string())
+            },
+            headers: self
+                .headers
+                .lines()
+                .filter_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    let name = name.trim();
+                    if name.is_empty() {
+                        return None;
+                    }
+                    Some((name.to_string(), value.trim().to_string()))
+                })
+                .collect(),
             enabled: self.enabled,
             description: if self.description.is_empty() {
                 None
@@ -96,6 +138,7 @@ impl EditingServerConfig {
                 .filter(|s| !s.trim().is_empty())
                 .map(|s| s.to_string())
                 .collect(),
+            sandbox: self.sandbox.clone(),
         }
     }
 
@@ -104,8 +147,12 @@ impl EditingServerConfig {
         if self.name.trim().is_empty() {
             return Err("Server name is required".to_string());
         }
-        if self.command.trim().is_empty() {
-            return Err("Command is required".to_string());
+        if self.command.trim().is_empty() && self.url.trim().is_empty() {
+            return Err("Command or URL is required".to_string());
+        }
+        let url = self.url.trim();
+        if !url.is_empty() && !(url.starts_with("http://") || url.starts_with("https://")) {
+            return Err(format!("Invalid URL: {}. Use http:// or https://", url));
         }
         // Validate env format
         for line in self.env.lines() {
@@ -117,6 +164,13 @@ impl EditingServerConfig {
                 ));
             }
         }
+        // Validate header format
+        for line in self.headers.lines() {
+            let line = line.trim();
+            if !line.is_empty() && !line.contains(':') {
+                return Err(format!("Invalid header format: {}. Use Name: Value", line));
+            }
+        }
         Ok(())
     }
 }
@@ -128,6 +182,8 @@ pub enum EditingField {
     Command,
     Args,
     Env,
+    Url,
+    Headers,
     Description,
     AutoApprove,
 }
@@ -36,10 +36,12 @@ impl McpServersPanel {
             .map(|(name, server_config)| ServerItem {
                 name: name.clone(),
                 config: server_config.clone(),
+                origin: config.origin(name).cloned(),
                 status: ServerConnectionStatus::Disconnected,
                 tool_count: 0,
                 resource_count: 0,
                 error: None,
+                stderr_tail: Vec::new(),
             })
             .collect();
 
@@ -73,19 +75,23 @@ impl McpServersPanel {
                     ServerItem {
                         name: name.clone(),
                         config: server_config.clone(),
+                        origin: self.config.origin(name).cloned(),
                         status: old_server.status,
                         tool_count: old_server.tool_count,
                         resource_count: old_server.resource_count,
                         error: old_server.error.clone(),
+                        stderr_tail: old_server.stderr_tail.clone(),
                     }
                 } else {
                     ServerItem {
                         name: name.clone(),
                         config: server_config.clone(),
+                        origin: self.config.origin(name).cloned(),
                         status: ServerConnectionStatus::Disconnected,
                         tool_count: 0,
                         resource_count: 0,
                         error: None,
+                        stderr_tail: Vec::new(),
                     }
                 }
             })
@@ -117,6 +123,14 @@ impl McpServersPanel {
         }
     }
 
+    /// Show the last stderr lines of a server (see [`crate::mcp::StderrLog::tail`])
+    pub fn set_server_stderr(&mut self, name: &str, lines: Vec<String>, cx: &mut Context<Self>) {
+        if let Some(server) = self.servers.iter_mut().find(|s| s.name == name) {
+            server.stderr_tail = lines;
+            cx.notify();
+        }
+    }
+
     /// Get servers list
     pub fn servers(&self) -> &[ServerItem] {
         &self.servers
@@ -69,6 +69,26 @@ impl McpServersPanel {
                                                 .text_color(theme.colors.text_muted)
                                                 .child("(disabled)"),
                                         )
+                                    })
+                                    .when_some(server.origin.as_ref(), |this, origin| {
+                                        let label = if origin.shadowed.is_empty() {
+                                            origin.source.kind.label().to_string()
+                                        } else {
+                                            format!(
+                                                "{} · overrides {}",
+                                                origin.source.kind.label(),
+                                                origin.shadowed.len()
+                                            )
+                                        };
+                                        this.child(
+                                            div()
+                                                .px_1()
+                                                .rounded_sm()
+                                                .bg(theme.colors.surface)
+                                                .text_xs()
+                                                .text_color(theme.colors.text_muted)
+                                                .child(label),
+                                        )
                                     }),
                             )
                             .child(
@@ -82,7 +102,7 @@ impl McpServersPanel {
                                         div()
                                             .text_xs()
                                             .text_color(theme.colors.text_muted)
-                                            .child(server.config.command.clone()),
+                                            .child(server.config.command_line()),
                                     )
                                     .when(
                                         server.status == ServerConnectionStatus::Connected,
@@ -205,5 +225,29 @@ impl McpServersPanel {
                         .child(error),
                 )
             })
+            .when(
+                server.error.is_some() && !server.stderr_tail.is_empty(),
+                |this| {
+                    this.child(
+                        div()
+                            .mt_1()
+                            .mx_3()
+                            .p_2()
+                            .rounded_md()
+                            .bg(theme.colors.background)
+                            .font_family("JetBrains Mono")
+                            .text_xs()
+                            .text_color(theme.colors.text_muted)
+                            .flex()
+                            .flex_col()
+                            .children(
+                                server
+                                    .stderr_tail
+                                    .iter()
+                                    .map(|line| div().child(line.clone())),
+                            ),
+                    )
+                },
+            )
     }
 }
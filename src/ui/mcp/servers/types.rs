@@ -1,6 +1,6 @@
 //! Types for MCP servers panel
 
-use crate::mcp::McpServerConfig;
+use crate::mcp::{McpServerConfig, McpServerOrigin};
 use gpui::*;
 
 /// Connection status for an MCP server
@@ -45,6 +45,8 @@ pub struct ServerItem {
     pub(crate) name: String,
     /// Server configuration
     pub(crate) config: McpServerConfig,
+    /// File the configuration comes from
+    pub(crate) origin: Option<McpServerOrigin>,
     /// Connection status
     pub(crate) status: ServerConnectionStatus,
     /// Number of available tools
@@ -53,6 +55,8 @@ pub struct ServerItem {
     pub(crate) resource_count: usize,
     /// Error message if connection failed
     pub(crate) error: Option<String>,
+    /// Last lines the server process wrote to stderr
+    pub(crate) stderr_tail: Vec<String>,
 }
 
 /// Events emitted by the MCP servers panel
@@ -99,7 +99,7 @@ impl SettingsModal {
                                         div()
                                             .text_xs()
                                             .text_color(theme.colors.text_muted)
-                                            .child(config.command.clone()),
+                                            .child(config.command_line()),
                                     )
                             }),
                         ))
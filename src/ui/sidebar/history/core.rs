@@ -6,6 +6,7 @@ use gpui::prelude::*;
 use gpui::FocusHandle;
 
 use crate::app::state::AppState;
+use crate::claude::transcripts::TranscriptImporter;
 use crate::project::manager::Project;
 use crate::storage::models::{Conversation, SearchFilter, SearchResult};
 
@@ -18,6 +19,10 @@ pub struct HistorySidebar {
     pub(crate) selected_conversation: Option<String>,
     pub(crate) search_query: String,
     pub(crate) search_results: Vec<SearchResult>,
+    /// Cursor of the next page of search results, if there is one
+    pub(crate) search_next_cursor: Option<String>,
+    /// Why the last search failed, such as an invalid query
+    pub(crate) search_error: Option<String>,
     pub(crate) display_mode: DisplayMode,
     pub(crate) focus_handle: FocusHandle,
     pub(crate) search_focus_handle: FocusHandle,
@@ -28,12 +33,6 @@ pub struct HistorySidebar {
 
 impl HistorySidebar {
     pub fn new(app_state: Arc<AppState>, cx: &mut Context<Self>) -> Self {
-        // Load conversations from database
-        let conversations = app_state
-            .database
-            .get_conversations(None)
-            .unwrap_or_default();
-
         // Load projects
         let projects = app_state
             .project_manager
@@ -41,29 +40,72 @@ impl HistorySidebar {
             .list_projects()
             .unwrap_or_default();
 
-        Self {
+        let mut sidebar = Self {
             app_state,
-            conversations,
+            conversations: Vec::new(),
             selected_conversation: None,
             search_query: String::new(),
             search_results: Vec::new(),
+            search_next_cursor: None,
+            search_error: None,
             display_mode: DisplayMode::Recent,
             focus_handle: cx.focus_handle(),
             search_focus_handle: cx.focus_handle(),
             search_filter: SearchFilter::default(),
             show_filters: false,
             projects,
+        };
+        // Load conversations from database
+        sidebar.refresh(cx);
+        if sidebar.app_state.settings.read(cx).import_cli_history {
+            sidebar.import_cli_history(cx);
         }
+        sidebar
     }
 
-    /// Refresh conversation list
+    /// Import sessions run with the Claude CLI, then show any new ones
+    pub fn import_cli_history(&mut self, cx: &mut Context<Self>) {
+        let Some(root) = TranscriptImporter::default_root() else {
+            return;
+        };
+        let import = TranscriptImporter::new(root).import_async(self.app_state.database.clone());
+        cx.spawn(async move |this, cx| {
+            let report = match import.await {
+                Ok(report) => report,
+                Err(e) => {
+                    tracing::warn!("Failed to import Claude CLI sessions: {}", e);
+                    return;
+                }
+            };
+            tracing::info!(
+                "Imported {} messages in {} new conversations from Claude CLI sessions",
+                report.messages,
+                report.conversations
+            );
+            if report.messages > 0 {
+                let _ = this.update(cx, |sidebar, cx| sidebar.refresh(cx));
+            }
+        })
+        .detach();
+    }
+
+    /// Refresh conversation list in the background
     pub fn refresh(&mut self, cx: &mut Context<Self>) {
-        self.conversations = self
+        let load = self
             .app_state
             .database
-            .get_conversations(None)
-            .unwrap_or_default();
-        cx.notify();
+            .read_async(|db| db.get_conversations(None));
+        cx.spawn(async move |this, cx| {
+            let conversations = load.await.unwrap_or_else(|e| {
+                tracing::error!("Failed to load conversations: {}", e);
+                Vec::new()
+            });
+            let _ = this.update(cx, |sidebar, cx| {
+                sidebar.conversations = conversations;
+                cx.notify();
+            });
+        })
+        .detach();
     }
 
     /// Select a conversation
@@ -75,14 +117,26 @@ impl HistorySidebar {
 
     /// Delete a conversation
     pub fn delete_conversation(&mut self, id: &str, cx: &mut Context<Self>) {
-        if let Err(e) = self.app_state.database.delete_conversation(id) {
-            tracing::error!("Failed to delete conversation: {}", e);
-        } else {
-            // If this was the selected conversation, clear selection
-            if self.selected_conversation.as_deref() == Some(id) {
-                self.selected_conversation = None;
+        let id = id.to_string();
+        let delete = {
+            let id = id.clone();
+            self.app_state
+                .database
+                .write_async(move |db| db.delete_conversation(&id))
+        };
+        cx.spawn(async move |this, cx| {
+            if let Err(e) = delete.await {
+                tracing::error!("Failed to delete conversation: {}", e);
+                return;
             }
-            self.refresh(cx);
-        }
+            let _ = this.update(cx, |sidebar, cx| {
+                // If this was the selected conversation, clear selection
+                if sidebar.selected_conversation.as_deref() == Some(id.as_str()) {
+                    sidebar.selected_conversation = None;
+                }
+                sidebar.refresh(cx);
+            });
+        })
+        .detach();
     }
 }
@@ -46,6 +46,14 @@ impl HistorySidebar {
         let is_search = display_mode == DisplayMode::Search;
         let conversation_is_empty = conversation_children.is_empty();
         let search_is_empty = search_children.is_empty();
+        let search_message = self
+            .search_error
+            .clone()
+            .unwrap_or_else(|| "No results found".to_string());
+        let has_more_results = self.search_next_cursor.is_some();
+        let on_load_more = cx.listener(|this, _, _window, cx| {
+            this.load_more_results(cx);
+        });
 
         div()
             .flex_1()
@@ -148,10 +156,26 @@ impl HistorySidebar {
                             .text_sm()
                             .text_color(text_muted)
                             .text_center()
-                            .child("No results found"),
+                            .child(search_message),
                     )
                 })
                 .children(search_children)
+                .when(has_more_results, |this| {
+                    this.child(
+                        div()
+                            .id("history-search-more")
+                            .px_3()
+                            .py_2()
+                            .rounded_md()
+                            .cursor_pointer()
+                            .text_xs()
+                            .text_color(accent)
+                            .text_center()
+                            .hover(move |s| s.bg(accent.opacity(0.1)))
+                            .on_click(on_load_more)
+                            .child("Show more results"),
+                    )
+                })
             })
     }
 
@@ -38,7 +38,7 @@ impl Render for HistorySidebar {
                 (
                     result.message.conversation_id.clone(),
                     result.conversation_title.clone(),
-                    Self::truncate(&result.message.content, 100),
+                    result.snippet.replace("<mark>", "").replace("</mark>", ""),
                     result.highlighted.clone(),
                     Self::format_relative_time(&result.message.timestamp),
                 )
@@ -2,9 +2,14 @@
 
 use gpui::*;
 
+use crate::storage::pagination::PaginationRequest;
+
 use super::core::HistorySidebar;
 use super::types::DisplayMode;
 
+/// Search results fetched at a time
+const SEARCH_PAGE_SIZE: usize = 50;
+
 impl HistorySidebar {
     /// Handle search input change
     pub(super) fn on_search_change(&mut self, text: &str, cx: &mut Context<Self>) {
@@ -13,6 +18,8 @@ impl HistorySidebar {
         if text.trim().is_empty() {
             self.display_mode = DisplayMode::Recent;
             self.search_results.clear();
+            self.search_next_cursor = None;
+            self.search_error = None;
         } else {
             self.display_mode = DisplayMode::Search;
             self.perform_search(cx);
@@ -20,32 +27,74 @@ impl HistorySidebar {
         cx.notify();
     }
 
-    /// Perform the search
-    pub(super) fn perform_search(&mut self, _cx: &mut Context<Self>) {
+    /// Perform the search in the background
+    pub(super) fn perform_search(&mut self, cx: &mut Context<Self>) {
         if self.search_query.trim().is_empty() {
             self.search_results.clear();
+            self.search_next_cursor = None;
+            self.search_error = None;
             return;
         }
+        self.run_search(PaginationRequest::first(SEARCH_PAGE_SIZE), cx);
+    }
 
-        match self.app_state.database.search_messages_with_filter(
-            &self.search_query,
-            &self.search_filter,
-            50,
-        ) {
-            Ok(results) => {
-                self.search_results = results;
-            }
-            Err(e) => {
-                tracing::error!("Search failed: {}", e);
-                self.search_results.clear();
-            }
+    /// Append the next page of search results
+    pub(super) fn load_more_results(&mut self, cx: &mut Context<Self>) {
+        if let Some(cursor) = self.search_next_cursor.take() {
+            self.run_search(PaginationRequest::after(cursor, SEARCH_PAGE_SIZE), cx);
         }
     }
 
+    /// Fetch a page of results, replacing them on the first page
+    fn run_search(&mut self, page: PaginationRequest, cx: &mut Context<Self>) {
+        let query = self.search_query.clone();
+        let filter = self.search_filter.clone();
+        let append = page.cursor.is_some();
+        let search = {
+            let query = query.clone();
+            self.app_state
+                .database
+                .read_async(move |db| db.search(&query, &filter, &page))
+        };
+        cx.spawn(async move |this, cx| {
+            let result = search.await;
+            let _ = this.update(cx, |sidebar, cx| {
+                // Results of a query the user has since changed
+                if sidebar.search_query != query {
+                    return;
+                }
+                match result {
+                    Ok(page) => {
+                        if !append {
+                            sidebar.search_results.clear();
+                        }
+                        sidebar.search_results.extend(page.items);
+                        sidebar.search_next_cursor = page
+                            .page_info
+                            .has_next_page
+                            .then_some(page.page_info.end_cursor)
+                            .flatten();
+                        sidebar.search_error = None;
+                    }
+                    Err(e) => {
+                        tracing::debug!("Search failed: {}", e);
+                        sidebar.search_results.clear();
+                        sidebar.search_next_cursor = None;
+                        sidebar.search_error = Some(e.to_string());
+                    }
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
     /// Clear search and return to recent view
     pub(super) fn clear_search(&mut self, cx: &mut Context<Self>) {
         self.search_query.clear();
         self.search_results.clear();
+        self.search_next_cursor = None;
+        self.search_error = None;
         self.display_mode = DisplayMode::Recent;
         cx.notify();
     }
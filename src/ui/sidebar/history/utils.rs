@@ -20,13 +20,4 @@ impl HistorySidebar {
             dt.format("%Y-%m-%d").to_string()
         }
     }
-
-    /// Truncate text with ellipsis
-    pub(super) fn truncate(text: &str, max_len: usize) -> String {
-        if text.len() <= max_len {
-            text.to_string()
-        } else {
-            format!("{}...", &text[..max_len.saturating_sub(3)])
-        }
-    }
 }
@@ -1,6 +1,8 @@
 //! Claude messaging functionality
 
 use super::core::Workspace;
+use crate::ai::mention::{parse_mentions, Mention, MentionKind};
+use crate::ai::{WebFetchConfig, WebFetcher};
 use crate::claude::client::PromptOptions;
 use crate::claude::message::{ClaudeEvent, ClaudeMessage};
 use gpui::*;
@@ -56,6 +58,8 @@ impl Workspace {
                     think_mode: chat.is_think_mode_enabled(),
                     model: chat.get_current_model().map(|m| m.id.clone()),
                     session_id: chat.current_session_id(),
+                    mcp_config: crate::mcp::app_server_mcp_config(),
+                    context: chat.pinned_context(cx),
                 }
             })
             .unwrap_or_default();
@@ -92,6 +96,15 @@ impl Workspace {
             });
         }
 
+        // Fetch pages for @url mentions off the UI thread
+        let url_mentions: Vec<Mention> = parse_mentions(&message)
+            .into_iter()
+            .filter(|m| matches!(m.kind, MentionKind::Url(_)))
+            .collect();
+        let web_fetch = (!url_mentions.is_empty()).then(|| {
+            WebFetcher::new(WebFetchConfig::default()).fetch_mentions_in_background(url_mentions)
+        });
+
         // Update status bar (streaming started)
         self.update_status_bar(cx);
 
@@ -99,8 +112,36 @@ impl Workspace {
         let client = self.claude_client.clone();
         let active_index = self.active_chat_index;
         cx.spawn(async move |this, cx| {
+            let fetched = match web_fetch {
+                Some(fetch) => fetch.await,
+                None => Vec::new(),
+            };
+
+            // Put the context selected for this message ahead of it; the chat
+            // shows the message as typed
+            let prompt = this
+                .update(cx, |workspace, cx| {
+                    let chat_view = workspace.chat_views.get(active_index)?;
+                    Some(chat_view.update(cx, |chat, cx| {
+                        for (url, result) in fetched {
+                            match result {
+                                Ok(item) => chat.add_web_context(item, cx),
+                                Err(e) => chat.add_message(
+                                    ClaudeMessage::error(format!("Could not fetch {}: {}", url, e)),
+                                    cx,
+                                ),
+                            }
+                        }
+                        let context = chat.context_for_prompt(&message, cx);
+                        format!("{}{}", context, message)
+                    }))
+                })
+                .ok()
+                .flatten()
+                .unwrap_or(message);
+
             match client
-                .send_prompt_with_options(&message, cwd.as_deref(), prompt_options)
+                .send_prompt_with_options(&prompt, cwd.as_deref(), prompt_options)
                 .await
             {
                 Ok(mut stream) => {